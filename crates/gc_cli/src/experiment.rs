@@ -0,0 +1,108 @@
+//! `experiment` subcommand: many headless runs summarized as CSV
+//!
+//! Runs an [`Experiment`] once per seed and prints one CSV row per seed
+//! with the chosen [`RunMetrics`], then a min/mean/max summary on stderr.
+//! With `--jobs N` the seeds are split into N contiguous chunks, each run
+//! by a child process of this binary; their rows are gathered in seed
+//! order, so the CSV is the same whatever the job count.
+
+use anyhow::{bail, Context, Result};
+use gc_core::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// What `gc_cli experiment` was asked to do
+pub struct ExperimentArgs<'a> {
+    pub experiment: Experiment,
+    pub seeds: &'a str,
+    pub metrics: &'a [String],
+    pub jobs: usize,
+    pub out: Option<&'a str>,
+    pub no_header: bool,
+    /// Global flags the child processes need again (`--yields`, `--disable`)
+    pub forwarded: Vec<String>,
+}
+
+pub fn run_experiment_command(args: ExperimentArgs) -> Result<()> {
+    let seeds = parse_seeds(args.seeds)?;
+    let metrics = parse_metrics(args.metrics)?;
+    let rows = if args.jobs > 1 && seeds.len() > 1 {
+        run_in_children(&args, &seeds, &metrics)?
+    } else {
+        seeds
+            .iter()
+            .map(|&seed| args.experiment.run_row(seed, &metrics))
+            .collect()
+    };
+
+    let mut csv = String::new();
+    if !args.no_header {
+        csv.push_str(&csv_header(&metrics));
+        csv.push('\n');
+    }
+    for row in &rows {
+        csv.push_str(&row.to_csv());
+        csv.push('\n');
+    }
+    match args.out {
+        Some(path) => std::fs::write(path, &csv).with_context(|| format!("writing {path}"))?,
+        None => std::io::stdout().write_all(csv.as_bytes())?,
+    }
+
+    if !args.no_header {
+        eprintln!(
+            "{} runs of '{}' x {} steps",
+            rows.len(),
+            args.experiment.profile.name,
+            args.experiment.steps
+        );
+        for s in summarize(&metrics, &rows) {
+            eprintln!(
+                "{}: mean {:.2}, min {}, max {}",
+                s.name, s.mean, s.min, s.max
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Split `seeds` across `args.jobs` child processes and collect their rows
+fn run_in_children(
+    args: &ExperimentArgs,
+    seeds: &[u64],
+    metrics: &[&str],
+) -> Result<Vec<ExperimentRow>> {
+    let exe = std::env::current_exe()?;
+    let chunk = seeds.len().div_ceil(args.jobs);
+    let children = seeds
+        .chunks(chunk)
+        .map(|part| {
+            let list: Vec<String> = part.iter().map(u64::to_string).collect();
+            Command::new(&exe)
+                .args(&args.forwarded)
+                .args(["experiment", "--no-header", "--jobs", "1"])
+                .args(["--profile", args.experiment.profile.name])
+                .args(["--steps", &args.experiment.steps.to_string()])
+                .args(["--seeds", &list.join(",")])
+                .args(["--metric", &metrics.join(",")])
+                .stdout(Stdio::piped())
+                .spawn()
+                .context("starting an experiment worker")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rows = Vec::with_capacity(seeds.len());
+    for child in children {
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("experiment worker failed: {}", output.status);
+        }
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            match ExperimentRow::from_csv(line) {
+                Some(row) => rows.push(row),
+                None => bail!("unreadable worker row '{line}'"),
+            }
+        }
+    }
+    Ok(rows)
+}
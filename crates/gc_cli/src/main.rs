@@ -1,13 +1,21 @@
 use anyhow::Result;
 use bevy_ecs::prelude::*;
 use clap::{Parser, Subcommand};
+use gc_core::bench::{self, BenchProfile};
 use gc_core::bootstrap::{
-    build_default_schedule as core_build_default_schedule, build_standard_world, WorldOptions,
+    build_default_schedule as core_build_default_schedule, build_embark_world,
+    build_standard_world, WorldOptions,
 };
 use gc_core::prelude::*;
 use gc_core::{designations, save};
+use rand::Rng;
 use std::io::{self, Write};
 
+mod experiment;
+mod repl;
+mod serve;
+mod soak;
+
 #[derive(Subcommand, Debug, Clone)]
 enum Demo {
     /// Interactive menu
@@ -17,7 +25,20 @@ enum Demo {
     /// Line-of-sight/FOV demo
     Fov,
     /// A* pathfinding demo
-    Path,
+    Path {
+        /// String-pull the path into straight runs
+        #[arg(long, default_value_t = false)]
+        smooth: bool,
+        /// Cost per direction change, in tenths of a step
+        #[arg(long, default_value_t = 0)]
+        turn_penalty: u8,
+        /// Stop next to the goal instead of on it
+        #[arg(long, default_value_t = false)]
+        adjacent: bool,
+        /// Walk as close as possible when the goal is unreachable
+        #[arg(long, default_value_t = false)]
+        partial: bool,
+    },
     /// Job board + designation assignment demo
     Jobs,
     /// Save/Load snapshot demo
@@ -25,7 +46,207 @@ enum Demo {
     /// Batched pathfinding with LRU cache
     PathBatch,
     /// TUI Prototype
-    Tui,
+    Tui {
+        /// Tutorial to play: a built-in name (first_dig) or a JSON file
+        #[arg(long)]
+        tutorial: Option<String>,
+    },
+    /// Render a world to a text or PNG screenshot file
+    Screenshot {
+        /// Save file to render, .json/.ron/.cbor (defaults to a freshly generated world)
+        #[arg(long)]
+        save: Option<String>,
+        /// Output file; `.png` writes an image, anything else writes text
+        #[arg(long, default_value = "map.txt")]
+        out: String,
+        /// Include the visibility overlay
+        #[arg(long, default_value_t = false)]
+        vis: bool,
+        /// Include mining designations
+        #[arg(long, default_value_t = false)]
+        designations: bool,
+        /// Include stockpile zones
+        #[arg(long, default_value_t = false)]
+        stockpiles: bool,
+        /// Include workers and items
+        #[arg(long, default_value_t = false)]
+        units: bool,
+    },
+    /// Generate the region-scale overmap and print it
+    Worldgen {
+        /// Overmap width in regions
+        #[arg(long, default_value_t = 64)]
+        world_width: u32,
+        /// Overmap height in regions
+        #[arg(long, default_value_t = 32)]
+        world_height: u32,
+    },
+    /// Embark on an overmap rectangle and print the resulting local map
+    Embark {
+        /// Left region of the site
+        #[arg(long)]
+        x: u32,
+        /// Top region of the site
+        #[arg(long)]
+        y: u32,
+        /// Site width in regions
+        #[arg(long, default_value_t = 3)]
+        w: u32,
+        /// Site height in regions
+        #[arg(long, default_value_t = 2)]
+        h: u32,
+        /// Overmap width in regions (must match `worldgen`)
+        #[arg(long, default_value_t = 64)]
+        world_width: u32,
+        /// Overmap height in regions (must match `worldgen`)
+        #[arg(long, default_value_t = 32)]
+        world_height: u32,
+    },
+    /// Map editor: paint tiles, place workers/items/stockpiles, save a scenario
+    Edit {
+        /// Save file to start from, .json/.ron/.cbor (defaults to a freshly generated map)
+        #[arg(long)]
+        save: Option<String>,
+        /// Scenario file written by the save key; the extension picks the format
+        #[arg(long, default_value = "scenario.json")]
+        out: String,
+    },
+    /// Smelter chain: ore and fuel hauled to a smelter, bars forged into weapons
+    Smelter {
+        /// smelt-iron orders to queue
+        #[arg(long, default_value_t = 4)]
+        orders: u32,
+        /// Ticks to run at most
+        #[arg(long, default_value_t = 600)]
+        ticks: u32,
+    },
+    /// Run `--steps` ticks and print announcements
+    Announcements {
+        /// Lowest severity to show: info|warning|urgent
+        #[arg(long, default_value = "info")]
+        min_severity: Severity,
+    },
+    /// Run `--steps` ticks and print where agents walked most
+    Traffic {
+        /// Busiest tiles to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Run a load-test scenario and report ticks per second
+    Bench {
+        /// Scenario size: colony|smoke
+        #[arg(long, default_value = "colony")]
+        profile: BenchProfile,
+        /// Ticks to run
+        #[arg(long, default_value_t = 200)]
+        ticks: u32,
+        /// Exit with an error when below the profile's target rate
+        #[arg(long, default_value_t = false)]
+        check: bool,
+    },
+    /// Run a colony for hours, checking invariants; fails on any violation
+    Soak {
+        /// Wall-clock budget in hours (fractions allowed)
+        #[arg(long, default_value_t = 1.0)]
+        hours: f64,
+        /// Colony seed (defaults to the global --seed)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Stop after this many ticks even with time left
+        #[arg(long)]
+        ticks: Option<u64>,
+        /// Ticks between invariant checks
+        #[arg(long, default_value_t = 1000)]
+        check_every: u64,
+        /// Scenario size: colony|smoke
+        #[arg(long, default_value = "smoke")]
+        profile: BenchProfile,
+    },
+    /// Run many seeds headlessly and print their metrics as CSV
+    Experiment {
+        /// Seeds: N, A..B (B left out), A..=B or N,M,...
+        #[arg(long, default_value = "1..=10")]
+        seeds: String,
+        /// Ticks per run
+        #[arg(long, default_value_t = 1000)]
+        steps: u64,
+        /// Metric to report (repeatable or comma-separated; default: all)
+        #[arg(long = "metric", value_name = "METRIC")]
+        metrics: Vec<String>,
+        /// Scenario size: colony|smoke
+        #[arg(long, default_value = "smoke")]
+        profile: BenchProfile,
+        /// Worker processes to split the seeds across
+        #[arg(long, default_value_t = 1)]
+        jobs: usize,
+        /// Write the CSV to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+        /// Print rows only, no header or summary (used by worker processes)
+        #[arg(long, hide = true, default_value_t = false)]
+        no_header: bool,
+    },
+    /// Export or import mining designations and zones as JSON or CSV
+    Designations {
+        #[command(subcommand)]
+        action: PlanAction,
+    },
+    /// Interactive shell: step, designate, spawn and inspect a live world
+    Repl {
+        /// Save file to start from (defaults to a freshly generated map)
+        #[arg(long)]
+        save: Option<String>,
+    },
+    /// Bundle a save, replay log, config, metrics and recent events for a bug report
+    Bugreport {
+        /// Save file to bundle (defaults to the demo colony after `--steps` ticks)
+        #[arg(long)]
+        save: Option<String>,
+        /// Bundle to write (a ZIP archive)
+        #[arg(long, default_value = "bundle.zip")]
+        out: String,
+        /// Most recent announcements to include
+        #[arg(long, default_value_t = 50)]
+        events: usize,
+        /// Replace entity names, and the account name in the config, with placeholders
+        #[arg(long, default_value_t = false)]
+        anonymize: bool,
+    },
+    /// Headless JSON-RPC server (one request per line over TCP)
+    Serve {
+        /// TCP port to listen on
+        #[arg(long, default_value_t = 7777)]
+        port: u16,
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+}
+
+/// Designation plan transfer; `.csv` files are CSV, anything else JSON
+#[derive(Subcommand, Debug, Clone)]
+enum PlanAction {
+    /// Write a world's designations and zones to a plan file
+    Export {
+        /// Save file to read, .json/.ron/.cbor (defaults to a freshly generated world)
+        #[arg(long)]
+        save: Option<String>,
+        /// Plan file to write
+        #[arg(long, default_value = "plan.json")]
+        out: String,
+    },
+    /// Apply a plan file to a world and save the result
+    Import {
+        /// Plan file to read
+        #[arg(long)]
+        plan: String,
+        /// Save file to start from (defaults to a freshly generated map)
+        #[arg(long)]
+        save: Option<String>,
+        /// Save file to write; the extension picks the format
+        #[arg(long, default_value = "scenario.json")]
+        out: String,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -43,6 +264,12 @@ struct Args {
     /// RNG seed for mapgen
     #[arg(long, default_value_t = 42)]
     seed: u64,
+    /// Terrain generator: noise|caves
+    #[arg(long, default_value = "noise")]
+    mapgen: MapGenAlgorithm,
+    /// Hidden caverns to seal into the rock
+    #[arg(long, default_value_t = 0)]
+    caverns: u32,
     /// Print ASCII map on start (demos that render maps)
     #[arg(long, default_value_t = true)]
     ascii_map: bool,
@@ -54,59 +281,89 @@ struct Args {
     #[arg(long, default_value = "json")]
     codec: String,
 
+    /// Load saves that fail their integrity check, with a warning
+    #[arg(long, default_value_t = false)]
+    skip_integrity: bool,
+
+    /// Config file (key bindings, palette, presets); missing file uses defaults
+    #[arg(long, default_value = "goblin-camp.json")]
+    config: String,
+
+    /// Mining yield table (JSON) replacing the built-in one
+    #[arg(long)]
+    yields: Option<String>,
+
+    /// Table read from `--yields`
+    #[arg(skip)]
+    yield_table: Option<YieldTable>,
+
+    /// Switch a subsystem off: auto-haul|combat|needs|weather (repeatable)
+    #[arg(long = "disable", value_name = "SUBSYSTEM")]
+    disable: Vec<Subsystem>,
+
     /// Choose a demo to run. If omitted or set to `menu`, an interactive picker is shown.
     #[command(subcommand)]
     demo: Option<Demo>,
 }
 
-fn print_ascii_map(map: &GameMap) {
-    for y in 0..map.height as i32 {
-        let mut line = String::with_capacity(map.width as usize);
-        for x in 0..map.width as i32 {
-            let ch = match map.get_tile(x, y).unwrap_or(TileKind::Wall) {
-                TileKind::Floor => '.',
-                TileKind::Wall => '#',
-                TileKind::Water => '~',
-                TileKind::Lava => '^',
-            };
-            line.push(ch);
-        }
+fn tile_char(tile: TileKind) -> char {
+    tile_glyph(tile).char
+}
+
+/// Print the map, with `overlay` replacing tiles it has a glyph for
+fn print_ascii_map_with(map: &GameMap, overlay: impl Fn(i32, i32) -> Option<char>) {
+    for (y, row) in map.rows().enumerate() {
+        let line: String = row
+            .iter()
+            .enumerate()
+            .map(|(x, &t)| overlay(x as i32, y as i32).unwrap_or_else(|| tile_char(t)))
+            .collect();
         println!("{}", line);
     }
 }
 
+fn print_ascii_map(map: &GameMap) {
+    print_ascii_map_with(map, |_, _| None);
+}
+
 fn print_ascii_map_with_path(map: &GameMap, path: &[(i32, i32)]) {
     use std::collections::HashSet;
     let set: HashSet<(i32, i32)> = path.iter().copied().collect();
-    for y in 0..map.height as i32 {
-        let mut line = String::with_capacity(map.width as usize);
-        for x in 0..map.width as i32 {
-            let ch = if set.contains(&(x, y)) {
-                'o'
-            } else {
-                match map.get_tile(x, y).unwrap_or(TileKind::Wall) {
-                    TileKind::Floor => '.',
-                    TileKind::Wall => '#',
-                    TileKind::Water => '~',
-                    TileKind::Lava => '^',
-                }
-            };
-            line.push(ch);
-        }
-        println!("{}", line);
-    }
+    print_ascii_map_with(map, |x, y| set.contains(&(x, y)).then_some('o'));
 }
 
 fn build_world(args: &Args) -> World {
-    build_standard_world(
+    let mut world = build_standard_world(
         args.width,
         args.height,
         args.seed,
         WorldOptions {
             populate_demo_scene: true,
             tick_ms: 100,
+            mapgen: args.mapgen,
+            caverns: args.caverns,
         },
-    )
+    );
+    if let Some(table) = &args.yield_table {
+        world.insert_resource(table.clone());
+    }
+    world.insert_resource(toggles(args));
+    world
+}
+
+/// Subsystems switched off by `--disable`
+fn toggles(args: &Args) -> SystemToggles {
+    SystemToggles::with_disabled(args.disable.iter().copied())
+}
+
+/// A built-in tutorial by name, else a tutorial JSON file
+fn load_tutorial(name_or_path: &str) -> Result<Tutorial> {
+    if let Some(tutorial) = builtin_tutorial(name_or_path) {
+        return Ok(tutorial);
+    }
+    Ok(decode_tutorial_json(&std::fs::read_to_string(
+        name_or_path,
+    )?)?)
 }
 
 fn build_default_schedule() -> Schedule {
@@ -120,6 +377,14 @@ fn run_demo_mapgen(args: &Args) -> Result<()> {
         print_ascii_map(map);
     }
     println!("Map {}x{} generated.", map.width, map.height);
+    let caverns = world.resource::<Caverns>();
+    if !caverns.is_empty() {
+        println!(
+            "{} hidden cavern(s) sealed in the rock ({} tiles).",
+            caverns.caverns.len(),
+            caverns.concealed().len()
+        );
+    }
     Ok(())
 }
 
@@ -143,23 +408,7 @@ fn run_demo_fov(args: &Args) -> Result<()> {
             for s in vis.per_entity.values() {
                 all.extend(s.iter().copied());
             }
-            for y in 0..map.height as i32 {
-                let mut line = String::with_capacity(map.width as usize);
-                for x in 0..map.width as i32 {
-                    let ch = if all.contains(&(x, y)) {
-                        '*'
-                    } else {
-                        match map.get_tile(x, y).unwrap_or(TileKind::Wall) {
-                            TileKind::Floor => '.',
-                            TileKind::Wall => '#',
-                            TileKind::Water => '~',
-                            TileKind::Lava => '^',
-                        }
-                    };
-                    line.push(ch);
-                }
-                println!("{}", line);
-            }
+            print_ascii_map_with(map, |x, y| all.contains(&(x, y)).then_some('*'));
         } else {
             print_ascii_map(map);
         }
@@ -171,13 +420,18 @@ fn run_demo_fov(args: &Args) -> Result<()> {
     Ok(())
 }
 
-fn run_demo_path(args: &Args) -> Result<()> {
+fn run_demo_path(args: &Args, options: PathOptions) -> Result<()> {
     let world = build_world(args);
     let map = world.resource::<GameMap>();
     let start = (1, 1);
     let goal = (args.width as i32 - 2, args.height as i32 - 2);
-    match astar_path(map, start, goal) {
+    match astar_path_with(map, start, goal, options) {
         Some((path, cost)) => {
+            let end = path.last().copied().unwrap_or(start);
+            let distance = (end.0 - goal.0).abs() + (end.1 - goal.1).abs();
+            if distance > i32::from(options.adjacent) {
+                println!("Goal unreachable; closest point {:?}", end);
+            }
             println!("Path found: length={}, cost={}", path.len(), cost);
             if args.ascii_map {
                 print_ascii_map_with_path(map, &path);
@@ -189,10 +443,16 @@ fn run_demo_path(args: &Args) -> Result<()> {
 }
 
 fn run_demo_path_batch(args: &Args) -> Result<()> {
-    let world = build_world(args);
-    let map = world.resource::<GameMap>();
-    let mut svc = gc_core::path::PathService::new(256);
+    let mut world = build_world(args);
+    // The world's shared service, as worker travel uses
+    world.init_resource::<gc_core::path::PathService>();
+    world.resource_scope(|world, mut svc: Mut<gc_core::path::PathService>| {
+        batch_paths(args, world.resource::<GameMap>(), &mut svc)
+    });
+    Ok(())
+}
 
+fn batch_paths(args: &Args, map: &GameMap, svc: &mut gc_core::path::PathService) {
     let starts = [(1, 1), (2, 2), (3, 3), (4, 4)];
     let goal = (args.width as i32 - 2, args.height as i32 - 2);
     let mut reqs = Vec::new();
@@ -218,7 +478,6 @@ fn run_demo_path_batch(args: &Args) -> Result<()> {
             print_ascii_map_with_path(map, path);
         }
     }
-    Ok(())
 }
 
 fn run_demo_jobs(args: &Args) -> Result<()> {
@@ -227,7 +486,7 @@ fn run_demo_jobs(args: &Args) -> Result<()> {
     // Set a wall tile at (5,5) for mining
     {
         let mut map = world.resource_mut::<GameMap>();
-        map.set_tile(5, 5, TileKind::Wall);
+        map.set_tile(5, 5, TileKind::Wall)?;
     }
 
     // Add a mine designation which will auto-spawn a job
@@ -250,6 +509,7 @@ fn run_demo_jobs(args: &Args) -> Result<()> {
 
     // Run simulation for the specified steps
     let mut schedule = build_default_schedule();
+    debug_validate_schedule(&mut schedule, &mut world);
     for _step in 0..args.steps {
         schedule.run(&mut world);
     }
@@ -311,6 +571,7 @@ fn run_demo_jobs(args: &Args) -> Result<()> {
         .filter(|j| matches!(j.kind, JobKind::Haul { .. }))
         .count();
     println!("Haul jobs queued: {}", haul_jobs);
+    print_shortages(&world);
 
     // Check if mined tile is now floor
     let map = world.resource::<GameMap>();
@@ -334,49 +595,429 @@ fn run_demo_jobs(args: &Args) -> Result<()> {
 fn run_demo_save(args: &Args) -> Result<()> {
     let mut world = build_world(args);
     let save = save_world(&mut world);
-    match args.codec.as_str() {
-        "json" => {
-            let data = save::encode_json(&save)?;
-            println!("Serialized (json) length: {} bytes", data.len());
-            let parsed: save::SaveGame = save::decode_json(&data)?;
-            let mut world2 = World::new();
-            load_world(parsed, &mut world2);
-            println!(
-                "Reloaded world with {}x{} map.",
-                world2.resource::<GameMap>().width,
-                world2.resource::<GameMap>().height
-            );
+    let Some(codec) = save::codec_by_name(&args.codec) else {
+        println!("Unknown codec '{}'", args.codec);
+        println!("Use one of: json|ron|cbor (default json)");
+        return Ok(());
+    };
+    let bytes = codec.encode(&save)?;
+    println!(
+        "Serialized ({}) length: {} bytes",
+        codec.name(),
+        bytes.len()
+    );
+    let parsed = codec.decode(&bytes)?;
+    let mut world2 = World::new();
+    save::load_world_checked(parsed, &mut world2)?;
+    println!(
+        "Reloaded world with {}x{} map.",
+        world2.resource::<GameMap>().width,
+        world2.resource::<GameMap>().height
+    );
+    println!(
+        "Integrity check passed (world hash {:016x}).",
+        save.world_hash.unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Read a save file, checking that it is intact and loads back faithfully
+///
+/// With `--skip-integrity` a failed check is a warning instead of an error.
+fn read_checked_save(args: &Args, path: &str) -> Result<SaveGame> {
+    let save = save::read_save_file(path)?;
+    if let Err(e) = save::load_world_checked(save.clone(), &mut World::new()) {
+        if !args.skip_integrity {
+            anyhow::bail!("{}: {} (pass --skip-integrity to load it anyway)", path, e);
         }
-        "ron" => {
-            let data = save::encode_ron(&save).map_err(|e| anyhow::anyhow!(e))?;
-            println!("Serialized (ron) length: {} bytes", data.len());
-            let parsed: save::SaveGame = save::decode_ron(&data).map_err(|e| anyhow::anyhow!(e))?;
-            let mut world2 = World::new();
-            load_world(parsed, &mut world2);
+        eprintln!("warning: {}: {}", path, e);
+    }
+    Ok(save)
+}
+
+/// World from a save file, or a freshly generated map without the demo scene
+fn load_or_fresh_world(args: &Args, save: Option<&str>) -> Result<World> {
+    let mut world = match save {
+        Some(path) => {
+            let mut world = World::new();
+            load_world(read_checked_save(args, path)?, &mut world);
+            world
+        }
+        None => build_standard_world(
+            args.width,
+            args.height,
+            args.seed,
+            WorldOptions {
+                mapgen: args.mapgen,
+                ..Default::default()
+            },
+        ),
+    };
+    world.insert_resource(toggles(args));
+    Ok(world)
+}
+
+fn run_designations(args: &Args, action: PlanAction) -> Result<()> {
+    match action {
+        PlanAction::Export { save, out } => {
+            let mut world = load_or_fresh_world(args, save.as_deref())?;
+            let plan = export_plan(&mut world);
+            std::fs::write(&out, encode_plan(&plan, PlanFormat::from_path(&out))?)?;
             println!(
-                "Reloaded world with {}x{} map.",
-                world2.resource::<GameMap>().width,
-                world2.resource::<GameMap>().height
+                "Exported {} mining designations and {} zones to {}",
+                plan.mines.len(),
+                plan.zones.len(),
+                out
             );
         }
-        "cbor" => {
-            let bytes = save::encode_cbor(&save).map_err(|e| anyhow::anyhow!(e))?;
-            println!("Serialized (cbor) length: {} bytes", bytes.len());
-            let parsed: save::SaveGame =
-                save::decode_cbor(&bytes).map_err(|e| anyhow::anyhow!(e))?;
-            let mut world2 = World::new();
-            load_world(parsed, &mut world2);
+        PlanAction::Import { plan, save, out } => {
+            let text = std::fs::read_to_string(&plan)?;
+            let parsed = decode_plan(&text, PlanFormat::from_path(&plan))
+                .map_err(|e| anyhow::anyhow!("{}: {}", plan, e))?;
+            let mut world = load_or_fresh_world(args, save.as_deref())?;
+            let report =
+                import_plan(&mut world, &parsed).map_err(|e| anyhow::anyhow!("{}: {}", plan, e))?;
+            save::write_save_file(&out, &save_world(&mut world))?;
             println!(
-                "Reloaded world with {}x{} map.",
-                world2.resource::<GameMap>().width,
-                world2.resource::<GameMap>().height
+                "Imported {} mining designations ({} duplicates skipped) and {} zones; saved to {}",
+                report.designations, report.duplicates, report.zones, out
             );
         }
-        other => {
-            println!("Unknown codec '{}'", other);
-            println!("Use one of: json|ron|cbor (default json)");
+    }
+    Ok(())
+}
+
+/// Overmap for the run's seed, drawn from the mapgen stream like local maps
+fn build_overmap(args: &Args, width: u32, height: u32) -> Overmap {
+    let seed = DeterministicRng::new(args.seed).mapgen_rng.gen::<u32>();
+    Overmap::generate(width, height, seed)
+}
+
+fn run_worldgen(args: &Args, width: u32, height: u32) -> Result<()> {
+    let overmap = build_overmap(args, width, height);
+    print!("{}", overmap.render_ascii());
+    println!(
+        "Overmap {}x{} regions (seed {}). Legend: ~ ocean . plains T forest % swamp : desert ^ mountains = river",
+        width, height, args.seed
+    );
+    Ok(())
+}
+
+fn run_embark(args: &Args, site: EmbarkSite, world_size: (u32, u32)) -> Result<()> {
+    let overmap = build_overmap(args, world_size.0, world_size.1);
+    let mut world = build_embark_world(&overmap, site, args.seed, WorldOptions::default())?;
+    print_ascii_map(world.resource::<GameMap>());
+    let biomes: Vec<&str> = (site.y..site.y + site.height)
+        .flat_map(|y| (site.x..site.x + site.width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| overmap.region(x, y))
+        .map(|r| r.biome.label())
+        .collect();
+    let trees = world
+        .query_filtered::<(), With<Tree>>()
+        .iter(&world)
+        .count();
+    let animals = world
+        .query_filtered::<(), With<Animal>>()
+        .iter(&world)
+        .count();
+    let map = world.resource::<GameMap>();
+    println!(
+        "Embarked at ({}, {}) {}x{} regions -> {}x{} tiles [{}]",
+        site.x,
+        site.y,
+        site.width,
+        site.height,
+        map.width,
+        map.height,
+        biomes.join(", ")
+    );
+    println!(
+        "{} trees, {} animals, ambient {}°C, raid difficulty {}%",
+        trees,
+        animals,
+        world.resource::<AmbientTemperature>().0,
+        world.resource::<RegionDifficulty>().0
+    );
+    Ok(())
+}
+
+fn run_demo_smelter(args: &Args, orders: u32, ticks: u32) -> Result<()> {
+    let mut world = build_standard_world(24, 12, args.seed, WorldOptions::default());
+    world.insert_resource(toggles(args));
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..12 {
+            for x in 0..24 {
+                map.set_tile(x, y, TileKind::Floor)?;
+            }
+        }
+    }
+    let smelter = world
+        .spawn((
+            Workshop,
+            Station::new(StationKind::Smelter),
+            Name("Smelter".into()),
+            Position(12, 3),
+        ))
+        .id();
+    let workshop = world
+        .spawn((
+            Workshop,
+            Station::new(StationKind::Workshop),
+            Name("Workshop".into()),
+            Position(12, 8),
+        ))
+        .id();
+    // Ore on the left, fuel on the right (enough to leave the smelter its
+    // reserve); coal and wood both burn
+    for i in 0..orders as i32 {
+        world.spawn((
+            Item {
+                item_type: ItemType::Ore,
+            },
+            Carriable,
+            Position(2, 1 + i % 10),
+        ));
+    }
+    let reserve = world.resource::<FuelConfig>().reserve;
+    for i in 0..(orders + reserve) as i32 {
+        let fuel = if i % 2 == 0 {
+            ItemType::Coal
+        } else {
+            ItemType::Wood
+        };
+        world.spawn((
+            Item { item_type: fuel },
+            Carriable,
+            Position(21, 1 + i % 10),
+        ));
+    }
+    world.spawn((
+        Name("Grak".into()),
+        Position(12, 5),
+        Miner,
+        MiningSkill::STARTING,
+        CraftingSkill(3),
+        AssignedJob::default(),
+    ));
+    for (name, y) in [("Urok", 4), ("Snag", 6)] {
+        world.spawn((
+            Name(name.into()),
+            Position(6, y),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ));
+    }
+    world
+        .spawn(StockpileBundle::new(16, 4, 18, 7))
+        .insert(Name("Stockpile".into()));
+
+    for _ in 0..orders {
+        queue_order(&mut world, smelter, "smelt-iron")?;
+    }
+    for _ in 0..orders / 2 {
+        queue_order(&mut world, workshop, "forge-weapon")?;
+    }
+
+    let mut schedule = build_default_schedule();
+    debug_validate_schedule(&mut schedule, &mut world);
+    let mut tick = 0;
+    while tick < ticks {
+        schedule.run(&mut world);
+        tick += 1;
+        let idle = [smelter, workshop]
+            .iter()
+            .all(|&s| world.get::<Station>(s).is_some_and(|s| s.orders.is_empty()));
+        if idle {
+            break;
+        }
+    }
+    println!("Ran {} ticks", tick);
+    let registry = world.resource::<TagRegistry>().clone();
+    for entity in [smelter, workshop] {
+        let s = world.get::<Station>(entity).expect("station spawned above");
+        println!(
+            "{}: {} crafted, {} fuel burned, {} order(s) left, {} fuel in reserve",
+            s.kind.name(),
+            s.crafts,
+            s.fuel_used,
+            s.orders.len(),
+            s.fuel(&registry)
+        );
+    }
+    let mut made: std::collections::BTreeMap<String, usize> = Default::default();
+    let mut q = world.query::<(&Item, Option<&Quality>)>();
+    for (item, quality) in q.iter(&world) {
+        if matches!(item.item_type, ItemType::Bar | ItemType::Weapon) {
+            let label = quality.map_or("", |q| q.label());
+            *made
+                .entry(format!("{} {:?}", label, item.item_type))
+                .or_default() += 1;
+        }
+    }
+    for (what, n) in made {
+        println!("  {} x{}", what.trim(), n);
+    }
+    Ok(())
+}
+
+fn run_announcements(args: &Args, min_severity: Severity) -> Result<()> {
+    let mut world = build_world(args);
+    let mut schedule = build_default_schedule();
+    debug_validate_schedule(&mut schedule, &mut world);
+    for _ in 0..args.steps {
+        schedule.run(&mut world);
+    }
+    let log = world.resource::<Announcements>();
+    let mut shown = 0;
+    for a in log.at_least(min_severity) {
+        println!("[{:>7}] t={:<5} {}", a.severity, a.tick, a.text);
+        shown += 1;
+    }
+    if shown == 0 {
+        println!("No announcements at or above '{}'", min_severity);
+    }
+    print_shortages(&world);
+    Ok(())
+}
+
+fn run_traffic(args: &Args, top: usize) -> Result<()> {
+    let mut world = build_world(args);
+    // Dig out the whole map with carriers walking real paths to the stockpile
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Walk,
+    });
+    let (w, h) = (args.width as i32, args.height as i32);
+    designations::designate_mine_rect(&mut world, &ZoneBounds::new(0, 0, w - 1, h - 1));
+    let mut schedule = build_default_schedule();
+    debug_validate_schedule(&mut schedule, &mut world);
+    for _ in 0..args.steps {
+        schedule.run(&mut world);
+    }
+    let traffic = world.resource::<TrafficMap>();
+    if traffic.is_empty() {
+        println!("No traffic in {} ticks", args.steps);
+        return Ok(());
+    }
+    let busiest = traffic.max();
+    if args.ascii_map {
+        print_ascii_map_with(world.resource::<GameMap>(), |x, y| {
+            char::from_digit(heat_level(traffic.count(x, y), busiest), 10).filter(|&c| c != '0')
+        });
+    }
+    println!("Busiest tiles after {} ticks:", args.steps);
+    for ((x, y), count) in traffic.hottest(top) {
+        println!("  ({x}, {y}): {count}");
+    }
+    Ok(())
+}
+
+/// Print job kinds that currently have nobody to do them
+fn print_shortages(world: &World) {
+    if let Some(unstaffed) = world.get_resource::<UnstaffedJobs>() {
+        for shortage in unstaffed.alerts() {
+            println!("Warning: {} (since t={})", shortage, shortage.since);
         }
     }
+}
+
+fn run_bench(args: &Args, profile: BenchProfile, ticks: u32, check: bool) -> Result<()> {
+    let report = bench::run_bench(profile, args.seed, ticks);
+    println!(
+        "Bench '{}': {} ticks in {:.2?} ({:.1} ticks/s, target {:.0}), {} entities",
+        report.profile,
+        report.ticks,
+        report.elapsed,
+        report.ticks_per_sec,
+        report.target_tps,
+        report.entities
+    );
+    if check && !report.meets_target() {
+        anyhow::bail!(
+            "'{}' ran at {:.1} ticks/s, below the {:.0} target",
+            report.profile,
+            report.ticks_per_sec,
+            report.target_tps
+        );
+    }
+    Ok(())
+}
+
+fn run_screenshot(
+    args: &Args,
+    save_path: Option<&str>,
+    out: &str,
+    layers: gc_tui::RenderLayers,
+) -> Result<()> {
+    let mut world = match save_path {
+        Some(path) => gc_tui::screenshot::world_from_save(read_checked_save(args, path)?),
+        None => {
+            let mut world = build_world(args);
+            let save = save_world(&mut world);
+            gc_tui::screenshot::world_from_save(save)
+        }
+    };
+    gc_tui::screenshot::save_screenshot(&mut world, layers, out)?;
+    println!("Screenshot written to {}", out);
+    Ok(())
+}
+
+fn run_bugreport(
+    args: &Args,
+    save_path: Option<&str>,
+    out: &str,
+    events: usize,
+    anonymize: bool,
+) -> Result<()> {
+    let mut world = match save_path {
+        Some(path) => {
+            let mut world = World::new();
+            load_world(read_checked_save(args, path)?, &mut world);
+            world
+        }
+        None => {
+            let mut world = build_world(args);
+            let mut schedule = build_default_schedule();
+            for _ in 0..args.steps {
+                schedule.run(&mut world);
+            }
+            world
+        }
+    };
+    let config = match std::fs::read_to_string(&args.config) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(anyhow::anyhow!("{}: {}", args.config, e)),
+    };
+    let redact = if anonymize {
+        ["USER", "USERNAME"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let report = bug_report(
+        &mut world,
+        &BugReportOptions {
+            events,
+            anonymize,
+            redact,
+            config,
+        },
+    );
+    save::write_atomic(std::path::Path::new(out), &report.to_zip(), false)?;
+    let m = &report.manifest;
+    println!(
+        "Bug report for seed {} at tick {} ({} commands, {} events{}) written to {}",
+        m.seed,
+        m.tick,
+        m.commands,
+        m.events,
+        if m.anonymized { ", anonymized" } else { "" },
+        out
+    );
     Ok(())
 }
 
@@ -397,11 +1038,16 @@ fn interactive_pick() -> Demo {
         match buf.trim() {
             "1" => Demo::Mapgen,
             "2" => Demo::Fov,
-            "3" => Demo::Path,
+            "3" => Demo::Path {
+                smooth: false,
+                turn_penalty: 0,
+                adjacent: false,
+                partial: false,
+            },
             "4" => Demo::Jobs,
             "5" => Demo::SaveLoad,
             "6" => Demo::PathBatch,
-            "7" => Demo::Tui,
+            "7" => Demo::Tui { tutorial: None },
             _ => Demo::Mapgen,
         }
     } else {
@@ -410,7 +1056,10 @@ fn interactive_pick() -> Demo {
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    if let Some(path) = &args.yields {
+        args.yield_table = Some(YieldTable::from_json(&std::fs::read_to_string(path)?)?);
+    }
 
     let chosen = match args.demo.clone().unwrap_or(Demo::Menu) {
         Demo::Menu => interactive_pick(),
@@ -420,11 +1069,148 @@ fn main() -> Result<()> {
     match chosen {
         Demo::Mapgen => run_demo_mapgen(&args),
         Demo::Fov => run_demo_fov(&args),
-        Demo::Path => run_demo_path(&args),
+        Demo::Path {
+            smooth,
+            turn_penalty,
+            adjacent,
+            partial,
+        } => run_demo_path(
+            &args,
+            PathOptions {
+                smooth,
+                turn_penalty,
+                adjacent,
+                partial,
+                ..Default::default()
+            },
+        ),
         Demo::Jobs => run_demo_jobs(&args),
         Demo::SaveLoad => run_demo_save(&args),
         Demo::PathBatch => run_demo_path_batch(&args),
-        Demo::Tui => gc_tui::run(args.width, args.height, args.seed),
+        Demo::Tui { tutorial } => gc_tui::run_with_config(
+            args.width,
+            args.height,
+            args.seed,
+            &args.config,
+            toggles(&args),
+            tutorial.as_deref().map(load_tutorial).transpose()?,
+        ),
+        Demo::Screenshot {
+            save,
+            out,
+            vis,
+            designations,
+            stockpiles,
+            units,
+        } => run_screenshot(
+            &args,
+            save.as_deref(),
+            &out,
+            gc_tui::RenderLayers {
+                visibility: vis,
+                designations,
+                stockpiles,
+                units,
+                traffic: false,
+            },
+        ),
+        Demo::Worldgen {
+            world_width,
+            world_height,
+        } => run_worldgen(&args, world_width, world_height),
+        Demo::Embark {
+            x,
+            y,
+            w,
+            h,
+            world_width,
+            world_height,
+        } => run_embark(
+            &args,
+            EmbarkSite {
+                x,
+                y,
+                width: w,
+                height: h,
+            },
+            (world_width, world_height),
+        ),
+        Demo::Edit { save, out } => {
+            let world = load_or_fresh_world(&args, save.as_deref())?;
+            gc_tui::editor::run_editor(world, out, &args.config)
+        }
+        Demo::Designations { action } => run_designations(&args, action),
+        Demo::Repl { save } => {
+            use std::io::IsTerminal;
+            let world = load_or_fresh_world(&args, save.as_deref())?;
+            let mut session = repl::Session::new(world, build_default_schedule());
+            session.presets = gc_tui::config::TuiConfig::load(&args.config)?.presets;
+            let interactive = io::stdin().is_terminal();
+            repl::run_repl(&mut session, io::stdin().lock(), io::stdout(), interactive)
+        }
+        Demo::Announcements { min_severity } => run_announcements(&args, min_severity),
+        Demo::Traffic { top } => run_traffic(&args, top),
+        Demo::Smelter { orders, ticks } => run_demo_smelter(&args, orders, ticks),
+        Demo::Bench {
+            profile,
+            ticks,
+            check,
+        } => run_bench(&args, profile, ticks, check),
+        Demo::Soak {
+            hours,
+            seed,
+            ticks,
+            check_every,
+            profile,
+        } => soak::run_soak_command(
+            profile,
+            seed.unwrap_or(args.seed),
+            hours,
+            ticks,
+            check_every,
+        ),
+        Demo::Experiment {
+            seeds,
+            steps,
+            metrics,
+            profile,
+            jobs,
+            out,
+            no_header,
+        } => {
+            let mut forwarded = Vec::new();
+            if let Some(path) = &args.yields {
+                forwarded.extend(["--yields".to_string(), path.clone()]);
+            }
+            for subsystem in &args.disable {
+                forwarded.extend(["--disable".to_string(), subsystem.to_string()]);
+            }
+            experiment::run_experiment_command(experiment::ExperimentArgs {
+                experiment: Experiment {
+                    profile,
+                    steps,
+                    yields: args.yield_table.clone(),
+                    toggles: toggles(&args),
+                },
+                seeds: &seeds,
+                metrics: &metrics,
+                jobs,
+                out: out.as_deref(),
+                no_header,
+                forwarded,
+            })
+        }
+        Demo::Bugreport {
+            save,
+            out,
+            events,
+            anonymize,
+        } => run_bugreport(&args, save.as_deref(), &out, events, anonymize),
+        Demo::Serve { port, bind } => serve::run_server(
+            build_world(&args),
+            build_default_schedule(),
+            &format!("{}:{}", bind, port),
+        ),
         Demo::Menu => Ok(()),
     }
 }
@@ -0,0 +1,429 @@
+//! `repl` subcommand: drive a world with typed commands
+//!
+//! Each line is one command from a small grammar (see [`HELP`]), applied to
+//! a world that lives for the whole session. Commands go through the same
+//! core APIs as the TUI and the RPC server: world commands for
+//! designations and zones, editor brushes for spawning and painting, and
+//! world views for inspection. Lines starting with `#` are comments, so a
+//! file of commands can be piped in as a script.
+
+use anyhow::Result;
+use bevy_ecs::prelude::*;
+use gc_core::editor::{paint, Brush};
+use gc_core::prelude::*;
+use gc_core::save;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+pub const HELP: &str = "\
+commands:
+  step [ticks]                      run the simulation (default 1 tick)
+  designate mine X Y [X2 Y2]        mine a tile or a rectangle
+  estimate X1 Y1 X2 Y2              what mining a rectangle would take and yield
+  cancel X Y                        withdraw mining designations at a tile
+  confirm X Y [X2 Y2]               dig designations next to water or lava anyway
+  zone KIND X1 Y1 X2 Y2 [TAGS]      mark out a zone (stockpiles take a tag filter)
+  preset NAME X1 Y1 X2 Y2           apply a zone or designation preset to an area
+  presets                           list the presets from the config file
+  fill X Y PERCENT [POLICY]|off     cap a stockpile; overflow reject|spill|drop
+  link X1 Y1 X2 Y2                  spill the first stockpile into the second
+  unlink X Y                        drop a stockpile's spill targets
+  forbid X Y [X2 Y2]                leave the items at a tile or in a rectangle alone
+  unforbid X Y [X2 Y2]              let the colony use them again
+  spawn miner|carrier|stone X Y     place a worker or an item
+  tile floor|wall|water|lava X Y    paint a tile
+  inspect X Y                       show the tile and everything on it
+  map                               print the map
+  status                            tick, entity and job counts
+  telemetry                         entities by category and leak alerts
+  save PATH                         write a save (.json/.ron/.cbor)
+  help                              this list
+  quit                              leave";
+
+/// One parsed line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+    Step(u32),
+    /// World commands: designations, cancellations and zones
+    World(WorldCommand),
+    /// Apply a named preset to a rectangle, corners in any order
+    Preset {
+        name: String,
+        a: (i32, i32),
+        b: (i32, i32),
+    },
+    Presets,
+    /// Preview mining a rectangle, corners in any order
+    Estimate {
+        a: (i32, i32),
+        b: (i32, i32),
+    },
+    Paint {
+        brush: Brush,
+        x: i32,
+        y: i32,
+    },
+    Inspect {
+        x: i32,
+        y: i32,
+    },
+    Map,
+    Status,
+    Telemetry,
+    Save(String),
+    Help,
+    Quit,
+}
+
+/// Parse one line; `Ok(None)` for blank lines and comments
+pub fn parse_command(line: &str) -> Result<Option<ReplCommand>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, args) = (words[0], &words[1..]);
+    let command = match (name, args) {
+        ("step", []) => ReplCommand::Step(1),
+        ("step", [n]) => ReplCommand::Step(number(n)?),
+        ("designate", ["mine", x, y]) => ReplCommand::World(WorldCommand::Designate {
+            x: number(x)?,
+            y: number(y)?,
+        }),
+        ("designate", ["mine", x0, y0, x1, y1]) => {
+            ReplCommand::World(WorldCommand::DesignateRect {
+                a: (number(x0)?, number(y0)?),
+                b: (number(x1)?, number(y1)?),
+            })
+        }
+        ("estimate", [x0, y0, x1, y1]) => ReplCommand::Estimate {
+            a: (number(x0)?, number(y0)?),
+            b: (number(x1)?, number(y1)?),
+        },
+        ("cancel", [x, y]) => ReplCommand::World(WorldCommand::CancelDesignation {
+            x: number(x)?,
+            y: number(y)?,
+        }),
+        ("confirm", [x, y]) => {
+            let at = (number(x)?, number(y)?);
+            ReplCommand::World(WorldCommand::ConfirmDesignations { a: at, b: at })
+        }
+        ("confirm", [x0, y0, x1, y1]) => ReplCommand::World(WorldCommand::ConfirmDesignations {
+            a: (number(x0)?, number(y0)?),
+            b: (number(x1)?, number(y1)?),
+        }),
+        ("zone", [kind, x0, y0, x1, y1, tags @ ..]) => {
+            let kind = ZoneKind::from_label(kind).ok_or_else(|| {
+                let kinds: Vec<&str> = ZoneKind::ALL.iter().map(|k| k.label()).collect();
+                format!("unknown zone kind '{kind}' ({})", kinds.join("|"))
+            })?;
+            let accepts = if tags.is_empty() {
+                None
+            } else {
+                Some(TagQuery::parse(&tags.join(" ")).map_err(|e| e.to_string())?)
+            };
+            ReplCommand::World(WorldCommand::Zone {
+                kind,
+                a: (number(x0)?, number(y0)?),
+                b: (number(x1)?, number(y1)?),
+                accepts,
+            })
+        }
+        ("preset", [name, x0, y0, x1, y1]) => ReplCommand::Preset {
+            name: name.to_string(),
+            a: (number(x0)?, number(y0)?),
+            b: (number(x1)?, number(y1)?),
+        },
+        ("presets", []) => ReplCommand::Presets,
+        ("fill", [x, y, "off"]) => ReplCommand::World(WorldCommand::SetFillLimit {
+            x: number(x)?,
+            y: number(y)?,
+            limit: None,
+        }),
+        ("fill", [x, y, percent, policy @ ..]) if policy.len() <= 1 => {
+            let percent: u8 = number(percent)?;
+            if percent > 100 {
+                return Err(format!("fill limit {percent}% is over 100%"));
+            }
+            let overflow = match policy {
+                [policy] => policy.parse()?,
+                _ => OverflowPolicy::default(),
+            };
+            ReplCommand::World(WorldCommand::SetFillLimit {
+                x: number(x)?,
+                y: number(y)?,
+                limit: Some(FillLimit::new(percent, overflow)),
+            })
+        }
+        ("link", [x0, y0, x1, y1]) => ReplCommand::World(WorldCommand::LinkStockpiles {
+            from: (number(x0)?, number(y0)?),
+            to: (number(x1)?, number(y1)?),
+        }),
+        ("unlink", [x, y]) => ReplCommand::World(WorldCommand::UnlinkStockpiles {
+            x: number(x)?,
+            y: number(y)?,
+        }),
+        ("forbid" | "unforbid", [x, y]) | ("forbid" | "unforbid", [x, y, _, _]) => {
+            let a = (number(x)?, number(y)?);
+            let b = match args {
+                [_, _, x1, y1] => (number(x1)?, number(y1)?),
+                _ => a,
+            };
+            ReplCommand::World(if name == "forbid" {
+                WorldCommand::ForbidItems { a, b }
+            } else {
+                WorldCommand::UnforbidItems { a, b }
+            })
+        }
+        ("spawn", [what, x, y]) => {
+            let brush = match *what {
+                "miner" => Brush::Miner,
+                "carrier" => Brush::Carrier,
+                "stone" => Brush::Stone,
+                other => return Err(format!("cannot spawn '{other}' (miner|carrier|stone)")),
+            };
+            ReplCommand::Paint {
+                brush,
+                x: number(x)?,
+                y: number(y)?,
+            }
+        }
+        ("tile", [kind, x, y]) => {
+            let kind = match *kind {
+                "floor" => TileKind::Floor,
+                "wall" => TileKind::Wall,
+                "water" => TileKind::Water,
+                "lava" => TileKind::Lava,
+                other => return Err(format!("unknown tile '{other}' (floor|wall|water|lava)")),
+            };
+            ReplCommand::Paint {
+                brush: Brush::Tile(kind),
+                x: number(x)?,
+                y: number(y)?,
+            }
+        }
+        ("inspect", [x, y]) => ReplCommand::Inspect {
+            x: number(x)?,
+            y: number(y)?,
+        },
+        ("map", []) => ReplCommand::Map,
+        ("status", []) => ReplCommand::Status,
+        ("telemetry", []) => ReplCommand::Telemetry,
+        ("save", [path]) => ReplCommand::Save(path.to_string()),
+        ("help", []) => ReplCommand::Help,
+        ("quit" | "exit", []) => ReplCommand::Quit,
+        (
+            "step" | "designate" | "estimate" | "cancel" | "confirm" | "zone" | "preset"
+            | "presets" | "fill" | "link" | "unlink" | "spawn" | "tile" | "inspect" | "map"
+            | "status" | "telemetry" | "save" | "help" | "quit" | "exit",
+            _,
+        ) => return Err(format!("wrong arguments for '{name}' (try 'help')")),
+        _ => return Err(format!("unknown command '{name}' (try 'help')")),
+    };
+    Ok(Some(command))
+}
+
+fn number<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("'{word}' is not a number"))
+}
+
+/// A world and its schedule, kept for the whole session
+pub struct Session {
+    pub world: World,
+    pub schedule: Schedule,
+    /// Presets `preset` can apply, from the config file
+    pub presets: Presets,
+}
+
+impl Session {
+    pub fn new(world: World, schedule: Schedule) -> Self {
+        Self {
+            world,
+            schedule,
+            presets: Presets::default(),
+        }
+    }
+
+    /// Apply a command and describe the outcome
+    pub fn execute(&mut self, command: &ReplCommand) -> Result<String, String> {
+        match command {
+            ReplCommand::Step(ticks) => {
+                for _ in 0..*ticks {
+                    self.schedule.run(&mut self.world);
+                }
+                let tick = self.world.get_resource::<Time>().map_or(0, |t| t.ticks);
+                Ok(format!("ran {ticks} ticks, now at tick {tick}"))
+            }
+            ReplCommand::World(command) => {
+                if apply_world_command(&mut self.world, command) {
+                    Ok("ok".into())
+                } else {
+                    Err("rejected (out of bounds, unknown tag or nothing there)".into())
+                }
+            }
+            ReplCommand::Preset { name, a, b } => {
+                let commands = self
+                    .presets
+                    .apply(name, *a, *b)
+                    .map_err(|e| e.to_string())?;
+                for command in &commands {
+                    if !apply_world_command(&mut self.world, command) {
+                        return Err("rejected (out of bounds or nothing to designate)".into());
+                    }
+                }
+                Ok(format!(
+                    "applied {name}: {}",
+                    self.presets.get(name).expect("applied")
+                ))
+            }
+            ReplCommand::Presets => {
+                let lines: Vec<String> = self
+                    .presets
+                    .iter()
+                    .map(|(name, preset)| format!("{name}: {preset}"))
+                    .collect();
+                if lines.is_empty() {
+                    Ok("no presets".into())
+                } else {
+                    Ok(lines.join("\n"))
+                }
+            }
+            ReplCommand::Estimate { a, b } => {
+                let bounds =
+                    ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+                Ok(estimate_designation(&mut self.world, &bounds).to_string())
+            }
+            ReplCommand::Paint { brush, x, y } => {
+                if paint(&mut self.world, *x, *y, *brush) {
+                    Ok(format!("placed {} at ({x}, {y})", brush.label()))
+                } else {
+                    Err(format!("({x}, {y}) is outside the map"))
+                }
+            }
+            ReplCommand::Inspect { x, y } => self.inspect(*x, *y),
+            ReplCommand::Map => {
+                let view = snapshot_world(&mut self.world);
+                Ok(extract_draw_list(&view).to_ascii().trim_end().to_string())
+            }
+            ReplCommand::Status => {
+                let view = snapshot_world(&mut self.world);
+                let m = &view.metrics;
+                let mut status = format!(
+                    "tick {}: {} entities, {} items, {} designations, {} queued / {} active jobs, {} faults",
+                    view.tick,
+                    m.entities,
+                    m.items,
+                    m.active_designations,
+                    m.queued_jobs,
+                    m.active_jobs,
+                    m.faults
+                );
+                if !view.disabled.is_empty() {
+                    let off: Vec<&str> = view.disabled.iter().map(|s| s.label()).collect();
+                    status.push_str(&format!(", off: {}", off.join(", ")));
+                }
+                Ok(status)
+            }
+            ReplCommand::Telemetry => {
+                let view = snapshot_world(&mut self.world);
+                let m = &view.metrics;
+                let counts: Vec<String> = m
+                    .entity_counts
+                    .iter()
+                    .map(|(category, n)| format!("{category} {n}"))
+                    .collect();
+                let mut lines = vec![format!("tick {}: {}", view.tick, counts.join(", "))];
+                lines.extend(m.leaks.iter().map(|leak| format!("leak: {leak}")));
+                Ok(lines.join("\n"))
+            }
+            ReplCommand::Save(path) => {
+                save::write_save_file(path, &save::save_world(&mut self.world))
+                    .map_err(|e| format!("{path}: {e}"))?;
+                Ok(format!("saved to {path}"))
+            }
+            ReplCommand::Help => Ok(HELP.into()),
+            ReplCommand::Quit => Ok(String::new()),
+        }
+    }
+
+    fn inspect(&mut self, x: i32, y: i32) -> Result<String, String> {
+        let view = snapshot_world(&mut self.world);
+        let tile = view
+            .tile(x, y)
+            .ok_or_else(|| format!("({x}, {y}) is outside the map"))?;
+        let mut out = format!("({x}, {y}): {tile:?}");
+        if let Some(material) = view.material(x, y) {
+            let _ = write!(out, " ({material:?})");
+        }
+        for e in view.entities_at(x, y) {
+            let _ = write!(out, "\n  {}", e.name.as_deref().unwrap_or("-"));
+            if !e.roles.is_empty() {
+                let _ = write!(out, " [{}]", e.roles.join(", "));
+            }
+            if let Some(item) = e.item_type {
+                let _ = write!(out, " item={item:?}");
+            }
+            if let Some((hp, max)) = e.health {
+                let _ = write!(out, " hp={hp}/{max}");
+            }
+            if let Some(job) = &e.assigned_job {
+                let _ = write!(out, " job={job}");
+            }
+            if let Some(state) = &e.designation {
+                let _ = write!(out, " designation={state}");
+            }
+            if let Some(stored) = &e.stored {
+                let _ = write!(out, " stored={}", stored.total);
+            }
+            if let Some(limit) = e.fill_limit {
+                let _ = write!(
+                    out,
+                    " fill<={}% ({})",
+                    limit.max_fill_percent, limit.overflow
+                );
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Read commands from `input` until it ends or `quit`, writing results and
+/// errors to `out`; `prompt` shows a `> ` before each line
+pub fn run_repl(
+    session: &mut Session,
+    input: impl BufRead,
+    mut out: impl Write,
+    prompt: bool,
+) -> Result<()> {
+    if prompt {
+        writeln!(out, "Goblin Camp REPL; 'help' lists commands")?;
+    }
+    let mut lines = input.lines();
+    loop {
+        if prompt {
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let command = match parse_command(&line?) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(e) => {
+                writeln!(out, "error: {e}")?;
+                continue;
+            }
+        };
+        if command == ReplCommand::Quit {
+            break;
+        }
+        match session.execute(&command) {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => writeln!(out, "{text}")?,
+            Err(e) => writeln!(out, "error: {e}")?,
+        }
+    }
+    Ok(())
+}
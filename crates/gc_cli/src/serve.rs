@@ -0,0 +1,194 @@
+//! `serve` subcommand: line-delimited JSON-RPC over TCP
+//!
+//! Each connection sends one JSON-RPC request per line and receives one
+//! response per line. The simulation runs paused on its own thread so
+//! clients advance it explicitly with `step` (or `resume`); all mutations go
+//! through the simulation's command channel and land on tick boundaries.
+//! Writes never block the server loop: each client has a bounded outgoing
+//! buffer, and a client that falls too far behind is dropped.
+
+use anyhow::{Context, Result};
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_core::rpc::{self, RpcAction, RpcError, RpcResponse};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Server-defined JSON-RPC error: the command queue is full
+const SIM_BUSY: i64 = -32000;
+
+/// Unsent bytes a client may have queued before it is dropped
+const MAX_PENDING: usize = 1 << 20;
+
+struct Client {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    /// Bytes queued for the client but not yet written
+    out: VecDeque<u8>,
+    subscribed: bool,
+    closed: bool,
+}
+
+impl Client {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            buf: Vec::new(),
+            out: VecDeque::new(),
+            subscribed: false,
+            closed: false,
+        }
+    }
+
+    /// Queue one line and write what the socket takes now
+    fn send(&mut self, value: &impl serde::Serialize) {
+        let Ok(line) = serde_json::to_vec(value) else {
+            return;
+        };
+        if self.out.len() + line.len() + 1 > MAX_PENDING {
+            // Not reading fast enough; dropping beats stalling everyone
+            self.closed = true;
+            return;
+        }
+        self.out.extend(line);
+        self.out.push_back(b'\n');
+        self.flush();
+    }
+
+    /// Write queued bytes until the socket would block
+    fn flush(&mut self) {
+        while !self.out.is_empty() && !self.closed {
+            let (front, _) = self.out.as_slices();
+            match self.stream.write(front) {
+                Ok(0) => self.closed = true,
+                Ok(n) => {
+                    self.out.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+
+    /// Complete lines read so far, without blocking
+    fn read_lines(&mut self) -> Vec<String> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+        let mut lines = Vec::new();
+        while let Some(i) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=i).collect();
+            let text = String::from_utf8_lossy(&line).trim().to_string();
+            if !text.is_empty() {
+                lines.push(text);
+            }
+        }
+        lines
+    }
+}
+
+/// Serve JSON-RPC on `addr` until the process is interrupted
+pub fn run_server(world: World, schedule: Schedule, addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).with_context(|| format!("binding {}", addr))?;
+    listener.set_nonblocking(true)?;
+    println!("JSON-RPC server listening on {}", listener.local_addr()?);
+
+    let sim = spawn_sim_thread(
+        world,
+        schedule,
+        SimThreadConfig {
+            start_paused: true,
+            ..Default::default()
+        },
+    );
+    let mut view: Arc<WorldView> = loop {
+        match sim.recv_timeout(Duration::from_secs(5)) {
+            Some(SimEvent::Snapshot(v)) => break v,
+            Some(_) => continue,
+            None => anyhow::bail!("simulation thread produced no snapshot"),
+        }
+    };
+    let mut clients: Vec<Client> = Vec::new();
+
+    loop {
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true)?;
+                    clients.push(Client::new(stream));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        for event in sim.drain() {
+            if let SimEvent::Snapshot(v) = event {
+                view = v;
+                let note = rpc::tick_notification(&view);
+                for client in clients.iter_mut().filter(|c| c.subscribed) {
+                    client.send(&note);
+                }
+            }
+        }
+
+        for client in &mut clients {
+            for line in client.read_lines() {
+                if let Some(response) = handle_line(&sim, &view, client, &line) {
+                    client.send(&response);
+                }
+            }
+            client.flush();
+        }
+        clients.retain(|c| !c.closed);
+
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Handle one request line; notifications (no id) get no response
+fn handle_line(
+    sim: &SimHandle,
+    view: &WorldView,
+    client: &mut Client,
+    line: &str,
+) -> Option<RpcResponse> {
+    let request = match rpc::parse_request(line) {
+        Ok(r) => r,
+        Err(e) => return Some(RpcResponse::err(Value::Null, e)),
+    };
+    let result = rpc::dispatch(view, &request).and_then(|action| match action {
+        RpcAction::Reply(value) => Ok(value),
+        RpcAction::Command(command, value) => sim
+            .send(command)
+            .map(|()| value)
+            .map_err(|e| RpcError::new(SIM_BUSY, e.to_string())),
+        RpcAction::Subscribe(on) => {
+            client.subscribed = on;
+            Ok(serde_json::json!({ "subscribed": on }))
+        }
+    });
+    let id = request.id?;
+    Some(match result {
+        Ok(value) => RpcResponse::ok(id, value),
+        Err(e) => RpcResponse::err(id, e),
+    })
+}
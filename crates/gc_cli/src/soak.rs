@@ -0,0 +1,121 @@
+//! `soak` subcommand: long stability runs with invariant checks
+//!
+//! Runs a bench colony for a wall-clock budget with
+//! [`gc_core::soak::run_soak`], printing each violation as it is found.
+//! With the `count-alloc` feature the binary's allocator counts live heap
+//! bytes so the memory check has something to measure; without it the
+//! memory check is skipped and other commands keep the plain allocator.
+//! Exits with an error if anything was violated, so nightly jobs fail
+//! loudly.
+
+use anyhow::{Context, Result};
+use gc_core::bench::BenchProfile;
+use gc_core::prelude::*;
+#[cfg(feature = "count-alloc")]
+use std::alloc::{GlobalAlloc, Layout, System};
+#[cfg(feature = "count-alloc")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The system allocator, counting live bytes
+#[cfg(feature = "count-alloc")]
+struct CountingAlloc;
+
+#[cfg(feature = "count-alloc")]
+static LIVE_BYTES: AtomicU64 = AtomicU64::new(0);
+
+// SAFETY: every call is forwarded to `System` unchanged; the counter is
+// only bookkeeping.
+#[cfg(feature = "count-alloc")]
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            LIVE_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            LIVE_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            LIVE_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+            LIVE_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        }
+        new
+    }
+}
+
+#[cfg(feature = "count-alloc")]
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+/// Heap bytes currently allocated by this process, when counted
+pub fn live_bytes() -> Option<u64> {
+    #[cfg(feature = "count-alloc")]
+    return Some(LIVE_BYTES.load(Ordering::Relaxed));
+    #[cfg(not(feature = "count-alloc"))]
+    None
+}
+
+pub fn run_soak_command(
+    profile: BenchProfile,
+    seed: u64,
+    hours: f64,
+    ticks: Option<u64>,
+    check_every: u64,
+) -> Result<()> {
+    let duration = Duration::try_from_secs_f64(hours.max(0.0) * 3600.0)
+        .with_context(|| format!("--hours {hours} is not a usable duration"))?;
+    let limits = SoakLimits {
+        duration,
+        max_ticks: ticks,
+        check_every,
+    };
+    println!(
+        "Soak '{}' seed {}: up to {:.2}h{}, checking every {} ticks",
+        profile.name,
+        seed,
+        hours,
+        ticks.map_or(String::new(), |t| format!(" or {t} ticks")),
+        check_every
+    );
+    let report = run_soak(
+        profile,
+        seed,
+        limits,
+        InvariantConfig::default(),
+        live_bytes,
+        |violation| eprintln!("violation {violation}"),
+    );
+    println!(
+        "Soak '{}': {} ticks, {} checks in {:.2?}; {} entities, {} jobs, peak heap {}",
+        report.profile,
+        report.ticks,
+        report.checks,
+        report.elapsed,
+        report.metrics.entities,
+        report.metrics.jobs,
+        report
+            .memory_peak
+            .map_or("not counted".to_string(), |peak| format!("{peak} bytes"))
+    );
+    if report.violations > 0 {
+        anyhow::bail!("{} invariant violations", report.violations);
+    }
+    println!("No invariant violations");
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use gc_core::bugreport::BugReport;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn bundles_a_repl_session_with_its_commands_and_config() {
+    let dir = std::env::temp_dir().join(format!("gc_bugreport_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (save, config, bundle) = (
+        dir.join("session.json"),
+        dir.join("config.json"),
+        dir.join("bundle.zip"),
+    );
+    std::fs::write(&config, "{}").unwrap();
+
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .args(["--width", "12", "--height", "8", "--seed", "7", "repl"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    write!(
+        repl.stdin.take().unwrap(),
+        "step 2\ndesignate mine 3 3 5 4\nstep 2\nzone stockpile 1 1 2 1\nsave {}\n",
+        save.display()
+    )
+    .unwrap();
+    assert!(repl.wait().unwrap().success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .args(["--config", config.to_str().unwrap(), "bugreport", "--save"])
+        .arg(&save)
+        .arg("--out")
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.starts_with("Bug report for seed 7 at tick 4 (2 commands, 0 events)"),
+        "{stdout}"
+    );
+
+    let report = BugReport::from_zip(&std::fs::read(&bundle).unwrap()).unwrap();
+    let ticks: Vec<u64> = report.save.commands.0.iter().map(|c| c.tick).collect();
+    assert_eq!(ticks, [2, 4]);
+    assert_eq!(report.config.as_deref(), Some("{}"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
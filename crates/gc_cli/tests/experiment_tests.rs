@@ -0,0 +1,53 @@
+use std::process::Command;
+
+/// Run `gc_cli experiment` with `flags`, returning (stdout, stderr)
+fn experiment(flags: &[&str]) -> (String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .arg("experiment")
+        .args(flags)
+        .output()
+        .expect("gc_cli runs");
+    assert!(output.status.success(), "{output:?}");
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn worker_processes_give_the_same_csv_as_one() {
+    let flags = [
+        "--seeds",
+        "1..=3",
+        "--steps",
+        "40",
+        "--metric",
+        "stones_hauled",
+        "--metric",
+        "tiles_mined",
+    ];
+    let (csv, summary) = experiment(&flags);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "seed,stones_hauled,tiles_mined");
+    assert_eq!(lines.len(), 4, "{csv}");
+    assert!(lines[3].starts_with("3,"), "{csv}");
+    assert!(
+        summary.starts_with("3 runs of 'smoke' x 40 steps"),
+        "{summary}"
+    );
+    assert!(summary.contains("stones_hauled: mean "), "{summary}");
+
+    let (parallel, _) = experiment(&[&flags[..], &["--jobs", "2"]].concat());
+    assert_eq!(parallel, csv);
+}
+
+#[test]
+fn unknown_metrics_fail_before_running() {
+    let output = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .args(["experiment", "--metric", "gold"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown metric 'gold'"), "{stderr}");
+}
@@ -0,0 +1,215 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run `gc_cli repl` on a small fresh map with `script` as its input
+fn repl(script: &str) -> String {
+    repl_with(&[], script)
+}
+
+/// [`repl`] with extra global flags
+fn repl_with(flags: &[&str], script: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .args(["--width", "12", "--height", "8"])
+        .args(flags)
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("gc_cli runs");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn a_script_mines_a_wall_in_a_persistent_world() {
+    let out = repl(
+        "# carve a wall next to a miner; the map has water beside it\n\
+         tile wall 5 5\n\
+         spawn miner 4 5\n\
+         designate mine 5 5\n\
+         confirm 5 5\n\
+         inspect 5 5\n\
+         step 30\n\
+         inspect 5 5\n\
+         status\n",
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "placed wall at (5, 5)");
+    assert_eq!(lines[1], "placed miner at (4, 5)");
+    assert_eq!(&lines[2..4], ["ok", "ok"]);
+    assert!(lines[4].starts_with("(5, 5): Wall"), "{out}");
+    assert!(lines[5].contains("[mine_designation]"), "{out}");
+    assert_eq!(lines[6], "ran 30 ticks, now at tick 30");
+    assert!(out.contains("(5, 5): Floor"), "the wall was mined:\n{out}");
+    assert!(lines.last().unwrap().starts_with("tick 30:"), "{out}");
+}
+
+#[test]
+fn bad_lines_report_errors_and_quit_stops_the_session() {
+    let out =
+        repl("dance\nstep many\nspawn dragon 1 1\ninspect 99 0\nzone moat 0 0 1 1\nquit\nstep\n");
+    assert_eq!(
+        out.lines().collect::<Vec<_>>(),
+        vec![
+            "error: unknown command 'dance' (try 'help')",
+            "error: 'many' is not a number",
+            "error: cannot spawn 'dragon' (miner|carrier|stone)",
+            "error: (99, 0) is outside the map",
+            "error: unknown zone kind 'moat' (stockpile|refuse|meeting|hospital|pasture)",
+        ]
+    );
+}
+
+#[test]
+fn estimate_previews_a_dig_without_designating_it() {
+    let out = repl(
+        "tile wall 5 5\n\
+         tile wall 6 5\n\
+         tile floor 7 5\n\
+         estimate 7 5 5 5\n\
+         inspect 5 5\n\
+         estimate 1 1 0\n",
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[3], "2 tiles, 2 work ticks, ~2 stone, ~0.1 gem, 2 by water/lava, 1 skipped",
+        "{out}"
+    );
+    assert!(lines[4].starts_with("(5, 5): Wall"), "{out}");
+    assert!(!out.contains("mine_designation"), "{out}");
+    assert_eq!(
+        lines[5],
+        "error: wrong arguments for 'estimate' (try 'help')"
+    );
+}
+
+#[test]
+fn fill_limits_and_links_show_up_in_inspect() {
+    let out = repl(
+        "zone stockpile 1 1 2 1\n\
+         zone stockpile 8 1 8 1\n\
+         fill 1 1 50 spill\n\
+         link 1 1 8 1\n\
+         inspect 1 1\n\
+         fill 1 1 150\n\
+         fill 1 1 50 flood\n\
+         fill 1 1 off\n\
+         unlink 1 1\n\
+         inspect 1 1\n",
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[5], "  Stockpile [stockpile] stored=0 fill<=50% (spill)",
+        "{out}"
+    );
+    assert_eq!(lines[6], "error: fill limit 150% is over 100%");
+    assert_eq!(lines[7], "error: unknown overflow policy 'flood'");
+    assert_eq!(&lines[8..10], ["ok", "ok"]);
+    assert_eq!(lines[11], "  Stockpile [stockpile] stored=0", "{out}");
+}
+
+#[test]
+fn map_and_save_reuse_the_core_renderer_and_codecs() {
+    let dir = std::env::temp_dir().join(format!("gc_repl_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("session.json");
+    let out = repl(&format!(
+        "zone stockpile 1 1 2 1 ore\nspawn carrier 6 3\nmap\nsave {}\n",
+        path.display()
+    ));
+    let map: Vec<&str> = out.lines().skip(2).take(8).collect();
+    assert_eq!(map[1].get(1..3), Some("=="), "{out}");
+    assert_eq!(map[3].chars().nth(6), Some('c'), "{out}");
+    assert!(out.ends_with(&format!("saved to {}\n", path.display())));
+    assert!(gc_core::save::read_save_file(&path).is_ok());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn disabled_subsystems_stay_off_and_show_in_status() {
+    let out = repl_with(
+        &["--disable", "auto-haul", "--disable", "needs"],
+        "zone stockpile 1 1 2 1\nspawn stone 6 3\nstep 3\nstatus\n",
+    );
+    let status = out.lines().last().unwrap();
+    assert!(status.contains("0 queued / 0 active jobs"), "{out}");
+    assert!(status.ends_with(", off: auto-haul, needs"), "{out}");
+
+    let out = repl("zone stockpile 1 1 2 1\nspawn stone 6 3\nstep 3\nstatus\n");
+    let status = out.lines().last().unwrap();
+    assert!(status.contains("1 queued / 0 active jobs"), "{out}");
+    assert!(!status.contains("off:"), "{out}");
+}
+
+#[test]
+fn unknown_subsystems_are_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_gc_cli"))
+        .args(["--disable", "rain", "repl"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown subsystem 'rain'"), "{stderr}");
+}
+
+#[test]
+fn telemetry_lists_entities_by_category() {
+    let out = repl("zone stockpile 1 1 2 1\nstep 1\ntelemetry\n");
+    let line = out.lines().last().unwrap();
+    assert!(line.starts_with("tick 1: workers "), "{out}");
+    assert!(line.contains(", zones 1, "), "{out}");
+    assert!(line.contains("orphan_items 0"), "{out}");
+    assert!(!out.contains("leak:"), "{out}");
+}
+
+#[test]
+fn presets_from_the_config_file_apply_to_an_area() {
+    let dir = std::env::temp_dir().join(format!("gc_repl_presets_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("goblin-camp.json");
+    std::fs::write(
+        &config,
+        r#"{ "presets": { "metals": { "type": "zone", "kind": "stockpile", "accepts": "metal | ore" } } }"#,
+    )
+    .unwrap();
+    let out = repl_with(
+        &["--config", config.to_str().unwrap()],
+        "presets\npreset metals 1 1 2 1\npreset gems 1 1 1 1\ninspect 1 1\n",
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "metals: stockpile accepting metal | ore", "{out}");
+    assert_eq!(lines[1], "applied metals: stockpile accepting metal | ore");
+    assert_eq!(lines[2], "error: unknown preset 'gems'");
+    assert!(out.contains("Stockpile [stockpile]"), "{out}");
+    assert_eq!(repl("presets\n").trim_end(), "no presets");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn forbid_and_unforbid_cover_a_tile_or_a_rectangle() {
+    let out = repl(
+        "spawn stone 3 3\n\
+         forbid 3 3\n\
+         inspect 3 3\n\
+         unforbid 4 4 2 2\n\
+         inspect 3 3\n\
+         forbid 9 9\n",
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[1], "ok");
+    assert_eq!(lines[3], "  Stone [forbidden] item=Stone", "{out}");
+    assert_eq!(lines[4], "ok");
+    assert_eq!(lines[6], "  Stone item=Stone", "{out}");
+    assert_eq!(
+        lines[7],
+        "error: rejected (out of bounds, unknown tag or nothing there)"
+    );
+}
@@ -0,0 +1,32 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gc_core::bench::{bench_schedule, build_bench_world, BenchProfile};
+
+// Regression benchmark for the `colony` load-test profile: 500 agents on a
+// 300x300 map with continuous designation churn
+fn bench_colony_ticks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("colony");
+    group.sample_size(10);
+
+    group.bench_function("10_ticks", |b| {
+        b.iter_batched(
+            || {
+                let mut world = build_bench_world(BenchProfile::COLONY, 42);
+                let mut schedule = bench_schedule();
+                // Warm-up tick: initial FOV and haul jobs for scattered items
+                schedule.run(&mut world);
+                (world, schedule)
+            },
+            |(mut world, mut schedule)| {
+                for _ in 0..10 {
+                    schedule.run(black_box(&mut world));
+                }
+            },
+            criterion::BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_colony_ticks);
+criterion_main!(benches);
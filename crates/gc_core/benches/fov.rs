@@ -25,10 +25,10 @@ fn bench_los_visible(c: &mut Criterion) {
         let mut map = GameMap::new(100, 100);
         // Add some walls to create line-of-sight obstacles
         for x in 10..90 {
-            map.set_tile(x, 50, TileKind::Wall);
+            map.set_tile(x, 50, TileKind::Wall).unwrap();
         }
         for y in 10..90 {
-            map.set_tile(50, y, TileKind::Wall);
+            map.set_tile(50, y, TileKind::Wall).unwrap();
         }
         map
     };
@@ -108,7 +108,7 @@ fn bench_compute_visibility_system(c: &mut Criterion) {
         for i in 0..num_entities {
             let x = (i as i32 * 5) % (width as i32 - 10) + 5;
             let y = (i as i32 * 3) % (height as i32 - 10) + 5;
-            world.spawn((Position(x, y), VisionRadius(vision_radius)));
+            world.spawn((Position(x, y), VisionRadius::new(vision_radius)));
         }
 
         world
@@ -165,7 +165,7 @@ fn bench_fov_patterns(c: &mut Criterion) {
         for y in (0..80).step_by(4) {
             for x in (0..80).step_by(2) {
                 if map.in_bounds(x, y) {
-                    map.set_tile(x, y, TileKind::Wall);
+                    map.set_tile(x, y, TileKind::Wall).unwrap();
                 }
             }
         }
@@ -186,7 +186,7 @@ fn bench_fov_patterns(c: &mut Criterion) {
                     world.insert_resource(Visibility::default());
 
                     // Single entity in center with large vision radius
-                    world.spawn((Position(40, 40), VisionRadius(20)));
+                    world.spawn((Position(40, 40), VisionRadius::new(20)));
 
                     let mut schedule = Schedule::default();
                     schedule.add_systems(compute_visibility_system);
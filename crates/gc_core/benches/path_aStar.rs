@@ -17,7 +17,7 @@ fn create_mostly_floor_map(width: u32, height: u32, wall_density: f32, seed: u32
     for y in 0..height as i32 {
         for x in 0..width as i32 {
             if rng.gen::<f32>() < wall_density {
-                map.set_tile(x, y, TileKind::Wall);
+                map.set_tile(x, y, TileKind::Wall).unwrap();
             }
             // else remains Floor (default)
         }
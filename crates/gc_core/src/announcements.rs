@@ -0,0 +1,422 @@
+//! Player-facing announcements and alerts
+//!
+//! Systems that notice something worth telling the player (a death, an
+//! invasion) push an [`Announcement`] into the [`Announcements`] resource with
+//! a [`Severity`]. Shells read the log to show an alert bar or answer CLI/RPC
+//! queries. When `pause_on_urgent` is set, an urgent announcement raises a
+//! pause request that the simulation driver (e.g. the simulation thread)
+//! honors after the current tick.
+//!
+//! [`unstaffed_jobs_system`] also watches the job board: when a job kind has
+//! had no living worker able to take it for a while, or only workers whose
+//! [`WorkArea`] keeps them from it, it warns once and records the shortage
+//! in [`UnstaffedJobs`] for status lines.
+
+use crate::components::{Carrier, Dead, Faction, FactionKind, Goblin, Miner};
+use crate::jobs::{faction_of, JobBoard, JobKind};
+use crate::systems::Time;
+use crate::work_area::{within_reach, WorkArea, WorkAreaConfig};
+use crate::world::{Name, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+/// How loudly an announcement should be surfaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Routine information
+    Info,
+    /// Needs attention soon
+    Warning,
+    /// Needs attention now; may pause the game
+    Urgent,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Urgent => "urgent",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "urgent" => Ok(Severity::Urgent),
+            other => Err(format!("unknown severity '{}'", other)),
+        }
+    }
+}
+
+/// A single message for the player
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub tick: u64,
+    pub severity: Severity,
+    pub text: String,
+}
+
+/// Bounded, chronological announcement log
+#[derive(Resource, Debug, Clone)]
+pub struct Announcements {
+    entries: VecDeque<Announcement>,
+    /// Oldest entries are dropped beyond this many
+    pub capacity: usize,
+    /// Request a pause whenever an urgent announcement is pushed
+    pub pause_on_urgent: bool,
+    pause_requested: bool,
+}
+
+impl Default for Announcements {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: 256,
+            pause_on_urgent: true,
+            pause_requested: false,
+        }
+    }
+}
+
+impl Announcements {
+    /// Append an announcement
+    pub fn push(&mut self, tick: u64, severity: Severity, text: impl Into<String>) {
+        if severity == Severity::Urgent && self.pause_on_urgent {
+            self.pause_requested = true;
+        }
+        self.entries.push_back(Announcement {
+            tick,
+            severity,
+            text: text.into(),
+        });
+        while self.entries.len() > self.capacity.max(1) {
+            self.entries.pop_front();
+        }
+    }
+
+    /// All retained announcements, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Announcement> {
+        self.entries.iter()
+    }
+
+    /// The `n` most recent announcements, oldest first
+    pub fn recent(&self, n: usize) -> impl Iterator<Item = &Announcement> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(n))
+    }
+
+    /// Announcements at or above `severity`, oldest first
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Announcement> {
+        self.entries.iter().filter(move |a| a.severity >= severity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Consume a pending pause request raised by an urgent announcement
+    pub fn take_pause_request(&mut self) -> bool {
+        std::mem::take(&mut self.pause_requested)
+    }
+}
+
+/// Push an announcement stamped with the current tick
+pub fn announce(world: &mut World, severity: Severity, text: impl Into<String>) {
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    world
+        .get_resource_or_insert_with(Announcements::default)
+        .push(tick, severity, text);
+}
+
+/// Announce newly dead entities; goblin deaths are urgent
+#[allow(clippy::type_complexity)]
+pub fn death_announcement_system(
+    time: Option<Res<Time>>,
+    announcements: Option<ResMut<Announcements>>,
+    q: Query<(Option<&Name>, Option<&Faction>, Has<Goblin>), Added<Dead>>,
+) {
+    let Some(mut announcements) = announcements else {
+        return;
+    };
+    let tick = time.map_or(0, |t| t.ticks);
+    for (name, faction, goblin) in q.iter() {
+        let kind = faction.map(|f| f.kind);
+        let (severity, who) = if goblin || kind == Some(FactionKind::Goblins) {
+            (Severity::Urgent, "A goblin")
+        } else if kind == Some(FactionKind::Invaders) {
+            (Severity::Info, "An invader")
+        } else {
+            (Severity::Info, "A creature")
+        };
+        let who = name.map_or(who, |n| n.0.as_str());
+        announcements.push(tick, severity, format!("{} has died!", who));
+    }
+}
+
+/// Announce invaders appearing on the map (once per tick)
+pub fn invader_arrival_system(
+    time: Option<Res<Time>>,
+    announcements: Option<ResMut<Announcements>>,
+    q: Query<&Faction, Added<Faction>>,
+) {
+    let Some(mut announcements) = announcements else {
+        return;
+    };
+    let arrived = q.iter().filter(|f| f.kind == FactionKind::Invaders).count();
+    if arrived > 0 {
+        let tick = time.map_or(0, |t| t.ticks);
+        announcements.push(tick, Severity::Urgent, "Invaders have arrived!");
+    }
+}
+
+/// Worker type that takes a kind of board job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerRole {
+    /// Takes [`JobKind::Mine`]
+    Miner,
+    /// Takes [`JobKind::Haul`]
+    Hauler,
+    /// Takes [`JobKind::Build`]; any miner can build
+    Builder,
+    /// Takes [`JobKind::Craft`]; any miner can craft
+    Crafter,
+    /// Takes [`JobKind::Extinguish`]; carriers fight fires
+    Firefighter,
+    /// Takes [`JobKind::Shear`]; carriers tend livestock
+    Shearer,
+    /// Takes [`JobKind::Bury`]; carriers bury the dead
+    Gravedigger,
+    /// Takes [`JobKind::PullLever`]; carriers work the mechanisms
+    Operator,
+}
+
+impl WorkerRole {
+    /// Role for board jobs of this kind; self-assigned kinds (rest, equip)
+    /// have none
+    pub fn for_job(kind: &JobKind) -> Option<WorkerRole> {
+        match kind {
+            JobKind::Mine { .. } => Some(WorkerRole::Miner),
+            JobKind::Haul { .. } => Some(WorkerRole::Hauler),
+            JobKind::Build { .. } => Some(WorkerRole::Builder),
+            JobKind::Craft { .. } => Some(WorkerRole::Crafter),
+            JobKind::Extinguish { .. } => Some(WorkerRole::Firefighter),
+            JobKind::Shear { .. } => Some(WorkerRole::Shearer),
+            JobKind::Bury { .. } => Some(WorkerRole::Gravedigger),
+            JobKind::PullLever { .. } => Some(WorkerRole::Operator),
+            _ => None,
+        }
+    }
+
+    /// Plural worker noun, e.g. "haulers"
+    pub fn workers(self) -> &'static str {
+        match self {
+            WorkerRole::Miner => "miners",
+            WorkerRole::Hauler => "haulers",
+            WorkerRole::Builder => "builders",
+            WorkerRole::Crafter => "crafters",
+            WorkerRole::Firefighter => "firefighters",
+            WorkerRole::Shearer => "shearers",
+            WorkerRole::Gravedigger => "gravediggers",
+            WorkerRole::Operator => "lever pullers",
+        }
+    }
+
+    /// Job noun, e.g. "haul"
+    pub fn job(self) -> &'static str {
+        match self {
+            WorkerRole::Miner => "mining",
+            WorkerRole::Hauler => "haul",
+            WorkerRole::Builder => "build",
+            WorkerRole::Crafter => "craft",
+            WorkerRole::Firefighter => "firefighting",
+            WorkerRole::Shearer => "shearing",
+            WorkerRole::Gravedigger => "burial",
+            WorkerRole::Operator => "lever",
+        }
+    }
+}
+
+/// How long a job kind may go unstaffed before it is announced
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaffingConfig {
+    pub warn_after_ticks: u64,
+}
+
+impl Default for StaffingConfig {
+    fn default() -> Self {
+        Self {
+            warn_after_ticks: 50,
+        }
+    }
+}
+
+/// A job kind that queued jobs are waiting on with nobody to do them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shortage {
+    pub faction: FactionKind,
+    pub role: WorkerRole,
+    /// Jobs of this kind on the board, or only those out of reach
+    pub pending: usize,
+    /// Tick the shortage started
+    pub since: u64,
+    /// There are workers, but the `pending` jobs lie outside every one's
+    /// [`WorkArea`]
+    #[serde(default)]
+    pub out_of_reach: bool,
+}
+
+impl fmt::Display for Shortage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.out_of_reach {
+            return write!(
+                f,
+                "No {} in range: {} {} jobs out of reach",
+                self.role.workers(),
+                self.pending,
+                self.role.job()
+            );
+        }
+        write!(
+            f,
+            "No {} available: {} {} jobs pending",
+            self.role.workers(),
+            self.pending,
+            self.role.job()
+        )
+    }
+}
+
+/// Current shortages, keyed by faction and role
+#[derive(Resource, Debug, Clone, Default)]
+pub struct UnstaffedJobs {
+    entries: BTreeMap<(FactionKind, WorkerRole), (Shortage, bool)>,
+}
+
+impl UnstaffedJobs {
+    /// Shortages that have lasted past the warning threshold, in key order
+    pub fn alerts(&self) -> impl Iterator<Item = &Shortage> {
+        self.entries
+            .values()
+            .filter(|(_, announced)| *announced)
+            .map(|(s, _)| s)
+    }
+}
+
+/// Warn when board jobs of a kind have had no eligible worker for
+/// [`StaffingConfig::warn_after_ticks`]
+///
+/// Eligible means alive, of the job's faction, and of the job's
+/// [`WorkerRole`]. When eligible workers exist, the jobs none of them can
+/// reach (see [`within_reach`]) count as a shortage of their own. Each
+/// shortage is announced once; it clears as soon as a worker exists, or can
+/// reach the jobs, or the jobs are gone.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn unstaffed_jobs_system(
+    time: Option<Res<Time>>,
+    config: Option<Res<StaffingConfig>>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    board: Option<Res<JobBoard>>,
+    unstaffed: Option<ResMut<UnstaffedJobs>>,
+    announcements: Option<ResMut<Announcements>>,
+    workers: Query<
+        (
+            Has<Miner>,
+            Has<Carrier>,
+            Option<&Faction>,
+            Option<&Position>,
+            Option<&WorkArea>,
+        ),
+        Without<Dead>,
+    >,
+) {
+    let (Some(board), Some(mut unstaffed)) = (board, unstaffed) else {
+        return;
+    };
+    let tick = time.map_or(0, |t| t.ticks);
+    let warn_after = config.map_or_else(
+        || StaffingConfig::default().warn_after_ticks,
+        |c| c.warn_after_ticks,
+    );
+
+    let eligible = |key: (FactionKind, WorkerRole)| {
+        workers
+            .iter()
+            .filter(move |(miner, carrier, faction, ..)| {
+                faction_of(*faction) == key.0
+                    && match key.1 {
+                        WorkerRole::Miner | WorkerRole::Builder | WorkerRole::Crafter => *miner,
+                        WorkerRole::Hauler
+                        | WorkerRole::Firefighter
+                        | WorkerRole::Shearer
+                        | WorkerRole::Gravedigger
+                        | WorkerRole::Operator => *carrier,
+                    }
+            })
+            .map(|(.., at, area)| (at, area))
+    };
+    // Per kind: jobs pending, and whether that counts every job because
+    // nobody is eligible, or only those out of every eligible worker's reach
+    let mut pending: BTreeMap<(FactionKind, WorkerRole), (usize, bool)> = BTreeMap::new();
+    for job in &board.0 {
+        let Some(role) = WorkerRole::for_job(&job.kind) else {
+            continue;
+        };
+        let key = (job.faction, role);
+        let mut workers = eligible(key).peekable();
+        if workers.peek().is_none() {
+            pending.entry(key).or_insert((0, false)).0 += 1;
+        } else if !workers
+            .any(|(at, area)| within_reach(area, work_areas.as_deref(), at, &job.kind))
+        {
+            pending.entry(key).or_insert((0, true)).0 += 1;
+        }
+    }
+    unstaffed.entries.retain(|key, (shortage, _)| {
+        pending
+            .get(key)
+            .is_some_and(|&(_, out_of_reach)| out_of_reach == shortage.out_of_reach)
+    });
+
+    let mut announcements = announcements;
+    for ((faction, role), (count, out_of_reach)) in pending {
+        let (shortage, announced) = unstaffed.entries.entry((faction, role)).or_insert((
+            Shortage {
+                faction,
+                role,
+                pending: count,
+                since: tick,
+                out_of_reach,
+            },
+            false,
+        ));
+        shortage.pending = count;
+        if !*announced && tick.saturating_sub(shortage.since) >= warn_after {
+            *announced = true;
+            if let Some(log) = announcements.as_deref_mut() {
+                log.push(tick, Severity::Warning, shortage.to_string());
+            }
+        }
+    }
+}
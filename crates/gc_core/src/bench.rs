@@ -0,0 +1,219 @@
+//! Load-test scenarios for throughput regression checks
+//!
+//! A [`BenchProfile`] describes a colony to stress the simulation: a
+//! generated map, a population of miners and carriers with vision, scattered
+//! items, stockpiles, and a steady trickle of new mine designations so the
+//! job board never drains. [`run_bench`] steps the scenario with
+//! [`bench_schedule`] and reports ticks per second; the `colony` profile is
+//! the reference load (`gc_cli bench --profile colony`) and is also run as a
+//! criterion benchmark.
+
+use crate::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use crate::components::{
+    AssignedJob, Carriable, Carrier, DesignationLifecycle, Inventory, Item, Miner, MiningSkill,
+    Stone, VisionRadius,
+};
+use crate::designations::MineDesignation;
+use crate::determinism::{run_tick, wall_clock};
+use crate::fov::Visibility;
+use crate::stockpiles::StockpileBundle;
+use crate::world::{GameMap, Name, Position, TileKind, Velocity};
+use bevy_ecs::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Size and churn of a load-test colony
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BenchProfile {
+    pub name: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Workers, split evenly between miners and carriers
+    pub agents: u32,
+    /// Loose stone scattered at start
+    pub items: u32,
+    /// Stockpiles laid out in a row across the map
+    pub stockpiles: u32,
+    /// New mine designations placed each tick
+    pub designations_per_tick: u32,
+    /// Ticks per second the profile is expected to sustain in release builds
+    pub target_tps: f64,
+}
+
+impl BenchProfile {
+    /// Reference load: 500 agents on a 300x300 map
+    pub const COLONY: BenchProfile = BenchProfile {
+        name: "colony",
+        width: 300,
+        height: 300,
+        agents: 500,
+        items: 3000,
+        stockpiles: 8,
+        designations_per_tick: 4,
+        target_tps: 200.0,
+    };
+
+    /// Small colony for quick checks and tests
+    pub const SMOKE: BenchProfile = BenchProfile {
+        name: "smoke",
+        width: 60,
+        height: 40,
+        agents: 20,
+        items: 100,
+        stockpiles: 2,
+        designations_per_tick: 1,
+        target_tps: 2000.0,
+    };
+
+    pub const ALL: [BenchProfile; 2] = [BenchProfile::COLONY, BenchProfile::SMOKE];
+}
+
+impl std::str::FromStr for BenchProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BenchProfile::ALL
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown bench profile '{}'", s))
+    }
+}
+
+/// Seeds the designation churn; kept apart from `DeterministicRng` so the
+/// driver does not shift the simulation's own streams
+#[derive(Resource)]
+pub struct BenchChurn {
+    rng: StdRng,
+    per_tick: u32,
+}
+
+/// Build the world for `profile`
+///
+/// Placement draws from a generator seeded with `seed`, so the same profile
+/// and seed always produce the same colony.
+pub fn build_bench_world(profile: BenchProfile, seed: u64) -> World {
+    let mut world =
+        build_standard_world(profile.width, profile.height, seed, WorldOptions::default());
+    world.insert_resource(Visibility::default());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let floors: Vec<(i32, i32)> = world
+        .resource::<GameMap>()
+        .find_tiles(TileKind::Floor)
+        .collect();
+    if floors.is_empty() {
+        return world;
+    }
+    let floor = |rng: &mut StdRng| floors[rng.gen_range(0..floors.len())];
+
+    let band = profile.width as i32 / profile.stockpiles.max(1) as i32;
+    for i in 0..profile.stockpiles as i32 {
+        let x = i * band + band / 2;
+        let y = profile.height as i32 / 2;
+        world
+            .spawn(StockpileBundle::new(x - 2, y - 2, x + 2, y + 2))
+            .insert(Name("Stockpile".into()));
+    }
+    for i in 0..profile.agents {
+        let (x, y) = floor(&mut rng);
+        let mut agent = world.spawn((
+            Position(x, y),
+            Velocity(0, 0),
+            AssignedJob::default(),
+            VisionRadius::new(8),
+        ));
+        if i % 2 == 0 {
+            agent.insert((Miner, MiningSkill::STARTING));
+        } else {
+            agent.insert((Carrier, Inventory::default()));
+        }
+    }
+    for _ in 0..profile.items {
+        let (x, y) = floor(&mut rng);
+        world.spawn((
+            Item::stone(),
+            Stone,
+            Position(x, y),
+            Carriable,
+            Name("Stone".into()),
+        ));
+    }
+    world.insert_resource(BenchChurn {
+        rng,
+        per_tick: profile.designations_per_tick,
+    });
+    world
+}
+
+/// Designate random walls each tick so mining (and the hauling it feeds)
+/// never runs dry
+pub fn bench_churn_system(
+    mut commands: Commands,
+    map: Res<GameMap>,
+    mut churn: ResMut<BenchChurn>,
+) {
+    let (w, h) = (map.width as i32, map.height as i32);
+    if w == 0 || h == 0 {
+        return;
+    }
+    let per_tick = churn.per_tick;
+    for _ in 0..per_tick {
+        // A few probes per designation; open maps may simply skip a tick
+        for _ in 0..8 {
+            let (x, y) = (churn.rng.gen_range(0..w), churn.rng.gen_range(0..h));
+            if map.get_tile(x, y) == Some(TileKind::Wall) {
+                commands.spawn((
+                    MineDesignation,
+                    Position(x, y),
+                    DesignationLifecycle::default(),
+                ));
+                break;
+            }
+        }
+    }
+}
+
+/// Default schedule plus designation churn
+pub fn bench_schedule() -> Schedule {
+    let mut schedule = build_default_schedule();
+    schedule.add_systems(bench_churn_system.before(crate::designations::designation_dedup_system));
+    schedule
+}
+
+/// Throughput measured by [`run_bench`]
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub profile: &'static str,
+    pub ticks: u32,
+    pub elapsed: Duration,
+    pub ticks_per_sec: f64,
+    pub entities: u32,
+    pub target_tps: f64,
+}
+
+impl BenchReport {
+    /// Whether the run sustained the profile's target rate
+    pub fn meets_target(&self) -> bool {
+        self.ticks_per_sec >= self.target_tps
+    }
+}
+
+/// Build `profile` and run it for `ticks` ticks
+pub fn run_bench(profile: BenchProfile, seed: u64, ticks: u32) -> BenchReport {
+    let mut world = build_bench_world(profile, seed);
+    let mut schedule = bench_schedule();
+    let start = wall_clock();
+    for _ in 0..ticks {
+        run_tick(&mut schedule, &mut world);
+    }
+    let elapsed = start.elapsed();
+    BenchReport {
+        profile: profile.name,
+        ticks,
+        elapsed,
+        ticks_per_sec: ticks as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        entities: world.entities().len(),
+        target_tps: profile.target_tps,
+    }
+}
@@ -0,0 +1,186 @@
+//! Blueprint capture and stamping for reusable designs
+//!
+//! A blueprint is a rectangular snapshot of player intent: mining designations
+//! and stockpile zones, stored relative to the region's top-left corner. It can
+//! be stamped elsewhere on the map with an offset and a quarter-turn rotation,
+//! generating every designation and zone in a single call. Blueprints are plain
+//! serde data so they can be shared as JSON files.
+
+use crate::components::{DesignationLifecycle, DesignationState, ItemType, Stockpile, ZoneBounds};
+use crate::designations::{designate_mine_cells, MineDesignation};
+use crate::stockpiles::StockpileBundle;
+use crate::world::{GameMap, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Quarter-turn rotation applied when stamping a blueprint (clockwise)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Rotation {
+    /// Stamp as captured
+    #[default]
+    None,
+    /// Rotate 90 degrees clockwise
+    Cw90,
+    /// Rotate 180 degrees
+    Cw180,
+    /// Rotate 270 degrees clockwise (90 counter-clockwise)
+    Cw270,
+}
+
+/// A stockpile zone stored in blueprint-local coordinates (inclusive corners)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlueprintZone {
+    /// Local minimum corner (x, y)
+    pub min: (i32, i32),
+    /// Local maximum corner (x, y)
+    pub max: (i32, i32),
+    /// Item filter copied from the source stockpile (None = accepts all)
+    pub accepts: Option<Vec<ItemType>>,
+}
+
+/// Serializable design captured from a rectangular region of the world
+///
+/// All coordinates are local to the captured region, with (0, 0) being the
+/// region's minimum corner. Planned constructions will be added here once a
+/// construction designation exists.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blueprint {
+    /// Width of the captured region in tiles
+    pub width: u32,
+    /// Height of the captured region in tiles
+    pub height: u32,
+    /// Local coordinates of mining designations
+    pub mines: Vec<(i32, i32)>,
+    /// Stockpile zones fully contained in the captured region
+    pub zones: Vec<BlueprintZone>,
+}
+
+/// Entities created by [`stamp_blueprint`]
+#[derive(Debug, Default, Clone)]
+pub struct StampResult {
+    /// Newly spawned mining designation entities
+    pub designations: Vec<Entity>,
+    /// Newly spawned stockpile entities
+    pub zones: Vec<Entity>,
+    /// Number of blueprint cells skipped because they fell outside the map
+    pub skipped: usize,
+    /// Mining cells already designated in the world
+    pub duplicates: usize,
+}
+
+/// Rotate a local coordinate inside a `width` x `height` region
+fn rotate_local(x: i32, y: i32, width: i32, height: i32, rotation: Rotation) -> (i32, i32) {
+    match rotation {
+        Rotation::None => (x, y),
+        Rotation::Cw90 => (height - 1 - y, x),
+        Rotation::Cw180 => (width - 1 - x, height - 1 - y),
+        Rotation::Cw270 => (y, width - 1 - x),
+    }
+}
+
+/// Capture designations and stockpile zones inside `region` into a blueprint
+///
+/// Active and Consumed mining designations are captured; Ignored duplicates are
+/// skipped. Stockpiles are only captured when their bounds lie entirely inside
+/// the region. Output is sorted so identical layouts produce identical JSON.
+pub fn capture_blueprint(world: &mut World, region: &ZoneBounds) -> Blueprint {
+    let (ox, oy) = (region.min_x, region.min_y);
+
+    let mut mines: Vec<(i32, i32)> = {
+        let mut q = world
+            .query_filtered::<(&Position, Option<&DesignationLifecycle>), With<MineDesignation>>();
+        q.iter(world)
+            .filter(|(_, lc)| !matches!(lc.map(|l| l.0), Some(DesignationState::Ignored)))
+            .filter(|(pos, _)| region.contains(pos.0, pos.1))
+            .map(|(pos, _)| (pos.0 - ox, pos.1 - oy))
+            .collect()
+    };
+    mines.sort_unstable();
+    mines.dedup();
+
+    let mut zones: Vec<BlueprintZone> = {
+        let mut q = world.query::<(&Stockpile, &ZoneBounds)>();
+        q.iter(world)
+            .filter(|(_, b)| region.contains(b.min_x, b.min_y) && region.contains(b.max_x, b.max_y))
+            .map(|(sp, b)| BlueprintZone {
+                min: (b.min_x - ox, b.min_y - oy),
+                max: (b.max_x - ox, b.max_y - oy),
+                accepts: sp.accepts.clone(),
+            })
+            .collect()
+    };
+    zones.sort_by_key(|z| (z.min, z.max));
+
+    Blueprint {
+        width: (region.max_x - region.min_x + 1).max(0) as u32,
+        height: (region.max_y - region.min_y + 1).max(0) as u32,
+        mines,
+        zones,
+    }
+}
+
+/// Stamp a blueprint with its (rotated) top-left corner at `origin`
+///
+/// Spawns a mining designation per captured cell and a stockpile per captured
+/// zone. Cells outside the map (when a `GameMap` resource exists) are skipped;
+/// zones are skipped unless both corners are in bounds. Cells that are already
+/// designated are counted in `duplicates` instead of spawning again.
+pub fn stamp_blueprint(
+    world: &mut World,
+    blueprint: &Blueprint,
+    origin: (i32, i32),
+    rotation: Rotation,
+) -> StampResult {
+    let (w, h) = (blueprint.width as i32, blueprint.height as i32);
+    let to_world = |(lx, ly): (i32, i32)| {
+        let (rx, ry) = rotate_local(lx, ly, w, h, rotation);
+        (origin.0 + rx, origin.1 + ry)
+    };
+    // Check every zone against the map before anything is spawned
+    let zones: Vec<Option<StockpileBundle>> = {
+        let map = world.get_resource::<GameMap>();
+        let in_bounds = |x: i32, y: i32| map.map_or(true, |m| m.in_bounds(x, y));
+        blueprint
+            .zones
+            .iter()
+            .map(|zone| {
+                let (ax, ay) = to_world(zone.min);
+                let (bx, by) = to_world(zone.max);
+                let (min_x, max_x) = (ax.min(bx), ax.max(bx));
+                let (min_y, max_y) = (ay.min(by), ay.max(by));
+                if !in_bounds(min_x, min_y) || !in_bounds(max_x, max_y) {
+                    return None;
+                }
+                let mut bundle = StockpileBundle::new(min_x, min_y, max_x, max_y);
+                bundle.stockpile.accepts = zone.accepts.clone();
+                Some(bundle)
+            })
+            .collect()
+    };
+
+    let mut result = StampResult::default();
+
+    let mines = designate_mine_cells(world, blueprint.mines.iter().map(|&c| to_world(c)));
+    result.designations = mines.spawned;
+    result.skipped += mines.invalid;
+    result.duplicates = mines.duplicates;
+
+    for bundle in zones {
+        match bundle {
+            Some(bundle) => result.zones.push(world.spawn(bundle).id()),
+            None => result.skipped += 1,
+        }
+    }
+
+    result
+}
+
+/// Encode a blueprint to a pretty-printed JSON string for sharing
+pub fn encode_blueprint_json(blueprint: &Blueprint) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(blueprint)
+}
+
+/// Decode a blueprint from JSON
+pub fn decode_blueprint_json(s: &str) -> Result<Blueprint, serde_json::Error> {
+    serde_json::from_str(s)
+}
@@ -5,11 +5,41 @@
 use bevy_ecs::prelude::*;
 use rand::Rng;
 
+use crate::announcements;
+use crate::burial;
+use crate::burrowing;
+use crate::caverns;
+use crate::construction;
+use crate::danger;
 use crate::designations;
+use crate::director;
+use crate::equipment;
+use crate::fire;
+use crate::forbid;
+use crate::fov;
+use crate::haul_priority;
+use crate::health;
+use crate::history;
 use crate::jobs;
+use crate::lighting;
+use crate::livestock;
+use crate::manager;
+use crate::mechanisms;
+use crate::metrics;
+use crate::names;
+use crate::path;
 use crate::prelude::*;
-use crate::stockpiles::StockpileBundle;
+use crate::production;
+use crate::stockpiles::{self, StockpileBundle};
 use crate::systems;
+use crate::telemetry;
+use crate::temperature;
+use crate::toggles;
+use crate::traffic;
+use crate::tutorial;
+use crate::vision;
+use crate::work_area;
+use crate::zones;
 
 /// Options controlling what entities/resources to include when building a world.
 #[derive(Debug, Clone, Copy)]
@@ -18,6 +48,10 @@ pub struct WorldOptions {
     pub populate_demo_scene: bool,
     /// Initial time tick duration in ms (fixed-step). Defaults to 100.
     pub tick_ms: u64,
+    /// Terrain generation backend. Defaults to noise.
+    pub mapgen: MapGenAlgorithm,
+    /// Hidden caverns to seal into the rock. Defaults to none.
+    pub caverns: u32,
 }
 
 impl Default for WorldOptions {
@@ -25,6 +59,8 @@ impl Default for WorldOptions {
         Self {
             populate_demo_scene: false,
             tick_ms: 100,
+            mapgen: MapGenAlgorithm::default(),
+            caverns: 0,
         }
     }
 }
@@ -43,15 +79,118 @@ pub fn build_standard_world(width: u32, height: u32, seed: u64, opts: WorldOptio
         let mut rng = world.resource_mut::<systems::DeterministicRng>();
         rng.mapgen_rng.gen::<u32>()
     };
-    let map = gen.generate(width, height, mapgen_seed);
+    let config = MapGenConfig {
+        algorithm: opts.mapgen,
+        caverns: opts.caverns,
+        ..MapGenConfig::new(width, height)
+    };
+    let map = gen.generate_with(&config, mapgen_seed);
+    let caverns = gen.generate_caverns(&config, &map, mapgen_seed);
+    world.insert_resource(map);
+    world.insert_resource(gen.generate_materials(&config, mapgen_seed));
+    insert_core_resources(&mut world, opts);
+    world.insert_resource(caverns);
+    world
+}
+
+/// Build a world on an embarked overmap site
+///
+/// Like [`build_standard_world`], but the map comes from
+/// [`Overmap::embark`] and the site is populated with biome flora, fauna,
+/// and ambient temperature. The overmap seed is expected to come from the
+/// same `mapgen_rng` draw that standard worlds use for their map.
+pub fn build_embark_world(
+    overmap: &Overmap,
+    site: EmbarkSite,
+    seed: u64,
+    opts: WorldOptions,
+) -> Result<World, EmbarkError> {
+    let map = overmap.embark(site)?;
+    let materials = overmap.embark_materials(site)?;
+    let mut world = World::new();
+    let mut rng = systems::DeterministicRng::new(seed);
+    // Keep stream positions identical to standard worlds
+    let _overmap_seed = rng.mapgen_rng.gen::<u32>();
+    world.insert_resource(rng);
     world.insert_resource(map);
+    world.insert_resource(materials);
+    insert_core_resources(&mut world, opts);
+    populate_embark(&mut world, overmap, site);
+    Ok(world)
+}
 
+/// Job/time resources and the optional demo scene shared by world builders
+fn insert_core_resources(world: &mut World, opts: WorldOptions) {
     // Core resources
     world.insert_resource(JobBoard::default());
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(stockpiles::StockpileContents::default());
+    world.insert_resource(tutorial::TutorialProgress::default());
+    world.insert_resource(telemetry::EntityTelemetry::default());
+    world.insert_resource(telemetry::TelemetryConfig::default());
+    world.insert_resource(metrics::RunMetrics::default());
+    world.insert_resource(history::EntityHistory::default());
+    world.insert_resource(work_area::WorkAreaConfig::default());
+    world.insert_resource(forbid::ForbidConfig::default());
+    world.insert_resource(manager::ManagerOrders::default());
+    world.insert_resource(burial::BurialConfig::default());
+    world.insert_resource(burrowing::BurrowConfig::default());
+    world.insert_resource(director::DirectorConfig::default());
+    world.insert_resource(director::EventDirector::default());
+    world.insert_resource(director::RegionDifficulty::default());
+    world.insert_resource(crate::command::CommandLog::default());
+    world.insert_resource(caverns::Caverns::default());
+    world.insert_resource(mechanisms::MechanismConfig::default());
+    world.insert_resource(mechanisms::MechanismQueue::default());
+    world.insert_resource(crate::yields::YieldTable::default());
+    world.insert_resource(crate::tags::TagRegistry::default());
+    world.insert_resource(production::RecipeBook::default());
+    world.insert_resource(production::FuelConfig::default());
+    world.insert_resource(fire::FireConfig::default());
+    world.insert_resource(fire::FireMap::default());
+    world.insert_resource(livestock::LivestockConfig::default());
+    world.insert_resource(systems::MovementConfig::default());
+    world.insert_resource(path::PathQueue::default());
+    world.insert_resource(path::PathService::default());
+    world.insert_resource(traffic::TrafficConfig::default());
+    world.insert_resource(traffic::TrafficMap::default());
+    world.insert_resource(toggles::SystemToggles::default());
+    world.insert_resource(haul_priority::HaulPriorityConfig::default());
+    world.insert_resource(path::PathfindingConfig::default());
+    world.insert_resource(danger::DangerConfig::default());
+    world.insert_resource(danger::DangerMap::default());
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        require_access: true,
+        confirm_hazards: true,
+        consumed_cleanup: designations::ConsumedCleanup::OnJobComplete,
+        ..Default::default()
+    });
     world.insert_resource(systems::Time::new(opts.tick_ms));
+    world.insert_resource(Announcements::default());
+    world.insert_resource(announcements::StaffingConfig::default());
+    world.insert_resource(announcements::UnstaffedJobs::default());
+    world.insert_resource(designations::DesignationEvents::default());
+    world.insert_resource(crate::fov::Visibility::default());
+    world.insert_resource(crate::fov::Explored::default());
+    world.insert_resource(jobs::ReassignPolicy::default());
+    world.insert_resource(jobs::JobGcConfig::default());
+    world.insert_resource(jobs::JobThrottle::default());
+    world.insert_resource(jobs::ThrottleState::default());
+    world.insert_resource(jobs::ReassignmentEvents::default());
+    world.insert_resource(crate::faults::SimFaults::default());
+    world.insert_resource(lighting::DayCycle::default());
+    world.insert_resource(lighting::LightingConfig::default());
+    world.insert_resource(lighting::LightMap::default());
+    world.insert_resource(vision::VisionConfig::default());
+    world.insert_resource(vision::Fog::default());
+    // Temperate, and comfortable even at night, until an embark sets the
+    // site's own
+    world.insert_resource(crate::overmap::AmbientTemperature(18));
+    world.insert_resource(temperature::TemperatureConfig::default());
+    world.insert_resource(temperature::TemperatureMap::default());
+    world.insert_resource(SquadUniforms::default());
 
     if opts.populate_demo_scene {
         // Miner
@@ -60,8 +199,9 @@ pub fn build_standard_world(width: u32, height: u32, seed: u64, opts: WorldOptio
             Position(5, 5),
             Velocity(0, 0),
             Miner,
+            MiningSkill::STARTING,
             AssignedJob::default(),
-            VisionRadius(8),
+            VisionRadius::new(8),
         ));
 
         // Carrier
@@ -72,7 +212,7 @@ pub fn build_standard_world(width: u32, height: u32, seed: u64, opts: WorldOptio
             Carrier,
             Inventory::default(),
             AssignedJob::default(),
-            VisionRadius(8),
+            VisionRadius::new(8),
         ));
 
         // Stockpile zone centered around (10,10)
@@ -80,28 +220,239 @@ pub fn build_standard_world(width: u32, height: u32, seed: u64, opts: WorldOptio
             .spawn(StockpileBundle::new(9, 9, 11, 11))
             .insert(Name("Stockpile".into()));
     }
-
-    world
 }
 
 /// Build the default simulation schedule used by shells for demos/play.
 pub fn build_default_schedule() -> Schedule {
     let mut schedule = Schedule::default();
     schedule.add_systems((
-        systems::movement,
+        names::name_unnamed_system,
+        (health::injury_pace_system, systems::movement).chain(),
         systems::confine_to_map,
         (
+            designations::designation_discovery_system,
+            designations::designation_access_system,
+            designations::designation_safety_system,
             designations::designation_dedup_system,
+            jobs::active_jobs_gc_system,
             designations::designation_to_jobs_system,
+            construction::construction_site_system,
+            manager::manager_system,
+            production::station_supply_system,
+            fire::fire_job_system,
+            fire::extinguish_job_assignment_system,
+            livestock::shearing_job_system,
+            livestock::shearing_job_assignment_system,
+            (
+                burial::corpse_system,
+                burial::ghost_system,
+                burial::burial_job_system,
+                burial::burial_job_assignment_system,
+            )
+                .chain(),
+            (
+                mechanisms::lever_job_system,
+                mechanisms::lever_job_assignment_system,
+            )
+                .chain(),
             jobs::job_assignment_system,
+            construction::build_job_assignment_system,
+            production::craft_job_assignment_system,
+            health::rest_job_system.run_if(subsystem_enabled(Subsystem::Needs)),
+            (equipment::uniform_job_system, history::job_history_system).chain(),
         )
             .chain(),
         (
-            jobs::mine_job_execution_system,
-            systems::hauling_execution_system,
-            systems::auto_haul_system,
+            // Mined stone lands via the spawn queue, and auto-haul sees it
+            // the same tick
+            (
+                jobs::mine_job_execution_system,
+                caverns::cavern_reveal_system,
+                jobs::process_item_spawn_queue_system,
+                systems::auto_haul_system.run_if(subsystem_enabled(Subsystem::AutoHaul)),
+            )
+                .chain(),
+            // Carriers walk the map as this tick's mining left it
+            systems::hauling_execution_system
+                .after(jobs::mine_job_execution_system)
+                .after(construction::build_execution_system),
+            health::rest_execution_system,
+            equipment::equipment_execution_system,
+            construction::build_execution_system.after(jobs::mine_job_execution_system),
+            production::craft_execution_system.after(construction::build_execution_system),
+            fire::extinguish_execution_system,
+            livestock::shearing_execution_system,
+            burial::burial_execution_system,
+            // Plates see where everyone ended up this tick
+            (
+                mechanisms::lever_execution_system,
+                mechanisms::mechanism_system,
+            )
+                .chain()
+                .after(systems::hauling_execution_system)
+                .after(burial::burial_execution_system)
+                .after(livestock::livestock_movement_system),
+            (
+                livestock::livestock_movement_system,
+                livestock::livestock_production_system,
+            )
+                .chain(),
+            zones::meeting_zone_system,
+        )
+            .after(health::injury_pace_system)
+            .after(history::job_history_system),
+        // Health changes land after everything that reads hp this tick
+        (
+            health::regeneration_system.run_if(subsystem_enabled(Subsystem::Needs)),
+            health::death_system,
+            announcements::death_announcement_system,
+        )
+            .chain()
+            .after(health::rest_execution_system)
+            .after(mechanisms::mechanism_system),
+        // Raiders spawned this tick are announced on the next
+        (
+            announcements::invader_arrival_system,
+            director::event_director_system
+                .after(announcements::invader_arrival_system)
+                .after(systems::hauling_execution_system)
+                .after(jobs::process_item_spawn_queue_system),
         ),
-        systems::advance_time,
+        // Light for next tick's field of view, once this tick's builds and
+        // mechanisms have changed the map
+        lighting::lighting_system
+            .run_if(subsystem_enabled(Subsystem::Weather))
+            .after(construction::build_execution_system)
+            .after(mechanisms::mechanism_system)
+            .after(jobs::mine_job_execution_system),
+        // Temperatures follow this tick's map and doors; comfort and food
+        // read them straight after
+        (
+            temperature::temperature_system,
+            temperature::temperature_comfort_system,
+            temperature::spoilage_system,
+        )
+            .chain()
+            .run_if(subsystem_enabled(Subsystem::Weather))
+            .after(construction::build_execution_system)
+            .after(mechanisms::mechanism_system)
+            .after(jobs::mine_job_execution_system)
+            .after(burial::ghost_system)
+            .after(systems::hauling_execution_system)
+            .after(livestock::livestock_production_system),
+        crate::tags::stockpile_tag_filter_system.before(systems::auto_haul_system),
+        // Counts items where they lie once this tick's hauls have dropped
+        stockpiles::stockpile_contents_system
+            .after(systems::hauling_execution_system)
+            .after(equipment::equipment_execution_system)
+            .after(jobs::process_item_spawn_queue_system),
+        announcements::unstaffed_jobs_system
+            .after(systems::auto_haul_system)
+            .after(announcements::invader_arrival_system),
+        (
+            (danger::danger_map_system, path::path_request_system).chain(),
+            // Items in danger are off limits before auto-haul looks at them
+            forbid::danger_forbid_system
+                .after(danger::danger_map_system)
+                .after(jobs::process_item_spawn_queue_system)
+                .before(systems::auto_haul_system),
+            // Sight radii are settled before field of view reads them
+            vision::vision_modifier_system.before(danger::danger_map_system),
+            // Hostiles seen and tiles explored this tick steer danger
+            // avoidance and designation discovery
+            (fov::compute_visibility_system, fov::record_explored_system)
+                .chain()
+                .after(vision::vision_modifier_system)
+                .before(danger::danger_map_system)
+                .before(designations::designation_discovery_system),
+        )
+            .after(systems::confine_to_map),
+        (
+            path::path_cache_system
+                .after(systems::confine_to_map)
+                .before(systems::hauling_execution_system),
+            // Walls dug this tick are mined-out rock to everything after
+            // mining
+            burrowing::burrower_system
+                .after(path::path_cache_system)
+                .before(jobs::mine_job_execution_system),
+        ),
+        (
+            designations::designation_status_system,
+            designations::designation_cleanup_system,
+        )
+            .chain()
+            .after(designations::designation_to_jobs_system)
+            .after(jobs::mine_job_execution_system),
+        // Counts every move of the tick, including late ones like plates
+        traffic::traffic_system
+            .after(stockpiles::stockpile_contents_system)
+            .after(mechanisms::mechanism_system)
+            .after(production::craft_execution_system),
+        // Goals read this tick's mining, designations and stockpile counts
+        tutorial::tutorial_system
+            .after(stockpiles::stockpile_contents_system)
+            .after(designations::designation_cleanup_system)
+            .after(announcements::unstaffed_jobs_system)
+            .after(announcements::death_announcement_system),
+        // Counts what is left once this tick's spawns and cleanups are done
+        telemetry::entity_telemetry_system
+            .after(traffic::traffic_system)
+            .after(tutorial::tutorial_system)
+            .after(announcements::death_announcement_system)
+            .after(designations::designation_cleanup_system)
+            .after(lighting::lighting_system)
+            .after(temperature::spoilage_system),
+        // Sees every job, rest and death change of the tick
+        history::history_system
+            .after(telemetry::entity_telemetry_system)
+            .after(temperature::spoilage_system),
+        // Every system reading `Time` sees the tick before it advances
+        systems::advance_time
+            .after(history::history_system)
+            .after(telemetry::entity_telemetry_system)
+            .after(tutorial::tutorial_system)
+            .after(traffic::traffic_system)
+            .after(health::injury_pace_system)
+            .after(announcements::death_announcement_system)
+            .after(announcements::invader_arrival_system)
+            .after(director::event_director_system)
+            .after(announcements::unstaffed_jobs_system)
+            .after(designations::designation_cleanup_system)
+            .after(lighting::lighting_system)
+            .after(temperature::spoilage_system),
     ));
     schedule
 }
+
+/// Run `schedule` until `done` holds, for at most `max_ticks` runs
+///
+/// Returns the number of runs it took, or `None` if `done` never held.
+/// Tests use this to skip past multi-tick work (travel, staged hauls)
+/// without depending on how many ticks it takes.
+pub fn fast_forward(
+    world: &mut World,
+    schedule: &mut Schedule,
+    max_ticks: u32,
+    mut done: impl FnMut(&mut World) -> bool,
+) -> Option<u32> {
+    for tick in 0..=max_ticks {
+        if done(world) {
+            return Some(tick);
+        }
+        if tick < max_ticks {
+            crate::determinism::run_tick(schedule, world);
+        }
+    }
+    None
+}
+
+/// Whether no jobs are queued or being worked
+pub fn jobs_idle(world: &mut World) -> bool {
+    world
+        .get_resource::<JobBoard>()
+        .map_or(true, |b| b.0.is_empty())
+        && world
+            .get_resource::<jobs::ActiveJobs>()
+            .map_or(true, |a| a.jobs.is_empty())
+}
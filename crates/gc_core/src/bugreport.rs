@@ -0,0 +1,400 @@
+//! Bug-report bundles: everything needed to reproduce a problem in one file
+//!
+//! [`bug_report`] gathers a world's save, the [`CommandLog`] that led to it,
+//! its seed and tick, a [`RunMetrics`] snapshot, the last few
+//! [`Announcements`] and (when given) the config file text. The bundle is a
+//! ZIP archive, written uncompressed so no compression dependency is
+//! needed, that any unzip tool can open:
+//!
+//! | entry           | contents                                         |
+//! |-----------------|--------------------------------------------------|
+//! | `manifest.json` | [`BugReportManifest`]: version, seed, tick, hash |
+//! | `save.json`     | the [`SaveGame`], loadable with [`load_world`]   |
+//! | `replay.json`   | the [`CommandLog`], replayable from the seed     |
+//! | `metrics.json`  | [`RunMetrics`]                                   |
+//! | `events.json`   | the last [`BugReportOptions::events`] announcements |
+//! | `config.json`   | the config file, when there is one               |
+//!
+//! With [`BugReportOptions::anonymize`] every entity [`Name`] becomes
+//! `Anon N` (numbered in save order, so owners still match their animals)
+//! in the save and the events, and each of [`BugReportOptions::redact`]
+//! (say, the account name in a path) becomes `[redacted]` in the events
+//! and config. Only whole words are replaced.
+//!
+//! [`replay_commands`] plays a replay log back on a world freshly built
+//! from the bundled seed, which should arrive at the bundled save.
+//!
+//! [`Name`]: crate::world::Name
+//! [`load_world`]: crate::save::load_world
+
+use crate::announcements::{Announcement, Announcements};
+use crate::command::{apply_world_command_as, CommandLog};
+use crate::determinism::run_tick;
+use crate::metrics::RunMetrics;
+use crate::save::{save_world, SaveGame};
+use crate::systems::Time;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What goes into a bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BugReportOptions {
+    /// Most recent announcements to include
+    pub events: usize,
+    /// Replace entity names with `Anon N`
+    pub anonymize: bool,
+    /// Words replaced by `[redacted]` in the events and config when
+    /// anonymizing
+    pub redact: Vec<String>,
+    /// Text of the config file, if there is one
+    pub config: Option<String>,
+}
+
+impl Default for BugReportOptions {
+    fn default() -> Self {
+        Self {
+            events: 50,
+            anonymize: false,
+            redact: Vec::new(),
+            config: None,
+        }
+    }
+}
+
+/// Summary of a bundle, written first so it can be read at a glance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BugReportManifest {
+    /// Version of the game that wrote the bundle
+    pub version: String,
+    /// Master seed the world was generated from
+    pub seed: u64,
+    /// Tick the world was saved at
+    pub tick: u64,
+    /// [`SaveGame::content_hash`] of the bundled save
+    pub save_hash: u64,
+    /// Commands in the replay log
+    pub commands: usize,
+    /// Announcements included
+    pub events: usize,
+    /// Names were replaced
+    pub anonymized: bool,
+}
+
+/// A bundle's contents, ready to write with [`BugReport::to_zip`]
+#[derive(Debug, Clone)]
+pub struct BugReport {
+    pub manifest: BugReportManifest,
+    pub save: SaveGame,
+    pub metrics: RunMetrics,
+    pub events: Vec<Announcement>,
+    pub config: Option<String>,
+}
+
+/// A bundle that cannot be read back
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("not a bug-report bundle: {0}")]
+    Malformed(&'static str),
+    #[error("entry {0} is compressed; bundles are written uncompressed")]
+    Compressed(String),
+    #[error("entry {0} is corrupted (checksum mismatch)")]
+    Checksum(String),
+    #[error("bundle has no {0}")]
+    Missing(&'static str),
+    #[error("{entry}: {source}")]
+    Json {
+        entry: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Gather a bug report from `world`
+pub fn bug_report(world: &mut World, options: &BugReportOptions) -> BugReport {
+    let mut save = save_world(world);
+    let mut events: Vec<Announcement> = world
+        .get_resource::<Announcements>()
+        .map(|a| a.recent(options.events).cloned().collect())
+        .unwrap_or_default();
+    let mut config = options.config.clone();
+    if options.anonymize {
+        let aliases = anonymize_save(&mut save);
+        let mut words: Vec<(&str, String)> = aliases
+            .iter()
+            .map(|(name, alias)| (name.as_str(), alias.clone()))
+            .collect();
+        words.extend(
+            options
+                .redact
+                .iter()
+                .filter(|w| !w.is_empty())
+                .map(|w| (w.as_str(), "[redacted]".to_string())),
+        );
+        // Longest first, so a name never shadows a longer one containing it
+        words.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(b.0)));
+        for event in &mut events {
+            event.text = replace_words(&event.text, &words);
+        }
+        config = config.map(|text| replace_words(&text, &words));
+    }
+    BugReport {
+        manifest: BugReportManifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: save.master_seed,
+            tick: save.ticks,
+            save_hash: save.content_hash(),
+            commands: save.commands.0.len(),
+            events: events.len(),
+            anonymized: options.anonymize,
+        },
+        save,
+        metrics: world
+            .get_resource::<RunMetrics>()
+            .copied()
+            .unwrap_or_default(),
+        events,
+        config,
+    }
+}
+
+/// Run `world` up to tick `until`, applying each logged command before the
+/// tick it was applied at
+///
+/// `world` should be built the way the logged one was, from the same seed.
+/// Commands logged before the world's current tick are applied at once;
+/// a world without [`Time`] gets the commands but runs no ticks.
+pub fn replay_commands(world: &mut World, schedule: &mut Schedule, log: &CommandLog, until: u64) {
+    let mut commands = log.0.iter().peekable();
+    loop {
+        let now = world.get_resource::<Time>().map_or(until, |t| t.ticks);
+        while let Some(logged) = commands.next_if(|c| c.tick <= now) {
+            apply_world_command_as(world, &logged.command, logged.issuer);
+        }
+        if now >= until {
+            break;
+        }
+        run_tick(schedule, world);
+    }
+}
+
+/// Rename every named entity (and owner) in `save` to `Anon N`, returning
+/// the aliases by original name
+fn anonymize_save(save: &mut SaveGame) -> BTreeMap<String, String> {
+    let mut aliases = BTreeMap::new();
+    for e in &save.entities {
+        for name in [&e.name, &e.owner].into_iter().flatten() {
+            if !aliases.contains_key(name) {
+                let alias = format!("Anon {}", aliases.len() + 1);
+                aliases.insert(name.clone(), alias);
+            }
+        }
+    }
+    for e in &mut save.entities {
+        for name in [&mut e.name, &mut e.owner].into_iter().flatten() {
+            *name = aliases[name.as_str()].clone();
+        }
+    }
+    // The integrity hash covered the real names
+    *save = SaveGame {
+        world_hash: None,
+        ..save.clone()
+    }
+    .hashed();
+    aliases
+}
+
+/// Replace whole-word occurrences of each `(word, with)` in turn
+fn replace_words(text: &str, words: &[(&str, String)]) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut text = text.to_string();
+    for (word, with) in words {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = 0;
+        for (at, _) in text.match_indices(word) {
+            let end = at + word.len();
+            if at < rest
+                || is_word(text[..at].chars().next_back())
+                || is_word(text[end..].chars().next())
+            {
+                continue;
+            }
+            out.push_str(&text[rest..at]);
+            out.push_str(with);
+            rest = end;
+        }
+        out.push_str(&text[rest..]);
+        text = out;
+    }
+    text
+}
+
+impl BugReport {
+    /// The bundle as ZIP archive bytes
+    pub fn to_zip(&self) -> Vec<u8> {
+        let mut entries = vec![
+            ("manifest.json", json(&self.manifest)),
+            ("save.json", json(&self.save)),
+            ("replay.json", json(&self.save.commands)),
+            ("metrics.json", json(&self.metrics)),
+            ("events.json", json(&self.events)),
+        ];
+        if let Some(config) = &self.config {
+            entries.push(("config.json", config.clone().into_bytes()));
+        }
+        write_zip(&entries)
+    }
+
+    /// Read a bundle written by [`to_zip`](Self::to_zip)
+    pub fn from_zip(bytes: &[u8]) -> Result<Self, BundleError> {
+        let entries = read_zip(bytes)?;
+        let entry = |name: &'static str| {
+            entries
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, data)| data.as_slice())
+        };
+        fn parse<T: for<'de> Deserialize<'de>>(
+            name: &'static str,
+            data: Option<&[u8]>,
+        ) -> Result<T, BundleError> {
+            let data = data.ok_or(BundleError::Missing(name))?;
+            serde_json::from_slice(data).map_err(|source| BundleError::Json {
+                entry: name.to_string(),
+                source,
+            })
+        }
+        Ok(Self {
+            manifest: parse("manifest.json", entry("manifest.json"))?,
+            save: parse("save.json", entry("save.json"))?,
+            metrics: parse("metrics.json", entry("metrics.json"))?,
+            events: parse("events.json", entry("events.json"))?,
+            config: entry("config.json").map(|c| String::from_utf8_lossy(c).into_owned()),
+        })
+    }
+}
+
+/// Pretty JSON for a bundle entry
+fn json<T: Serialize>(value: &T) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).expect("bundle entries serialize")
+}
+
+// --- ZIP (stored entries only) ---
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+/// 1980-01-01, the earliest DOS date; a fixed stamp keeps bundles
+/// byte-identical for the same world
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+fn write_zip(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in entries {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        // Fields shared by the local and central headers, from the version
+        // needed to the name length: 2.0, no flags, stored, time and date
+        let mut common = Vec::new();
+        for half in [20u16, 0, 0, 0, DOS_DATE] {
+            common.extend_from_slice(&half.to_le_bytes());
+        }
+        for word in [crc, data.len() as u32, data.len() as u32] {
+            common.extend_from_slice(&word.to_le_bytes());
+        }
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        out.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&common);
+        // Comment length, disk, internal and external attributes
+        for half in [0u16, 0, 0, 0, 0] {
+            directory.extend_from_slice(&half.to_le_bytes());
+        }
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let directory_offset = out.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+    let count = entries.len() as u16;
+    for half in [0u16, 0, count, count] {
+        out.extend_from_slice(&half.to_le_bytes());
+    }
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+/// Entries of a stored ZIP archive, read front to back
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, BundleError> {
+    let truncated = BundleError::Malformed("truncated archive");
+    let u16_at = |at: usize| {
+        bytes
+            .get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let mut entries = Vec::new();
+    let mut at = 0;
+    loop {
+        match u32_at(at) {
+            Some(LOCAL_HEADER) => {}
+            Some(CENTRAL_HEADER | END_OF_DIRECTORY) if !entries.is_empty() => break,
+            Some(_) => return Err(BundleError::Malformed("not a ZIP archive")),
+            None => return Err(truncated),
+        }
+        let (Some(flags), Some(method), Some(crc), Some(size), Some(name_len), Some(extra_len)) = (
+            u16_at(at + 6),
+            u16_at(at + 8),
+            u32_at(at + 14),
+            u32_at(at + 18),
+            u16_at(at + 26),
+            u16_at(at + 28),
+        ) else {
+            return Err(truncated);
+        };
+        let name_at = at + 30;
+        let data_at = name_at + name_len + extra_len;
+        let (Some(name), Some(data)) = (
+            bytes.get(name_at..name_at + name_len),
+            bytes.get(data_at..data_at + size as usize),
+        ) else {
+            return Err(truncated);
+        };
+        let name = String::from_utf8_lossy(name).into_owned();
+        // Bit 3: sizes trail the data, which only compressing writers do
+        if method != 0 || flags & 0x8 != 0 {
+            return Err(BundleError::Compressed(name));
+        }
+        if crc32(data) != crc {
+            return Err(BundleError::Checksum(name));
+        }
+        entries.push((name, data.to_vec()));
+        at = data_at + size as usize;
+    }
+    Ok(entries)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,412 @@
+//! Burial, graves and ghosts
+//!
+//! A dead goblin lies where it fell as a [`Corpse`]. Once a finished
+//! [`Grave`] (a [`ConstructionKind::Grave`]: a dug plot with a wooden
+//! coffin) is free, [`burial_job_system`] posts a [`JobKind::Bury`] for the
+//! corpse and the nearest free grave. An idle carrier walks to the body,
+//! carries it to the grave and buries it, leaving it [`Buried`].
+//!
+//! A corpse left unburied for [`BurialConfig::ghost_after`] ticks rises as a
+//! [`Ghost`]. Every [`BurialConfig::haunt_interval`] ticks after that it
+//! haunts the camp: every living goblin loses [`BurialConfig::mood_penalty`]
+//! [`Mood`], and the station or construction site nearest the body loses
+//! the work put into it. Burying the body lays the ghost to rest. Everything
+//! runs off tick counts and entity order, so haunting is deterministic.
+//!
+//! [`ConstructionKind::Grave`]: crate::construction::ConstructionKind::Grave
+
+use crate::announcements::{Announcements, Severity};
+use crate::components::{
+    AssignedJob, CarriedBy, Carrier, Dead, Faction, FactionKind, Goblin, Mood, MovementOverride,
+};
+use crate::construction::Construction;
+use crate::determinism::RngStream;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, faction_of, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId,
+    JobKind,
+};
+use crate::path::PathService;
+use crate::production::Station;
+use crate::systems::{travel, DeterministicRng, MovementConfig, Time};
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Name, Position};
+use bevy_ecs::prelude::*;
+use std::collections::HashSet;
+
+/// A finished grave, empty until someone is buried in it
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Grave {
+    pub occupant: Option<Entity>,
+}
+
+/// A dead goblin waiting for burial
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Corpse {
+    /// Tick the body was first seen dead
+    pub died: u64,
+    /// Burial job posted for the body
+    pub burial_job: Option<JobId>,
+}
+
+/// A body laid in a grave
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Buried;
+
+/// The restless spirit of a corpse left unburied too long
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ghost {
+    /// Tick the ghost rose
+    pub since: u64,
+}
+
+/// Ghost timing and consequences
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurialConfig {
+    /// Ticks a corpse may lie unburied before its ghost rises
+    pub ghost_after: u64,
+    /// Ticks between hauntings
+    pub haunt_interval: u64,
+    /// Mood every living goblin loses per haunting
+    pub mood_penalty: i16,
+}
+
+impl Default for BurialConfig {
+    fn default() -> Self {
+        Self {
+            ghost_after: 600,
+            haunt_interval: 100,
+            mood_penalty: 50,
+        }
+    }
+}
+
+/// Whether a dead entity gets buried (and can haunt): goblins only
+pub(crate) fn is_goblin(goblin: bool, faction: Option<&Faction>) -> bool {
+    goblin || faction.is_some_and(|f| f.kind == FactionKind::Goblins)
+}
+
+/// Track newly dead goblins as corpses awaiting burial
+#[allow(clippy::type_complexity)]
+pub fn corpse_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    dead: Query<
+        (Entity, Has<Goblin>, Option<&Faction>),
+        (With<Dead>, With<Position>, Without<Corpse>, Without<Buried>),
+    >,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    for (entity, goblin, faction) in dead.iter() {
+        if is_goblin(goblin, faction) {
+            commands.entity(entity).insert(Corpse {
+                died: tick,
+                burial_job: None,
+            });
+        }
+    }
+}
+
+/// Pair corpses with free graves and post their burial jobs
+///
+/// Corpses are handled in entity order, each taking the nearest free grave
+/// (Manhattan, ties by entity). A body whose carrier died or gave up the job
+/// is put down where it is.
+#[allow(clippy::type_complexity)]
+pub fn burial_job_system(
+    mut commands: Commands,
+    mut board: ResMut<JobBoard>,
+    active: Res<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    mut corpses: Query<(
+        Entity,
+        &Position,
+        &mut Corpse,
+        Option<&Faction>,
+        Option<&CarriedBy>,
+    )>,
+    graves: Query<(Entity, &Position, &Grave)>,
+    carriers: Query<&AssignedJob, Without<Dead>>,
+) {
+    let live: HashSet<JobId> = board
+        .0
+        .iter()
+        .map(|j| j.id)
+        .chain(active.jobs.keys().copied())
+        .collect();
+    let mut reserved: HashSet<(i32, i32)> = board
+        .0
+        .iter()
+        .chain(active.jobs.values())
+        .filter_map(|job| match job.kind {
+            JobKind::Bury { to, .. } => Some(to),
+            _ => None,
+        })
+        .collect();
+
+    let mut order: Vec<Entity> = corpses.iter().map(|(e, ..)| e).collect();
+    order.sort();
+    for entity in order {
+        let (_, pos, mut corpse, faction, carried) = corpses.get_mut(entity).expect("listed above");
+        if let Some(carrier) = carried {
+            let still_held = carriers
+                .get(carrier.0)
+                .is_ok_and(|a| a.0.is_some() && a.0 == corpse.burial_job);
+            if still_held {
+                continue;
+            }
+            commands.entity(entity).remove::<CarriedBy>();
+        }
+        if corpse.burial_job.is_some_and(|id| live.contains(&id)) {
+            continue;
+        }
+        corpse.burial_job = None;
+        let grave = graves
+            .iter()
+            .filter(|(_, p, g)| g.occupant.is_none() && !reserved.contains(&(p.0, p.1)))
+            .min_by_key(|(e, p, _)| ((p.0 - pos.0).abs() + (p.1 - pos.1).abs(), *e));
+        let Some((_, at, _)) = grave else {
+            continue;
+        };
+        let to = (at.0, at.1);
+        reserved.insert(to);
+        let id = add_job_for(
+            &mut board,
+            JobKind::Bury {
+                from: (pos.0, pos.1),
+                to,
+            },
+            IssuedBy::System,
+            faction_of(faction),
+            rng.stream(RngStream::Job, "burial_job_system"),
+        );
+        corpse.burial_job = Some(id);
+    }
+}
+
+/// Hand one queued burial job to an idle carrier of its faction per run, within
+/// its [`WorkArea`]
+pub fn burial_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut carriers: Query<BoardWorker, (With<Carrier>, Without<Dead>)>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut carriers,
+        |kind| matches!(kind, JobKind::Bury { .. }),
+    );
+}
+
+/// Walk gravediggers to the body, carry it to its grave and bury it
+///
+/// The body moves with its carrier. A job whose body is gone is dropped; a
+/// grave filled or removed on the way leaves the body on the grave's tile
+/// for a new job.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn burial_execution_system(
+    mut commands: Commands,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    time: Option<Res<Time>>,
+    announcements: Option<ResMut<Announcements>>,
+    mut active: ResMut<ActiveJobs>,
+    mut diggers: Query<
+        (
+            Entity,
+            &mut AssignedJob,
+            &mut Position,
+            Option<&MovementOverride>,
+        ),
+        (With<Carrier>, Without<Dead>, Without<Stalled>),
+    >,
+    mut corpses: Query<
+        (
+            Entity,
+            &mut Position,
+            &mut Corpse,
+            Option<&CarriedBy>,
+            Option<&Name>,
+            Has<Ghost>,
+        ),
+        With<Dead>,
+    >,
+    mut graves: Query<(&Position, &mut Grave), (Without<Dead>, Without<Carrier>)>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let tick = time.map_or(0, |t| t.ticks);
+    let mut announcements = announcements;
+    for (digger, mut assigned, mut pos, movement) in diggers.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(JobKind::Bury { to, .. }) = active.jobs.get(&id).map(|j| j.kind.clone()) else {
+            continue;
+        };
+        let Some((body, mut body_pos, mut corpse, carried, name, ghost)) = corpses
+            .iter_mut()
+            .find(|(_, _, c, ..)| c.burial_job == Some(id))
+        else {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        };
+        if carried.map(|c| c.0) != Some(digger) {
+            // Fetch the body from beside it
+            let (dx, dy) = (pos.0 - body_pos.0, pos.1 - body_pos.1);
+            if dx.abs() > 1 || dy.abs() > 1 {
+                let beside = (body_pos.0 + dx.signum(), body_pos.1 + dy.signum());
+                travel(
+                    &mut pos,
+                    beside,
+                    config.mode_for(movement),
+                    map.as_deref(),
+                    paths.as_deref_mut(),
+                );
+                continue;
+            }
+            commands.entity(body).insert(CarriedBy(digger));
+            *body_pos = *pos;
+            continue;
+        }
+        if (pos.0, pos.1) != to {
+            travel(
+                &mut pos,
+                to,
+                config.mode_for(movement),
+                map.as_deref(),
+                paths.as_deref_mut(),
+            );
+            *body_pos = *pos;
+            continue;
+        }
+        active.jobs.remove(&id);
+        assigned.0 = None;
+        corpse.burial_job = None;
+        let grave = graves
+            .iter_mut()
+            .find(|(p, g)| (p.0, p.1) == to && g.occupant.is_none());
+        let Some((_, mut grave)) = grave else {
+            commands.entity(body).remove::<CarriedBy>();
+            continue;
+        };
+        grave.occupant = Some(body);
+        commands
+            .entity(body)
+            .remove::<(Corpse, CarriedBy, Ghost)>()
+            .insert(Buried);
+        if ghost {
+            if let Some(announcements) = announcements.as_deref_mut() {
+                let who = name.map_or("a goblin", |n| n.0.as_str());
+                announcements.push(
+                    tick,
+                    Severity::Info,
+                    format!("The ghost of {} has been laid to rest", who),
+                );
+            }
+        }
+    }
+}
+
+/// Raise ghosts from corpses left unburied and let them haunt
+///
+/// Ghosts haunt in entity order. Sabotage picks the station or construction
+/// site nearest the body (Manhattan, ties by entity) that has work in
+/// progress; with none, the haunting only costs mood.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn ghost_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    config: Option<Res<BurialConfig>>,
+    announcements: Option<ResMut<Announcements>>,
+    corpses: Query<(Entity, &Position, &Corpse, Option<&Name>, Option<&Ghost>)>,
+    mut living: Query<
+        (Entity, Has<Goblin>, Option<&Faction>, Option<&mut Mood>),
+        (Without<Dead>, Without<Station>, Without<Construction>),
+    >,
+    mut stations: Query<(Entity, &Position, &mut Station), Without<Construction>>,
+    mut sites: Query<(Entity, &Position, &mut Construction), Without<Station>>,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    let config = config.as_deref().copied().unwrap_or_default();
+    let mut announcements = announcements;
+    let mut order: Vec<Entity> = corpses.iter().map(|(e, ..)| e).collect();
+    order.sort();
+    for entity in order {
+        let (_, pos, corpse, name, ghost) = corpses.get(entity).expect("listed above");
+        let who = name.map_or("a goblin", |n| n.0.as_str());
+        let Some(ghost) = ghost else {
+            if tick.saturating_sub(corpse.died) >= config.ghost_after {
+                commands.entity(entity).insert(Ghost { since: tick });
+                if let Some(announcements) = announcements.as_deref_mut() {
+                    announcements.push(
+                        tick,
+                        Severity::Urgent,
+                        format!("The ghost of {} rises from its unburied body!", who),
+                    );
+                }
+            }
+            continue;
+        };
+        let haunting = tick - ghost.since;
+        if haunting == 0 || config.haunt_interval == 0 || haunting % config.haunt_interval != 0 {
+            continue;
+        }
+        for (goblin, is_goblin_marker, faction, mood) in living.iter_mut() {
+            if !is_goblin(is_goblin_marker, faction) {
+                continue;
+            }
+            match mood {
+                Some(mut mood) => mood.shift(-config.mood_penalty),
+                None => {
+                    let mut mood = Mood::default();
+                    mood.shift(-config.mood_penalty);
+                    commands.entity(goblin).insert(mood);
+                }
+            }
+        }
+
+        let distance = |p: &Position| (p.0 - pos.0).abs() + (p.1 - pos.1).abs();
+        let station = stations
+            .iter()
+            .filter(|(_, _, s)| s.progress > 0)
+            .map(|(e, p, _)| (distance(p), e, (p.0, p.1)))
+            .min();
+        let site = sites
+            .iter()
+            .filter(|(_, _, c)| c.progress > 0)
+            .map(|(e, p, _)| (distance(p), e, (p.0, p.1)))
+            .min();
+        let spoiled = match (station, site) {
+            (Some(s), Some(c)) if c < s => {
+                sites.get_mut(c.1).expect("listed above").2.progress = 0;
+                Some(c.2)
+            }
+            (Some(s), _) => {
+                stations.get_mut(s.1).expect("listed above").2.progress = 0;
+                Some(s.2)
+            }
+            (None, Some(c)) => {
+                sites.get_mut(c.1).expect("listed above").2.progress = 0;
+                Some(c.2)
+            }
+            (None, None) => None,
+        };
+        if let Some(announcements) = announcements.as_deref_mut() {
+            let text = match spoiled {
+                Some(at) => format!(
+                    "The ghost of {} haunts the camp and spoils the work at ({}, {})",
+                    who, at.0, at.1
+                ),
+                None => format!("The ghost of {} haunts the camp", who),
+            };
+            announcements.push(tick, Severity::Warning, text);
+        }
+    }
+}
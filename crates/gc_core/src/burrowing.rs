@@ -0,0 +1,235 @@
+//! Burrowing invaders: hostiles that dig through walls to reach the fort
+//!
+//! A [`Burrower`] is an invader that walls alone do not keep out. Each tick
+//! [`burrower_system`] heads it for the nearest goblin (a living worker of
+//! the colony). While an open path exists it walks it through the shared
+//! [`PathService`]; when none does it plans a tunnel with [`tunnel_path`]
+//! and follows that instead, digging out each wall on the way.
+//!
+//! Digging follows the same work-time rules as a construction site: a
+//! burrower works a wall for [`BurrowConfig::dig_ticks`] ticks, one per
+//! tick, before it turns to floor, and only walls of a material its
+//! [`MiningSkill`] can dig (see [`Material::required_skill`]) are planned
+//! through. Dug walls drop nothing.
+//!
+//! Digging is loud. The first time a burrower works a wall within
+//! [`BurrowConfig::hearing`] tiles of a goblin, the colony is warned; it is
+//! not announced again until that burrower has broken through to open
+//! ground and starts another tunnel.
+
+use crate::announcements::{Announcements, Severity};
+use crate::components::{AssignedJob, Dead, Faction, FactionKind, MiningSkill};
+use crate::faults::SimFaults;
+use crate::health::Stalled;
+use crate::jobs::faction_of;
+use crate::path::{astar_path, PathResult, PathService};
+use crate::systems::Time;
+use crate::world::{GameMap, Material, MaterialMap, Position, TileKind};
+use bevy_ecs::prelude::*;
+use pathfinding::prelude::astar;
+
+/// Invader that digs its own way in when no open path reaches the fort
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Burrower {
+    /// Wall being dug and the ticks of work it has had
+    pub digging: Option<((i32, i32), u32)>,
+    /// Goblins have heard this tunnel being dug
+    pub heard: bool,
+}
+
+/// How fast burrowers dig and how far away they can be heard
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurrowConfig {
+    /// Ticks of work to dig out one wall; planned tunnels count each wall
+    /// as this many steps on top of the one to walk it
+    pub dig_ticks: u32,
+    /// Chebyshev distance from a dug wall within which goblins hear it
+    pub hearing: i32,
+}
+
+impl Default for BurrowConfig {
+    fn default() -> Self {
+        Self {
+            dig_ticks: 4,
+            hearing: 10,
+        }
+    }
+}
+
+/// Whether a digger with `skill` can dig out (x, y)
+///
+/// Only walls qualify, and only those whose [`Material`] (rock without one
+/// counts as soil) needs no more skill than `skill`.
+pub fn can_dig(map: &GameMap, materials: Option<&MaterialMap>, skill: u8, x: i32, y: i32) -> bool {
+    map.get_tile(x, y) == Some(TileKind::Wall)
+        && materials
+            .and_then(|m| m.get(x, y))
+            .map_or(Material::Soil, |m| m)
+            .required_skill()
+            <= skill
+}
+
+/// Cheapest route from `start` to `goal` digging through walls as needed
+///
+/// Floor costs one step and a wall the digger can dig ([`can_dig`])
+/// `1 + dig_ticks`; anything else is impassable. The cost is in those
+/// units. Returns `None` when even digging cannot reach `goal`, which
+/// must be floor.
+pub fn tunnel_path(
+    map: &GameMap,
+    materials: Option<&MaterialMap>,
+    skill: u8,
+    dig_ticks: u32,
+    start: (i32, i32),
+    goal: (i32, i32),
+) -> PathResult {
+    if map.idx(start.0, start.1).is_none() || !map.is_walkable(goal.0, goal.1) {
+        return None;
+    }
+    let dig_cost = 1 + dig_ticks as i32;
+    astar(
+        &start,
+        |&(x, y)| {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .into_iter()
+                .map(move |(dx, dy)| (x + dx, y + dy))
+                .filter_map(|(nx, ny)| {
+                    if map.is_walkable(nx, ny) {
+                        Some(((nx, ny), 1))
+                    } else if can_dig(map, materials, skill, nx, ny) {
+                        Some(((nx, ny), dig_cost))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        },
+        |&(x, y)| (goal.0 - x).abs() + (goal.1 - y).abs(),
+        |&p| p == goal,
+    )
+}
+
+/// Move burrowers toward the nearest goblin, digging where they must
+///
+/// Each burrower stops once next to its goblin. Otherwise it keeps
+/// working a wall it has started, walks an open path when one exists, or
+/// takes the next tile of its [`tunnel_path`]: stepping onto floor, or
+/// starting on a wall. Finished walls are written to the map as floor,
+/// and refused writes are reported to [`SimFaults`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn burrower_system(
+    time: Option<Res<Time>>,
+    config: Option<Res<BurrowConfig>>,
+    materials: Option<Res<MaterialMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut faults: Option<ResMut<SimFaults>>,
+    mut announcements: Option<ResMut<Announcements>>,
+    mut map: ResMut<GameMap>,
+    mut burrowers: Query<
+        (Entity, &mut Burrower, &mut Position, Option<&MiningSkill>),
+        (Without<Dead>, Without<Stalled>),
+    >,
+    goblins: Query<
+        (Entity, &Position, Option<&Faction>),
+        (With<AssignedJob>, Without<Dead>, Without<Burrower>),
+    >,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let tick = time.as_deref().map_or(0, |t| t.ticks);
+    let mut colony: Vec<(Entity, (i32, i32))> = goblins
+        .iter()
+        .filter(|(_, _, f)| faction_of(*f) == FactionKind::Goblins)
+        .map(|(e, p, _)| (e, (p.0, p.1)))
+        .collect();
+    colony.sort_unstable();
+    if colony.is_empty() {
+        return;
+    }
+    let mut order: Vec<Entity> = burrowers.iter().map(|(e, ..)| e).collect();
+    order.sort_unstable();
+
+    for entity in order {
+        let Ok((_, mut burrower, mut pos, skill)) = burrowers.get_mut(entity) else {
+            continue;
+        };
+        let from = (pos.0, pos.1);
+        let distance = |p: (i32, i32)| (p.0 - from.0).abs() + (p.1 - from.1).abs();
+        let Some(goal) = colony.iter().map(|&(_, p)| p).min_by_key(|&p| distance(p)) else {
+            continue;
+        };
+        if distance(goal) <= 1 {
+            burrower.digging = None;
+            continue;
+        }
+        if let Some((wall, _)) = burrower.digging {
+            if map.get_tile(wall.0, wall.1) != Some(TileKind::Wall) {
+                burrower.digging = None;
+            }
+        }
+        if burrower.digging.is_none() {
+            let open = match paths.as_deref_mut() {
+                Some(paths) => paths.next_step(&map, from, goal),
+                None => astar_path(&map, from, goal).and_then(|(path, _)| path.get(1).copied()),
+            };
+            if let Some(next) = open {
+                burrower.heard = false;
+                if next != goal {
+                    pos.set_if_neq(Position(next.0, next.1));
+                }
+                continue;
+            }
+            let skill = skill.map_or(0, |s| s.0);
+            let tunnel = tunnel_path(
+                &map,
+                materials.as_deref(),
+                skill,
+                config.dig_ticks,
+                from,
+                goal,
+            );
+            let Some(next) = tunnel.and_then(|(path, _)| path.get(1).copied()) else {
+                continue;
+            };
+            if map.get_tile(next.0, next.1) != Some(TileKind::Wall) {
+                if next != goal {
+                    pos.set_if_neq(Position(next.0, next.1));
+                }
+                continue;
+            }
+            burrower.digging = Some((next, 0));
+        }
+
+        let Some((wall, worked)) = burrower.digging else {
+            continue;
+        };
+        if !burrower.heard {
+            let within = |&(_, p): &(Entity, (i32, i32))| {
+                (p.0 - wall.0).abs().max((p.1 - wall.1).abs()) <= config.hearing
+            };
+            if colony.iter().any(within) {
+                burrower.heard = true;
+                if let Some(announcements) = announcements.as_deref_mut() {
+                    announcements.push(
+                        tick,
+                        Severity::Warning,
+                        format!("Digging heard in the rock near ({}, {})!", wall.0, wall.1),
+                    );
+                }
+            }
+        }
+        let worked = worked + 1;
+        if worked < config.dig_ticks {
+            burrower.digging = Some((wall, worked));
+            continue;
+        }
+        burrower.digging = None;
+        if let Err(err) = map.set_tile(wall.0, wall.1, TileKind::Floor) {
+            err.report(
+                faults.as_deref_mut(),
+                time.as_deref(),
+                "burrower_system",
+                Some(entity),
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Hidden caverns sealed in rock until mining breaks into them
+//!
+//! Map generation can seal [`Cavern`]s into solid rock
+//! ([`MapGenConfig::caverns`](crate::world::MapGenConfig::caverns)). While
+//! concealed, a cavern's tiles stay [`TileKind::Wall`] in the [`GameMap`],
+//! so nothing can path through or see into it, and renderers draw them as
+//! plain rock whatever overlays are on. Its real tiles (floor, water) and
+//! its fauna are kept in the [`Caverns`] resource.
+//!
+//! As soon as a walkable tile is adjacent (8-way) to a concealed tile,
+//! usually because a miner just dug it out, [`cavern_reveal_system`] opens
+//! the whole cavern: its tiles are written to the map in one
+//! [`GameMap::apply`] batch (so path caches and FOV see an ordinary map
+//! change), its fauna spawns, and the reveal is announced.
+
+use crate::announcements::{Announcements, Severity};
+use crate::components::{Animal, Health};
+use crate::livestock::Species;
+use crate::systems::Time;
+use crate::world::{GameMap, Name, Position, TileChange, TileKind, Velocity};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// One sealed region and what is inside it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cavern {
+    /// Tiles of the cavern, sorted
+    pub tiles: Vec<(i32, i32)>,
+    /// Real kind of each tile in `tiles`, applied when revealed
+    pub kinds: Vec<TileKind>,
+    /// Animals that spawn when the cavern opens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fauna: Vec<((i32, i32), Species)>,
+    /// Tick the cavern was revealed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revealed: Option<u64>,
+}
+
+impl Cavern {
+    pub fn is_concealed(&self) -> bool {
+        self.revealed.is_none()
+    }
+
+    /// Tiles outside the cavern touching it (8-way)
+    fn frontier(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let inside: HashSet<(i32, i32)> = self.tiles.iter().copied().collect();
+        self.tiles
+            .iter()
+            .flat_map(|&(x, y)| {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            })
+            .filter(move |p| !inside.contains(p))
+    }
+}
+
+/// Every generated cavern, concealed or revealed
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Caverns {
+    pub caverns: Vec<Cavern>,
+    /// Map revision last checked for breaches
+    #[serde(skip)]
+    checked: Option<u64>,
+}
+
+impl Caverns {
+    pub fn new(caverns: Vec<Cavern>) -> Self {
+        Self {
+            caverns,
+            checked: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.caverns.is_empty()
+    }
+
+    /// Whether (x, y) lies in a cavern that is still concealed
+    pub fn is_concealed(&self, x: i32, y: i32) -> bool {
+        self.caverns
+            .iter()
+            .any(|c| c.is_concealed() && c.tiles.binary_search(&(x, y)).is_ok())
+    }
+
+    /// Every concealed tile, sorted
+    pub fn concealed(&self) -> Vec<(i32, i32)> {
+        let mut tiles: Vec<(i32, i32)> = self
+            .caverns
+            .iter()
+            .filter(|c| c.is_concealed())
+            .flat_map(|c| c.tiles.iter().copied())
+            .collect();
+        tiles.sort_unstable();
+        tiles
+    }
+}
+
+/// Open every concealed cavern that a walkable tile now touches
+///
+/// Only runs when the map revision has moved since the last check.
+/// Caverns open in generation order; one opening can breach the next.
+pub fn cavern_reveal_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    caverns: Option<ResMut<Caverns>>,
+    announcements: Option<ResMut<Announcements>>,
+    mut map: ResMut<GameMap>,
+) {
+    let Some(mut caverns) = caverns else {
+        return;
+    };
+    if caverns.checked == Some(map.revision()) {
+        return;
+    }
+    let tick = time.map_or(0, |t| t.ticks);
+    let mut announcements = announcements;
+    loop {
+        let breached = caverns
+            .caverns
+            .iter()
+            .position(|c| c.is_concealed() && c.frontier().any(|(x, y)| map.is_walkable(x, y)));
+        let Some(i) = breached else {
+            break;
+        };
+        let cavern = &mut caverns.caverns[i];
+        cavern.revealed = Some(tick);
+        let changes: Vec<TileChange> = cavern
+            .tiles
+            .iter()
+            .zip(&cavern.kinds)
+            .map(|(&pos, &kind)| TileChange { pos, kind })
+            .collect();
+        map.apply(&changes);
+        for &((x, y), species) in &cavern.fauna {
+            commands.spawn((
+                Animal,
+                species,
+                Position(x, y),
+                Velocity(0, 0),
+                Health::full(8),
+                Name(format!("Cave {}", species.label())),
+            ));
+        }
+        if let Some(announcements) = announcements.as_deref_mut() {
+            let (x, y) = cavern.tiles[0];
+            announcements.push(
+                tick,
+                Severity::Warning,
+                format!("Mining has broken into a hidden cavern at ({}, {})!", x, y),
+            );
+        }
+    }
+    caverns.checked = Some(map.revision());
+}
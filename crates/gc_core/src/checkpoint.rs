@@ -0,0 +1,439 @@
+//! In-memory checkpoints for branching the simulation
+//!
+//! [`checkpoint`] clones every registered component and resource into a
+//! [`Snapshot`]; [`restore`] puts the world back exactly as it was, as
+//! often as needed. Nothing is encoded, so both are far cheaper than a
+//! save, which makes them suitable for search-based AI and "what if" tools
+//! that try several futures from one state.
+//!
+//! Restoring keeps entity ids (generations included), the order entities
+//! are stored and iterated in, and the allocator's free slots, so the same
+//! schedule run on a restored world makes the same moves. Change detection
+//! cannot be rewound, though: after a restore every component and resource
+//! reads as newly added. So that the run that carries on after a checkpoint
+//! is the same branch as every later restore, taking a checkpoint restores
+//! the world from it in place.
+//!
+//! Only the types in the registry below are captured. Entities holding any
+//! other component cannot be checkpointed ([`CheckpointError`]); other
+//! resources (front-end settings, benchmark state) are left untouched by a
+//! restore. State kept in systems' `Local`s is not captured either, which
+//! is why simulation systems keep their between-tick state in resources.
+
+use crate::announcements::{Announcements, StaffingConfig, UnstaffedJobs};
+use crate::burial::{BurialConfig, Buried, Corpse, Ghost, Grave};
+use crate::burrowing::{BurrowConfig, Burrower};
+use crate::caverns::Caverns;
+use crate::command::CommandLog;
+use crate::components::*;
+use crate::construction::{Construction, MaterialRequirement, Workshop};
+use crate::danger::{DangerConfig, DangerMap};
+use crate::designations::{
+    DesignationConfig, DesignationEvents, DesignationJob, DesignationPriority, DesignationStatus,
+    HazardConfirmed, MineDesignation,
+};
+use crate::director::{DirectorConfig, EventDirector, RegionDifficulty};
+use crate::equipment::{Equipment, EquippedBy, Gear, Squad, SquadUniforms};
+use crate::faults::SimFaults;
+use crate::fire::{FireConfig, FireMap, WaterBucket};
+use crate::forbid::{ForbidConfig, Forbidden};
+use crate::fov::{Explored, Visibility};
+use crate::haul_priority::HaulPriorityConfig;
+use crate::health::{Bed, Resting, Stalled};
+use crate::history::EntityHistory;
+use crate::jobs::{
+    ActiveJobs, IssuedBy, ItemSpawnQueue, JobBoard, JobGcConfig, JobThrottle, ReassignPolicy,
+    ReassignmentEvents, ThrottleState,
+};
+use crate::lighting::{DayCycle, LightMap, LightSource, LightingConfig};
+use crate::livestock::{Livestock, LivestockConfig, OwnedBy, Species};
+use crate::manager::ManagerOrders;
+use crate::mechanisms::{
+    Bridge, Door, Lever, MechanismConfig, MechanismLinks, MechanismQueue, PressurePlate, Trap,
+};
+use crate::metrics::RunMetrics;
+use crate::overmap::AmbientTemperature;
+use crate::path::{Passability, PathQueue, PathReady, PathService, PathfindingConfig};
+use crate::production::{FuelConfig, RecipeBook, Station};
+use crate::quality::{CraftingSkill, Quality};
+use crate::render::RenderGlyph;
+use crate::stockpiles::{FillLimit, GiveTo, StockpileContents};
+use crate::systems::{DeterministicRng, MovementConfig, Time};
+use crate::tags::{AcceptsTags, TagRegistry};
+use crate::telemetry::{EntityTelemetry, TelemetryConfig};
+use crate::temperature::{Spoilage, TemperatureConfig, TemperatureMap};
+use crate::toggles::SystemToggles;
+use crate::traffic::{TrafficConfig, TrafficMap};
+use crate::tutorial::TutorialProgress;
+use crate::vision::{Fog, VisionConfig};
+use crate::work_area::{WorkArea, WorkAreaConfig};
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, Velocity};
+use crate::yields::YieldTable;
+use crate::zones::ZoneKind;
+use crate::ActionLog;
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::{EntityRef, EntityWorldMut};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Components a checkpoint captures
+const COMPONENTS: &[ComponentEntry] = &[
+    // components
+    ComponentEntry::of::<Goblin>(),
+    ComponentEntry::of::<Tree>(),
+    ComponentEntry::of::<Animal>(),
+    ComponentEntry::of::<JobQueue>(),
+    ComponentEntry::of::<Carrier>(),
+    ComponentEntry::of::<Miner>(),
+    ComponentEntry::of::<MiningSkill>(),
+    ComponentEntry::of::<AssignedJob>(),
+    ComponentEntry::of::<VisionRadius>(),
+    ComponentEntry::of::<DesignationLifecycle>(),
+    ComponentEntry::of::<HazardConfirmed>(),
+    ComponentEntry::of::<DesignationPriority>(),
+    ComponentEntry::of::<Item>(),
+    ComponentEntry::of::<Carriable>(),
+    ComponentEntry::of::<Stone>(),
+    ComponentEntry::of::<Inventory>(),
+    ComponentEntry::of::<CarriedBy>(),
+    ComponentEntry::of::<HaulPhase>(),
+    ComponentEntry::of::<MovementOverride>(),
+    ComponentEntry::of::<ZoneBounds>(),
+    ComponentEntry::of::<Stockpile>(),
+    ComponentEntry::of::<Faction>(),
+    ComponentEntry::of::<Health>(),
+    ComponentEntry::of::<CombatStats>(),
+    ComponentEntry::of::<AttackCooldown>(),
+    ComponentEntry::of::<Combatant>(),
+    ComponentEntry::of::<Dead>(),
+    ComponentEntry::of::<Mood>(),
+    ComponentEntry::of::<Target>(),
+    // world
+    ComponentEntry::of::<Position>(),
+    ComponentEntry::of::<Velocity>(),
+    ComponentEntry::of::<Name>(),
+    ComponentEntry::of::<Material>(),
+    // everything else, by module
+    ComponentEntry::of::<Grave>(),
+    ComponentEntry::of::<Corpse>(),
+    ComponentEntry::of::<Buried>(),
+    ComponentEntry::of::<Ghost>(),
+    ComponentEntry::of::<Construction>(),
+    ComponentEntry::of::<MaterialRequirement>(),
+    ComponentEntry::of::<Workshop>(),
+    ComponentEntry::of::<MineDesignation>(),
+    ComponentEntry::of::<DesignationJob>(),
+    ComponentEntry::of::<DesignationStatus>(),
+    ComponentEntry::of::<Gear>(),
+    ComponentEntry::of::<Equipment>(),
+    ComponentEntry::of::<EquippedBy>(),
+    ComponentEntry::of::<Squad>(),
+    ComponentEntry::of::<WaterBucket>(),
+    ComponentEntry::of::<Stalled>(),
+    ComponentEntry::of::<Resting>(),
+    ComponentEntry::of::<Bed>(),
+    ComponentEntry::of::<IssuedBy>(),
+    ComponentEntry::of::<LightSource>(),
+    ComponentEntry::of::<Species>(),
+    ComponentEntry::of::<OwnedBy>(),
+    ComponentEntry::of::<Livestock>(),
+    ComponentEntry::of::<Lever>(),
+    ComponentEntry::of::<PressurePlate>(),
+    ComponentEntry::of::<MechanismLinks>(),
+    ComponentEntry::of::<Door>(),
+    ComponentEntry::of::<Bridge>(),
+    ComponentEntry::of::<Trap>(),
+    ComponentEntry::of::<PathReady>(),
+    ComponentEntry::of::<Station>(),
+    ComponentEntry::of::<CraftingSkill>(),
+    ComponentEntry::of::<Quality>(),
+    ComponentEntry::of::<RenderGlyph>(),
+    ComponentEntry::of::<AcceptsTags>(),
+    ComponentEntry::of::<FillLimit>(),
+    ComponentEntry::of::<GiveTo>(),
+    ComponentEntry::of::<ZoneKind>(),
+    ComponentEntry::of::<Spoilage>(),
+    ComponentEntry::of::<WorkArea>(),
+    ComponentEntry::of::<Forbidden>(),
+    ComponentEntry::of::<Passability>(),
+    ComponentEntry::of::<Burrower>(),
+];
+
+/// Resources a checkpoint captures; one missing from the checkpointed world
+/// is removed on restore
+const RESOURCES: &[ResourceEntry] = &[
+    ResourceEntry::of::<GameMap>(),
+    ResourceEntry::of::<MaterialMap>(),
+    ResourceEntry::of::<Time>(),
+    ResourceEntry::of::<DeterministicRng>(),
+    ResourceEntry::of::<MovementConfig>(),
+    ResourceEntry::of::<ActionLog>(),
+    ResourceEntry::of::<JobBoard>(),
+    ResourceEntry::of::<ActiveJobs>(),
+    ResourceEntry::of::<ItemSpawnQueue>(),
+    ResourceEntry::of::<ReassignPolicy>(),
+    ResourceEntry::of::<ReassignmentEvents>(),
+    ResourceEntry::of::<JobGcConfig>(),
+    ResourceEntry::of::<JobThrottle>(),
+    ResourceEntry::of::<ThrottleState>(),
+    ResourceEntry::of::<DesignationConfig>(),
+    ResourceEntry::of::<DesignationEvents>(),
+    ResourceEntry::of::<Announcements>(),
+    ResourceEntry::of::<StaffingConfig>(),
+    ResourceEntry::of::<UnstaffedJobs>(),
+    ResourceEntry::of::<BurialConfig>(),
+    ResourceEntry::of::<Caverns>(),
+    ResourceEntry::of::<DangerConfig>(),
+    ResourceEntry::of::<DangerMap>(),
+    ResourceEntry::of::<SquadUniforms>(),
+    ResourceEntry::of::<SimFaults>(),
+    ResourceEntry::of::<FireConfig>(),
+    ResourceEntry::of::<FireMap>(),
+    ResourceEntry::of::<Visibility>(),
+    ResourceEntry::of::<Explored>(),
+    ResourceEntry::of::<DayCycle>(),
+    ResourceEntry::of::<LightingConfig>(),
+    ResourceEntry::of::<LightMap>(),
+    ResourceEntry::of::<LivestockConfig>(),
+    ResourceEntry::of::<ManagerOrders>(),
+    ResourceEntry::of::<MechanismConfig>(),
+    ResourceEntry::of::<MechanismQueue>(),
+    ResourceEntry::of::<AmbientTemperature>(),
+    ResourceEntry::of::<PathService>(),
+    ResourceEntry::of::<PathfindingConfig>(),
+    ResourceEntry::of::<PathQueue>(),
+    ResourceEntry::of::<RecipeBook>(),
+    ResourceEntry::of::<FuelConfig>(),
+    ResourceEntry::of::<StockpileContents>(),
+    ResourceEntry::of::<TagRegistry>(),
+    ResourceEntry::of::<TrafficConfig>(),
+    ResourceEntry::of::<TrafficMap>(),
+    ResourceEntry::of::<SystemToggles>(),
+    ResourceEntry::of::<HaulPriorityConfig>(),
+    ResourceEntry::of::<YieldTable>(),
+    ResourceEntry::of::<TutorialProgress>(),
+    ResourceEntry::of::<EntityTelemetry>(),
+    ResourceEntry::of::<TelemetryConfig>(),
+    ResourceEntry::of::<RunMetrics>(),
+    ResourceEntry::of::<TemperatureConfig>(),
+    ResourceEntry::of::<TemperatureMap>(),
+    ResourceEntry::of::<EntityHistory>(),
+    ResourceEntry::of::<WorkAreaConfig>(),
+    ResourceEntry::of::<ForbidConfig>(),
+    ResourceEntry::of::<BurrowConfig>(),
+    ResourceEntry::of::<DirectorConfig>(),
+    ResourceEntry::of::<EventDirector>(),
+    ResourceEntry::of::<RegionDifficulty>(),
+    ResourceEntry::of::<CommandLog>(),
+    ResourceEntry::of::<VisionConfig>(),
+    ResourceEntry::of::<Fog>(),
+];
+
+/// A world that cannot be checkpointed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CheckpointError {
+    #[error("entity {entity:?} has component {component}, which checkpoints do not capture")]
+    Unregistered { entity: Entity, component: String },
+}
+
+/// The captured state of a world, restorable any number of times
+pub struct Snapshot {
+    /// Entities in storage order, with their components
+    entities: Vec<(Entity, Vec<Box<dyn HeldComponent>>)>,
+    /// Every slot the entity allocator had handed out, live or free, as
+    /// last used
+    slots: Vec<Entity>,
+    resources: Vec<Box<dyn HeldResource>>,
+}
+
+impl Snapshot {
+    /// Live entities captured
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("entities", &self.entities.len())
+            .field("slots", &self.slots.len())
+            .field("resources", &self.resources.len())
+            .finish()
+    }
+}
+
+/// Capture `world`, then restore it from the capture (see the module docs)
+pub fn checkpoint(world: &mut World) -> Result<Snapshot, CheckpointError> {
+    world.flush();
+    let snapshot = capture(world)?;
+    restore(&snapshot, world);
+    Ok(snapshot)
+}
+
+/// Put `world` back as it was when `snapshot` was taken
+///
+/// Every entity is replaced and every registered resource reset; events
+/// of removed components are dropped. Runs are only guaranteed to repeat
+/// on the world the snapshot was taken from, whose storage layout they
+/// share.
+pub fn restore(snapshot: &Snapshot, world: &mut World) {
+    world.clear_entities();
+
+    // Hand out every slot in index order so none is taken from the free
+    // list, then free the ones that were free, bumping them back to their
+    // generations. Never-used free slots are on the free list already.
+    let live: HashSet<Entity> = snapshot.entities.iter().map(|(e, _)| *e).collect();
+    let mut freed = Vec::new();
+    for &slot in &snapshot.slots {
+        if live.contains(&slot) {
+            world.get_or_spawn(slot).expect("slot is free");
+        } else if slot.generation() > 1 {
+            let last_used = Entity::from_bits(
+                (u64::from(slot.generation() - 1) << 32) | u64::from(slot.index()),
+            );
+            world.get_or_spawn(last_used).expect("slot is free");
+            freed.push(last_used);
+        }
+    }
+    for &entity in freed.iter().rev() {
+        world.despawn(entity);
+    }
+
+    for (entity, components) in &snapshot.entities {
+        let mut entity = world.entity_mut(*entity);
+        for component in components {
+            component.insert(&mut entity);
+        }
+    }
+    for resource in &snapshot.resources {
+        resource.insert(world);
+    }
+    // Removal events are double-buffered
+    world.clear_trackers();
+    world.clear_trackers();
+}
+
+fn capture(world: &World) -> Result<Snapshot, CheckpointError> {
+    let registered: BTreeMap<ComponentId, &ComponentEntry> = COMPONENTS
+        .iter()
+        .filter_map(|entry| Some(((entry.id)(world)?, entry)))
+        .collect();
+    let mut stored = Vec::new();
+    for archetype in world.archetypes().iter() {
+        if archetype.is_empty() {
+            continue;
+        }
+        let mut entries = Vec::new();
+        for id in archetype.components() {
+            match registered.get(&id) {
+                Some(entry) => entries.push(*entry),
+                None => {
+                    return Err(CheckpointError::Unregistered {
+                        entity: archetype.entities()[0].id(),
+                        component: world
+                            .components()
+                            .get_info(id)
+                            .map_or_else(|| format!("{id:?}"), |info| info.name().to_string()),
+                    })
+                }
+            }
+        }
+        for entity in archetype.entities() {
+            let key = (
+                archetype.table_id().as_usize(),
+                entity.table_row().as_usize(),
+            );
+            stored.push((key, entity.id(), entries.clone()));
+        }
+    }
+    stored.sort_by_key(|(key, ..)| *key);
+    let entities = stored
+        .into_iter()
+        .map(|(_, entity, entries)| {
+            let entity_ref = world.entity(entity);
+            let components = entries
+                .iter()
+                .map(|entry| (entry.capture)(&entity_ref))
+                .collect();
+            (entity, components)
+        })
+        .collect();
+    let slots = (0..world.entities().total_count() as u32)
+        .filter_map(|index| world.entities().resolve_from_id(index))
+        .collect();
+    let resources = RESOURCES
+        .iter()
+        .map(|entry| (entry.capture)(world))
+        .collect();
+    Ok(Snapshot {
+        entities,
+        slots,
+        resources,
+    })
+}
+
+/// How to find and clone one component type
+struct ComponentEntry {
+    id: fn(&World) -> Option<ComponentId>,
+    capture: fn(&EntityRef) -> Box<dyn HeldComponent>,
+}
+
+impl ComponentEntry {
+    const fn of<T: Component + Clone>() -> Self {
+        Self {
+            id: |world| world.component_id::<T>(),
+            capture: |entity| Box::new(Held(entity.get::<T>().expect("in archetype").clone())),
+        }
+    }
+}
+
+/// How to clone one resource type
+struct ResourceEntry {
+    capture: fn(&World) -> Box<dyn HeldResource>,
+}
+
+impl ResourceEntry {
+    const fn of<T: Resource + Clone>() -> Self {
+        Self {
+            capture: |world| match world.get_resource::<T>() {
+                Some(resource) => Box::new(Held(resource.clone())),
+                None => Box::new(Absent::<T>(PhantomData)),
+            },
+        }
+    }
+}
+
+trait HeldComponent: Send + Sync {
+    fn insert(&self, entity: &mut EntityWorldMut);
+}
+
+trait HeldResource: Send + Sync {
+    fn insert(&self, world: &mut World);
+}
+
+/// A captured component or resource
+struct Held<T>(T);
+
+/// A registered resource the world did not have
+struct Absent<T>(PhantomData<fn() -> T>);
+
+impl<T: Component + Clone> HeldComponent for Held<T> {
+    fn insert(&self, entity: &mut EntityWorldMut) {
+        entity.insert(self.0.clone());
+    }
+}
+
+impl<T: Resource + Clone> HeldResource for Held<T> {
+    fn insert(&self, world: &mut World) {
+        world.insert_resource(self.0.clone());
+    }
+}
+
+impl<T: Resource> HeldResource for Absent<T> {
+    fn insert(&self, world: &mut World) {
+        world.remove_resource::<T>();
+    }
+}
@@ -0,0 +1,169 @@
+//! Colony summary: one row per living goblin, for overview screens
+//!
+//! [`colony_summary`] lists the colony the way the director counts its
+//! population: living workers of the goblin faction (see [`faction_of`]),
+//! plus anything marked [`Goblin`]. Each [`GoblinSummary`] carries the
+//! job being worked, mood, health and location, and [`sort_summary`] orders
+//! rows by any [`SummaryColumn`].
+//!
+//! Hunger and fatigue are part of every row but stay `None` until needs
+//! are tracked per goblin; rows without a value sort last in either
+//! direction, so those columns are already usable once they fill in.
+
+use crate::components::{AssignedJob, Dead, Faction, FactionKind, Goblin, Health, Mood};
+use crate::jobs::{faction_of, ActiveJobs};
+use crate::world::{Name, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// What an overview shows of one goblin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoblinSummary {
+    /// Entity id bits, as in [`EntityView::id`](crate::view::EntityView::id)
+    pub id: u64,
+    /// Name, or `#<id>` for the unnamed
+    pub name: String,
+    /// Label of the job being worked (see [`JobKind::label`](crate::jobs::JobKind::label))
+    pub job: Option<String>,
+    /// Hunger, 0 (fed) to 1000 (starving), once needs are tracked
+    pub hunger: Option<u16>,
+    /// Fatigue, 0 (rested) to 1000 (exhausted), once needs are tracked
+    pub fatigue: Option<u16>,
+    /// Current [`Mood`]
+    pub mood: Option<i16>,
+    /// Hit points and their maximum
+    pub health: Option<(i32, i32)>,
+    /// Where the goblin stands, if anywhere
+    pub pos: Option<(i32, i32)>,
+}
+
+impl GoblinSummary {
+    /// Health as a percentage of the maximum
+    pub fn health_percent(&self) -> Option<i32> {
+        self.health
+            .map(|(hp, max)| if max > 0 { hp * 100 / max } else { 0 })
+    }
+}
+
+/// A column of the colony overview
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryColumn {
+    #[default]
+    Name,
+    Job,
+    Hunger,
+    Fatigue,
+    Mood,
+    Health,
+    Location,
+}
+
+impl SummaryColumn {
+    /// All columns in display order
+    pub const ALL: [SummaryColumn; 7] = [
+        SummaryColumn::Name,
+        SummaryColumn::Job,
+        SummaryColumn::Hunger,
+        SummaryColumn::Fatigue,
+        SummaryColumn::Mood,
+        SummaryColumn::Health,
+        SummaryColumn::Location,
+    ];
+
+    /// Column heading
+    pub fn label(self) -> &'static str {
+        match self {
+            SummaryColumn::Name => "name",
+            SummaryColumn::Job => "job",
+            SummaryColumn::Hunger => "hunger",
+            SummaryColumn::Fatigue => "fatigue",
+            SummaryColumn::Mood => "mood",
+            SummaryColumn::Health => "health",
+            SummaryColumn::Location => "location",
+        }
+    }
+
+    /// The column `step` places to the right, wrapping around
+    pub fn shift(self, step: i32) -> Self {
+        let n = Self::ALL.len() as i32;
+        let at = Self::ALL.iter().position(|&c| c == self).unwrap_or(0) as i32;
+        Self::ALL[(at + step).rem_euclid(n) as usize]
+    }
+
+    /// Compare two rows by this column; rows missing the value come last
+    fn compare(self, a: &GoblinSummary, b: &GoblinSummary, descending: bool) -> Ordering {
+        fn by<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) if descending => b.cmp(&a),
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match self {
+            SummaryColumn::Name => by(Some(&a.name), Some(&b.name), descending),
+            SummaryColumn::Job => by(a.job.as_ref(), b.job.as_ref(), descending),
+            SummaryColumn::Hunger => by(a.hunger, b.hunger, descending),
+            SummaryColumn::Fatigue => by(a.fatigue, b.fatigue, descending),
+            SummaryColumn::Mood => by(a.mood, b.mood, descending),
+            SummaryColumn::Health => by(a.health_percent(), b.health_percent(), descending),
+            // Row-major, like the map
+            SummaryColumn::Location => by(
+                a.pos.map(|(x, y)| (y, x)),
+                b.pos.map(|(x, y)| (y, x)),
+                descending,
+            ),
+        }
+    }
+}
+
+/// Every living goblin, sorted by name (ties by id)
+pub fn colony_summary(world: &mut World) -> Vec<GoblinSummary> {
+    let mut q = world.query_filtered::<(
+        Entity,
+        Option<&Faction>,
+        Option<&Name>,
+        Option<&AssignedJob>,
+        Option<&Mood>,
+        Option<&Health>,
+        Option<&Position>,
+    ), (Or<(With<AssignedJob>, With<Goblin>)>, Without<Dead>)>();
+    let jobs = world.get_resource::<ActiveJobs>();
+    let mut rows: Vec<GoblinSummary> = q
+        .iter(world)
+        .filter(|(_, faction, ..)| faction_of(*faction) == FactionKind::Goblins)
+        .map(
+            |(entity, _, name, assigned, mood, health, pos)| GoblinSummary {
+                id: entity.to_bits(),
+                name: name.map_or_else(|| format!("#{}", entity.to_bits()), |n| n.0.clone()),
+                job: assigned
+                    .and_then(|a| a.0)
+                    .and_then(|id| jobs?.jobs.get(&id))
+                    .map(|job| job.kind.label().to_string()),
+                hunger: None,
+                fatigue: None,
+                mood: mood.map(|m| m.0),
+                health: health.map(|h| (h.hp, h.max_hp)),
+                pos: pos.map(|p| (p.0, p.1)),
+            },
+        )
+        .collect();
+    sort_summary(&mut rows, SummaryColumn::Name, false);
+    rows
+}
+
+/// Order rows by `column`, ascending or descending
+///
+/// Rows missing the value come last either way; ties keep name order, then
+/// id order, so the result does not depend on the order rows came in.
+pub fn sort_summary(rows: &mut [GoblinSummary], column: SummaryColumn, descending: bool) {
+    rows.sort_by(|a, b| {
+        column
+            .compare(a, b, descending)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
@@ -0,0 +1,87 @@
+//! Attack resolution
+//!
+//! A single attack rolls to hit against [`CombatStats::hit_chance`] (the
+//! attacker's accuracy against the defender's evasion) and, on
+//! a hit, rolls damage between `dmg_min` and `dmg_max` plus the attacker's
+//! attack minus the defender's defense (at least 1). Both sides use their
+//! [`effective_stats`], so equipped weapons and armor count. Rolls come from
+//! the `combat_rng` stream. Both sides get the attack in their
+//! [`EntityHistory`].
+
+use crate::components::{CombatStats, Dead, Health};
+use crate::determinism::RngStream;
+use crate::equipment::effective_stats;
+use crate::history::{EntityHistory, HistoryEvent};
+use crate::systems::{DeterministicRng, Time};
+use crate::toggles::{is_enabled, Subsystem};
+use bevy_ecs::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Result of one attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttackOutcome {
+    Miss,
+    Hit { damage: i32 },
+}
+
+/// Resolve one attack of `attacker` on `defender` and apply the damage
+///
+/// Returns None when either side lacks `CombatStats`, the defender has no
+/// `Health`, either side is dead, or [`Subsystem::Combat`] is switched off.
+pub fn resolve_attack(
+    world: &mut World,
+    attacker: Entity,
+    defender: Entity,
+) -> Option<AttackOutcome> {
+    if !is_enabled(world, Subsystem::Combat) {
+        return None;
+    }
+    if world.get::<Dead>(attacker).is_some() || world.get::<Dead>(defender).is_some() {
+        return None;
+    }
+    world.get::<Health>(defender)?;
+    let atk = effective_stats(world, attacker)?;
+    let def = effective_stats(world, defender)?;
+
+    // Hit chance pits the attacker's accuracy against the defender's evasion
+    let chance = CombatStats {
+        evasion: def.evasion,
+        ..atk.clone()
+    }
+    .hit_chance();
+    let outcome = {
+        let mut rng = world.resource_mut::<DeterministicRng>();
+        if rng
+            .stream(RngStream::Combat, "resolve_attack")
+            .gen_range(0..100)
+            >= chance
+        {
+            AttackOutcome::Miss
+        } else {
+            let roll = rng
+                .stream(RngStream::Combat, "resolve_attack")
+                .gen_range(atk.dmg_min..=atk.dmg_max);
+            AttackOutcome::Hit {
+                damage: (roll + atk.attack - def.defense).max(1),
+            }
+        }
+    };
+    if let AttackOutcome::Hit { damage } = outcome {
+        world.get_mut::<Health>(defender)?.take_damage(damage);
+    }
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    if let Some(mut history) = world.get_resource_mut::<EntityHistory>() {
+        let attacked = HistoryEvent::Attacked {
+            target: defender,
+            outcome,
+        };
+        history.record(attacker, tick, attacked);
+        history.record(
+            defender,
+            tick,
+            HistoryEvent::AttackedBy { attacker, outcome },
+        );
+    }
+    Some(outcome)
+}
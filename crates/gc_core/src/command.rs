@@ -0,0 +1,372 @@
+//! Serializable world commands applied at tick boundaries
+//!
+//! Shells (TUI, network server, lockstep peers) never mutate the `World`
+//! directly while the simulation is running. Instead they submit
+//! [`WorldCommand`]s which the owner of the world applies between schedule
+//! runs, in submission order, keeping the simulation deterministic for a given
+//! command sequence.
+//!
+//! Every command is applied on behalf of an [`IssuedBy`]: designations it
+//! places carry the issuer through to their jobs and events, and it may only
+//! cancel its own (or system) designations. Zone commands ([`WorldCommand::Zone`]
+//! and friends) are not owned: any issuer may edit or remove any zone.
+//!
+//! Worlds with a [`CommandLog`] keep every command applied to them, with its
+//! tick and issuer, so a bug report can replay a session from its seed.
+
+use crate::components::{Stockpile, ZoneBounds};
+use crate::designations::{
+    cancel_designation, designate_mine_rect, may_cancel, DesignationBundle, DesignationJob,
+    DesignationPriority, HazardConfirmed, MineDesignation,
+};
+use crate::forbid::{forbid_items, unforbid_items};
+use crate::jobs::{IssuedBy, JobBoard};
+use crate::stockpiles::{FillLimit, GiveTo, StockpileBundle};
+use crate::systems::Time;
+use crate::tags::{AcceptsTags, TagQuery, TagRegistry};
+use crate::world::{GameMap, Name, Position};
+use crate::zones::{ZoneBundle, ZoneKind};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A player intent that mutates simulation state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorldCommand {
+    /// Place a mining designation at (x, y)
+    Designate { x: i32, y: i32 },
+    /// Designate every tile between two corners (inclusive, in any order)
+    DesignateRect { a: (i32, i32), b: (i32, i32) },
+    /// Withdraw mining designations at (x, y) and their jobs
+    CancelDesignation { x: i32, y: i32 },
+    /// Let the mining designations between two corners (inclusive, in any
+    /// order) be dug even though they border water or lava
+    ConfirmDesignations { a: (i32, i32), b: (i32, i32) },
+    /// Set the priority of the mining designations between two corners
+    /// (inclusive, in any order), and of any jobs they already posted
+    PrioritizeDesignations {
+        a: (i32, i32),
+        b: (i32, i32),
+        priority: u32,
+    },
+    /// Mark out a zone between two corners (inclusive, in any order)
+    ///
+    /// `accepts` filters a stockpile by tags and is ignored for other kinds.
+    Zone {
+        kind: ZoneKind,
+        a: (i32, i32),
+        b: (i32, i32),
+        #[serde(default)]
+        accepts: Option<TagQuery>,
+    },
+    /// Change the kind and filter of every zone covering (x, y)
+    EditZone {
+        x: i32,
+        y: i32,
+        kind: ZoneKind,
+        #[serde(default)]
+        accepts: Option<TagQuery>,
+    },
+    /// Remove every zone covering (x, y)
+    RemoveZone { x: i32, y: i32 },
+    /// Set or clear (`None`) the fill limit of every stockpile covering (x, y)
+    SetFillLimit {
+        x: i32,
+        y: i32,
+        #[serde(default)]
+        limit: Option<FillLimit>,
+    },
+    /// Make the stockpiles covering `to` take the overflow of those
+    /// covering `from`, after any targets they already have
+    LinkStockpiles { from: (i32, i32), to: (i32, i32) },
+    /// Drop every overflow target of the stockpiles covering (x, y)
+    UnlinkStockpiles { x: i32, y: i32 },
+    /// Forbid the items between two corners (inclusive, in any order)
+    ForbidItems { a: (i32, i32), b: (i32, i32) },
+    /// Lift every forbid on the items between two corners (inclusive, in
+    /// any order)
+    UnforbidItems { a: (i32, i32), b: (i32, i32) },
+}
+
+/// Apply a command to the world on behalf of the local player
+///
+/// Returns false when the command was rejected (e.g. target out of bounds).
+/// Must only be called between schedule runs.
+pub fn apply_world_command(world: &mut World, command: &WorldCommand) -> bool {
+    apply_world_command_as(world, command, IssuedBy::LOCAL_PLAYER)
+}
+
+/// A command as it was applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggedCommand {
+    /// Tick the command was applied before
+    pub tick: u64,
+    pub issuer: IssuedBy,
+    pub command: WorldCommand,
+}
+
+/// Every command applied to the world, oldest first
+///
+/// Rejected commands are kept too: replaying them rejects them again.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandLog(pub Vec<LoggedCommand>);
+
+impl CommandLog {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// [`apply_world_command`] on behalf of `issuer`
+pub fn apply_world_command_as(world: &mut World, command: &WorldCommand, issuer: IssuedBy) -> bool {
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    if let Some(mut log) = world.get_resource_mut::<CommandLog>() {
+        log.0.push(LoggedCommand {
+            tick,
+            issuer,
+            command: command.clone(),
+        });
+    }
+    match *command {
+        WorldCommand::Designate { x, y } => {
+            let in_bounds = world
+                .get_resource::<GameMap>()
+                .map_or(true, |m| m.in_bounds(x, y));
+            if !in_bounds {
+                return false;
+            }
+            world.spawn((
+                DesignationBundle {
+                    pos: Position(x, y),
+                    ..Default::default()
+                },
+                issuer,
+            ));
+            true
+        }
+        WorldCommand::DesignateRect { a, b } => {
+            let bounds = ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            let spawned = designate_mine_rect(world, &bounds).spawned;
+            for &designation in &spawned {
+                world.entity_mut(designation).insert(issuer);
+            }
+            !spawned.is_empty()
+        }
+        WorldCommand::CancelDesignation { x, y } => {
+            let mut q = world
+                .query_filtered::<(Entity, &Position, Option<&IssuedBy>), With<MineDesignation>>();
+            let doomed: Vec<Entity> = q
+                .iter(world)
+                .filter(|(_, p, by)| {
+                    (p.0, p.1) == (x, y) && may_cancel(issuer, by.copied().unwrap_or_default())
+                })
+                .map(|(e, _, _)| e)
+                .collect();
+            for &designation in &doomed {
+                cancel_designation(world, designation);
+            }
+            !doomed.is_empty()
+        }
+        WorldCommand::ConfirmDesignations { a, b } => {
+            let bounds = ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            let mut q = world.query_filtered::<(Entity, &Position, Option<&IssuedBy>), (
+                With<MineDesignation>,
+                Without<HazardConfirmed>,
+            )>();
+            let confirmed: Vec<Entity> = q
+                .iter(world)
+                .filter(|(_, p, by)| {
+                    bounds.contains(p.0, p.1) && may_cancel(issuer, by.copied().unwrap_or_default())
+                })
+                .map(|(e, _, _)| e)
+                .collect();
+            for &designation in &confirmed {
+                world.entity_mut(designation).insert(HazardConfirmed);
+            }
+            !confirmed.is_empty()
+        }
+        WorldCommand::PrioritizeDesignations { a, b, priority } => {
+            let bounds = ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            let mut q = world.query_filtered::<(
+                Entity,
+                &Position,
+                Option<&IssuedBy>,
+                Option<&DesignationJob>,
+            ), With<MineDesignation>>();
+            let marked: Vec<(Entity, Option<DesignationJob>)> = q
+                .iter(world)
+                .filter(|(_, p, by, _)| {
+                    bounds.contains(p.0, p.1) && may_cancel(issuer, by.copied().unwrap_or_default())
+                })
+                .map(|(e, _, _, job)| (e, job.copied()))
+                .collect();
+            for &(designation, _) in &marked {
+                world
+                    .entity_mut(designation)
+                    .insert(DesignationPriority(priority));
+            }
+            // Jobs still waiting on the board take the new priority too
+            if let Some(mut board) = world.get_resource_mut::<JobBoard>() {
+                for posted in marked.iter().filter_map(|(_, job)| job.as_ref()) {
+                    if let Some(job) = board.0.iter_mut().find(|j| j.id == posted.job) {
+                        job.priority = priority;
+                    }
+                }
+            }
+            !marked.is_empty()
+        }
+        WorldCommand::Zone {
+            kind,
+            a,
+            b,
+            ref accepts,
+        } => {
+            let in_bounds = world
+                .get_resource::<GameMap>()
+                .map_or(true, |m| m.in_bounds(a.0, a.1) && m.in_bounds(b.0, b.1));
+            if !in_bounds || !known_tags(world, accepts.as_ref()) {
+                return false;
+            }
+            let (min_x, min_y) = (a.0.min(b.0), a.1.min(b.1));
+            let (max_x, max_y) = (a.0.max(b.0), a.1.max(b.1));
+            let zone = if kind == ZoneKind::Stockpile {
+                world
+                    .spawn(StockpileBundle::new(min_x, min_y, max_x, max_y))
+                    .insert(Name("Stockpile".into()))
+                    .id()
+            } else {
+                world
+                    .spawn(ZoneBundle::new(kind, min_x, min_y, max_x, max_y))
+                    .id()
+            };
+            set_zone(world, zone, kind, accepts.as_ref());
+            true
+        }
+        WorldCommand::EditZone {
+            x,
+            y,
+            kind,
+            ref accepts,
+        } => {
+            if !known_tags(world, accepts.as_ref()) {
+                return false;
+            }
+            let zones = zones_at(world, x, y);
+            for &zone in &zones {
+                set_zone(world, zone, kind, accepts.as_ref());
+            }
+            !zones.is_empty()
+        }
+        WorldCommand::RemoveZone { x, y } => {
+            let zones = zones_at(world, x, y);
+            for &zone in &zones {
+                world.despawn(zone);
+            }
+            !zones.is_empty()
+        }
+        WorldCommand::SetFillLimit { x, y, limit } => {
+            let stockpiles = stockpiles_at(world, x, y);
+            for &stockpile in &stockpiles {
+                let mut entity = world.entity_mut(stockpile);
+                match limit {
+                    Some(limit) => entity.insert(limit),
+                    None => entity.remove::<FillLimit>(),
+                };
+            }
+            !stockpiles.is_empty()
+        }
+        WorldCommand::LinkStockpiles { from, to } => {
+            let givers = stockpiles_at(world, from.0, from.1);
+            let takers = stockpiles_at(world, to.0, to.1);
+            let mut linked = false;
+            for &giver in &givers {
+                let mut entity = world.entity_mut(giver);
+                let mut targets = entity.take::<GiveTo>().unwrap_or_default();
+                for &taker in &takers {
+                    if taker != giver && !targets.0.contains(&taker) {
+                        targets.0.push(taker);
+                        linked = true;
+                    }
+                }
+                entity.insert(targets);
+            }
+            linked
+        }
+        WorldCommand::UnlinkStockpiles { x, y } => {
+            let stockpiles = stockpiles_at(world, x, y);
+            for &stockpile in &stockpiles {
+                world.entity_mut(stockpile).remove::<GiveTo>();
+            }
+            !stockpiles.is_empty()
+        }
+        WorldCommand::ForbidItems { a, b } => {
+            let bounds = ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            !forbid_items(world, &bounds).is_empty()
+        }
+        WorldCommand::UnforbidItems { a, b } => {
+            let bounds = ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            !unforbid_items(world, &bounds).is_empty()
+        }
+    }
+}
+
+/// Whether every tag `accepts` names is in the world's [`TagRegistry`]
+fn known_tags(world: &World, accepts: Option<&TagQuery>) -> bool {
+    let Some(query) = accepts else {
+        return true;
+    };
+    match world.get_resource::<TagRegistry>() {
+        Some(registry) => registry.validate(query).is_ok(),
+        None => TagRegistry::default().validate(query).is_ok(),
+    }
+}
+
+/// Zones whose bounds cover (x, y), in entity order
+fn zones_at(world: &mut World, x: i32, y: i32) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &ZoneBounds), With<ZoneKind>>();
+    let mut zones: Vec<Entity> = q
+        .iter(world)
+        .filter(|(_, b)| b.contains(x, y))
+        .map(|(e, _)| e)
+        .collect();
+    zones.sort();
+    zones
+}
+
+/// Stockpiles covering (x, y), in entity order
+fn stockpiles_at(world: &mut World, x: i32, y: i32) -> Vec<Entity> {
+    let mut zones = zones_at(world, x, y);
+    zones.retain(|&zone| world.get::<Stockpile>(zone).is_some());
+    zones
+}
+
+/// Give `zone` its kind and, for stockpiles, its tag filter
+///
+/// A zone that stops being a stockpile loses its storage; one that becomes
+/// a stockpile starts out accepting everything.
+fn set_zone(world: &mut World, zone: Entity, kind: ZoneKind, accepts: Option<&TagQuery>) {
+    let mut entity = world.entity_mut(zone);
+    entity.insert(kind);
+    if kind != ZoneKind::Stockpile {
+        entity.remove::<(Stockpile, AcceptsTags, FillLimit, GiveTo)>();
+        return;
+    }
+    if !entity.contains::<Stockpile>() {
+        entity.insert(Stockpile {
+            accepts: None,
+            priority: 0,
+        });
+    }
+    match accepts {
+        // The filter system fills in `accepts` on the next tick
+        Some(query) => {
+            entity.insert(AcceptsTags(query.clone()));
+        }
+        None => {
+            entity.remove::<AcceptsTags>();
+            if let Some(mut stockpile) = entity.get_mut::<Stockpile>() {
+                stockpile.accepts = None;
+            }
+        }
+    }
+}
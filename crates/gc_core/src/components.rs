@@ -8,34 +8,103 @@ use serde::{Deserialize, Serialize};
 /// the properties and capabilities of game entities.
 /// Marker component for goblin entities
 /// Used to identify goblin agents in the world for queries and systems
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Goblin;
 
+/// Marker component for trees spawned by embark flora
+#[derive(Component, Debug, Clone)]
+pub struct Tree;
+
+/// Marker component for wild animals
+#[derive(Component, Debug, Clone)]
+pub struct Animal;
+
 /// Component for entities that have job queues
 /// Currently unused but reserved for future job scheduling features
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct JobQueue;
 
 /// Component marking an entity as capable of carrying/hauling items
 /// Carriers can pick up items and transport them to stockpiles
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Carrier;
 
 /// Component marking an entity as capable of mining operations
 /// Miners can execute mining jobs to convert wall tiles to floor tiles
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Miner;
 
+/// Mining proficiency; miners only take jobs on layers whose
+/// [`Material::required_skill`](crate::world::Material::required_skill) it meets.
+/// Miners without this component count as skill 0.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiningSkill(pub u8);
+
+impl MiningSkill {
+    /// What new miners start with: enough to dig every layer
+    pub const STARTING: Self = MiningSkill(2);
+}
+
 /// Component tracking which job (if any) is currently assigned to an entity
 /// Contains an optional JobId that references a job in the JobBoard
 /// When None, the entity is available for new job assignments
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Clone, Default)]
 pub struct AssignedJob(pub Option<crate::jobs::JobId>);
 
 /// Component defining how far an entity can see for line-of-sight calculations
 /// Used by the FOV (Field of View) system to determine visibility ranges
-#[derive(Component, Debug)]
-pub struct VisionRadius(pub i32);
+///
+/// The radius is the entity's own `base` plus a stack of modifiers, one per
+/// source. Only [`vision_modifier_system`](crate::vision::vision_modifier_system)
+/// writes the stack; everything else sets `base` and reads [`radius`](Self::radius).
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct VisionRadius {
+    /// Radius with nothing modifying it
+    pub base: i32,
+    /// Current modifiers, at most one per source, in source order
+    pub modifiers: Vec<VisionModifier>,
+}
+
+impl VisionRadius {
+    /// An unmodified radius of `base`
+    pub fn new(base: i32) -> Self {
+        Self {
+            base,
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// Radius to see by: the base plus every modifier
+    ///
+    /// Modifiers never take it below 1, or below the base if that is
+    /// smaller.
+    pub fn radius(&self) -> i32 {
+        let delta: i32 = self.modifiers.iter().map(|m| m.delta).sum();
+        (self.base + delta).max(self.base.min(1))
+    }
+}
+
+/// What changes how far an entity sees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisionSource {
+    /// Outdoors after dark
+    Night,
+    /// Fog over the site
+    Fog,
+    /// Badly hurt
+    Injury,
+    /// Carrying a light through the dark
+    Lantern,
+}
+
+/// One entry of a [`VisionRadius`] stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VisionModifier {
+    pub source: VisionSource,
+    /// Tiles added to the radius (negative to take away)
+    pub delta: i32,
+}
 
 /// Represents the lifecycle state of a designation
 /// Designations go through states to prevent duplicate processing and
@@ -52,28 +121,57 @@ pub enum DesignationState {
     /// Designation that has been consumed/processed (for future use)
     /// Reserved for tracking completed designations
     Consumed,
+    /// Waiting for its tile to be discovered (fog-of-war designation rule)
+    /// Becomes Active again once exploration reaches the tile
+    Undiscovered,
+    /// No worker can reach a tile next to it (accessibility designation rule)
+    /// Becomes Active again once mining opens a way in
+    Unreachable,
+    /// The wall borders water or lava (safety designation rule)
+    /// Becomes Active once confirmed with `designations::HazardConfirmed`
+    Dangerous,
 }
 
 /// Component to track the lifecycle state of designations
 /// Attached to designation entities to manage their processing lifecycle
 /// and prevent duplicate job creation from the same designation
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Clone, Default)]
 pub struct DesignationLifecycle(pub DesignationState);
 
 /// Types of items that can exist in the world
 /// This enum defines all possible item types that can be created,
-/// carried, and stored in stockpiles. Currently only Stone is implemented.
+/// carried, and stored in stockpiles.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ItemType {
     /// Stone items created from mining operations
     /// These are the primary resource produced by mining wall tiles
     Stone,
+    /// Weapons worn in the weapon slot (see `equipment::Gear`)
+    Weapon,
+    /// Armor worn in the armor slot (see `equipment::Gear`)
+    Armor,
+    /// Rough gems occasionally found while mining (see `yields`)
+    Gem,
+    /// Metal ore, smelted into bars (see `production`)
+    Ore,
+    /// Fuel for smelters and other fuel-burning stations
+    Coal,
+    /// Fuel, and general-purpose timber
+    Wood,
+    /// Smelted metal bar
+    Bar,
+    /// Laid by tame chickens (see `livestock`)
+    Egg,
+    /// Shorn from tame sheep (see `livestock`)
+    Wool,
+    /// Stone cut into building blocks by a mason (see `production`)
+    Block,
 }
 
 /// Component representing an item entity that can be spawned, carried, and placed
 /// Items are full ECS entities with position and other properties,
 /// making them part of the spatial simulation rather than just data
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Item {
     /// The specific type of this item (Stone, Wood, etc.)
     pub item_type: ItemType,
@@ -92,13 +190,13 @@ impl Item {
 /// Marker component indicating that an item can be carried/hauled by agents
 /// Items with this component can be picked up by Carrier entities
 /// and transported to stockpiles or other locations
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Carriable;
 
 /// Component representing a stone item
 /// This is a specific marker for stone items, used in conjunction
 /// with the more generic Item component for type-specific behavior
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Stone;
 
 /// Inventory component for agents to carry a single item (MVP)
@@ -106,13 +204,70 @@ pub struct Stone;
 /// Currently supports only one item at a time for simplicity
 /// When Some(entity), the entity is the item being carried
 /// When None, the inventory is empty and can accept a new item
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Clone, Default)]
 pub struct Inventory(pub Option<Entity>);
 
+/// Item-side half of [`Inventory`]: the agent carrying this item
+/// Carried items have no `Position`; it is restored when they are dropped
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarriedBy(pub Entity);
+
+/// Where a carrier is in its current haul
+/// Present only while a haul job is held; phases run in declaration order
+/// and are advanced by `hauling_execution_system`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaulPhase {
+    /// Heading to the pickup tile
+    Approach,
+    /// At the pickup tile, taking the item
+    Pickup,
+    /// Carrying the item to the destination
+    Deliver,
+    /// At the destination, setting the item down
+    Drop,
+}
+
+/// How an agent covers the distance to its work
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MovementMode {
+    /// Arrive in the same tick
+    #[default]
+    Teleport,
+    /// One tile per tick, diagonals included; greedy, so it stops at a wall
+    /// it cannot slide along rather than entering it
+    Step,
+    /// One tile per tick along a shortest walkable path from the shared
+    /// [`PathService`](crate::path::PathService); steps straight like
+    /// `Step` when there is no service or map, and waits when there is no
+    /// path
+    Walk,
+}
+
+/// Per-agent [`MovementMode`], overriding `MovementConfig`
+/// (e.g. teleporting debug agents in a stepping colony)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovementOverride(pub MovementMode);
+
+impl HaulPhase {
+    /// Phase a carrier starts in, given whether it already holds an item
+    pub fn initial(carrying: bool) -> Self {
+        if carrying {
+            HaulPhase::Deliver
+        } else {
+            HaulPhase::Approach
+        }
+    }
+
+    /// Whether the phase expects the carrier to hold the item
+    pub fn carrying(self) -> bool {
+        matches!(self, HaulPhase::Deliver | HaulPhase::Drop)
+    }
+}
+
 /// Defines rectangular bounds for a zone
 /// Used by stockpiles and other area-based game features
 /// Coordinates are inclusive on all sides
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
 pub struct ZoneBounds {
     /// Minimum X coordinate (inclusive)
     pub min_x: i32,
@@ -152,12 +307,54 @@ impl ZoneBounds {
 /// Component marking a stockpile zone that can accept items
 /// Stockpiles are storage areas where items can be hauled and organized
 /// They use ZoneBounds to define their spatial area
-#[derive(Component, Debug)]
+///
+/// Deserializing also accepts the older `{ "accepts_any": bool }` shape:
+/// `true` accepts everything, `false` nothing.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StockpileRepr")]
 pub struct Stockpile {
     /// Items accepted by this stockpile (None = accepts all)
     /// When Some(vec), only items matching the specified types are accepted
     /// When None, all item types are accepted (current MVP behavior)
     pub accepts: Option<Vec<ItemType>>,
+    /// Haul destination preference; higher wins over nearer (default 0)
+    #[serde(default)]
+    pub priority: u8,
+}
+
+/// Every serialized shape a [`Stockpile`] has had
+#[derive(Deserialize)]
+struct StockpileRepr {
+    #[serde(default)]
+    accepts: Option<Vec<ItemType>>,
+    /// Before item filters: whether the stockpile took every item
+    #[serde(default)]
+    accepts_any: Option<bool>,
+    #[serde(default)]
+    priority: u8,
+}
+
+impl From<StockpileRepr> for Stockpile {
+    fn from(repr: StockpileRepr) -> Self {
+        let accepts = match (repr.accepts, repr.accepts_any) {
+            (Some(types), _) => Some(types),
+            (None, Some(false)) => Some(Vec::new()),
+            (None, _) => None,
+        };
+        Stockpile {
+            accepts,
+            priority: repr.priority,
+        }
+    }
+}
+
+impl Stockpile {
+    /// Whether items of `item_type` may be stored here
+    pub fn accepts_item(&self, item_type: ItemType) -> bool {
+        self.accepts
+            .as_ref()
+            .map_or(true, |types| types.contains(&item_type))
+    }
 }
 
 // ============================================================================
@@ -166,9 +363,12 @@ pub struct Stockpile {
 
 /// Faction types for combat and social interactions
 /// Determines hostility and targeting behavior between entities
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub enum FactionKind {
     /// Player-controlled goblins and allies
+    #[default]
     Goblins,
     /// Hostile invaders and enemies
     Invaders,
@@ -341,14 +541,29 @@ impl AttackCooldown {
 
 /// Marker component indicating an entity can participate in combat
 /// Entities with this component can attack, be attacked, and use combat systems
-#[derive(Component, Debug, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Combatant;
 
 /// Marker component indicating an entity is dead
 /// Dead entities should not participate in combat, movement, or jobs
-#[derive(Component, Debug, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Dead;
 
+/// Short-term affect, from [`Mood::MIN`] (miserable) to [`Mood::MAX`]
+/// (elated); 0 is neutral
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mood(pub i16);
+
+impl Mood {
+    pub const MIN: i16 = -1000;
+    pub const MAX: i16 = 1000;
+
+    /// Shift the mood by `delta`, staying within range
+    pub fn shift(&mut self, delta: i16) {
+        self.0 = self.0.saturating_add(delta).clamp(Self::MIN, Self::MAX);
+    }
+}
+
 /// Component for targeting other entities in combat
 /// Lightweight pointer to the target entity for combat systems
 #[derive(Component, Debug, Clone)]
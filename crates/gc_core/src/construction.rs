@@ -0,0 +1,649 @@
+//! Constructions: sites that gather materials, then get built
+//!
+//! A construction site is an entity with a [`Construction`], a
+//! [`MaterialRequirement`] and a [`Position`]. It goes through two phases:
+//!
+//! 1. Gathering: [`construction_site_system`] posts haul jobs bringing
+//!    loose or stockpiled items of the required type to the site, and
+//!    absorbs each item dropped there into the delivered count.
+//! 2. Building: once every unit is delivered it posts a single
+//!    [`JobKind::Build`], which an idle miner takes
+//!    ([`build_job_assignment_system`]) and works off next to the site
+//!    ([`build_execution_system`]).
+//!
+//! A finished wall becomes a [`TileKind::Wall`] tile and a cast floor a
+//! [`TileKind::Floor`] tile over water; a finished bridge makes the tile
+//! under it floor until it is raised. A finished torch or brazier becomes a
+//! [`LightSource`]. A finished workshop, smelter or mason's workshop stays as an entity marked [`Workshop`] with a production
+//! [`Station`]. Stone keeps its [`Material`] through the site: a wall built
+//! from granite is a granite wall. [`cancel_construction`] removes a
+//! site at any point, dropping its delivered materials back on the ground.
+
+use crate::burial::Grave;
+use crate::components::{
+    AssignedJob, Faction, Health, Item, ItemType, Miner, MovementOverride, Stone,
+};
+use crate::determinism::RngStream;
+use crate::faults::SimFaults;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, faction_of, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId,
+    JobKind,
+};
+use crate::lighting::LightSource;
+use crate::mechanisms::{Bridge, Door, Lever, PressurePlate, Trap};
+use crate::path::{approach_tile, PathService};
+use crate::production::{Station, StationKind};
+use crate::systems::{travel, DeterministicRng, MovementConfig, Time};
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// What is being built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstructionKind {
+    Wall,
+    Workshop,
+    Smelter,
+    /// A dug plot with a wooden coffin (see `burial`)
+    Grave,
+    /// A lever that operates linked mechanisms when pulled (see `mechanisms`)
+    Lever,
+    /// A plate that operates linked mechanisms when stepped on
+    PressurePlate,
+    /// A door that mechanisms can open and close
+    Door,
+    /// A spike trap that mechanisms can spring
+    Trap,
+    /// A bridge over water that levers can raise and lower
+    Bridge,
+    /// Stone cast into water, leaving solid floor
+    CastFloor,
+    /// A wooden torch that lights its surroundings (see `lighting`)
+    Torch,
+    /// A stone brazier, lighting further than a torch
+    Brazier,
+    /// A mason's workshop, cutting stone into blocks
+    Mason,
+}
+
+impl ConstructionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConstructionKind::Wall => "wall",
+            ConstructionKind::Workshop => "workshop",
+            ConstructionKind::Smelter => "smelter",
+            ConstructionKind::Grave => "grave",
+            ConstructionKind::Lever => "lever",
+            ConstructionKind::PressurePlate => "pressure plate",
+            ConstructionKind::Door => "door",
+            ConstructionKind::Trap => "trap",
+            ConstructionKind::Bridge => "bridge",
+            ConstructionKind::CastFloor => "cast floor",
+            ConstructionKind::Torch => "torch",
+            ConstructionKind::Brazier => "brazier",
+            ConstructionKind::Mason => "mason's workshop",
+        }
+    }
+
+    /// Material units needed before building can start
+    pub fn requirement(self) -> (ItemType, u32) {
+        match self {
+            ConstructionKind::Wall => (ItemType::Stone, 2),
+            ConstructionKind::Workshop => (ItemType::Stone, 4),
+            ConstructionKind::Smelter => (ItemType::Stone, 3),
+            ConstructionKind::Grave => (ItemType::Wood, 1),
+            ConstructionKind::Lever | ConstructionKind::PressurePlate => (ItemType::Stone, 1),
+            ConstructionKind::Door => (ItemType::Wood, 2),
+            ConstructionKind::Trap => (ItemType::Stone, 2),
+            ConstructionKind::Bridge => (ItemType::Wood, 2),
+            ConstructionKind::CastFloor => (ItemType::Stone, 3),
+            ConstructionKind::Torch => (ItemType::Wood, 1),
+            ConstructionKind::Brazier => (ItemType::Stone, 2),
+            ConstructionKind::Mason => (ItemType::Stone, 3),
+        }
+    }
+
+    /// Ticks of work once the materials are in
+    pub fn work_ticks(self) -> u32 {
+        match self {
+            ConstructionKind::Wall => 3,
+            ConstructionKind::Workshop => 8,
+            ConstructionKind::Smelter => 6,
+            ConstructionKind::Grave => 4,
+            ConstructionKind::Lever | ConstructionKind::PressurePlate => 3,
+            ConstructionKind::Door => 4,
+            ConstructionKind::Trap => 5,
+            ConstructionKind::Bridge => 5,
+            ConstructionKind::CastFloor => 4,
+            ConstructionKind::Torch => 2,
+            ConstructionKind::Brazier => 4,
+            ConstructionKind::Mason => 6,
+        }
+    }
+
+    /// Production station a finished building of this kind works as
+    pub fn station(self) -> Option<StationKind> {
+        match self {
+            ConstructionKind::Wall
+            | ConstructionKind::Grave
+            | ConstructionKind::Lever
+            | ConstructionKind::PressurePlate
+            | ConstructionKind::Door
+            | ConstructionKind::Trap
+            | ConstructionKind::Bridge
+            | ConstructionKind::CastFloor
+            | ConstructionKind::Torch
+            | ConstructionKind::Brazier => None,
+            ConstructionKind::Workshop => Some(StationKind::Workshop),
+            ConstructionKind::Smelter => Some(StationKind::Smelter),
+            ConstructionKind::Mason => Some(StationKind::Mason),
+        }
+    }
+}
+
+/// A construction site and its build progress
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Construction {
+    pub kind: ConstructionKind,
+    /// Ticks of work done
+    pub progress: u32,
+    /// Build job posted once the materials are in
+    pub build_job: Option<JobId>,
+}
+
+impl Construction {
+    pub fn new(kind: ConstructionKind) -> Self {
+        Self {
+            kind,
+            progress: 0,
+            build_job: None,
+        }
+    }
+}
+
+/// Materials a construction site still needs
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct MaterialRequirement {
+    pub item_type: ItemType,
+    pub required: u32,
+    /// Units absorbed into the site so far
+    pub delivered: u32,
+    /// Delivered units that carry a [`Material`], by material
+    pub materials: BTreeMap<Material, u32>,
+    /// Haul jobs bringing units here, queued or under way
+    pub(crate) incoming: Vec<JobId>,
+}
+
+impl MaterialRequirement {
+    pub fn new(item_type: ItemType, required: u32) -> Self {
+        Self {
+            item_type,
+            required,
+            delivered: 0,
+            materials: BTreeMap::new(),
+            incoming: Vec::new(),
+        }
+    }
+
+    /// Material most of the delivered units carry, the lowest on a tie;
+    /// what the finished building is made of
+    pub fn material(&self) -> Option<Material> {
+        self.materials
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(&m, _)| m)
+    }
+
+    /// Whether every unit has been delivered
+    pub fn is_met(&self) -> bool {
+        self.delivered >= self.required
+    }
+
+    /// Haul jobs currently bringing units here
+    pub fn incoming(&self) -> &[JobId] {
+        &self.incoming
+    }
+
+    /// Units neither delivered nor on their way
+    pub fn outstanding(&self) -> u32 {
+        self.required
+            .saturating_sub(self.delivered + self.incoming.len() as u32)
+    }
+}
+
+/// A finished workshop
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Workshop;
+
+/// Bundle for a new construction site at (x, y)
+#[derive(Bundle)]
+pub struct ConstructionBundle {
+    pub construction: Construction,
+    pub requirement: MaterialRequirement,
+    pub position: Position,
+    pub name: Name,
+}
+
+impl ConstructionBundle {
+    pub fn new(kind: ConstructionKind, x: i32, y: i32) -> Self {
+        let (item_type, required) = kind.requirement();
+        Self {
+            construction: Construction::new(kind),
+            requirement: MaterialRequirement::new(item_type, required),
+            position: Position(x, y),
+            name: Name(format!("{} site", kind.label())),
+        }
+    }
+}
+
+/// Gather materials for every site and post its build job once they are in
+///
+/// Each tick, per site (in entity order):
+/// - haul jobs that finished or vanished stop counting as incoming;
+/// - items of the required type lying on the site are absorbed, up to the
+///   requirement;
+/// - outstanding units get haul jobs from the nearest free items, where free
+///   means not on a site and not already the pickup tile of a haul;
+/// - a met requirement with no build job posts one.
+#[allow(clippy::type_complexity)]
+pub fn construction_site_system(
+    mut commands: Commands,
+    mut board: ResMut<JobBoard>,
+    active: Res<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    mut sites: Query<(
+        Entity,
+        &Position,
+        &mut Construction,
+        &mut MaterialRequirement,
+        Option<&Faction>,
+    )>,
+    items: Query<(Entity, &Position, &Item, Option<&Material>)>,
+) {
+    if sites.is_empty() {
+        return;
+    }
+    let site_tiles: HashSet<(i32, i32)> = sites.iter().map(|(_, p, ..)| (p.0, p.1)).collect();
+    let mut claimed: HashSet<(i32, i32)> = board
+        .0
+        .iter()
+        .chain(active.jobs.values())
+        .filter_map(|job| match job.kind {
+            JobKind::Haul { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect();
+    let mut absorbed: HashSet<Entity> = HashSet::new();
+
+    let mut order: Vec<Entity> = sites.iter().map(|(e, ..)| e).collect();
+    order.sort();
+    for site in order {
+        let (_, pos, mut construction, mut requirement, faction) =
+            sites.get_mut(site).expect("site listed above");
+        let at = (pos.0, pos.1);
+        let faction = faction_of(faction);
+
+        let live: HashSet<JobId> = board.0.iter().map(|j| j.id).collect();
+        requirement
+            .incoming
+            .retain(|id| live.contains(id) || active.jobs.contains_key(id));
+
+        let mut here: Vec<(Entity, Option<Material>)> = items
+            .iter()
+            .filter(|(e, p, item, _)| {
+                (p.0, p.1) == at && item.item_type == requirement.item_type && !absorbed.contains(e)
+            })
+            .map(|(e, _, _, material)| (e, material.copied()))
+            .collect();
+        here.sort();
+        for (item, material) in here {
+            if requirement.is_met() {
+                break;
+            }
+            requirement.delivered += 1;
+            if let Some(material) = material {
+                *requirement.materials.entry(material).or_default() += 1;
+            }
+            absorbed.insert(item);
+            commands.entity(item).despawn();
+        }
+
+        let mut free: Vec<(i32, Entity, (i32, i32))> = items
+            .iter()
+            .filter(|(e, p, item, _)| {
+                item.item_type == requirement.item_type
+                    && !absorbed.contains(e)
+                    && !site_tiles.contains(&(p.0, p.1))
+            })
+            .map(|(e, p, ..)| ((p.0 - at.0).abs() + (p.1 - at.1).abs(), e, (p.0, p.1)))
+            .collect();
+        free.sort();
+        for (_, _, from) in free {
+            if requirement.outstanding() == 0 {
+                break;
+            }
+            if !claimed.insert(from) {
+                continue;
+            }
+            let id = add_job_for(
+                &mut board,
+                JobKind::Haul { from, to: at },
+                IssuedBy::System,
+                faction,
+                rng.stream(RngStream::Job, "construction_site_system"),
+            );
+            requirement.incoming.push(id);
+        }
+
+        if requirement.is_met() && construction.build_job.is_none() {
+            let id = add_job_for(
+                &mut board,
+                JobKind::Build { x: at.0, y: at.1 },
+                IssuedBy::System,
+                faction,
+                rng.stream(RngStream::Job, "construction_site_system"),
+            );
+            construction.build_job = Some(id);
+        }
+    }
+}
+
+/// Hand one queued build job to an idle miner of its faction per run, within
+/// its [`WorkArea`]
+pub fn build_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut builders: Query<BoardWorker, With<Miner>>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut builders,
+        |kind| matches!(kind, JobKind::Build { .. }),
+    );
+}
+
+/// Walk builders next to their site and put one tick of work in per run
+///
+/// Builders work from a walkable tile beside the site and wait when there
+/// is none. Creatures standing on a wall site as it is finished are moved
+/// to a walkable tile beside it; with none free the wall waits. A finished wall turns its tile into [`TileKind::Wall`] and a cast floor
+/// into [`TileKind::Floor`], and the site is despawned; a finished workshop, smelter or mason's workshop keeps its entity as a
+/// [`Workshop`] with an empty [`Station`], and a finished grave becomes an
+/// empty [`Grave`]. Levers, plates, doors and traps become unlinked
+/// mechanisms; doors are built open and bridges lowered, which makes
+/// the tile under a bridge walkable floor. Torches and braziers become
+/// [`LightSource`]s. What was built is made of the [`Material`] most of its
+/// delivered units carried: a wall or cast floor writes it into the
+/// [`MaterialMap`], anything else keeps it as a component. A build job
+/// whose site is gone is dropped; a site off the map is reported to
+/// [`SimFaults`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn build_execution_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    mut faults: Option<ResMut<SimFaults>>,
+    mut map: ResMut<GameMap>,
+    mut materials: Option<ResMut<MaterialMap>>,
+    config: Option<Res<MovementConfig>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut active: ResMut<ActiveJobs>,
+    mut builders: Query<
+        (
+            Entity,
+            &mut AssignedJob,
+            &mut Position,
+            Option<&MovementOverride>,
+        ),
+        (With<Miner>, Without<Stalled>, Without<Construction>),
+    >,
+    mut sites: Query<
+        (
+            Entity,
+            &Position,
+            &mut Construction,
+            Option<&MaterialRequirement>,
+        ),
+        Without<Miner>,
+    >,
+    others: Query<(Entity, &Position), (Or<(With<Health>, With<AssignedJob>)>, Without<Miner>)>,
+    stalled: Query<(Entity, &Position), (With<Miner>, With<Stalled>)>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    // Creatures on each tile, kept up to date as builders move
+    let mut occupants: HashMap<(i32, i32), Vec<Entity>> = HashMap::new();
+    for (entity, p) in builders
+        .iter()
+        .map(|(e, _, p, _)| (e, p))
+        .chain(others.iter())
+        .chain(stalled.iter())
+    {
+        occupants.entry((p.0, p.1)).or_default().push(entity);
+    }
+    for (builder, mut assigned, mut pos, movement) in builders.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(JobKind::Build { x, y }) = active.jobs.get(&id).map(|j| j.kind.clone()) else {
+            continue;
+        };
+        let Some((site, _, mut construction, requirement)) = sites
+            .iter_mut()
+            .find(|(_, p, c, _)| (p.0, p.1) == (x, y) && c.build_job == Some(id))
+        else {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        };
+        // Work from a walkable neighbouring tile, never the site itself
+        let (dx, dy) = (pos.0 - x, pos.1 - y);
+        if dx.abs() > 1 || dy.abs() > 1 || (dx, dy) == (0, 0) {
+            let from = (pos.0, pos.1);
+            if let Some(approach) = approach_tile(&map, from, x, y) {
+                travel(
+                    &mut pos,
+                    approach,
+                    config.mode_for(movement),
+                    Some(&map),
+                    paths.as_deref_mut(),
+                );
+            }
+            if (pos.0, pos.1) != from {
+                if let Some(here) = occupants.get_mut(&from) {
+                    here.retain(|&e| e != builder);
+                }
+                occupants.entry((pos.0, pos.1)).or_default().push(builder);
+            }
+            continue;
+        }
+        let last_tick = construction.progress + 1 >= construction.kind.work_ticks();
+        if last_tick && construction.kind == ConstructionKind::Wall {
+            // Step whoever stands on the site aside rather than wall them
+            // in; with nowhere to go the wall waits
+            let trapped = occupants.remove(&(x, y)).unwrap_or_default();
+            if !trapped.is_empty() {
+                let Some(aside) = approach_tile(&map, (x, y), x, y) else {
+                    occupants.insert((x, y), trapped);
+                    continue;
+                };
+                for &entity in &trapped {
+                    commands.entity(entity).insert(Position(aside.0, aside.1));
+                }
+                occupants.entry(aside).or_default().extend(trapped);
+            }
+        }
+        construction.progress += 1;
+        if construction.progress < construction.kind.work_ticks() {
+            continue;
+        }
+        active.jobs.remove(&id);
+        assigned.0 = None;
+        if let Some(material) = requirement.and_then(MaterialRequirement::material) {
+            match construction.kind {
+                ConstructionKind::Wall | ConstructionKind::CastFloor => {
+                    // A site off the map is reported below
+                    if let Some(materials) = materials.as_deref_mut() {
+                        materials.set(x, y, material).ok();
+                    }
+                }
+                _ => {
+                    commands.entity(site).insert(material);
+                }
+            }
+        }
+        match construction.kind {
+            ConstructionKind::Wall => {
+                if let Err(err) = map.set_tile(x, y, TileKind::Wall) {
+                    err.report(
+                        faults.as_deref_mut(),
+                        time.as_deref(),
+                        "build_execution_system",
+                        Some(site),
+                    );
+                }
+                commands.entity(site).despawn();
+            }
+            ConstructionKind::CastFloor => {
+                if let Err(err) = map.set_tile(x, y, TileKind::Floor) {
+                    err.report(
+                        faults.as_deref_mut(),
+                        time.as_deref(),
+                        "build_execution_system",
+                        Some(site),
+                    );
+                }
+                commands.entity(site).despawn();
+            }
+            ConstructionKind::Workshop | ConstructionKind::Smelter | ConstructionKind::Mason => {
+                let kind = construction.kind.station().expect("buildings are stations");
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Workshop, Station::new(kind), Name(kind.name().into())));
+            }
+            ConstructionKind::Grave => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Grave::default(), Name("Grave".into())));
+            }
+            ConstructionKind::Lever => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Lever::default(), Name("Lever".into())));
+            }
+            ConstructionKind::PressurePlate => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((PressurePlate::default(), Name("Pressure plate".into())));
+            }
+            ConstructionKind::Door => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Door::default(), Name("Door".into())));
+            }
+            ConstructionKind::Trap => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Trap, Name("Trap".into())));
+            }
+            ConstructionKind::Bridge => {
+                let under = map.get_tile(x, y).unwrap_or(TileKind::Water);
+                if let Err(err) = map.set_tile(x, y, TileKind::Floor) {
+                    err.report(
+                        faults.as_deref_mut(),
+                        time.as_deref(),
+                        "build_execution_system",
+                        Some(site),
+                    );
+                }
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((Bridge::lowered(under), Name("Bridge".into())));
+            }
+            ConstructionKind::Torch => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((LightSource::TORCH, Name("Torch".into())));
+            }
+            ConstructionKind::Brazier => {
+                commands
+                    .entity(site)
+                    .remove::<(Construction, MaterialRequirement)>()
+                    .insert((LightSource::BRAZIER, Name("Brazier".into())));
+            }
+        }
+    }
+}
+
+/// Remove a construction site, refunding what was delivered
+///
+/// Delivered units are dropped on the site's tile as loose items, in the
+/// materials they were delivered in; haul jobs bringing more are taken off
+/// the board (hauls under way finish normally) and the build job is
+/// dropped, freeing its builder. Returns false when `site` is not a
+/// construction site.
+pub fn cancel_construction(world: &mut World, site: Entity) -> bool {
+    let Some(construction) = world.get::<Construction>(site).cloned() else {
+        return false;
+    };
+    let requirement = world
+        .get::<MaterialRequirement>(site)
+        .cloned()
+        .expect("construction sites carry a requirement");
+    let pos = *world.get::<Position>(site).expect("sites have a position");
+
+    let mut dropped: Vec<JobId> = requirement.incoming.clone();
+    dropped.extend(construction.build_job);
+    world
+        .resource_mut::<JobBoard>()
+        .0
+        .retain(|j| !dropped.contains(&j.id));
+    if let Some(id) = construction.build_job {
+        world.resource_mut::<ActiveJobs>().jobs.remove(&id);
+        let mut q = world.query::<&mut AssignedJob>();
+        for mut assigned in q.iter_mut(world) {
+            if assigned.0 == Some(id) {
+                assigned.0 = None;
+            }
+        }
+    }
+
+    let mut materials = requirement
+        .materials
+        .iter()
+        .flat_map(|(&m, &n)| std::iter::repeat(m).take(n as usize));
+    for _ in 0..requirement.delivered {
+        let mut item = world.spawn((
+            Item {
+                item_type: requirement.item_type,
+            },
+            pos,
+            crate::components::Carriable,
+        ));
+        let material = materials.next();
+        if let Some(material) = material {
+            item.insert(material);
+        }
+        if requirement.item_type == ItemType::Stone {
+            let name = material.map_or("Stone", Material::item_name);
+            item.insert((Stone, Name(name.into())));
+        }
+    }
+    world.despawn(site);
+    true
+}
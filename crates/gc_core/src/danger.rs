@@ -0,0 +1,183 @@
+//! Danger map: tiles civilians should keep away from
+//!
+//! Each faction knows about the hostiles its members can currently see
+//! (through [`Visibility`]). [`danger_map_system`] turns those sightings
+//! into a per-faction cost layer that falls off with distance from each
+//! hostile. Queued path searches for civilian goblins (goblins outside a
+//! [`Squad`]) add the layer to their step costs, so they detour around
+//! enemies when a detour exists and walk through when it doesn't: the cost
+//! of a tile is capped, never infinite.
+//!
+//! The layer is kept incrementally: only hostiles that appeared, moved or
+//! vanished since the last run add or remove their contribution.
+//!
+//! [`Squad`]: crate::equipment::Squad
+
+use crate::components::{Dead, Faction, FactionKind, VisionRadius};
+use crate::fov::Visibility;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Shape of the danger around one hostile
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DangerConfig {
+    /// Manhattan distance over which a hostile adds danger
+    pub radius: i32,
+    /// Extra cost on the hostile's own tile, in tenths of a step; falls off
+    /// linearly to nothing past `radius`
+    pub peak: i32,
+    /// Most extra cost any tile can carry, in tenths of a step
+    pub cap: i32,
+}
+
+impl Default for DangerConfig {
+    fn default() -> Self {
+        Self {
+            radius: 4,
+            peak: 50,
+            cap: 100,
+        }
+    }
+}
+
+impl DangerConfig {
+    /// Danger a hostile adds `distance` tiles away
+    fn falloff(&self, distance: i32) -> i32 {
+        if distance > self.radius {
+            return 0;
+        }
+        self.peak * (self.radius + 1 - distance) / (self.radius + 1)
+    }
+}
+
+/// Per-faction extra path cost from known hostiles
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DangerMap {
+    /// Positions of hostiles each faction currently sees
+    sources: BTreeMap<FactionKind, BTreeSet<(i32, i32)>>,
+    /// Uncapped summed danger per tile
+    costs: BTreeMap<FactionKind, HashMap<(i32, i32), i32>>,
+    /// Config the costs were built with
+    config: DangerConfig,
+}
+
+impl DangerMap {
+    /// Extra cost for a member of `faction` to step on (x, y), in tenths of
+    /// a step
+    pub fn cost(&self, faction: FactionKind, x: i32, y: i32) -> i32 {
+        self.costs
+            .get(&faction)
+            .and_then(|c| c.get(&(x, y)))
+            .map_or(0, |&c| c.min(self.config.cap))
+    }
+
+    /// Hostile positions `faction` currently knows about, sorted
+    pub fn known_hostiles(&self, faction: FactionKind) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.sources.get(&faction).into_iter().flatten().copied()
+    }
+
+    /// Whether `faction` has any danger to avoid
+    pub fn is_clear(&self, faction: FactionKind) -> bool {
+        self.sources.get(&faction).map_or(true, |s| s.is_empty())
+    }
+
+    fn spread(&mut self, faction: FactionKind, (x, y): (i32, i32), sign: i32) {
+        let config = self.config;
+        let costs = self.costs.entry(faction).or_default();
+        let r = config.radius;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let add = config.falloff(dx.abs() + dy.abs());
+                if add == 0 {
+                    continue;
+                }
+                let tile = (x + dx, y + dy);
+                let total = costs.entry(tile).or_default();
+                *total += sign * add;
+                if *total <= 0 {
+                    costs.remove(&tile);
+                }
+            }
+        }
+    }
+
+    /// Replace the hostiles `faction` knows about, adjusting only the
+    /// tiles around those that changed
+    pub fn set_known_hostiles(&mut self, faction: FactionKind, hostiles: BTreeSet<(i32, i32)>) {
+        let old = self.sources.remove(&faction).unwrap_or_default();
+        for &gone in old.difference(&hostiles) {
+            self.spread(faction, gone, -1);
+        }
+        for &new in hostiles.difference(&old) {
+            self.spread(faction, new, 1);
+        }
+        if hostiles.is_empty() {
+            self.costs.remove(&faction);
+        } else {
+            self.sources.insert(faction, hostiles);
+        }
+    }
+
+    /// Rebuild every layer under `config`
+    fn reconfigure(&mut self, config: DangerConfig) {
+        let sources = std::mem::take(&mut self.sources);
+        self.costs.clear();
+        self.config = config;
+        for (faction, hostiles) in sources {
+            self.set_known_hostiles(faction, hostiles);
+        }
+    }
+}
+
+/// Update [`DangerMap`] from what each faction's members can see
+///
+/// A hostile is known to a faction when it stands on a tile visible to a
+/// living member of that faction with a [`VisionRadius`]. Without a
+/// [`Visibility`] resource nobody sees anything and the map stays clear.
+#[allow(clippy::type_complexity)]
+pub fn danger_map_system(
+    config: Option<Res<DangerConfig>>,
+    vis: Option<Res<Visibility>>,
+    danger: Option<ResMut<DangerMap>>,
+    observers: Query<(Entity, &Faction), (With<VisionRadius>, Without<Dead>)>,
+    hostiles: Query<(&Position, &Faction), Without<Dead>>,
+) {
+    let Some(mut danger) = danger else {
+        return;
+    };
+    let config = config.as_deref().copied().unwrap_or_default();
+    if config != danger.config {
+        danger.reconfigure(config);
+    }
+
+    let mut factions: BTreeSet<FactionKind> = danger.sources.keys().copied().collect();
+    factions.extend(observers.iter().map(|(_, f)| f.kind));
+    for kind in factions {
+        let me = Faction::new(kind);
+        let mut known = BTreeSet::new();
+        let enemies: Vec<(i32, i32)> = hostiles
+            .iter()
+            .filter(|(_, f)| me.is_hostile_to(f))
+            .map(|(p, _)| (p.0, p.1))
+            .collect();
+        if let (false, Some(vis)) = (enemies.is_empty(), vis.as_deref()) {
+            let seen: HashSet<(i32, i32)> = observers
+                .iter()
+                .filter(|(_, f)| f.kind == kind)
+                .filter_map(|(e, _)| vis.per_entity.get(&e))
+                .flatten()
+                .copied()
+                .collect();
+            known.extend(enemies.into_iter().filter(|p| seen.contains(p)));
+        }
+        if danger
+            .sources
+            .get(&kind)
+            .map_or(known.is_empty(), |s| *s == known)
+        {
+            continue;
+        }
+        danger.set_known_hostiles(kind, known);
+    }
+}
@@ -1,8 +1,20 @@
-use crate::components::{DesignationLifecycle, DesignationState};
-use crate::jobs::{add_job, JobBoard, JobKind};
-use crate::systems::DeterministicRng;
+use crate::announcements::{Announcements, Severity};
+use crate::components::{
+    AssignedJob, Dead, DesignationLifecycle, DesignationState, Faction, FactionKind, ItemType,
+    Miner, MiningSkill, ZoneBounds,
+};
+use crate::determinism::RngStream;
+use crate::fov::Explored;
+use crate::jobs::{
+    add_job_for, faction_of, round_robin, ActiveJobs, IssuedBy, JobBoard, JobId, JobKind,
+    JobThrottle, ThrottleState,
+};
+use crate::systems::{DeterministicRng, Time};
+use crate::world::{GameMap, MaterialMap, Position, TileKind};
+use crate::yields::YieldTable;
 use bevy_ecs::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
 
 /// Designation System for Player Input and Job Creation
 ///
@@ -15,15 +27,42 @@ use std::collections::HashMap;
 /// Component marking an entity as a mining designation
 /// Mining designations mark tiles that should be converted from Wall to Floor
 /// These are typically created by player input or scripted scenarios
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct MineDesignation;
 
+/// Confirms digging a mining designation next to water or lava
+///
+/// Without it such designations wait as [`DesignationState::Dangerous`]
+/// while [`DesignationConfig::confirm_hazards`] is on.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HazardConfirmed;
+
+/// Priority passed on to the job a mining designation posts
+///
+/// Miners take higher-priority mine jobs first; designations without one
+/// post at priority 0.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DesignationPriority(pub u32);
+
+/// Water or lava orthogonally next to the wall at (x, y), lava first
+///
+/// Floors and other non-walls have no hazard: digging them opens nothing.
+pub fn wall_hazard(map: &GameMap, x: i32, y: i32) -> Option<TileKind> {
+    if map.get_tile(x, y) != Some(TileKind::Wall) {
+        return None;
+    }
+    let neighbors = [(0, -1), (1, 0), (0, 1), (-1, 0)].map(|(dx, dy)| map.get_tile(x + dx, y + dy));
+    [TileKind::Lava, TileKind::Water]
+        .into_iter()
+        .find(|hazard| neighbors.contains(&Some(*hazard)))
+}
+
 /// Bundle for creating complete designation entities
 /// Provides a convenient way to spawn designations with all required components
 #[derive(Bundle)]
 pub struct DesignationBundle {
     /// World position of the designation
-    pub pos: crate::world::Position,
+    pub pos: Position,
     /// Type of designation (currently only mining)
     pub kind: MineDesignation,
     /// Lifecycle tracking for deduplication and processing
@@ -34,28 +73,501 @@ impl Default for DesignationBundle {
     /// Create a default mining designation at (0, 0)
     fn default() -> Self {
         Self {
-            pos: crate::world::Position(0, 0),
+            pos: Position(0, 0),
             kind: MineDesignation,
             lifecycle: DesignationLifecycle::default(),
         }
     }
 }
 
+/// Outcome of [`designate_mine_cells`] and [`designate_mine_rect`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesignateBatch {
+    /// Newly spawned designation entities, in input order
+    pub spawned: Vec<Entity>,
+    /// Cells already designated, or repeated within the batch
+    pub duplicates: usize,
+    /// Cells outside the map
+    pub invalid: usize,
+    /// Spawned designations on walls next to water or lava, left
+    /// [`DesignationState::Dangerous`] until confirmed
+    pub dangerous: usize,
+}
+
+/// Designate every cell in `cells` for mining in one call
+///
+/// Duplicates are resolved up front: cells that already hold an Active or
+/// Consumed mining designation, or that repeat earlier in `cells`, are
+/// counted rather than spawned, as are cells off the map (when a `GameMap`
+/// exists). The spawned designations start Active, except walls next to
+/// water or lava, which start Dangerous under the safety rule (see
+/// [`DesignationConfig::confirm_hazards`]).
+pub fn designate_mine_cells(
+    world: &mut World,
+    cells: impl IntoIterator<Item = (i32, i32)>,
+) -> DesignateBatch {
+    let mut taken: HashSet<(i32, i32)> = world
+        .query_filtered::<(&Position, &DesignationLifecycle), With<MineDesignation>>()
+        .iter(world)
+        .filter(|(_, lifecycle)| lifecycle.0 != DesignationState::Ignored)
+        .map(|(pos, _)| (pos.0, pos.1))
+        .collect();
+    let map = world.get_resource::<GameMap>();
+    let mut batch = DesignateBatch::default();
+    let mut fresh = Vec::new();
+    for (x, y) in cells {
+        if !map.map_or(true, |m| m.in_bounds(x, y)) {
+            batch.invalid += 1;
+        } else if !taken.insert((x, y)) {
+            batch.duplicates += 1;
+        } else {
+            fresh.push((x, y));
+        }
+    }
+    let confirm = world
+        .get_resource::<DesignationConfig>()
+        .is_some_and(|c| c.confirm_hazards);
+    let lifecycles: Vec<DesignationLifecycle> = fresh
+        .iter()
+        .map(|&(x, y)| match map {
+            Some(map) if confirm && wall_hazard(map, x, y).is_some() => {
+                DesignationLifecycle(DesignationState::Dangerous)
+            }
+            _ => DesignationLifecycle::default(),
+        })
+        .collect();
+    batch.dangerous = lifecycles
+        .iter()
+        .filter(|l| l.0 == DesignationState::Dangerous)
+        .count();
+    batch.spawned = world
+        .spawn_batch(
+            fresh
+                .into_iter()
+                .zip(lifecycles)
+                .map(|((x, y), lifecycle)| DesignationBundle {
+                    pos: Position(x, y),
+                    lifecycle,
+                    ..Default::default()
+                }),
+        )
+        .collect();
+    batch
+}
+
+/// Designate every tile in `bounds` (inclusive) for mining
+/// See [`designate_mine_cells`] for how duplicates and off-map tiles are
+/// handled. Only the part of `bounds` on the map is walked; the rest is
+/// counted as invalid without visiting it, so huge rectangles stay cheap.
+pub fn designate_mine_rect(world: &mut World, bounds: &ZoneBounds) -> DesignateBatch {
+    let (min, max) = ((bounds.min_x, bounds.min_y), (bounds.max_x, bounds.max_y));
+    let Some(map) = world.get_resource::<GameMap>() else {
+        let cells: Vec<(i32, i32)> = (min.1..=max.1)
+            .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+            .collect();
+        return designate_mine_cells(world, cells);
+    };
+    let cells: Vec<(i32, i32)> = map.iter_rect(min, max).map(|(p, _)| p).collect();
+    let span = |lo: i32, hi: i32| (i64::from(hi) - i64::from(lo) + 1).max(0) as u64;
+    let off_map = span(min.0, max.0)
+        .saturating_mul(span(min.1, max.1))
+        .saturating_sub(cells.len() as u64);
+    let mut batch = designate_mine_cells(world, cells);
+    batch.invalid = batch
+        .invalid
+        .saturating_add(usize::try_from(off_map).unwrap_or(usize::MAX));
+    batch
+}
+
+/// What digging out an area would take and yield, before designating it
+///
+/// Worked out by [`estimate_designation`] from the map as it is now.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesignationEstimate {
+    /// Walls that would be newly designated
+    pub tiles: u32,
+    /// Of those, walls whose material needs more skill than any miner has
+    pub too_hard: u32,
+    /// Of those, walls next to water or lava (see [`wall_hazard`])
+    pub dangerous: u32,
+    /// Tiles left out: already designated, not rock, or off the map
+    pub skipped: u32,
+    /// Miner ticks of digging; a wall is mined in the one tick a miner
+    /// works it
+    pub work_ticks: u64,
+    /// Living miners who could take the jobs
+    pub miners: u32,
+    /// Expected drops by item type, in hundredths of an item (the sum of
+    /// the [`YieldTable`] chances of every wall)
+    pub yields: BTreeMap<ItemType, u32>,
+}
+
+impl DesignationEstimate {
+    /// Expected number of `item` dropped
+    pub fn expected(&self, item: ItemType) -> f32 {
+        self.yields.get(&item).copied().unwrap_or(0) as f32 / 100.0
+    }
+
+    /// Ticks until the diggable walls are done with every miner at work,
+    /// or None without miners
+    pub fn crew_ticks(&self) -> Option<u64> {
+        let diggable = u64::from(self.tiles - self.too_hard);
+        (self.miners > 0).then(|| diggable.div_ceil(u64::from(self.miners)))
+    }
+}
+
+impl fmt::Display for DesignationEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tiles, {} work ticks", self.tiles, self.work_ticks)?;
+        if let Some(ticks) = self.crew_ticks() {
+            write!(f, " (~{} with {} miners)", ticks, self.miners)?;
+        }
+        for (item, hundredths) in &self.yields {
+            let name = format!("{:?}", item).to_lowercase();
+            if hundredths % 100 == 0 {
+                write!(f, ", ~{} {}", hundredths / 100, name)?;
+            } else {
+                write!(f, ", ~{:.1} {}", *hundredths as f32 / 100.0, name)?;
+            }
+        }
+        if self.too_hard > 0 {
+            write!(f, ", {} too hard", self.too_hard)?;
+        }
+        if self.dangerous > 0 {
+            write!(f, ", {} by water/lava", self.dangerous)?;
+        }
+        if self.skipped > 0 {
+            write!(f, ", {} skipped", self.skipped)?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate mining every tile in `bounds` (inclusive) without designating
+/// anything
+///
+/// Counts the tiles [`designate_mine_rect`] would designate that are
+/// still rock, and rates them against the [`MaterialMap`] and
+/// [`YieldTable`] the way [`mine_job_execution_system`] would dig them:
+/// without a yield table every wall drops one stone.
+///
+/// [`mine_job_execution_system`]: crate::jobs::mine_job_execution_system
+pub fn estimate_designation(world: &mut World, bounds: &ZoneBounds) -> DesignationEstimate {
+    let taken: HashSet<(i32, i32)> = world
+        .query_filtered::<(&Position, &DesignationLifecycle), With<MineDesignation>>()
+        .iter(world)
+        .filter(|(_, lifecycle)| lifecycle.0 != DesignationState::Ignored)
+        .map(|(pos, _)| (pos.0, pos.1))
+        .collect();
+    let skills: Vec<u8> = world
+        .query_filtered::<Option<&MiningSkill>, (With<Miner>, Without<Dead>)>()
+        .iter(world)
+        .map(|skill| skill.map_or(0, |s| s.0))
+        .collect();
+    let best_skill = skills.iter().copied().max();
+    let map = world.get_resource::<GameMap>();
+    let materials = world.get_resource::<MaterialMap>();
+    let yields = world.get_resource::<YieldTable>();
+
+    let mut estimate = DesignationEstimate {
+        miners: skills.len() as u32,
+        ..Default::default()
+    };
+    for y in bounds.min_y..=bounds.max_y {
+        for x in bounds.min_x..=bounds.max_x {
+            let wall = map.is_some_and(|m| m.get_tile(x, y) == Some(TileKind::Wall));
+            if !wall || taken.contains(&(x, y)) {
+                estimate.skipped += 1;
+                continue;
+            }
+            estimate.tiles += 1;
+            estimate.work_ticks += 1;
+            if map.is_some_and(|m| wall_hazard(m, x, y).is_some()) {
+                estimate.dangerous += 1;
+            }
+            let material = materials.and_then(|m| m.get(x, y));
+            if material.is_some_and(|m| m.required_skill() > best_skill.unwrap_or(0)) {
+                estimate.too_hard += 1;
+            }
+            match yields {
+                Some(table) => {
+                    for entry in table.entries(material) {
+                        *estimate.yields.entry(entry.item).or_default() +=
+                            u32::from(entry.chance.min(100));
+                    }
+                }
+                None => *estimate.yields.entry(ItemType::Stone).or_default() += 100,
+            }
+        }
+    }
+    estimate.yields.retain(|_, hundredths| *hundredths > 0);
+    estimate
+}
+
+/// Job created from a designation, recorded when it is consumed
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DesignationJob {
+    pub job: JobId,
+    /// Tick at which the designation was consumed (0 without `Time`)
+    pub consumed_at: u64,
+}
+
+/// What happens to Consumed designations (see [`designation_cleanup_system`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsumedCleanup {
+    /// Keep them as entities indefinitely
+    #[default]
+    Keep,
+    /// Despawn once the linked job is neither queued nor active
+    OnJobComplete,
+    /// Despawn this many ticks after consumption, whatever the job's state
+    AfterTicks(u64),
+}
+
+/// Progress of the work a designation asked for
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignationStatus {
+    /// Not yet turned into a job (e.g. waiting on discovery)
+    Pending,
+    /// Another designation on the same tile carries the work
+    Duplicate,
+    /// Job is on the board waiting for a worker
+    Queued,
+    /// Job is held by this worker, who has not reached the tile yet
+    Assigned(Entity),
+    /// This worker is within reach of the tile
+    InProgress(Entity),
+    /// Job finished and the tile was mined
+    Done,
+    /// No worker can get next to the tile yet
+    Unreachable,
+    /// The wall borders water or lava and waits for [`HazardConfirmed`]
+    Dangerous,
+    /// Job disappeared while the tile is still a wall
+    Failed,
+    /// Removed by [`cancel_designation`]
+    Cancelled,
+}
+
+impl DesignationStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            DesignationStatus::Pending => "pending",
+            DesignationStatus::Duplicate => "duplicate",
+            DesignationStatus::Queued => "queued",
+            DesignationStatus::Assigned(_) => "assigned",
+            DesignationStatus::InProgress(_) => "in progress",
+            DesignationStatus::Unreachable => "unreachable",
+            DesignationStatus::Dangerous => "dangerous",
+            DesignationStatus::Done => "done",
+            DesignationStatus::Failed => "failed",
+            DesignationStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the status can no longer change
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            DesignationStatus::Done | DesignationStatus::Failed | DesignationStatus::Cancelled
+        )
+    }
+}
+
+/// A designation's status changed (see [`DesignationEvents`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DesignationStatusChange {
+    pub tick: u64,
+    pub designation: Entity,
+    pub pos: (i32, i32),
+    pub status: DesignationStatus,
+    pub issued_by: IssuedBy,
+}
+
+/// Bounded queue of designation status changes for UIs to drain
+#[derive(Resource, Debug, Clone)]
+pub struct DesignationEvents {
+    events: VecDeque<DesignationStatusChange>,
+    /// Oldest changes are dropped beyond this many
+    pub capacity: usize,
+}
+
+impl Default for DesignationEvents {
+    fn default() -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity: 256,
+        }
+    }
+}
+
+impl DesignationEvents {
+    pub fn push(&mut self, change: DesignationStatusChange) {
+        self.events.push_back(change);
+        while self.events.len() > self.capacity {
+            self.events.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DesignationStatusChange> {
+        self.events.iter()
+    }
+
+    /// Take all pending changes, oldest first
+    pub fn drain(&mut self) -> Vec<DesignationStatusChange> {
+        self.events.drain(..).collect()
+    }
+}
+
 /// Configuration resource for designation behavior
 /// Controls how designations are processed and converted to jobs
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Clone)]
 pub struct DesignationConfig {
     /// Whether to automatically create jobs from designations
     /// When true, Active designations are automatically converted to jobs
     /// When false, designations remain in place without creating jobs
     pub auto_jobs: bool,
+    /// Fog-of-war rule: mine designations only count on tiles that are
+    /// explored or adjacent to explored tiles (see `fov::Explored`)
+    /// Others wait as Undiscovered until exploration reaches them
+    pub require_discovery: bool,
+    /// Accessibility rule: mine designations only count when a miner can
+    /// walk to a tile next to them (see [`designation_access_system`])
+    /// Others wait as Unreachable until mining opens a way in
+    pub require_access: bool,
+    /// Safety rule: mine designations on walls next to water or lava (see
+    /// [`wall_hazard`]) wait as Dangerous until they carry
+    /// [`HazardConfirmed`], since breaching them would flood the fort
+    pub confirm_hazards: bool,
+    /// Cleanup policy for Consumed designations
+    pub consumed_cleanup: ConsumedCleanup,
+}
+
+/// System that applies the discovery rule from [`DesignationConfig`]
+/// Active designations on undiscovered tiles become Undiscovered, and are
+/// re-validated every run so they turn Active as exploration expands.
+/// Without an `Explored` resource no tile counts as discovered.
+/// Turning the rule off releases every Undiscovered designation.
+pub fn designation_discovery_system(
+    config: Res<DesignationConfig>,
+    explored: Option<Res<Explored>>,
+    mut q: Query<(&crate::world::Position, &mut DesignationLifecycle), With<MineDesignation>>,
+) {
+    for (pos, mut lifecycle) in q.iter_mut() {
+        let discovered =
+            !config.require_discovery || explored.as_ref().is_some_and(|e| e.borders(pos.0, pos.1));
+        match lifecycle.0 {
+            DesignationState::Active if !discovered => {
+                lifecycle.0 = DesignationState::Undiscovered;
+            }
+            DesignationState::Undiscovered if discovered => {
+                lifecycle.0 = DesignationState::Active;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// System that applies the accessibility rule from [`DesignationConfig`]
+/// Active designations with no reachable walkable neighbor become
+/// Unreachable, so enclosed walls never turn into jobs nobody can do.
+/// Reachable means flood-filled from any living miner; without miners any
+/// walkable neighbor counts. Unreachable designations are re-checked
+/// whenever the map changes. Turning the rule off releases them all.
+#[allow(clippy::type_complexity)]
+pub fn designation_access_system(
+    config: Res<DesignationConfig>,
+    map: Option<Res<GameMap>>,
+    miners: Query<&Position, (With<Miner>, Without<Dead>)>,
+    mut q: Query<(&Position, &mut DesignationLifecycle), With<MineDesignation>>,
+) {
+    let Some(map) = map else {
+        return;
+    };
+    if !config.require_access {
+        for (_, mut lifecycle) in q.iter_mut() {
+            if lifecycle.0 == DesignationState::Unreachable {
+                lifecycle.0 = DesignationState::Active;
+            }
+        }
+        return;
+    }
+    // Only new designations and map edits can change the answer
+    let pending = |s: DesignationState| {
+        s == DesignationState::Active || (map.is_changed() && s == DesignationState::Unreachable)
+    };
+    if !q.iter().any(|(_, l)| pending(l.0)) {
+        return;
+    }
+    let region = (!miners.is_empty())
+        .then(|| crate::path::reachable_tiles(&map, miners.iter().map(|p| (p.0, p.1))));
+    for (pos, mut lifecycle) in q.iter_mut() {
+        if !pending(lifecycle.0) {
+            continue;
+        }
+        let next = if crate::path::adjacent_reachable(&map, region.as_ref(), pos.0, pos.1) {
+            DesignationState::Active
+        } else {
+            DesignationState::Unreachable
+        };
+        if lifecycle.0 != next {
+            lifecycle.0 = next;
+        }
+    }
+}
+
+/// System that applies the safety rule from [`DesignationConfig`]
+/// Active designations on walls next to water or lava become Dangerous
+/// unless confirmed with [`HazardConfirmed`]; Dangerous ones turn Active
+/// once confirmed or once the hazard or wall is gone. Newly Dangerous
+/// designations, whether flagged here or spawned so by
+/// [`designate_mine_cells`], raise one warning per run. Turning the rule
+/// off releases them all.
+#[allow(clippy::type_complexity)]
+pub fn designation_safety_system(
+    config: Res<DesignationConfig>,
+    map: Option<Res<GameMap>>,
+    time: Option<Res<Time>>,
+    announcements: Option<ResMut<Announcements>>,
+    mut q: Query<
+        (&Position, &mut DesignationLifecycle, Has<HazardConfirmed>),
+        With<MineDesignation>,
+    >,
+) {
+    let Some(map) = map else {
+        return;
+    };
+    let mut flagged = 0;
+    for (pos, mut lifecycle, confirmed) in q.iter_mut() {
+        let dangerous =
+            config.confirm_hazards && !confirmed && wall_hazard(&map, pos.0, pos.1).is_some();
+        match lifecycle.0 {
+            DesignationState::Active if dangerous => {
+                lifecycle.0 = DesignationState::Dangerous;
+                flagged += 1;
+            }
+            DesignationState::Dangerous if !dangerous => {
+                lifecycle.0 = DesignationState::Active;
+            }
+            DesignationState::Dangerous if lifecycle.is_changed() => flagged += 1,
+            _ => {}
+        }
+    }
+    if let (true, Some(mut announcements)) = (flagged > 0, announcements) {
+        let s = if flagged == 1 { "" } else { "s" };
+        announcements.push(
+            time.map_or(0, |t| t.ticks),
+            Severity::Warning,
+            format!("{flagged} mining designation{s} next to water or lava need confirming"),
+        );
+    }
 }
 
 /// System that deduplicates designations by marking later ones at the same position as Ignored
 /// Prevents multiple jobs from being created for the same location
 /// Uses a two-pass approach to avoid borrowing conflicts while maintaining deterministic behavior
 ///
-/// The system preserves the first designation at each position and marks subsequent ones as Ignored.
+/// The system preserves the lowest designation entity at each position and marks the others as Ignored.
 /// Only Active designations are considered for deduplication - Ignored and Consumed designations are left unchanged.
 pub fn designation_dedup_system(
     mut q_designations: Query<
@@ -76,10 +588,12 @@ pub fn designation_dedup_system(
 
     // Find entities to mark as ignored (all but first at each position)
     let mut entities_to_ignore: Vec<Entity> = Vec::new();
-    for entities in position_map.values() {
+    for entities in position_map.values_mut() {
         if entities.len() > 1 {
-            // Keep the first entity at this position, mark the rest as ignored
-            // This ensures deterministic behavior regardless of query order
+            // Keep the lowest entity at this position, mark the rest as
+            // ignored; query order shifts with archetype moves, so it
+            // cannot pick the survivor
+            entities.sort();
             entities_to_ignore.extend(entities.iter().skip(1));
         }
     }
@@ -98,27 +612,342 @@ pub fn designation_dedup_system(
 ///
 /// Only runs when auto_jobs is enabled in DesignationConfig
 /// Uses deterministic RNG to ensure reproducible job IDs
+///
+/// At most [`JobThrottle::designations_per_tick`] are converted per run,
+/// taken round-robin across issuers (and factions), each in entity order,
+/// so one big designation cannot hold back everyone else's. The rest stay
+/// Active for later runs. Jobs carry their designation's
+/// [`DesignationPriority`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn designation_to_jobs_system(
+    mut commands: Commands,
     config: Res<DesignationConfig>,
+    throttle: Option<Res<JobThrottle>>,
+    state: Option<ResMut<ThrottleState>>,
+    mut detached: Local<ThrottleState>,
+    time: Option<Res<Time>>,
     mut board: ResMut<JobBoard>,
     mut rng: ResMut<DeterministicRng>,
-    mut q: Query<(&crate::world::Position, &mut DesignationLifecycle), With<MineDesignation>>,
+    mut q: Query<
+        (
+            Entity,
+            &crate::world::Position,
+            &mut DesignationLifecycle,
+            Option<&IssuedBy>,
+            Option<&Faction>,
+            Option<&DesignationPriority>,
+        ),
+        With<MineDesignation>,
+    >,
 ) {
     if !config.auto_jobs {
         return;
     }
+    let consumed_at = time.map_or(0, |t| t.ticks);
+    let throttle = throttle.as_deref().copied().unwrap_or_default();
+    let cap = throttle.allowance(throttle.designations_per_tick, &board);
 
     // Only process active designations and mark them consumed to prevent duplicates
-    for (pos, mut lifecycle) in q.iter_mut() {
-        if lifecycle.0 == DesignationState::Active {
-            // Create a mining job for this designation
-            add_job(
-                &mut board,
-                JobKind::Mine { x: pos.0, y: pos.1 },
-                &mut rng.job_rng,
-            );
-            // Mark designation as consumed so it won't create another job
-            lifecycle.0 = DesignationState::Consumed;
+    let mut pending: Vec<(IssuedBy, FactionKind, Entity)> = q
+        .iter()
+        .filter(|(_, _, lifecycle, ..)| lifecycle.0 == DesignationState::Active)
+        .map(|(entity, _, _, issued_by, faction, _)| {
+            (
+                issued_by.copied().unwrap_or_default(),
+                faction_of(faction),
+                entity,
+            )
+        })
+        .collect();
+    pending.sort();
+    let mut sources: BTreeMap<(IssuedBy, FactionKind), VecDeque<Entity>> = BTreeMap::new();
+    for (issued_by, faction, entity) in pending {
+        sources
+            .entry((issued_by, faction))
+            .or_default()
+            .push_back(entity);
+    }
+    let cursor = match state {
+        Some(state) => &mut state.into_inner().designation_cursor,
+        None => &mut detached.designation_cursor,
+    };
+    let (batch, last) = round_robin(sources, cursor.as_ref(), cap);
+    *cursor = last;
+    for entity in batch {
+        let (_, pos, mut lifecycle, issued_by, faction, priority) =
+            q.get_mut(entity).expect("listed above");
+        // Create a mining job for this designation, owned by its issuer
+        // and worked by its faction (goblins unless it has a Faction)
+        let job = add_job_for(
+            &mut board,
+            JobKind::Mine { x: pos.0, y: pos.1 },
+            issued_by.copied().unwrap_or_default(),
+            faction_of(faction),
+            rng.stream(RngStream::Job, "designation_to_jobs_system"),
+        );
+        if let (Some(priority), Some(posted)) = (priority, board.0.last_mut()) {
+            posted.priority = priority.0;
+        }
+        // Mark designation as consumed so it won't create another job
+        lifecycle.0 = DesignationState::Consumed;
+        commands
+            .entity(entity)
+            .insert(DesignationJob { job, consumed_at });
+    }
+}
+
+/// Despawn Consumed designations according to
+/// [`DesignationConfig::consumed_cleanup`]
+/// A job counts as complete once it is on neither the board nor the active
+/// list, so jobs returned to the board keep their designation alive.
+pub fn designation_cleanup_system(
+    mut commands: Commands,
+    config: Res<DesignationConfig>,
+    time: Option<Res<Time>>,
+    board: Res<JobBoard>,
+    active: Res<ActiveJobs>,
+    q: Query<(Entity, &DesignationLifecycle, &DesignationJob)>,
+) {
+    let now = time.map_or(0, |t| t.ticks);
+    let done: Box<dyn Fn(&DesignationJob) -> bool> = match config.consumed_cleanup {
+        ConsumedCleanup::Keep => return,
+        ConsumedCleanup::OnJobComplete => {
+            let live: HashSet<JobId> = board
+                .0
+                .iter()
+                .map(|j| j.id)
+                .chain(active.jobs.keys().copied())
+                .collect();
+            Box::new(move |link| !live.contains(&link.job))
+        }
+        ConsumedCleanup::AfterTicks(grace) => {
+            Box::new(move |link| now.saturating_sub(link.consumed_at) >= grace)
+        }
+    };
+    for (entity, lifecycle, link) in q.iter() {
+        if lifecycle.0 == DesignationState::Consumed && done(link) {
+            commands.entity(entity).despawn();
         }
     }
 }
+
+/// Where a job currently sits, gathered once per status update
+struct JobLocations {
+    queued: HashSet<JobId>,
+    active: HashSet<JobId>,
+    /// Worker holding each job and that worker's position
+    workers: HashMap<JobId, (Entity, (i32, i32))>,
+}
+
+fn resolve_status(
+    lifecycle: DesignationState,
+    link: Option<&DesignationJob>,
+    pos: (i32, i32),
+    jobs: &JobLocations,
+    map: Option<&GameMap>,
+) -> DesignationStatus {
+    let job = match (lifecycle, link) {
+        (DesignationState::Ignored, _) => return DesignationStatus::Duplicate,
+        (DesignationState::Unreachable, _) => return DesignationStatus::Unreachable,
+        (DesignationState::Dangerous, _) => return DesignationStatus::Dangerous,
+        (DesignationState::Consumed, Some(link)) => link.job,
+        _ => return DesignationStatus::Pending,
+    };
+    if jobs.queued.contains(&job) {
+        return DesignationStatus::Queued;
+    }
+    if jobs.active.contains(&job) {
+        return match jobs.workers.get(&job) {
+            Some(&(worker, (wx, wy))) if (wx - pos.0).abs() <= 1 && (wy - pos.1).abs() <= 1 => {
+                DesignationStatus::InProgress(worker)
+            }
+            Some(&(worker, _)) => DesignationStatus::Assigned(worker),
+            // Active but unheld: about to be returned or dropped
+            None => DesignationStatus::Queued,
+        };
+    }
+    let still_wall = map.is_some_and(|m| m.get_tile(pos.0, pos.1) == Some(TileKind::Wall));
+    if still_wall {
+        DesignationStatus::Failed
+    } else {
+        DesignationStatus::Done
+    }
+}
+
+/// Keep each designation's [`DesignationStatus`] current and report changes
+/// to [`DesignationEvents`]
+/// Runs after job execution so a finished job is reported as `Done` before
+/// [`designation_cleanup_system`] can despawn its designation.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn designation_status_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    board: Res<JobBoard>,
+    active: Res<ActiveJobs>,
+    map: Option<Res<GameMap>>,
+    events: Option<ResMut<DesignationEvents>>,
+    q_workers: Query<(Entity, &AssignedJob, &Position)>,
+    mut q: Query<
+        (
+            Entity,
+            &Position,
+            &DesignationLifecycle,
+            Option<&DesignationJob>,
+            Option<&mut DesignationStatus>,
+            Option<&IssuedBy>,
+        ),
+        With<MineDesignation>,
+    >,
+) {
+    let jobs = JobLocations {
+        queued: board.0.iter().map(|j| j.id).collect(),
+        active: active.jobs.keys().copied().collect(),
+        workers: q_workers
+            .iter()
+            .filter_map(|(e, assigned, p)| assigned.0.map(|job| (job, (e, (p.0, p.1)))))
+            .collect(),
+    };
+    let tick = time.map_or(0, |t| t.ticks);
+    let mut events = events;
+    for (entity, pos, lifecycle, link, status, issued_by) in q.iter_mut() {
+        if status.as_deref().is_some_and(|s| s.is_final()) {
+            continue;
+        }
+        let next = resolve_status(lifecycle.0, link, (pos.0, pos.1), &jobs, map.as_deref());
+        let changed = match status {
+            Some(mut current) => current.set_if_neq(next),
+            None => {
+                commands.entity(entity).insert(next);
+                true
+            }
+        };
+        if let (true, Some(events)) = (changed, events.as_deref_mut()) {
+            events.push(DesignationStatusChange {
+                tick,
+                designation: entity,
+                pos: (pos.0, pos.1),
+                status: next,
+                issued_by: issued_by.copied().unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Current status of `designation`, computed from the world
+///
+/// Does not depend on [`designation_status_system`] having run, so it is
+/// exact between ticks. Returns `None` for entities that are not mining
+/// designations.
+pub fn designation_status(world: &World, designation: Entity) -> Option<DesignationStatus> {
+    let e = world.get_entity(designation)?;
+    e.get::<MineDesignation>()?;
+    let pos = *e.get::<Position>()?;
+    let lifecycle = e.get::<DesignationLifecycle>()?.0;
+    let link = e.get::<DesignationJob>();
+    if let Some(&status) = e.get::<DesignationStatus>().filter(|s| s.is_final()) {
+        return Some(status);
+    }
+    let mut jobs = JobLocations {
+        queued: HashSet::new(),
+        active: HashSet::new(),
+        workers: HashMap::new(),
+    };
+    if let Some(link) = link {
+        let job = link.job;
+        if world
+            .get_resource::<JobBoard>()
+            .is_some_and(|b| b.0.iter().any(|j| j.id == job))
+        {
+            jobs.queued.insert(job);
+        }
+        if world
+            .get_resource::<ActiveJobs>()
+            .is_some_and(|a| a.jobs.contains_key(&job))
+        {
+            jobs.active.insert(job);
+        }
+        if let Some(worker) = world
+            .iter_entities()
+            .find(|w| w.get::<AssignedJob>().and_then(|a| a.0) == Some(job))
+        {
+            let wp = worker.get::<Position>().map_or(pos, |p| *p);
+            jobs.workers.insert(job, (worker.id(), (wp.0, wp.1)));
+        }
+    }
+    Some(resolve_status(
+        lifecycle,
+        link,
+        (pos.0, pos.1),
+        &jobs,
+        world.get_resource::<GameMap>(),
+    ))
+}
+
+/// Withdraw a mining designation and the job it created
+///
+/// The job is taken off the board or out of the active list, and a worker
+/// holding it is released. The designation is despawned and a `Cancelled`
+/// change is reported. Returns false when `designation` is not a mining
+/// designation.
+pub fn cancel_designation(world: &mut World, designation: Entity) -> bool {
+    let Some(e) = world.get_entity(designation) else {
+        return false;
+    };
+    if e.get::<MineDesignation>().is_none() {
+        return false;
+    }
+    let pos = e.get::<Position>().map_or((0, 0), |p| (p.0, p.1));
+    let issued_by = e.get::<IssuedBy>().copied().unwrap_or_default();
+    if let Some(job) = e.get::<DesignationJob>().map(|l| l.job) {
+        if let Some(mut board) = world.get_resource_mut::<JobBoard>() {
+            board.0.retain(|j| j.id != job);
+        }
+        if let Some(mut active) = world.get_resource_mut::<ActiveJobs>() {
+            active.jobs.remove(&job);
+        }
+        let mut q = world.query::<&mut AssignedJob>();
+        for mut assigned in q.iter_mut(world) {
+            if assigned.0 == Some(job) {
+                assigned.0 = None;
+            }
+        }
+    }
+    world.despawn(designation);
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    if let Some(mut events) = world.get_resource_mut::<DesignationEvents>() {
+        events.push(DesignationStatusChange {
+            tick,
+            designation,
+            pos,
+            status: DesignationStatus::Cancelled,
+            issued_by,
+        });
+    }
+    true
+}
+
+/// Whether `canceller` may withdraw a designation issued by `issued_by`
+///
+/// Anyone may withdraw system designations; player and script ones only by
+/// their own issuer.
+pub fn may_cancel(canceller: IssuedBy, issued_by: IssuedBy) -> bool {
+    issued_by == IssuedBy::System || issued_by == canceller
+}
+
+/// Withdraw every mining designation issued by `issued_by`
+///
+/// Returns how many were cancelled. Designations without an [`IssuedBy`]
+/// count as [`IssuedBy::System`].
+pub fn cancel_designations_by(world: &mut World, issued_by: IssuedBy) -> usize {
+    let mut q = world.query_filtered::<(Entity, Option<&IssuedBy>), With<MineDesignation>>();
+    let mut doomed: Vec<Entity> = q
+        .iter(world)
+        .filter(|(_, by)| by.copied().unwrap_or_default() == issued_by)
+        .map(|(e, _)| e)
+        .collect();
+    doomed.sort();
+    doomed
+        .into_iter()
+        .filter(|&d| cancel_designation(world, d))
+        .count()
+}
@@ -0,0 +1,225 @@
+//! Determinism guard: keep wall clocks and unseeded randomness out of ticks
+//!
+//! A tick must depend only on the world and its seed. Four pieces help keep
+//! it that way:
+//!
+//! - [`audit_source`] is a lint over source text that flags wall-clock reads
+//!   and unseeded RNGs (`Instant::now`, `SystemTime`, `thread_rng`, ...).
+//!   The test suite runs it over every module of this crate; the only
+//!   sanctioned way to reach those is through [`wall_clock`] and
+//!   [`thread_rng`] here.
+//! - With the `determinism-guard` feature, [`run_tick`] marks the calling
+//!   thread as inside a tick, and [`wall_clock`] and [`thread_rng`] panic
+//!   when called there. Without the feature they are plain pass-throughs.
+//! - [`DeterministicRng::stream`] hands out the seeded streams by name and
+//!   records which system drew from which ([`RngAudit`]), for audit logs.
+//! - [`stable_order`] gives systems where the first entity met wins an
+//!   iteration order that does not depend on spawn order or archetype
+//!   layout.
+//!
+//! [`DeterministicRng::stream`]: crate::systems::DeterministicRng::stream
+
+use crate::world::{Name, Position};
+use bevy_ecs::prelude::*;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// One of the seeded streams in [`DeterministicRng`]
+///
+/// [`DeterministicRng`]: crate::systems::DeterministicRng
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RngStream {
+    Mapgen,
+    Job,
+    Combat,
+    Pathfinding,
+    Names,
+}
+
+impl RngStream {
+    pub fn label(self) -> &'static str {
+        match self {
+            RngStream::Mapgen => "mapgen",
+            RngStream::Job => "job",
+            RngStream::Combat => "combat",
+            RngStream::Pathfinding => "pathfinding",
+            RngStream::Names => "names",
+        }
+    }
+}
+
+/// Which system drew from which stream, and how often
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RngAudit {
+    uses: BTreeMap<(&'static str, RngStream), u64>,
+}
+
+impl RngAudit {
+    pub(crate) fn record(&mut self, system: &'static str, stream: RngStream) {
+        *self.uses.entry((system, stream)).or_default() += 1;
+    }
+
+    /// Times `system` took a handle to `stream`
+    pub fn uses(&self, system: &str, stream: RngStream) -> u64 {
+        self.uses
+            .iter()
+            .filter(|((s, st), _)| *s == system && *st == stream)
+            .map(|(_, &n)| n)
+            .sum()
+    }
+
+    /// Streams `system` has drawn from, in stream order
+    pub fn streams_used_by(&self, system: &str) -> Vec<RngStream> {
+        self.uses
+            .keys()
+            .filter(|(s, _)| *s == system)
+            .map(|&(_, stream)| stream)
+            .collect()
+    }
+
+    /// One line per system and stream, e.g. `resolve_attack: combat x4`
+    pub fn log_lines(&self) -> Vec<String> {
+        self.uses
+            .iter()
+            .map(|((system, stream), n)| format!("{}: {} x{}", system, stream.label(), n))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.uses.clear();
+    }
+}
+
+#[cfg(feature = "determinism-guard")]
+thread_local! {
+    static TICK_DEPTH: std::cell::Cell<u32> = const { std::cell::Cell::new(0) };
+}
+
+/// Marks the thread as inside a tick until dropped, even on panic
+#[cfg(feature = "determinism-guard")]
+struct TickScope;
+
+#[cfg(feature = "determinism-guard")]
+impl TickScope {
+    fn enter() -> Self {
+        TICK_DEPTH.with(|d| d.set(d.get() + 1));
+        TickScope
+    }
+}
+
+#[cfg(feature = "determinism-guard")]
+impl Drop for TickScope {
+    fn drop(&mut self) {
+        TICK_DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
+/// Entities sorted into a stable processing order
+///
+/// Query iteration follows archetype and table layout, which follows spawn
+/// order and shifts whenever components come and go. Systems where the
+/// first entity met wins (handing out jobs, claiming items, dropping
+/// duplicates) walk their entities in this order instead, so the winner
+/// depends on the world's contents rather than on how it was built:
+/// row-major by [`Position`] (entities without one first), then by
+/// [`Name`] (unnamed first), and only then by entity.
+pub fn stable_order<'a>(
+    entities: impl IntoIterator<Item = (Entity, Option<&'a Position>, Option<&'a Name>)>,
+) -> Vec<Entity> {
+    let mut keyed: Vec<_> = entities
+        .into_iter()
+        .map(|(e, pos, name)| (pos.map(|p| (p.1, p.0)), name.map(|n| n.0.as_str()), e))
+        .collect();
+    keyed.sort_unstable();
+    keyed.into_iter().map(|(.., e)| e).collect()
+}
+
+/// Run one tick of `schedule`
+///
+/// The simulation's own loops go through here rather than calling
+/// [`Schedule::run`] directly, so the guard knows when a tick is under way.
+/// Systems run on the calling thread (bevy's single-threaded executor).
+pub fn run_tick(schedule: &mut Schedule, world: &mut World) {
+    #[cfg(feature = "determinism-guard")]
+    let _scope = TickScope::enter();
+    schedule.run(world);
+}
+
+/// Whether the calling thread is inside [`run_tick`]
+///
+/// Always false without the `determinism-guard` feature.
+pub fn in_tick() -> bool {
+    #[cfg(feature = "determinism-guard")]
+    {
+        TICK_DEPTH.with(|d| d.get() > 0)
+    }
+    #[cfg(not(feature = "determinism-guard"))]
+    {
+        false
+    }
+}
+
+fn forbid_in_tick(what: &str) {
+    if in_tick() {
+        panic!(
+            "{} used during a simulation tick; draw from DeterministicRng or count ticks instead",
+            what
+        );
+    }
+}
+
+/// The current instant, for pacing and timing outside ticks
+///
+/// Panics inside a tick under the `determinism-guard` feature.
+pub fn wall_clock() -> Instant {
+    forbid_in_tick("the wall clock");
+    Instant::now()
+}
+
+/// An unseeded RNG, for things that must not repeat across runs (never
+/// simulation state)
+///
+/// Panics inside a tick under the `determinism-guard` feature.
+pub fn thread_rng() -> rand::rngs::ThreadRng {
+    forbid_in_tick("rand::thread_rng");
+    rand::thread_rng()
+}
+
+/// Source patterns [`audit_source`] flags
+pub const FORBIDDEN_PATTERNS: [&str; 5] = [
+    "Instant::now",
+    "SystemTime",
+    "thread_rng",
+    "rand::random",
+    "OsRng",
+];
+
+/// A forbidden pattern found by [`audit_source`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeterminismViolation {
+    /// 1-based line number
+    pub line: usize,
+    pub pattern: &'static str,
+}
+
+/// Find wall-clock reads and unseeded RNGs in Rust source text
+///
+/// Comment lines are skipped, so docs may mention the patterns.
+pub fn audit_source(text: &str) -> Vec<DeterminismViolation> {
+    let mut found = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let code = line.trim_start();
+        if code.starts_with("//") {
+            continue;
+        }
+        for pattern in FORBIDDEN_PATTERNS {
+            if code.contains(pattern) {
+                found.push(DeterminismViolation {
+                    line: i + 1,
+                    pattern,
+                });
+            }
+        }
+    }
+    found
+}
@@ -0,0 +1,247 @@
+//! Event director: raids scaled to what the fort is worth
+//!
+//! The [`EventDirector`] keeps track of the fort's wealth (the summed
+//! [`material_item_value`] of every item) and population (living goblin
+//! workers), reassessed every [`DirectorConfig::assess_interval`] ticks.
+//! Together they make the fort's threat score:
+//!
+//! ```text
+//! threat = (wealth / wealth_per_threat + population * threat_per_goblin)
+//!          * difficulty / 100
+//! ```
+//!
+//! where the difficulty is the site's [`RegionDifficulty`]: harsher biomes
+//! draw bigger, more frequent raids. The tuning [`Curve`]s in
+//! [`DirectorConfig`] turn the score into the size of a raid and the wait
+//! until the next one.
+//!
+//! Raids are scheduled by tick, never rolled: the first arrives once
+//! [`DirectorConfig::grace_ticks`] have passed, and each raid schedules
+//! the next from the threat at the time. Raiders enter together at a
+//! map-edge floor tile picked on the combat stream; some of them
+//! ([`DirectorConfig::burrower_percent`]) are [`Burrower`]s. Raids are
+//! counted in [`RunMetrics::raids`].
+
+use crate::burrowing::Burrower;
+use crate::components::{AssignedJob, Dead, Faction, FactionKind, Health, Item};
+use crate::determinism::RngStream;
+use crate::jobs::faction_of;
+use crate::metrics::RunMetrics;
+use crate::quality::{material_item_value, Quality};
+use crate::systems::{DeterministicRng, Time};
+use crate::world::{GameMap, Material, Position, Velocity};
+use bevy_ecs::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Piecewise-linear tuning curve through `(x, y)` points sorted by `x`
+///
+/// Inputs before the first point or past the last take that point's `y`;
+/// an empty curve is 0 everywhere.
+/// ```
+/// use gc_core::director::Curve;
+///
+/// let curve = Curve(vec![(0, 10), (100, 20)]);
+/// assert_eq!(curve.sample(50), 15);
+/// assert_eq!(curve.sample(500), 20);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Curve(pub Vec<(u32, u32)>);
+
+impl Curve {
+    /// Value of the curve at `x`, rounded down between points
+    pub fn sample(&self, x: u32) -> u32 {
+        let points = &self.0;
+        let Some(&(first_x, first_y)) = points.first() else {
+            return 0;
+        };
+        if x <= first_x {
+            return first_y;
+        }
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if x <= x1 {
+                let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+                let y = y0 + (y1 - y0) * (x as i64 - x0) / (x1 - x0).max(1);
+                return y as u32;
+            }
+        }
+        points[points.len() - 1].1
+    }
+}
+
+/// Raid tuning
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectorConfig {
+    /// No raid arrives before this tick
+    pub grace_ticks: u64,
+    /// Ticks between wealth and population assessments
+    pub assess_interval: u64,
+    /// Item value worth one point of threat
+    pub wealth_per_threat: u32,
+    /// Threat points per living goblin
+    pub threat_per_goblin: u32,
+    /// Raiders per raid, by threat
+    pub raid_size: Curve,
+    /// Ticks from one raid to the next, by threat
+    pub raid_interval: Curve,
+    /// Percent of each raid (rounded down) that burrows
+    pub burrower_percent: u32,
+}
+
+impl Default for DirectorConfig {
+    fn default() -> Self {
+        Self {
+            grace_ticks: 6000,
+            assess_interval: 100,
+            wealth_per_threat: 10,
+            threat_per_goblin: 5,
+            raid_size: Curve(vec![(0, 1), (20, 2), (60, 4), (150, 8)]),
+            raid_interval: Curve(vec![(0, 6000), (60, 4000), (150, 2400)]),
+            burrower_percent: 25,
+        }
+    }
+}
+
+/// Percent scaling of raid threat for the embarked site
+///
+/// Set from the biomes' [`BiomeParams::raid_difficulty`] at embark; 100
+/// elsewhere.
+///
+/// [`BiomeParams::raid_difficulty`]: crate::overmap::BiomeParams::raid_difficulty
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionDifficulty(pub u32);
+
+impl Default for RegionDifficulty {
+    fn default() -> Self {
+        Self(100)
+    }
+}
+
+/// What the director last saw of the fort, and when the next raid comes
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventDirector {
+    /// Summed value of every item, at the last assessment
+    pub wealth: u32,
+    /// Living goblin workers, at the last assessment
+    pub population: u32,
+    /// Threat score from the last assessment
+    pub threat: u32,
+    /// Tick of the last assessment
+    pub assessed_at: Option<u64>,
+    /// Tick the next raid arrives; `None` until the first is scheduled
+    pub next_raid: Option<u64>,
+    /// Size of the last raid sent
+    pub last_raid: u32,
+}
+
+impl EventDirector {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Threat score for a fort worth `wealth` with `population` goblins
+pub fn threat_score(config: &DirectorConfig, difficulty: u32, wealth: u32, population: u32) -> u32 {
+    let base = u64::from(wealth / config.wealth_per_threat.max(1))
+        + u64::from(population) * u64::from(config.threat_per_goblin);
+    (base * u64::from(difficulty) / 100).min(u64::from(u32::MAX)) as u32
+}
+
+/// Floor tiles along the map's edge, in row-major order
+fn entry_tiles(map: &GameMap) -> Vec<(i32, i32)> {
+    let (w, h) = (map.width as i32, map.height as i32);
+    map.iter_tiles()
+        .filter(|&((x, y), _)| x == 0 || y == 0 || x == w - 1 || y == h - 1)
+        .filter(|&((x, y), _)| map.is_walkable(x, y))
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// Assess the fort and send raids when they are due
+///
+/// A raid with nowhere to enter (no floor on the map's edge) is skipped;
+/// the next one is scheduled as usual.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn event_director_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    config: Option<Res<DirectorConfig>>,
+    difficulty: Option<Res<RegionDifficulty>>,
+    director: Option<ResMut<EventDirector>>,
+    mut rng: Option<ResMut<DeterministicRng>>,
+    mut metrics: Option<ResMut<RunMetrics>>,
+    map: Res<GameMap>,
+    items: Query<(&Item, Option<&Material>, Option<&Quality>)>,
+    goblins: Query<Option<&Faction>, (With<AssignedJob>, Without<Dead>)>,
+) {
+    let Some(mut director) = director else {
+        return;
+    };
+    let config = config.as_deref().cloned().unwrap_or_default();
+    let difficulty = difficulty.as_deref().copied().unwrap_or_default().0;
+    let tick = time.map_or(0, |t| t.ticks);
+    let raid_due = director.next_raid.is_some_and(|t| tick >= t);
+    let assess_due = director
+        .assessed_at
+        .map_or(true, |t| tick >= t + config.assess_interval.max(1));
+    if assess_due || raid_due {
+        let wealth = items
+            .iter()
+            .map(|(item, material, quality)| {
+                u64::from(material_item_value(
+                    item.item_type,
+                    material.copied(),
+                    quality.copied(),
+                ))
+            })
+            .sum::<u64>()
+            .min(u64::from(u32::MAX)) as u32;
+        let population = goblins
+            .iter()
+            .filter(|f| faction_of(*f) == FactionKind::Goblins)
+            .count() as u32;
+        director.wealth = wealth;
+        director.population = population;
+        director.threat = threat_score(&config, difficulty, wealth, population);
+        director.assessed_at = Some(tick);
+    }
+    if director.next_raid.is_none() {
+        director.next_raid = Some(config.grace_ticks);
+    }
+    if !raid_due {
+        return;
+    }
+
+    let threat = director.threat;
+    director.next_raid = Some(tick + u64::from(config.raid_interval.sample(threat)).max(1));
+    let size = config.raid_size.sample(threat);
+    let entries = entry_tiles(&map);
+    if size == 0 || entries.is_empty() {
+        return;
+    }
+    let entry = match rng.as_deref_mut() {
+        Some(rng) => {
+            let rng = rng.stream(RngStream::Combat, "event_director_system");
+            entries[rng.gen_range(0..entries.len())]
+        }
+        None => entries[0],
+    };
+    let burrowers = size * config.burrower_percent.min(100) / 100;
+    for i in 0..size {
+        let mut raider = commands.spawn((
+            Position(entry.0, entry.1),
+            Velocity(0, 0),
+            Health::full(10),
+            Faction::new(FactionKind::Invaders),
+        ));
+        if i < burrowers {
+            raider.insert(Burrower::default());
+        }
+    }
+    director.last_raid = size;
+    if let Some(metrics) = metrics.as_deref_mut() {
+        metrics.raids += 1;
+    }
+}
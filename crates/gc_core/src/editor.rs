@@ -0,0 +1,170 @@
+//! Scenario editing operations
+//!
+//! The map editor paints tiles and places workers, items, and stockpiles on a
+//! world whose schedule is not running. Each [`Brush`] stroke is a direct
+//! `World` mutation; the result is persisted with `save::save_world`, which
+//! keeps worker roles and stockpile zones so edited scenarios load back
+//! playable.
+
+use crate::components::{AssignedJob, Carriable, Carrier, Inventory, Item, Miner, Stone};
+use crate::components::{MiningSkill, VisionRadius, ZoneBounds};
+use crate::stockpiles::StockpileBundle;
+use crate::world::{GameMap, Name, Position, TileKind, Velocity};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// What a stroke at the cursor does
+///
+/// Serialized as its [`label`](Brush::label), as in tutorial setups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Brush {
+    Tile(TileKind),
+    Miner,
+    Carrier,
+    Stone,
+    /// Rectangle between two strokes (see [`place_stockpile`])
+    Stockpile,
+    /// Remove workers, items, and stockpiles at the cursor
+    Erase,
+}
+
+impl Brush {
+    /// Brushes in selection order (number keys 1-9 in the TUI editor)
+    pub const ALL: [Brush; 9] = [
+        Brush::Tile(TileKind::Floor),
+        Brush::Tile(TileKind::Wall),
+        Brush::Tile(TileKind::Water),
+        Brush::Tile(TileKind::Lava),
+        Brush::Miner,
+        Brush::Carrier,
+        Brush::Stone,
+        Brush::Stockpile,
+        Brush::Erase,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Brush::Tile(TileKind::Floor) => "floor",
+            Brush::Tile(TileKind::Wall) => "wall",
+            Brush::Tile(TileKind::Water) => "water",
+            Brush::Tile(TileKind::Lava) => "lava",
+            Brush::Miner => "miner",
+            Brush::Carrier => "carrier",
+            Brush::Stone => "stone",
+            Brush::Stockpile => "stockpile",
+            Brush::Erase => "erase",
+        }
+    }
+}
+
+impl FromStr for Brush {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase();
+        Brush::ALL
+            .into_iter()
+            .find(|b| b.label() == name)
+            .ok_or_else(|| format!("unknown brush '{}'", s))
+    }
+}
+
+impl TryFrom<String> for Brush {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Brush> for String {
+    fn from(brush: Brush) -> Self {
+        brush.label().to_string()
+    }
+}
+
+/// Apply a single-tile brush at (x, y)
+///
+/// Returns false when (x, y) is outside the map or the brush needs two
+/// corners ([`Brush::Stockpile`]).
+pub fn paint(world: &mut World, x: i32, y: i32, brush: Brush) -> bool {
+    let in_bounds = world
+        .get_resource::<GameMap>()
+        .is_some_and(|m| m.in_bounds(x, y));
+    if !in_bounds {
+        return false;
+    }
+    match brush {
+        Brush::Tile(kind) => return world.resource_mut::<GameMap>().set_tile(x, y, kind).is_ok(),
+        Brush::Miner => {
+            world.spawn((
+                Position(x, y),
+                Velocity(0, 0),
+                Miner,
+                MiningSkill::STARTING,
+                AssignedJob::default(),
+                VisionRadius::new(8),
+            ));
+        }
+        Brush::Carrier => {
+            world.spawn((
+                Position(x, y),
+                Velocity(0, 0),
+                Carrier,
+                Inventory::default(),
+                AssignedJob::default(),
+                VisionRadius::new(8),
+            ));
+        }
+        Brush::Stone => {
+            world.spawn((
+                Item::stone(),
+                Stone,
+                Position(x, y),
+                Carriable,
+                Name("Stone".into()),
+            ));
+        }
+        Brush::Stockpile => return false,
+        Brush::Erase => erase(world, x, y),
+    }
+    true
+}
+
+/// Place a stockpile spanning two corners (in any order)
+///
+/// Returns false when either corner is off the map.
+pub fn place_stockpile(world: &mut World, a: (i32, i32), b: (i32, i32)) -> bool {
+    let in_bounds = world
+        .get_resource::<GameMap>()
+        .is_some_and(|m| m.in_bounds(a.0, a.1) && m.in_bounds(b.0, b.1));
+    if !in_bounds {
+        return false;
+    }
+    world
+        .spawn(StockpileBundle::new(
+            a.0.min(b.0),
+            a.1.min(b.1),
+            a.0.max(b.0),
+            a.1.max(b.1),
+        ))
+        .insert(Name("Stockpile".into()));
+    true
+}
+
+fn erase(world: &mut World, x: i32, y: i32) {
+    let mut q = world.query::<(Entity, Option<&Position>, Option<&ZoneBounds>)>();
+    let doomed: Vec<Entity> = q
+        .iter(world)
+        .filter(|(_, pos, zone)| match zone {
+            Some(z) => z.contains(x, y),
+            None => pos.is_some_and(|p| (p.0, p.1) == (x, y)),
+        })
+        .map(|(e, ..)| e)
+        .collect();
+    for entity in doomed {
+        world.despawn(entity);
+    }
+}
@@ -0,0 +1,344 @@
+//! Weapon and armor slots, equip jobs, and squad uniforms
+//!
+//! Wearable items carry a [`Gear`] component naming the slot they fit and the
+//! stat bonuses they grant. An entity's [`Equipment`] points at the gear it
+//! wears; equipped gear leaves the map (no `Position`) and is tagged
+//! [`EquippedBy`] until dropped again.
+//!
+//! Squads demand [`Uniform`]s: every member of a [`Squad`] whose uniform
+//! lists a slot it has not filled gets a self-assigned `JobKind::Equip` to
+//! fetch matching gear from a stockpile, and gear worn outside the uniform is
+//! taken off with `JobKind::Unequip`.
+
+use crate::components::{
+    AssignedJob, CombatStats, Dead, Faction, ItemType, MovementOverride, Stockpile, ZoneBounds,
+};
+use crate::determinism::RngStream;
+use crate::health::{Resting, Stalled};
+use crate::jobs::{faction_of, new_job_for, ActiveJobs, IssuedBy, Job, JobBoard, JobId, JobKind};
+use crate::path::PathService;
+use crate::quality::Quality;
+use crate::systems::{travel, DeterministicRng, MovementConfig};
+use crate::world::{GameMap, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Body slot a piece of gear occupies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipSlot {
+    Weapon,
+    Armor,
+}
+
+impl EquipSlot {
+    pub const ALL: [EquipSlot; 2] = [EquipSlot::Weapon, EquipSlot::Armor];
+
+    /// Slot an item type is worn in, if it is wearable at all
+    pub fn for_item(item_type: ItemType) -> Option<Self> {
+        match item_type {
+            ItemType::Weapon => Some(EquipSlot::Weapon),
+            ItemType::Armor => Some(EquipSlot::Armor),
+            ItemType::Stone
+            | ItemType::Gem
+            | ItemType::Ore
+            | ItemType::Coal
+            | ItemType::Wood
+            | ItemType::Bar
+            | ItemType::Egg
+            | ItemType::Wool
+            | ItemType::Block => None,
+        }
+    }
+}
+
+/// Wearable item: its slot and the stat bonuses it grants
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gear {
+    pub slot: EquipSlot,
+    pub attack: i32,
+    pub defense: i32,
+}
+
+impl Gear {
+    /// Default gear stats for a wearable item type
+    pub fn for_item(item_type: ItemType) -> Option<Self> {
+        EquipSlot::for_item(item_type).map(|slot| match slot {
+            EquipSlot::Weapon => Gear {
+                slot,
+                attack: 2,
+                defense: 0,
+            },
+            EquipSlot::Armor => Gear {
+                slot,
+                attack: 0,
+                defense: 2,
+            },
+        })
+    }
+
+    /// Bonuses as (attack, defense), with quality improving the slot's stat
+    pub fn bonus(&self, quality: Option<Quality>) -> (i32, i32) {
+        let q = quality.unwrap_or_default().combat_bonus();
+        match self.slot {
+            EquipSlot::Weapon => (self.attack + q, self.defense),
+            EquipSlot::Armor => (self.attack, self.defense + q),
+        }
+    }
+}
+
+/// Gear an entity is wearing, one item per slot
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Equipment {
+    pub weapon: Option<Entity>,
+    pub armor: Option<Entity>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipSlot) -> Option<Entity> {
+        match slot {
+            EquipSlot::Weapon => self.weapon,
+            EquipSlot::Armor => self.armor,
+        }
+    }
+
+    /// Put `item` in `slot`, returning whatever was there before
+    pub fn set(&mut self, slot: EquipSlot, item: Option<Entity>) -> Option<Entity> {
+        let target = match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+        };
+        std::mem::replace(target, item)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        EquipSlot::ALL.into_iter().filter_map(|s| self.get(s))
+    }
+}
+
+/// Marks gear currently worn by the given entity
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EquippedBy(pub Entity);
+
+/// Squad membership by squad id
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Squad(pub u32);
+
+/// Equipment set a squad's members must wear
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Uniform {
+    /// Slots that must be filled
+    pub slots: Vec<EquipSlot>,
+    /// Lowest acceptable gear quality (None = any)
+    #[serde(default)]
+    pub min_quality: Option<Quality>,
+}
+
+impl Uniform {
+    pub fn requires(&self, slot: EquipSlot) -> bool {
+        self.slots.contains(&slot)
+    }
+
+    fn accepts(&self, gear: &Gear, quality: Option<Quality>) -> bool {
+        self.requires(gear.slot)
+            && self
+                .min_quality
+                .map_or(true, |min| quality.unwrap_or_default() >= min)
+    }
+}
+
+/// Uniform demanded by each squad id
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SquadUniforms(pub BTreeMap<u32, Uniform>);
+
+/// Base stats of `entity` plus bonuses from everything it has equipped
+///
+/// Returns None when the entity has no [`CombatStats`].
+pub fn effective_stats(world: &World, entity: Entity) -> Option<CombatStats> {
+    let mut stats = world.get::<CombatStats>(entity)?.clone();
+    if let Some(equipment) = world.get::<Equipment>(entity) {
+        for item in equipment.iter() {
+            if let Some(gear) = world.get::<Gear>(item) {
+                let (attack, defense) = gear.bonus(world.get::<Quality>(item).copied());
+                stats.attack += attack;
+                stats.defense += defense;
+            }
+        }
+    }
+    Some(stats)
+}
+
+/// Post equip/unequip jobs so squad members match their uniform
+///
+/// Only idle members get jobs. Each request claims one stockpiled item so two
+/// members never walk to the same piece of gear.
+#[allow(clippy::type_complexity)]
+pub fn uniform_job_system(
+    uniforms: Res<SquadUniforms>,
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    stockpiles: Query<&ZoneBounds, With<Stockpile>>,
+    gear: Query<(Entity, &Position, &Gear, Option<&Quality>), Without<EquippedBy>>,
+    mut members: Query<
+        (
+            Entity,
+            &Squad,
+            &Position,
+            &Equipment,
+            &mut AssignedJob,
+            Option<&Faction>,
+        ),
+        (Without<Dead>, Without<Resting>),
+    >,
+) {
+    let mut claimed: HashSet<(i32, i32, EquipSlot)> = active
+        .jobs
+        .values()
+        .filter_map(|j| match j.kind {
+            JobKind::Equip { x, y, slot } => Some((x, y, slot)),
+            _ => None,
+        })
+        .collect();
+    let mut stored: Vec<(Entity, (i32, i32), Gear, Option<Quality>)> = gear
+        .iter()
+        .filter(|(_, p, ..)| stockpiles.iter().any(|z| z.contains(p.0, p.1)))
+        .map(|(e, p, g, q)| (e, (p.0, p.1), *g, q.copied()))
+        .collect();
+    stored.sort_by_key(|(e, ..)| *e);
+
+    let mut idle: Vec<_> = members.iter_mut().filter(|m| m.4 .0.is_none()).collect();
+    idle.sort_by_key(|(e, ..)| *e);
+    for (_, squad, pos, equipment, mut assigned, faction) in idle {
+        let Some(uniform) = uniforms.0.get(&squad.0) else {
+            continue;
+        };
+        let mut kind = EquipSlot::ALL
+            .into_iter()
+            .find(|s| equipment.get(*s).is_some() && !uniform.requires(*s))
+            .map(|slot| JobKind::Unequip { slot });
+        if kind.is_none() {
+            for slot in EquipSlot::ALL {
+                if !uniform.requires(slot) || equipment.get(slot).is_some() {
+                    continue;
+                }
+                let nearest = stored
+                    .iter()
+                    .filter(|(_, at, g, q)| {
+                        g.slot == slot
+                            && uniform.accepts(g, *q)
+                            && !claimed.contains(&(at.0, at.1, slot))
+                    })
+                    .min_by_key(|(_, at, ..)| (at.0 - pos.0).abs() + (at.1 - pos.1).abs());
+                if let Some((_, at, ..)) = nearest {
+                    claimed.insert((at.0, at.1, slot));
+                    kind = Some(JobKind::Equip {
+                        x: at.0,
+                        y: at.1,
+                        slot,
+                    });
+                    break;
+                }
+            }
+        }
+        let Some(kind) = kind else {
+            continue;
+        };
+        // Equip jobs are personal, never posted for others
+        let job = new_job_for(
+            &mut board,
+            kind,
+            IssuedBy::System,
+            faction_of(faction),
+            rng.stream(RngStream::Job, "uniform_job_system"),
+        );
+        assigned.0 = Some(job.id);
+        active.jobs.insert(job.id, job);
+    }
+}
+
+/// Walk to gear and put it on, or take gear off where the wearer stands
+///
+/// Equipped gear loses its `Position`; dropped gear gets the wearer's.
+#[allow(clippy::type_complexity)]
+pub fn equipment_execution_system(
+    mut commands: Commands,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut active: ResMut<ActiveJobs>,
+    gear: Query<(Entity, &Position, &Gear), (Without<EquippedBy>, Without<Equipment>)>,
+    mut wearers: Query<
+        (
+            Entity,
+            &mut Position,
+            &mut Equipment,
+            &mut AssignedJob,
+            Option<&MovementOverride>,
+        ),
+        (Without<Dead>, Without<Stalled>),
+    >,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let mut taken = HashSet::new();
+    for (wearer, mut pos, mut equipment, mut assigned, movement) in wearers.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        match active.jobs.get(&id) {
+            Some(Job {
+                kind: JobKind::Equip { x, y, slot },
+                ..
+            }) => {
+                let (x, y, slot) = (*x, *y, *slot);
+                let mode = config.mode_for(movement);
+                if !travel(&mut pos, (x, y), mode, map.as_deref(), paths.as_deref_mut()) {
+                    continue;
+                }
+                let mut here: Vec<Entity> = gear
+                    .iter()
+                    .filter(|(e, p, g)| {
+                        (p.0, p.1) == (x, y) && g.slot == slot && !taken.contains(e)
+                    })
+                    .map(|(e, ..)| e)
+                    .collect();
+                here.sort();
+                // Gone by the time we arrived: finish and let the uniform retry
+                if let Some(&item) = here.first() {
+                    taken.insert(item);
+                    commands
+                        .entity(item)
+                        .remove::<Position>()
+                        .insert(EquippedBy(wearer));
+                    if let Some(old) = equipment.set(slot, Some(item)) {
+                        drop_at(&mut commands, old, &pos);
+                    }
+                }
+                complete(&mut active, &mut assigned, id);
+            }
+            Some(Job {
+                kind: JobKind::Unequip { slot },
+                ..
+            }) => {
+                if let Some(old) = equipment.set(*slot, None) {
+                    drop_at(&mut commands, old, &pos);
+                }
+                complete(&mut active, &mut assigned, id);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn drop_at(commands: &mut Commands, item: Entity, pos: &Position) {
+    commands
+        .entity(item)
+        .remove::<EquippedBy>()
+        .insert(Position(pos.0, pos.1));
+}
+
+fn complete(active: &mut ActiveJobs, assigned: &mut AssignedJob, id: JobId) {
+    active.jobs.remove(&id);
+    assigned.0 = None;
+}
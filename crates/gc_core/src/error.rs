@@ -0,0 +1,63 @@
+//! Typed errors for core world APIs
+//!
+//! Helpers that change the world on request (picking items up, putting them
+//! down, writing map tiles and materials) say why they refused with a
+//! [`GcError`] instead of a bare `false`. Systems that hit one mid-tick do
+//! not stop; they hand it to [`GcError::report`], which files it as a
+//! [`SimFault`](crate::faults::SimFault) carrying the error's message.
+
+use crate::faults::{report_fault, FaultKind, SimFaults};
+use crate::systems::Time;
+use bevy_ecs::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum GcError {
+    #[error("entity {0:?} does not exist")]
+    InvalidEntity(Entity),
+    #[error("{0:?} is not on the map to be picked up")]
+    NotCarriable(Entity),
+    #[error("{0:?} has no inventory")]
+    NoInventory(Entity),
+    #[error("{agent:?} already carries {carried:?}")]
+    InventoryFull { agent: Entity, carried: Entity },
+    #[error("{0:?} is not carrying anything")]
+    NothingCarried(Entity),
+    #[error("({x}, {y}) is outside the {width}x{height} map")]
+    OutOfBounds {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl GcError {
+    /// Fault kind this error is filed under when a system reports it
+    pub fn fault_kind(&self) -> FaultKind {
+        match self {
+            GcError::InvalidEntity(_) | GcError::NotCarriable(_) => FaultKind::MissingItem,
+            GcError::NoInventory(_)
+            | GcError::InventoryFull { .. }
+            | GcError::NothingCarried(_) => FaultKind::InventoryMismatch,
+            GcError::OutOfBounds { .. } => FaultKind::OutOfBounds,
+        }
+    }
+
+    /// Report this error as a fault from `system`; see [`report_fault`]
+    pub fn report(
+        &self,
+        faults: Option<&mut SimFaults>,
+        time: Option<&Time>,
+        system: &'static str,
+        entity: Option<Entity>,
+    ) {
+        report_fault(
+            faults,
+            time,
+            self.fault_kind(),
+            system,
+            entity,
+            self.to_string(),
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Batch experiments: one scenario over many seeds
+//!
+//! Balancing mining yields, work costs or raid difficulty takes more than
+//! one colony. An [`Experiment`] builds a [`BenchProfile`] colony per seed,
+//! steps it headlessly and reads its [`RunMetrics`] at the end; the rows
+//! are written as CSV, one per seed (`gc_cli experiment --seeds 1..100
+//! --steps 5000 --metric stones_hauled`). Runs share nothing, so a seed
+//! list can be split across processes and the rows put back in order.
+
+use crate::bench::{bench_schedule, build_bench_world, BenchProfile};
+use crate::determinism::run_tick;
+use crate::metrics::RunMetrics;
+use crate::toggles::SystemToggles;
+use crate::yields::YieldTable;
+
+/// A seed list or metric name that cannot be used
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExperimentError {
+    #[error("bad seed list '{0}': use N, A..B, A..=B or N,M,...")]
+    Seeds(String),
+    #[error("seed list '{0}' has more than {max} seeds", max = MAX_SEEDS)]
+    TooManySeeds(String),
+    #[error("unknown metric '{0}' (one of {names})", names = RunMetrics::NAMES.join(", "))]
+    UnknownMetric(String),
+}
+
+/// Most seeds one batch may run
+pub const MAX_SEEDS: u64 = 1_000_000;
+
+/// Parse a seed list: a single seed, a range (`1..100` leaves out 100,
+/// `1..=100` includes it) or a comma-separated list
+///
+/// Ranges longer than [`MAX_SEEDS`] are refused before any seed is listed.
+pub fn parse_seeds(spec: &str) -> Result<Vec<u64>, ExperimentError> {
+    let bad = || ExperimentError::Seeds(spec.to_string());
+    let number = |s: &str| s.trim().parse::<u64>().map_err(|_| bad());
+    let capped = |len: u64| {
+        if len > MAX_SEEDS {
+            Err(ExperimentError::TooManySeeds(spec.to_string()))
+        } else {
+            Ok(())
+        }
+    };
+    let seeds: Vec<u64> = if let Some((a, b)) = spec.split_once("..=") {
+        let (a, b) = (number(a)?, number(b)?);
+        capped(b.saturating_sub(a).saturating_add(1))?;
+        (a..=b).collect()
+    } else if let Some((a, b)) = spec.split_once("..") {
+        let (a, b) = (number(a)?, number(b)?);
+        capped(b.saturating_sub(a))?;
+        (a..b).collect()
+    } else {
+        spec.split(',').map(number).collect::<Result<_, _>>()?
+    };
+    if seeds.is_empty() {
+        return Err(bad());
+    }
+    Ok(seeds)
+}
+
+/// Check metric names against [`RunMetrics::NAMES`]; no names means all
+pub fn parse_metrics(names: &[String]) -> Result<Vec<&'static str>, ExperimentError> {
+    if names.is_empty() {
+        return Ok(RunMetrics::NAMES.to_vec());
+    }
+    names
+        .iter()
+        .flat_map(|n| n.split(','))
+        .map(|name| {
+            RunMetrics::NAMES
+                .into_iter()
+                .find(|&known| known == name.trim())
+                .ok_or_else(|| ExperimentError::UnknownMetric(name.to_string()))
+        })
+        .collect()
+}
+
+/// The scenario every seed of a batch runs
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub profile: BenchProfile,
+    /// Ticks per run
+    pub steps: u64,
+    /// Yield table replacing the built-in one
+    pub yields: Option<YieldTable>,
+    pub toggles: SystemToggles,
+}
+
+impl Experiment {
+    /// Run the scenario with `seed` and return its final counters
+    pub fn run_seed(&self, seed: u64) -> RunMetrics {
+        let mut world = build_bench_world(self.profile, seed);
+        if let Some(table) = &self.yields {
+            world.insert_resource(table.clone());
+        }
+        world.insert_resource(self.toggles.clone());
+        let mut schedule = bench_schedule();
+        for _ in 0..self.steps {
+            run_tick(&mut schedule, &mut world);
+        }
+        world
+            .get_resource::<RunMetrics>()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// [`run_seed`](Self::run_seed) reduced to the chosen `metrics`
+    pub fn run_row(&self, seed: u64, metrics: &[&str]) -> ExperimentRow {
+        let totals = self.run_seed(seed);
+        ExperimentRow {
+            seed,
+            values: metrics
+                .iter()
+                .map(|name| totals.get(name).unwrap_or(0))
+                .collect(),
+        }
+    }
+}
+
+/// One seed's results, in the order of the metrics asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExperimentRow {
+    pub seed: u64,
+    pub values: Vec<u64>,
+}
+
+impl ExperimentRow {
+    /// The row as a CSV line (no newline)
+    pub fn to_csv(&self) -> String {
+        std::iter::once(self.seed)
+            .chain(self.values.iter().copied())
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Read back a line written by [`to_csv`](Self::to_csv)
+    pub fn from_csv(line: &str) -> Option<Self> {
+        let mut fields = line.trim().split(',').map(|f| f.parse::<u64>().ok());
+        let seed = fields.next()??;
+        let values = fields.collect::<Option<Vec<_>>>()?;
+        Some(Self { seed, values })
+    }
+}
+
+/// CSV header line for `metrics` (no newline)
+pub fn csv_header(metrics: &[&str]) -> String {
+    std::iter::once("seed")
+        .chain(metrics.iter().copied())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Spread of one metric across a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSummary {
+    pub name: &'static str,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+/// Min, max and mean of each metric over `rows`; empty without rows
+pub fn summarize(metrics: &[&'static str], rows: &[ExperimentRow]) -> Vec<MetricSummary> {
+    if rows.is_empty() {
+        return Vec::new();
+    }
+    metrics
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| {
+            let values = rows.iter().map(|r| r.values.get(i).copied().unwrap_or(0));
+            MetricSummary {
+                name,
+                min: values.clone().min().unwrap_or(0),
+                max: values.clone().max().unwrap_or(0),
+                mean: values.sum::<u64>() as f64 / rows.len() as f64,
+            }
+        })
+        .collect()
+}
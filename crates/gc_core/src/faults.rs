@@ -0,0 +1,162 @@
+//! Simulation faults: anomalies systems recover from but should not see
+//!
+//! Systems meet states that should not happen (a worker holding a job that
+//! no longer exists, a mine job on a tile that is not rock, a haul whose
+//! item is gone). They carry on as before (drop the job, skip the work),
+//! but also report a [`SimFault`] to the [`SimFaults`] resource so bugs
+//! show up in logs and metrics instead of passing silently. With
+//! [`SimFaults::strict`] set, the first fault panics; tests switch it on
+//! to catch systems that leave bad state behind.
+
+use crate::systems::Time;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// What kind of anomaly a system ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// A worker's assigned job is not among the active jobs
+    OrphanAssignment,
+    /// A mine job targets a tile that is off the map or not a wall
+    InvalidDesignationTarget,
+    /// An item a job relies on could not be found
+    MissingItem,
+    /// An inventory move the carrier's hands did not allow
+    InventoryMismatch,
+    /// A tile write landed off the map
+    OutOfBounds,
+}
+
+impl FaultKind {
+    pub const ALL: [FaultKind; 5] = [
+        FaultKind::OrphanAssignment,
+        FaultKind::InvalidDesignationTarget,
+        FaultKind::MissingItem,
+        FaultKind::InventoryMismatch,
+        FaultKind::OutOfBounds,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FaultKind::OrphanAssignment => "orphan_assignment",
+            FaultKind::InvalidDesignationTarget => "invalid_designation_target",
+            FaultKind::MissingItem => "missing_item",
+            FaultKind::InventoryMismatch => "inventory_mismatch",
+            FaultKind::OutOfBounds => "out_of_bounds",
+        }
+    }
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// One reported anomaly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimFault {
+    pub tick: u64,
+    pub kind: FaultKind,
+    /// Reporting system
+    pub system: &'static str,
+    /// Entity involved (usually the worker), if any
+    pub entity: Option<Entity>,
+    /// What was wrong, for logs
+    pub context: String,
+}
+
+impl fmt::Display for SimFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[tick {}] {} in {}", self.tick, self.kind, self.system)?;
+        if let Some(entity) = self.entity {
+            write!(f, " ({:?})", entity)?;
+        }
+        write!(f, ": {}", self.context)
+    }
+}
+
+/// Bounded queue of faults for logs to drain, with running counts
+#[derive(Resource, Debug, Clone)]
+pub struct SimFaults {
+    faults: VecDeque<SimFault>,
+    counts: BTreeMap<FaultKind, u64>,
+    /// Oldest faults are dropped beyond this many (counts keep them)
+    pub capacity: usize,
+    /// Panic on the first fault instead of recovering
+    pub strict: bool,
+}
+
+impl Default for SimFaults {
+    fn default() -> Self {
+        Self {
+            faults: VecDeque::new(),
+            counts: BTreeMap::new(),
+            capacity: 256,
+            strict: false,
+        }
+    }
+}
+
+impl SimFaults {
+    /// Faults that panic on the first report
+    pub fn strict() -> Self {
+        Self {
+            strict: true,
+            ..Default::default()
+        }
+    }
+
+    /// Record a fault, panicking instead in strict mode
+    pub fn push(&mut self, fault: SimFault) {
+        assert!(!self.strict, "simulation fault: {fault}");
+        *self.counts.entry(fault.kind).or_default() += 1;
+        self.faults.push_back(fault);
+        while self.faults.len() > self.capacity {
+            self.faults.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SimFault> {
+        self.faults.iter()
+    }
+
+    /// Take all retained faults, oldest first; counts are kept
+    pub fn drain(&mut self) -> Vec<SimFault> {
+        self.faults.drain(..).collect()
+    }
+
+    /// Faults of `kind` reported so far, drained or not
+    pub fn count(&self, kind: FaultKind) -> u64 {
+        self.counts.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// All faults reported so far
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+/// Report a fault from a system that may run without [`SimFaults`] or
+/// [`Time`]; nothing is recorded when the resource is missing
+pub fn report_fault(
+    faults: Option<&mut SimFaults>,
+    time: Option<&Time>,
+    kind: FaultKind,
+    system: &'static str,
+    entity: Option<Entity>,
+    context: impl Into<String>,
+) {
+    if let Some(faults) = faults {
+        faults.push(SimFault {
+            tick: time.map_or(0, |t| t.ticks),
+            kind,
+            system,
+            entity,
+            context: context.into(),
+        });
+    }
+}
@@ -0,0 +1,550 @@
+//! Fire: ignition, spread, burnout and firefighting
+//!
+//! Fire lives per tile in the [`FireMap`]. A tile can burn when it has
+//! fuel: soil floors carry [`FireConfig::tile_fuel`] until they have burned
+//! once, and every item tagged `flammable` (wood, coal) lying there adds
+//! [`FireConfig::item_fuel`]. Tiles ignite next to lava, or when something
+//! calls [`ignite`].
+//!
+//! [`fire_system`] advances every fire once per tick, with no randomness:
+//!
+//! 1. flammable items on burning tiles are consumed into the fire's fuel;
+//! 2. a fire that has burned [`FireConfig::spread_after`] ticks ignites each
+//!    flammable orthogonal neighbour;
+//! 3. each fire burns one unit of fuel, and a fire out of fuel leaves a
+//!    scorched tile that will not burn again on its own;
+//! 4. anything with [`Health`] standing in a fire takes
+//!    [`FireConfig::damage`].
+//!
+//! Every burning tile gets a [`JobKind::Extinguish`] that carriers take.
+//! A firefighter fills a bucket at the nearest water tile and douses the
+//! fire; on maps without water it beats the flames out instead, which takes
+//! [`FireConfig::smother_ticks`] of work. Doused tiles stay wet, and
+//! unburnable, for [`FireConfig::wet_ticks`].
+
+use crate::announcements::{Announcements, Severity};
+use crate::components::{AssignedJob, Carrier, Dead, Health, Item, MovementOverride};
+use crate::determinism::RngStream;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, faction_of, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId,
+    JobKind,
+};
+use crate::path::PathService;
+use crate::systems::{travel, DeterministicRng, MovementConfig, Time};
+use crate::tags::TagRegistry;
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Tag marking items that burn
+pub const FLAMMABLE_TAG: &str = "flammable";
+
+const NEIGHBOURS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// How fires burn and how they are fought
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FireConfig {
+    /// Fuel units an unburned soil floor carries
+    pub tile_fuel: u32,
+    /// Fuel units each flammable item adds
+    pub item_fuel: u32,
+    /// Ticks a fire burns before it ignites its neighbours
+    pub spread_after: u32,
+    /// Hit points lost per tick standing in a fire
+    pub damage: i32,
+    /// Ticks a doused tile stays too wet to burn
+    pub wet_ticks: u32,
+    /// Ticks of work to beat out a fire without water
+    pub smother_ticks: u32,
+}
+
+impl Default for FireConfig {
+    fn default() -> Self {
+        Self {
+            tile_fuel: 6,
+            item_fuel: 4,
+            spread_after: 3,
+            damage: 2,
+            wet_ticks: 30,
+            smother_ticks: 4,
+        }
+    }
+}
+
+/// One burning tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BurningTile {
+    pub x: i32,
+    pub y: i32,
+    /// Fuel units left
+    pub fuel: u32,
+    /// Ticks burned so far
+    pub age: u32,
+}
+
+/// Saved form of the [`FireMap`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FireData {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    burning: Vec<BurningTile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scorched: Vec<(i32, i32)>,
+    /// (x, y, ticks until dry)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    wet: Vec<(i32, i32, u32)>,
+}
+
+/// Extinguish job for a burning tile, and smothering work put into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FireJob {
+    id: JobId,
+    work: u32,
+}
+
+/// Per-tile fire state
+///
+/// Extinguish jobs are not saved; [`fire_job_system`] posts them again
+/// after loading.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "FireData", into = "FireData")]
+pub struct FireMap {
+    burning: BTreeMap<(i32, i32), BurningTile>,
+    scorched: BTreeSet<(i32, i32)>,
+    wet: BTreeMap<(i32, i32), u32>,
+    jobs: BTreeMap<(i32, i32), FireJob>,
+    /// Tiles next to lava, and the map revision they were found at
+    lava_edge: Option<(u64, Vec<(i32, i32)>)>,
+}
+
+impl From<FireData> for FireMap {
+    fn from(data: FireData) -> Self {
+        Self {
+            burning: data.burning.into_iter().map(|b| ((b.x, b.y), b)).collect(),
+            scorched: data.scorched.into_iter().collect(),
+            wet: data.wet.into_iter().map(|(x, y, t)| ((x, y), t)).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<FireMap> for FireData {
+    fn from(fire: FireMap) -> Self {
+        Self {
+            burning: fire.burning.into_values().collect(),
+            scorched: fire.scorched.into_iter().collect(),
+            wet: fire.wet.into_iter().map(|((x, y), t)| (x, y, t)).collect(),
+        }
+    }
+}
+
+impl FireMap {
+    pub fn is_burning(&self, x: i32, y: i32) -> bool {
+        self.burning.contains_key(&(x, y))
+    }
+
+    /// Burning tiles in (x, y) order
+    pub fn burning(&self) -> impl Iterator<Item = &BurningTile> {
+        self.burning.values()
+    }
+
+    pub fn is_scorched(&self, x: i32, y: i32) -> bool {
+        self.scorched.contains(&(x, y))
+    }
+
+    pub fn is_wet(&self, x: i32, y: i32) -> bool {
+        self.wet.contains_key(&(x, y))
+    }
+
+    /// Whether nothing burns, has burned or is wet
+    pub fn is_empty(&self) -> bool {
+        self.burning.is_empty() && self.scorched.is_empty() && self.wet.is_empty()
+    }
+
+    /// Put out the fire at (x, y), leaving the tile wet; false when it was
+    /// not burning
+    pub fn douse(&mut self, x: i32, y: i32, wet_ticks: u32) -> bool {
+        if self.burning.remove(&(x, y)).is_none() {
+            return false;
+        }
+        self.wet.insert((x, y), wet_ticks);
+        true
+    }
+
+    /// Fuel an unburning tile offers: its floor, if soil and unburned
+    fn tile_fuel(
+        &self,
+        map: &GameMap,
+        materials: Option<&MaterialMap>,
+        config: &FireConfig,
+        (x, y): (i32, i32),
+    ) -> u32 {
+        let soil = map.get_tile(x, y) == Some(TileKind::Floor)
+            && materials.and_then(|m| m.get(x, y)) == Some(Material::Soil);
+        if soil && !self.is_scorched(x, y) {
+            config.tile_fuel
+        } else {
+            0
+        }
+    }
+
+    /// Set (x, y) alight if it has fuel and is neither burning nor wet
+    fn try_ignite(&mut self, at: (i32, i32), tile_fuel: u32, item_fuel: u32) -> bool {
+        if self.burning.contains_key(&at) || self.wet.contains_key(&at) {
+            return false;
+        }
+        if tile_fuel + item_fuel == 0 {
+            return false;
+        }
+        // Item fuel arrives as the fire consumes the items
+        self.burning.insert(
+            at,
+            BurningTile {
+                x: at.0,
+                y: at.1,
+                fuel: tile_fuel,
+                age: 0,
+            },
+        );
+        true
+    }
+
+    fn lava_edge(&mut self, map: &GameMap) -> &[(i32, i32)] {
+        let stale = self
+            .lava_edge
+            .as_ref()
+            .map_or(true, |(rev, _)| *rev != map.revision());
+        if stale {
+            let mut edge: BTreeSet<(i32, i32)> = BTreeSet::new();
+            for (x, y) in map.find_tiles(TileKind::Lava) {
+                for (dx, dy) in NEIGHBOURS {
+                    let n = (x + dx, y + dy);
+                    if map.get_tile(n.0, n.1) == Some(TileKind::Floor) {
+                        edge.insert(n);
+                    }
+                }
+            }
+            self.lava_edge = Some((map.revision(), edge.into_iter().collect()));
+        }
+        &self.lava_edge.as_ref().expect("computed above").1
+    }
+}
+
+/// Flammable items lying on each tile
+fn flammable_items(
+    items: &Query<(Entity, &Position, &Item)>,
+    registry: &TagRegistry,
+) -> BTreeMap<(i32, i32), Vec<Entity>> {
+    let mut by_tile: BTreeMap<(i32, i32), Vec<Entity>> = BTreeMap::new();
+    for (entity, pos, item) in items.iter() {
+        if registry.has_tag(item.item_type, FLAMMABLE_TAG) {
+            by_tile.entry((pos.0, pos.1)).or_default().push(entity);
+        }
+    }
+    for list in by_tile.values_mut() {
+        list.sort();
+    }
+    by_tile
+}
+
+/// Set (x, y) alight from outside the simulation (events, scripts, tests)
+///
+/// Returns false when the tile has nothing to burn, is already burning or
+/// is wet.
+pub fn ignite(world: &mut World, x: i32, y: i32) -> bool {
+    let config = world
+        .get_resource::<FireConfig>()
+        .copied()
+        .unwrap_or_default();
+    let registry = world
+        .get_resource::<TagRegistry>()
+        .cloned()
+        .unwrap_or_default();
+    let flammable = world
+        .query::<(&Position, &Item)>()
+        .iter(world)
+        .filter(|(p, i)| (p.0, p.1) == (x, y) && registry.has_tag(i.item_type, FLAMMABLE_TAG))
+        .count() as u32;
+    world.init_resource::<FireMap>();
+    world.resource_scope(|world, mut fire: Mut<FireMap>| {
+        let map = world.resource::<GameMap>();
+        let tile = fire.tile_fuel(map, world.get_resource::<MaterialMap>(), &config, (x, y));
+        fire.try_ignite((x, y), tile, flammable * config.item_fuel)
+    })
+}
+
+/// Advance every fire one tick (see the module docs for the steps)
+///
+/// Also dries wet tiles and lights fuel next to lava. The first fire on a
+/// fire-free map is announced.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn fire_system(
+    mut commands: Commands,
+    mut fire: ResMut<FireMap>,
+    map: Res<GameMap>,
+    materials: Option<Res<MaterialMap>>,
+    config: Option<Res<FireConfig>>,
+    registry: Option<Res<TagRegistry>>,
+    time: Option<Res<Time>>,
+    announcements: Option<ResMut<Announcements>>,
+    items: Query<(Entity, &Position, &Item)>,
+    mut victims: Query<(&Position, &mut Health), Without<Dead>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let fallback;
+    let registry = match registry.as_deref() {
+        Some(r) => r,
+        None => {
+            fallback = TagRegistry::default();
+            &fallback
+        }
+    };
+    let materials = materials.as_deref();
+    let was_quiet = fire.burning.is_empty();
+
+    fire.wet.retain(|_, ticks| {
+        *ticks = ticks.saturating_sub(1);
+        *ticks > 0
+    });
+
+    let flammable = flammable_items(&items, registry);
+    let fuel_at = |fire: &FireMap, at: (i32, i32)| {
+        let items = flammable.get(&at).map_or(0, |l| l.len() as u32);
+        (
+            fire.tile_fuel(&map, materials, &config, at),
+            items * config.item_fuel,
+        )
+    };
+
+    let edge = fire.lava_edge(&map).to_vec();
+    for at in edge {
+        let (tile, items) = fuel_at(&fire, at);
+        fire.try_ignite(at, tile, items);
+    }
+
+    // Consume flammable items in the flames
+    let burning: Vec<(i32, i32)> = fire.burning.keys().copied().collect();
+    for &at in &burning {
+        if let Some(list) = flammable.get(&at) {
+            for &item in list {
+                commands.entity(item).despawn();
+            }
+            let tile = fire.burning.get_mut(&at).expect("listed above");
+            tile.fuel += list.len() as u32 * config.item_fuel;
+        }
+    }
+
+    let mut spread: Vec<(i32, i32)> = Vec::new();
+    for &at in &burning {
+        let tile = fire.burning.get_mut(&at).expect("listed above");
+        tile.age += 1;
+        if tile.age == config.spread_after {
+            spread.extend(NEIGHBOURS.iter().map(|(dx, dy)| (at.0 + dx, at.1 + dy)));
+        }
+        tile.fuel = tile.fuel.saturating_sub(1);
+        if tile.fuel == 0 {
+            fire.burning.remove(&at);
+            fire.scorched.insert(at);
+        }
+    }
+    for at in spread {
+        let (tile, items) = fuel_at(&fire, at);
+        fire.try_ignite(at, tile, items);
+    }
+
+    for (pos, mut health) in victims.iter_mut() {
+        if fire.is_burning(pos.0, pos.1) {
+            health.take_damage(config.damage);
+        }
+    }
+
+    if was_quiet {
+        if let (Some(first), Some(mut announcements)) = (fire.burning().next(), announcements) {
+            let tick = time.map_or(0, |t| t.ticks);
+            announcements.push(
+                tick,
+                Severity::Warning,
+                format!("Fire has broken out at ({}, {})", first.x, first.y),
+            );
+        }
+    }
+}
+
+/// Post an extinguish job for every burning tile without one
+///
+/// Jobs for fires that went out are taken off the board; one already taken
+/// is dropped by [`extinguish_execution_system`].
+pub fn fire_job_system(
+    mut fire: ResMut<FireMap>,
+    mut board: ResMut<JobBoard>,
+    mut rng: ResMut<DeterministicRng>,
+) {
+    let out: Vec<(i32, i32)> = fire
+        .jobs
+        .keys()
+        .filter(|at| !fire.burning.contains_key(at))
+        .copied()
+        .collect();
+    for at in out {
+        let job = fire.jobs.remove(&at).expect("listed above");
+        board.0.retain(|j| j.id != job.id);
+    }
+    let unstaffed: Vec<(i32, i32)> = fire
+        .burning
+        .keys()
+        .filter(|at| !fire.jobs.contains_key(at))
+        .copied()
+        .collect();
+    for (x, y) in unstaffed {
+        let id = add_job_for(
+            &mut board,
+            JobKind::Extinguish { x, y },
+            IssuedBy::System,
+            faction_of(None),
+            rng.stream(RngStream::Job, "fire_job_system"),
+        );
+        fire.jobs.insert((x, y), FireJob { id, work: 0 });
+    }
+}
+
+/// Hand one queued extinguish job to an idle carrier of its faction per
+/// run, within its [`WorkArea`]
+///
+/// Runs before ordinary job assignment, so idle carriers fight fires
+/// before hauling.
+pub fn extinguish_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut carriers: Query<BoardWorker, (With<Carrier>, Without<Dead>)>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut carriers,
+        |kind| matches!(kind, JobKind::Extinguish { .. }),
+    );
+}
+
+/// A bucket of water, carried by a firefighter
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct WaterBucket;
+
+/// Nearest tile of `kind` to `from`, ties broken by (y, x)
+fn nearest_tile(map: &GameMap, from: (i32, i32), kind: TileKind) -> Option<(i32, i32)> {
+    map.find_tiles(kind)
+        .min_by_key(|&(x, y)| ((x - from.0).abs() + (y - from.1).abs(), y, x))
+}
+
+/// Walk toward the tile next to `target` on `pos`'s side; true once there
+fn approach(
+    pos: &mut Mut<Position>,
+    target: (i32, i32),
+    config: &MovementConfig,
+    movement: Option<&MovementOverride>,
+    map: &GameMap,
+    paths: Option<&mut PathService>,
+) -> bool {
+    let (dx, dy) = (pos.0 - target.0, pos.1 - target.1);
+    if dx.abs() <= 1 && dy.abs() <= 1 {
+        return true;
+    }
+    let beside = (target.0 + dx.signum(), target.1 + dy.signum());
+    travel(pos, beside, config.mode_for(movement), Some(map), paths);
+    false
+}
+
+/// Walk firefighters to water, then to their fire, and put it out
+///
+/// A firefighter without a bucket fills one next to the nearest water
+/// tile first; with no water on the map it smothers the fire by hand. A
+/// job whose fire already went out is dropped (a filled bucket is kept
+/// for the next one).
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn extinguish_execution_system(
+    mut commands: Commands,
+    map: Res<GameMap>,
+    mut paths: Option<ResMut<PathService>>,
+    config: Option<Res<FireConfig>>,
+    movement: Option<Res<MovementConfig>>,
+    mut fire: ResMut<FireMap>,
+    mut active: ResMut<ActiveJobs>,
+    mut workers: Query<
+        (
+            Entity,
+            &mut AssignedJob,
+            &mut Position,
+            Option<&MovementOverride>,
+            Has<WaterBucket>,
+        ),
+        (Without<Stalled>, Without<Dead>),
+    >,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let movement_config = movement.as_deref().copied().unwrap_or_default();
+    let mut water: HashMap<(i32, i32), Option<(i32, i32)>> = HashMap::new();
+    for (entity, mut assigned, mut pos, movement, bucket) in workers.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(JobKind::Extinguish { x, y }) = active.jobs.get(&id).map(|j| j.kind.clone())
+        else {
+            continue;
+        };
+        if !fire.is_burning(x, y) {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        }
+        if !bucket {
+            let from = (pos.0, pos.1);
+            let source = *water
+                .entry(from)
+                .or_insert_with(|| nearest_tile(&map, from, TileKind::Water));
+            if let Some(source) = source {
+                if approach(
+                    &mut pos,
+                    source,
+                    &movement_config,
+                    movement,
+                    &map,
+                    paths.as_deref_mut(),
+                ) {
+                    commands.entity(entity).insert(WaterBucket);
+                }
+                continue;
+            }
+        }
+        if !approach(
+            &mut pos,
+            (x, y),
+            &movement_config,
+            movement,
+            &map,
+            paths.as_deref_mut(),
+        ) {
+            continue;
+        }
+        if !bucket {
+            let job = fire.jobs.get_mut(&(x, y)).filter(|j| j.id == id);
+            let work = job.map_or(config.smother_ticks, |j| {
+                j.work += 1;
+                j.work
+            });
+            if work < config.smother_ticks {
+                continue;
+            }
+        } else {
+            commands.entity(entity).remove::<WaterBucket>();
+        }
+        fire.douse(x, y, config.wet_ticks);
+        fire.jobs.remove(&(x, y));
+        active.jobs.remove(&id);
+        assigned.0 = None;
+    }
+}
@@ -0,0 +1,161 @@
+//! Forbidden items: left where they lie until unforbidden
+//!
+//! A [`Forbidden`] item is invisible to the colony's logistics: auto-haul
+//! does not queue it, stations do not take it as a crafting input or fuel,
+//! and a carrier arriving to pick it up leaves it be. The player forbids and
+//! unforbids items a rectangle at a time with
+//! [`WorldCommand::ForbidItems`](crate::command::WorldCommand::ForbidItems)
+//! and [`WorldCommand::UnforbidItems`](crate::command::WorldCommand::UnforbidItems).
+//!
+//! [`danger_forbid_system`] also forbids items lying where the
+//! [`DangerMap`] says the colony should keep away, and lifts those forbids
+//! again once the danger has passed. Forbids the player placed are never
+//! lifted automatically. Unforbidden items go back into auto-haul's backlog.
+
+use crate::components::{Item, ZoneBounds};
+use crate::danger::DangerMap;
+use crate::jobs::{faction_of, JobBoard, JobKind};
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+use std::collections::HashSet;
+
+/// Item the colony leaves alone
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Forbidden {
+    /// Forbidden by [`danger_forbid_system`] rather than the player; lifted
+    /// again once the tile is safe
+    pub by_danger: bool,
+}
+
+/// When items are forbidden without the player asking
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForbidConfig {
+    /// Danger cost (see [`DangerMap::cost`]) at which items on a tile are
+    /// forbidden; `None` never forbids for danger
+    pub danger: Option<i32>,
+}
+
+impl Default for ForbidConfig {
+    fn default() -> Self {
+        Self { danger: Some(25) }
+    }
+}
+
+/// Forbid the items lying inside `bounds`; returns those newly forbidden
+///
+/// Items forbidden for danger become the player's forbids. Hauls still on
+/// the board from tiles left with nothing to pick up are withdrawn.
+pub fn forbid_items(world: &mut World, bounds: &ZoneBounds) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &Position, Option<&Forbidden>), With<Item>>();
+    let mut marked: Vec<(Entity, bool)> = q
+        .iter(world)
+        .filter(|(_, p, f)| bounds.contains(p.0, p.1) && f.map_or(true, |f| f.by_danger))
+        .map(|(e, _, f)| (e, f.is_none()))
+        .collect();
+    marked.sort();
+    for &(item, _) in &marked {
+        world
+            .entity_mut(item)
+            .insert(Forbidden { by_danger: false });
+    }
+    withdraw_forbidden_hauls(world);
+    marked
+        .into_iter()
+        .filter(|&(_, new)| new)
+        .map(|(e, _)| e)
+        .collect()
+}
+
+/// Lift every forbid on the items lying inside `bounds`; returns the items
+pub fn unforbid_items(world: &mut World, bounds: &ZoneBounds) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &Position), (With<Item>, With<Forbidden>)>();
+    let mut lifted: Vec<Entity> = q
+        .iter(world)
+        .filter(|(_, p)| bounds.contains(p.0, p.1))
+        .map(|(e, _)| e)
+        .collect();
+    lifted.sort();
+    for &item in &lifted {
+        world.entity_mut(item).remove::<Forbidden>();
+    }
+    lifted
+}
+
+/// Drop board hauls from tiles where every item is forbidden
+fn withdraw_forbidden_hauls(world: &mut World) {
+    let mut q = world.query_filtered::<(&Position, Has<Forbidden>), With<Item>>();
+    let mut haulable = HashSet::new();
+    let mut forbidden = HashSet::new();
+    for (pos, is_forbidden) in q.iter(world) {
+        if is_forbidden {
+            forbidden.insert((pos.0, pos.1));
+        } else {
+            haulable.insert((pos.0, pos.1));
+        }
+    }
+    if let Some(mut board) = world.get_resource_mut::<JobBoard>() {
+        withdraw_hauls(&mut board, &forbidden, &haulable);
+    }
+}
+
+/// Drop board hauls from tiles in `forbidden` but not in `haulable`,
+/// leaving the board untouched when there are none
+fn withdraw_hauls(
+    board: &mut Mut<JobBoard>,
+    forbidden: &HashSet<(i32, i32)>,
+    haulable: &HashSet<(i32, i32)>,
+) {
+    let blocked = |kind: &JobKind| match *kind {
+        JobKind::Haul { from, .. } => forbidden.contains(&from) && !haulable.contains(&from),
+        _ => false,
+    };
+    if board.0.iter().any(|job| blocked(&job.kind)) {
+        board.0.retain(|job| !blocked(&job.kind));
+    }
+}
+
+/// Forbid items on dangerous tiles, and lift those forbids once the tiles
+/// are safe again
+///
+/// Danger is the colony's (the local player's faction) at
+/// [`ForbidConfig::danger`]. Board hauls from tiles whose items are all
+/// forbidden are withdrawn.
+#[allow(clippy::type_complexity)]
+pub fn danger_forbid_system(
+    mut commands: Commands,
+    config: Option<Res<ForbidConfig>>,
+    danger: Option<Res<DangerMap>>,
+    board: Option<ResMut<JobBoard>>,
+    items: Query<(Entity, &Position, Option<&Forbidden>), With<Item>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let faction = faction_of(None);
+    let dangerous = |pos: &Position| match (config.danger, danger.as_deref()) {
+        (Some(threshold), Some(map)) => map.cost(faction, pos.0, pos.1) >= threshold,
+        _ => false,
+    };
+    let mut haulable = HashSet::new();
+    let mut forbidden = HashSet::new();
+    for (item, pos, forbid) in items.iter() {
+        let tile = (pos.0, pos.1);
+        match forbid {
+            None if dangerous(pos) => {
+                commands.entity(item).insert(Forbidden { by_danger: true });
+                forbidden.insert(tile);
+            }
+            Some(f) if f.by_danger && !dangerous(pos) => {
+                commands.entity(item).remove::<Forbidden>();
+                haulable.insert(tile);
+            }
+            Some(_) => {
+                forbidden.insert(tile);
+            }
+            None => {
+                haulable.insert(tile);
+            }
+        }
+    }
+    if let Some(board) = board {
+        withdraw_hauls(&mut board.into(), &forbidden, &haulable);
+    }
+}
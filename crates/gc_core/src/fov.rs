@@ -1,3 +1,4 @@
+use crate::lighting::{LightMap, LightingConfig};
 use crate::world::{GameMap, TileKind};
 use bevy_ecs::entity::Entity;
 use bevy_ecs::prelude::*;
@@ -45,34 +46,166 @@ pub struct Visibility {
     pub per_entity: HashMap<Entity, HashSet<(i32, i32)>>,
 }
 
+/// Every tile that has ever been visible to any entity
+#[derive(Resource, Default, Debug, Clone)]
+pub struct Explored {
+    pub tiles: HashSet<(i32, i32)>,
+}
+
+impl Explored {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.tiles.contains(&(x, y))
+    }
+
+    /// True when (x, y) is explored or touches an explored tile (8-neighbourhood)
+    pub fn borders(&self, x: i32, y: i32) -> bool {
+        (-1..=1).any(|dy| (-1..=1).any(|dx| self.contains(x + dx, y + dy)))
+    }
+}
+
+/// Fold this tick's visibility into the explored set; run after
+/// `compute_visibility_system`
+/// Only entities with a `VisionRadius` explore; designations and items also
+/// get visibility entries but are not observers.
+pub fn record_explored_system(
+    vis: Res<Visibility>,
+    mut explored: ResMut<Explored>,
+    observers: Query<(), With<crate::components::VisionRadius>>,
+) {
+    for (entity, tiles) in vis.per_entity.iter() {
+        if observers.contains(*entity) {
+            explored.tiles.extend(tiles.iter().copied());
+        }
+    }
+}
+
+/// Recompute each entity's visible tiles
+///
+/// Only entities that moved, changed `VisionRadius`, or have a changed tile
+/// within their radius since the last run are recomputed; the rest keep
+/// their previous set. Tile changes come from the map's change log
+/// ([`GameMap::changes_since`]); a new or replaced map, a resize, a log
+/// that no longer reaches back to the last run, or a map marked changed
+/// with nothing logged (direct `tiles` writes) recomputes everyone.
+///
+/// In [`LightingConfig::darkness`] mode only lit tiles (see [`LightMap`])
+/// are seen beyond the dark radius, and any change of light or lighting
+/// config recomputes everyone.
+#[allow(clippy::type_complexity)]
 pub fn compute_visibility_system(
     map: Res<GameMap>,
     mut vis: ResMut<Visibility>,
+    mut last: Local<Option<(usize, u64)>>,
+    lighting: Option<Res<LightingConfig>>,
+    light: Option<Res<LightMap>>,
     q: Query<(
         Entity,
-        &crate::world::Position,
-        Option<&crate::components::VisionRadius>,
+        Ref<crate::world::Position>,
+        Option<Ref<crate::components::VisionRadius>>,
     )>,
 ) {
-    let mut per = HashMap::new();
+    let since = match *last {
+        Some((len, revision)) if len == map.tiles.len() && !map.is_added() => map
+            .changes_since(revision)
+            .filter(|batch| !(map.is_changed() && batch.is_empty())),
+        _ => None,
+    };
+    let relit = lighting.as_ref().is_some_and(|c| c.is_changed())
+        || (lighting.as_ref().is_some_and(|c| c.darkness)
+            && light.as_ref().is_some_and(|l| l.is_changed()));
+    let resized = since.is_none() || relit;
+    let darkness = darkness(lighting.as_deref(), light.as_deref());
+    let changed_tiles: Vec<(i32, i32)> = since
+        .map(|batch| batch.changes.iter().map(|c| c.pos).collect())
+        .unwrap_or_default();
+    *last = Some((map.tiles.len(), map.revision()));
+
+    let mut per = HashMap::with_capacity(vis.per_entity.len());
     for (e, pos, vr) in q.iter() {
-        let mut visible = HashSet::new();
-        let r = vr.map(|v| v.0).unwrap_or(8);
-        for dy in -r..=r {
-            for dx in -r..=r {
-                let nx = pos.0 + dx;
-                let ny = pos.1 + dy;
-                if !map.in_bounds(nx, ny) {
-                    continue;
-                }
-                if (dx * dx + dy * dy) as f32 <= (r as f32 * r as f32)
-                    && los_visible(&map, pos.0, pos.1, nx, ny)
-                {
-                    visible.insert((nx, ny));
-                }
+        let r = vr.as_ref().map(|v| v.radius()).unwrap_or(8);
+        let stale = resized
+            || pos.is_changed()
+            || vr.as_ref().is_some_and(|v| v.is_changed())
+            || changed_tiles
+                .iter()
+                .any(|&(x, y)| (x - pos.0).abs() <= r && (y - pos.1).abs() <= r);
+        if !stale {
+            if let Some(previous) = vis.per_entity.remove(&e) {
+                per.insert(e, previous);
+                continue;
             }
         }
-        per.insert(e, visible);
+        per.insert(e, sight(&map, darkness, pos.0, pos.1, r));
     }
     vis.per_entity = per;
 }
+
+/// Recompute every entity's visibility from scratch and fold observers'
+/// into [`Explored`], inserting either resource if missing
+/// For use outside the schedule, e.g. right after loading; the result is
+/// what [`compute_visibility_system`] then [`record_explored_system`] give.
+pub fn refresh_visibility(world: &mut World) {
+    let mut q = world.query::<(
+        Entity,
+        &crate::world::Position,
+        Option<&crate::components::VisionRadius>,
+    )>();
+    let map = world.resource::<GameMap>();
+    let darkness = darkness(
+        world.get_resource::<LightingConfig>(),
+        world.get_resource::<LightMap>(),
+    );
+    let mut per = HashMap::new();
+    let mut seen = HashSet::new();
+    for (e, pos, vr) in q.iter(world) {
+        let tiles = sight(map, darkness, pos.0, pos.1, vr.map_or(8, |v| v.radius()));
+        if vr.is_some() {
+            seen.extend(tiles.iter().copied());
+        }
+        per.insert(e, tiles);
+    }
+    world.insert_resource(Visibility { per_entity: per });
+    world
+        .get_resource_or_insert_with(Explored::default)
+        .tiles
+        .extend(seen);
+}
+
+/// The light and dark radius to see by, when darkness limits sight
+fn darkness<'a>(
+    config: Option<&LightingConfig>,
+    light: Option<&'a LightMap>,
+) -> Option<(&'a LightMap, i32)> {
+    let config = config.filter(|c| c.darkness)?;
+    light.map(|l| (l, config.dark_radius))
+}
+
+/// Tiles seen from (x, y) with radius `r`: lit ones only beyond the dark
+/// radius when darkness applies
+fn sight(
+    map: &GameMap,
+    darkness: Option<(&LightMap, i32)>,
+    x: i32,
+    y: i32,
+    r: i32,
+) -> HashSet<(i32, i32)> {
+    let mut tiles = visible_from(map, x, y, r);
+    if let Some((light, dark)) = darkness {
+        tiles.retain(|&(nx, ny)| {
+            let (dx, dy) = (nx - x, ny - y);
+            dx * dx + dy * dy <= dark * dark || light.is_lit(nx, ny)
+        });
+    }
+    tiles
+}
+
+/// Tiles within radius `r` of (x, y) with line of sight
+pub(crate) fn visible_from(map: &GameMap, x: i32, y: i32, r: i32) -> HashSet<(i32, i32)> {
+    map.iter_rect((x - r, y - r), (x + r, y + r))
+        .map(|(p, _)| p)
+        .filter(|&(nx, ny)| {
+            let (dx, dy) = (nx - x, ny - y);
+            dx * dx + dy * dy <= r * r && los_visible(map, x, y, nx, ny)
+        })
+        .collect()
+}
@@ -0,0 +1,96 @@
+//! Haul priority: which loose items are hauled first
+//!
+//! [`haul_priority`] scores an item from its trade value, how close it lies
+//! to water or lava, and how long it has waited for a hauler. Auto-haul
+//! looks at high scores first when it is throttled and stamps each haul it
+//! posts with its item's score in [`Job::priority`]; carriers take the
+//! highest-priority haul on the board, oldest first among equals.
+//!
+//! Scores are integer arithmetic on the world state, so replays agree.
+//! Weights come from [`HaulPriorityConfig`]; a world without it uses the
+//! defaults.
+//!
+//! [`Job::priority`]: crate::jobs::Job::priority
+
+use crate::components::ItemType;
+use crate::quality::{item_value, Quality};
+use crate::world::{GameMap, TileKind};
+use bevy_ecs::prelude::*;
+
+/// Weights of the haul priority terms
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HaulPriorityConfig {
+    /// Points per unit of [`item_value`]
+    pub value_weight: u32,
+    /// Tiles within this many steps (diagonals count as one) of water or
+    /// lava are exposed
+    pub danger_radius: i32,
+    /// Points for lying on or next to lava; falls off linearly to nothing
+    /// past `danger_radius`
+    pub lava_weight: u32,
+    /// As `lava_weight`, for water
+    pub water_weight: u32,
+    /// Ticks waited per point of age; 0 ignores age
+    pub age_every: u64,
+    /// Most points waiting can add
+    pub age_cap: u32,
+}
+
+impl Default for HaulPriorityConfig {
+    fn default() -> Self {
+        Self {
+            value_weight: 1,
+            danger_radius: 3,
+            lava_weight: 40,
+            water_weight: 20,
+            age_every: 10,
+            age_cap: 20,
+        }
+    }
+}
+
+/// Steps from (x, y) to the nearest `kind` tile within `radius`
+pub fn distance_to_tile(map: &GameMap, x: i32, y: i32, kind: TileKind, radius: i32) -> Option<i32> {
+    (0..=radius.max(0)).find(|&r| {
+        (-r..=r).any(|dy| {
+            (-r..=r).any(|dx| {
+                (dx.abs() == r || dy.abs() == r) && map.get_tile(x + dx, y + dy) == Some(kind)
+            })
+        })
+    })
+}
+
+/// Points for lying near water or lava
+pub fn exposure(config: &HaulPriorityConfig, map: &GameMap, x: i32, y: i32) -> u32 {
+    let reach = config.danger_radius.max(0) as u32 + 1;
+    [
+        (TileKind::Lava, config.lava_weight),
+        (TileKind::Water, config.water_weight),
+    ]
+    .into_iter()
+    .filter_map(|(kind, weight)| {
+        distance_to_tile(map, x, y, kind, config.danger_radius)
+            .map(|d| weight * (reach - d as u32) / reach)
+    })
+    .max()
+    .unwrap_or(0)
+}
+
+/// How urgently an item at (x, y) that has waited `waited` ticks should
+/// be hauled; higher goes first
+pub fn haul_priority(
+    config: &HaulPriorityConfig,
+    map: Option<&GameMap>,
+    item_type: ItemType,
+    quality: Option<Quality>,
+    (x, y): (i32, i32),
+    waited: u64,
+) -> u32 {
+    let value = config.value_weight * item_value(item_type, quality);
+    let danger = map.map_or(0, |map| exposure(config, map, x, y));
+    let age = match config.age_every {
+        0 => 0,
+        every => (waited / every).min(config.age_cap as u64) as u32,
+    };
+    value + danger + age
+}
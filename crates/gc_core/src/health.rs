@@ -0,0 +1,290 @@
+//! Injuries, regeneration, and resting
+//!
+//! Hit points alone do not change behavior; this module turns them into an
+//! [`InjuryState`] that slows injured actors, lets resting actors slowly
+//! regenerate, and sends badly hurt workers to rest in the nearest free
+//! [`Bed`], preferring beds in a hospital zone. Without a free bed they lie
+//! down in a hospital zone, or where they stand when there is none.
+//!
+//! Slowdown is modeled by pacing: an injured entity only acts every
+//! [`InjuryState::action_interval`] ticks. On other ticks it carries the
+//! [`Stalled`] marker, which movement and job execution systems skip.
+
+use crate::components::{
+    AssignedJob, CarriedBy, Dead, Faction, FactionKind, Health, Inventory, MovementOverride,
+    ZoneBounds,
+};
+use crate::determinism::RngStream;
+use crate::jobs::{faction_of, new_job_for, ActiveJobs, IssuedBy, Job, JobBoard, JobId, JobKind};
+use crate::metrics::RunMetrics;
+use crate::path::PathService;
+use crate::systems::{travel, DeterministicRng, MovementConfig, Time};
+use crate::world::{GameMap, Position};
+use crate::zones::ZoneKind;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Ticks between +1 hp while resting
+pub const REGEN_INTERVAL: u64 = 10;
+
+/// Injury severity derived from remaining hit points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InjuryState {
+    Healthy,
+    /// Below 50% hp: acts every other tick
+    Wounded,
+    /// Below 25% hp: acts every fourth tick and seeks rest
+    Crippled,
+}
+
+impl InjuryState {
+    pub fn from_health(health: &Health) -> Self {
+        if health.max_hp <= 0 {
+            return InjuryState::Healthy;
+        }
+        let percent = health.hp * 100 / health.max_hp;
+        if percent < 25 {
+            InjuryState::Crippled
+        } else if percent < 50 {
+            InjuryState::Wounded
+        } else {
+            InjuryState::Healthy
+        }
+    }
+
+    /// An entity in this state acts only on ticks divisible by this
+    pub fn action_interval(self) -> u64 {
+        match self {
+            InjuryState::Healthy => 1,
+            InjuryState::Wounded => 2,
+            InjuryState::Crippled => 4,
+        }
+    }
+}
+
+/// Skips movement and work this tick (injury pacing)
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Stalled;
+
+/// Lying down to recover; regenerates hp
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Resting;
+
+/// Furniture an injured entity can rest in
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Bed;
+
+/// Mark entities whose hp reached zero as [`Dead`], counting goblin and
+/// invader deaths in [`RunMetrics`]
+#[allow(clippy::type_complexity)]
+pub fn death_system(
+    mut commands: Commands,
+    mut metrics: Option<ResMut<RunMetrics>>,
+    mut q: Query<
+        (
+            Entity,
+            &Health,
+            Option<&Position>,
+            Option<&mut Inventory>,
+            Option<&Faction>,
+        ),
+        Without<Dead>,
+    >,
+) {
+    for (entity, health, pos, inventory, faction) in q.iter_mut() {
+        if health.is_dead() {
+            commands.entity(entity).insert(Dead).remove::<Resting>();
+            if let Some(metrics) = metrics.as_deref_mut() {
+                match faction_of(faction) {
+                    FactionKind::Goblins => metrics.goblin_deaths += 1,
+                    FactionKind::Invaders => metrics.invader_deaths += 1,
+                    FactionKind::Neutral => {}
+                }
+            }
+            // The dead drop whatever they were carrying where they fell
+            if let (Some(pos), Some(mut inventory)) = (pos, inventory) {
+                if let Some(mut item) = inventory.0.take().and_then(|i| commands.get_entity(i)) {
+                    item.insert(*pos).remove::<CarriedBy>();
+                }
+            }
+        }
+    }
+}
+
+/// Insert or remove [`Stalled`] according to each entity's injury pace
+pub fn injury_pace_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    q: Query<(Entity, &Health, Has<Stalled>), Without<Dead>>,
+) {
+    for (entity, health, stalled) in q.iter() {
+        let interval = InjuryState::from_health(health).action_interval();
+        let should_stall = time.ticks % interval != 0;
+        if should_stall && !stalled {
+            commands.entity(entity).insert(Stalled);
+        } else if !should_stall && stalled {
+            commands.entity(entity).remove::<Stalled>();
+        }
+    }
+}
+
+/// Resting entities regain 1 hp every [`REGEN_INTERVAL`] ticks
+pub fn regeneration_system(
+    time: Res<Time>,
+    mut q: Query<&mut Health, (With<Resting>, Without<Dead>)>,
+) {
+    if time.ticks % REGEN_INTERVAL != 0 {
+        return;
+    }
+    for mut health in q.iter_mut() {
+        health.heal(1);
+    }
+}
+
+/// Send crippled workers to rest in the nearest free bed
+///
+/// Beds inside hospital zones are taken first, then any bed, then a free
+/// walkable hospital tile (nearest zone first, row by row). A worker's
+/// current job goes back to the board, unless it is carrying an item (it
+/// finishes the delivery first).
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn rest_job_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    map: Option<Res<GameMap>>,
+    beds: Query<&Position, With<Bed>>,
+    zones: Query<(Entity, &ZoneKind, &Position, &ZoneBounds)>,
+    mut workers: Query<
+        (
+            Entity,
+            &Health,
+            &Position,
+            &mut AssignedJob,
+            Option<&Inventory>,
+            Option<&Faction>,
+        ),
+        (Without<Dead>, Without<Resting>),
+    >,
+) {
+    // Beds already targeted by rest jobs are taken
+    let mut taken: HashSet<(i32, i32)> = active
+        .jobs
+        .values()
+        .filter_map(|j| match j.kind {
+            JobKind::Rest { x, y } => Some((x, y)),
+            _ => None,
+        })
+        .collect();
+    let mut free_beds: Vec<(i32, i32)> = beds.iter().map(|p| (p.0, p.1)).collect();
+    free_beds.sort_unstable();
+    let mut hospitals: Vec<(Entity, (i32, i32), &ZoneBounds)> = zones
+        .iter()
+        .filter(|(_, k, ..)| **k == ZoneKind::Hospital)
+        .map(|(e, _, p, b)| (e, (p.0, p.1), b))
+        .collect();
+    hospitals.sort_by_key(|(e, ..)| *e);
+    let in_hospital = |b: &(i32, i32)| hospitals.iter().any(|(_, _, z)| z.contains(b.0, b.1));
+
+    let mut hurt: Vec<_> = workers
+        .iter_mut()
+        .filter(|(_, h, ..)| InjuryState::from_health(h) == InjuryState::Crippled)
+        .collect();
+    hurt.sort_by_key(|(e, ..)| *e);
+    for (_, _, pos, mut assigned, inventory, faction) in hurt {
+        let current = assigned.0.and_then(|id| active.jobs.get(&id));
+        if current.is_some_and(|j| matches!(j.kind, JobKind::Rest { .. })) {
+            continue;
+        }
+        if inventory.is_some_and(|i| i.0.is_some()) {
+            continue;
+        }
+        if let Some(job) = assigned.0.and_then(|id| active.jobs.remove(&id)) {
+            board.0.push(job);
+        }
+        let dist = |b: &(i32, i32)| (b.0 - pos.0).abs() + (b.1 - pos.1).abs();
+        let bed = free_beds
+            .iter()
+            .filter(|b| !taken.contains(*b))
+            .min_by_key(|b| (!in_hospital(b), dist(b)))
+            .copied()
+            .or_else(|| {
+                let mut wards = hospitals.clone();
+                wards.sort_by_key(|(e, center, _)| (dist(center), *e));
+                wards.into_iter().find_map(|(_, _, z)| {
+                    (z.min_y..=z.max_y)
+                        .flat_map(|y| (z.min_x..=z.max_x).map(move |x| (x, y)))
+                        .find(|t| {
+                            !taken.contains(t)
+                                && map.as_deref().map_or(true, |m| m.is_walkable(t.0, t.1))
+                        })
+                })
+            })
+            .unwrap_or((pos.0, pos.1));
+        taken.insert(bed);
+
+        // Rest jobs are self-assigned, never posted for others
+        let job = new_job_for(
+            &mut board,
+            JobKind::Rest { x: bed.0, y: bed.1 },
+            IssuedBy::System,
+            faction_of(faction),
+            rng.stream(RngStream::Job, "rest_job_system"),
+        );
+        assigned.0 = Some(job.id);
+        active.jobs.insert(job.id, job);
+    }
+}
+
+/// Walk resting workers to their bed, rest there, and release them when healed
+#[allow(clippy::type_complexity)]
+pub fn rest_execution_system(
+    mut commands: Commands,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut active: ResMut<ActiveJobs>,
+    mut q: Query<
+        (
+            Entity,
+            &Health,
+            &mut Position,
+            &mut AssignedJob,
+            Has<Resting>,
+            Option<&MovementOverride>,
+        ),
+        (Without<Dead>, Without<Stalled>),
+    >,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    for (entity, health, mut pos, mut assigned, resting, movement) in q.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(Job {
+            kind: JobKind::Rest { x, y },
+            ..
+        }) = active.jobs.get(&id)
+        else {
+            continue;
+        };
+        let (x, y) = (*x, *y);
+        // Injury pacing slows the walk
+        let mode = config.mode_for(movement);
+        if !travel(&mut pos, (x, y), mode, map.as_deref(), paths.as_deref_mut()) {
+            continue;
+        }
+        if !resting {
+            commands.entity(entity).insert(Resting);
+        } else if health.hp >= health.max_hp {
+            commands.entity(entity).remove::<Resting>();
+            complete(&mut active, &mut assigned, id);
+        }
+    }
+}
+
+fn complete(active: &mut ActiveJobs, assigned: &mut AssignedJob, id: JobId) {
+    active.jobs.remove(&id);
+    assigned.0 = None;
+}
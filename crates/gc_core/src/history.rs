@@ -0,0 +1,314 @@
+//! Entity history: what each worker has been doing lately
+//!
+//! [`EntityHistory`] keeps a short timeline per entity: jobs taken and
+//! finished or given up, attacks made and taken, rests, and death. Attacks
+//! are recorded by [`resolve_attack`](crate::combat::resolve_attack) as they
+//! happen. [`job_history_system`] notes jobs as soon as they are handed out,
+//! and [`history_system`] picks up the rest from component changes at the
+//! end of each tick. Each timeline is bounded twice: by
+//! [`EntityHistory::capacity`] entries and by [`EntityHistory::max_age`]
+//! ticks, so "what has Grak been doing for the last 500 ticks?" is one call
+//! to [`entity_history`]. Timelines of despawned entities are dropped.
+
+use crate::combat::AttackOutcome;
+use crate::components::{AssignedJob, Dead};
+use crate::health::Resting;
+use crate::jobs::{ActiveJobs, JobBoard, JobId, JobKind};
+use crate::systems::Time;
+use crate::world::Name;
+use bevy_ecs::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Something that happened to an entity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryEvent {
+    JobTaken(JobKind),
+    /// The job was completed, or handed back to the board when `finished`
+    /// is false
+    JobEnded {
+        kind: JobKind,
+        finished: bool,
+    },
+    Attacked {
+        target: Entity,
+        outcome: AttackOutcome,
+    },
+    AttackedBy {
+        attacker: Entity,
+        outcome: AttackOutcome,
+    },
+    RestStarted,
+    /// Rested until healed
+    Rested,
+    Died,
+}
+
+/// A [`HistoryEvent`] and the tick it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub tick: u64,
+    pub event: HistoryEvent,
+}
+
+impl HistoryEntry {
+    /// One line for the player, e.g. `t=120 took mine (5, 5)`; `name` gives
+    /// the other side of a fight
+    pub fn describe(&self, name: impl Fn(Entity) -> String) -> String {
+        let what = match &self.event {
+            HistoryEvent::JobTaken(kind) => format!("took {}", describe_job(kind)),
+            HistoryEvent::JobEnded {
+                kind,
+                finished: true,
+            } => format!("finished {}", describe_job(kind)),
+            HistoryEvent::JobEnded {
+                kind,
+                finished: false,
+            } => format!("gave up {}", describe_job(kind)),
+            HistoryEvent::Attacked { target, outcome } => {
+                format!("attacked {}{}", name(*target), describe_outcome(outcome))
+            }
+            HistoryEvent::AttackedBy { attacker, outcome } => {
+                format!(
+                    "attacked by {}{}",
+                    name(*attacker),
+                    describe_outcome(outcome)
+                )
+            }
+            HistoryEvent::RestStarted => "lay down to rest".to_string(),
+            HistoryEvent::Rested => "rested until healed".to_string(),
+            HistoryEvent::Died => "died".to_string(),
+        };
+        format!("t={} {}", self.tick, what)
+    }
+}
+
+fn describe_outcome(outcome: &AttackOutcome) -> String {
+    match outcome {
+        AttackOutcome::Miss => ", missed".to_string(),
+        AttackOutcome::Hit { damage } => format!(", hit for {}", damage),
+    }
+}
+
+/// Short description of a job, e.g. `mine (5, 5)`
+pub fn describe_job(kind: &JobKind) -> String {
+    match kind {
+        JobKind::Mine { x, y } => format!("mine ({}, {})", x, y),
+        JobKind::Haul { from, to } => format!("haul {:?} to {:?}", from, to),
+        JobKind::Rest { x, y } => format!("rest at ({}, {})", x, y),
+        JobKind::Equip { x, y, slot } => {
+            format!("equip {:?} from ({}, {})", slot, x, y).to_lowercase()
+        }
+        JobKind::Unequip { slot } => format!("unequip {:?}", slot).to_lowercase(),
+        JobKind::Build { x, y } => format!("build ({}, {})", x, y),
+        JobKind::Craft { x, y } => format!("craft ({}, {})", x, y),
+        JobKind::Extinguish { x, y } => format!("extinguish ({}, {})", x, y),
+        JobKind::Shear { x, y } => format!("shear ({}, {})", x, y),
+        JobKind::Bury { from, to } => format!("bury {:?} at {:?}", from, to),
+        JobKind::PullLever { x, y } => format!("pull lever ({}, {})", x, y),
+    }
+}
+
+/// Recent events of every entity that has any
+#[derive(Resource, Debug, Clone)]
+pub struct EntityHistory {
+    timelines: BTreeMap<Entity, VecDeque<HistoryEntry>>,
+    /// Job each worker was last seen holding
+    holding: BTreeMap<Entity, (JobId, JobKind)>,
+    /// Oldest entries are dropped beyond this many per entity
+    pub capacity: usize,
+    /// Entries older than this many ticks are dropped
+    pub max_age: u64,
+}
+
+impl Default for EntityHistory {
+    fn default() -> Self {
+        Self {
+            timelines: BTreeMap::new(),
+            holding: BTreeMap::new(),
+            capacity: 32,
+            max_age: 500,
+        }
+    }
+}
+
+impl EntityHistory {
+    /// Append `event` to `entity`'s timeline
+    pub fn record(&mut self, entity: Entity, tick: u64, event: HistoryEvent) {
+        let timeline = self.timelines.entry(entity).or_default();
+        timeline.push_back(HistoryEntry { tick, event });
+        while timeline.len() > self.capacity.max(1) {
+            timeline.pop_front();
+        }
+    }
+
+    /// `entity`'s entries no older than [`max_age`](Self::max_age) at
+    /// `now`, oldest first
+    pub fn recent(&self, entity: Entity, now: u64) -> impl Iterator<Item = &HistoryEntry> {
+        let since = now.saturating_sub(self.max_age);
+        self.timelines
+            .get(&entity)
+            .into_iter()
+            .flatten()
+            .filter(move |e| e.tick >= since)
+    }
+
+    /// Drop entries older than [`max_age`](Self::max_age) at `now`, and
+    /// timelines left empty
+    pub fn prune(&mut self, now: u64) {
+        let since = now.saturating_sub(self.max_age);
+        for timeline in self.timelines.values_mut() {
+            while timeline.front().is_some_and(|e| e.tick < since) {
+                timeline.pop_front();
+            }
+        }
+        self.timelines.retain(|_, t| !t.is_empty());
+    }
+
+    /// Entities with a timeline
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.timelines.keys().copied()
+    }
+
+    /// Forget `entity` entirely
+    pub fn forget(&mut self, entity: Entity) {
+        self.timelines.remove(&entity);
+        self.holding.remove(&entity);
+    }
+}
+
+/// What `entity` has done within the last [`EntityHistory::max_age`]
+/// ticks, oldest first; empty for worlds without a history
+pub fn entity_history(world: &World, entity: Entity) -> Vec<HistoryEntry> {
+    let now = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    world
+        .get_resource::<EntityHistory>()
+        .map(|h| h.recent(entity, now).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// [`entity_history`] as lines for the player, naming the other side of a
+/// fight by its [`Name`] where it has one
+pub fn describe_history(world: &World, entity: Entity) -> Vec<String> {
+    let name = |e: Entity| {
+        world
+            .get::<Name>(e)
+            .map_or_else(|| format!("{:?}", e), |n| n.0.clone())
+    };
+    entity_history(world, entity)
+        .iter()
+        .map(|entry| entry.describe(name))
+        .collect()
+}
+
+/// Record job changes of `workers` since they were last looked at
+///
+/// A job counts as finished when it is on neither the board nor the active
+/// list any more, and as given up when it went back to the board.
+fn track_jobs(
+    history: &mut EntityHistory,
+    tick: u64,
+    board: Option<&JobBoard>,
+    active: Option<&ActiveJobs>,
+    workers: &Query<(Entity, Ref<AssignedJob>)>,
+) {
+    let mut changed: Vec<(Entity, Option<JobId>)> = workers
+        .iter()
+        .filter(|(_, a)| a.is_changed())
+        .map(|(e, a)| (e, a.0))
+        .collect();
+    changed.sort_by_key(|&(e, _)| e);
+    for (entity, now) in changed {
+        let before = history.holding.get(&entity).map(|(id, _)| *id);
+        if before == now {
+            continue;
+        }
+        if let Some((id, kind)) = history.holding.remove(&entity) {
+            let on_board = board.is_some_and(|b| b.0.iter().any(|j| j.id == id));
+            let held = active.is_some_and(|a| a.jobs.contains_key(&id));
+            history.record(
+                entity,
+                tick,
+                HistoryEvent::JobEnded {
+                    kind,
+                    finished: !on_board && !held,
+                },
+            );
+        }
+        let kind = now.and_then(|id| Some((id, active?.jobs.get(&id)?.kind.clone())));
+        if let Some((id, kind)) = kind {
+            history.record(entity, tick, HistoryEvent::JobTaken(kind.clone()));
+            history.holding.insert(entity, (id, kind));
+        }
+    }
+}
+
+/// Record jobs taken this tick, before anyone works (and maybe finishes)
+/// them
+pub fn job_history_system(
+    time: Option<Res<Time>>,
+    mut history: ResMut<EntityHistory>,
+    board: Option<Res<JobBoard>>,
+    active: Option<Res<ActiveJobs>>,
+    workers: Query<(Entity, Ref<AssignedJob>)>,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    track_jobs(
+        &mut history,
+        tick,
+        board.as_deref(),
+        active.as_deref(),
+        &workers,
+    );
+}
+
+/// Record jobs ended, rests and deaths, and drop stale entries
+#[allow(clippy::too_many_arguments)]
+pub fn history_system(
+    time: Option<Res<Time>>,
+    mut history: ResMut<EntityHistory>,
+    board: Option<Res<JobBoard>>,
+    active: Option<Res<ActiveJobs>>,
+    workers: Query<(Entity, Ref<AssignedJob>)>,
+    resting: Query<Entity, Added<Resting>>,
+    mut stopped: RemovedComponents<Resting>,
+    died: Query<Entity, Added<Dead>>,
+    living: Query<(), Without<Dead>>,
+    all: Query<()>,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    track_jobs(
+        &mut history,
+        tick,
+        board.as_deref(),
+        active.as_deref(),
+        &workers,
+    );
+
+    let mut rests: Vec<Entity> = resting.iter().collect();
+    rests.sort();
+    for entity in rests {
+        history.record(entity, tick, HistoryEvent::RestStarted);
+    }
+    let mut rested: Vec<Entity> = stopped.read().filter(|&e| living.contains(e)).collect();
+    rested.sort();
+    for entity in rested {
+        history.record(entity, tick, HistoryEvent::Rested);
+    }
+    let mut deaths: Vec<Entity> = died.iter().collect();
+    deaths.sort();
+    for entity in deaths {
+        history.record(entity, tick, HistoryEvent::Died);
+    }
+
+    history.prune(tick);
+    let gone: Vec<Entity> = history
+        .timelines
+        .keys()
+        .chain(history.holding.keys())
+        .copied()
+        .filter(|&e| !all.contains(e))
+        .collect();
+    for entity in gone {
+        history.forget(entity);
+    }
+}
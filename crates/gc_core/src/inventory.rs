@@ -1,60 +1,84 @@
 //! Inventory system for agents carrying items
+//!
+//! A carried item has no `Position`: it is off the map, marked with
+//! [`CarriedBy`], until it is put down again.
 
-use crate::components::Inventory;
-use crate::world::Position;
+use crate::components::{CarriedBy, Inventory};
+use crate::error::GcError;
+use crate::world::{GameMap, Position};
 use bevy_ecs::prelude::*;
 
 /// Pick up an item from the world into an agent's inventory
-/// Returns true if successful, false if agent already carries something or item doesn't exist
-pub fn pick_up_item(world: &mut World, agent_entity: Entity, item_entity: Entity) -> bool {
-    // First check if item exists and agent has an inventory
-    let item_exists = world.get::<Position>(item_entity).is_some();
-    if !item_exists {
-        return false;
-    }
-
-    // Check if agent has inventory and it's empty
-    if let Some(mut inventory) = world.get_mut::<Inventory>(agent_entity) {
-        if inventory.0.is_some() {
-            return false; // Already carrying something
+///
+/// Fails without changing anything when either entity is gone, the item is
+/// not on the map (someone already holds it), or the agent has no free hands.
+pub fn pick_up_item(
+    world: &mut World,
+    agent_entity: Entity,
+    item_entity: Entity,
+) -> Result<(), GcError> {
+    for entity in [agent_entity, item_entity] {
+        if world.get_entity(entity).is_none() {
+            return Err(GcError::InvalidEntity(entity));
         }
-
-        // Move item to inventory
-        inventory.0 = Some(item_entity);
-        true
-    } else {
-        false // Agent doesn't have inventory component
     }
+    if world.get::<Position>(item_entity).is_none() {
+        return Err(GcError::NotCarriable(item_entity));
+    }
+    let Some(mut inventory) = world.get_mut::<Inventory>(agent_entity) else {
+        return Err(GcError::NoInventory(agent_entity));
+    };
+    if let Some(carried) = inventory.0 {
+        return Err(GcError::InventoryFull {
+            agent: agent_entity,
+            carried,
+        });
+    }
+    inventory.0 = Some(item_entity);
+    world
+        .entity_mut(item_entity)
+        .insert(CarriedBy(agent_entity))
+        .remove::<Position>();
+    Ok(())
 }
 
-/// Put down an item from an agent's inventory into the world at a specific position
-/// Returns true if successful, false if agent doesn't carry anything
-pub fn put_down_item(world: &mut World, agent_entity: Entity, world_position: (i32, i32)) -> bool {
-    // Check if agent has inventory with an item
-    let item_entity = if let Some(inventory) = world.get::<Inventory>(agent_entity) {
-        inventory.0
-    } else {
-        return false;
+/// Put down the item an agent carries at a world position
+///
+/// The position must be on the [`GameMap`] when the world has one. A
+/// carried item whose entity is gone is reported and left in the inventory.
+pub fn put_down_item(
+    world: &mut World,
+    agent_entity: Entity,
+    world_position: (i32, i32),
+) -> Result<(), GcError> {
+    let (x, y) = world_position;
+    let Some(inventory) = world.get::<Inventory>(agent_entity) else {
+        return Err(match world.get_entity(agent_entity) {
+            Some(_) => GcError::NoInventory(agent_entity),
+            None => GcError::InvalidEntity(agent_entity),
+        });
     };
-
-    if let Some(item_entity) = item_entity {
-        // Try to set item position in world
-        if let Some(mut position) = world.get_mut::<Position>(item_entity) {
-            position.0 = world_position.0;
-            position.1 = world_position.1;
-
-            // Now clear the inventory
-            if let Some(mut inventory) = world.get_mut::<Inventory>(agent_entity) {
-                inventory.0 = None;
-            }
-            true
-        } else {
-            // Item entity is invalid, do not clear inventory
-            false
+    let Some(item_entity) = inventory.0 else {
+        return Err(GcError::NothingCarried(agent_entity));
+    };
+    if let Some(map) = world.get_resource::<GameMap>() {
+        if !map.in_bounds(x, y) {
+            return Err(GcError::OutOfBounds {
+                x,
+                y,
+                width: map.width,
+                height: map.height,
+            });
         }
-    } else {
-        false // Not carrying anything
     }
+    let Some(mut item) = world.get_entity_mut(item_entity) else {
+        return Err(GcError::InvalidEntity(item_entity));
+    };
+    item.insert(Position(x, y)).remove::<CarriedBy>();
+    if let Some(mut inventory) = world.get_mut::<Inventory>(agent_entity) {
+        inventory.0 = None;
+    }
+    Ok(())
 }
 
 /// Check if an agent is carrying any item
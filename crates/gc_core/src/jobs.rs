@@ -1,8 +1,21 @@
-use crate::components::{AssignedJob, Item, ItemType};
-use crate::world::{GameMap, Position, TileKind};
+use crate::components::{
+    AssignedJob, Dead, Faction, FactionKind, Inventory, Item, ItemType, MiningSkill, Stockpile,
+    ZoneBounds,
+};
+use crate::determinism::{stable_order, RngStream};
+use crate::faults::{report_fault, FaultKind, SimFaults};
+use crate::metrics::RunMetrics;
+use crate::stockpiles::{revalidate_haul, HaulDestination};
+use crate::systems::{DeterministicRng, Time};
+use crate::work_area::{within_reach, WorkArea, WorkAreaConfig};
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind};
+use crate::yields::YieldTable;
 use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryFilter;
 use rand::rngs::StdRng;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 
 /// Job System for Goblin Camp
@@ -13,12 +26,12 @@ use uuid::Uuid;
 /// and then executed by specialized systems.
 /// Unique identifier for jobs using UUID
 /// Provides globally unique IDs that are deterministic when using seeded RNG
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct JobId(pub Uuid);
 
 /// Enumeration of different job types that can be assigned to entities
 /// Each job type contains the specific parameters needed for execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JobKind {
     /// Mining job to convert a wall tile to floor at specific coordinates
     /// Parameters: target coordinates (x, y) to mine
@@ -26,23 +39,153 @@ pub enum JobKind {
     /// Hauling job to move an item from one location to another
     /// Parameters: source position and destination position
     Haul { from: (i32, i32), to: (i32, i32) },
+    /// Rest at a bed (or in place) until healed
+    /// Self-assigned by injured workers; never taken from the board by others
+    Rest { x: i32, y: i32 },
+    /// Fetch gear lying at (x, y) and wear it in `slot`
+    /// Self-assigned by squad members missing part of their uniform
+    Equip {
+        x: i32,
+        y: i32,
+        slot: crate::equipment::EquipSlot,
+    },
+    /// Take off whatever is worn in `slot` and drop it in place
+    Unequip { slot: crate::equipment::EquipSlot },
+    /// Build the construction site at (x, y) once its materials are in
+    /// (see `construction`)
+    Build { x: i32, y: i32 },
+    /// Work the head order of the production station at (x, y) once its
+    /// inputs and fuel are stocked (see `production`)
+    Craft { x: i32, y: i32 },
+    /// Put out the fire at (x, y) (see `fire`)
+    Extinguish { x: i32, y: i32 },
+    /// Shear the tame sheep posted from (x, y) (see `livestock`)
+    Shear { x: i32, y: i32 },
+    /// Carry the body lying at `from` to the grave at `to` and bury it
+    /// (see `burial`)
+    Bury { from: (i32, i32), to: (i32, i32) },
+    /// Pull the lever at (x, y) (see `mechanisms`)
+    PullLever { x: i32, y: i32 },
+}
+
+impl JobKind {
+    /// Short verb for overviews, e.g. "mine" or "pull lever"
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobKind::Mine { .. } => "mine",
+            JobKind::Haul { .. } => "haul",
+            JobKind::Rest { .. } => "rest",
+            JobKind::Equip { .. } => "equip",
+            JobKind::Unequip { .. } => "unequip",
+            JobKind::Build { .. } => "build",
+            JobKind::Craft { .. } => "craft",
+            JobKind::Extinguish { .. } => "extinguish",
+            JobKind::Shear { .. } => "shear",
+            JobKind::Bury { .. } => "bury",
+            JobKind::PullLever { .. } => "pull lever",
+        }
+    }
+}
+
+/// Who ordered a designation or job
+///
+/// Also a component on designation entities; designations without one
+/// count as [`IssuedBy::System`].
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+#[serde(tag = "by", content = "id", rename_all = "snake_case")]
+pub enum IssuedBy {
+    /// The simulation itself (auto-haul, rest, uniforms, blueprints)
+    #[default]
+    System,
+    /// A player, by lockstep peer id (0 for the local player)
+    Player(u32),
+    /// A script, by script id
+    Script(u32),
+}
+
+impl IssuedBy {
+    /// The player at this console
+    pub const LOCAL_PLAYER: IssuedBy = IssuedBy::Player(0);
+}
+
+impl std::fmt::Display for IssuedBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::System => write!(f, "system"),
+            Self::Player(id) => write!(f, "player {id}"),
+            Self::Script(id) => write!(f, "script {id}"),
+        }
+    }
 }
 
 /// A job with its unique identifier and specific task details
 /// Jobs are created on the job board and assigned to appropriate workers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Job {
     /// Unique identifier for this job
     pub id: JobId,
     /// Specific type and parameters of the job
     pub kind: JobKind,
+    /// Who ordered it
+    #[serde(default)]
+    pub issued_by: IssuedBy,
+    /// Only workers of this faction take it (see [`faction_of`])
+    #[serde(default)]
+    pub faction: FactionKind,
+    /// Posting order, from [`JobBoard::next_seq`]; saves list active jobs
+    /// by it
+    #[serde(default)]
+    pub seq: u64,
+    /// Workers take higher first; set on hauls by auto-haul (see
+    /// [`haul_priority`](crate::haul_priority)) and on mine jobs from their
+    /// [`DesignationPriority`](crate::designations::DesignationPriority)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: u32,
+    /// A haul bound for a stockpile; its destination is checked again when
+    /// a carrier takes the job and before pickup (see
+    /// [`revalidate_haul`](crate::stockpiles::revalidate_haul))
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub to_stockpile: bool,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// The faction a worker works for; workers without a [`Faction`] count
+/// as goblins
+pub fn faction_of(faction: Option<&Faction>) -> FactionKind {
+    faction.map_or(FactionKind::Goblins, |f| f.kind)
 }
 
 /// Resource representing the global job board where unassigned jobs are stored
 /// Jobs are posted here by designation systems and taken by assignment systems
 /// Uses a Vec as a simple LIFO queue (last posted, first assigned)
-#[derive(Resource, Default, Debug)]
-pub struct JobBoard(pub Vec<Job>);
+/// The second field is the [`Job::seq`] the next posted job gets
+#[derive(Resource, Default, Debug, Clone)]
+pub struct JobBoard(pub Vec<Job>, pub u64);
+
+impl JobBoard {
+    /// Hand out the next posting sequence number
+    pub fn next_seq(&mut self) -> u64 {
+        let seq = self.1;
+        self.1 += 1;
+        seq
+    }
+}
 
 /// Event emitted when an item should be spawned in the world
 /// Used to decouple item creation from the systems that trigger it (like mining)
@@ -53,12 +196,14 @@ pub struct ItemSpawnRequest {
     pub item_type: ItemType,
     /// World coordinates where the item should be placed
     pub position: (i32, i32),
+    /// Material the item is made of (mined stone), if any
+    pub material: Option<Material>,
 }
 
 /// Resource to track item spawn requests that need to be processed
 /// Acts as a queue between systems that generate items and the system that creates them
 /// Ensures items are spawned in the correct order and timing
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Clone)]
 pub struct ItemSpawnQueue {
     /// Queue of pending item spawn requests
     pub requests: Vec<ItemSpawnRequest>,
@@ -68,12 +213,56 @@ pub struct ItemSpawnQueue {
 /// Uses the provided RNG to generate a reproducible job ID for deterministic simulation
 /// Returns the JobId for reference by other systems
 pub fn add_job(board: &mut ResMut<JobBoard>, kind: JobKind, rng: &mut StdRng) -> JobId {
+    add_job_by(board, kind, IssuedBy::System, rng)
+}
+
+/// [`add_job`] on behalf of `issued_by`
+pub fn add_job_by(
+    board: &mut ResMut<JobBoard>,
+    kind: JobKind,
+    issued_by: IssuedBy,
+    rng: &mut StdRng,
+) -> JobId {
+    add_job_for(board, kind, issued_by, FactionKind::Goblins, rng)
+}
+
+/// [`add_job_by`] for workers of `faction`
+pub fn add_job_for(
+    board: &mut ResMut<JobBoard>,
+    kind: JobKind,
+    issued_by: IssuedBy,
+    faction: FactionKind,
+    rng: &mut StdRng,
+) -> JobId {
+    let job = new_job_for(board, kind, issued_by, faction, rng);
+    let id = job.id;
+    board.0.push(job);
+    id
+}
+
+/// A job for workers of `faction` with the board's next id and sequence
+/// number, not posted; for jobs a worker sets itself, like resting
+pub fn new_job_for(
+    board: &mut JobBoard,
+    kind: JobKind,
+    issued_by: IssuedBy,
+    faction: FactionKind,
+    rng: &mut StdRng,
+) -> Job {
     // Generate deterministic UUID using job_rng stream
     let mut bytes = [0u8; 16];
     rng.fill(&mut bytes);
     let id = JobId(Uuid::from_bytes(bytes));
-    board.0.push(Job { id, kind });
-    id
+    let seq = board.next_seq();
+    Job {
+        id,
+        kind,
+        issued_by,
+        faction,
+        seq,
+        priority: 0,
+        to_stockpile: false,
+    }
 }
 
 /// Remove and return the next available job from the job board
@@ -83,36 +272,302 @@ pub fn take_next_job(board: &mut ResMut<JobBoard>) -> Option<Job> {
     board.0.pop()
 }
 
+/// What [`assign_board_job`] reads of each worker
+pub type BoardWorker = (
+    Entity,
+    &'static mut AssignedJob,
+    Option<&'static Faction>,
+    Option<&'static Position>,
+    Option<&'static WorkArea>,
+);
+
+/// Hand the first posted job whose kind `wanted` accepts to an idle worker
+/// of its faction that may reach it, trying workers in [`stable_order`];
+/// at most one job per call
+pub fn assign_board_job<F: QueryFilter>(
+    board: &mut JobBoard,
+    active: &mut ActiveJobs,
+    work_areas: Option<&WorkAreaConfig>,
+    names: &Query<&Name>,
+    workers: &mut Query<BoardWorker, F>,
+    wanted: impl Fn(&JobKind) -> bool,
+) {
+    let order = stable_order(
+        workers
+            .iter()
+            .map(|(e, _, _, at, _)| (e, at, names.get(e).ok())),
+    );
+    for e in order {
+        let (_, mut assigned, faction, at, area) = workers.get_mut(e).expect("listed above");
+        if assigned.0.is_some() {
+            continue;
+        }
+        let faction = faction_of(faction);
+        if let Some(i) = board.0.iter().position(|j| {
+            j.faction == faction && wanted(&j.kind) && within_reach(area, work_areas, at, &j.kind)
+        }) {
+            let job = board.0.remove(i);
+            assigned.0 = Some(job.id);
+            active.jobs.insert(job.id, job);
+            break;
+        }
+    }
+}
+
+/// When an idle worker may take over a job another worker already holds
+///
+/// Only jobs tied to a place qualify: mine jobs, and haul jobs whose carrier
+/// has not picked the item up yet. Workers only steal when nothing on the
+/// board suits them.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReassignPolicy {
+    /// Assignments hold until the job finishes or is abandoned
+    #[default]
+    Never,
+    /// Every tick, steal when at most half the holder's distance from the job
+    TwiceAsClose,
+    /// Every `ticks` ticks, steal when strictly closer than the holder
+    Every { ticks: u64 },
+}
+
+impl ReassignPolicy {
+    /// Whether a worker `ours` tiles from a job should take it from a
+    /// holder `theirs` tiles away, at `tick`
+    pub fn should_steal(self, tick: u64, ours: i32, theirs: i32) -> bool {
+        match self {
+            Self::Never => false,
+            Self::TwiceAsClose => ours * 2 <= theirs && ours < theirs,
+            Self::Every { ticks } => ticks > 0 && tick % ticks == 0 && ours < theirs,
+        }
+    }
+}
+
+/// A job that moved from one worker to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobReassigned {
+    pub tick: u64,
+    pub job: JobId,
+    pub issued_by: IssuedBy,
+    pub faction: FactionKind,
+    pub from: Entity,
+    pub to: Entity,
+}
+
+/// Bounded queue of reassignments for UIs and logs to drain
+#[derive(Resource, Debug, Clone)]
+pub struct ReassignmentEvents {
+    events: VecDeque<JobReassigned>,
+    /// Oldest events are dropped beyond this many
+    pub capacity: usize,
+}
+
+impl Default for ReassignmentEvents {
+    fn default() -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity: 256,
+        }
+    }
+}
+
+impl ReassignmentEvents {
+    pub fn push(&mut self, event: JobReassigned) {
+        self.events.push_back(event);
+        while self.events.len() > self.capacity {
+            self.events.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &JobReassigned> {
+        self.events.iter()
+    }
+
+    /// Take all pending events, oldest first
+    pub fn drain(&mut self) -> Vec<JobReassigned> {
+        self.events.drain(..).collect()
+    }
+}
+
+/// A held job another worker could take over
+struct Holding {
+    worker: Entity,
+    job: JobId,
+    target: (i32, i32),
+    distance: i32,
+}
+
+/// Where the holder of `job` is headed, if the job may be stolen
+fn steal_target(job: &Job, carrying: bool) -> Option<(i32, i32)> {
+    match job.kind {
+        JobKind::Mine { x, y } => Some((x, y)),
+        JobKind::Haul { from, .. } if !carrying => Some(from),
+        _ => None,
+    }
+}
+
+fn manhattan(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+/// Pair idle workers with held jobs per `policy`
+///
+/// Idle workers go in entity order, each taking the nearest qualifying job
+/// (ties to the lowest holder entity); a job moves at most once per call.
+/// Returns (job, from, to) triples.
+fn plan_steals(
+    policy: ReassignPolicy,
+    tick: u64,
+    mut idle: Vec<(Entity, (i32, i32))>,
+    mut held: Vec<Holding>,
+    can_take: impl Fn(Entity, JobId) -> bool,
+) -> Vec<(JobId, Entity, Entity)> {
+    idle.sort_by_key(|&(e, _)| e);
+    held.sort_by_key(|h| h.worker);
+    let mut steals = Vec::new();
+    for (worker, pos) in idle {
+        let best = held
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| can_take(worker, h.job))
+            .map(|(i, h)| (manhattan(pos, h.target), i))
+            .filter(|&(ours, i)| policy.should_steal(tick, ours, held[i].distance))
+            .min();
+        if let Some((_, i)) = best {
+            let h = held.remove(i);
+            steals.push((h.job, h.worker, worker));
+        }
+    }
+    steals
+}
+
+/// Point queued stockpile hauls at stockpiles as they are now
+///
+/// Each haul with [`Job::to_stockpile`] is checked with [`revalidate_haul`]
+/// for the first item on its `from` tile, with `items` given in
+/// [`stable_order`]. Hauls with no stockpile
+/// left to go to are dropped from the board and their items handed back
+/// to auto-haul's backlog; hauls whose item is gone are left for pickup
+/// to report.
+fn revalidate_board_hauls(
+    board: &mut JobBoard,
+    throttle: Option<&mut ThrottleState>,
+    items: impl IntoIterator<Item = (Entity, (i32, i32), ItemType)>,
+    stockpiles: &[(Entity, &Position, &Stockpile, Option<&ZoneBounds>)],
+) {
+    let mut on_tile: std::collections::HashMap<(i32, i32), (Entity, ItemType)> =
+        std::collections::HashMap::new();
+    for (item, at, item_type) in items {
+        on_tile.entry(at).or_insert((item, item_type));
+    }
+    let mut dropped = Vec::new();
+    board.0.retain_mut(|job| {
+        if !job.to_stockpile {
+            return true;
+        }
+        let JobKind::Haul { from, ref mut to } = job.kind else {
+            return true;
+        };
+        let Some(&(item, item_type)) = on_tile.get(&from) else {
+            return true;
+        };
+        match revalidate_haul(stockpiles, from, *to, item_type) {
+            HaulDestination::Valid => true,
+            HaulDestination::Retarget(next) => {
+                *to = next;
+                true
+            }
+            HaulDestination::Gone => {
+                dropped.push(item);
+                false
+            }
+        }
+    });
+    if let Some(throttle) = throttle {
+        for item in dropped {
+            if !throttle.haul_backlog.contains(&item) {
+                throttle.haul_backlog.push(item);
+            }
+        }
+    }
+}
+
 /// System that assigns available jobs to workers based on their capabilities
 /// Miners get mining jobs, Carriers get hauling jobs
 /// Only assigns one job per entity per system run to prevent over-assignment
 /// Jobs are moved from the JobBoard to ActiveJobs when assigned
+/// Workers only take jobs of their own faction (see [`faction_of`])
+/// Workers left idle may then take held jobs per [`ReassignPolicy`]
+/// Before an idle carrier picks a haul, stockpile hauls on the board are
+/// checked against the current stockpiles (see [`Job::to_stockpile`])
+/// Workers only take or steal jobs within their [`WorkArea`]
+/// Workers are tried in [`stable_order`], so which of several idle workers
+/// gets a job does not depend on spawn order or archetype layout
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn job_assignment_system(
     mut board: ResMut<JobBoard>,
     mut active_jobs: ResMut<ActiveJobs>,
+    materials: Option<Res<MaterialMap>>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    policy: Option<Res<ReassignPolicy>>,
+    time: Option<Res<Time>>,
+    mut events: Option<ResMut<ReassignmentEvents>>,
+    throttle: Option<ResMut<ThrottleState>>,
+    q_items: Query<(Entity, &Position, &Item)>,
+    q_stockpiles: Query<(Entity, &Position, &Stockpile, Option<&ZoneBounds>)>,
+    q_names: Query<&Name>,
     mut q_miners: Query<
-        &mut AssignedJob,
+        (
+            Entity,
+            &mut AssignedJob,
+            Option<&MiningSkill>,
+            Option<&Position>,
+            Option<&Faction>,
+            Option<&WorkArea>,
+        ),
         (
             With<crate::components::Miner>,
             Without<crate::components::Carrier>,
         ),
     >,
     mut q_carriers: Query<
-        &mut AssignedJob,
+        (
+            Entity,
+            &mut AssignedJob,
+            Option<&Position>,
+            Option<&Inventory>,
+            Option<&Faction>,
+            Option<&WorkArea>,
+        ),
         (
             With<crate::components::Carrier>,
             Without<crate::components::Miner>,
         ),
     >,
 ) {
-    // Assign mining jobs to miners
-    for mut assigned in q_miners.iter_mut() {
+    // Assign mining jobs to miners skilled enough for the tile's layer,
+    // highest priority first
+    let work_areas = work_areas.as_deref();
+    let order = stable_order(
+        q_miners
+            .iter()
+            .map(|(e, _, _, at, ..)| (e, at, q_names.get(e).ok())),
+    );
+    for e in order {
+        let (_, mut assigned, skill, at, faction, area) =
+            q_miners.get_mut(e).expect("listed above");
         if assigned.0.is_none() {
             // Find a mining job
-            if let Some(pos) = board
+            let faction = faction_of(faction);
+            if let Some((pos, _)) = board
                 .0
                 .iter()
-                .position(|job| matches!(job.kind, JobKind::Mine { .. }))
+                .enumerate()
+                .filter(|(_, job)| {
+                    job.faction == faction
+                        && can_mine(job, skill, materials.as_deref())
+                        && within_reach(area, work_areas, at, &job.kind)
+                })
+                .max_by_key(|&(i, job)| (job.priority, std::cmp::Reverse(i)))
             {
                 let job = board.0.remove(pos);
                 let job_id = job.id;
@@ -124,14 +579,49 @@ pub fn job_assignment_system(
         }
     }
 
-    // Assign hauling jobs to carriers
-    for mut assigned in q_carriers.iter_mut() {
+    let idle_carrier = q_carriers
+        .iter()
+        .any(|(_, assigned, ..)| assigned.0.is_none());
+    if idle_carrier && board.0.iter().any(|job| job.to_stockpile) {
+        let stockpiles: Vec<_> = q_stockpiles.iter().collect();
+        revalidate_board_hauls(
+            &mut board,
+            throttle.map(|t| t.into_inner()),
+            stable_order(
+                q_items
+                    .iter()
+                    .map(|(e, pos, _)| (e, Some(pos), q_names.get(e).ok())),
+            )
+            .into_iter()
+            .map(|e| {
+                let (_, pos, item) = q_items.get(e).expect("listed above");
+                (e, (pos.0, pos.1), item.item_type)
+            }),
+            &stockpiles,
+        );
+    }
+
+    // Assign hauling jobs to carriers, highest priority first
+    let order = stable_order(
+        q_carriers
+            .iter()
+            .map(|(e, _, at, ..)| (e, at, q_names.get(e).ok())),
+    );
+    for e in order {
+        let (_, mut assigned, at, _, faction, area) = q_carriers.get_mut(e).expect("listed above");
         if assigned.0.is_none() {
             // Find a hauling job
-            if let Some(pos) = board
+            let faction = faction_of(faction);
+            if let Some((pos, _)) = board
                 .0
                 .iter()
-                .position(|job| matches!(job.kind, JobKind::Haul { .. }))
+                .enumerate()
+                .filter(|(_, job)| {
+                    job.faction == faction
+                        && matches!(job.kind, JobKind::Haul { .. })
+                        && within_reach(area, work_areas, at, &job.kind)
+                })
+                .max_by_key(|&(i, job)| (job.priority, std::cmp::Reverse(i)))
             {
                 let job = board.0.remove(pos);
                 let job_id = job.id;
@@ -142,24 +632,168 @@ pub fn job_assignment_system(
             }
         }
     }
+
+    let policy = policy.as_deref().copied().unwrap_or_default();
+    if policy == ReassignPolicy::Never {
+        return;
+    }
+    let tick = time.map_or(0, |t| t.ticks);
+    let mut steals = Vec::new();
+
+    // Miners: idle ones with no suitable board job may take a held mine job
+    let mut idle = Vec::new();
+    let mut held = Vec::new();
+    let mut skills = std::collections::HashMap::new();
+    for (e, assigned, skill, at, faction, area) in q_miners.iter() {
+        let Some(pos) = at.map(|p| (p.0, p.1)) else {
+            continue;
+        };
+        let faction = faction_of(faction);
+        match assigned.0.and_then(|id| active_jobs.jobs.get(&id)) {
+            None if !board.0.iter().any(|job| {
+                job.faction == faction
+                    && can_mine(job, skill, materials.as_deref())
+                    && within_reach(area, work_areas, at, &job.kind)
+            }) =>
+            {
+                idle.push((e, pos));
+                skills.insert(e, (skill.copied(), faction, at.copied(), area.cloned()));
+            }
+            None => {}
+            Some(job) => {
+                if let Some(target) = steal_target(job, false) {
+                    held.push(Holding {
+                        worker: e,
+                        job: job.id,
+                        target,
+                        distance: manhattan(pos, target),
+                    });
+                }
+            }
+        }
+    }
+    for (job, from, to) in plan_steals(policy, tick, idle, held, |e, id| {
+        let (skill, faction, at, area) = &skills[&e];
+        active_jobs.jobs.get(&id).is_some_and(|job| {
+            job.faction == *faction
+                && can_mine(job, skill.as_ref(), materials.as_deref())
+                && within_reach(area.as_ref(), work_areas, at.as_ref(), &job.kind)
+        })
+    }) {
+        if let Ok([(_, mut robbed, ..), (_, mut thief, ..)]) = q_miners.get_many_mut([from, to]) {
+            robbed.0 = None;
+            thief.0 = Some(job);
+        }
+        steals.push((job, from, to));
+    }
+
+    // Carriers: only hauls whose item is still on the ground move
+    let has_haul = |faction: FactionKind, at: Option<&Position>, area: Option<&WorkArea>| {
+        board.0.iter().any(|job| {
+            job.faction == faction
+                && matches!(job.kind, JobKind::Haul { .. })
+                && within_reach(area, work_areas, at, &job.kind)
+        })
+    };
+    let mut idle = Vec::new();
+    let mut held = Vec::new();
+    let mut factions = std::collections::HashMap::new();
+    for (e, assigned, at, inventory, faction, area) in q_carriers.iter() {
+        let Some(pos) = at.map(|p| (p.0, p.1)) else {
+            continue;
+        };
+        let faction = faction_of(faction);
+        match assigned.0.and_then(|id| active_jobs.jobs.get(&id)) {
+            None if !has_haul(faction, at, area) => {
+                idle.push((e, pos));
+                factions.insert(e, (faction, at.copied(), area.cloned()));
+            }
+            None => {}
+            Some(job) => {
+                let carrying = inventory.is_some_and(|inv| inv.0.is_some());
+                if let Some(target) = steal_target(job, carrying) {
+                    held.push(Holding {
+                        worker: e,
+                        job: job.id,
+                        target,
+                        distance: manhattan(pos, target),
+                    });
+                }
+            }
+        }
+    }
+    for (job, from, to) in plan_steals(policy, tick, idle, held, |e, id| {
+        let (faction, at, area) = &factions[&e];
+        active_jobs.jobs.get(&id).is_some_and(|job| {
+            job.faction == *faction
+                && within_reach(area.as_ref(), work_areas, at.as_ref(), &job.kind)
+        })
+    }) {
+        if let Ok([(_, mut robbed, ..), (_, mut thief, ..)]) = q_carriers.get_many_mut([from, to]) {
+            robbed.0 = None;
+            thief.0 = Some(job);
+        }
+        steals.push((job, from, to));
+    }
+
+    if let Some(events) = events.as_deref_mut() {
+        for (job, from, to) in steals {
+            let (issued_by, faction) = active_jobs
+                .jobs
+                .get(&job)
+                .map_or((IssuedBy::System, FactionKind::Goblins), |j| {
+                    (j.issued_by, j.faction)
+                });
+            events.push(JobReassigned {
+                tick,
+                job,
+                issued_by,
+                faction,
+                from,
+                to,
+            });
+        }
+    }
 }
 
 /// Assigns mining jobs specifically to miners (specialized version)
 /// Alternative to the general job_assignment_system when you only want mining assignment
 /// More focused and predictable for testing specific mining scenarios
+#[allow(clippy::type_complexity)]
 pub fn mining_job_assignment_system(
     mut board: ResMut<JobBoard>,
     mut active_jobs: ResMut<ActiveJobs>,
-    mut q_miners: Query<&mut AssignedJob, With<crate::components::Miner>>,
+    materials: Option<Res<MaterialMap>>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    q_names: Query<&Name>,
+    mut q_miners: Query<
+        (
+            Entity,
+            &mut AssignedJob,
+            Option<&MiningSkill>,
+            Option<&Faction>,
+            Option<&Position>,
+            Option<&WorkArea>,
+        ),
+        With<crate::components::Miner>,
+    >,
 ) {
-    for mut assigned in q_miners.iter_mut() {
+    let order = stable_order(
+        q_miners
+            .iter()
+            .map(|(e, _, _, _, at, _)| (e, at, q_names.get(e).ok())),
+    );
+    for e in order {
+        let (_, mut assigned, skill, faction, at, area) =
+            q_miners.get_mut(e).expect("listed above");
         if assigned.0.is_none() {
             // Look for a mining job specifically
-            if let Some(pos) = board
-                .0
-                .iter()
-                .position(|job| matches!(job.kind, JobKind::Mine { .. }))
-            {
+            let faction = faction_of(faction);
+            if let Some(pos) = board.0.iter().position(|job| {
+                job.faction == faction
+                    && can_mine(job, skill, materials.as_deref())
+                    && within_reach(area, work_areas.as_deref(), at, &job.kind)
+            }) {
                 let job = board.0.remove(pos);
                 let job_id = job.id;
 
@@ -171,15 +805,262 @@ pub fn mining_job_assignment_system(
     }
 }
 
+/// Whether a miner with `skill` may take `job`
+///
+/// True for mine jobs on tiles whose material needs no more than the
+/// miner's skill; without a [`MaterialMap`] every mine job qualifies.
+pub fn can_mine(job: &Job, skill: Option<&MiningSkill>, materials: Option<&MaterialMap>) -> bool {
+    let JobKind::Mine { x, y } = job.kind else {
+        return false;
+    };
+    let skill = skill.map_or(0, |s| s.0);
+    materials
+        .and_then(|m| m.get(x, y))
+        .map_or(true, |m| m.required_skill() <= skill)
+}
+
 /// Resource to track active jobs being executed
 /// Jobs are moved here from the JobBoard when assigned to workers
 /// Contains the full job details needed for execution systems
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Clone)]
 pub struct ActiveJobs {
     /// Map of JobId to Job for quick lookup during execution
     pub jobs: std::collections::HashMap<JobId, Job>,
 }
 
+/// Configuration resource for [`active_jobs_gc_system`]
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct JobGcConfig {
+    /// Reconcile every this many ticks (0 disables the check)
+    pub interval: u64,
+    /// Panic instead of repairing when orphans turn up; tests switch this on
+    /// to catch systems that leak jobs
+    pub assert_no_orphans: bool,
+}
+
+impl Default for JobGcConfig {
+    fn default() -> Self {
+        Self {
+            interval: 50,
+            assert_no_orphans: false,
+        }
+    }
+}
+
+/// Per-tick caps on job generation, so a flood of designations or mined
+/// items trickles onto the board instead of swamping it
+///
+/// Work over a cap is not lost: designations stay Active and items wait
+/// in the auto-haul backlog for later ticks. A cap of 0 is unlimited.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobThrottle {
+    /// Mine jobs made from designations per tick
+    pub designations_per_tick: usize,
+    /// Haul jobs made by auto-haul per tick
+    pub hauls_per_tick: usize,
+    /// Generation pauses while the board holds this many queued jobs
+    pub board_limit: usize,
+}
+
+impl Default for JobThrottle {
+    fn default() -> Self {
+        Self {
+            designations_per_tick: 256,
+            hauls_per_tick: 256,
+            board_limit: 4096,
+        }
+    }
+}
+
+/// Where the throttled generators left off between ticks
+///
+/// Kept as a resource rather than in the systems so that a
+/// [`checkpoint`](crate::checkpoint) carries it and a restored world
+/// resumes the same round-robin.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThrottleState {
+    /// Items waiting for auto-haul, oldest first
+    pub haul_backlog: Vec<Entity>,
+    /// Tick each backlogged item joined the backlog, for its haul priority
+    pub haul_since: BTreeMap<Entity, u64>,
+    /// Item type auto-haul served last
+    pub haul_cursor: Option<ItemType>,
+    /// Issuer and faction whose designations were converted last
+    pub designation_cursor: Option<(IssuedBy, FactionKind)>,
+}
+
+impl JobThrottle {
+    /// Jobs a generator capped at `per_tick` may post onto `board` now
+    pub fn allowance(&self, per_tick: usize, board: &JobBoard) -> usize {
+        let per_tick = if per_tick == 0 { usize::MAX } else { per_tick };
+        let headroom = if self.board_limit == 0 {
+            usize::MAX
+        } else {
+            self.board_limit.saturating_sub(board.0.len())
+        };
+        per_tick.min(headroom)
+    }
+}
+
+/// Take up to `cap` entries from `queues` one queue at a time, a round at
+/// a time, starting with the first queue after `cursor`
+///
+/// Returns what was taken, in turn order, and the key served last (the
+/// cursor for the next call), so no queue can starve the others.
+pub fn round_robin<K: Ord + Clone, T>(
+    mut queues: std::collections::BTreeMap<K, VecDeque<T>>,
+    cursor: Option<&K>,
+    cap: usize,
+) -> (Vec<T>, Option<K>) {
+    let mut keys: Vec<K> = queues.keys().cloned().collect();
+    if let Some(cursor) = cursor {
+        let split = keys.partition_point(|k| k <= cursor);
+        keys.rotate_left(split);
+    }
+    let mut taken = Vec::new();
+    let mut last = cursor.cloned();
+    while taken.len() < cap {
+        let mut any = false;
+        for key in &keys {
+            if taken.len() >= cap {
+                break;
+            }
+            if let Some(entry) = queues.get_mut(key).and_then(VecDeque::pop_front) {
+                taken.push(entry);
+                last = Some(key.clone());
+                any = true;
+            }
+        }
+        if !any {
+            break;
+        }
+    }
+    (taken, last)
+}
+
+/// Active jobs no living worker holds, in id order
+///
+/// A job counts as held when some entity without [`Dead`] has it in its
+/// [`AssignedJob`].
+pub fn orphaned_jobs(world: &mut World) -> Vec<JobId> {
+    let held: std::collections::HashSet<JobId> = world
+        .query_filtered::<&AssignedJob, Without<Dead>>()
+        .iter(world)
+        .filter_map(|a| a.0)
+        .collect();
+    let mut orphans: Vec<JobId> = world
+        .resource::<ActiveJobs>()
+        .jobs
+        .keys()
+        .filter(|id| !held.contains(id))
+        .copied()
+        .collect();
+    orphans.sort_by_key(|id| id.0);
+    orphans
+}
+
+/// Every [`JobGcConfig::interval`] ticks, take back active jobs whose worker
+/// died or was despawned
+///
+/// Mine, haul and build jobs go back on the board under their old id, so
+/// designation and site links survive; worker-specific jobs (rest, equip)
+/// are dropped. A haul is sent back for its item where it now lies, on its
+/// old pickup tile or where its carrier fell, and dropped when no item is
+/// on either. The dead
+/// are released from their assignment. Living workers holding a job that
+/// is not active are released too, and reported as
+/// [`FaultKind::OrphanAssignment`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn active_jobs_gc_system(
+    config: Option<Res<JobGcConfig>>,
+    time: Option<Res<Time>>,
+    mut faults: Option<ResMut<SimFaults>>,
+    mut active_jobs: ResMut<ActiveJobs>,
+    mut board: ResMut<JobBoard>,
+    mut q_living: Query<(Entity, &mut AssignedJob), Without<Dead>>,
+    mut q_dead: Query<(&mut AssignedJob, Option<&Position>), With<Dead>>,
+    q_items: Query<&Position, With<Item>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let tick = time.as_ref().map_or(0, |t| t.ticks);
+    if config.interval == 0 || tick % config.interval != 0 {
+        return;
+    }
+    let mut dangling: Vec<(Entity, Mut<AssignedJob>)> = q_living
+        .iter_mut()
+        .filter(|(_, a)| a.0.is_some_and(|id| !active_jobs.jobs.contains_key(&id)))
+        .collect();
+    dangling.sort_by_key(|(e, _)| *e);
+    for (worker, mut assigned) in dangling {
+        let id = assigned.0.take().expect("filtered above");
+        report_fault(
+            faults.as_deref_mut(),
+            time.as_deref(),
+            FaultKind::OrphanAssignment,
+            "active_jobs_gc_system",
+            Some(worker),
+            format!("assigned job {:?} is not active", id.0),
+        );
+    }
+    if active_jobs.jobs.is_empty() {
+        return;
+    }
+    let held: std::collections::HashSet<JobId> = q_living.iter().filter_map(|(_, a)| a.0).collect();
+    let mut orphans: Vec<JobId> = active_jobs
+        .jobs
+        .keys()
+        .filter(|id| !held.contains(id))
+        .copied()
+        .collect();
+    if orphans.is_empty() {
+        return;
+    }
+    assert!(
+        !config.assert_no_orphans,
+        "{} orphaned active job(s) at tick {tick}: {orphans:?}",
+        orphans.len()
+    );
+    orphans.sort_by_key(|id| id.0);
+    // The dead dropped what they carried where they fell
+    let fell: HashMap<JobId, (i32, i32)> = q_dead
+        .iter()
+        .filter_map(|(a, p)| Some((a.0?, p.map(|p| (p.0, p.1))?)))
+        .collect();
+    let lying: HashSet<(i32, i32)> = q_items.iter().map(|p| (p.0, p.1)).collect();
+    for id in orphans {
+        if let Some(mut job) = active_jobs.jobs.remove(&id) {
+            if let JobKind::Haul { from, to } = job.kind {
+                let Some(at) = [Some(from), fell.get(&id).copied()]
+                    .into_iter()
+                    .flatten()
+                    .find(|at| lying.contains(at))
+                else {
+                    continue;
+                };
+                job.kind = JobKind::Haul { from: at, to };
+            }
+            if matches!(
+                job.kind,
+                JobKind::Mine { .. }
+                    | JobKind::Haul { .. }
+                    | JobKind::Build { .. }
+                    | JobKind::Craft { .. }
+                    | JobKind::Extinguish { .. }
+                    | JobKind::Shear { .. }
+                    | JobKind::Bury { .. }
+                    | JobKind::PullLever { .. }
+            ) {
+                board.0.push(job);
+            }
+        }
+    }
+    for (mut assigned, _) in q_dead.iter_mut() {
+        if assigned.0.is_some() {
+            assigned.0 = None;
+        }
+    }
+}
+
 /// System that processes ItemSpawnQueue and creates actual item entities
 /// This system runs after job execution systems to create items from queued requests
 /// Decouples item creation from the systems that trigger it for better system ordering
@@ -193,15 +1074,56 @@ pub fn process_item_spawn_queue_system(
         match request.item_type {
             ItemType::Stone => {
                 // Create a complete stone item entity with all necessary components
-                commands.spawn((
+                let name = request.material.map_or("Stone", Material::item_name);
+                let mut item = commands.spawn((
                     Item {
                         item_type: ItemType::Stone,
                     },
                     crate::components::Stone,
                     crate::world::Position(x, y),
                     crate::components::Carriable,
-                    crate::world::Name("Stone".to_string()),
+                    crate::world::Name(name.to_string()),
+                ));
+                if let Some(material) = request.material {
+                    item.insert(material);
+                }
+            }
+            ItemType::Gem => {
+                let name = request.material.map_or("Rough gem".to_string(), |m| {
+                    format!("Rough {} gem", m.label())
+                });
+                let mut item = commands.spawn((
+                    Item {
+                        item_type: ItemType::Gem,
+                    },
+                    crate::world::Position(x, y),
+                    crate::components::Carriable,
+                    crate::world::Name(name),
+                ));
+                if let Some(material) = request.material {
+                    item.insert(material);
+                }
+            }
+            ItemType::Weapon
+            | ItemType::Armor
+            | ItemType::Ore
+            | ItemType::Coal
+            | ItemType::Wood
+            | ItemType::Bar
+            | ItemType::Egg
+            | ItemType::Wool
+            | ItemType::Block => {
+                let mut item = commands.spawn((
+                    Item {
+                        item_type: request.item_type,
+                    },
+                    crate::world::Position(x, y),
+                    crate::components::Carriable,
+                    crate::world::Name(format!("{:?}", request.item_type)),
                 ));
+                if let Some(gear) = crate::equipment::Gear::for_item(request.item_type) {
+                    item.insert(gear);
+                }
             }
         }
     }
@@ -210,27 +1132,86 @@ pub fn process_item_spawn_queue_system(
 /// System that executes mining jobs by converting Wall tiles to Floor and emitting ItemSpawn events
 /// This is the core mining execution system that performs the actual work of mining
 /// Miners with assigned Mine jobs will execute them here, modifying the world and creating items
+///
+/// A target that is no longer rock, or an assignment whose job is gone,
+/// is reported to [`SimFaults`] and dropped. Mined tiles and their drops
+/// are counted in [`RunMetrics`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn mine_job_execution_system(
+    time: Option<Res<Time>>,
+    mut faults: Option<ResMut<SimFaults>>,
+    mut metrics: Option<ResMut<RunMetrics>>,
     mut map: ResMut<GameMap>,
+    materials: Option<Res<MaterialMap>>,
+    yields: Option<Res<YieldTable>>,
+    mut rng: Option<ResMut<DeterministicRng>>,
     mut item_spawn_queue: ResMut<ItemSpawnQueue>,
     mut active_jobs: ResMut<ActiveJobs>,
-    mut q_miners: Query<(&mut AssignedJob, &Position), With<crate::components::Miner>>,
+    mut q_miners: Query<
+        (Entity, &mut AssignedJob, &Position),
+        (
+            With<crate::components::Miner>,
+            Without<crate::health::Stalled>,
+        ),
+    >,
 ) {
-    for (mut assigned_job, _miner_pos) in q_miners.iter_mut() {
+    for (miner, mut assigned_job, _miner_pos) in q_miners.iter_mut() {
         if let Some(job_id) = assigned_job.0 {
             // Look up the job details from active jobs
             if let Some(job) = active_jobs.jobs.get(&job_id) {
                 if let JobKind::Mine { x, y } = job.kind {
-                    if let Some(current_tile) = map.get_tile(x, y) {
+                    let current_tile = map.get_tile(x, y);
+                    if current_tile != Some(TileKind::Wall) {
+                        report_fault(
+                            faults.as_deref_mut(),
+                            time.as_deref(),
+                            FaultKind::InvalidDesignationTarget,
+                            "mine_job_execution_system",
+                            Some(miner),
+                            match current_tile {
+                                Some(tile) => format!("mine target ({x}, {y}) is {tile:?}"),
+                                None => format!("mine target ({x}, {y}) is off the map"),
+                            },
+                        );
+                    }
+                    if let Some(current_tile) = current_tile {
                         if current_tile == TileKind::Wall {
                             // Convert Wall to Floor (the primary mining action)
-                            map.set_tile(x, y, TileKind::Floor);
+                            if let Err(err) = map.set_tile(x, y, TileKind::Floor) {
+                                err.report(
+                                    faults.as_deref_mut(),
+                                    time.as_deref(),
+                                    "mine_job_execution_system",
+                                    Some(miner),
+                                );
+                            } else if let Some(metrics) = metrics.as_deref_mut() {
+                                metrics.tiles_mined += 1;
+                            }
 
-                            // Queue ItemSpawn request for stone (mining produces stone items)
-                            item_spawn_queue.requests.push(ItemSpawnRequest {
-                                item_type: ItemType::Stone,
-                                position: (x, y),
-                            });
+                            // Queue ItemSpawn requests for the drops: stone only
+                            // without a yield table or an RNG to roll it on
+                            let material = materials.as_ref().and_then(|m| m.get(x, y));
+                            let drops = match (yields.as_deref(), rng.as_deref_mut()) {
+                                (Some(table), Some(rng)) => table.roll(
+                                    material,
+                                    rng.stream(RngStream::Job, "mine_job_execution_system"),
+                                ),
+                                _ => vec![ItemType::Stone],
+                            };
+                            for item_type in drops {
+                                if let Some(metrics) = metrics.as_deref_mut() {
+                                    match item_type {
+                                        ItemType::Stone => metrics.stones_mined += 1,
+                                        ItemType::Gem => metrics.gems_mined += 1,
+                                        _ => {}
+                                    }
+                                }
+                                item_spawn_queue.requests.push(ItemSpawnRequest {
+                                    item_type,
+                                    position: (x, y),
+                                    material,
+                                });
+                            }
                         }
                     }
 
@@ -240,6 +1221,14 @@ pub fn mine_job_execution_system(
                 }
             } else {
                 // Job not found in active jobs, clear assignment defensively
+                report_fault(
+                    faults.as_deref_mut(),
+                    time.as_deref(),
+                    FaultKind::OrphanAssignment,
+                    "mine_job_execution_system",
+                    Some(miner),
+                    format!("assigned job {:?} is not active", job_id.0),
+                );
                 assigned_job.0 = None;
             }
         }
@@ -31,13 +31,65 @@
 //! - [`mapgen`]: Procedural terrain generation
 //! - [`save`]: World serialization and persistence
 //! - [`inventory`]: Item carrying and storage systems
+//! - [`blueprint`]: Capture and stamp reusable designation layouts
+//! - [`view`]: Immutable world snapshots for renderers and external tools
+//! - [`command`]: Serializable player commands applied between ticks
+//! - [`sim_thread`]: Background simulation thread with command/event channels
+//! - [`rpc`]: JSON-RPC request parsing and dispatch for remote clients
+//! - [`lockstep`]: Lockstep input exchange and desync detection for multiplayer
+//! - [`announcements`]: Severity-classified announcements with pause-on-urgent
+//! - [`names`]: Seeded name generation for goblins, invaders, and animals
+//! - [`health`]: Injury states, regeneration, and automatic rest jobs
+//! - [`quality`]: Item quality levels, crafting skill rolls, and trade values
+//! - [`equipment`]: Equipment slots, gear bonuses, and squad uniforms
+//! - [`combat`]: Attack resolution with equipment bonuses
+//! - [`editor`]: Scenario editing brushes (tiles, workers, items, stockpiles)
+//! - [`overmap`]: Overworld regions (biomes, rivers) and embark to a local map
+//! - [`bench`]: Load-test colony scenarios and throughput reports
+//! - [`schedule_check`]: Schedule validation for duplicate systems, missing resources, and ambiguities
+//! - [`zones`]: Zone kinds and per-kind behaviors
+//! - [`danger`]: Danger cost layer from visible hostiles, avoided by civilian pathing
+//! - [`yields`]: Data-driven mining yield tables (stone, gems) rolled on the job RNG
+//! - [`construction`]: Construction sites with material requirements, delivery hauls, and build jobs
+//! - [`determinism`]: Determinism guard, source audit and RNG stream audit
+//! - [`tags`]: Item tags, tag queries, tag-filtered stockpiles and ingredients
+//! - [`production`]: Production stations, recipe orders and fuel-burning smelters
+//! - [`fire`]: Fire spread, burnout and extinguish jobs
+//! - [`livestock`]: Taming, pastures, egg laying and shearing
+//! - [`manager`]: Standing orders that keep stockpiled products between two levels
+//! - [`burial`]: Graves, burial jobs and ghosts of corpses left unburied
+//! - [`caverns`]: Caverns sealed in rock until mining breaks into them
+//! - [`mechanisms`]: Levers and pressure plates linked to doors and traps
+//! - [`faults`]: Simulation fault reports, counts and strict mode
+//! - [`error`]: Typed errors for inventory moves and map writes
+//! - [`lighting`]: Light sources, the day/night cycle and darkness-limited sight
+//! - [`render`]: Render glyphs, draw layers and per-tile draw lists for renderers
+//! - [`plans`]: Designation and zone plans exported and imported as JSON or CSV
+//! - [`traffic`]: Decaying per-tile step counts for congestion overlays
+//! - [`checkpoint`]: In-memory snapshots for rolling the simulation back
+//! - [`toggles`]: Switching subsystems off at runtime for debugging
+//! - [`tutorial`]: Scripted tutorial scenarios with goals and announced hints
+//! - [`soak`]: Long runs with periodic invariant, growth and memory checks
+//! - [`telemetry`]: Live entity counts by category and leak alerts
+//! - [`presets`]: Named zone and designation presets applied to a selected area
+//! - [`metrics`]: Running totals of mining, hauling and deaths
+//! - [`temperature`]: Room temperature, insulation, comfort and food spoilage
+//! - [`history`]: Bounded per-entity timelines of jobs, fights, rests and deaths
+//! - [`work_area`]: Per-worker limits on how far a job may send them
+//! - [`forbid`]: Items the colony leaves alone, by order or for danger
+//! - [`experiment`]: Batch runs of a scenario over many seeds, with CSV output
+//! - [`burrowing`]: Invaders that dig through walls when no open path reaches the fort
+//! - [`director`]: Raids sized and timed by fort wealth, population and region difficulty
+//! - [`bugreport`]: Bug-report bundles of a save, replay log, metrics and recent events
+//! - [`vision`]: Vision radius modifiers from night, fog, injuries and lanterns
+//! - [`colony`]: Colony summary rows (job, needs, mood, health, location) for overview screens
 //!
 //! ## Usage Example
 //!
 //! ```rust
 //! use bevy_ecs::prelude::*;
 //! use gc_core::prelude::*;
-//! use gc_core::systems;
+//! use gc_core::{jobs, systems};
 //!
 //! let mut world = World::new();
 //!
@@ -45,6 +97,7 @@
 //! world.insert_resource(GameMap::new(50, 50));
 //! world.insert_resource(JobBoard::default());
 //! world.insert_resource(ActiveJobs::default());
+//! world.insert_resource(ItemSpawnQueue::default());
 //! world.insert_resource(systems::Time::new(16)); // 16ms per tick
 //! world.insert_resource(systems::DeterministicRng::new(42));
 //!
@@ -61,7 +114,11 @@
 //! let mut schedule = Schedule::default();
 //! schedule.add_systems((
 //!     systems::movement,
-//!     systems::mining_execution_system,
+//!     (
+//!         jobs::mine_job_execution_system,
+//!         jobs::process_item_spawn_queue_system,
+//!     )
+//!         .chain(),
 //!     systems::advance_time,
 //! ));
 //!
@@ -74,7 +131,7 @@
 /// Action logging for lifecycle events and debugging
 /// Provides a centralized log for tracking significant simulation events
 /// such as job assignments, mining operations, and item movements
-#[derive(bevy_ecs::prelude::Resource, Default, Debug)]
+#[derive(bevy_ecs::prelude::Resource, Default, Debug, Clone)]
 pub struct ActionLog {
     /// Chronological list of logged events
     pub events: Vec<String>,
@@ -103,24 +160,76 @@ impl ActionLog {
 /// // Now you have access to Position, GameMap, JobBoard, etc.
 /// ```
 pub mod prelude {
+    pub use crate::announcements::*;
+    pub use crate::blueprint::*;
     pub use crate::bootstrap::*;
+    pub use crate::bugreport::*;
+    pub use crate::burial::*;
+    pub use crate::burrowing::*;
+    pub use crate::caverns::*;
+    pub use crate::checkpoint::*;
+    pub use crate::colony::*;
+    pub use crate::combat::*;
+    pub use crate::command::*;
     pub use crate::components::*;
+    pub use crate::construction::*;
+    pub use crate::danger::*;
     pub use crate::designations::*;
+    pub use crate::director::*;
+    pub use crate::editor::*;
+    pub use crate::equipment::*;
+    pub use crate::error::*;
+    pub use crate::experiment::*;
+    pub use crate::faults::*;
+    pub use crate::fire::*;
+    pub use crate::forbid::*;
     pub use crate::fov::*;
+    pub use crate::haul_priority::*;
+    pub use crate::health::*;
+    pub use crate::history::*;
     pub use crate::inventory::*;
     pub use crate::jobs::*;
+    pub use crate::lighting::*;
+    pub use crate::livestock::*;
+    pub use crate::lockstep::*;
+    pub use crate::manager::*;
     pub use crate::mapgen::*;
+    pub use crate::mechanisms::*;
+    pub use crate::metrics::*;
+    pub use crate::names::*;
+    pub use crate::overmap::*;
     pub use crate::path::*;
+    pub use crate::plans::*;
+    pub use crate::presets::*;
+    pub use crate::production::*;
+    pub use crate::quality::*;
+    pub use crate::render::*;
     pub use crate::save::*;
+    pub use crate::schedule_check::*;
+    pub use crate::sim_thread::*;
+    pub use crate::soak::*;
     pub use crate::stockpiles::*;
     pub use crate::systems::*;
+    pub use crate::tags::*;
+    pub use crate::telemetry::*;
+    pub use crate::temperature::*;
+    pub use crate::toggles::*;
+    pub use crate::traffic::*;
+    pub use crate::tutorial::*;
+    pub use crate::view::*;
+    pub use crate::vision::*;
+    pub use crate::work_area::*;
     pub use crate::world::*;
+    pub use crate::yields::*;
+    pub use crate::zones::*;
     pub use crate::ActionLog;
 }
 
 // Public module declarations
 // Each module contains related functionality for specific simulation aspects
 
+/// Reusable designation/zone layouts with JSON import/export
+pub mod blueprint;
 /// ECS components for entities, spatial data, and game state
 pub mod components;
 /// Player designation system for marking areas for mining, construction, etc.
@@ -147,4 +256,160 @@ pub mod world;
 /// Bootstrap helpers for building standard worlds and schedules shared by CLI/TUI
 pub mod bootstrap;
 
+/// Read-only, serializable world snapshots for renderers and tools
+pub mod view;
+
+/// Serializable world commands applied at tick boundaries
+pub mod command;
+
+/// Background simulation thread driven by a command channel
+pub mod sim_thread;
+
+/// Transport-agnostic JSON-RPC protocol for remote control
+pub mod rpc;
+
+/// Lockstep multiplayer session layer
+pub mod lockstep;
+
+/// Player-facing announcements and alerts
+pub mod announcements;
+
+/// Deterministic name generation
+pub mod names;
+
+/// Injuries, regeneration, and resting
+pub mod health;
+
+/// Item quality from crafter skill
+pub mod quality;
+
+/// Weapon/armor slots, equip jobs, and squad uniforms
+pub mod equipment;
+
+/// Attack resolution using equipped stats
+pub mod combat;
+
+/// Map editor brushes for building scenarios
+pub mod editor;
+
+/// Region-scale overmap generation and embark site extraction
+pub mod overmap;
+
+/// Load-test scenarios for throughput regression checks
+pub mod bench;
+
+/// Schedule validation: duplicate systems, missing resources, ambiguous orderings
+pub mod schedule_check;
+
+/// Zone kinds (stockpile, refuse, meeting, hospital) and their behaviors
+pub mod zones;
+
+/// Per-faction danger map from sighted hostiles
+pub mod danger;
+
+/// Data-driven mining yields and gem finds
+pub mod yields;
+
+/// Construction sites, material delivery and build jobs
+pub mod construction;
+
+/// Determinism guard: wall-clock/RNG checks and stream audits
+pub mod determinism;
+
+/// Item tags, tag queries and tag-based filters
+pub mod tags;
+
+/// Production stations, recipes and fuel
+pub mod production;
+
+/// Fire ignition, spread and firefighting
+pub mod fire;
+
+/// Pets, livestock, pastures and animal products
+pub mod livestock;
+
+/// Manager: standing orders that queue crafts from stock levels
+pub mod manager;
+
+/// Burial, graves and ghosts of the unburied dead
+pub mod burial;
+
+/// Hidden caverns revealed by mining
+pub mod caverns;
+
+/// Levers and pressure plates linked to doors and traps
+pub mod mechanisms;
+
+/// Anomalies systems recover from, reported for logs and metrics
+pub mod faults;
+
+/// Why an inventory move or map write was refused
+pub mod error;
+
+/// Torches, braziers, daylight and the light map
+pub mod lighting;
+
+/// Render glyphs and the per-tile draw list shared by renderers
+pub mod render;
+
+/// Batch export and import of designations and zones
+pub mod plans;
+
+/// Per-tile traffic counts for spotting congestion
+pub mod traffic;
+
+/// In-memory checkpoints and deterministic restore
+pub mod checkpoint;
+
+/// Runtime subsystem switches for debugging
+pub mod toggles;
+
+/// Scripted tutorials stepping the player through the basics
+pub mod tutorial;
+
+/// Soak runs: invariant checks for long stability runs
+pub mod soak;
+
+/// Per-category entity counts and unbounded-growth alerts
+pub mod telemetry;
+
+/// Which loose items are hauled first
+pub mod haul_priority;
+
+/// Named zone and designation settings for selections
+pub mod presets;
+
+/// Counters of work done, for comparing runs
+pub mod metrics;
+
+/// Many-seed batch runs summarized as CSV
+pub mod experiment;
+
+/// Tile temperatures relaxed room by room, and what they do to goblins and food
+pub mod temperature;
+
+/// What each entity has been doing lately
+pub mod history;
+
+/// How far from home each worker may be sent
+pub mod work_area;
+
+/// Items left where they lie until unforbidden
+pub mod forbid;
+
+/// Invaders that tunnel toward the fort
+pub mod burrowing;
+
+/// Raid scheduling scaled to the fort's wealth
+pub mod director;
+
+/// Reproducible bug-report bundles
+pub mod bugreport;
+
+/// Vision radius modifier stacks
+pub mod vision;
+
+/// Colony summary query for overview screens
+pub mod colony;
+
 // Removed empty internal tests module; tests live in `tests/` integration folder.
@@ -0,0 +1,205 @@
+//! Lighting: light sources, the day/night cycle and darkness
+//!
+//! [`lighting_system`] keeps a [`LightMap`] of lit tiles. By day every
+//! tile open to the outside is lit; tiles in enclosed rooms (walkable
+//! space walled off from the map edge) and everything at night rely on
+//! [`LightSource`]s: torches and braziers built as constructions, which
+//! light the tiles they can see within their radius. The time of day comes
+//! from [`DayCycle`] and the `Time` tick.
+//!
+//! With [`LightingConfig::darkness`] on, field of view only shows lit tiles
+//! beyond [`LightingConfig::dark_radius`] (see `fov`), so a lamp lets
+//! workers see down a dark tunnel. With it off (the default) lighting has
+//! no effect on what anyone sees.
+
+use crate::fov::{is_opaque, visible_from};
+use crate::systems::Time;
+use crate::world::{GameMap, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// An entity that lights the tiles it can see within `radius`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightSource {
+    pub radius: i32,
+}
+
+impl LightSource {
+    /// Light of a finished torch
+    pub const TORCH: LightSource = LightSource { radius: 5 };
+    /// Light of a finished brazier
+    pub const BRAZIER: LightSource = LightSource { radius: 8 };
+}
+
+/// Length of a day and the daylight hours within it, in ticks
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayCycle {
+    pub ticks_per_day: u64,
+    /// Tick of the day the sun rises
+    pub dawn: u64,
+    /// Tick of the day the sun sets
+    pub dusk: u64,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self {
+            ticks_per_day: 2400,
+            dawn: 600,
+            dusk: 1800,
+        }
+    }
+}
+
+impl DayCycle {
+    /// Whether the sun is up at `tick`; a zero-length day is always day
+    pub fn is_day(&self, tick: u64) -> bool {
+        if self.ticks_per_day == 0 {
+            return true;
+        }
+        (self.dawn..self.dusk).contains(&(tick % self.ticks_per_day))
+    }
+}
+
+/// How darkness limits field of view
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightingConfig {
+    /// Only lit tiles are visible beyond `dark_radius`
+    pub darkness: bool,
+    /// How far anyone sees without light
+    pub dark_radius: i32,
+}
+
+impl Default for LightingConfig {
+    fn default() -> Self {
+        Self {
+            darkness: false,
+            dark_radius: 1,
+        }
+    }
+}
+
+/// Which tiles are lit this tick
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LightMap {
+    pub width: u32,
+    pub height: u32,
+    /// Whether the sun is up
+    pub daylight: bool,
+    /// Row-major: tiles open to the outside, lit by day
+    pub outdoors: Vec<bool>,
+    /// Tiles lit by light sources
+    pub lamps: HashSet<(i32, i32)>,
+}
+
+impl LightMap {
+    /// Light for `map` at the given time of day with `lights` as (x, y, radius)
+    pub fn compute(
+        map: &GameMap,
+        daylight: bool,
+        lights: impl IntoIterator<Item = (i32, i32, i32)>,
+    ) -> Self {
+        let mut lamps = HashSet::new();
+        for (x, y, radius) in lights {
+            lamps.extend(visible_from(map, x, y, radius));
+        }
+        Self {
+            width: map.width,
+            height: map.height,
+            daylight,
+            outdoors: outdoor_tiles(map),
+            lamps,
+        }
+    }
+
+    /// Whether the tile is open to the sky (not inside an enclosed room)
+    pub fn is_outdoors(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+        self.outdoors[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    pub fn is_lit(&self, x: i32, y: i32) -> bool {
+        (self.daylight && self.is_outdoors(x, y)) || self.lamps.contains(&(x, y))
+    }
+}
+
+/// Tiles reachable from the map edge through see-through tiles, plus the
+/// walls bordering them; everything else is inside an enclosed room or
+/// solid rock
+pub fn outdoor_tiles(map: &GameMap) -> Vec<bool> {
+    let (w, h) = (map.width as i32, map.height as i32);
+    let mut open = vec![false; map.tiles.len()];
+    let mut queue = VecDeque::new();
+    let visit = |x: i32, y: i32, open: &mut Vec<bool>, queue: &mut VecDeque<(i32, i32)>| {
+        if let Some(i) = map.idx(x, y) {
+            if !open[i] && !is_opaque(map.tiles[i]) {
+                open[i] = true;
+                queue.push_back((x, y));
+            }
+        }
+    };
+    for x in 0..w {
+        visit(x, 0, &mut open, &mut queue);
+        visit(x, h - 1, &mut open, &mut queue);
+    }
+    for y in 0..h {
+        visit(0, y, &mut open, &mut queue);
+        visit(w - 1, y, &mut open, &mut queue);
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            visit(x + dx, y + dy, &mut open, &mut queue);
+        }
+    }
+    // Sunlight falls on the faces of walls around open ground too
+    let mut lit = open.clone();
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) as usize;
+            if !open[i] && is_opaque(map.tiles[i]) {
+                lit[i] = (-1..=1)
+                    .any(|dy| (-1..=1).any(|dx| map.idx(x + dx, y + dy).is_some_and(|j| open[j])));
+            }
+        }
+    }
+    lit
+}
+
+/// Recompute the [`LightMap`] when the map, a light source, or the time
+/// of day changed
+///
+/// The map is only written when the light actually differs, so field of
+/// view can tell a change apart from a tick without one.
+pub fn lighting_system(
+    map: Res<GameMap>,
+    time: Option<Res<Time>>,
+    cycle: Option<Res<DayCycle>>,
+    mut light: ResMut<LightMap>,
+    lights: Query<(Ref<Position>, Ref<LightSource>)>,
+    mut removed: RemovedComponents<LightSource>,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    let daylight = cycle.as_deref().copied().unwrap_or_default().is_day(tick);
+    let lights_changed = removed.read().count() > 0
+        || lights
+            .iter()
+            .any(|(pos, source)| pos.is_changed() || source.is_changed());
+    let stale = map.is_changed()
+        || lights_changed
+        || daylight != light.daylight
+        || light.outdoors.len() != map.tiles.len();
+    if !stale {
+        return;
+    }
+    let next = LightMap::compute(
+        &map,
+        daylight,
+        lights.iter().map(|(p, s)| (p.0, p.1, s.radius)),
+    );
+    if *light != next {
+        *light = next;
+    }
+}
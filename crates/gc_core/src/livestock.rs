@@ -0,0 +1,354 @@
+//! Pets and livestock: taming, pastures and animal products
+//!
+//! Every [`Animal`] has a [`Species`]. Taming one ([`tame`]) gives it an
+//! [`OwnedBy`] naming the goblin it belongs to and a [`Livestock`] record;
+//! from then on:
+//!
+//! - dogs are pets and stay within [`PET_LEASH`] tiles of their owner;
+//! - sheep graze: they must be kept in a [`ZoneKind::Pasture`], walk to the
+//!   nearest one when outside, and only grow wool while inside;
+//! - chickens lay an [`ItemType::Egg`] where they stand every
+//!   [`LivestockConfig::lay_interval`] ticks, which hauling picks up.
+//!
+//! A sheep whose wool has grown ([`LivestockConfig::wool_interval`] ticks of
+//! grazing) posts a [`JobKind::Shear`]; an idle carrier walks over and
+//! shears it, dropping an [`ItemType::Wool`]. All timers count ticks, so
+//! production is deterministic.
+
+use crate::components::{
+    Animal, AssignedJob, Carriable, Carrier, Dead, Faction, Item, ItemType, MovementMode,
+    MovementOverride, ZoneBounds,
+};
+use crate::determinism::RngStream;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, faction_of, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId,
+    JobKind,
+};
+use crate::path::PathService;
+use crate::systems::{travel, DeterministicRng, MovementConfig};
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Name, Position};
+use crate::zones::{in_zone_of, nearest_zone, ZoneKind};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Furthest (Chebyshev) distance a pet strays from its owner
+pub const PET_LEASH: i32 = 2;
+
+/// Kind of animal
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Species {
+    Chicken,
+    Sheep,
+    Dog,
+}
+
+impl Species {
+    pub const ALL: [Species; 3] = [Species::Chicken, Species::Sheep, Species::Dog];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Species::Chicken => "chicken",
+            Species::Sheep => "sheep",
+            Species::Dog => "dog",
+        }
+    }
+
+    /// Whether tame animals of this species must be kept in a pasture
+    pub fn grazes(self) -> bool {
+        self == Species::Sheep
+    }
+
+    /// Whether tame animals of this species follow their owner around
+    pub fn is_pet(self) -> bool {
+        self == Species::Dog
+    }
+}
+
+/// Why an animal could not be tamed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LivestockError {
+    #[error("entity is not an animal")]
+    NotAnAnimal,
+    #[error("animal is dead")]
+    Dead,
+    #[error("animal already has an owner")]
+    AlreadyOwned,
+    #[error("owner does not exist")]
+    NoOwner,
+}
+
+/// The goblin a tame animal belongs to
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnedBy(pub Entity);
+
+/// Production state of a tame animal
+#[derive(Component, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Livestock {
+    /// Ticks toward the next egg, or of grazing toward the next fleece
+    pub timer: u32,
+    /// A fleece has grown and is waiting to be shorn
+    pub wool_ready: bool,
+    /// Shear job posted for the grown fleece
+    pub shear_job: Option<JobId>,
+}
+
+/// Production timers
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivestockConfig {
+    /// Ticks between eggs
+    pub lay_interval: u32,
+    /// Ticks of grazing to grow a fleece
+    pub wool_interval: u32,
+}
+
+impl Default for LivestockConfig {
+    fn default() -> Self {
+        Self {
+            lay_interval: 60,
+            wool_interval: 100,
+        }
+    }
+}
+
+/// Make `animal` livestock (or a pet) owned by `owner`
+pub fn tame(world: &mut World, animal: Entity, owner: Entity) -> Result<(), LivestockError> {
+    let Some(target) = world.get_entity(animal) else {
+        return Err(LivestockError::NotAnAnimal);
+    };
+    if !target.contains::<Animal>() || !target.contains::<Species>() {
+        return Err(LivestockError::NotAnAnimal);
+    }
+    if target.contains::<Dead>() {
+        return Err(LivestockError::Dead);
+    }
+    if target.contains::<OwnedBy>() {
+        return Err(LivestockError::AlreadyOwned);
+    }
+    if world.get_entity(owner).is_none() {
+        return Err(LivestockError::NoOwner);
+    }
+    world
+        .entity_mut(animal)
+        .insert((OwnedBy(owner), Livestock::default()));
+    Ok(())
+}
+
+/// Keep grazers in pastures and pets near their owners
+///
+/// Grazers outside every pasture step toward the nearest one; pets further
+/// than [`PET_LEASH`] from their owner step toward them. Animals move one
+/// tile per tick.
+#[allow(clippy::type_complexity)]
+pub fn livestock_movement_system(
+    map: Option<Res<GameMap>>,
+    mut animals: Query<
+        (&Species, &OwnedBy, &mut Position),
+        (With<Livestock>, Without<Dead>, Without<ZoneBounds>),
+    >,
+    zones: Query<(Entity, &ZoneKind, &Position, &ZoneBounds), Without<Livestock>>,
+    owners: Query<&Position, (Without<Livestock>, Without<ZoneBounds>)>,
+) {
+    for (species, owner, mut pos) in animals.iter_mut() {
+        if species.grazes() {
+            let inside = in_zone_of(
+                zones.iter().map(|(_, k, _, b)| (k, b)),
+                ZoneKind::Pasture,
+                pos.0,
+                pos.1,
+            );
+            if inside {
+                continue;
+            }
+            let nearest = nearest_zone(
+                zones.iter().map(|(e, k, p, _)| (e, k, p)),
+                ZoneKind::Pasture,
+                (pos.0, pos.1),
+            );
+            if let Some((_, center)) = nearest {
+                let center = (center.0, center.1);
+                travel(&mut pos, center, MovementMode::Step, map.as_deref(), None);
+            }
+        } else if species.is_pet() {
+            let Ok(at) = owners.get(owner.0) else {
+                continue;
+            };
+            let (dx, dy) = (at.0 - pos.0, at.1 - pos.1);
+            if dx.abs().max(dy.abs()) > PET_LEASH {
+                travel(
+                    &mut pos,
+                    (at.0, at.1),
+                    MovementMode::Step,
+                    map.as_deref(),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// Advance production timers: chickens lay, grazing sheep grow wool
+pub fn livestock_production_system(
+    mut commands: Commands,
+    config: Option<Res<LivestockConfig>>,
+    mut animals: Query<(&Species, &Position, &mut Livestock), Without<Dead>>,
+    zones: Query<(&ZoneKind, &ZoneBounds)>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    for (species, pos, mut livestock) in animals.iter_mut() {
+        match species {
+            Species::Chicken => {
+                livestock.timer += 1;
+                if livestock.timer >= config.lay_interval {
+                    livestock.timer = 0;
+                    commands.spawn((
+                        Item {
+                            item_type: ItemType::Egg,
+                        },
+                        Carriable,
+                        *pos,
+                        Name("Egg".into()),
+                    ));
+                }
+            }
+            Species::Sheep => {
+                if livestock.wool_ready
+                    || !in_zone_of(zones.iter(), ZoneKind::Pasture, pos.0, pos.1)
+                {
+                    continue;
+                }
+                livestock.timer += 1;
+                if livestock.timer >= config.wool_interval {
+                    livestock.timer = 0;
+                    livestock.wool_ready = true;
+                }
+            }
+            Species::Dog => {}
+        }
+    }
+}
+
+/// Post a shear job for every sheep with a grown fleece and no live job
+///
+/// Jobs go to the faction of the sheep's owner.
+pub fn shearing_job_system(
+    mut board: ResMut<JobBoard>,
+    active: Res<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    mut animals: Query<(Entity, &Position, &OwnedBy, &mut Livestock), Without<Dead>>,
+    factions: Query<&Faction>,
+) {
+    let live: HashSet<JobId> = board
+        .0
+        .iter()
+        .map(|j| j.id)
+        .chain(active.jobs.keys().copied())
+        .collect();
+    let mut order: Vec<Entity> = animals
+        .iter()
+        .filter(|(_, _, _, l)| l.wool_ready)
+        .map(|(e, ..)| e)
+        .collect();
+    order.sort();
+    for animal in order {
+        let (_, pos, owner, mut livestock) = animals.get_mut(animal).expect("listed above");
+        if livestock.shear_job.is_some_and(|id| live.contains(&id)) {
+            continue;
+        }
+        let id = add_job_for(
+            &mut board,
+            JobKind::Shear { x: pos.0, y: pos.1 },
+            IssuedBy::System,
+            faction_of(factions.get(owner.0).ok()),
+            rng.stream(RngStream::Job, "shearing_job_system"),
+        );
+        livestock.shear_job = Some(id);
+    }
+}
+
+/// Hand one queued shear job to an idle carrier of its faction per run, within
+/// its [`WorkArea`]
+pub fn shearing_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut carriers: Query<BoardWorker, (With<Carrier>, Without<Dead>)>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut carriers,
+        |kind| matches!(kind, JobKind::Shear { .. }),
+    );
+}
+
+/// Walk shearers to their sheep and shear it, dropping the wool there
+///
+/// The shearer follows the sheep if it has moved since the job was posted.
+/// A job whose sheep is gone or already shorn is dropped.
+#[allow(clippy::type_complexity)]
+pub fn shearing_execution_system(
+    mut commands: Commands,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut active: ResMut<ActiveJobs>,
+    mut shearers: Query<
+        (&mut AssignedJob, &mut Position, Option<&MovementOverride>),
+        (With<Carrier>, Without<Stalled>, Without<Livestock>),
+    >,
+    mut animals: Query<(&Position, &mut Livestock), Without<Carrier>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    for (mut assigned, mut pos, movement) in shearers.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        if !matches!(
+            active.jobs.get(&id).map(|j| &j.kind),
+            Some(JobKind::Shear { .. })
+        ) {
+            continue;
+        }
+        let Some((at, mut livestock)) = animals
+            .iter_mut()
+            .find(|(_, l)| l.shear_job == Some(id) && l.wool_ready)
+        else {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        };
+        let (dx, dy) = (pos.0 - at.0, pos.1 - at.1);
+        if dx.abs() > 1 || dy.abs() > 1 {
+            let beside = (at.0 + dx.signum(), at.1 + dy.signum());
+            travel(
+                &mut pos,
+                beside,
+                config.mode_for(movement),
+                map.as_deref(),
+                paths.as_deref_mut(),
+            );
+            continue;
+        }
+        livestock.wool_ready = false;
+        livestock.shear_job = None;
+        commands.spawn((
+            Item {
+                item_type: ItemType::Wool,
+            },
+            Carriable,
+            *at,
+            Name("Wool".into()),
+        ));
+        active.jobs.remove(&id);
+        assigned.0 = None;
+    }
+}
@@ -0,0 +1,242 @@
+//! Lockstep session layer for deterministic multiplayer
+//!
+//! Every peer runs the full simulation from the same seed. Instead of
+//! synchronizing state, peers exchange the [`WorldCommand`]s each of them
+//! issued for a tick; a tick only advances once inputs from *all* peers are
+//! present, and commands are applied in peer-id order so every world sees the
+//! same sequence. Every `hash_interval` ticks peers exchange a [`world_hash`]
+//! and a mismatch is reported as [`LockstepError::Desync`].
+//!
+//! The session is transport-agnostic: callers ship [`LockstepMessage`]s over
+//! whatever channel they like (they are serde-serializable).
+
+use crate::command::{apply_world_command_as, WorldCommand};
+use crate::determinism::run_tick;
+use crate::jobs::IssuedBy;
+use crate::view::snapshot_world;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Identifier of a lockstep participant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PeerId(pub u32);
+
+/// Message exchanged between peers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LockstepMessage {
+    /// Commands a peer issued for `tick` (possibly none)
+    Inputs {
+        peer: PeerId,
+        tick: u64,
+        commands: Vec<WorldCommand>,
+    },
+    /// A peer's world hash after completing `tick`
+    Hash { peer: PeerId, tick: u64, hash: u64 },
+}
+
+/// Lockstep protocol failures
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum LockstepError {
+    #[error("unknown peer {0:?}")]
+    UnknownPeer(PeerId),
+    #[error("inputs for tick {tick} from {peer:?} arrived after the tick was simulated")]
+    LateInputs { peer: PeerId, tick: u64 },
+    #[error("peer {peer:?} sent inputs for tick {tick} twice")]
+    DuplicateInputs { peer: PeerId, tick: u64 },
+    #[error("world hashes diverged at tick {tick}: {hashes:?}")]
+    Desync {
+        tick: u64,
+        hashes: BTreeMap<PeerId, u64>,
+    },
+}
+
+/// Deterministic 64-bit hash of the observable world state
+///
+/// Hashes the canonical [`WorldView`](crate::view::WorldView) encoding with
+/// FNV-1a, so it is stable across processes and platforms.
+pub fn world_hash(world: &mut World) -> u64 {
+    let view = snapshot_world(world);
+    let bytes = serde_json::to_vec(&view).expect("world view serializes");
+    fnv1a64(&bytes)
+}
+
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// One peer's view of a lockstep session
+#[derive(Debug, Clone)]
+pub struct LockstepSession {
+    local: PeerId,
+    peers: Vec<PeerId>,
+    /// Next tick to simulate (ticks are numbered from 0)
+    next_tick: u64,
+    hash_interval: u64,
+    inputs: BTreeMap<u64, BTreeMap<PeerId, Vec<WorldCommand>>>,
+    hashes: BTreeMap<u64, BTreeMap<PeerId, u64>>,
+}
+
+impl LockstepSession {
+    /// Create a session for `local` among `peers` (which must include `local`)
+    ///
+    /// Hashes are exchanged every `hash_interval` ticks; 0 disables checks.
+    pub fn new(local: PeerId, peers: &[PeerId], hash_interval: u64) -> Self {
+        let mut peers = peers.to_vec();
+        if !peers.contains(&local) {
+            peers.push(local);
+        }
+        peers.sort_unstable();
+        peers.dedup();
+        Self {
+            local,
+            peers,
+            next_tick: 0,
+            hash_interval,
+            inputs: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+        }
+    }
+
+    pub fn local(&self) -> PeerId {
+        self.local
+    }
+
+    /// Next tick waiting to be simulated
+    pub fn next_tick(&self) -> u64 {
+        self.next_tick
+    }
+
+    /// Record the local commands for the next unsubmitted tick
+    ///
+    /// Returns the message to broadcast to other peers.
+    pub fn submit_local(
+        &mut self,
+        commands: Vec<WorldCommand>,
+    ) -> Result<LockstepMessage, LockstepError> {
+        let tick = self
+            .inputs
+            .iter()
+            .rev()
+            .find(|(_, by_peer)| by_peer.contains_key(&self.local))
+            .map_or(self.next_tick, |(t, _)| t + 1)
+            .max(self.next_tick);
+        let msg = LockstepMessage::Inputs {
+            peer: self.local,
+            tick,
+            commands,
+        };
+        self.receive(msg.clone())?;
+        Ok(msg)
+    }
+
+    /// Accept a message from any peer (including echoes of our own)
+    pub fn receive(&mut self, msg: LockstepMessage) -> Result<(), LockstepError> {
+        match msg {
+            LockstepMessage::Inputs {
+                peer,
+                tick,
+                commands,
+            } => {
+                self.check_peer(peer)?;
+                if tick < self.next_tick {
+                    return Err(LockstepError::LateInputs { peer, tick });
+                }
+                let slot = self.inputs.entry(tick).or_default();
+                if slot.contains_key(&peer) {
+                    return Err(LockstepError::DuplicateInputs { peer, tick });
+                }
+                slot.insert(peer, commands);
+                Ok(())
+            }
+            LockstepMessage::Hash { peer, tick, hash } => {
+                self.check_peer(peer)?;
+                self.hashes.entry(tick).or_default().insert(peer, hash);
+                self.verify_hashes(tick)
+            }
+        }
+    }
+
+    /// Whether every peer has supplied inputs for the next tick
+    pub fn ready(&self) -> bool {
+        self.inputs
+            .get(&self.next_tick)
+            .is_some_and(|by_peer| by_peer.len() == self.peers.len())
+    }
+
+    /// Peers whose inputs for the next tick are still missing
+    pub fn waiting_on(&self) -> Vec<PeerId> {
+        let have = self.inputs.get(&self.next_tick);
+        self.peers
+            .iter()
+            .copied()
+            .filter(|p| have.map_or(true, |h| !h.contains_key(p)))
+            .collect()
+    }
+
+    /// Simulate the next tick if all inputs are present
+    ///
+    /// Applies commands in peer-id order, runs the schedule once, and on hash
+    /// ticks returns the local [`LockstepMessage::Hash`] to broadcast.
+    /// Returns `Ok(None)` without advancing when inputs are missing.
+    pub fn advance(
+        &mut self,
+        world: &mut World,
+        schedule: &mut Schedule,
+    ) -> Result<Option<LockstepMessage>, LockstepError> {
+        if !self.ready() {
+            return Ok(None);
+        }
+        let tick = self.next_tick;
+        let by_peer = self.inputs.remove(&tick).unwrap_or_default();
+        for (peer, commands) in &by_peer {
+            for command in commands {
+                apply_world_command_as(world, command, IssuedBy::Player(peer.0));
+            }
+        }
+        run_tick(schedule, world);
+        self.next_tick += 1;
+
+        if self.hash_interval == 0 || (tick + 1) % self.hash_interval != 0 {
+            return Ok(None);
+        }
+        let msg = LockstepMessage::Hash {
+            peer: self.local,
+            tick,
+            hash: world_hash(world),
+        };
+        self.receive(msg.clone())?;
+        Ok(Some(msg))
+    }
+
+    fn check_peer(&self, peer: PeerId) -> Result<(), LockstepError> {
+        if self.peers.binary_search(&peer).is_ok() {
+            Ok(())
+        } else {
+            Err(LockstepError::UnknownPeer(peer))
+        }
+    }
+
+    /// Compare hashes for `tick` once every peer reported one
+    fn verify_hashes(&mut self, tick: u64) -> Result<(), LockstepError> {
+        let Some(hashes) = self.hashes.get(&tick) else {
+            return Ok(());
+        };
+        if hashes.len() < self.peers.len() {
+            return Ok(());
+        }
+        let hashes = self.hashes.remove(&tick).unwrap_or_default();
+        let mut values = hashes.values();
+        let first = values.next();
+        if values.any(|h| Some(h) != first) {
+            return Err(LockstepError::Desync { tick, hashes });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+//! Manager: standing orders that keep stock levels up
+//!
+//! A [`StandingOrder`] names a recipe and a band of stock levels, like
+//! "keep at least 20 bars, topping up to 25". Each tick
+//! [`manager_system`] walks the orders front to back:
+//!
+//! - an idle order starts refilling once fewer than `min` of the recipe's
+//!   product lie in stockpiles;
+//! - a refilling order queues crafts at stations of the recipe's kind until
+//!   the products in stockpiles, on their way there, and still to be
+//!   crafted reach `max`;
+//! - it goes idle again once stockpiles hold `max`.
+//!
+//! The gap between `min` and `max` is the hysteresis: taking one item out
+//! of a full stockpile does not queue a craft, so stations are not flipped
+//! between working and idle every time stock moves. New crafts go to the
+//! station of the right kind with the fewest orders, lowest entity first.
+
+use crate::components::{CarriedBy, Item, ItemType};
+use crate::equipment::EquippedBy;
+use crate::production::{RecipeBook, Station};
+use crate::stockpiles::StockpileContents;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Why a standing order was refused
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ManagerError {
+    #[error("unknown recipe '{0}'")]
+    UnknownRecipe(String),
+    #[error("refill level {max} is below the trigger level {min}")]
+    InvertedBand { min: u32, max: u32 },
+}
+
+/// Keep between `min` and `max` of a recipe's product stockpiled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandingOrder {
+    pub recipe: String,
+    /// Stockpiled count below which the order starts refilling
+    pub min: u32,
+    /// Count the order refills up to
+    pub max: u32,
+    /// Whether the order is refilling
+    #[serde(default)]
+    pub refilling: bool,
+    /// Crafts queued by this order so far
+    #[serde(default)]
+    pub issued: u32,
+}
+
+impl StandingOrder {
+    pub fn new(recipe: impl Into<String>, min: u32, max: u32) -> Result<Self, ManagerError> {
+        if max < min {
+            return Err(ManagerError::InvertedBand { min, max });
+        }
+        Ok(Self {
+            recipe: recipe.into(),
+            min,
+            max,
+            refilling: false,
+            issued: 0,
+        })
+    }
+}
+
+/// Every standing order, evaluated in order
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagerOrders {
+    pub orders: Vec<StandingOrder>,
+}
+
+impl ManagerOrders {
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Add `order`, replacing any standing order for the same recipe
+    pub fn set(&mut self, order: StandingOrder) {
+        match self.orders.iter_mut().find(|o| o.recipe == order.recipe) {
+            Some(existing) => *existing = order,
+            None => self.orders.push(order),
+        }
+    }
+
+    /// Drop the standing order for `recipe`; returns whether there was one
+    pub fn remove(&mut self, recipe: &str) -> bool {
+        let before = self.orders.len();
+        self.orders.retain(|o| o.recipe != recipe);
+        self.orders.len() != before
+    }
+
+    pub fn get(&self, recipe: &str) -> Option<&StandingOrder> {
+        self.orders.iter().find(|o| o.recipe == recipe)
+    }
+}
+
+/// Keep between `min` and `max` of `recipe`'s product stockpiled
+pub fn add_standing_order(
+    world: &mut World,
+    recipe: &str,
+    min: u32,
+    max: u32,
+) -> Result<(), ManagerError> {
+    let known = world
+        .get_resource::<RecipeBook>()
+        .cloned()
+        .unwrap_or_default()
+        .get(recipe)
+        .is_some();
+    if !known {
+        return Err(ManagerError::UnknownRecipe(recipe.to_string()));
+    }
+    let order = StandingOrder::new(recipe, min, max)?;
+    world
+        .get_resource_or_insert_with(ManagerOrders::default)
+        .set(order);
+    Ok(())
+}
+
+/// Evaluate standing orders and queue the crafts they call for
+///
+/// Stockpiled counts come from [`StockpileContents`] as of its last run.
+/// Products lying loose or being carried count as on their way; equipped
+/// gear does not count at all.
+#[allow(clippy::type_complexity)]
+pub fn manager_system(
+    manager: Option<ResMut<ManagerOrders>>,
+    book: Option<Res<RecipeBook>>,
+    contents: Option<Res<StockpileContents>>,
+    items: Query<&Item, (Or<(With<Position>, With<CarriedBy>)>, Without<EquippedBy>)>,
+    mut stations: Query<(Entity, &mut Station)>,
+) {
+    let Some(mut manager) = manager else {
+        return;
+    };
+    if manager.is_empty() {
+        return;
+    }
+    let book_fallback;
+    let book = match book.as_deref() {
+        Some(b) => b,
+        None => {
+            book_fallback = RecipeBook::default();
+            &book_fallback
+        }
+    };
+    let existing =
+        |item_type: ItemType| items.iter().filter(|i| i.item_type == item_type).count() as u32;
+
+    for order in manager.orders.iter_mut() {
+        let Some(recipe) = book.get(&order.recipe) else {
+            continue;
+        };
+        let stockpiled = contents
+            .as_deref()
+            .map_or(0, |c| c.total(recipe.output) as u32);
+        if stockpiled >= order.max {
+            order.refilling = false;
+            continue;
+        }
+        if !order.refilling {
+            if stockpiled >= order.min {
+                continue;
+            }
+            order.refilling = true;
+        }
+        let queued: u32 = stations
+            .iter()
+            .flat_map(|(_, s)| s.orders.iter())
+            .filter(|name| **name == order.recipe)
+            .count() as u32;
+        let projected = existing(recipe.output).max(stockpiled) + queued;
+        for _ in projected..order.max {
+            let target = stations
+                .iter()
+                .filter(|(_, s)| s.kind == recipe.station)
+                .map(|(e, s)| (s.orders.len(), e))
+                .min();
+            let Some((_, entity)) = target else {
+                break;
+            };
+            let (_, mut station) = stations.get_mut(entity).expect("listed above");
+            if station.queue(recipe).is_err() {
+                break;
+            }
+            order.issued += 1;
+        }
+    }
+}
@@ -1,5 +1,12 @@
-use crate::world::{GameMap, TileKind};
+use crate::caverns::{Cavern, Caverns};
+use crate::livestock::Species;
+use crate::world::{
+    CaveParams, GameMap, MapGenAlgorithm, MapGenConfig, Material, MaterialMap, TileKind,
+};
 use noise::{Fbm, NoiseFn, Seedable};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
 
 pub struct MapGenerator;
 
@@ -14,26 +21,258 @@ impl MapGenerator {
         Self
     }
 
+    /// Generate with the default (noise) backend
     pub fn generate(&self, width: u32, height: u32, mapgen_seed: u32) -> GameMap {
-        let mut map = GameMap::new(width, height);
-        let fbm = Fbm::<noise::SuperSimplex>::new(0).set_seed(mapgen_seed);
-        for y in 0..height as i32 {
-            for x in 0..width as i32 {
-                let nx = x as f64 / width as f64 - 0.5;
-                let ny = y as f64 / height as f64 - 0.5;
-                let e = fbm.get([nx * 3.0, ny * 3.0]);
-                let kind = if e < -0.2 {
-                    TileKind::Water
-                } else if e > 0.6 {
+        self.generate_with(&MapGenConfig::new(width, height), mapgen_seed)
+    }
+
+    /// Generate using the backend selected by `config.algorithm`
+    pub fn generate_with(&self, config: &MapGenConfig, mapgen_seed: u32) -> GameMap {
+        match config.algorithm {
+            MapGenAlgorithm::Noise => generate_noise(config.width, config.height, mapgen_seed),
+            MapGenAlgorithm::Caves => {
+                generate_caves(config.width, config.height, mapgen_seed, config.caves)
+            }
+        }
+    }
+
+    /// Generate the material layer for a map made by [`Self::generate_with`]
+    pub fn generate_materials(&self, config: &MapGenConfig, mapgen_seed: u32) -> MaterialMap {
+        let mut materials = MaterialMap::filled(config.width, config.height, Material::Soil);
+        // Separate noise field so layers do not just mirror the terrain
+        let fbm = Fbm::<noise::SuperSimplex>::new(0).set_seed(mapgen_seed ^ MATERIAL_SALT);
+        for y in 0..config.height as i32 {
+            for x in 0..config.width as i32 {
+                let nx = x as f64 / config.width as f64;
+                let ny = y as f64 / config.height as f64;
+                let depth = fbm.get([nx * 2.0, ny * 2.0]);
+                materials
+                    .set(x, y, layer_at(depth, config.igneous_percent))
+                    .expect("within the map");
+            }
+        }
+        materials
+    }
+
+    /// Seal up to `config.caverns` hidden caverns into solid rock of `map`
+    ///
+    /// Each cavern is a rough disc of radius 2 or 3 placed where it and a
+    /// one-tile ring around it are all wall, away from the map edge and
+    /// from other caverns. Its center may hold a water pool and one tile
+    /// holds an animal. `map` itself is not changed: the caverns stay rock
+    /// until revealed. Fewer caverns come back when the rock runs out.
+    pub fn generate_caverns(
+        &self,
+        config: &MapGenConfig,
+        map: &GameMap,
+        mapgen_seed: u32,
+    ) -> Caverns {
+        let mut rng = StdRng::seed_from_u64((mapgen_seed ^ CAVERN_SALT) as u64);
+        let (w, h) = (map.width as i32, map.height as i32);
+        let mut taken: Vec<(i32, i32)> = Vec::new();
+        let mut caverns = Vec::new();
+        for _ in 0..config.caverns {
+            for _attempt in 0..CAVERN_ATTEMPTS {
+                let r: i32 = rng.gen_range(2..=3);
+                // Keep the ring clear of the border wall
+                if w < 2 * r + 5 || h < 2 * r + 5 {
+                    break;
+                }
+                let cx = rng.gen_range(r + 2..w - r - 2);
+                let cy = rng.gen_range(r + 2..h - r - 2);
+                let disc = |dx: i32, dy: i32, r: i32| dx * dx + dy * dy <= r * r + 1;
+                let mut tiles: Vec<(i32, i32)> = Vec::new();
+                let mut solid = true;
+                for y in cy - r - 1..=cy + r + 1 {
+                    for x in cx - r - 1..=cx + r + 1 {
+                        if !disc(x - cx, y - cy, r + 1) {
+                            continue;
+                        }
+                        let near_other = taken
+                            .iter()
+                            .any(|&(tx, ty)| (tx - x).abs() <= 2 && (ty - y).abs() <= 2);
+                        if map.get_tile(x, y) != Some(TileKind::Wall) || near_other {
+                            solid = false;
+                        }
+                        if disc(x - cx, y - cy, r) {
+                            tiles.push((x, y));
+                        }
+                    }
+                }
+                if !solid {
+                    continue;
+                }
+                tiles.sort_unstable();
+                let pool = rng.gen_range(0..100) < 50;
+                let kinds: Vec<TileKind> = tiles
+                    .iter()
+                    .map(|&(x, y)| {
+                        if pool && (x - cx).abs() + (y - cy).abs() <= 1 {
+                            TileKind::Water
+                        } else {
+                            TileKind::Floor
+                        }
+                    })
+                    .collect();
+                let dry: Vec<(i32, i32)> = tiles
+                    .iter()
+                    .zip(&kinds)
+                    .filter(|(_, &k)| k == TileKind::Floor)
+                    .map(|(&p, _)| p)
+                    .collect();
+                let den = dry[rng.gen_range(0..dry.len())];
+                let species = Species::ALL[rng.gen_range(0..Species::ALL.len())];
+                taken.extend(tiles.iter().copied());
+                caverns.push(Cavern {
+                    tiles,
+                    kinds,
+                    fauna: vec![(den, species)],
+                    revealed: None,
+                });
+                break;
+            }
+        }
+        Caverns::new(caverns)
+    }
+}
+
+const MATERIAL_SALT: u32 = 0x5eed_1a7e;
+
+const CAVERN_SALT: u32 = 0xca7e_0001;
+
+/// Placement tries per cavern before giving up on it
+const CAVERN_ATTEMPTS: u32 = 40;
+
+/// Percent of tiles in the shallow soil layer
+const SOIL_PERCENT: u8 = 20;
+
+/// Layer for a depth sample (roughly -1.0 to 1.0)
+///
+/// Shallow samples are soil and the deepest `igneous_percent` are igneous;
+/// percentages map linearly onto the central [-0.6, 0.6] band, as for embark
+/// terrain. Anything between is sedimentary.
+pub fn layer_at(depth: f64, igneous_percent: u8) -> Material {
+    let soil_below = -0.6 + 1.2 * SOIL_PERCENT as f64 / 100.0;
+    let igneous_above = 0.6 - 1.2 * igneous_percent.min(100) as f64 / 100.0;
+    if igneous_percent > 0 && depth > igneous_above {
+        Material::Igneous
+    } else if depth < soil_below {
+        Material::Soil
+    } else {
+        Material::Sedimentary
+    }
+}
+
+fn generate_noise(width: u32, height: u32, mapgen_seed: u32) -> GameMap {
+    let fbm = Fbm::<noise::SuperSimplex>::new(0).set_seed(mapgen_seed);
+    GameMap::from_fn(width, height, |x, y| {
+        let nx = x as f64 / width as f64 - 0.5;
+        let ny = y as f64 / height as f64 - 0.5;
+        let e = fbm.get([nx * 3.0, ny * 3.0]);
+        if e < -0.2 {
+            TileKind::Water
+        } else if e > 0.6 {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    })
+}
+
+/// Cellular-automata caves
+///
+/// Interior tiles start as wall with `fill_percent` chance (the border is
+/// always wall), then each smoothing pass turns a tile to wall when more than
+/// four of its eight neighbours are walls and to floor when fewer than four
+/// are. Finally every floor region except the largest is filled in, so the
+/// whole cavern is reachable.
+fn generate_caves(width: u32, height: u32, mapgen_seed: u32, params: CaveParams) -> GameMap {
+    let mut rng = StdRng::seed_from_u64(mapgen_seed as u64);
+    let (w, h) = (width as i32, height as i32);
+    let border = |x: i32, y: i32| x == 0 || y == 0 || x == w - 1 || y == h - 1;
+    let mut map = GameMap::from_fn(width, height, |x, y| {
+        if border(x, y) || rng.gen_range(0..100) < params.fill_percent {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    });
+
+    for _ in 0..params.passes {
+        map.tiles = map
+            .iter_tiles()
+            .map(|((x, y), tile)| {
+                let walls = wall_neighbours(&map, x, y);
+                if border(x, y) || walls > 4 {
                     TileKind::Wall
-                } else {
+                } else if walls < 4 {
                     TileKind::Floor
-                };
-                if let Some(i) = map.idx(x, y) {
-                    map.tiles[i] = kind;
+                } else {
+                    tile
                 }
+            })
+            .collect();
+    }
+
+    keep_largest_cavern(&mut map);
+    map
+}
+
+/// Walls among the eight neighbours; off-map counts as wall
+fn wall_neighbours(map: &GameMap, x: i32, y: i32) -> usize {
+    let mut n = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if (dx, dy) != (0, 0)
+                && map.get_tile(x + dx, y + dy).unwrap_or(TileKind::Wall) == TileKind::Wall
+            {
+                n += 1;
             }
         }
-        map
+    }
+    n
+}
+
+/// Fill every 4-connected floor region but the largest with wall
+///
+/// Ties go to the region found first in row-major order. A map with no floor
+/// at all gets its center tile opened so there is somewhere to stand.
+fn keep_largest_cavern(map: &mut GameMap) {
+    let (w, h) = (map.width as i32, map.height as i32);
+    let mut region = vec![usize::MAX; map.tiles.len()];
+    let mut sizes: Vec<usize> = Vec::new();
+    for start in 0..map.tiles.len() {
+        if map.tiles[start] != TileKind::Floor || region[start] != usize::MAX {
+            continue;
+        }
+        let id = sizes.len();
+        let mut size = 0;
+        let mut queue = VecDeque::from([start]);
+        region[start] = id;
+        while let Some(i) = queue.pop_front() {
+            size += 1;
+            let (x, y) = map.pos(i);
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if let Some(j) = map.idx(nx, ny) {
+                    if map.tiles[j] == TileKind::Floor && region[j] == usize::MAX {
+                        region[j] = id;
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        sizes.push(size);
+    }
+
+    let Some(largest) = (0..sizes.len()).max_by_key(|&id| (sizes[id], std::cmp::Reverse(id)))
+    else {
+        map.set_tile(w / 2, h / 2, TileKind::Floor)
+            .expect("the centre is on the map");
+        return;
+    };
+    for (tile, r) in map.tiles.iter_mut().zip(&region) {
+        if *tile == TileKind::Floor && *r != largest {
+            *tile = TileKind::Wall;
+        }
     }
 }
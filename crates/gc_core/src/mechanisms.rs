@@ -0,0 +1,382 @@
+//! Mechanisms: levers and pressure plates linked to doors, bridges and traps
+//!
+//! A trigger ([`Lever`] or [`PressurePlate`]) carries [`MechanismLinks`] to
+//! the targets it operates. Activating a trigger operates every linked
+//! target in link order: a [`Door`] opens or closes, a [`Bridge`] is raised
+//! or lowered, a [`Trap`] strikes whatever stands on it.
+//!
+//! Levers are pulled by job: [`pull_lever`] marks one, [`lever_job_system`]
+//! posts a [`JobKind::PullLever`], an idle carrier walks over and pulls it.
+//! Pressure plates fire when a creature steps onto an empty plate; by
+//! default goblins walk over their own plates safely.
+//!
+//! [`mechanism_system`] evaluates the tick's activations in trigger
+//! position order (then entity), so the outcome of two triggers fired on
+//! the same tick does not depend on spawn order. A closed door is a
+//! [`TileKind::Wall`] tile, so it blocks paths and sight like rock; a door
+//! with anyone or anything in the doorway stays open. A lowered bridge is a
+//! [`TileKind::Floor`] tile; raised, the tile goes back to what the bridge
+//! spans (usually water). A bridge with anyone or anything on it stays
+//! down.
+
+use crate::components::{
+    AssignedJob, Carrier, Dead, Faction, FactionKind, Goblin, Health, Item, MovementOverride,
+};
+use crate::determinism::RngStream;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId, JobKind,
+};
+use crate::path::PathService;
+use crate::systems::{travel, DeterministicRng, MovementConfig};
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Name, Position, TileChange, TileKind};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MechanismError {
+    #[error("entity is not a lever or pressure plate")]
+    NotATrigger,
+    #[error("entity is not a lever")]
+    NotALever,
+    #[error("entity is not a door, bridge or trap")]
+    NotATarget,
+    #[error("trigger is already linked to that target")]
+    AlreadyLinked,
+}
+
+/// A lever; each pull operates its links
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lever {
+    /// Which way it was last thrown
+    pub on: bool,
+    /// A pull has been ordered and not yet done
+    pub pull_requested: bool,
+    /// Pull job posted for the order
+    pub pull_job: Option<JobId>,
+}
+
+/// A plate that operates its links when stepped on
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PressurePlate {
+    /// Whether goblins set it off too; by default only other creatures do
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub goblins_trigger: bool,
+    /// Something that triggers it is standing on it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pressed: bool,
+}
+
+/// Targets a trigger operates, in order
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MechanismLinks(pub Vec<Entity>);
+
+/// A door; closed, its tile is a wall
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Door {
+    pub open: bool,
+}
+
+impl Default for Door {
+    fn default() -> Self {
+        Self { open: true }
+    }
+}
+
+/// A bridge; lowered, its tile is floor
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bridge {
+    pub lowered: bool,
+    /// Tile the bridge spans, restored while it is raised
+    pub under: TileKind,
+}
+
+impl Bridge {
+    /// A lowered bridge over `under`
+    pub fn lowered(under: TileKind) -> Self {
+        Self {
+            lowered: true,
+            under,
+        }
+    }
+}
+
+/// A spike trap that strikes everything on its tile when operated
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Trap;
+
+/// Trap strength
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MechanismConfig {
+    /// Damage a trap deals to each creature on it
+    pub trap_damage: i32,
+}
+
+impl Default for MechanismConfig {
+    fn default() -> Self {
+        Self { trap_damage: 6 }
+    }
+}
+
+/// Triggers activated this tick, waiting for [`mechanism_system`]
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MechanismQueue {
+    pub pending: Vec<Entity>,
+}
+
+/// Link `trigger` to operate `target`
+pub fn link_mechanism(
+    world: &mut World,
+    trigger: Entity,
+    target: Entity,
+) -> Result<(), MechanismError> {
+    let is_target = world
+        .get_entity(target)
+        .is_some_and(|e| e.contains::<Door>() || e.contains::<Bridge>() || e.contains::<Trap>());
+    if !is_target {
+        return Err(MechanismError::NotATarget);
+    }
+    let Some(mut trigger) = world.get_entity_mut(trigger) else {
+        return Err(MechanismError::NotATrigger);
+    };
+    if !trigger.contains::<Lever>() && !trigger.contains::<PressurePlate>() {
+        return Err(MechanismError::NotATrigger);
+    }
+    match trigger.get_mut::<MechanismLinks>() {
+        Some(links) if links.0.contains(&target) => Err(MechanismError::AlreadyLinked),
+        Some(mut links) => {
+            links.0.push(target);
+            Ok(())
+        }
+        None => {
+            trigger.insert(MechanismLinks(vec![target]));
+            Ok(())
+        }
+    }
+}
+
+/// Order a goblin to pull `lever`
+pub fn pull_lever(world: &mut World, lever: Entity) -> Result<(), MechanismError> {
+    let mut lever = world
+        .get_mut::<Lever>(lever)
+        .ok_or(MechanismError::NotALever)?;
+    lever.pull_requested = true;
+    Ok(())
+}
+
+/// Activate `trigger` on the next [`mechanism_system`] run, as if pulled or
+/// stepped on
+pub fn activate(world: &mut World, trigger: Entity) -> Result<(), MechanismError> {
+    let is_trigger = world
+        .get_entity(trigger)
+        .is_some_and(|e| e.contains::<Lever>() || e.contains::<PressurePlate>());
+    if !is_trigger {
+        return Err(MechanismError::NotATrigger);
+    }
+    world
+        .get_resource_or_insert_with(MechanismQueue::default)
+        .pending
+        .push(trigger);
+    Ok(())
+}
+
+/// Post a pull job for every lever ordered pulled with no live job
+pub fn lever_job_system(
+    mut board: ResMut<JobBoard>,
+    active: Res<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    mut levers: Query<(Entity, &Position, &mut Lever)>,
+) {
+    let live: HashSet<JobId> = board
+        .0
+        .iter()
+        .map(|j| j.id)
+        .chain(active.jobs.keys().copied())
+        .collect();
+    let mut order: Vec<Entity> = levers
+        .iter()
+        .filter(|(_, _, l)| l.pull_requested)
+        .map(|(e, ..)| e)
+        .collect();
+    order.sort();
+    for entity in order {
+        let (_, pos, mut lever) = levers.get_mut(entity).expect("listed above");
+        if lever.pull_job.is_some_and(|id| live.contains(&id)) {
+            continue;
+        }
+        let id = add_job_for(
+            &mut board,
+            JobKind::PullLever { x: pos.0, y: pos.1 },
+            IssuedBy::System,
+            FactionKind::Goblins,
+            rng.stream(RngStream::Job, "lever_job_system"),
+        );
+        lever.pull_job = Some(id);
+    }
+}
+
+/// Hand one queued pull job to an idle carrier of its faction per run, within
+/// its [`WorkArea`]
+pub fn lever_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut carriers: Query<BoardWorker, (With<Carrier>, Without<Dead>)>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut carriers,
+        |kind| matches!(kind, JobKind::PullLever { .. }),
+    );
+}
+
+/// Walk lever pullers next to their lever and pull it
+///
+/// A pull flips the lever and queues it for [`mechanism_system`]. A job
+/// whose lever is gone is dropped.
+#[allow(clippy::type_complexity)]
+pub fn lever_execution_system(
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    mut active: ResMut<ActiveJobs>,
+    mut queue: ResMut<MechanismQueue>,
+    mut pullers: Query<
+        (&mut AssignedJob, &mut Position, Option<&MovementOverride>),
+        (With<Carrier>, Without<Dead>, Without<Stalled>),
+    >,
+    mut levers: Query<(Entity, &Position, &mut Lever), Without<Carrier>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    for (mut assigned, mut pos, movement) in pullers.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(JobKind::PullLever { x, y }) = active.jobs.get(&id).map(|j| j.kind.clone()) else {
+            continue;
+        };
+        let Some((entity, _, mut lever)) = levers
+            .iter_mut()
+            .find(|(_, p, l)| (p.0, p.1) == (x, y) && l.pull_job == Some(id))
+        else {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        };
+        let (dx, dy) = (pos.0 - x, pos.1 - y);
+        if dx.abs() > 1 || dy.abs() > 1 {
+            let beside = (x + dx.signum(), y + dy.signum());
+            travel(
+                &mut pos,
+                beside,
+                config.mode_for(movement),
+                map.as_deref(),
+                paths.as_deref_mut(),
+            );
+            continue;
+        }
+        active.jobs.remove(&id);
+        assigned.0 = None;
+        lever.on = !lever.on;
+        lever.pull_requested = false;
+        lever.pull_job = None;
+        queue.pending.push(entity);
+    }
+}
+
+/// Fire pressure plates and operate the targets of every activated trigger
+///
+/// A plate fires when a creature that triggers it arrives on it while it
+/// is empty; it fires again only after being cleared. Activations run in
+/// trigger position order, then entity order, and each operates its links
+/// in order. Doors and bridges toggle (unless something is in the way)
+/// and traps strike.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn mechanism_system(
+    config: Option<Res<MechanismConfig>>,
+    queue: Option<ResMut<MechanismQueue>>,
+    mut map: ResMut<GameMap>,
+    mut plates: Query<(Entity, &Position, &mut PressurePlate)>,
+    triggers: Query<(&Position, &MechanismLinks)>,
+    mut creatures: Query<(&Position, Has<Goblin>, Option<&Faction>, &mut Health), Without<Dead>>,
+    blockers: Query<&Position, (Or<(With<Health>, With<Item>)>, Without<Door>)>,
+    mut doors: Query<(&Position, &mut Door)>,
+    mut bridges: Query<(&Position, &mut Bridge)>,
+    traps: Query<&Position, With<Trap>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let mut activations: Vec<((i32, i32), Entity)> = Vec::new();
+    for (entity, pos, mut plate) in plates.iter_mut() {
+        let pressed = creatures.iter().any(|(p, goblin, faction, _)| {
+            let is_goblin = goblin || faction.is_some_and(|f| f.kind == FactionKind::Goblins);
+            p == pos && (plate.goblins_trigger || !is_goblin)
+        });
+        if pressed && !plate.pressed {
+            activations.push(((pos.0, pos.1), entity));
+        }
+        plate.pressed = pressed;
+    }
+    if let Some(mut queue) = queue {
+        for entity in queue.pending.drain(..) {
+            if let Ok((pos, _)) = triggers.get(entity) {
+                activations.push(((pos.0, pos.1), entity));
+            }
+        }
+    }
+    activations.sort();
+
+    let occupied: HashSet<(i32, i32)> = blockers.iter().map(|p| (p.0, p.1)).collect();
+    // Final state of each door and bridge tile; toggled twice, nothing
+    // changes
+    let mut doorways: BTreeMap<(i32, i32), TileKind> = BTreeMap::new();
+    for (_, trigger) in activations {
+        let Ok((_, links)) = triggers.get(trigger) else {
+            continue;
+        };
+        for &target in &links.0 {
+            if let Ok((pos, mut door)) = doors.get_mut(target) {
+                let at = (pos.0, pos.1);
+                if door.open && occupied.contains(&at) {
+                    continue;
+                }
+                door.open = !door.open;
+                let kind = if door.open {
+                    TileKind::Floor
+                } else {
+                    TileKind::Wall
+                };
+                doorways.insert(at, kind);
+            } else if let Ok((pos, mut bridge)) = bridges.get_mut(target) {
+                let at = (pos.0, pos.1);
+                if bridge.lowered && occupied.contains(&at) {
+                    continue;
+                }
+                bridge.lowered = !bridge.lowered;
+                let kind = if bridge.lowered {
+                    TileKind::Floor
+                } else {
+                    bridge.under
+                };
+                doorways.insert(at, kind);
+            } else if let Ok(pos) = traps.get(target) {
+                for (_, _, _, mut health) in creatures.iter_mut().filter(|(p, ..)| *p == pos) {
+                    health.take_damage(config.trap_damage);
+                }
+            }
+        }
+    }
+    let changes: Vec<TileChange> = doorways
+        .into_iter()
+        .filter(|&((x, y), kind)| map.get_tile(x, y) != Some(kind))
+        .map(|(pos, kind)| TileChange { pos, kind })
+        .collect();
+    if !changes.is_empty() {
+        map.apply(&changes);
+    }
+}
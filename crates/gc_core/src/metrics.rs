@@ -0,0 +1,69 @@
+//! Run metrics: running totals of what a colony got done
+//!
+//! [`RunMetrics`] is bumped by the systems doing the work: tiles mined and
+//! what they yielded, items dropped off by haul jobs, deaths on each side
+//! and raids sent by the [`director`](crate::director). The counters only grow, so the difference between two samples is
+//! the work done in between. Batch experiments (see
+//! [`experiment`](crate::experiment)) read them at the end of each run to
+//! compare seeds and tuning; worlds without the resource count nothing.
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Counters of mining, hauling, deaths and raids since the world was built
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetrics {
+    /// Walls turned to floor by mine jobs
+    pub tiles_mined: u64,
+    /// Stone dropped by mined tiles
+    pub stones_mined: u64,
+    /// Gems dropped by mined tiles
+    pub gems_mined: u64,
+    /// Items dropped off by haul jobs, of any type
+    pub items_hauled: u64,
+    /// Stone dropped off by haul jobs
+    pub stones_hauled: u64,
+    /// Goblins killed (workers without a faction count as goblins)
+    pub goblin_deaths: u64,
+    /// Invaders killed
+    pub invader_deaths: u64,
+    /// Raids sent by the event director
+    pub raids: u64,
+}
+
+impl RunMetrics {
+    /// Metric names, in [`named`](Self::named) order
+    pub const NAMES: [&'static str; 8] = [
+        "tiles_mined",
+        "stones_mined",
+        "gems_mined",
+        "items_hauled",
+        "stones_hauled",
+        "goblin_deaths",
+        "invader_deaths",
+        "raids",
+    ];
+
+    /// Every counter with its name
+    pub fn named(&self) -> [(&'static str, u64); 8] {
+        let values = [
+            self.tiles_mined,
+            self.stones_mined,
+            self.gems_mined,
+            self.items_hauled,
+            self.stones_hauled,
+            self.goblin_deaths,
+            self.invader_deaths,
+            self.raids,
+        ];
+        std::array::from_fn(|i| (Self::NAMES[i], values[i]))
+    }
+
+    /// The counter called `name`
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.named()
+            .into_iter()
+            .find(|&(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+}
@@ -0,0 +1,167 @@
+//! Deterministic name generation from seeded syllable lists
+//!
+//! Names are assembled from small syllable tables using the dedicated
+//! `names_rng` stream of [`DeterministicRng`], so the same seed always names
+//! the same actors the same way without disturbing other RNG streams.
+//! Spawners can call [`next_name`] directly, or simply spawn an entity
+//! without a [`Name`] and let [`name_unnamed_system`] fill it in.
+
+use crate::components::{Carrier, Faction, FactionKind, Goblin, Miner};
+use crate::determinism::RngStream;
+use crate::systems::DeterministicRng;
+use crate::world::Name;
+use bevy_ecs::prelude::*;
+use rand::Rng;
+
+/// Which naming tradition to draw from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    /// Camp goblins: given name plus clan surname
+    Goblin,
+    /// Hostile outsiders: harsher given name plus epithet
+    Invader,
+    /// Creatures: single short name
+    Animal,
+}
+
+const GOBLIN_START: &[&str] = &[
+    "Gr", "Sn", "Kr", "Ur", "Zog", "Bl", "Sk", "Gn", "Rat", "Mog",
+];
+const GOBLIN_VOWEL: &[&str] = &["a", "o", "u", "i", "aa", "ou"];
+const GOBLIN_END: &[&str] = &["k", "g", "zz", "b", "rk", "t", "nk", "sh"];
+const CLAN_FIRST: &[&str] = &[
+    "Mud", "Rot", "Stone", "Grub", "Moss", "Ash", "Bone", "Tooth", "Soot", "Bog",
+];
+const CLAN_SECOND: &[&str] = &[
+    "foot", "belly", "nose", "fist", "ear", "tooth", "back", "gut", "eye",
+];
+const INVADER_START: &[&str] = &["Har", "Vor", "Ald", "Ber", "Thr", "Eld", "Kor"];
+const INVADER_END: &[&str] = &["ic", "ulf", "ard", "en", "rim", "oth", "an"];
+const INVADER_EPITHET: &[&str] = &[
+    "the Grim",
+    "the Red",
+    "Ironhand",
+    "the Tall",
+    "Oathbreaker",
+    "the Cruel",
+];
+const ANIMAL_NAMES: &[&str] = &[
+    "Pip", "Nub", "Scab", "Wart", "Fluff", "Chomp", "Snout", "Dot",
+];
+
+/// A generated name split into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedName {
+    pub given: String,
+    /// Clan name or epithet, if the tradition has one
+    pub surname: Option<String>,
+    /// Job title shown after the name, e.g. "miner"
+    pub profession: Option<String>,
+}
+
+impl GeneratedName {
+    /// Display form: `Grak Mudfoot the miner`
+    pub fn full(&self) -> String {
+        let mut out = self.given.clone();
+        if let Some(surname) = &self.surname {
+            out.push(' ');
+            out.push_str(surname);
+        }
+        if let Some(profession) = &self.profession {
+            out.push_str(" the ");
+            out.push_str(profession);
+        }
+        out
+    }
+}
+
+fn pick<'a, R: Rng + ?Sized>(rng: &mut R, table: &[&'a str]) -> &'a str {
+    table[rng.gen_range(0..table.len())]
+}
+
+/// Generate a name of the given kind from `rng`
+pub fn generate_name<R: Rng + ?Sized>(rng: &mut R, kind: NameKind) -> GeneratedName {
+    match kind {
+        NameKind::Goblin => GeneratedName {
+            given: format!(
+                "{}{}{}",
+                pick(rng, GOBLIN_START),
+                pick(rng, GOBLIN_VOWEL),
+                pick(rng, GOBLIN_END)
+            ),
+            surname: Some(format!(
+                "{}{}",
+                pick(rng, CLAN_FIRST),
+                pick(rng, CLAN_SECOND)
+            )),
+            profession: None,
+        },
+        NameKind::Invader => GeneratedName {
+            given: format!("{}{}", pick(rng, INVADER_START), pick(rng, INVADER_END)),
+            surname: Some(pick(rng, INVADER_EPITHET).to_string()),
+            profession: None,
+        },
+        NameKind::Animal => GeneratedName {
+            given: pick(rng, ANIMAL_NAMES).to_string(),
+            surname: None,
+            profession: None,
+        },
+    }
+}
+
+/// Draw the next name from the world's `names_rng` stream
+pub fn next_name(world: &mut World, kind: NameKind) -> GeneratedName {
+    let mut rng = world.resource_mut::<DeterministicRng>();
+    generate_name(rng.stream(RngStream::Names, "next_name"), kind)
+}
+
+/// Profession title implied by an entity's role markers
+fn profession(miner: bool, carrier: bool) -> Option<String> {
+    match (miner, carrier) {
+        (true, _) => Some("miner".into()),
+        (false, true) => Some("hauler".into()),
+        _ => None,
+    }
+}
+
+/// Give generated names to goblins and invaders spawned without a [`Name`]
+///
+/// Entities are named in id order so results do not depend on query
+/// iteration order.
+#[allow(clippy::type_complexity)]
+pub fn name_unnamed_system(
+    mut commands: Commands,
+    rng: Option<ResMut<DeterministicRng>>,
+    q: Query<
+        (
+            Entity,
+            Has<Goblin>,
+            Option<&Faction>,
+            Has<Miner>,
+            Has<Carrier>,
+        ),
+        Without<Name>,
+    >,
+) {
+    let Some(mut rng) = rng else {
+        return;
+    };
+    let mut unnamed: Vec<_> = q
+        .iter()
+        .filter_map(|(e, goblin, faction, miner, carrier)| {
+            let kind = match faction.map(|f| f.kind) {
+                Some(FactionKind::Invaders) => NameKind::Invader,
+                Some(FactionKind::Goblins) => NameKind::Goblin,
+                _ if goblin => NameKind::Goblin,
+                _ => return None,
+            };
+            Some((e, kind, miner, carrier))
+        })
+        .collect();
+    unnamed.sort_by_key(|(e, ..)| *e);
+    for (entity, kind, miner, carrier) in unnamed {
+        let mut name = generate_name(rng.stream(RngStream::Names, "name_unnamed_system"), kind);
+        name.profession = profession(miner, carrier);
+        commands.entity(entity).insert(Name(name.full()));
+    }
+}
@@ -0,0 +1,478 @@
+//! Region-scale world generation and embarking
+//!
+//! The [`Overmap`] is a coarse grid of regions, each with an elevation,
+//! moisture, [`Biome`], and optional river. An embark picks a rectangle of
+//! regions ([`EmbarkSite`]) and expands it into a local [`GameMap`] with
+//! [`REGION_TILES`] tiles per region side.
+//!
+//! Everything is derived from the seed and global tile coordinates, so the
+//! same site always yields the same map and neighbouring embarks line up at
+//! their shared edges. Each biome's [`BiomeParams`] drive local terrain and
+//! the flora, fauna, temperature and raid difficulty set up by
+//! [`populate_embark`].
+
+use crate::components::{Animal, Health, Tree};
+use crate::director::RegionDifficulty;
+use crate::livestock::Species;
+use crate::mapgen::layer_at;
+use crate::names::{next_name, NameKind};
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind, Velocity};
+use bevy_ecs::prelude::*;
+use noise::{Fbm, NoiseFn, Seedable};
+use serde::{Deserialize, Serialize};
+
+/// Local map tiles per overmap region side
+pub const REGION_TILES: u32 = 16;
+
+/// Rivers traced per overmap, starting from the highest regions
+pub const RIVER_SOURCES: usize = 4;
+
+/// Region climate class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    Ocean,
+    Plains,
+    Forest,
+    Swamp,
+    Desert,
+    Mountains,
+}
+
+impl Biome {
+    /// Overmap legend glyph
+    pub fn glyph(self) -> char {
+        match self {
+            Biome::Ocean => '~',
+            Biome::Plains => '.',
+            Biome::Forest => 'T',
+            Biome::Swamp => '%',
+            Biome::Desert => ':',
+            Biome::Mountains => '^',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Biome::Ocean => "ocean",
+            Biome::Plains => "plains",
+            Biome::Forest => "forest",
+            Biome::Swamp => "swamp",
+            Biome::Desert => "desert",
+            Biome::Mountains => "mountains",
+        }
+    }
+
+    /// Local terrain and ecology parameters for this biome
+    pub fn params(self) -> BiomeParams {
+        let (water, wall, trees, igneous, temperature, fauna, raids) = match self {
+            Biome::Ocean => (100, 0, 0, 30, 12, 0, 100),
+            Biome::Plains => (5, 5, 3, 20, 15, 4, 90),
+            Biome::Forest => (5, 5, 35, 25, 12, 6, 100),
+            Biome::Swamp => (45, 0, 15, 5, 22, 5, 110),
+            Biome::Desert => (0, 10, 1, 40, 35, 1, 120),
+            Biome::Mountains => (0, 65, 2, 80, -2, 2, 140),
+        };
+        BiomeParams {
+            water_coverage: water,
+            wall_coverage: wall,
+            tree_density: trees,
+            igneous_percent: igneous,
+            ambient_temperature: temperature,
+            fauna_per_region: fauna,
+            raid_difficulty: raids,
+        }
+    }
+
+    fn classify(elevation: f64, moisture: f64) -> Self {
+        if elevation < -0.25 {
+            Biome::Ocean
+        } else if elevation > 0.45 {
+            Biome::Mountains
+        } else if moisture > 0.3 && elevation < 0.0 {
+            Biome::Swamp
+        } else if moisture > 0.1 {
+            Biome::Forest
+        } else if moisture < -0.3 {
+            Biome::Desert
+        } else {
+            Biome::Plains
+        }
+    }
+}
+
+/// Terrain and ecology knobs for local maps in a biome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BiomeParams {
+    /// Percent of tiles that are open water
+    pub water_coverage: u8,
+    /// Percent of tiles that are rock outcrops
+    pub wall_coverage: u8,
+    /// Percent of floor tiles with a tree
+    pub tree_density: u8,
+    /// Percent of bedrock that is igneous rather than sedimentary
+    pub igneous_percent: u8,
+    /// Ambient temperature in degrees Celsius
+    pub ambient_temperature: i32,
+    /// Wild animals spawned per region at embark
+    pub fauna_per_region: u8,
+    /// Percent scaling of raid threat (see [`RegionDifficulty`])
+    pub raid_difficulty: u32,
+}
+
+/// Average ambient temperature (°C) of the embarked site
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AmbientTemperature(pub i32);
+
+/// One overmap region
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    /// Roughly -1.0 (deep sea) to 1.0 (peaks)
+    pub elevation: f64,
+    /// Roughly -1.0 (arid) to 1.0 (wet)
+    pub moisture: f64,
+    pub biome: Biome,
+    pub river: bool,
+}
+
+/// Coarse world map of regions, row-major
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Overmap {
+    pub width: u32,
+    pub height: u32,
+    pub seed: u32,
+    pub regions: Vec<Region>,
+}
+
+/// Rectangle of overmap regions to embark on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbarkSite {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Reasons an embark site cannot be used
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum EmbarkError {
+    #[error("embark site must be at least 1x1 regions")]
+    Empty,
+    #[error("embark site {0:?} extends past the overmap")]
+    OutOfBounds(EmbarkSite),
+}
+
+struct Fields {
+    elevation: Fbm<noise::SuperSimplex>,
+    moisture: Fbm<noise::SuperSimplex>,
+    detail: Fbm<noise::SuperSimplex>,
+    strata: Fbm<noise::SuperSimplex>,
+}
+
+impl Fields {
+    fn new(seed: u32) -> Self {
+        Self {
+            elevation: Fbm::<noise::SuperSimplex>::new(0).set_seed(seed),
+            moisture: Fbm::<noise::SuperSimplex>::new(0).set_seed(seed.wrapping_add(1)),
+            detail: Fbm::<noise::SuperSimplex>::new(0).set_seed(seed.wrapping_add(2)),
+            strata: Fbm::<noise::SuperSimplex>::new(0).set_seed(seed.wrapping_add(3)),
+        }
+    }
+
+    /// Elevation at region-space coordinates (fractional for local tiles)
+    fn elevation(&self, rx: f64, ry: f64) -> f64 {
+        self.elevation.get([rx / 12.0, ry / 12.0])
+    }
+
+    fn moisture(&self, rx: f64, ry: f64) -> f64 {
+        self.moisture.get([rx / 10.0, ry / 10.0])
+    }
+}
+
+impl Overmap {
+    /// Generate a `width` x `height` region overmap from `seed`
+    pub fn generate(width: u32, height: u32, seed: u32) -> Self {
+        let fields = Fields::new(seed);
+        let mut regions = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (rx, ry) = (x as f64 + 0.5, y as f64 + 0.5);
+                let elevation = fields.elevation(rx, ry);
+                let moisture = fields.moisture(rx, ry);
+                regions.push(Region {
+                    elevation,
+                    moisture,
+                    biome: Biome::classify(elevation, moisture),
+                    river: false,
+                });
+            }
+        }
+        let mut overmap = Self {
+            width,
+            height,
+            seed,
+            regions,
+        };
+        overmap.trace_rivers();
+        overmap
+    }
+
+    pub fn region(&self, x: u32, y: u32) -> Option<&Region> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.regions.get((y * self.width + x) as usize)
+    }
+
+    /// Rivers run downhill from the highest land regions until they reach
+    /// the ocean or a basin with no lower neighbour
+    fn trace_rivers(&mut self) {
+        let mut sources: Vec<usize> = (0..self.regions.len())
+            .filter(|&i| self.regions[i].biome == Biome::Mountains)
+            .collect();
+        // Highest first; index breaks ties so the order is total
+        sources.sort_by(|&a, &b| {
+            self.regions[b]
+                .elevation
+                .total_cmp(&self.regions[a].elevation)
+                .then(a.cmp(&b))
+        });
+        for &start in sources.iter().take(RIVER_SOURCES) {
+            let mut at = start;
+            loop {
+                let region = &mut self.regions[at];
+                if region.biome == Biome::Ocean || region.river && at != start {
+                    break;
+                }
+                region.river = true;
+                let (x, y) = (
+                    (at as u32 % self.width) as i32,
+                    (at as u32 / self.width) as i32,
+                );
+                let lowest = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                    .into_iter()
+                    .filter(|&(nx, ny)| {
+                        nx >= 0 && ny >= 0 && (nx as u32) < self.width && (ny as u32) < self.height
+                    })
+                    .map(|(nx, ny)| (ny as u32 * self.width + nx as u32) as usize)
+                    .min_by(|&a, &b| {
+                        self.regions[a]
+                            .elevation
+                            .total_cmp(&self.regions[b].elevation)
+                    });
+                match lowest {
+                    Some(next) if self.regions[next].elevation < self.regions[at].elevation => {
+                        at = next;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    /// One glyph per region: the river `=` or the biome glyph
+    pub fn render_ascii(&self) -> String {
+        let mut out = String::with_capacity(((self.width + 1) * self.height) as usize);
+        for (i, region) in self.regions.iter().enumerate() {
+            out.push(if region.river && region.biome != Biome::Ocean {
+                '='
+            } else {
+                region.biome.glyph()
+            });
+            if (i as u32 + 1) % self.width == 0 {
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Reject empty sites and sites not wholly on the overmap, including
+    /// ones whose far edge overflows
+    fn check_site(&self, site: EmbarkSite) -> Result<(), EmbarkError> {
+        if site.width == 0 || site.height == 0 {
+            return Err(EmbarkError::Empty);
+        }
+        let fits = |start: u32, len: u32, limit: u32| {
+            start.checked_add(len).is_some_and(|end| end <= limit)
+        };
+        if !fits(site.x, site.width, self.width) || !fits(site.y, site.height, self.height) {
+            return Err(EmbarkError::OutOfBounds(site));
+        }
+        Ok(())
+    }
+
+    /// Expand a rectangle of regions into a local map
+    ///
+    /// Each region becomes [`REGION_TILES`] squared tiles. Terrain follows
+    /// the region's biome with tile-level noise sampled in global
+    /// coordinates; river regions get a water channel.
+    pub fn embark(&self, site: EmbarkSite) -> Result<GameMap, EmbarkError> {
+        self.check_site(site)?;
+        let fields = Fields::new(self.seed);
+        let map = GameMap::from_fn(
+            site.width * REGION_TILES,
+            site.height * REGION_TILES,
+            |lx, ly| {
+                let gx = site.x * REGION_TILES + lx as u32;
+                let gy = site.y * REGION_TILES + ly as u32;
+                let (rx, ry) = (gx / REGION_TILES, gy / REGION_TILES);
+                let Some(region) = self.region(rx, ry) else {
+                    return TileKind::Floor;
+                };
+                let fx = gx as f64 / REGION_TILES as f64;
+                let fy = gy as f64 / REGION_TILES as f64;
+                let detail = fields.detail.get([fx * 4.0, fy * 4.0]);
+                let mut kind = local_tile(region.biome.params(), detail);
+                if region.river && region.biome != Biome::Ocean {
+                    // Channel meanders around the region's center line
+                    let center = REGION_TILES as f64 / 2.0 + detail * 3.0;
+                    let across = if self.river_runs_vertically(rx, ry) {
+                        (gx % REGION_TILES) as f64
+                    } else {
+                        (gy % REGION_TILES) as f64
+                    };
+                    if (across - center).abs() < 1.5 {
+                        kind = TileKind::Water;
+                    }
+                }
+                kind
+            },
+        );
+        Ok(map)
+    }
+
+    /// Material layer for an embark, matching the map from [`Self::embark`]
+    ///
+    /// Each region's [`BiomeParams::igneous_percent`] sets how much of its
+    /// bedrock is igneous.
+    pub fn embark_materials(&self, site: EmbarkSite) -> Result<MaterialMap, EmbarkError> {
+        self.check_site(site)?;
+        let fields = Fields::new(self.seed);
+        let (width, height) = (site.width * REGION_TILES, site.height * REGION_TILES);
+        let mut materials = MaterialMap::filled(width, height, Material::Soil);
+        for ly in 0..height {
+            for lx in 0..width {
+                let gx = site.x * REGION_TILES + lx;
+                let gy = site.y * REGION_TILES + ly;
+                let Some(region) = self.region(gx / REGION_TILES, gy / REGION_TILES) else {
+                    continue;
+                };
+                let fx = gx as f64 / REGION_TILES as f64;
+                let fy = gy as f64 / REGION_TILES as f64;
+                let depth = fields.strata.get([fx, fy]);
+                materials
+                    .set(
+                        lx as i32,
+                        ly as i32,
+                        layer_at(depth, region.biome.params().igneous_percent),
+                    )
+                    .expect("within the embark map");
+            }
+        }
+        Ok(materials)
+    }
+
+    fn river_runs_vertically(&self, x: u32, y: u32) -> bool {
+        let river = |x: Option<u32>, y: Option<u32>| match (x, y) {
+            (Some(x), Some(y)) => self.region(x, y).is_some_and(|r| r.river),
+            _ => false,
+        };
+        river(Some(x), y.checked_sub(1)) || river(Some(x), Some(y + 1))
+    }
+}
+
+/// Tile for a biome given detail noise (roughly -1.0 to 1.0)
+///
+/// Coverage percentages map linearly onto the central [-0.6, 0.6] band of
+/// the noise, which holds nearly all samples.
+fn local_tile(params: BiomeParams, detail: f64) -> TileKind {
+    if params.water_coverage >= 100 {
+        return TileKind::Water;
+    }
+    let water_below = -0.6 + 1.2 * params.water_coverage as f64 / 100.0;
+    let wall_above = 0.6 - 1.2 * params.wall_coverage as f64 / 100.0;
+    if detail > wall_above {
+        TileKind::Wall
+    } else if detail < water_below {
+        TileKind::Water
+    } else {
+        TileKind::Floor
+    }
+}
+
+/// Stable per-tile hash so flora/fauna placement needs no RNG draws and
+/// agrees between overlapping embarks
+fn tile_hash(seed: u32, x: u32, y: u32, salt: u32) -> u64 {
+    let mut h = ((seed as u64) << 32) ^ ((x as u64) << 16) ^ (y as u64) ^ ((salt as u64) << 48);
+    // splitmix64 finalizer
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Spawn trees and wild animals for an embarked site and set the
+/// [`AmbientTemperature`] and [`RegionDifficulty`], averaged over its
+/// regions
+///
+/// The world's `GameMap` must be the map returned by
+/// [`Overmap::embark`] for the same `site`. Animals are named from the
+/// `names_rng` stream in region order.
+pub fn populate_embark(world: &mut World, overmap: &Overmap, site: EmbarkSite) {
+    let map = world.resource::<GameMap>().clone();
+    let mut trees = Vec::new();
+    let mut animals = Vec::new();
+    let mut temperature = 0;
+    let mut difficulty = 0;
+    for ry in site.y..site.y.saturating_add(site.height) {
+        for rx in site.x..site.x.saturating_add(site.width) {
+            let Some(region) = overmap.region(rx, ry) else {
+                continue;
+            };
+            let params = region.biome.params();
+            temperature += params.ambient_temperature;
+            difficulty += params.raid_difficulty;
+            let (ox, oy) = ((rx - site.x) * REGION_TILES, (ry - site.y) * REGION_TILES);
+            for ty in 0..REGION_TILES {
+                for tx in 0..REGION_TILES {
+                    let (gx, gy) = (rx * REGION_TILES + tx, ry * REGION_TILES + ty);
+                    let (lx, ly) = ((ox + tx) as i32, (oy + ty) as i32);
+                    if map.get_tile(lx, ly) == Some(TileKind::Floor)
+                        && tile_hash(overmap.seed, gx, gy, 0) % 100 < params.tree_density as u64
+                    {
+                        trees.push((lx, ly));
+                    }
+                }
+            }
+            for k in 0..params.fauna_per_region as u32 {
+                let h = tile_hash(overmap.seed, rx, ry, k + 1);
+                let (tx, ty) = (
+                    (h % REGION_TILES as u64) as u32,
+                    ((h >> 16) % REGION_TILES as u64) as u32,
+                );
+                let (lx, ly) = ((ox + tx) as i32, (oy + ty) as i32);
+                if map.get_tile(lx, ly) == Some(TileKind::Floor) {
+                    let species = Species::ALL[((h >> 32) % Species::ALL.len() as u64) as usize];
+                    animals.push((lx, ly, species));
+                }
+            }
+        }
+    }
+
+    for (x, y) in trees {
+        world.spawn((Tree, Position(x, y), Name("Tree".into())));
+    }
+    for (x, y, species) in animals {
+        let name = next_name(world, NameKind::Animal).full();
+        world.spawn((
+            Animal,
+            species,
+            Position(x, y),
+            Velocity(0, 0),
+            Health::full(8),
+            Name(name),
+        ));
+    }
+    let regions = (site.width * site.height).max(1) as i32;
+    world.insert_resource(AmbientTemperature(temperature / regions));
+    world.insert_resource(RegionDifficulty(difficulty / regions as u32));
+}
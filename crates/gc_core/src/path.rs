@@ -1,6 +1,14 @@
-use crate::world::GameMap;
+use crate::components::{Faction, FactionKind};
+use crate::danger::DangerMap;
+use crate::equipment::Squad;
+use crate::jobs::faction_of;
+use crate::world::{GameMap, MapChanged, TileKind};
+use bevy_ecs::prelude::*;
 use lru::LruCache;
 use pathfinding::prelude::astar;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::num::NonZeroUsize;
 
 // A* Pathfinding System with LRU Caching
@@ -14,39 +22,143 @@ use std::num::NonZeroUsize;
 // - LRU cache to improve performance for repeated path requests
 // - Batch processing for multiple path calculations
 // - Statistics tracking for cache hit/miss analysis
+// - A shared `PathService` resource that walking agents step along
+// - Time-sliced searches answered over several ticks (`PathQueue`)
+// - Passability classes: walkers, swimmers and flyers each have their own
+//   tiles (`Passability`)
 
 // Type aliases and structures for pathfinding
 
 /// Result type for pathfinding operations
 ///
 /// Returns `Some((path_coords, total_cost))` on success, `None` if no path exists
-type PathResult = Option<(Vec<(i32, i32)>, i32)>;
+pub type PathResult = Option<(Vec<(i32, i32)>, i32)>;
 /// Cache key combining start and goal coordinates: (start_x, start_y, goal_x, goal_y)
 type CacheKey = (i32, i32, i32, i32);
 /// LRU cache storing pathfinding results
 type PathCache = LruCache<CacheKey, PathResult>;
+/// Next tile from a position toward a goal: ((x, y), goal) -> next
+type StepCache = LruCache<((i32, i32), (i32, i32)), (i32, i32)>;
+
+/// Cache capacity of [`PathService::default`]
+pub const DEFAULT_PATH_CACHE: usize = 512;
+/// Next steps kept per cached path
+const STEPS_PER_PATH: usize = 32;
+/// Min-heap entry for [`PathSearch`]: (f, h, insertion order, node)
+type OpenEntry = Reverse<(i32, i32, u64, SearchNode)>;
+
+/// How a creature gets around, and so which tiles it may enter
+///
+/// Entities without the component are walkers. Searches take the class
+/// through [`PathOptions::passability`], and [`movement`](crate::systems::movement)
+/// keeps movers with a class on their tiles.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Passability {
+    /// Floor only
+    #[default]
+    Walks,
+    /// Water only
+    Swims,
+    /// Anything but solid wall: floor, water and lava
+    Flies,
+}
+
+impl Passability {
+    /// Whether a mover of this class may enter a tile of `kind`
+    pub fn can_enter(self, kind: TileKind) -> bool {
+        match self {
+            Passability::Walks => kind == TileKind::Floor,
+            Passability::Swims => kind == TileKind::Water,
+            Passability::Flies => kind != TileKind::Wall,
+        }
+    }
+
+    /// Whether a mover of this class may enter (x, y); never off the map
+    pub fn passable(self, map: &GameMap, x: i32, y: i32) -> bool {
+        map.get_tile(x, y).is_some_and(|t| self.can_enter(t))
+    }
+}
 
 /// Generate neighbors for A* pathfinding with 4-directional movement
 /// Only returns walkable neighboring tiles based on the game map
 /// Each neighbor has a movement cost of 1 (uniform cost grid)
 fn neighbors(map: &GameMap, x: i32, y: i32) -> Vec<((i32, i32), i32)> {
+    neighbors_for(map, Passability::Walks, x, y)
+}
+
+/// [`neighbors`] for movers of `class`
+fn neighbors_for(map: &GameMap, class: Passability, x: i32, y: i32) -> Vec<((i32, i32), i32)> {
     let mut n = Vec::with_capacity(4);
     // 4-directional movement: right, left, down, up
     let dirs = [(1, 0), (-1, 0), (0, 1), (0, -1)];
     for (dx, dy) in dirs {
         let nx = x + dx;
         let ny = y + dy;
-        if map.is_walkable(nx, ny) {
+        if class.passable(map, nx, ny) {
             n.push(((nx, ny), 1));
         }
     }
     n
 }
 
+/// Every tile reachable from any of `starts` by 4-directional walking
+/// Starts are included even when they are not walkable themselves
+pub fn reachable_tiles(
+    map: &GameMap,
+    starts: impl IntoIterator<Item = (i32, i32)>,
+) -> HashSet<(i32, i32)> {
+    let mut seen: HashSet<(i32, i32)> = HashSet::new();
+    let mut frontier: VecDeque<(i32, i32)> = VecDeque::new();
+    for start in starts {
+        if seen.insert(start) {
+            frontier.push_back(start);
+        }
+    }
+    while let Some((x, y)) = frontier.pop_front() {
+        for (next, _) in neighbors(map, x, y) {
+            if seen.insert(next) {
+                frontier.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Whether some walkable 4-neighbor of (x, y) lies in `region`
+/// Without a region any walkable neighbor will do
+pub fn adjacent_reachable(
+    map: &GameMap,
+    region: Option<&HashSet<(i32, i32)>>,
+    x: i32,
+    y: i32,
+) -> bool {
+    neighbors(map, x, y)
+        .into_iter()
+        .any(|(n, _)| region.map_or(true, |r| r.contains(&n)))
+}
+
+/// Walkable tile next to (x, y) to work it from, nearest `from` with ties
+/// broken by (y, x); None when every neighbour is blocked
+pub fn approach_tile(map: &GameMap, from: (i32, i32), x: i32, y: i32) -> Option<(i32, i32)> {
+    (-1..=1)
+        .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(|&(nx, ny)| (nx, ny) != (x, y) && Passability::Walks.passable(map, nx, ny))
+        .min_by_key(|&(nx, ny)| {
+            let (dx, dy) = ((nx - from.0).abs(), (ny - from.1).abs());
+            (dx.max(dy), dx + dy, ny, nx)
+        })
+}
+
 /// Find shortest path using A* algorithm with Manhattan distance heuristic
 /// Returns None if no path exists, otherwise returns (path, total_cost)
 /// The path includes both start and goal positions
+/// A start off the map or a goal that cannot be stood on (other than the
+/// start itself) fails at once instead of searching the whole region; use
+/// [`PathOptions::adjacent`] to walk up to such a goal.
 pub fn astar_path(map: &GameMap, start: (i32, i32), goal: (i32, i32)) -> PathResult {
+    if !endpoints_valid(map, Passability::Walks, start, goal) {
+        return None;
+    }
     astar(
         &start,
         |&(x, y)| neighbors(map, x, y),
@@ -55,6 +167,11 @@ pub fn astar_path(map: &GameMap, start: (i32, i32), goal: (i32, i32)) -> PathRes
     )
 }
 
+/// Whether a search from `start` to stand on `goal` can succeed at all
+fn endpoints_valid(map: &GameMap, class: Passability, start: (i32, i32), goal: (i32, i32)) -> bool {
+    map.idx(start.0, start.1).is_some() && (start == goal || class.passable(map, goal.0, goal.1))
+}
+
 /// Request structure for batch pathfinding operations
 /// Encapsulates start and goal coordinates for a single pathfinding request
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -68,10 +185,23 @@ pub struct PathRequest {
 /// Pathfinding service with LRU caching for performance optimization
 /// Caches computed paths to avoid redundant calculations for frequently requested routes
 /// Maintains statistics for cache performance analysis
-#[derive(Debug)]
+/// Follows the map's change log, so edits between requests invalidate
+/// exactly the cached paths they could affect (see [`Self::invalidate`])
+///
+/// Worlds from `build_standard_world` share one as a resource, kept in
+/// step with the map by [`path_cache_system`]; agents walking with
+/// [`MovementMode::Walk`](crate::components::MovementMode::Walk) ask it for
+/// their [`next_step`](Self::next_step), so its stats cover them all.
+/// Its paths are for walkers; other [`Passability`] classes search with
+/// [`PathOptions::passability`].
+#[derive(Resource, Debug, Clone)]
 pub struct PathService {
     /// LRU cache storing path results
     cache: PathCache,
+    /// Next step from every tile of each path found, for walkers
+    steps: StepCache,
+    /// Map revision the cache reflects; None before the first request
+    revision: Option<u64>,
     /// Number of cache hits (requests served from cache)
     hits: usize,
     /// Number of cache misses (requests requiring computation)
@@ -83,8 +213,11 @@ impl PathService {
     /// Larger capacity means more paths cached but higher memory usage
     pub fn new(capacity: usize) -> Self {
         let cap = NonZeroUsize::new(capacity.max(1)).unwrap();
+        let steps = NonZeroUsize::new(cap.get() * STEPS_PER_PATH).unwrap();
         Self {
             cache: LruCache::new(cap),
+            steps: LruCache::new(steps),
+            revision: None,
             hits: 0,
             misses: 0,
         }
@@ -94,6 +227,7 @@ impl PathService {
     /// Automatically updates cache with new calculations
     /// Returns None if no path exists
     pub fn get(&mut self, map: &GameMap, start: (i32, i32), goal: (i32, i32)) -> PathResult {
+        self.sync(map);
         let key = (start.0, start.1, goal.0, goal.1);
         if let Some(v) = self.cache.get(&key) {
             self.hits += 1;
@@ -105,6 +239,83 @@ impl PathService {
         v
     }
 
+    /// First tile to step onto from `from` on a shortest path to `goal`
+    ///
+    /// Every path found also records the next step from each of its
+    /// tiles, so an agent walking it is answered from the cache all the
+    /// way. `None` at the goal or when no path exists.
+    pub fn next_step(
+        &mut self,
+        map: &GameMap,
+        from: (i32, i32),
+        goal: (i32, i32),
+    ) -> Option<(i32, i32)> {
+        self.sync(map);
+        if from == goal {
+            return None;
+        }
+        if let Some(&next) = self.steps.get(&(from, goal)) {
+            self.hits += 1;
+            return Some(next);
+        }
+        let (path, _) = self.get(map, from, goal)?;
+        for pair in path.windows(2) {
+            self.steps.put((pair[0], goal), pair[1]);
+        }
+        path.get(1).copied()
+    }
+
+    /// Forget every cached path
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.steps.clear();
+    }
+
+    /// Drop cache entries made stale by changes since the last request
+    ///
+    /// A log that no longer reaches back that far clears everything.
+    pub fn sync(&mut self, map: &GameMap) {
+        match self.revision {
+            Some(r) if r == map.revision() => {}
+            Some(r) => match map.changes_since(r) {
+                Some(batch) => self.invalidate(&batch),
+                None => self.clear(),
+            },
+            None => {}
+        }
+        self.revision = Some(map.revision());
+    }
+
+    /// Drop cached results `changes` could affect
+    ///
+    /// A tile turning walkable may open shorter routes (or any route), so
+    /// the whole cache goes. Tiles turning unwalkable only break the paths
+    /// through them; failed searches stay failed. Cached next steps go
+    /// either way.
+    pub fn invalidate(&mut self, changes: &MapChanged) {
+        if !changes.walkability_changed() {
+            return;
+        }
+        self.steps.clear();
+        if changes.changes.iter().any(|c| c.new == TileKind::Floor) {
+            self.cache.clear();
+            return;
+        }
+        let blocked: HashSet<(i32, i32)> = changes.changes.iter().map(|c| c.pos).collect();
+        let stale: Vec<CacheKey> = self
+            .cache
+            .iter()
+            .filter(|(_, v)| {
+                v.as_ref()
+                    .is_some_and(|(path, _)| path.iter().any(|p| blocked.contains(p)))
+            })
+            .map(|(k, _)| *k)
+            .collect();
+        for key in stale {
+            self.cache.pop(&key);
+        }
+    }
+
     /// Process multiple pathfinding requests in batch
     /// More efficient than individual calls for multiple paths
     /// Each request is still cached independently
@@ -129,3 +340,502 @@ impl PathService {
         self.misses = 0;
     }
 }
+
+impl Default for PathService {
+    fn default() -> Self {
+        Self::new(DEFAULT_PATH_CACHE)
+    }
+}
+
+/// Keep the shared [`PathService`] in step with the map
+///
+/// Edits are applied from the change log each tick, before anyone walks,
+/// rather than on the next request. A new or replaced map (a load, a
+/// reset), or one marked changed that the log does not account for since
+/// the last run (direct `tiles` writes, a restored checkpoint), clears the
+/// cache.
+pub fn path_cache_system(
+    map: Res<GameMap>,
+    paths: Option<ResMut<PathService>>,
+    mut last: Local<Option<u64>>,
+) {
+    let Some(mut paths) = paths else {
+        return;
+    };
+    let unlogged = map.is_changed()
+        && last.is_some_and(|r| map.changes_since(r).map_or(true, |batch| batch.is_empty()));
+    *last = Some(map.revision());
+    if map.is_added() || unlogged {
+        paths.clear();
+        paths.revision = Some(map.revision());
+    } else {
+        paths.sync(&map);
+    }
+}
+
+/// Post-processing and cost shaping for searches
+///
+/// The default leaves paths exactly as [`astar_path`] finds them, for a
+/// walker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Pull the path taut: runs between points with a clear line are
+    /// replaced by that line, which may step diagonally (never between two
+    /// blocked orthogonal neighbours)
+    pub smooth: bool,
+    /// Extra cost per change of direction, in tenths of a step, so searches
+    /// prefer straight corridors among near-equal routes
+    pub turn_penalty: u8,
+    /// Stop on any walkable 4-neighbour of the goal instead of the goal
+    /// itself, which may be a wall (a mining face, a creature's tile)
+    pub adjacent: bool,
+    /// When the goal cannot be reached, return the path to the reachable
+    /// tile closest to it (Manhattan distance, then fewest steps) instead
+    /// of `None`; check the path's last point to tell the two apart
+    pub partial: bool,
+    /// Class of the mover the path is for; every tile of the path,
+    /// smoothed or not, is one it can enter
+    pub passability: Passability,
+}
+
+/// Cost of one step in the search's fixed-point units
+const STEP: i32 = 10;
+/// No incoming direction (the start node, or turn penalties disabled)
+const NO_DIR: u8 = 4;
+/// Search state: tile plus the direction it was entered from
+type SearchNode = ((i32, i32), u8);
+
+/// Find a path from start to goal shaped by `options`
+///
+/// With default options this is [`astar_path`]. Otherwise the cost is the
+/// number of steps along the returned path.
+/// ```
+/// use gc_core::prelude::*;
+///
+/// let mut map = GameMap::new(5, 1);
+/// for x in 0..5 {
+///     map.set_tile(x, 0, TileKind::Water).unwrap();
+/// }
+/// let swim = PathOptions {
+///     passability: Passability::Swims,
+///     ..Default::default()
+/// };
+/// assert!(astar_path(&map, (0, 0), (4, 0)).is_none());
+/// assert_eq!(astar_path_with(&map, (0, 0), (4, 0), swim).unwrap().1, 4);
+/// ```
+pub fn astar_path_with(
+    map: &GameMap,
+    start: (i32, i32),
+    goal: (i32, i32),
+    options: PathOptions,
+) -> PathResult {
+    if options == PathOptions::default() {
+        return astar_path(map, start, goal);
+    }
+    PathSearch::with_options(PathRequest { start, goal }, options)
+        .step(map, usize::MAX)
+        .flatten()
+}
+
+/// A* search that can be suspended between ticks
+///
+/// Expands up to a budget of nodes per call, so a long search is spread
+/// across ticks; finished paths cost the same as [`astar_path`]'s. Ties in
+/// the open list are broken by heuristic, then insertion order, keeping
+/// results deterministic.
+/// The map is read afresh on each call; a search spanning ticks sees tiles
+/// as they are when each node is expanded.
+#[derive(Debug, Clone)]
+pub struct PathSearch {
+    pub request: PathRequest,
+    pub options: PathOptions,
+    open: BinaryHeap<OpenEntry>,
+    g: HashMap<SearchNode, i32>,
+    parent: HashMap<SearchNode, SearchNode>,
+    pushed: u64,
+    /// Whether any step so far carried extra cost from a cost layer
+    weighted: bool,
+    /// Expanded node closest to the goal as (distance, g, node), for
+    /// partial paths
+    closest: Option<(i32, i32, SearchNode)>,
+    /// Nodes expanded so far
+    pub expanded: usize,
+}
+
+impl PathSearch {
+    pub fn new(request: PathRequest) -> Self {
+        Self::with_options(request, PathOptions::default())
+    }
+
+    pub fn with_options(request: PathRequest, options: PathOptions) -> Self {
+        let start = (request.start, NO_DIR);
+        let mut search = Self {
+            request,
+            options,
+            open: BinaryHeap::new(),
+            g: HashMap::from([(start, 0)]),
+            parent: HashMap::new(),
+            pushed: 0,
+            weighted: false,
+            closest: None,
+            expanded: 0,
+        };
+        search.push(start, 0);
+        search
+    }
+
+    /// Manhattan distance from `(x, y)` to the goal
+    fn distance(&self, (x, y): (i32, i32)) -> i32 {
+        (x - self.request.goal.0).abs() + (y - self.request.goal.1).abs()
+    }
+
+    /// Steps still needed from a tile: to the goal, or next to it
+    fn remaining(&self, node: (i32, i32)) -> i32 {
+        let d = self.distance(node);
+        if self.options.adjacent {
+            (d - 1).max(0)
+        } else {
+            d
+        }
+    }
+
+    fn is_goal(&self, node: (i32, i32)) -> bool {
+        self.remaining(node) == 0
+    }
+
+    fn heuristic(&self, node: (i32, i32)) -> i32 {
+        STEP * self.remaining(node)
+    }
+
+    fn push(&mut self, node: SearchNode, g: i32) {
+        let h = self.heuristic(node.0);
+        self.open.push(Reverse((g + h, h, self.pushed, node)));
+        self.pushed += 1;
+    }
+
+    /// Expand up to `budget` nodes
+    ///
+    /// Returns `Some(result)` once the search has finished (`Some(None)`
+    /// when the goal is unreachable) and `None` while it needs more budget.
+    pub fn step(&mut self, map: &GameMap, budget: usize) -> Option<PathResult> {
+        self.step_weighted(map, budget, |_, _| 0)
+    }
+
+    /// [`step`](Self::step) with `extra(x, y)` added to the cost of
+    /// entering each tile, in tenths of a step
+    ///
+    /// Extra costs must not be negative. Once any step has carried extra
+    /// cost, the reported cost is the number of steps along the path.
+    pub fn step_weighted(
+        &mut self,
+        map: &GameMap,
+        budget: usize,
+        extra: impl Fn(i32, i32) -> i32,
+    ) -> Option<PathResult> {
+        let penalty = i32::from(self.options.turn_penalty);
+        if self.expanded == 0 && !self.endpoints_valid(map) {
+            self.open.clear();
+        }
+        for _ in 0..budget {
+            let Some(Reverse((_, _, _, state))) = self.open.pop() else {
+                return Some(self.give_up(map));
+            };
+            self.expanded += 1;
+            let g = self.g[&state];
+            let (node, dir) = state;
+            if self.is_goal(node) {
+                return Some(Some(self.finish(map, state, g)));
+            }
+            if self.options.partial {
+                let key = (self.distance(node), g);
+                if self.closest.map_or(true, |(d, cg, _)| key < (d, cg)) {
+                    self.closest = Some((key.0, key.1, state));
+                }
+            }
+            for (next, cost) in neighbors_for(map, self.options.passability, node.0, node.1) {
+                let next_dir = if penalty > 0 {
+                    direction(node, next)
+                } else {
+                    NO_DIR
+                };
+                let turn = dir != NO_DIR && next_dir != dir;
+                let danger = extra(next.0, next.1).max(0);
+                self.weighted |= danger > 0;
+                let ng = g + STEP * cost + danger + if turn { penalty } else { 0 };
+                let next_state = (next, next_dir);
+                if self.g.get(&next_state).map_or(true, |&old| ng < old) {
+                    self.g.insert(next_state, ng);
+                    self.parent.insert(next_state, state);
+                    self.push(next_state, ng);
+                }
+            }
+        }
+        if self.open.is_empty() {
+            Some(self.give_up(map))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the search can succeed at all; an off-map start always
+    /// fails, and so does a goal nobody can stand on unless the search
+    /// only needs to get next to it or close to it
+    fn endpoints_valid(&self, map: &GameMap) -> bool {
+        let PathRequest { start, goal } = self.request;
+        if self.options.adjacent || self.options.partial {
+            map.idx(start.0, start.1).is_some()
+        } else {
+            endpoints_valid(map, self.options.passability, start, goal)
+        }
+    }
+
+    /// Result once the open list is exhausted: the partial path to the
+    /// closest tile when asked for one, otherwise unreachable
+    fn give_up(&self, map: &GameMap) -> PathResult {
+        self.closest
+            .filter(|_| self.options.partial)
+            .map(|(_, g, node)| self.finish(map, node, g))
+    }
+
+    fn finish(&self, map: &GameMap, goal: SearchNode, g: i32) -> (Vec<(i32, i32)>, i32) {
+        let mut path = vec![goal.0];
+        let mut at = goal;
+        while let Some(&prev) = self.parent.get(&at) {
+            path.push(prev.0);
+            at = prev;
+        }
+        path.reverse();
+        if self.options.smooth {
+            path = smooth_path_for(map, self.options.passability, &path);
+        }
+        let cost = if self.options == PathOptions::default() && !self.weighted {
+            g / STEP
+        } else {
+            path.len() as i32 - 1
+        };
+        (path, cost)
+    }
+}
+
+/// Index of the orthogonal step from `a` to `b`
+fn direction(a: (i32, i32), b: (i32, i32)) -> u8 {
+    match (b.0 - a.0, b.1 - a.1) {
+        (1, 0) => 0,
+        (-1, 0) => 1,
+        (0, 1) => 2,
+        _ => 3,
+    }
+}
+
+/// Tiles on the line from `a` to `b`, excluding `a`, if `class` may enter
+/// them all
+/// Diagonal steps must not squeeze between two tiles it may not enter.
+fn clear_line(
+    map: &GameMap,
+    class: Passability,
+    a: (i32, i32),
+    b: (i32, i32),
+) -> Option<Vec<(i32, i32)>> {
+    let open = |x, y| class.passable(map, x, y);
+    let (dx, dy) = ((b.0 - a.0).abs(), -(b.1 - a.1).abs());
+    let (sx, sy) = ((b.0 - a.0).signum(), (b.1 - a.1).signum());
+    let mut err = dx + dy;
+    let (mut x, mut y) = a;
+    let mut cells = Vec::new();
+    while (x, y) != b {
+        let e2 = 2 * err;
+        let (px, py) = (x, y);
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        let diagonal = x != px && y != py;
+        if !open(x, y) || (diagonal && !open(x, py) && !open(px, y)) {
+            return None;
+        }
+        cells.push((x, y));
+    }
+    Some(cells)
+}
+
+/// String-pull a path: from each anchor jump to the furthest later point
+/// with a clear line, keeping the tiles on that line
+/// The result starts and ends where `path` does and is never longer.
+pub fn smooth_path(map: &GameMap, path: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    smooth_path_for(map, Passability::Walks, path)
+}
+
+/// [`smooth_path`] for a mover of `class`: lines only cross tiles it may
+/// enter
+pub fn smooth_path_for(map: &GameMap, class: Passability, path: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    if path.len() < 3 {
+        return path.to_vec();
+    }
+    let mut out = vec![path[0]];
+    let mut anchor = 0;
+    while anchor < path.len() - 1 {
+        let (next, line) = (anchor + 1..path.len())
+            .rev()
+            .find_map(|j| clear_line(map, class, path[anchor], path[j]).map(|line| (j, line)))
+            .unwrap_or((anchor + 1, vec![path[anchor + 1]]));
+        out.extend(line);
+        anchor = next;
+    }
+    out
+}
+
+/// Configuration resource for time-sliced pathfinding
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PathfindingConfig {
+    /// Nodes [`path_request_system`] may expand per tick, across all searches
+    pub nodes_per_tick: usize,
+    /// Shaping applied to every queued search
+    pub options: PathOptions,
+}
+
+impl Default for PathfindingConfig {
+    fn default() -> Self {
+        Self {
+            nodes_per_tick: 4096,
+            options: PathOptions::default(),
+        }
+    }
+}
+
+/// Finished path, inserted on the requesting entity by [`path_request_system`]
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct PathReady {
+    pub request: PathRequest,
+    /// Path including start and goal plus its cost, `None` when unreachable
+    pub result: PathResult,
+}
+
+/// Pending path requests, answered a budget at a time
+///
+/// Requests are served first come, first served; one entity has at most one
+/// request outstanding, and a new request replaces the old one.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PathQueue {
+    /// Requests with their own options, or `None` for the configured ones
+    waiting: VecDeque<(Entity, PathRequest, Option<PathOptions>)>,
+    current: Option<(Entity, PathSearch)>,
+    /// Nodes expanded during the last run of [`path_request_system`]
+    pub last_tick_expanded: usize,
+}
+
+impl PathQueue {
+    /// Ask for a path for `requester`; the answer arrives as [`PathReady`]
+    pub fn request(&mut self, requester: Entity, start: (i32, i32), goal: (i32, i32)) {
+        self.cancel(requester);
+        self.waiting
+            .push_back((requester, PathRequest { start, goal }, None));
+    }
+
+    /// [`request`](Self::request) with `options` in place of
+    /// [`PathfindingConfig::options`], e.g. to walk up next to a wall
+    pub fn request_with(
+        &mut self,
+        requester: Entity,
+        start: (i32, i32),
+        goal: (i32, i32),
+        options: PathOptions,
+    ) {
+        self.cancel(requester);
+        self.waiting
+            .push_back((requester, PathRequest { start, goal }, Some(options)));
+    }
+
+    /// Drop any outstanding request from `requester`
+    pub fn cancel(&mut self, requester: Entity) {
+        self.waiting.retain(|(e, _, _)| *e != requester);
+        if self.current.as_ref().is_some_and(|(e, _)| *e == requester) {
+            self.current = None;
+        }
+    }
+
+    /// Whether `requester` is still waiting for a path
+    pub fn is_pending(&self, requester: Entity) -> bool {
+        self.current.as_ref().is_some_and(|(e, _)| *e == requester)
+            || self.waiting.iter().any(|(e, _, _)| *e == requester)
+    }
+
+    /// Requests not yet answered, including one in progress
+    pub fn len(&self) -> usize {
+        self.waiting.len() + usize::from(self.current.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Work through [`PathQueue`] within [`PathfindingConfig::nodes_per_tick`]
+/// Searches that run out of budget resume next tick, so a burst of requests
+/// spreads over several ticks instead of stalling one.
+/// Civilian goblins (no [`Squad`](crate::equipment::Squad)) path around
+/// the hostiles in their faction's [`DangerMap`]. A requester with a
+/// [`Passability`] is searched for as that class, whatever the options say.
+pub fn path_request_system(
+    mut commands: Commands,
+    map: Res<GameMap>,
+    config: Option<Res<PathfindingConfig>>,
+    queue: Option<ResMut<PathQueue>>,
+    danger: Option<Res<DangerMap>>,
+    requesters: Query<(Option<&Faction>, Has<Squad>, Option<&Passability>)>,
+) {
+    let Some(mut queue) = queue else {
+        return;
+    };
+    if queue.is_empty() {
+        queue.last_tick_expanded = 0;
+        return;
+    }
+    let config = config.as_deref().copied().unwrap_or_default();
+    let budget = config.nodes_per_tick;
+    let mut spent = 0;
+    while spent < budget {
+        let (requester, mut search) = match queue.current.take() {
+            Some(current) => current,
+            None => match queue.waiting.pop_front() {
+                Some((e, request, options)) => {
+                    let mut options = options.unwrap_or(config.options);
+                    if let Ok((_, _, Some(&class))) = requesters.get(e) {
+                        options.passability = class;
+                    }
+                    (e, PathSearch::with_options(request, options))
+                }
+                None => break,
+            },
+        };
+        let avoid = requesters
+            .get(requester)
+            .ok()
+            .filter(|(faction, squad, _)| !squad && faction_of(*faction) == FactionKind::Goblins)
+            .and(danger.as_deref())
+            .filter(|d| !d.is_clear(FactionKind::Goblins));
+        let before = search.expanded;
+        let result = match avoid {
+            Some(danger) => search.step_weighted(&map, budget - spent, |x, y| {
+                danger.cost(FactionKind::Goblins, x, y)
+            }),
+            None => search.step(&map, budget - spent),
+        };
+        spent += search.expanded - before;
+        match result {
+            Some(result) => {
+                if let Some(mut entity) = commands.get_entity(requester) {
+                    entity.insert(PathReady {
+                        request: search.request,
+                        result,
+                    });
+                }
+            }
+            None => queue.current = Some((requester, search)),
+        }
+    }
+    queue.last_tick_expanded = spent;
+}
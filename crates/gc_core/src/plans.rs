@@ -0,0 +1,361 @@
+//! Designation plans: batch export and import of dig plans
+//!
+//! A [`DesignationPlan`] lists the mining designations and zones of a world
+//! in map coordinates, so external tools can read a colony's plans or
+//! generate new ones. Plans are written as JSON (the serde form of the
+//! plan) or CSV, one entry per row:
+//!
+//! ```text
+//! kind,x0,y0,x1,y1,accepts
+//! mine,4,2,6,2,
+//! stockpile,1,1,3,2,gem | ore
+//! ```
+//!
+//! `kind` is `mine` or a zone kind label; the corners are inclusive, and a
+//! `mine` row designates every tile of its rectangle. `accepts` is a
+//! stockpile's tag filter. [`import_plan`] checks every entry against the
+//! map before applying any, so a bad plan leaves the world untouched.
+//!
+//! Stockpile filters given as item type lists rather than tags are not
+//! part of a plan.
+
+use crate::command::{apply_world_command, WorldCommand};
+use crate::components::{DesignationLifecycle, DesignationState, ZoneBounds};
+use crate::designations::{designate_mine_cells, MineDesignation};
+use crate::jobs::IssuedBy;
+use crate::tags::{AcceptsTags, TagQuery, TagRegistry};
+use crate::world::{GameMap, Position};
+use crate::zones::ZoneKind;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Header row of CSV plans
+pub const PLAN_CSV_HEADER: &str = "kind,x0,y0,x1,y1,accepts";
+
+/// Why a plan could not be read or imported
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    #[error("invalid plan JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("line {line}: {message}")]
+    Csv { line: usize, message: String },
+    #[error("mine designation at ({x}, {y}) is outside the {width}x{height} map")]
+    MineOutOfBounds {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    #[error("{} zone ({}, {})-({}, {}) is outside the {width}x{height} map", kind.label(), min.0, min.1, max.0, max.1)]
+    ZoneOutOfBounds {
+        kind: ZoneKind,
+        min: (i32, i32),
+        max: (i32, i32),
+        width: u32,
+        height: u32,
+    },
+    #[error("{} zone at ({}, {}): {reason}", kind.label(), min.0, min.1)]
+    InvalidZone {
+        kind: ZoneKind,
+        min: (i32, i32),
+        reason: String,
+    },
+}
+
+/// A zone in a plan, corners inclusive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanZone {
+    pub kind: ZoneKind,
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+    /// Tag filter of a stockpile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepts: Option<TagQuery>,
+}
+
+/// Mining designations and zones in map coordinates
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesignationPlan {
+    /// Tiles designated for mining, sorted
+    #[serde(default)]
+    pub mines: Vec<(i32, i32)>,
+    #[serde(default)]
+    pub zones: Vec<PlanZone>,
+}
+
+/// What [`import_plan`] created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanImport {
+    pub designations: usize,
+    pub zones: usize,
+    /// Mining tiles already designated in the world, or repeated in the plan
+    pub duplicates: usize,
+}
+
+/// File format of a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Json,
+    Csv,
+}
+
+impl PlanFormat {
+    /// `.csv` files are CSV, anything else JSON
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => PlanFormat::Csv,
+            _ => PlanFormat::Json,
+        }
+    }
+}
+
+/// The world's live mining designations and its zones
+///
+/// Designations that were consumed or ignored are left out; zones keep
+/// their entity order.
+pub fn export_plan(world: &mut World) -> DesignationPlan {
+    let mut mines: Vec<(i32, i32)> = world
+        .query_filtered::<(&Position, Option<&DesignationLifecycle>), With<MineDesignation>>()
+        .iter(world)
+        .filter(|(_, l)| {
+            !matches!(
+                l.map(|l| l.0),
+                Some(DesignationState::Ignored | DesignationState::Consumed)
+            )
+        })
+        .map(|(p, _)| (p.0, p.1))
+        .collect();
+    mines.sort_unstable();
+    mines.dedup();
+
+    let mut zones: Vec<(Entity, PlanZone)> = world
+        .query::<(Entity, &ZoneKind, &ZoneBounds, Option<&AcceptsTags>)>()
+        .iter(world)
+        .map(|(e, kind, b, tags)| {
+            (
+                e,
+                PlanZone {
+                    kind: *kind,
+                    min: (b.min_x, b.min_y),
+                    max: (b.max_x, b.max_y),
+                    accepts: tags.map(|t| t.0.clone()),
+                },
+            )
+        })
+        .collect();
+    zones.sort_by_key(|(e, _)| *e);
+    DesignationPlan {
+        mines,
+        zones: zones.into_iter().map(|(_, z)| z).collect(),
+    }
+}
+
+/// Check every entry of `plan` against the world's map and tag registry
+pub fn validate_plan(world: &World, plan: &DesignationPlan) -> Result<(), PlanError> {
+    if let Some(map) = world.get_resource::<GameMap>() {
+        let (width, height) = (map.width, map.height);
+        if let Some(&(x, y)) = plan.mines.iter().find(|&&(x, y)| !map.in_bounds(x, y)) {
+            return Err(PlanError::MineOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+        if let Some(zone) = plan
+            .zones
+            .iter()
+            .find(|z| !map.in_bounds(z.min.0, z.min.1) || !map.in_bounds(z.max.0, z.max.1))
+        {
+            return Err(PlanError::ZoneOutOfBounds {
+                kind: zone.kind,
+                min: zone.min,
+                max: zone.max,
+                width,
+                height,
+            });
+        }
+    }
+    let default_registry = TagRegistry::default();
+    let registry = world
+        .get_resource::<TagRegistry>()
+        .unwrap_or(&default_registry);
+    for zone in &plan.zones {
+        let reason = if zone.min.0 > zone.max.0 || zone.min.1 > zone.max.1 {
+            Some("minimum corner is past the maximum".to_string())
+        } else if zone.accepts.is_some() && zone.kind != ZoneKind::Stockpile {
+            Some("only stockpiles take a filter".to_string())
+        } else {
+            zone.accepts
+                .as_ref()
+                .and_then(|q| registry.validate(q).err())
+                .map(|e| e.to_string())
+        };
+        if let Some(reason) = reason {
+            return Err(PlanError::InvalidZone {
+                kind: zone.kind,
+                min: zone.min,
+                reason,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Designate and zone everything in `plan` for the local player
+///
+/// Nothing is applied unless the whole plan is valid. Must only be called
+/// between schedule runs.
+pub fn import_plan(world: &mut World, plan: &DesignationPlan) -> Result<PlanImport, PlanError> {
+    validate_plan(world, plan)?;
+    let batch = designate_mine_cells(world, plan.mines.iter().copied());
+    for &designation in &batch.spawned {
+        world.entity_mut(designation).insert(IssuedBy::LOCAL_PLAYER);
+    }
+    let mut zones = 0;
+    for zone in &plan.zones {
+        let command = WorldCommand::Zone {
+            kind: zone.kind,
+            a: zone.min,
+            b: zone.max,
+            accepts: zone.accepts.clone(),
+        };
+        zones += usize::from(apply_world_command(world, &command));
+    }
+    Ok(PlanImport {
+        designations: batch.spawned.len(),
+        zones,
+        duplicates: batch.duplicates,
+    })
+}
+
+pub fn encode_plan_json(plan: &DesignationPlan) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(plan)
+}
+
+pub fn decode_plan_json(s: &str) -> Result<DesignationPlan, PlanError> {
+    Ok(serde_json::from_str(s)?)
+}
+
+/// Write `plan` as CSV, one row per mining tile and per zone
+pub fn encode_plan_csv(plan: &DesignationPlan) -> String {
+    let mut out = format!("{PLAN_CSV_HEADER}\n");
+    for &(x, y) in &plan.mines {
+        let _ = writeln!(out, "mine,{x},{y},{x},{y},");
+    }
+    for z in &plan.zones {
+        let accepts = z.accepts.as_ref().map(|q| csv_field(&q.to_string()));
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            z.kind.label(),
+            z.min.0,
+            z.min.1,
+            z.max.0,
+            z.max.1,
+            accepts.unwrap_or_default()
+        );
+    }
+    out
+}
+
+/// Read a CSV plan; the header row and blank lines are skipped, and corners
+/// may be given in any order
+pub fn decode_plan_csv(s: &str) -> Result<DesignationPlan, PlanError> {
+    let mut plan = DesignationPlan::default();
+    for (i, raw) in s.lines().enumerate() {
+        let line = i + 1;
+        let text = raw.trim();
+        if text.is_empty() || text == PLAN_CSV_HEADER {
+            continue;
+        }
+        let err = |message: String| PlanError::Csv { line, message };
+        let fields = split_csv(text).map_err(|m| err(m.to_string()))?;
+        if !(5..=6).contains(&fields.len()) {
+            return Err(err(format!(
+                "expected 5 or 6 fields, found {}",
+                fields.len()
+            )));
+        }
+        let mut corner = [0i32; 4];
+        for (value, field) in corner.iter_mut().zip(&fields[1..5]) {
+            *value = field
+                .trim()
+                .parse()
+                .map_err(|_| err(format!("'{}' is not a coordinate", field.trim())))?;
+        }
+        let [x0, y0, x1, y1] = corner;
+        let (min, max) = ((x0.min(x1), y0.min(y1)), (x0.max(x1), y0.max(y1)));
+        let accepts = fields.get(5).map(|f| f.trim()).filter(|f| !f.is_empty());
+        let kind = fields[0].trim();
+        if kind == "mine" {
+            if accepts.is_some() {
+                return Err(err("mine rows take no filter".into()));
+            }
+            plan.mines
+                .extend((min.1..=max.1).flat_map(|y| (min.0..=max.0).map(move |x| (x, y))));
+            continue;
+        }
+        let kind =
+            ZoneKind::from_label(kind).ok_or_else(|| err(format!("unknown kind '{kind}'")))?;
+        let accepts = accepts
+            .map(TagQuery::parse)
+            .transpose()
+            .map_err(|e| err(e.to_string()))?;
+        plan.zones.push(PlanZone {
+            kind,
+            min,
+            max,
+            accepts,
+        });
+    }
+    Ok(plan)
+}
+
+pub fn encode_plan(plan: &DesignationPlan, format: PlanFormat) -> Result<String, PlanError> {
+    match format {
+        PlanFormat::Json => Ok(encode_plan_json(plan)?),
+        PlanFormat::Csv => Ok(encode_plan_csv(plan)),
+    }
+}
+
+pub fn decode_plan(s: &str, format: PlanFormat) -> Result<DesignationPlan, PlanError> {
+    match format {
+        PlanFormat::Json => decode_plan_json(s),
+        PlanFormat::Csv => decode_plan_csv(s),
+    }
+}
+
+/// Quote a CSV field when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Split one CSV row, honouring double-quoted fields
+fn split_csv(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("at least one field");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote");
+    }
+    Ok(fields)
+}
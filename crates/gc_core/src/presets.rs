@@ -0,0 +1,154 @@
+//! Selection presets: named zone and designation settings
+//!
+//! Setting up a fort repeats the same choices: a stockpile for metal and
+//! ore, a refuse pile, a rush dig by the entrance. A [`Preset`] names one
+//! such set of choices so the TUI and the REPL can apply it to a selected
+//! area in one step. Presets live in the config file:
+//!
+//! ```json
+//! "presets": {
+//!   "metals": { "type": "zone", "kind": "stockpile", "accepts": "metal | ore" },
+//!   "rush": { "type": "designation", "priority": 5 }
+//! }
+//! ```
+//!
+//! They are checked against the [`TagRegistry`] on load
+//! ([`Presets::validate`]), so a misspelled tag fails the config instead
+//! of creating stockpiles that take nothing.
+
+use crate::command::WorldCommand;
+use crate::tags::{TagError, TagQuery, TagRegistry};
+use crate::zones::ZoneKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Why a preset was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PresetError {
+    #[error("unknown preset '{0}'")]
+    Unknown(String),
+    #[error("invalid preset name '{0}': use letters, digits, '-' and '_'")]
+    InvalidName(String),
+    #[error("preset '{0}': only stockpiles take a tag filter")]
+    FilterOnZone(String),
+    #[error("preset '{name}': {source}")]
+    Tag {
+        name: String,
+        #[source]
+        source: TagError,
+    },
+}
+
+/// Settings applied to a selected area
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Preset {
+    /// Mark out a zone; `accepts` filters a stockpile by tags
+    Zone {
+        kind: ZoneKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accepts: Option<TagQuery>,
+    },
+    /// Designate the area for mining at a job priority
+    Designation {
+        #[serde(default)]
+        priority: u32,
+    },
+}
+
+impl Preset {
+    pub fn is_zone(&self) -> bool {
+        matches!(self, Preset::Zone { .. })
+    }
+
+    /// Commands applying the preset to the area between two corners
+    /// (inclusive, in any order)
+    pub fn commands(&self, a: (i32, i32), b: (i32, i32)) -> Vec<WorldCommand> {
+        match *self {
+            Preset::Zone { kind, ref accepts } => vec![WorldCommand::Zone {
+                kind,
+                a,
+                b,
+                accepts: accepts.clone(),
+            }],
+            Preset::Designation { priority: 0 } => vec![WorldCommand::DesignateRect { a, b }],
+            Preset::Designation { priority } => vec![
+                WorldCommand::DesignateRect { a, b },
+                WorldCommand::PrioritizeDesignations { a, b, priority },
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Preset::Zone {
+                kind,
+                accepts: Some(query),
+            } => write!(f, "{} accepting {}", kind.label(), query),
+            Preset::Zone {
+                kind,
+                accepts: None,
+            } => f.write_str(kind.label()),
+            Preset::Designation { priority } => write!(f, "mine at priority {priority}"),
+        }
+    }
+}
+
+/// Named presets, in name order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Presets(pub BTreeMap<String, Preset>);
+
+impl Presets {
+    pub fn get(&self, name: &str) -> Result<&Preset, PresetError> {
+        self.0
+            .get(name)
+            .ok_or_else(|| PresetError::Unknown(name.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Preset)> {
+        self.0.iter().map(|(name, preset)| (name.as_str(), preset))
+    }
+
+    /// Check names, and every tag filter against `registry`
+    pub fn validate(&self, registry: &TagRegistry) -> Result<(), PresetError> {
+        for (name, preset) in self.iter() {
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(PresetError::InvalidName(name.to_string()));
+            }
+            if let Preset::Zone {
+                kind,
+                accepts: Some(query),
+            } = preset
+            {
+                if *kind != ZoneKind::Stockpile {
+                    return Err(PresetError::FilterOnZone(name.to_string()));
+                }
+                registry
+                    .validate(query)
+                    .map_err(|source| PresetError::Tag {
+                        name: name.to_string(),
+                        source,
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Commands applying preset `name` to the area between two corners
+    pub fn apply(
+        &self,
+        name: &str,
+        a: (i32, i32),
+        b: (i32, i32),
+    ) -> Result<Vec<WorldCommand>, PresetError> {
+        Ok(self.get(name)?.commands(a, b))
+    }
+}
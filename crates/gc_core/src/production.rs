@@ -0,0 +1,689 @@
+//! Production: stations, recipes and fuel
+//!
+//! A finished workshop, smelter or mason's workshop carries a [`Station`] with a queue of
+//! orders, each naming a [`Recipe`] in the [`RecipeBook`]. For the order at
+//! the head of the queue:
+//!
+//! 1. Supply: [`station_supply_system`] posts haul jobs bringing the
+//!    recipe's inputs and fuel to the station and absorbs each item dropped
+//!    there into the station's stock.
+//! 2. Crafting: once everything is stocked it posts a single
+//!    [`JobKind::Craft`], which an idle miner takes
+//!    ([`craft_job_assignment_system`]) and works off next to the station
+//!    ([`craft_execution_system`]). The product is dropped at the crafter's
+//!    feet, where hauling picks it up like any other item.
+//!
+//! Products of material-bearing inputs keep the [`Material`] of the first
+//! one consumed: a mason's blocks cut from granite are granite blocks, and
+//! worth what granite is worth.
+//!
+//! Fuel is any item tagged `fuel` in the [`TagRegistry`] (coal, wood).
+//! Recipes declare a fuel cost, and stations that burn fuel charge at least
+//! [`StationKind::min_fuel`] per craft. Those stations also keep
+//! [`FuelConfig::reserve`] units stocked while idle, so the next order does
+//! not wait on a fuel run. Each station counts its crafts and the fuel they
+//! burned.
+
+use crate::components::{AssignedJob, Faction, Item, ItemType, Miner, MovementOverride};
+use crate::determinism::RngStream;
+use crate::equipment::Gear;
+use crate::forbid::Forbidden;
+use crate::health::Stalled;
+use crate::jobs::{
+    add_job_for, assign_board_job, faction_of, ActiveJobs, BoardWorker, IssuedBy, JobBoard, JobId,
+    JobKind,
+};
+use crate::path::PathService;
+use crate::quality::{crafted_item, roll_quality, CraftingSkill};
+use crate::systems::{travel, DeterministicRng, MovementConfig};
+use crate::tags::{Ingredient, TagQuery, TagRegistry};
+use crate::work_area::WorkAreaConfig;
+use crate::world::{GameMap, Material, Name, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+/// Tag marking items a station can burn
+pub const FUEL_TAG: &str = "fuel";
+
+/// Kind of production station
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StationKind {
+    Workshop,
+    Smelter,
+    /// Cuts stone into blocks
+    Mason,
+}
+
+impl StationKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            StationKind::Workshop => "workshop",
+            StationKind::Smelter => "smelter",
+            StationKind::Mason => "mason",
+        }
+    }
+
+    /// Display name of a finished station
+    pub fn name(self) -> &'static str {
+        match self {
+            StationKind::Workshop => "Workshop",
+            StationKind::Smelter => "Smelter",
+            StationKind::Mason => "Mason's workshop",
+        }
+    }
+
+    /// Fuel units burned per craft whatever the recipe says; zero for
+    /// stations that need no fuel
+    pub fn min_fuel(self) -> u32 {
+        match self {
+            StationKind::Workshop | StationKind::Mason => 0,
+            StationKind::Smelter => 1,
+        }
+    }
+
+    /// Whether the station burns fuel and keeps a reserve of it
+    pub fn burns_fuel(self) -> bool {
+        self.min_fuel() > 0
+    }
+}
+
+/// Why an order was refused
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProductionError {
+    #[error("unknown recipe '{0}'")]
+    UnknownRecipe(String),
+    #[error("recipe '{recipe}' needs a {needs}, not a {station}")]
+    WrongStation {
+        recipe: String,
+        needs: &'static str,
+        station: &'static str,
+    },
+    #[error("entity is not a production station")]
+    NotAStation,
+}
+
+/// `count` items matching `ingredient`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeInput {
+    pub ingredient: Ingredient,
+    pub count: u32,
+}
+
+/// What a station turns into what
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub station: StationKind,
+    pub inputs: Vec<RecipeInput>,
+    pub output: ItemType,
+    /// Ticks of work once inputs and fuel are stocked
+    pub work_ticks: u32,
+    /// Fuel units burned per craft
+    #[serde(default)]
+    pub fuel: u32,
+}
+
+impl Recipe {
+    /// Fuel units one craft actually burns at its station
+    pub fn fuel_cost(&self) -> u32 {
+        self.fuel.max(self.station.min_fuel())
+    }
+}
+
+/// Every recipe stations can be ordered to make
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+}
+
+impl Default for RecipeBook {
+    fn default() -> Self {
+        let metal = Ingredient::Tagged(TagQuery::tag("metal").expect("valid tag"));
+        Self {
+            recipes: vec![
+                Recipe {
+                    name: "smelt-iron".into(),
+                    station: StationKind::Smelter,
+                    inputs: vec![RecipeInput {
+                        ingredient: Ingredient::Item(ItemType::Ore),
+                        count: 1,
+                    }],
+                    output: ItemType::Bar,
+                    work_ticks: 4,
+                    fuel: 1,
+                },
+                Recipe {
+                    name: "forge-weapon".into(),
+                    station: StationKind::Workshop,
+                    inputs: vec![RecipeInput {
+                        ingredient: metal.clone(),
+                        count: 2,
+                    }],
+                    output: ItemType::Weapon,
+                    work_ticks: 6,
+                    fuel: 0,
+                },
+                Recipe {
+                    name: "forge-armor".into(),
+                    station: StationKind::Workshop,
+                    inputs: vec![RecipeInput {
+                        ingredient: metal,
+                        count: 3,
+                    }],
+                    output: ItemType::Armor,
+                    work_ticks: 8,
+                    fuel: 0,
+                },
+                Recipe {
+                    name: "cut-blocks".into(),
+                    station: StationKind::Mason,
+                    inputs: vec![RecipeInput {
+                        ingredient: Ingredient::Item(ItemType::Stone),
+                        count: 1,
+                    }],
+                    output: ItemType::Block,
+                    work_ticks: 3,
+                    fuel: 0,
+                },
+            ],
+        }
+    }
+}
+
+impl RecipeBook {
+    pub fn get(&self, name: &str) -> Option<&Recipe> {
+        self.recipes.iter().find(|r| r.name == name)
+    }
+}
+
+/// Fuel stocking policy
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuelConfig {
+    /// Fuel units a fuel-burning station keeps stocked, orders or not
+    pub reserve: u32,
+}
+
+impl Default for FuelConfig {
+    fn default() -> Self {
+        Self { reserve: 2 }
+    }
+}
+
+/// What an incoming haul brings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Supply {
+    /// Input at this index of the head order's recipe
+    Input(usize),
+    Fuel,
+}
+
+/// A production station: its order queue, stock and tallies
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Station {
+    pub kind: StationKind,
+    /// Recipe names, worked front to back
+    pub orders: VecDeque<String>,
+    /// Items absorbed into the station, by type
+    pub stock: BTreeMap<ItemType, u32>,
+    /// Stocked items that carry a [`Material`], by material
+    pub materials: BTreeMap<Material, u32>,
+    /// Haul jobs bringing items here, queued or under way
+    pub(crate) incoming: Vec<(JobId, Supply)>,
+    /// Craft job posted once the head order is supplied
+    pub craft_job: Option<JobId>,
+    /// Ticks of work done on the head order
+    pub progress: u32,
+    /// Orders finished
+    pub crafts: u32,
+    /// Fuel units burned by finished orders
+    pub fuel_used: u32,
+}
+
+impl Station {
+    pub fn new(kind: StationKind) -> Self {
+        Self {
+            kind,
+            orders: VecDeque::new(),
+            stock: BTreeMap::new(),
+            materials: BTreeMap::new(),
+            incoming: Vec::new(),
+            craft_job: None,
+            progress: 0,
+            crafts: 0,
+            fuel_used: 0,
+        }
+    }
+
+    /// Add an order for `recipe` to the back of the queue
+    pub fn queue(&mut self, recipe: &Recipe) -> Result<(), ProductionError> {
+        if recipe.station != self.kind {
+            return Err(ProductionError::WrongStation {
+                recipe: recipe.name.clone(),
+                needs: recipe.station.label(),
+                station: self.kind.label(),
+            });
+        }
+        self.orders.push_back(recipe.name.clone());
+        Ok(())
+    }
+
+    /// Fuel units in stock
+    pub fn fuel(&self, registry: &TagRegistry) -> u32 {
+        self.stocked(|t| registry.has_tag(t, FUEL_TAG))
+    }
+
+    /// Haul jobs currently bringing items here
+    pub fn incoming(&self) -> impl Iterator<Item = JobId> + '_ {
+        self.incoming.iter().map(|&(id, _)| id)
+    }
+
+    fn stocked(&self, wanted: impl Fn(ItemType) -> bool) -> u32 {
+        self.stock
+            .iter()
+            .filter(|(&t, _)| wanted(t))
+            .map(|(_, &n)| n)
+            .sum()
+    }
+
+    /// Remove `count` units of matching items, lowest item type first
+    fn take(&mut self, mut count: u32, wanted: impl Fn(ItemType) -> bool) {
+        for (_, n) in self.stock.iter_mut().filter(|(&t, _)| wanted(t)) {
+            let used = count.min(*n);
+            *n -= used;
+            count -= used;
+        }
+        self.stock.retain(|_, n| *n > 0);
+    }
+
+    /// Remove up to `count` units from the material tallies, lowest
+    /// material first, returning the first material taken
+    fn take_materials(&mut self, mut count: u32) -> Option<Material> {
+        let mut first = None;
+        for (&material, n) in self.materials.iter_mut() {
+            if count == 0 {
+                break;
+            }
+            let used = count.min(*n);
+            if used > 0 {
+                first = first.or(Some(material));
+            }
+            *n -= used;
+            count -= used;
+        }
+        self.materials.retain(|_, n| *n > 0);
+        first
+    }
+
+    fn in_flight(&self, supply: Supply) -> u32 {
+        self.incoming.iter().filter(|&&(_, s)| s == supply).count() as u32
+    }
+}
+
+/// Queue `recipe` at `station` by name
+pub fn queue_order(
+    world: &mut World,
+    station: Entity,
+    recipe: &str,
+) -> Result<(), ProductionError> {
+    let recipe = world
+        .get_resource::<RecipeBook>()
+        .cloned()
+        .unwrap_or_default()
+        .get(recipe)
+        .cloned()
+        .ok_or_else(|| ProductionError::UnknownRecipe(recipe.to_string()))?;
+    world
+        .get_mut::<Station>(station)
+        .ok_or(ProductionError::NotAStation)?
+        .queue(&recipe)
+}
+
+/// Units of `supply` the station still lacks for the head order (and, for
+/// fuel, its reserve)
+fn shortfall(
+    station: &Station,
+    recipe: Option<&Recipe>,
+    supply: Supply,
+    registry: &TagRegistry,
+    reserve: u32,
+) -> u32 {
+    match supply {
+        Supply::Input(i) => {
+            let Some(input) = recipe.and_then(|r| r.inputs.get(i)) else {
+                return 0;
+            };
+            input
+                .count
+                .saturating_sub(station.stocked(|t| input.ingredient.matches(t, registry)))
+        }
+        Supply::Fuel => {
+            let cost = recipe.map_or(0, Recipe::fuel_cost);
+            let target = if station.kind.burns_fuel() {
+                cost.max(reserve)
+            } else {
+                cost
+            };
+            target.saturating_sub(station.fuel(registry))
+        }
+    }
+}
+
+fn supplies(
+    item_type: ItemType,
+    recipe: Option<&Recipe>,
+    supply: Supply,
+    registry: &TagRegistry,
+) -> bool {
+    match supply {
+        Supply::Input(i) => recipe
+            .and_then(|r| r.inputs.get(i))
+            .is_some_and(|input| input.ingredient.matches(item_type, registry)),
+        Supply::Fuel => registry.has_tag(item_type, FUEL_TAG),
+    }
+}
+
+/// Supply every station's head order and post its craft job once supplied
+///
+/// Each tick, per station (in entity order):
+/// - haul jobs that finished or vanished stop counting as incoming, and
+///   orders naming unknown recipes are dropped;
+/// - items the head order or the fuel reserve still lacks are absorbed from
+///   the station's tile;
+/// - the remaining shortfall gets haul jobs from the nearest free items,
+///   where free means not [`Forbidden`], not on a station or construction
+///   site and not already the pickup tile of a haul;
+/// - a fully supplied head order with no craft job posts one.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn station_supply_system(
+    mut commands: Commands,
+    mut board: ResMut<JobBoard>,
+    active: Res<ActiveJobs>,
+    mut rng: ResMut<DeterministicRng>,
+    book: Option<Res<RecipeBook>>,
+    registry: Option<Res<TagRegistry>>,
+    fuel: Option<Res<FuelConfig>>,
+    mut stations: Query<(Entity, &Position, &mut Station, Option<&Faction>)>,
+    sites: Query<&Position, With<crate::construction::Construction>>,
+    items: Query<(Entity, &Position, &Item, Option<&Material>), Without<Forbidden>>,
+) {
+    if stations.is_empty() {
+        return;
+    }
+    let (book_fallback, registry_fallback);
+    let book = match book.as_deref() {
+        Some(b) => b,
+        None => {
+            book_fallback = RecipeBook::default();
+            &book_fallback
+        }
+    };
+    let registry = match registry.as_deref() {
+        Some(r) => r,
+        None => {
+            registry_fallback = TagRegistry::default();
+            &registry_fallback
+        }
+    };
+    let reserve = fuel.as_deref().copied().unwrap_or_default().reserve;
+
+    let busy_tiles: HashSet<(i32, i32)> = stations
+        .iter()
+        .map(|(_, p, ..)| (p.0, p.1))
+        .chain(sites.iter().map(|p| (p.0, p.1)))
+        .collect();
+    let mut claimed: HashSet<(i32, i32)> = board
+        .0
+        .iter()
+        .chain(active.jobs.values())
+        .filter_map(|job| match job.kind {
+            JobKind::Haul { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect();
+    let mut absorbed: HashSet<Entity> = HashSet::new();
+
+    let mut order: Vec<Entity> = stations.iter().map(|(e, ..)| e).collect();
+    order.sort();
+    for entity in order {
+        let (_, pos, mut station, faction) = stations.get_mut(entity).expect("listed above");
+        let at = (pos.0, pos.1);
+        let faction = faction_of(faction);
+
+        let live: HashSet<JobId> = board.0.iter().map(|j| j.id).collect();
+        station
+            .incoming
+            .retain(|(id, _)| live.contains(id) || active.jobs.contains_key(id));
+        while station
+            .orders
+            .front()
+            .is_some_and(|name| book.get(name).is_none())
+        {
+            station.orders.pop_front();
+            station.progress = 0;
+        }
+        let recipe = station.orders.front().and_then(|name| book.get(name));
+        let mut wanted: Vec<Supply> = (0..recipe.map_or(0, |r| r.inputs.len()))
+            .map(Supply::Input)
+            .collect();
+        wanted.push(Supply::Fuel);
+
+        let mut here: Vec<(Entity, ItemType, Option<Material>)> = items
+            .iter()
+            .filter(|(e, p, ..)| (p.0, p.1) == at && !absorbed.contains(e))
+            .map(|(e, _, item, material)| (e, item.item_type, material.copied()))
+            .collect();
+        here.sort();
+        for (item, item_type, material) in here {
+            let needed = wanted.iter().any(|&s| {
+                supplies(item_type, recipe, s, registry)
+                    && shortfall(&station, recipe, s, registry, reserve) > 0
+            });
+            if needed {
+                *station.stock.entry(item_type).or_default() += 1;
+                if let Some(material) = material {
+                    *station.materials.entry(material).or_default() += 1;
+                }
+                absorbed.insert(item);
+                commands.entity(item).despawn();
+            }
+        }
+
+        for &supply in &wanted {
+            let mut outstanding = shortfall(&station, recipe, supply, registry, reserve)
+                .saturating_sub(station.in_flight(supply));
+            if outstanding == 0 {
+                continue;
+            }
+            let mut free: Vec<(i32, Entity, (i32, i32))> = items
+                .iter()
+                .filter(|(e, p, item, _)| {
+                    supplies(item.item_type, recipe, supply, registry)
+                        && !absorbed.contains(e)
+                        && !busy_tiles.contains(&(p.0, p.1))
+                })
+                .map(|(e, p, ..)| ((p.0 - at.0).abs() + (p.1 - at.1).abs(), e, (p.0, p.1)))
+                .collect();
+            free.sort();
+            for (_, _, from) in free {
+                if outstanding == 0 {
+                    break;
+                }
+                if !claimed.insert(from) {
+                    continue;
+                }
+                let id = add_job_for(
+                    &mut board,
+                    JobKind::Haul { from, to: at },
+                    IssuedBy::System,
+                    faction,
+                    rng.stream(RngStream::Job, "station_supply_system"),
+                );
+                station.incoming.push((id, supply));
+                outstanding -= 1;
+            }
+        }
+
+        let supplied = recipe.is_some()
+            && wanted
+                .iter()
+                .filter(|s| matches!(s, Supply::Input(_)))
+                .all(|&s| shortfall(&station, recipe, s, registry, reserve) == 0)
+            && station.fuel(registry) >= recipe.map_or(0, Recipe::fuel_cost);
+        if supplied && station.craft_job.is_none() {
+            let id = add_job_for(
+                &mut board,
+                JobKind::Craft { x: at.0, y: at.1 },
+                IssuedBy::System,
+                faction,
+                rng.stream(RngStream::Job, "station_supply_system"),
+            );
+            station.craft_job = Some(id);
+        }
+    }
+}
+
+/// Hand one queued craft job to an idle miner of its faction per run, within
+/// its [`WorkArea`]
+pub fn craft_job_assignment_system(
+    mut board: ResMut<JobBoard>,
+    mut active: ResMut<ActiveJobs>,
+    work_areas: Option<Res<WorkAreaConfig>>,
+    names: Query<&Name>,
+    mut crafters: Query<BoardWorker, With<Miner>>,
+) {
+    assign_board_job(
+        &mut board,
+        &mut active,
+        work_areas.as_deref(),
+        &names,
+        &mut crafters,
+        |kind| matches!(kind, JobKind::Craft { .. }),
+    );
+}
+
+/// Walk crafters next to their station and put one tick of work in per run
+///
+/// A finished order consumes its inputs and fuel cost from the station's
+/// stock and drops the product, with quality rolled from the crafter's
+/// [`CraftingSkill`], on the crafter's tile. A craft job whose station is
+/// gone, or whose stock no longer covers the order, is dropped.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn craft_execution_system(
+    mut commands: Commands,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
+    book: Option<Res<RecipeBook>>,
+    registry: Option<Res<TagRegistry>>,
+    mut rng: ResMut<DeterministicRng>,
+    mut active: ResMut<ActiveJobs>,
+    mut crafters: Query<
+        (
+            &mut AssignedJob,
+            &mut Position,
+            Option<&MovementOverride>,
+            Option<&CraftingSkill>,
+        ),
+        (With<Miner>, Without<Stalled>, Without<Station>),
+    >,
+    mut stations: Query<(&Position, &mut Station), Without<Miner>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let (book_fallback, registry_fallback);
+    let book = match book.as_deref() {
+        Some(b) => b,
+        None => {
+            book_fallback = RecipeBook::default();
+            &book_fallback
+        }
+    };
+    let registry = match registry.as_deref() {
+        Some(r) => r,
+        None => {
+            registry_fallback = TagRegistry::default();
+            &registry_fallback
+        }
+    };
+    for (mut assigned, mut pos, movement, skill) in crafters.iter_mut() {
+        let Some(id) = assigned.0 else {
+            continue;
+        };
+        let Some(JobKind::Craft { x, y }) = active.jobs.get(&id).map(|j| j.kind.clone()) else {
+            continue;
+        };
+        let recipe = stations
+            .iter_mut()
+            .find(|(p, s)| (p.0, p.1) == (x, y) && s.craft_job == Some(id))
+            .and_then(|(_, station)| {
+                let recipe = book.get(station.orders.front()?)?.clone();
+                Some((station, recipe))
+            });
+        let Some((mut station, recipe)) = recipe else {
+            active.jobs.remove(&id);
+            assigned.0 = None;
+            continue;
+        };
+        // Work from the neighbouring tile on the crafter's side
+        let (dx, dy) = (pos.0 - x, pos.1 - y);
+        if dx.abs() > 1 || dy.abs() > 1 {
+            let approach = (x + dx.signum(), y + dy.signum());
+            travel(
+                &mut pos,
+                approach,
+                config.mode_for(movement),
+                map.as_deref(),
+                paths.as_deref_mut(),
+            );
+            continue;
+        }
+        station.progress += 1;
+        if station.progress < recipe.work_ticks {
+            continue;
+        }
+        active.jobs.remove(&id);
+        assigned.0 = None;
+        station.craft_job = None;
+        station.progress = 0;
+
+        let cost = recipe.fuel_cost();
+        let stocked =
+            recipe.inputs.iter().all(|input| {
+                station.stocked(|t| input.ingredient.matches(t, registry)) >= input.count
+            }) && station.fuel(registry) >= cost;
+        if !stocked {
+            continue;
+        }
+        for input in &recipe.inputs {
+            station.take(input.count, |t| input.ingredient.matches(t, registry));
+        }
+        let consumed = recipe.inputs.iter().map(|i| i.count).sum();
+        let material = station.take_materials(consumed);
+        station.take(cost, |t| registry.has_tag(t, FUEL_TAG));
+        station.fuel_used += cost;
+        station.crafts += 1;
+        station.orders.pop_front();
+
+        let quality = roll_quality(
+            skill.copied().unwrap_or_default(),
+            rng.stream(RngStream::Job, "craft_execution_system"),
+        );
+        let mut product = commands.spawn(crafted_item(recipe.output, quality, (pos.0, pos.1)));
+        if let Some(gear) = Gear::for_item(recipe.output) {
+            product.insert(gear);
+        }
+        if let Some(material) = material {
+            product.insert((
+                material,
+                Name(format!(
+                    "{} {} {:?}",
+                    quality.label(),
+                    material.item_name(),
+                    recipe.output
+                )),
+            ));
+        }
+    }
+}
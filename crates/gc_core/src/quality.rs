@@ -0,0 +1,225 @@
+//! Item quality from crafter skill
+//!
+//! Crafted items carry a [`Quality`] rolled from the crafter's
+//! [`CraftingSkill`] on the `job_rng` stream, so the same seed and job order
+//! always produce the same goods. Quality scales trade value through
+//! [`item_value`] and will feed combat stats for weapons and armor.
+//!
+//! Stone and blocks are also worth more or less by the rock they are made
+//! of ([`material_item_value`]), which carries into the value of a room
+//! ([`room_value`]).
+
+use crate::components::{Carriable, Item, ItemType, ZoneBounds};
+use crate::construction::ConstructionKind;
+use crate::determinism::RngStream;
+use crate::equipment::Gear;
+use crate::systems::DeterministicRng;
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind};
+use bevy_ecs::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Highest meaningful crafting skill level
+pub const MAX_SKILL: u8 = 20;
+
+/// Crafting proficiency of a worker (0 = novice, [`MAX_SKILL`] = legendary)
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CraftingSkill(pub u8);
+
+/// Workmanship of a crafted item
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Quality {
+    Poor,
+    #[default]
+    Standard,
+    Fine,
+    Superior,
+    Masterwork,
+}
+
+impl Quality {
+    /// All qualities from worst to best
+    pub const ALL: [Quality; 5] = [
+        Quality::Poor,
+        Quality::Standard,
+        Quality::Fine,
+        Quality::Superior,
+        Quality::Masterwork,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Quality::Poor => "poor",
+            Quality::Standard => "standard",
+            Quality::Fine => "fine",
+            Quality::Superior => "superior",
+            Quality::Masterwork => "masterwork",
+        }
+    }
+
+    /// Trade value multiplier in percent
+    pub fn value_percent(self) -> u32 {
+        match self {
+            Quality::Poor => 50,
+            Quality::Standard => 100,
+            Quality::Fine => 150,
+            Quality::Superior => 200,
+            Quality::Masterwork => 400,
+        }
+    }
+
+    /// Flat bonus to attack/defense when used as a weapon or armor
+    pub fn combat_bonus(self) -> i32 {
+        match self {
+            Quality::Poor => -1,
+            Quality::Standard => 0,
+            Quality::Fine => 1,
+            Quality::Superior => 2,
+            Quality::Masterwork => 3,
+        }
+    }
+}
+
+/// Base trade value of an item type before quality
+pub fn base_value(item_type: ItemType) -> u32 {
+    match item_type {
+        ItemType::Stone => 2,
+        ItemType::Weapon => 10,
+        ItemType::Armor => 12,
+        ItemType::Gem => 25,
+        ItemType::Ore => 3,
+        ItemType::Coal => 2,
+        ItemType::Wood => 1,
+        ItemType::Bar => 8,
+        ItemType::Egg => 1,
+        ItemType::Wool => 4,
+        ItemType::Block => 5,
+    }
+}
+
+/// Trade value of an item, scaled by quality when it has one
+pub fn item_value(item_type: ItemType, quality: Option<Quality>) -> u32 {
+    base_value(item_type) * quality.unwrap_or_default().value_percent() / 100
+}
+
+/// Trade value of an item of `material`, scaled by quality when it has one
+///
+/// Material only changes what stone and blocks are worth (see
+/// [`Material::value_percent`]); a gem is worth the same whatever rock it
+/// came out of.
+pub fn material_item_value(
+    item_type: ItemType,
+    material: Option<Material>,
+    quality: Option<Quality>,
+) -> u32 {
+    let material = match (item_type, material) {
+        (ItemType::Stone | ItemType::Block, Some(m)) => m.value_percent(),
+        _ => 100,
+    };
+    base_value(item_type) * quality.unwrap_or_default().value_percent() * material / 10_000
+}
+
+/// Value of a room: the loose items inside `bounds` plus the stone in its
+/// walls
+///
+/// Every wall tile on or inside the bounds counts as the stone a built
+/// [`ConstructionKind::Wall`] takes, valued in the tile's [`Material`], so
+/// a hall cut from granite is worth more than one dug out of clay. Walls
+/// without a material count as plain stone.
+pub fn room_value(world: &mut World, bounds: &ZoneBounds) -> u32 {
+    let (stone, per_wall) = ConstructionKind::Wall.requirement();
+    let mut walls = 0;
+    if let Some(map) = world.get_resource::<GameMap>() {
+        let materials = world.get_resource::<MaterialMap>();
+        for y in bounds.min_y..=bounds.max_y {
+            for x in bounds.min_x..=bounds.max_x {
+                if map.get_tile(x, y) == Some(TileKind::Wall) {
+                    let material = materials.and_then(|m| m.get(x, y));
+                    walls += per_wall * material_item_value(stone, material, None);
+                }
+            }
+        }
+    }
+    let mut items = world.query::<(&Item, &Position, Option<&Material>, Option<&Quality>)>();
+    let loose: u32 = items
+        .iter(world)
+        .filter(|(_, p, ..)| bounds.contains(p.0, p.1))
+        .map(|(item, _, material, quality)| {
+            material_item_value(item.item_type, material.copied(), quality.copied())
+        })
+        .sum();
+    walls + loose
+}
+
+/// Roll a quality for an item made by a crafter of `skill`
+///
+/// A d100 roll is shifted by 4 points per skill level; higher skill makes
+/// poor results rare and masterworks possible.
+pub fn roll_quality(skill: CraftingSkill, rng: &mut StdRng) -> Quality {
+    let skill = skill.0.min(MAX_SKILL) as i32;
+    let score = rng.gen_range(0..100) + skill * 4;
+    match score {
+        s if s < 20 => Quality::Poor,
+        s if s < 70 => Quality::Standard,
+        s if s < 110 => Quality::Fine,
+        s if s < 150 => Quality::Superior,
+        _ => Quality::Masterwork,
+    }
+}
+
+/// Components of a crafted item of `quality` lying at `pos`
+///
+/// Wearable types also need their [`Gear`].
+pub fn crafted_item(
+    item_type: ItemType,
+    quality: Quality,
+    pos: (i32, i32),
+) -> (Item, Quality, Position, Carriable, Name) {
+    (
+        Item { item_type },
+        quality,
+        Position(pos.0, pos.1),
+        Carriable,
+        Name(format!("{} {:?}", quality.label(), item_type)),
+    )
+}
+
+/// Spawn a crafted item at `pos` with quality rolled from `crafter`'s skill
+///
+/// Entry point for production systems; crafters without a [`CraftingSkill`]
+/// count as novices.
+pub fn craft_item(
+    world: &mut World,
+    crafter: Entity,
+    item_type: ItemType,
+    pos: (i32, i32),
+) -> Entity {
+    let skill = world
+        .get::<CraftingSkill>(crafter)
+        .copied()
+        .unwrap_or_default();
+    let quality = {
+        let mut rng = world.resource_mut::<DeterministicRng>();
+        roll_quality(skill, rng.stream(RngStream::Job, "craft_item"))
+    };
+    let mut item = world.spawn(crafted_item(item_type, quality, pos));
+    if let Some(gear) = Gear::for_item(item_type) {
+        item.insert(gear);
+    }
+    item.id()
+}
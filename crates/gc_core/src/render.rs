@@ -0,0 +1,207 @@
+//! Render extraction: what to draw on each tile, in draw order
+//!
+//! Renderers used to pick glyphs with their own ad hoc checks (agent over
+//! item over tile). [`extract_draw_list`] now turns a [`WorldView`] into a
+//! [`DrawList`]: per tile, every glyph that wants to be drawn there, sorted
+//! bottom to top by [`RenderLayer`]. Renderers draw the topmost glyph of
+//! the layers they show, so the TUI, the ASCII snapshot and the screenshot
+//! export all agree on what wins.
+//!
+//! Entities carry an explicit [`RenderGlyph`] component when they want a
+//! particular look; otherwise [`entity_glyph`] derives one from their roles
+//! (miners, carriers, items, mining designations, zones). Within a layer,
+//! later entities draw over earlier ones.
+
+use crate::view::{EntityView, WorldView};
+use crate::world::TileKind;
+use crate::zones::ZoneKind;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Draw layers, bottom to top
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderLayer {
+    Terrain,
+    Zone,
+    Designation,
+    Fire,
+    Item,
+    Unit,
+}
+
+impl RenderLayer {
+    pub const ALL: [RenderLayer; 6] = [
+        RenderLayer::Terrain,
+        RenderLayer::Zone,
+        RenderLayer::Designation,
+        RenderLayer::Fire,
+        RenderLayer::Item,
+        RenderLayer::Unit,
+    ];
+}
+
+/// Palette slot of a glyph; renderers map it to a colour through their
+/// theme, so colour-blind palettes keep working
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlyphColor {
+    Floor,
+    Wall,
+    Water,
+    Lava,
+    Unit,
+    Designation,
+    Stockpile,
+    Zone,
+}
+
+/// How an entity (or tile) is drawn
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderGlyph {
+    pub char: char,
+    pub layer: RenderLayer,
+    pub color: GlyphColor,
+}
+
+impl RenderGlyph {
+    pub const fn new(char: char, layer: RenderLayer, color: GlyphColor) -> Self {
+        Self { char, layer, color }
+    }
+}
+
+/// Glyph of a map tile
+pub fn tile_glyph(tile: TileKind) -> RenderGlyph {
+    let (char, color) = match tile {
+        TileKind::Floor => ('.', GlyphColor::Floor),
+        TileKind::Wall => ('#', GlyphColor::Wall),
+        TileKind::Water => ('~', GlyphColor::Water),
+        TileKind::Lava => ('^', GlyphColor::Lava),
+    };
+    RenderGlyph::new(char, RenderLayer::Terrain, color)
+}
+
+/// Glyph of a zone's tiles
+pub fn zone_glyph(kind: ZoneKind) -> RenderGlyph {
+    let color = if kind == ZoneKind::Stockpile {
+        GlyphColor::Stockpile
+    } else {
+        GlyphColor::Zone
+    };
+    RenderGlyph::new(kind.glyph(), RenderLayer::Zone, color)
+}
+
+/// Glyph of a burning tile
+pub const FIRE_GLYPH: RenderGlyph = RenderGlyph::new('&', RenderLayer::Fire, GlyphColor::Lava);
+
+/// Glyph drawn at an entity's position: its [`RenderGlyph`] component, or
+/// one derived from its roles; `None` for entities that are not drawn
+pub fn entity_glyph(e: &EntityView) -> Option<RenderGlyph> {
+    if e.glyph.is_some() {
+        return e.glyph;
+    }
+    let is = |role: &str| e.roles.iter().any(|r| r == role);
+    if is("miner") {
+        Some(RenderGlyph::new('m', RenderLayer::Unit, GlyphColor::Unit))
+    } else if is("carrier") {
+        Some(RenderGlyph::new('c', RenderLayer::Unit, GlyphColor::Unit))
+    } else if e.item_type.is_some() {
+        Some(RenderGlyph::new('o', RenderLayer::Item, GlyphColor::Unit))
+    } else if is("mine_designation") {
+        Some(RenderGlyph::new(
+            'x',
+            RenderLayer::Designation,
+            GlyphColor::Designation,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Everything to draw on each tile of a view, bottom to top
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrawList {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major; each tile starts with its terrain glyph
+    tiles: Vec<Vec<RenderGlyph>>,
+}
+
+impl DrawList {
+    /// Glyphs on the tile, bottom to top; empty off the map
+    pub fn at(&self, x: i32, y: i32) -> &[RenderGlyph] {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return &[];
+        }
+        &self.tiles[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    /// Topmost glyph on the tile among the layers `show` accepts
+    pub fn top(&self, x: i32, y: i32, show: impl Fn(RenderLayer) -> bool) -> Option<RenderGlyph> {
+        self.at(x, y).iter().rev().find(|g| show(g.layer)).copied()
+    }
+
+    /// The map as text, one line per row, drawing the topmost glyph of
+    /// every tile
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::with_capacity(((self.width + 1) * self.height) as usize);
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                out.push(self.top(x, y, |_| true).map_or(' ', |g| g.char));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Sort everything drawn on the view's tiles into a [`DrawList`]
+///
+/// Concealed tiles show only rock, hiding whatever is inside.
+pub fn extract_draw_list(view: &WorldView) -> DrawList {
+    let (w, h) = (view.width as i32, view.height as i32);
+    let mut tiles: Vec<Vec<RenderGlyph>> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| vec![tile_glyph(view.tile(x, y).unwrap_or(TileKind::Wall))])
+        .collect();
+    let mut push = |x: i32, y: i32, glyph: RenderGlyph| {
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            tiles[(y * w + x) as usize].push(glyph);
+        }
+    };
+    for &(x, y) in &view.burning {
+        push(x, y, FIRE_GLYPH);
+    }
+    for e in &view.entities {
+        let kind = e.zone_kind.or_else(|| {
+            e.roles
+                .iter()
+                .any(|r| r == "stockpile")
+                .then_some(ZoneKind::Stockpile)
+        });
+        if let (Some((x0, y0, x1, y1)), Some(kind)) = (e.zone, kind) {
+            for y in y0.max(0)..=y1.min(h - 1) {
+                for x in x0.max(0)..=x1.min(w - 1) {
+                    push(x, y, zone_glyph(kind));
+                }
+            }
+        }
+        if let (Some((x, y)), Some(glyph)) = (e.pos, entity_glyph(e)) {
+            push(x, y, glyph);
+        }
+    }
+    for &(x, y) in &view.concealed {
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            tiles[(y * w + x) as usize] = vec![tile_glyph(TileKind::Wall)];
+        }
+    }
+    for glyphs in &mut tiles {
+        // Stable: later entities stay above earlier ones on the same layer
+        glyphs.sort_by_key(|g| g.layer);
+    }
+    DrawList {
+        width: view.width,
+        height: view.height,
+        tiles,
+    }
+}
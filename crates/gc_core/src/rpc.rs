@@ -0,0 +1,218 @@
+//! Transport-agnostic JSON-RPC 2.0 protocol for remote control
+//!
+//! External clients (bots, web viewers) drive the engine with JSON-RPC
+//! requests. This module only parses requests and decides what to do with
+//! them; a server (see `gc_cli serve`) owns the socket and the
+//! [`SimHandle`](crate::sim_thread::SimHandle). State-changing methods become
+//! [`SimCommand`]s, so they are applied at tick boundaries like any other
+//! shell input. Queries are answered from the latest [`WorldView`].
+//!
+//! Methods:
+//!
+//! | method        | params            | result                       |
+//! |---------------|-------------------|------------------------------|
+//! | `step`        | `{ticks?}`        | `{queued}`                   |
+//! | `pause`       |                   | `{paused: true}`             |
+//! | `resume`      |                   | `{paused: false}`            |
+//! | `designate`   | `{x, y}`          | `{queued: true}`             |
+//! | `get_tile`    | `{x, y}`          | tile kind or null            |
+//! | `get_entities`| `{x?, y?}`        | entity views                 |
+//! | `get_view`    |                   | full [`WorldView`]           |
+//! | `get_announcements` | `{min_severity?}` | recent announcements   |
+//! | `subscribe`   |                   | `{subscribed: true}`         |
+//! | `unsubscribe` |                   | `{subscribed: false}`        |
+//!
+//! Subscribed clients receive a `tick` notification for every published
+//! snapshot (see [`tick_notification`]).
+
+use crate::announcements::Severity;
+use crate::command::WorldCommand;
+use crate::sim_thread::SimCommand;
+use crate::view::WorldView;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// JSON-RPC error codes from the specification
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// An incoming JSON-RPC request (or notification when `id` is absent)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Outgoing JSON-RPC response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    pub fn ok(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn err(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// What a server must do to fulfil a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcAction {
+    /// Reply immediately with this result
+    Reply(Value),
+    /// Forward the command to the simulation, then reply with the result
+    Command(SimCommand, Value),
+    /// Change this client's subscription to tick notifications
+    Subscribe(bool),
+}
+
+/// Parse one request line
+pub fn parse_request(line: &str) -> Result<RpcRequest, RpcError> {
+    let value: Value =
+        serde_json::from_str(line).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))?;
+    serde_json::from_value(value).map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))
+}
+
+#[derive(Deserialize)]
+struct XyParams {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize, Default)]
+struct StepParams {
+    #[serde(default)]
+    ticks: Option<u32>,
+}
+
+#[derive(Deserialize, Default)]
+struct EntityParams {
+    x: Option<i32>,
+    y: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+struct AnnouncementParams {
+    min_severity: Option<Severity>,
+}
+
+fn params<T: for<'de> Deserialize<'de> + Default>(value: &Value) -> Result<T, RpcError> {
+    if value.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(value.clone()).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn xy(value: &Value) -> Result<XyParams, RpcError> {
+    serde_json::from_value(value.clone()).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Decide how to handle `request` given the latest snapshot
+pub fn dispatch(view: &WorldView, request: &RpcRequest) -> Result<RpcAction, RpcError> {
+    let p = &request.params;
+    let action = match request.method.as_str() {
+        "step" => {
+            let ticks = params::<StepParams>(p)?.ticks.unwrap_or(1);
+            RpcAction::Command(SimCommand::Step(ticks), json!({ "queued": ticks }))
+        }
+        "pause" => RpcAction::Command(SimCommand::Pause, json!({ "paused": true })),
+        "resume" => RpcAction::Command(SimCommand::Resume, json!({ "paused": false })),
+        "designate" => {
+            let XyParams { x, y } = xy(p)?;
+            if view.tile(x, y).is_none() {
+                return Err(RpcError::new(INVALID_PARAMS, "designation out of bounds"));
+            }
+            RpcAction::Command(
+                SimCommand::World(WorldCommand::Designate { x, y }),
+                json!({ "queued": true }),
+            )
+        }
+        "get_tile" => {
+            let XyParams { x, y } = xy(p)?;
+            RpcAction::Reply(json!(view.tile(x, y)))
+        }
+        "get_entities" => {
+            let EntityParams { x, y } = params(p)?;
+            let entities: Vec<_> = match (x, y) {
+                (Some(x), Some(y)) => view.entities_at(x, y).collect(),
+                (None, None) => view.entities.iter().collect(),
+                _ => {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        "give both x and y or neither",
+                    ))
+                }
+            };
+            RpcAction::Reply(json!(entities))
+        }
+        "get_view" => RpcAction::Reply(json!(view)),
+        "get_announcements" => {
+            let AnnouncementParams { min_severity } = params(p)?;
+            let min = min_severity.unwrap_or(Severity::Info);
+            let recent: Vec<_> = view
+                .announcements
+                .iter()
+                .filter(|a| a.severity >= min)
+                .collect();
+            RpcAction::Reply(json!(recent))
+        }
+        "subscribe" => RpcAction::Subscribe(true),
+        "unsubscribe" => RpcAction::Subscribe(false),
+        other => {
+            return Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", other),
+            ))
+        }
+    };
+    Ok(action)
+}
+
+/// Notification pushed to subscribers for each published snapshot
+pub fn tick_notification(view: &WorldView) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "tick",
+        "params": { "tick": view.tick, "metrics": view.metrics },
+    })
+}
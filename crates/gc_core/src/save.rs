@@ -1,13 +1,44 @@
-use crate::components::{Carriable, Item, ItemType};
+use crate::burial::Grave;
+use crate::caverns::Caverns;
+use crate::command::CommandLog;
+use crate::components::{
+    Animal, AssignedJob, Carriable, CarriedBy, Carrier, DesignationLifecycle, DesignationState,
+    Faction, FactionKind, Inventory, Item, ItemType, Miner, MiningSkill, Stockpile, VisionRadius,
+    ZoneBounds,
+};
+use crate::construction::{Construction, ConstructionKind, MaterialRequirement, Workshop};
+use crate::designations::{DesignationPriority, HazardConfirmed, MineDesignation};
+use crate::director::EventDirector;
+use crate::equipment::Gear;
+use crate::fire::FireMap;
+use crate::fov::{self, Explored};
+use crate::jobs::{ActiveJobs, IssuedBy, Job, JobBoard, JobId, JobKind};
+use crate::lighting::LightSource;
+use crate::livestock::{Livestock, OwnedBy, Species};
+use crate::manager::ManagerOrders;
+use crate::mechanisms::{Bridge, Door, Lever, MechanismLinks, PressurePlate, Trap};
+use crate::production::{Station, StationKind, Supply};
+use crate::quality::Quality;
+use crate::render::RenderGlyph;
+use crate::stockpiles::{FillLimit, GiveTo};
 use crate::systems;
-use crate::world::{GameMap, Name, Position, TileKind, Velocity};
+use crate::tags::{AcceptsTags, TagQuery};
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind, Velocity};
+use crate::yields::YieldTable;
+use crate::zones::ZoneKind;
 use bevy_ecs::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 // Cursor is only used inside decode_cbor
 
 /// Sort entity records in a stable, deterministic order.
 ///
-/// Ordering key: (name, pos, vel, item_type, carriable)
+/// Ordering key: (name, pos, vel, item_type, carriable, quality, miner, carrier, zone,
+/// material, mining_skill, stockpile_priority, vision_radius, zone_kind, faction,
+/// accepts_tags, stockpile_accepts, fill_limit, give_to, light, glyph, mine_designation,
+/// hazard_confirmed, designation_priority, issued_by, assigned_job)
 fn sort_entities_deterministically(entities: &mut [EntityData]) {
     use std::cmp::Ordering;
     entities.sort_by(|a, b| {
@@ -27,16 +58,63 @@ fn sort_entities_deterministically(entities: &mut [EntityData]) {
         if item_ord != Ordering::Equal {
             return item_ord;
         }
-        a.carriable.cmp(&b.carriable)
+        let carriable_ord = a.carriable.cmp(&b.carriable);
+        if carriable_ord != Ordering::Equal {
+            return carriable_ord;
+        }
+        a.quality
+            .cmp(&b.quality)
+            .then(a.miner.cmp(&b.miner))
+            .then(a.carrier.cmp(&b.carrier))
+            .then(a.zone.cmp(&b.zone))
+            .then(a.material.cmp(&b.material))
+            .then(a.mining_skill.cmp(&b.mining_skill))
+            .then(a.stockpile_priority.cmp(&b.stockpile_priority))
+            .then(a.vision_radius.cmp(&b.vision_radius))
+            .then(a.zone_kind.cmp(&b.zone_kind))
+            .then(a.faction.cmp(&b.faction))
+            .then(a.species.cmp(&b.species))
+            .then_with(|| {
+                let tags = |e: &EntityData| e.accepts_tags.as_ref().map(|q| q.to_string());
+                tags(a).cmp(&tags(b))
+            })
+            .then_with(|| a.stockpile_accepts.cmp(&b.stockpile_accepts))
+            .then_with(|| {
+                let fill = |e: &EntityData| e.fill_limit.map(|f| (f.max_fill_percent, f.overflow));
+                fill(a).cmp(&fill(b))
+            })
+            .then_with(|| a.give_to.cmp(&b.give_to))
+            .then_with(|| {
+                let radius = |e: &EntityData| e.light.map(|l| l.radius);
+                radius(a).cmp(&radius(b))
+            })
+            .then_with(|| {
+                let glyph = |e: &EntityData| e.glyph.map(|g| (g.layer, g.char));
+                glyph(a).cmp(&glyph(b))
+            })
+            .then(a.mine_designation.cmp(&b.mine_designation))
+            .then(a.hazard_confirmed.cmp(&b.hazard_confirmed))
+            .then(a.designation_priority.cmp(&b.designation_priority))
+            .then(a.issued_by.cmp(&b.issued_by))
+            .then_with(|| {
+                let job = |e: &EntityData| e.assigned_job.map(|j| j.0);
+                job(a).cmp(&job(b))
+            })
     });
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveGame {
     pub width: u32,
     pub height: u32,
     pub tiles: Vec<TileKind>,
+    /// Per-tile materials parallel to `tiles` (empty when the world has none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materials: Vec<Material>,
     pub entities: Vec<EntityData>,
+    /// Every tile ever seen, sorted; visibility itself is recomputed on load
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explored: Vec<(i32, i32)>,
     // Determinism: persist tick timing and RNG seed
     // Note: RNG stream positions not yet persisted - reloading resets RNG to initial state
     // TODO: Serialize per-stream RNG state for full determinism across save/load
@@ -46,19 +124,308 @@ pub struct SaveGame {
     pub ticks: u64,
     #[serde(default)]
     pub master_seed: u64,
+    /// [`YieldTable::hash`] of the yields the world was played with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yield_table_hash: Option<u64>,
+    /// The yields themselves, restored on load; older saves have only the hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yield_table: Option<YieldTable>,
+    /// Burning, scorched and wet tiles
+    #[serde(default, skip_serializing_if = "FireMap::is_empty")]
+    pub fire: FireMap,
+    /// Standing orders and whether each is refilling
+    #[serde(default, skip_serializing_if = "ManagerOrders::is_empty")]
+    pub manager: ManagerOrders,
+    /// Hidden caverns, concealed or already revealed
+    #[serde(default, skip_serializing_if = "Caverns::is_empty")]
+    pub caverns: Caverns,
+    /// Last assessment of the fort and when the next raid comes
+    #[serde(default, skip_serializing_if = "EventDirector::is_default")]
+    pub director: EventDirector,
+    /// Commands applied to the world so far, for replaying it from its seed
+    #[serde(default, skip_serializing_if = "CommandLog::is_empty")]
+    pub commands: CommandLog,
+    /// The job board in queue order: the next job taken is the last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jobs: Vec<Job>,
+    /// Jobs held by workers, by [`Job::seq`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub active_jobs: Vec<Job>,
+    /// [`Job::seq`] of the next posted job
+    #[serde(default)]
+    pub next_job_seq: u64,
+    /// [`SaveGame::content_hash`] when the save was written, checked by
+    /// [`load_world_checked`]; older saves have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world_hash: Option<u64>,
+}
+
+impl SaveGame {
+    /// Whether this save was made under `table`'s yields
+    ///
+    /// Saves from before yield tables, or from worlds without one, are
+    /// compatible with any table.
+    pub fn yields_compatible(&self, table: &YieldTable) -> bool {
+        self.yield_table_hash.map_or(true, |h| h == table.hash())
+    }
+
+    /// Stable hash of everything in the save except [`world_hash`](Self::world_hash)
+    ///
+    /// Entities are already in canonical order, so two saves of the same
+    /// world hash alike whatever codec carried them.
+    pub fn content_hash(&self) -> u64 {
+        let bytes = if self.world_hash.is_some() {
+            let unhashed = SaveGame {
+                world_hash: None,
+                ..self.clone()
+            };
+            serde_json::to_vec(&unhashed)
+        } else {
+            serde_json::to_vec(self)
+        };
+        crate::lockstep::fnv1a64(&bytes.expect("save serializes"))
+    }
+
+    /// Upgrade records written by older versions to the current shape
+    ///
+    /// Stockpiles saved as a whole component (including the old
+    /// `{ "accepts_any": bool }` form) become zone records with
+    /// `stockpile_accepts` and `stockpile_priority`; one saved without
+    /// bounds covers just its own tile. [`load_world`] runs this itself;
+    /// running it twice changes nothing.
+    pub fn migrate(&mut self) {
+        for e in &mut self.entities {
+            let Some(stockpile) = e.stockpile.take() else {
+                continue;
+            };
+            if e.zone.is_none() {
+                e.zone = e.pos.map(|(x, y)| (x, y, x, y));
+            }
+            e.zone_kind = None;
+            e.stockpile_accepts = e.stockpile_accepts.take().or(stockpile.accepts);
+            e.stockpile_priority = e
+                .stockpile_priority
+                .or(Some(stockpile.priority).filter(|&p| p != 0));
+        }
+    }
+
+    /// Record the content hash in [`world_hash`](Self::world_hash)
+    pub(crate) fn hashed(mut self) -> Self {
+        self.world_hash = Some(self.content_hash());
+        self
+    }
+
+    /// Check the contents against the recorded hash
+    ///
+    /// Saves without a hash pass.
+    pub fn verify(&self) -> Result<(), IntegrityError> {
+        match self.world_hash {
+            Some(recorded) if recorded != self.content_hash() => Err(IntegrityError::Corrupted {
+                recorded,
+                actual: self.content_hash(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A save that failed its integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum IntegrityError {
+    #[error("save contents hash to {actual:016x} but {recorded:016x} was recorded: the file is corrupted or was edited")]
+    Corrupted { recorded: u64, actual: u64 },
+    #[error("the loaded world hashes to {actual:016x} but {recorded:016x} was recorded: the save did not load back faithfully")]
+    NotReconstructed { recorded: u64, actual: u64 },
+    #[error("the saved yield table hashes to {actual:016x} but the world was played with {recorded:016x}")]
+    YieldsMismatch { recorded: u64, actual: u64 },
 }
 
 fn default_tick_ms() -> u64 {
     100
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityData {
     pub name: Option<String>,
     pub pos: Option<(i32, i32)>,
     pub vel: Option<(i32, i32)>,
     pub item_type: Option<ItemType>,
     pub carriable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<Quality>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub miner: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub carrier: bool,
+    /// Stockpile bounds as (min_x, min_y, max_x, max_y)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<(i32, i32, i32, i32)>,
+    /// Stockpile haul priority, when not the default 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stockpile_priority: Option<u8>,
+    /// Stockpile tag filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepts_tags: Option<TagQuery>,
+    /// Item types a stockpile without a tag filter accepts; absent accepts all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stockpile_accepts: Option<Vec<ItemType>>,
+    /// The whole component, as older saves wrote it; folded into the fields
+    /// above by [`SaveGame::migrate`] and never written
+    #[serde(default, skip_serializing)]
+    pub stockpile: Option<Stockpile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_limit: Option<FillLimit>,
+    /// Bounds of the stockpiles taking this one's overflow, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub give_to: Vec<(i32, i32, i32, i32)>,
+    /// Kind of the zone at `zone`, when not a stockpile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_kind: Option<ZoneKind>,
+    /// Which side the entity is on, and so which jobs it takes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faction: Option<FactionKind>,
+    /// Material of mined stone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<Material>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mining_skill: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision_radius: Option<i32>,
+    /// Construction site state; its jobs are posted again after loading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub construction: Option<ConstructionData>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub workshop: bool,
+    /// Production station state; supply and craft jobs are posted again
+    /// after loading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station: Option<StationData>,
+    /// Species of an animal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species: Option<Species>,
+    /// Production timers of a tame animal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub livestock: Option<LivestockData>,
+    /// Name of a tame animal's owner; owners without a name are not kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// A finished grave; the dead are not saved, so graves load empty
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub grave: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lever: Option<LeverData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plate: Option<PressurePlate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub door: Option<Door>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridge: Option<Bridge>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trap: bool,
+    /// Positions of the doors, bridges and traps a lever or plate operates, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<(i32, i32)>,
+    /// A torch's or brazier's light
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light: Option<LightSource>,
+    /// Explicit render glyph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<RenderGlyph>,
+    /// A mining designation still waiting to be dug; it loads active and
+    /// its job is posted again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mine_designation: bool,
+    /// The designation was confirmed despite bordering water or lava
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hazard_confirmed: bool,
+    /// The designation's [`DesignationPriority`], if set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub designation_priority: Option<u32>,
+    /// Who placed the designation; designations without one are the
+    /// system's, which anyone may cancel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_by: Option<IssuedBy>,
+    /// Job the worker holds, one of [`SaveGame::active_jobs`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_job: Option<JobId>,
+}
+
+/// Saved [`Construction`] and [`MaterialRequirement`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstructionData {
+    pub kind: ConstructionKind,
+    pub delivered: u32,
+    /// Delivered units that carry a material, by material
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<Material, u32>,
+    pub progress: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_job: Option<JobId>,
+    /// Haul jobs bringing materials
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incoming: Vec<JobId>,
+}
+
+/// Saved [`Station`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StationData {
+    pub kind: StationKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orders: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stock: BTreeMap<ItemType, u32>,
+    /// Stocked items that carry a material, by material
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<Material, u32>,
+    #[serde(default)]
+    pub crafts: u32,
+    #[serde(default)]
+    pub fuel_used: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub craft_job: Option<JobId>,
+    /// Haul jobs bringing inputs and fuel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incoming: Vec<(JobId, Supply)>,
+}
+
+/// Saved [`Livestock`]; a pending shear job without a saved one is posted
+/// again after loading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LivestockData {
+    #[serde(default)]
+    pub timer: u32,
+    #[serde(default)]
+    pub wool_ready: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shear_job: Option<JobId>,
+}
+
+/// Saved [`Lever`]; an ordered pull without a saved job is posted again
+/// after loading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeverData {
+    #[serde(default)]
+    pub on: bool,
+    #[serde(default)]
+    pub pull_requested: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_job: Option<JobId>,
+}
+
+/// Whether a job of this kind is saved
+///
+/// Rests, gear changes and burials depend on health, equipment and corpses,
+/// which saves do not keep; extinguish jobs are tracked by the fire map.
+/// Their holders load idle and the jobs are posted again as needed.
+fn job_is_saved(job: &Job) -> bool {
+    !matches!(
+        job.kind,
+        JobKind::Rest { .. }
+            | JobKind::Equip { .. }
+            | JobKind::Unequip { .. }
+            | JobKind::Bury { .. }
+            | JobKind::Extinguish { .. }
+    )
 }
 
 pub fn save_world(world: &mut World) -> SaveGame {
@@ -67,6 +434,10 @@ pub fn save_world(world: &mut World) -> SaveGame {
         let map = world.resource::<GameMap>();
         (map.width, map.height, map.tiles.clone())
     };
+    let materials = world
+        .get_resource::<MaterialMap>()
+        .map(|m| m.layers.clone())
+        .unwrap_or_default();
 
     let mut entities = Vec::new();
     let mut q = world.query::<(
@@ -75,16 +446,213 @@ pub fn save_world(world: &mut World) -> SaveGame {
         Option<&Velocity>,
         Option<&Item>,
         Option<&Carriable>,
+        Option<&Quality>,
+        Has<Miner>,
+        Has<Carrier>,
+        (
+            Option<&ZoneBounds>,
+            Option<&Stockpile>,
+            Option<&ZoneKind>,
+            Option<&AcceptsTags>,
+            Option<&FillLimit>,
+            Option<&GiveTo>,
+        ),
+        Option<&Material>,
+        Option<&MiningSkill>,
+        Option<&CarriedBy>,
+        Option<&VisionRadius>,
+        (
+            Option<&Faction>,
+            Option<&Construction>,
+            Option<&MaterialRequirement>,
+            Has<Workshop>,
+            Option<&Station>,
+            Option<&LightSource>,
+            Option<&RenderGlyph>,
+            Has<MineDesignation>,
+            Option<&DesignationLifecycle>,
+            Has<HazardConfirmed>,
+            Option<&DesignationPriority>,
+            Option<&IssuedBy>,
+        ),
+        (
+            Option<&Species>,
+            Option<&Livestock>,
+            Option<&OwnedBy>,
+            Has<Grave>,
+            Option<&AssignedJob>,
+            (
+                Option<&Lever>,
+                Option<&PressurePlate>,
+                Option<&Door>,
+                Option<&Bridge>,
+                Has<Trap>,
+                Option<&MechanismLinks>,
+            ),
+        ),
     )>();
-    for (name, pos, vel, item, carriable) in q.iter(world) {
+    for (
+        name,
+        pos,
+        vel,
+        item,
+        carriable,
+        quality,
+        miner,
+        carrier,
+        (zone, stockpile, zone_kind, accepts_tags, fill_limit, give_to),
+        material,
+        skill,
+        held,
+        vision,
+        (
+            faction,
+            construction,
+            requirement,
+            workshop,
+            station,
+            light,
+            glyph,
+            designation,
+            lifecycle,
+            confirmed,
+            designation_priority,
+            issued_by,
+        ),
+        (species, livestock, owner, grave, assigned, (lever, plate, door, bridge, trap, links)),
+    ) in q.iter(world)
+    {
+        // Inventories are not saved, so carried items load dropped at the
+        // carrier's feet
+        let pos = pos.or_else(|| held.and_then(|c| world.get::<Position>(c.0)));
+        let mine_designation = designation
+            && !matches!(
+                lifecycle.map(|l| l.0),
+                Some(DesignationState::Ignored | DesignationState::Consumed)
+            );
         entities.push(EntityData {
             name: name.map(|n| n.0.clone()),
             pos: pos.map(|p| (p.0, p.1)),
             vel: vel.map(|v| (v.0, v.1)),
             item_type: item.map(|i| i.item_type),
             carriable: carriable.is_some(),
+            quality: quality.copied(),
+            miner,
+            carrier,
+            zone: zone.map(|z| (z.min_x, z.min_y, z.max_x, z.max_y)),
+            stockpile_priority: stockpile.map(|s| s.priority).filter(|&p| p != 0),
+            accepts_tags: accepts_tags.map(|a| a.0.clone()),
+            // A tag filter decides the list itself once loaded
+            stockpile_accepts: stockpile
+                .filter(|_| accepts_tags.is_none())
+                .and_then(|s| s.accepts.clone()),
+            stockpile: None,
+            fill_limit: fill_limit.copied(),
+            give_to: give_to
+                .into_iter()
+                .flat_map(|g| &g.0)
+                .filter_map(|&t| world.get::<ZoneBounds>(t))
+                .map(|z| (z.min_x, z.min_y, z.max_x, z.max_y))
+                .collect(),
+            vision_radius: vision.map(|v| v.base),
+            zone_kind: zone_kind.copied().filter(|&k| k != ZoneKind::Stockpile),
+            faction: faction.map(|f| f.kind),
+            material: material.copied(),
+            mining_skill: skill.map(|s| s.0),
+            construction: construction.map(|c| ConstructionData {
+                kind: c.kind,
+                delivered: requirement.map_or(0, |r| r.delivered),
+                materials: requirement.map(|r| r.materials.clone()).unwrap_or_default(),
+                progress: c.progress,
+                build_job: c.build_job,
+                incoming: requirement.map(|r| r.incoming.clone()).unwrap_or_default(),
+            }),
+            workshop,
+            station: station.map(|s| StationData {
+                kind: s.kind,
+                orders: s.orders.iter().cloned().collect(),
+                stock: s.stock.clone(),
+                materials: s.materials.clone(),
+                crafts: s.crafts,
+                fuel_used: s.fuel_used,
+                craft_job: s.craft_job,
+                incoming: s.incoming.clone(),
+            }),
+            species: species.copied(),
+            livestock: livestock.map(|l| LivestockData {
+                timer: l.timer,
+                wool_ready: l.wool_ready,
+                shear_job: l.shear_job,
+            }),
+            owner: owner
+                .and_then(|o| world.get::<Name>(o.0))
+                .map(|n| n.0.clone()),
+            grave,
+            lever: lever.map(|l| LeverData {
+                on: l.on,
+                pull_requested: l.pull_requested,
+                pull_job: l.pull_job,
+            }),
+            plate: plate.copied(),
+            door: door.copied(),
+            bridge: bridge.copied(),
+            trap,
+            links: links
+                .into_iter()
+                .flat_map(|l| &l.0)
+                .filter_map(|&t| world.get::<Position>(t))
+                .map(|p| (p.0, p.1))
+                .collect(),
+            light: light.copied(),
+            glyph: glyph.copied(),
+            mine_designation,
+            hazard_confirmed: designation && confirmed,
+            designation_priority: designation_priority.filter(|_| designation).map(|p| p.0),
+            issued_by: issued_by
+                .filter(|_| mine_designation)
+                .copied()
+                .filter(|&by| by != IssuedBy::System),
+            assigned_job: assigned.and_then(|a| a.0),
         });
     }
+    // The board keeps its queue order; active jobs go by posting order
+    let (jobs, next_job_seq) = world
+        .get_resource::<JobBoard>()
+        .map(|b| {
+            (
+                b.0.iter().filter(|j| job_is_saved(j)).cloned().collect(),
+                b.1,
+            )
+        })
+        .unwrap_or_default();
+    let mut active_jobs: Vec<Job> = world
+        .get_resource::<ActiveJobs>()
+        .map(|a| {
+            a.jobs
+                .values()
+                .filter(|j| job_is_saved(j))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    active_jobs.sort_by_key(|j| (j.seq, j.id.0));
+    // A carried item loads at its carrier's feet, so its haul starts there
+    let carried_from: HashMap<JobId, (i32, i32)> = world
+        .query::<(&AssignedJob, &Inventory, &Position)>()
+        .iter(world)
+        .filter(|(_, inventory, _)| inventory.0.is_some())
+        .filter_map(|(assigned, _, p)| assigned.0.map(|id| (id, (p.0, p.1))))
+        .collect();
+    for job in &mut active_jobs {
+        if let (JobKind::Haul { from, .. }, Some(&at)) = (&mut job.kind, carried_from.get(&job.id))
+        {
+            *from = at;
+        }
+    }
+    let held: HashSet<JobId> = active_jobs.iter().map(|j| j.id).collect();
+    for e in &mut entities {
+        e.assigned_job = e.assigned_job.filter(|id| held.contains(id));
+    }
     // Deterministic ordering across codecs and runs
     sort_entities_deterministically(&mut entities);
     // Persist determinism metadata (fallback to defaults if resources are absent)
@@ -97,29 +665,94 @@ pub fn save_world(world: &mut World) -> SaveGame {
         .map(|rng| rng.master_seed)
         .unwrap_or(0);
 
+    let mut explored: Vec<(i32, i32)> = world
+        .get_resource::<Explored>()
+        .map(|e| e.tiles.iter().copied().collect())
+        .unwrap_or_default();
+    explored.sort_unstable();
+
     SaveGame {
         width,
         height,
         tiles,
+        materials,
         entities,
+        explored,
         tick_ms,
         ticks,
         master_seed,
+        yield_table_hash: world.get_resource::<YieldTable>().map(YieldTable::hash),
+        yield_table: world.get_resource::<YieldTable>().cloned(),
+        fire: world.get_resource::<FireMap>().cloned().unwrap_or_default(),
+        manager: world
+            .get_resource::<ManagerOrders>()
+            .cloned()
+            .unwrap_or_default(),
+        caverns: world.get_resource::<Caverns>().cloned().unwrap_or_default(),
+        director: world
+            .get_resource::<EventDirector>()
+            .cloned()
+            .unwrap_or_default(),
+        commands: world
+            .get_resource::<CommandLog>()
+            .cloned()
+            .unwrap_or_default(),
+        jobs,
+        active_jobs,
+        next_job_seq,
+        world_hash: None,
     }
+    .hashed()
 }
 
-pub fn load_world(save: SaveGame, world: &mut World) {
-    world.insert_resource(GameMap {
-        width: save.width,
-        height: save.height,
-        tiles: save.tiles,
-    });
+pub fn load_world(mut save: SaveGame, world: &mut World) {
+    save.migrate();
+    world.insert_resource(GameMap::from_tiles(save.width, save.height, save.tiles));
+    // Older saves have no material layer; a mismatched one is ignored too
+    if save.materials.len() == (save.width * save.height) as usize {
+        world.insert_resource(MaterialMap {
+            width: save.width,
+            height: save.height,
+            layers: save.materials,
+        });
+    } else {
+        world.remove_resource::<MaterialMap>();
+    }
+    match save.yield_table {
+        Some(table) => world.insert_resource(table),
+        None => {
+            world.remove_resource::<YieldTable>();
+        }
+    }
     // Restore deterministic time and RNG seed
     world.insert_resource(systems::Time {
         ticks: save.ticks,
         tick_ms: save.tick_ms,
     });
-    world.insert_resource(systems::DeterministicRng::new(save.master_seed));
+    let mut rng = systems::DeterministicRng::new(save.master_seed);
+    if !save.jobs.is_empty() || !save.active_jobs.is_empty() {
+        // Stream positions are not saved; move the job stream off its start
+        // so new job ids do not repeat those of the restored jobs
+        rng.job_rng = StdRng::seed_from_u64(
+            save.master_seed ^ save.ticks.rotate_left(32) ^ save.next_job_seq,
+        );
+    }
+    world.insert_resource(rng);
+    world.insert_resource(JobBoard(save.jobs, save.next_job_seq));
+    world.insert_resource(ActiveJobs {
+        jobs: save.active_jobs.into_iter().map(|j| (j.id, j)).collect(),
+    });
+    world.insert_resource(save.fire);
+    world.insert_resource(save.manager);
+    world.insert_resource(save.caverns);
+    world.insert_resource(save.director);
+    world.insert_resource(save.commands);
+    // Tame animals whose owner is resolved once everyone is spawned
+    let mut owned = Vec::new();
+    // Triggers whose targets are resolved by position likewise
+    let mut linked = Vec::new();
+    // Stockpiles whose overflow targets are resolved by bounds
+    let mut givers = Vec::new();
     for e in save.entities {
         let mut ec = world.spawn(());
         if let Some(name) = e.name {
@@ -133,10 +766,459 @@ pub fn load_world(save: SaveGame, world: &mut World) {
         }
         if let Some(item_type) = e.item_type {
             ec.insert(Item { item_type });
+            if let Some(gear) = Gear::for_item(item_type) {
+                ec.insert(gear);
+            }
         }
         if e.carriable {
             ec.insert(Carriable);
         }
+        if let Some(quality) = e.quality {
+            ec.insert(quality);
+        }
+        if e.miner || e.carrier || e.assigned_job.is_some() {
+            ec.insert(AssignedJob(e.assigned_job));
+        }
+        if e.miner {
+            ec.insert(Miner);
+        }
+        if let Some(skill) = e.mining_skill {
+            ec.insert(MiningSkill(skill));
+        }
+        if let Some(radius) = e.vision_radius {
+            ec.insert(VisionRadius::new(radius));
+        }
+        if let Some(kind) = e.faction {
+            ec.insert(Faction::new(kind));
+        }
+        if let Some(material) = e.material {
+            ec.insert(material);
+        }
+        if e.carrier {
+            ec.insert((Carrier, Inventory::default()));
+        }
+        if let Some(data) = e.construction {
+            let (item_type, required) = data.kind.requirement();
+            let mut requirement = MaterialRequirement::new(item_type, required);
+            requirement.delivered = data.delivered;
+            requirement.materials = data.materials;
+            requirement.incoming = data.incoming;
+            let mut construction = Construction::new(data.kind);
+            construction.progress = data.progress;
+            construction.build_job = data.build_job;
+            ec.insert((construction, requirement));
+        }
+        if e.workshop {
+            ec.insert(Workshop);
+        }
+        if let Some(data) = e.station {
+            let mut station = Station::new(data.kind);
+            station.orders = data.orders.into();
+            station.stock = data.stock;
+            station.materials = data.materials;
+            station.crafts = data.crafts;
+            station.fuel_used = data.fuel_used;
+            station.craft_job = data.craft_job;
+            station.incoming = data.incoming;
+            ec.insert(station);
+        } else if e.workshop {
+            // Saves from before production stations
+            ec.insert(Station::new(StationKind::Workshop));
+        }
+        if e.grave {
+            ec.insert(Grave::default());
+        }
+        if let Some(data) = e.lever {
+            ec.insert(Lever {
+                on: data.on,
+                pull_requested: data.pull_requested,
+                pull_job: data.pull_job,
+            });
+        }
+        if let Some(plate) = e.plate {
+            ec.insert(plate);
+        }
+        if let Some(door) = e.door {
+            ec.insert(door);
+        }
+        if let Some(bridge) = e.bridge {
+            ec.insert(bridge);
+        }
+        if e.trap {
+            ec.insert(Trap);
+        }
+        if let Some(light) = e.light {
+            ec.insert(light);
+        }
+        if let Some(glyph) = e.glyph {
+            ec.insert(glyph);
+        }
+        if e.mine_designation {
+            ec.insert((MineDesignation, DesignationLifecycle::default()));
+            if e.hazard_confirmed {
+                ec.insert(HazardConfirmed);
+            }
+            if let Some(priority) = e.designation_priority {
+                ec.insert(DesignationPriority(priority));
+            }
+            if let Some(issued_by) = e.issued_by {
+                ec.insert(issued_by);
+            }
+        }
+        if !e.links.is_empty() {
+            linked.push((ec.id(), e.links));
+        }
+        if let Some(species) = e.species {
+            ec.insert((Animal, species));
+        }
+        if let (Some(data), Some(owner)) = (e.livestock, e.owner) {
+            ec.insert(Livestock {
+                timer: data.timer,
+                wool_ready: data.wool_ready,
+                shear_job: data.shear_job,
+            });
+            owned.push((ec.id(), owner));
+        }
+        if let Some((min_x, min_y, max_x, max_y)) = e.zone {
+            let kind = e.zone_kind.unwrap_or_default();
+            ec.insert((kind, ZoneBounds::new(min_x, min_y, max_x, max_y)));
+            if kind == ZoneKind::Stockpile {
+                ec.insert(Stockpile {
+                    accepts: e.stockpile_accepts,
+                    priority: e.stockpile_priority.unwrap_or(0),
+                });
+                // With a tag filter, the filter system fills in `accepts`
+                // on the next tick
+                if let Some(query) = e.accepts_tags {
+                    ec.insert(AcceptsTags(query));
+                }
+                if let Some(limit) = e.fill_limit {
+                    ec.insert(limit);
+                }
+                if !e.give_to.is_empty() {
+                    givers.push((ec.id(), e.give_to));
+                }
+            }
+        }
+    }
+    if !owned.is_empty() {
+        // Entities load in save order, so the first one with a name wins
+        let mut named: Vec<(Entity, String)> = world
+            .query_filtered::<(Entity, &Name), Without<Animal>>()
+            .iter(world)
+            .map(|(e, n)| (e, n.0.clone()))
+            .collect();
+        named.sort();
+        let mut by_name: BTreeMap<String, Entity> = BTreeMap::new();
+        for (entity, name) in named {
+            by_name.entry(name).or_insert(entity);
+        }
+        for (animal, owner) in owned {
+            match by_name.get(&owner) {
+                Some(&owner) => {
+                    world.entity_mut(animal).insert(OwnedBy(owner));
+                }
+                // Without an owner the animal is wild again
+                None => {
+                    world.entity_mut(animal).remove::<Livestock>();
+                }
+            }
+        }
+    }
+    if !linked.is_empty() {
+        let mut targets: Vec<((i32, i32), Entity)> = world
+            .query_filtered::<(Entity, &Position), Or<(With<Door>, With<Bridge>, With<Trap>)>>()
+            .iter(world)
+            .map(|(e, p)| ((p.0, p.1), e))
+            .collect();
+        targets.sort();
+        let mut by_pos: BTreeMap<(i32, i32), Entity> = BTreeMap::new();
+        for (pos, entity) in targets {
+            by_pos.entry(pos).or_insert(entity);
+        }
+        for (trigger, links) in linked {
+            let links = links
+                .iter()
+                .filter_map(|p| by_pos.get(p).copied())
+                .collect();
+            world.entity_mut(trigger).insert(MechanismLinks(links));
+        }
+    }
+    if !givers.is_empty() {
+        let mut stockpiles: Vec<((i32, i32, i32, i32), Entity)> = world
+            .query_filtered::<(Entity, &ZoneBounds), With<Stockpile>>()
+            .iter(world)
+            .map(|(e, z)| ((z.min_x, z.min_y, z.max_x, z.max_y), e))
+            .collect();
+        stockpiles.sort();
+        let mut by_bounds: BTreeMap<(i32, i32, i32, i32), Entity> = BTreeMap::new();
+        for (bounds, entity) in stockpiles {
+            by_bounds.entry(bounds).or_insert(entity);
+        }
+        for (giver, targets) in givers {
+            let targets = targets
+                .iter()
+                .filter_map(|b| by_bounds.get(b).copied())
+                .collect();
+            world.entity_mut(giver).insert(GiveTo(targets));
+        }
+    }
+    world.insert_resource(Explored {
+        tiles: save.explored.into_iter().collect(),
+    });
+    // Entity ids are new, so nothing from before the save can be reused;
+    // recompute now so the first frame after loading is not blank
+    fov::refresh_visibility(world);
+}
+
+/// [`load_world`], then check that the save was intact, that its yield
+/// table is the one the world was played with, and that the world it built
+/// saves back to the recorded hash
+///
+/// Older saves record only the yield hash, which is kept as it is; loading
+/// recomputes visibility, so newly explored tiles are ignored. The world is
+/// loaded even when the check fails, so callers may warn and carry on.
+/// Saves without a hash skip the last check.
+pub fn load_world_checked(save: SaveGame, world: &mut World) -> Result<(), IntegrityError> {
+    let recorded = save.world_hash;
+    let intact = save.verify();
+    let yields = match (&save.yield_table, save.yield_table_hash) {
+        (Some(table), Some(recorded)) if !save.yields_compatible(table) => {
+            Err(IntegrityError::YieldsMismatch {
+                recorded,
+                actual: table.hash(),
+            })
+        }
+        _ => Ok(()),
+    };
+    let (yield_table_hash, explored) = (save.yield_table_hash, save.explored.clone());
+    let has_table = save.yield_table.is_some();
+    load_world(save, world);
+    intact?;
+    yields?;
+    let Some(recorded) = recorded else {
+        return Ok(());
+    };
+    let mut reloaded = save_world(world);
+    // Saves without a table cannot hand one back, and recomputed
+    // visibility may add to what was explored but never forget any of it
+    if !has_table {
+        reloaded.yield_table_hash = yield_table_hash;
+    }
+    if explored
+        .iter()
+        .all(|t| reloaded.explored.binary_search(t).is_ok())
+    {
+        reloaded.explored = explored;
+    }
+    let actual = reloaded.content_hash();
+    if actual == recorded {
+        Ok(())
+    } else {
+        Err(IntegrityError::NotReconstructed { recorded, actual })
+    }
+}
+
+// --- Codecs ---
+
+/// Failure to read, write, encode, or decode a save
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("ron: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("cbor: {0}")]
+    Cbor(String),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot tell the save format of {0}")]
+    UnknownFormat(String),
+}
+
+/// One on-disk save format
+///
+/// Every codec must load into the same world: the round-trip tests compare
+/// world hashes across all of [`CODECS`].
+pub trait SaveCodec: Sync {
+    /// Name used on the command line (`--codec`)
+    fn name(&self) -> &'static str;
+    /// File extension, without the dot
+    fn extension(&self) -> &'static str;
+    fn encode(&self, save: &SaveGame) -> Result<Vec<u8>, CodecError>;
+    fn decode(&self, bytes: &[u8]) -> Result<SaveGame, CodecError>;
+    /// Whether `bytes` look like this format (used when the extension is unknown)
+    fn sniff(&self, bytes: &[u8]) -> bool;
+}
+
+pub struct JsonCodec;
+pub struct RonCodec;
+pub struct CborCodec;
+
+/// All codecs, JSON first as the default
+pub const CODECS: [&dyn SaveCodec; 3] = [&JsonCodec, &RonCodec, &CborCodec];
+
+impl SaveCodec for JsonCodec {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+    fn encode(&self, save: &SaveGame) -> Result<Vec<u8>, CodecError> {
+        Ok(encode_json(save)?.into_bytes())
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<SaveGame, CodecError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        first_non_space(bytes) == Some(b'{')
+    }
+}
+
+impl SaveCodec for RonCodec {
+    fn name(&self) -> &'static str {
+        "ron"
+    }
+    fn extension(&self) -> &'static str {
+        "ron"
+    }
+    fn encode(&self, save: &SaveGame) -> Result<Vec<u8>, CodecError> {
+        Ok(encode_ron(save)?.into_bytes())
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<SaveGame, CodecError> {
+        let text = std::str::from_utf8(bytes).map_err(|e| CodecError::Ron(e.into()))?;
+        Ok(decode_ron(text)?)
+    }
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        first_non_space(bytes) == Some(b'(')
+    }
+}
+
+impl SaveCodec for CborCodec {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+    fn extension(&self) -> &'static str {
+        "cbor"
+    }
+    fn encode(&self, save: &SaveGame) -> Result<Vec<u8>, CodecError> {
+        encode_cbor(save).map_err(|e| CodecError::Cbor(e.to_string()))
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<SaveGame, CodecError> {
+        decode_cbor(bytes).map_err(|e| CodecError::Cbor(e.to_string()))
+    }
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        // A save is a CBOR map: major type 5
+        bytes.first().is_some_and(|b| b >> 5 == 5)
+    }
+}
+
+fn first_non_space(bytes: &[u8]) -> Option<u8> {
+    bytes.iter().copied().find(|b| !b.is_ascii_whitespace())
+}
+
+/// Codec registered under `name` (case-insensitive)
+pub fn codec_by_name(name: &str) -> Option<&'static dyn SaveCodec> {
+    CODECS
+        .into_iter()
+        .find(|c| c.name().eq_ignore_ascii_case(name))
+}
+
+/// Codec for `path`'s extension, if it is one of ours
+pub fn codec_for_path(path: &std::path::Path) -> Option<&'static dyn SaveCodec> {
+    let ext = path.extension()?.to_str()?;
+    CODECS
+        .into_iter()
+        .find(|c| c.extension().eq_ignore_ascii_case(ext))
+}
+
+/// Read a save, choosing the codec by extension or, failing that, by content
+pub fn read_save_file(path: impl AsRef<std::path::Path>) -> Result<SaveGame, CodecError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    let codec = codec_for_path(path)
+        .or_else(|| CODECS.into_iter().find(|c| c.sniff(&bytes)))
+        .ok_or_else(|| CodecError::UnknownFormat(path.display().to_string()))?;
+    codec.decode(&bytes)
+}
+
+/// Write a save in the format named by `path`'s extension
+/// Unknown extensions get JSON. The file is replaced atomically (see
+/// [`write_atomic`]) and no backup is kept.
+pub fn write_save_file(
+    path: impl AsRef<std::path::Path>,
+    save: &SaveGame,
+) -> Result<(), CodecError> {
+    write_save_file_with(path, save, SaveWriteOptions::default())
+}
+
+/// How a save file replaces an existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveWriteOptions {
+    /// Keep the previous file as [`backup_path`] before replacing it
+    pub backup: bool,
+}
+
+/// [`write_save_file`] with explicit [`SaveWriteOptions`]
+pub fn write_save_file_with(
+    path: impl AsRef<std::path::Path>,
+    save: &SaveGame,
+    options: SaveWriteOptions,
+) -> Result<(), CodecError> {
+    let path = path.as_ref();
+    let codec = codec_for_path(path).unwrap_or(CODECS[0]);
+    write_atomic(path, &codec.encode(save)?, options.backup)?;
+    Ok(())
+}
+
+/// Where [`write_atomic`] keeps the previous file: `<path>.bak`
+pub fn backup_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `bytes` so a crash leaves either the old file or the
+/// new one, never a truncated mix
+///
+/// The bytes go to `<path>.tmp` first and are flushed to disk, then renamed
+/// over `path`; the directory is synced so the rename itself survives a
+/// power cut. With `backup` the previous file is first copied to
+/// [`backup_path`]. A failed write removes the temporary file and leaves
+/// `path` untouched.
+pub fn write_atomic(path: &std::path::Path, bytes: &[u8], backup: bool) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+    let written = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        if backup && path.exists() {
+            std::fs::copy(path, backup_path(path))?;
+        }
+        std::fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written?;
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// Flush a directory entry change to disk where the platform allows it
+fn sync_parent_dir(path: &std::path::Path) {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    // Directories cannot be opened for syncing everywhere (e.g. Windows);
+    // the rename has happened either way
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
     }
 }
 
@@ -0,0 +1,169 @@
+//! Schedule validation: catching mis-assembled schedules before they run
+//!
+//! Shells assemble schedules from [`crate::bootstrap::build_default_schedule`]
+//! plus their own systems, and the pieces drift. [`validate_schedule`]
+//! initializes a schedule against the world it will run on and reports what
+//! Bevy would otherwise only surface as a panic mid-run or as silent
+//! nondeterminism.
+
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::NodeId;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// One problem found by [`validate_schedule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleIssue {
+    /// The same system function was added more than once
+    DuplicateSystem { system: String, count: usize },
+    /// A system reads or writes a resource the world does not have
+    ///
+    /// Optional (`Option<Res<_>>`) parameters count too: shells are expected
+    /// to insert defaults for every config resource, as bootstrap does.
+    MissingResource { system: String, resource: String },
+    /// Two systems touch the same data with no order between them
+    ///
+    /// Not fatal: the default schedule still has some of these.
+    Ambiguity {
+        first: String,
+        second: String,
+        conflicts: Vec<String>,
+    },
+    /// The schedule could not be built at all (cycles, bad set ordering)
+    Build(String),
+}
+
+impl ScheduleIssue {
+    /// Whether [`debug_validate_schedule`] refuses to run with this issue
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Self::Ambiguity { .. })
+    }
+}
+
+impl fmt::Display for ScheduleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateSystem { system, count } => {
+                write!(f, "{system} is added {count} times")
+            }
+            Self::MissingResource { system, resource } => {
+                write!(f, "{system} needs missing resource {resource}")
+            }
+            Self::Ambiguity {
+                first,
+                second,
+                conflicts,
+            } => write!(
+                f,
+                "{first} and {second} are unordered but both access {}",
+                if conflicts.is_empty() {
+                    "the world".to_string()
+                } else {
+                    conflicts.join(", ")
+                }
+            ),
+            Self::Build(e) => write!(f, "schedule does not build: {e}"),
+        }
+    }
+}
+
+/// Bevy inserts these between ordered systems; they are not duplicates
+const APPLY_DEFERRED: &str = "apply_deferred";
+
+/// Initialize `schedule` against `world` and list everything wrong with it
+///
+/// Issues come out grouped by kind and sorted by system name, so the report
+/// is stable between runs. The schedule is left initialized and runnable.
+pub fn validate_schedule(schedule: &mut Schedule, world: &mut World) -> Vec<ScheduleIssue> {
+    if let Err(e) = schedule.initialize(world) {
+        return vec![ScheduleIssue::Build(e.to_string())];
+    }
+    let Ok(systems) = schedule.systems() else {
+        return vec![ScheduleIssue::Build("schedule not initialized".into())];
+    };
+    let mut names: HashMap<NodeId, String> = HashMap::new();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut missing = Vec::new();
+    for (id, system) in systems {
+        let name = system.name().to_string();
+        if !name.ends_with(APPLY_DEFERRED) {
+            *counts.entry(name.clone()).or_default() += 1;
+        }
+        for resource in system.component_access().reads_and_writes() {
+            if is_resource(world, resource) && world.get_resource_by_id(resource).is_none() {
+                missing.push(ScheduleIssue::MissingResource {
+                    system: name.clone(),
+                    resource: component_name(world, resource),
+                });
+            }
+        }
+        names.insert(id, name);
+    }
+
+    let mut issues: Vec<ScheduleIssue> = counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(system, count)| ScheduleIssue::DuplicateSystem { system, count })
+        .collect();
+    missing.sort_by_key(|issue| issue.to_string());
+    missing.dedup();
+    issues.extend(missing);
+
+    let mut ambiguities: Vec<ScheduleIssue> = schedule
+        .graph()
+        .conflicting_systems()
+        .iter()
+        .map(|(a, b, conflicts)| {
+            let (mut first, mut second) = (names[a].clone(), names[b].clone());
+            if second < first {
+                std::mem::swap(&mut first, &mut second);
+            }
+            let mut conflicts: Vec<String> = conflicts
+                .iter()
+                .map(|&id| component_name(world, id))
+                .collect();
+            conflicts.sort();
+            ScheduleIssue::Ambiguity {
+                first,
+                second,
+                conflicts,
+            }
+        })
+        .collect();
+    ambiguities.sort_by_key(|issue| issue.to_string());
+    issues.extend(ambiguities);
+    issues
+}
+
+/// [`validate_schedule`], panicking in debug builds if any issue is fatal
+///
+/// The panic lists every fatal issue. Release builds skip the check.
+pub fn debug_validate_schedule(schedule: &mut Schedule, world: &mut World) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let issues: Vec<ScheduleIssue> = validate_schedule(schedule, world)
+        .into_iter()
+        .filter(ScheduleIssue::is_fatal)
+        .collect();
+    if !issues.is_empty() {
+        let report: Vec<String> = issues.iter().map(|i| format!("  - {i}")).collect();
+        panic!("invalid schedule:\n{}", report.join("\n"));
+    }
+}
+
+fn is_resource(world: &World, id: ComponentId) -> bool {
+    world
+        .components()
+        .get_info(id)
+        .and_then(|info| info.type_id())
+        .is_some_and(|type_id| world.components().get_resource_id(type_id) == Some(id))
+}
+
+fn component_name(world: &World, id: ComponentId) -> String {
+    world
+        .components()
+        .get_info(id)
+        .map_or_else(|| format!("{id:?}"), |info| info.name().to_string())
+}
@@ -0,0 +1,397 @@
+//! Run the simulation on a dedicated thread behind a command channel
+//!
+//! [`spawn_sim_thread`] moves a `World` and its `Schedule` onto a worker
+//! thread. Shells talk to it through a [`SimHandle`]: [`SimCommand`]s go in,
+//! [`SimEvent`]s (snapshots, saves, estimates) come out. Both channels are
+//! bounded:
+//!
+//! - Commands use `try_send`, so a flooded simulation reports
+//!   [`SimSendError::Full`] instead of stalling the caller's frame.
+//! - Snapshots are dropped when the consumer falls behind (a newer one always
+//!   follows); saves and shutdown notices are never dropped.
+//!
+//! Besides the handle's own event queue, any number of consumers can
+//! [`subscribe`](SimHandle::subscribe) with an [`EventFilter`] and get their
+//! own receiver. Subscribers never slow the simulation down: an event that
+//! does not fit in a subscriber's queue is dropped for that subscriber, and
+//! dropped receivers are forgotten.
+//!
+//! Urgent announcements pause the thread when their log requests it.
+//!
+//! Commands are drained and applied at tick boundaries in arrival order, so
+//! the resulting world only depends on which commands landed before which
+//! tick, never on wall-clock timing inside a tick.
+
+use crate::announcements::Announcements;
+use crate::command::{apply_world_command, WorldCommand};
+use crate::components::ZoneBounds;
+use crate::designations::{estimate_designation, DesignationEstimate};
+use crate::determinism::{run_tick, wall_clock};
+use crate::save::{save_world, SaveGame};
+use crate::toggles::{Subsystem, SystemToggles};
+use crate::view::{snapshot_world, WorldView};
+use bevy_ecs::prelude::*;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Tuning for the simulation thread
+#[derive(Debug, Clone)]
+pub struct SimThreadConfig {
+    /// Wall-clock time between tick batches (pacing only, never fed to systems)
+    pub tick_interval: Duration,
+    /// Schedule runs per batch while unpaused
+    pub steps_per_tick: u32,
+    /// Start in the paused state
+    pub start_paused: bool,
+    /// Bounded capacity of the command channel
+    pub command_capacity: usize,
+    /// Bounded capacity of the event channel
+    pub event_capacity: usize,
+}
+
+impl Default for SimThreadConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_millis(33),
+            steps_per_tick: 1,
+            start_paused: false,
+            command_capacity: 256,
+            event_capacity: 4,
+        }
+    }
+}
+
+/// Message sent to the simulation thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimCommand {
+    /// Mutate the world at the next tick boundary
+    World(WorldCommand),
+    Pause,
+    Resume,
+    TogglePause,
+    /// Run this many extra ticks, even while paused
+    Step(u32),
+    /// Change schedule runs per batch (clamped to at least 1)
+    SetSpeed(u32),
+    /// Publish a fresh snapshot without advancing time
+    Snapshot,
+    /// Reply with a [`SimEvent::Saved`] of the current world
+    Save,
+    /// Switch a subsystem off, or back on (see [`SystemToggles`])
+    ToggleSubsystem(Subsystem),
+    /// Reply with a [`SimEvent::Estimate`] for mining this area
+    Estimate(ZoneBounds),
+    /// Stop the thread after the current boundary
+    Shutdown,
+}
+
+/// Message published by the simulation thread
+#[derive(Debug, Clone)]
+pub enum SimEvent {
+    /// World state after a tick batch or state-changing command
+    Snapshot(Arc<WorldView>),
+    /// Reply to [`SimCommand::Save`]
+    Saved(Box<SaveGame>),
+    /// Reply to [`SimCommand::Estimate`]
+    Estimate {
+        bounds: ZoneBounds,
+        estimate: DesignationEstimate,
+    },
+    /// Pause state changed
+    Paused(bool),
+    /// The thread has exited at this tick
+    Stopped { tick: u64 },
+}
+
+/// The kind of a [`SimEvent`], for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimEventKind {
+    Snapshot,
+    Saved,
+    Estimate,
+    Paused,
+    Stopped,
+}
+
+impl SimEvent {
+    pub fn kind(&self) -> SimEventKind {
+        match self {
+            SimEvent::Snapshot(_) => SimEventKind::Snapshot,
+            SimEvent::Saved(_) => SimEventKind::Saved,
+            SimEvent::Estimate { .. } => SimEventKind::Estimate,
+            SimEvent::Paused(_) => SimEventKind::Paused,
+            SimEvent::Stopped { .. } => SimEventKind::Stopped,
+        }
+    }
+}
+
+/// Which events a subscriber receives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventFilter {
+    kinds: Vec<SimEventKind>,
+}
+
+impl EventFilter {
+    /// Every event
+    pub fn all() -> Self {
+        Self::only(&[
+            SimEventKind::Snapshot,
+            SimEventKind::Saved,
+            SimEventKind::Estimate,
+            SimEventKind::Paused,
+            SimEventKind::Stopped,
+        ])
+    }
+
+    /// Only events of the listed kinds
+    pub fn only(kinds: &[SimEventKind]) -> Self {
+        Self {
+            kinds: kinds.to_vec(),
+        }
+    }
+
+    pub fn accepts(&self, event: &SimEvent) -> bool {
+        self.kinds.contains(&event.kind())
+    }
+}
+
+struct Subscriber {
+    filter: EventFilter,
+    events: SyncSender<SimEvent>,
+}
+
+/// Subscribers shared between the handle and the simulation thread
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+/// Hand `event` to every subscriber that wants it, without blocking
+fn fan_out(subscribers: &Subscribers, event: &SimEvent) {
+    let mut subscribers = subscribers.lock().expect("subscriber list poisoned");
+    subscribers.retain(|s| {
+        !s.filter.accepts(event)
+            || !matches!(
+                s.events.try_send(event.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+    });
+}
+
+/// Failure to enqueue a command
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SimSendError {
+    #[error("simulation command queue is full")]
+    Full,
+    #[error("simulation thread has stopped")]
+    Disconnected,
+}
+
+/// Owner-side handle to a running simulation thread
+pub struct SimHandle {
+    commands: SyncSender<SimCommand>,
+    events: Receiver<SimEvent>,
+    subscribers: Subscribers,
+    /// Queue capacity for new subscribers
+    event_capacity: usize,
+    thread: Option<JoinHandle<(World, Schedule)>>,
+}
+
+impl SimHandle {
+    /// Enqueue a command without blocking
+    pub fn send(&self, command: SimCommand) -> Result<(), SimSendError> {
+        self.commands.try_send(command).map_err(|e| match e {
+            TrySendError::Full(_) => SimSendError::Full,
+            TrySendError::Disconnected(_) => SimSendError::Disconnected,
+        })
+    }
+
+    /// Enqueue a command, waiting for queue space
+    pub fn send_blocking(&self, command: SimCommand) -> Result<(), SimSendError> {
+        self.commands
+            .send(command)
+            .map_err(|_| SimSendError::Disconnected)
+    }
+
+    /// Next pending event, if any
+    pub fn try_recv(&self) -> Option<SimEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<SimEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// All pending events in publication order
+    pub fn drain(&self) -> Vec<SimEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// A receiver of its own for the events `filter` accepts
+    ///
+    /// Only events published after subscribing are delivered. The queue
+    /// holds as many events as the handle's own; when it is full further
+    /// events are dropped for this subscriber until it catches up. Drop the
+    /// receiver to unsubscribe.
+    pub fn subscribe(&self, filter: EventFilter) -> Receiver<SimEvent> {
+        let (tx, rx) = mpsc::sync_channel(self.event_capacity);
+        self.subscribers
+            .lock()
+            .expect("subscriber list poisoned")
+            .push(Subscriber { filter, events: tx });
+        rx
+    }
+
+    /// Stop the thread and take back the world and schedule
+    pub fn shutdown(mut self) -> Option<(World, Schedule)> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Option<(World, Schedule)> {
+        let thread = self.thread.take()?;
+        // The thread may be blocked publishing an event; drain while retrying
+        loop {
+            match self.commands.try_send(SimCommand::Shutdown) {
+                Ok(()) | Err(TrySendError::Disconnected(_)) => break,
+                Err(TrySendError::Full(_)) => {
+                    let _ = self.events.recv_timeout(Duration::from_millis(1));
+                }
+            }
+        }
+        while self.events.recv().is_ok() {}
+        thread.join().ok()
+    }
+}
+
+impl Drop for SimHandle {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Move `world` and `schedule` onto a new simulation thread
+///
+/// An initial snapshot is published before the first tick.
+pub fn spawn_sim_thread(world: World, schedule: Schedule, config: SimThreadConfig) -> SimHandle {
+    let (cmd_tx, cmd_rx) = mpsc::sync_channel(config.command_capacity.max(1));
+    let event_capacity = config.event_capacity.max(1);
+    let (evt_tx, evt_rx) = mpsc::sync_channel(event_capacity);
+    let subscribers = Subscribers::default();
+    let shared = subscribers.clone();
+    let thread = std::thread::Builder::new()
+        .name("gc-sim".into())
+        .spawn(move || sim_loop(world, schedule, config, cmd_rx, evt_tx, shared))
+        .expect("failed to spawn simulation thread");
+    SimHandle {
+        commands: cmd_tx,
+        events: evt_rx,
+        subscribers,
+        event_capacity,
+        thread: Some(thread),
+    }
+}
+
+fn sim_loop(
+    mut world: World,
+    mut schedule: Schedule,
+    config: SimThreadConfig,
+    commands: Receiver<SimCommand>,
+    events: SyncSender<SimEvent>,
+    subscribers: Subscribers,
+) -> (World, Schedule) {
+    let publish_event = |event: SimEvent| {
+        fan_out(&subscribers, &event);
+        let _ = events.send(event);
+    };
+    let mut paused = config.start_paused;
+    let mut steps_per_tick = config.steps_per_tick.max(1);
+    // Holds at most the one command that woke the loop early
+    let mut woken_by: Option<SimCommand> = None;
+    let mut publish = true;
+    let mut next_tick = wall_clock();
+
+    'run: loop {
+        // Tick boundary: apply everything received so far, in order
+        let mut extra_steps = 0u32;
+        for command in woken_by.take().into_iter().chain(commands.try_iter()) {
+            match command {
+                SimCommand::World(c) => publish |= apply_world_command(&mut world, &c),
+                SimCommand::Pause | SimCommand::Resume | SimCommand::TogglePause => {
+                    let next = match command {
+                        SimCommand::Pause => true,
+                        SimCommand::Resume => false,
+                        _ => !paused,
+                    };
+                    if next != paused {
+                        paused = next;
+                        next_tick = wall_clock();
+                        publish_event(SimEvent::Paused(paused));
+                    }
+                }
+                SimCommand::Step(n) => extra_steps = extra_steps.saturating_add(n),
+                SimCommand::SetSpeed(n) => steps_per_tick = n.max(1),
+                SimCommand::Snapshot => publish = true,
+                SimCommand::ToggleSubsystem(subsystem) => {
+                    world
+                        .get_resource_or_insert_with(SystemToggles::default)
+                        .toggle(subsystem);
+                    publish = true;
+                }
+                SimCommand::Save => {
+                    let save = save_world(&mut world);
+                    publish_event(SimEvent::Saved(Box::new(save)));
+                }
+                SimCommand::Estimate(bounds) => {
+                    let estimate = estimate_designation(&mut world, &bounds);
+                    publish_event(SimEvent::Estimate { bounds, estimate });
+                }
+                SimCommand::Shutdown => break 'run,
+            }
+        }
+
+        let now = wall_clock();
+        let due = !paused && now >= next_tick;
+        if due {
+            // Skip missed batches rather than bursting to catch up
+            next_tick = (next_tick + config.tick_interval).max(now);
+        }
+        let runs = if due { steps_per_tick } else { 0 } + extra_steps;
+        for _ in 0..runs {
+            run_tick(&mut schedule, &mut world);
+        }
+        let pause_requested = world
+            .get_resource_mut::<Announcements>()
+            .is_some_and(|mut a| a.take_pause_request());
+        if pause_requested && !paused {
+            paused = true;
+            publish_event(SimEvent::Paused(true));
+        }
+        if runs > 0 || publish {
+            publish = false;
+            let view = Arc::new(snapshot_world(&mut world));
+            fan_out(&subscribers, &SimEvent::Snapshot(view.clone()));
+            // A full queue means the consumer is behind; it will get a newer view
+            if let Err(TrySendError::Disconnected(_)) = events.try_send(SimEvent::Snapshot(view)) {
+                break 'run;
+            }
+        }
+
+        // Sleep until the next batch or the next command, whichever is first
+        let received = if paused {
+            commands.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            commands.recv_timeout(next_tick.saturating_duration_since(wall_clock()))
+        };
+        match received {
+            Ok(command) => woken_by = Some(command),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break 'run,
+        }
+    }
+
+    let tick = world
+        .get_resource::<crate::systems::Time>()
+        .map_or(0, |t| t.ticks);
+    publish_event(SimEvent::Stopped { tick });
+    (world, schedule)
+}
@@ -0,0 +1,383 @@
+//! Soak runs: long simulations checked against invariants
+//!
+//! [`check_invariants`] looks for states the simulation must never be in
+//! at a tick boundary: entities off the map, workers holding jobs that do
+//! not exist, hit points out of range, and more jobs than the map can
+//! account for. [`SoakMonitor`] runs those checks periodically and adds
+//! checks over time:
+//!
+//! - new [`SimFaults`] since the last check;
+//! - a metric (entities, jobs, designations, items) that rose at every one
+//!   of the last [`InvariantConfig::growth_window`] checks, which is how
+//!   unbounded growth looks from outside;
+//! - live heap bytes, when the caller can count them, more than
+//!   [`InvariantConfig::memory_factor`] times the level after warm-up.
+//!
+//! [`run_soak`] drives a [`BenchProfile`] colony until a wall-clock budget
+//! or tick cap runs out (`gc_cli soak --hours N --seed S` for nightly
+//! stability runs). The checks read the world between ticks and never
+//! change it, so a soak run is the same simulation as a plain one.
+
+use crate::bench::{bench_schedule, build_bench_world, BenchProfile};
+use crate::components::{AssignedJob, Health, Item};
+use crate::designations::MineDesignation;
+use crate::determinism::{run_tick, wall_clock};
+use crate::faults::SimFaults;
+use crate::jobs::{ActiveJobs, JobBoard};
+use crate::systems::Time;
+use crate::world::{GameMap, Position};
+use bevy_ecs::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+/// Which invariant a [`Violation`] breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvariantKind {
+    /// An entity's position is outside the map
+    OffMap,
+    /// A worker's assigned job is not among the active jobs
+    OrphanAssignment,
+    /// Hit points below zero or above the maximum
+    HealthRange,
+    /// More queued and active jobs than the bound
+    JobBound,
+    /// Systems reported faults since the last check
+    Faults,
+    /// A metric rose at every check of the growth window
+    Growth,
+    /// Live heap bytes grew past the memory factor
+    Memory,
+}
+
+impl InvariantKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            InvariantKind::OffMap => "off_map",
+            InvariantKind::OrphanAssignment => "orphan_assignment",
+            InvariantKind::HealthRange => "health_range",
+            InvariantKind::JobBound => "job_bound",
+            InvariantKind::Faults => "faults",
+            InvariantKind::Growth => "growth",
+            InvariantKind::Memory => "memory",
+        }
+    }
+}
+
+impl fmt::Display for InvariantKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// One broken invariant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub tick: u64,
+    pub kind: InvariantKind,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[tick {}] {}: {}", self.tick, self.kind, self.detail)
+    }
+}
+
+/// Bounds the invariants are checked against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvariantConfig {
+    /// Most queued plus active jobs per map tile
+    pub jobs_per_tile: usize,
+    /// Checks a metric must rise at in a row to count as growing
+    pub growth_window: usize,
+    /// Live heap bytes may reach this multiple of the post-warm-up level
+    pub memory_factor: f64,
+}
+
+impl Default for InvariantConfig {
+    fn default() -> Self {
+        Self {
+            jobs_per_tile: 2,
+            growth_window: 20,
+            memory_factor: 2.0,
+        }
+    }
+}
+
+/// Sizes watched for unbounded growth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SoakMetrics {
+    pub entities: u64,
+    /// Queued plus active jobs
+    pub jobs: u64,
+    pub designations: u64,
+    pub items: u64,
+}
+
+impl SoakMetrics {
+    pub fn sample(world: &mut World) -> Self {
+        let jobs = world.get_resource::<JobBoard>().map_or(0, |b| b.0.len())
+            + world
+                .get_resource::<ActiveJobs>()
+                .map_or(0, |a| a.jobs.len());
+        Self {
+            entities: world.entities().len() as u64,
+            jobs: jobs as u64,
+            designations: world
+                .query_filtered::<(), With<MineDesignation>>()
+                .iter(world)
+                .count() as u64,
+            items: world.query::<&Item>().iter(world).count() as u64,
+        }
+    }
+
+    fn named(&self) -> [(&'static str, u64); 4] {
+        [
+            ("entities", self.entities),
+            ("jobs", self.jobs),
+            ("designations", self.designations),
+            ("items", self.items),
+        ]
+    }
+}
+
+/// Check the invariants that hold at every tick boundary
+pub fn check_invariants(world: &mut World, config: &InvariantConfig) -> Vec<Violation> {
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+    let mut out = Vec::new();
+    let mut violation = |kind, detail: String| out.push(Violation { tick, kind, detail });
+
+    let (width, height) = world
+        .get_resource::<GameMap>()
+        .map_or((0, 0), |m| (m.width, m.height));
+    if width > 0 && height > 0 {
+        let mut q = world.query::<(Entity, &Position)>();
+        for (entity, pos) in q.iter(world) {
+            if pos.0 < 0 || pos.1 < 0 || pos.0 as u32 >= width || pos.1 as u32 >= height {
+                violation(
+                    InvariantKind::OffMap,
+                    format!("{entity:?} at ({}, {})", pos.0, pos.1),
+                );
+            }
+        }
+    }
+
+    let mut q = world.query::<(Entity, &AssignedJob)>();
+    let held: Vec<_> = q
+        .iter(world)
+        .filter_map(|(entity, assigned)| assigned.0.map(|id| (entity, id)))
+        .collect();
+    let active = world.get_resource::<ActiveJobs>();
+    for (entity, id) in held {
+        if !active.is_some_and(|a| a.jobs.contains_key(&id)) {
+            violation(
+                InvariantKind::OrphanAssignment,
+                format!("{entity:?} holds missing job {:?}", id.0),
+            );
+        }
+    }
+
+    let mut q = world.query::<(Entity, &Health)>();
+    for (entity, health) in q.iter(world) {
+        if health.hp < 0 || health.hp > health.max_hp {
+            violation(
+                InvariantKind::HealthRange,
+                format!("{entity:?} has {}/{} hp", health.hp, health.max_hp),
+            );
+        }
+    }
+
+    let jobs = world.get_resource::<JobBoard>().map_or(0, |b| b.0.len())
+        + world
+            .get_resource::<ActiveJobs>()
+            .map_or(0, |a| a.jobs.len());
+    let bound = config.jobs_per_tile * (width * height) as usize;
+    if width > 0 && jobs > bound {
+        violation(
+            InvariantKind::JobBound,
+            format!("{jobs} jobs on a {width}x{height} map (bound {bound})"),
+        );
+    }
+    out
+}
+
+/// Periodic invariant checks with the history growth checks need
+#[derive(Debug, Clone)]
+pub struct SoakMonitor {
+    pub config: InvariantConfig,
+    checks: usize,
+    /// Latest samples per metric, at most `growth_window + 1`
+    history: BTreeMap<&'static str, VecDeque<u64>>,
+    /// Metrics already reported as growing, until they stop
+    growing: BTreeSet<&'static str>,
+    faults_seen: u64,
+    /// Memory level further growth is measured from, once warmed up
+    memory_baseline: Option<u64>,
+    memory_peak: u64,
+}
+
+impl SoakMonitor {
+    pub fn new(config: InvariantConfig) -> Self {
+        Self {
+            config,
+            checks: 0,
+            history: BTreeMap::new(),
+            growing: BTreeSet::new(),
+            faults_seen: 0,
+            memory_baseline: None,
+            memory_peak: 0,
+        }
+    }
+
+    /// Checks run so far
+    pub fn checks(&self) -> usize {
+        self.checks
+    }
+
+    /// Most live heap bytes seen
+    pub fn memory_peak(&self) -> u64 {
+        self.memory_peak
+    }
+
+    /// Run every check against `world`; `memory` is the live heap size in
+    /// bytes, if the caller counts it
+    pub fn check(&mut self, world: &mut World, memory: Option<u64>) -> Vec<Violation> {
+        self.checks += 1;
+        let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+        let mut out = check_invariants(world, &self.config);
+
+        let faults = world.get_resource::<SimFaults>().map_or(0, |f| f.total());
+        if faults > self.faults_seen {
+            out.push(Violation {
+                tick,
+                kind: InvariantKind::Faults,
+                detail: format!("{} new simulation faults", faults - self.faults_seen),
+            });
+        }
+        self.faults_seen = faults;
+
+        let window = self.config.growth_window.max(1);
+        for (name, value) in SoakMetrics::sample(world).named() {
+            let samples = self.history.entry(name).or_default();
+            samples.push_back(value);
+            if samples.len() > window + 1 {
+                samples.pop_front();
+            }
+            let rising = samples.len() > window
+                && samples
+                    .iter()
+                    .zip(samples.iter().skip(1))
+                    .all(|(a, b)| b > a);
+            if !rising {
+                self.growing.remove(name);
+            } else if self.growing.insert(name) {
+                out.push(Violation {
+                    tick,
+                    kind: InvariantKind::Growth,
+                    detail: format!("{name} rose at each of the last {window} checks, to {value}"),
+                });
+            }
+        }
+
+        if let Some(bytes) = memory {
+            self.memory_peak = self.memory_peak.max(bytes);
+            match self.memory_baseline {
+                None if self.checks >= window => self.memory_baseline = Some(self.memory_peak),
+                None => {}
+                Some(baseline) if bytes as f64 > baseline as f64 * self.config.memory_factor => {
+                    out.push(Violation {
+                        tick,
+                        kind: InvariantKind::Memory,
+                        detail: format!("{bytes} live heap bytes, up from {baseline}"),
+                    });
+                    // Report each further step of growth, not every check
+                    self.memory_baseline = Some(bytes);
+                }
+                Some(_) => {}
+            }
+        }
+        out
+    }
+}
+
+/// When a soak run stops and how often it checks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoakLimits {
+    /// Wall-clock budget
+    pub duration: Duration,
+    /// Stop after this many ticks even with time left
+    pub max_ticks: Option<u64>,
+    /// Ticks between invariant checks
+    pub check_every: u64,
+}
+
+/// Outcome of [`run_soak`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SoakReport {
+    pub profile: &'static str,
+    pub seed: u64,
+    pub ticks: u64,
+    pub checks: usize,
+    pub elapsed: Duration,
+    pub violations: usize,
+    pub metrics: SoakMetrics,
+    /// Most live heap bytes seen, when memory was sampled
+    pub memory_peak: Option<u64>,
+}
+
+/// Run `profile` until `limits` are spent, checking invariants every
+/// `check_every` ticks
+///
+/// `memory` samples live heap bytes at each check (None when the caller
+/// cannot count them); every violation is passed to `on_violation` as it
+/// is found.
+pub fn run_soak(
+    profile: BenchProfile,
+    seed: u64,
+    limits: SoakLimits,
+    config: InvariantConfig,
+    memory: impl Fn() -> Option<u64>,
+    mut on_violation: impl FnMut(&Violation),
+) -> SoakReport {
+    let mut world = build_bench_world(profile, seed);
+    let mut schedule = bench_schedule();
+    let mut monitor = SoakMonitor::new(config);
+    let check_every = limits.check_every.max(1);
+    let start = wall_clock();
+    let mut ticks = 0;
+    let mut violations = 0;
+    let mut sampled = false;
+    loop {
+        // The last batch stops at `max_ticks` rather than a full interval
+        let batch = limits.max_ticks.map_or(check_every, |max| {
+            check_every.min(max.saturating_sub(ticks))
+        });
+        for _ in 0..batch {
+            run_tick(&mut schedule, &mut world);
+        }
+        ticks += batch;
+        let bytes = memory();
+        sampled |= bytes.is_some();
+        for violation in monitor.check(&mut world, bytes) {
+            on_violation(&violation);
+            violations += 1;
+        }
+        let out_of_ticks = limits.max_ticks.is_some_and(|max| ticks >= max);
+        if out_of_ticks || start.elapsed() >= limits.duration {
+            break;
+        }
+    }
+    SoakReport {
+        profile: profile.name,
+        seed,
+        ticks,
+        checks: monitor.checks(),
+        elapsed: start.elapsed(),
+        violations,
+        metrics: SoakMetrics::sample(&mut world),
+        memory_peak: sampled.then(|| monitor.memory_peak()),
+    }
+}
@@ -1,6 +1,11 @@
-use crate::components::{Stockpile, ZoneBounds};
-use crate::world::Position;
+use crate::components::{Item, ItemType, Stockpile, ZoneBounds};
+use crate::world::{GameMap, Position};
+use crate::zones::ZoneKind;
 use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
 
 /// Stockpile System for Item Storage and Organization
 ///
@@ -17,6 +22,8 @@ pub struct StockpileBundle {
     pub position: Position,
     /// Zone bounds defining the spatial area of the stockpile
     pub bounds: ZoneBounds,
+    /// Always [`ZoneKind::Stockpile`]
+    pub kind: ZoneKind,
 }
 
 impl StockpileBundle {
@@ -26,11 +33,109 @@ impl StockpileBundle {
     pub fn new(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Self {
         let center = ((min_x + max_x) / 2, (min_y + max_y) / 2);
         Self {
-            stockpile: Stockpile { accepts: None }, // Accept all item types
+            // Accept all item types
+            stockpile: Stockpile {
+                accepts: None,
+                priority: 0,
+            },
             position: Position(center.0, center.1),
             bounds: ZoneBounds::new(min_x, min_y, max_x, max_y),
+            kind: ZoneKind::Stockpile,
         }
     }
+
+    /// Set the haul destination priority (see [`Stockpile::priority`])
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.stockpile.priority = priority;
+        self
+    }
+}
+
+/// How full a stockpile may get before hauls stop bringing it items
+///
+/// A stockpile holds one item per tile of its bounds; it counts as full
+/// once what it stores plus what is being hauled to it reaches
+/// `max_fill_percent` of that, so a feeder stockpile can be kept partly
+/// empty for throughput. Stockpiles without the component never fill up.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillLimit {
+    pub max_fill_percent: u8,
+    /// Where items go once the stockpile is full
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+impl FillLimit {
+    pub fn new(max_fill_percent: u8, overflow: OverflowPolicy) -> Self {
+        Self {
+            max_fill_percent,
+            overflow,
+        }
+    }
+
+    /// Items a stockpile of `capacity` tiles may hold
+    pub fn limit(&self, capacity: usize) -> usize {
+        capacity * usize::from(self.max_fill_percent.min(100)) / 100
+    }
+}
+
+/// What happens to an item bound for a full stockpile
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Go to the next stockpile that accepts it and has room
+    #[default]
+    Reject,
+    /// Go to the first of the stockpile's [`GiveTo`] targets with room
+    Spill,
+    /// Be dropped on the floor next to the stockpile
+    Drop,
+}
+
+impl OverflowPolicy {
+    pub const ALL: [OverflowPolicy; 3] = [
+        OverflowPolicy::Reject,
+        OverflowPolicy::Spill,
+        OverflowPolicy::Drop,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverflowPolicy::Reject => "reject",
+            OverflowPolicy::Spill => "spill",
+            OverflowPolicy::Drop => "drop",
+        }
+    }
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OverflowPolicy::ALL
+            .into_iter()
+            .find(|p| p.label().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown overflow policy '{}'", s))
+    }
+}
+
+/// Stockpiles that take this one's overflow, in order of preference
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GiveTo(pub Vec<Entity>);
+
+/// Tiles a stockpile covers: the area of its bounds, or its own tile
+pub fn stockpile_capacity(bounds: Option<&ZoneBounds>) -> usize {
+    bounds.map_or(1, |b| {
+        ((b.max_x - b.min_x + 1).max(0) * (b.max_y - b.min_y + 1).max(0)) as usize
+    })
 }
 
 /// Find the nearest stockpile to a given position within a world
@@ -62,6 +167,118 @@ pub fn find_nearest_stockpile(
     nearest
 }
 
+/// Whether a stockpile at `pos` covers (x, y)
+/// Stockpiles without [`ZoneBounds`] cover only their own tile
+pub fn stockpile_covers(pos: &Position, bounds: Option<&ZoneBounds>, x: i32, y: i32) -> bool {
+    match bounds {
+        Some(bounds) => bounds.contains(x, y),
+        None => (pos.0, pos.1) == (x, y),
+    }
+}
+
+/// Whether an item of `item_type` at (x, y) is already stored: inside a
+/// stockpile that accepts it
+/// Takes stockpiles as plain tuples so both systems (queries) and
+/// `World`-level callers can use it
+pub fn item_is_stored<'a>(
+    stockpiles: impl IntoIterator<Item = (&'a Position, &'a Stockpile, Option<&'a ZoneBounds>)>,
+    x: i32,
+    y: i32,
+    item_type: ItemType,
+) -> bool {
+    stockpiles.into_iter().any(|(pos, stockpile, bounds)| {
+        stockpile.accepts_item(item_type) && stockpile_covers(pos, bounds, x, y)
+    })
+}
+
+/// Pick where an item of `item_type` at `from` should be hauled
+/// Among stockpiles accepting it, orders by priority (highest first), then
+/// squared distance from `from` to the stockpile's position, then entity, so
+/// the choice never depends on query order
+pub fn select_haul_destination<'a>(
+    stockpiles: impl IntoIterator<Item = (Entity, &'a Position, &'a Stockpile)>,
+    from: (i32, i32),
+    item_type: ItemType,
+) -> Option<(Entity, Position)> {
+    stockpiles
+        .into_iter()
+        .filter(|(_, _, stockpile)| stockpile.accepts_item(item_type))
+        .min_by_key(|&(entity, pos, stockpile)| {
+            let (dx, dy) = (pos.0 - from.0, pos.1 - from.1);
+            (
+                std::cmp::Reverse(stockpile.priority),
+                dx * dx + dy * dy,
+                entity,
+            )
+        })
+        .map(|(entity, pos, _)| (entity, *pos))
+}
+
+/// Where a stockpile haul should still go, per [`revalidate_haul`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaulDestination {
+    /// A stockpile at the destination still accepts the item
+    Valid,
+    /// The destination no longer takes it; this stockpile does
+    Retarget((i32, i32)),
+    /// No stockpile takes it any more
+    Gone,
+}
+
+/// Check a stockpile haul of an `item_type` item from `from` to `to`
+/// against the stockpiles as they are now
+///
+/// A stockpile deleted, moved or refiltered since the haul was posted
+/// leaves it pointing at a tile that no longer stores the item; the haul
+/// then goes to the stockpile [`select_haul_destination`] picks instead,
+/// if any. Fill limits are not consulted here: auto-haul already counted
+/// the item toward one stockpile's room.
+pub fn revalidate_haul(
+    stockpiles: &[(Entity, &Position, &Stockpile, Option<&ZoneBounds>)],
+    from: (i32, i32),
+    to: (i32, i32),
+    item_type: ItemType,
+) -> HaulDestination {
+    if item_is_stored(
+        stockpiles.iter().map(|&(_, p, s, b)| (p, s, b)),
+        to.0,
+        to.1,
+        item_type,
+    ) {
+        return HaulDestination::Valid;
+    }
+    match select_haul_destination(
+        stockpiles.iter().map(|&(e, p, s, _)| (e, p, s)),
+        from,
+        item_type,
+    ) {
+        Some((_, pos)) => HaulDestination::Retarget((pos.0, pos.1)),
+        None => HaulDestination::Gone,
+    }
+}
+
+/// Floor tile just outside `bounds` where overflow is dropped
+///
+/// Picks the walkable tile bordering `bounds` (diagonals included) nearest
+/// to `from` by Manhattan distance, then by row and column, skipping tiles
+/// inside any of `stockpiles` so the dropped item is not stored after all.
+pub fn overflow_drop_tile<'a>(
+    map: &GameMap,
+    bounds: &ZoneBounds,
+    stockpiles: &[(&'a Position, Option<&'a ZoneBounds>)],
+    from: (i32, i32),
+) -> Option<(i32, i32)> {
+    (bounds.min_y - 1..=bounds.max_y + 1)
+        .flat_map(|y| (bounds.min_x - 1..=bounds.max_x + 1).map(move |x| (x, y)))
+        .filter(|&(x, y)| !bounds.contains(x, y) && map.is_walkable(x, y))
+        .filter(|&(x, y)| {
+            !stockpiles
+                .iter()
+                .any(|&(pos, b)| stockpile_covers(pos, b, x, y))
+        })
+        .min_by_key(|&(x, y)| ((x - from.0).abs() + (y - from.1).abs(), y, x))
+}
+
 /// Check if a position is within any stockpile zone
 /// Useful for determining if an item is already in a stockpile
 /// Returns true if the position overlaps with any stockpile bounds
@@ -93,3 +310,145 @@ pub fn find_stockpiles_at_position(world: &mut World, x: i32, y: i32) -> Vec<Ent
         })
         .collect()
 }
+
+/// What a stockpile holds: item counts by type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StockpileSummary {
+    pub by_type: BTreeMap<ItemType, usize>,
+    /// Items of every type
+    pub total: usize,
+}
+
+impl StockpileSummary {
+    /// Count of items of `item_type`
+    pub fn count(&self, item_type: ItemType) -> usize {
+        self.by_type.get(&item_type).copied().unwrap_or(0)
+    }
+
+    fn add(&mut self, item_type: ItemType) {
+        *self.by_type.entry(item_type).or_default() += 1;
+        self.total += 1;
+    }
+
+    fn remove(&mut self, item_type: ItemType) {
+        if let Some(n) = self.by_type.get_mut(&item_type) {
+            *n -= 1;
+            if *n == 0 {
+                self.by_type.remove(&item_type);
+            }
+            self.total -= 1;
+        }
+    }
+}
+
+/// Cached contents of every stockpile, kept by [`stockpile_contents_system`]
+///
+/// An item lying inside several overlapping stockpiles counts toward the
+/// one with the lowest entity only.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StockpileContents {
+    by_stockpile: HashMap<Entity, StockpileSummary>,
+    /// Where each counted item is filed, and as what
+    filed: HashMap<Entity, (Entity, ItemType)>,
+}
+
+impl StockpileContents {
+    /// Contents of `stockpile`; empty for unknown entities
+    pub fn get(&self, stockpile: Entity) -> StockpileSummary {
+        self.by_stockpile
+            .get(&stockpile)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Items of `item_type` across every stockpile
+    pub fn total(&self, item_type: ItemType) -> usize {
+        self.by_stockpile.values().map(|s| s.count(item_type)).sum()
+    }
+
+    fn file(&mut self, item: Entity, stockpile: Option<Entity>, item_type: ItemType) {
+        self.unfile(item);
+        if let Some(stockpile) = stockpile {
+            self.by_stockpile
+                .entry(stockpile)
+                .or_default()
+                .add(item_type);
+            self.filed.insert(item, (stockpile, item_type));
+        }
+    }
+
+    fn unfile(&mut self, item: Entity) {
+        if let Some((stockpile, item_type)) = self.filed.remove(&item) {
+            if let Some(summary) = self.by_stockpile.get_mut(&stockpile) {
+                summary.remove(item_type);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.by_stockpile.clear();
+        self.filed.clear();
+    }
+}
+
+/// Contents of `stockpile` from the world's [`StockpileContents`] cache
+///
+/// Reflects the last run of [`stockpile_contents_system`]; worlds without
+/// the cache report every stockpile as empty.
+pub fn stockpile_contents(world: &World, stockpile: Entity) -> StockpileSummary {
+    world
+        .get_resource::<StockpileContents>()
+        .map(|c| c.get(stockpile))
+        .unwrap_or_default()
+}
+
+/// Keep [`StockpileContents`] in step with item movement
+///
+/// Only items that moved, appeared, were picked up (lost their
+/// `Position`) or were despawned since the last run are refiled. Adding,
+/// resizing or removing a stockpile refiles every item.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn stockpile_contents_system(
+    contents: Option<ResMut<StockpileContents>>,
+    stockpiles: Query<(Entity, &Position, Option<&ZoneBounds>), With<Stockpile>>,
+    changed_stockpiles: Query<
+        (),
+        (
+            With<Stockpile>,
+            Or<(Changed<Position>, Changed<ZoneBounds>, Added<Stockpile>)>,
+        ),
+    >,
+    mut removed_stockpiles: RemovedComponents<Stockpile>,
+    all_items: Query<(Entity, &Item, &Position)>,
+    moved_items: Query<(Entity, &Item, &Position), Or<(Changed<Position>, Changed<Item>)>>,
+    mut lost_position: RemovedComponents<Position>,
+    mut lost_item: RemovedComponents<Item>,
+) {
+    let Some(mut contents) = contents else {
+        return;
+    };
+    let holder = |x: i32, y: i32| {
+        stockpiles
+            .iter()
+            .filter(|(_, pos, bounds)| stockpile_covers(pos, *bounds, x, y))
+            .map(|(e, ..)| e)
+            .min()
+    };
+
+    let rebuild = !changed_stockpiles.is_empty() || removed_stockpiles.read().count() > 0;
+    if rebuild {
+        lost_position.clear();
+        lost_item.clear();
+        contents.clear();
+        for (item, kind, pos) in all_items.iter() {
+            contents.file(item, holder(pos.0, pos.1), kind.item_type);
+        }
+        return;
+    }
+    for item in lost_position.read().chain(lost_item.read()) {
+        contents.unfile(item);
+    }
+    for (item, kind, pos) in moved_items.iter() {
+        contents.file(item, holder(pos.0, pos.1), kind.item_type);
+    }
+}
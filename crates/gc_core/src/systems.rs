@@ -1,10 +1,24 @@
 use crate::components::*;
+use crate::determinism::{stable_order, RngAudit, RngStream};
+use crate::error::GcError;
+use crate::faults::{report_fault, FaultKind, SimFaults};
+use crate::forbid::Forbidden;
+use crate::haul_priority::{haul_priority, HaulPriorityConfig};
+use crate::health::Stalled;
 use crate::jobs::*;
+use crate::metrics::RunMetrics;
+use crate::path::{Passability, PathService};
+use crate::quality::Quality;
+use crate::stockpiles::{
+    overflow_drop_tile, revalidate_haul, stockpile_capacity, stockpile_covers, FillLimit, GiveTo,
+    HaulDestination, OverflowPolicy, StockpileContents,
+};
 use crate::world::*;
+use crate::zones::ZoneKind;
 use bevy_ecs::prelude::*;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 /// Core Systems for Goblin Camp Simulation
 ///
@@ -36,7 +50,7 @@ impl Time {
 /// Centralized deterministic RNG resource with separate streams per subsystem
 /// Ensures reproducible simulation by providing seeded RNG streams
 /// Each subsystem gets its own stream to avoid cross-contamination
-#[derive(Resource, Debug)]
+#[derive(Resource, Debug, Clone)]
 pub struct DeterministicRng {
     /// Master seed for reproducibility - can be used to recreate entire simulation
     pub master_seed: u64,
@@ -48,6 +62,10 @@ pub struct DeterministicRng {
     pub combat_rng: StdRng,
     /// RNG stream for pathfinding randomization (future use)
     pub pathfinding_rng: StdRng,
+    /// RNG stream for generated entity names
+    pub names_rng: StdRng,
+    /// Which systems took which stream through [`stream`](Self::stream)
+    audit: RngAudit,
 }
 
 impl DeterministicRng {
@@ -62,17 +80,58 @@ impl DeterministicRng {
             job_rng: StdRng::seed_from_u64(seed.wrapping_mul(0x9e3779b9).wrapping_add(1)),
             combat_rng: StdRng::seed_from_u64(seed.wrapping_mul(0x9e3779b9).wrapping_add(2)),
             pathfinding_rng: StdRng::seed_from_u64(seed.wrapping_mul(0x9e3779b9).wrapping_add(3)),
+            names_rng: StdRng::seed_from_u64(seed.wrapping_mul(0x9e3779b9).wrapping_add(4)),
+            audit: RngAudit::default(),
         }
     }
+
+    /// Borrow `stream` on behalf of `system`, recording the use
+    ///
+    /// Prefer this over the stream fields so [`audit`](Self::audit) can
+    /// tell which system consumed which stream.
+    pub fn stream(&mut self, stream: RngStream, system: &'static str) -> &mut StdRng {
+        self.audit.record(system, stream);
+        match stream {
+            RngStream::Mapgen => &mut self.mapgen_rng,
+            RngStream::Job => &mut self.job_rng,
+            RngStream::Combat => &mut self.combat_rng,
+            RngStream::Pathfinding => &mut self.pathfinding_rng,
+            RngStream::Names => &mut self.names_rng,
+        }
+    }
+
+    /// Stream uses recorded since the world was built
+    pub fn audit(&self) -> &RngAudit {
+        &self.audit
+    }
+
+    pub fn audit_mut(&mut self) -> &mut RngAudit {
+        &mut self.audit
+    }
 }
 
 /// Movement system (runs early in the schedule)
 /// Applies velocity to position for all entities with both components
 /// This is a basic kinematic system for entity movement
-pub fn movement(mut q: Query<(&mut Position, &Velocity)>) {
-    for (mut pos, vel) in q.iter_mut() {
-        pos.0 += vel.0;
-        pos.1 += vel.1;
+/// Movers with a [`Passability`] stay put rather than enter a tile their
+/// class cannot; those without one move freely.
+pub fn movement(
+    map: Option<Res<GameMap>>,
+    mut q: Query<(&mut Position, &Velocity, Option<&Passability>), Without<Stalled>>,
+) {
+    for (mut pos, vel, class) in q.iter_mut() {
+        // Leave idle entities untouched so change detection stays quiet
+        if (vel.0, vel.1) == (0, 0) {
+            continue;
+        }
+        let (x, y) = (pos.0 + vel.0, pos.1 + vel.1);
+        if let (Some(class), Some(map)) = (class, map.as_deref()) {
+            if !class.passable(map, x, y) {
+                continue;
+            }
+        }
+        pos.0 = x;
+        pos.1 = y;
     }
 }
 
@@ -81,8 +140,11 @@ pub fn movement(mut q: Query<(&mut Position, &Velocity)>) {
 /// Clamps positions to the map boundaries for safety
 pub fn confine_to_map(map: Res<GameMap>, mut q: Query<&mut Position>) {
     for mut pos in q.iter_mut() {
-        pos.0 = pos.0.clamp(0, map.width as i32 - 1);
-        pos.1 = pos.1.clamp(0, map.height as i32 - 1);
+        let clamped = Position(
+            pos.0.clamp(0, map.width as i32 - 1),
+            pos.1.clamp(0, map.height as i32 - 1),
+        );
+        pos.set_if_neq(clamped);
     }
 }
 
@@ -93,273 +155,566 @@ pub fn advance_time(mut time: ResMut<Time>) {
     time.ticks += 1;
 }
 
-/// Mining execution system - processes Mine jobs and converts Wall->Floor, spawns Stone items
-/// This is the core mining system that executes mining jobs assigned to Miner entities
-/// Miners must be adjacent to (or at) the target tile to successfully mine it
-/// Mining converts Wall tiles to Floor tiles and spawns Stone items at the mined location
-pub fn mining_execution_system(
-    mut commands: Commands,
-    mut map: ResMut<GameMap>,
-    mut active_jobs: ResMut<ActiveJobs>,
-    mut q_miners: Query<(&mut AssignedJob, &Position), With<Miner>>,
-) {
-    for (mut assigned_job, miner_pos) in q_miners.iter_mut() {
-        if let Some(job_id) = assigned_job.0 {
-            if let Some(job) = active_jobs.jobs.get(&job_id) {
-                if let JobKind::Mine { x, y } = job.kind {
-                    // Allow mining when adjacent (including same tile)
-                    // This gives miners a 3x3 area of effect around their position
-                    let dx = (miner_pos.0 - x).abs();
-                    let dy = (miner_pos.1 - y).abs();
-                    if dx <= 1 && dy <= 1 && map.get_tile(x, y) == Some(TileKind::Wall) {
-                        // Convert Wall to Floor (the primary mining action)
-                        map.set_tile(x, y, TileKind::Floor);
+/// Configuration resource for how agents travel to their work
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct MovementConfig {
+    /// Travel mode for agents without a [`MovementOverride`]
+    pub mode: MovementMode,
+}
 
-                        // Spawn a stone item at the mined location
-                        // Items are full entities with position and carriable properties
-                        commands.spawn((
-                            Item {
-                                item_type: crate::components::ItemType::Stone,
-                            },
-                            Stone,
-                            Position(x, y),
-                            Carriable,
-                            Name("Stone".to_string()),
-                        ));
+impl MovementConfig {
+    /// Travel mode for an agent, honoring its override
+    pub fn mode_for(&self, agent: Option<&MovementOverride>) -> MovementMode {
+        agent.map_or(self.mode, |o| o.0)
+    }
+}
 
-                        // Complete job - remove from active jobs and clear assignment
-                        active_jobs.jobs.remove(&job_id);
-                        assigned_job.0 = None;
-                    }
-                }
-            } else {
-                // Job missing in active jobs; clear assignment defensively
-                // This can happen if jobs are manually removed or due to system ordering
-                assigned_job.0 = None;
+/// Move `pos` toward `target` according to `mode`, routing
+/// [`MovementMode::Walk`] over `map` through the shared [`PathService`]
+///
+/// Returns true once `pos` is at `target`. Teleporting ignores terrain;
+/// stepping, and walking without a path service, heads straight for
+/// `target` but only onto walkable tiles of `map`, sliding along one axis
+/// when the diagonal is blocked. A walker with no path to `target` stays
+/// where it is rather than stepping through walls.
+pub fn travel(
+    pos: &mut Mut<Position>,
+    target: (i32, i32),
+    mode: MovementMode,
+    map: Option<&GameMap>,
+    paths: Option<&mut PathService>,
+) -> bool {
+    let here = (pos.0, pos.1);
+    let next = match (mode, map, paths) {
+        (MovementMode::Teleport, ..) => target,
+        (MovementMode::Walk, Some(map), Some(paths)) => match paths.next_step(map, here, target) {
+            Some(next) => next,
+            None => return here == target,
+        },
+        (MovementMode::Step | MovementMode::Walk, ..) => {
+            let (dx, dy) = ((target.0 - here.0).signum(), (target.1 - here.1).signum());
+            let open =
+                |(x, y): (i32, i32)| map.map_or(true, |m| Passability::Walks.passable(m, x, y));
+            let slides = [
+                (here.0 + dx, here.1 + dy),
+                (here.0 + dx, here.1),
+                (here.0, here.1 + dy),
+            ];
+            match slides.into_iter().find(|&t| t != here && open(t)) {
+                Some(next) => next,
+                None => return here == target,
             }
         }
-    }
+    };
+    pos.set_if_neq(Position(next.0, next.1));
+    next == target
+}
+
+/// Outcome of one [`HaulPhase`] step
+enum HaulStep {
+    /// Move on to this phase within the same tick
+    Continue(HaulPhase),
+    /// Stop for this tick, resuming in this phase
+    Wait(HaulPhase),
+    /// Job finished or abandoned
+    Finished,
 }
 
-/// Execute hauling jobs: move items to stockpiles using improved inventory system
-/// This is a complex system that handles item transportation from pickup to delivery
-/// Uses a multi-pass approach to avoid borrowing conflicts and ensure consistent state
-/// Supports both immediate delivery (pickup+drop in one tick) and staged hauling
-#[allow(clippy::type_complexity)]
+/// Execute hauling jobs: move items to stockpiles
+/// Each carrier holding a haul job steps through its [`HaulPhase`]
+/// (Approach, Pickup, Deliver, Drop). Travel phases take as many ticks as
+/// the carrier's [`MovementMode`] needs; a pickup ends the tick, so even a
+/// teleporting carrier delivers on the tick after it picks up. Picked-up
+/// items trade their `Position` for [`CarriedBy`] until they are dropped.
+/// Pickups match items by position through an index of pre-tick positions,
+/// built only when some carrier needs one; of several items on a tile the
+/// first in [`stable_order`] is picked up, as board revalidation assumes. A carrier finding nothing to pick up
+/// abandons the job, reporting a [`FaultKind::MissingItem`] unless the
+/// items there are [`Forbidden`]. Carriers act in stable order too, so
+/// which of two carriers sent to one tile gets the item does not depend on
+/// spawn order or archetype layout.
+///
+/// Before picking up for a stockpile haul ([`Job::to_stockpile`]) the
+/// destination is checked with [`revalidate_haul`]: a haul whose stockpile
+/// was removed or no longer accepts the item is retargeted, or abandoned
+/// with the item left where it lies and handed back to auto-haul's
+/// backlog. Drop-offs are counted in [`RunMetrics`].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn hauling_execution_system(
-    _commands: Commands,
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    mut faults: Option<ResMut<SimFaults>>,
+    config: Option<Res<MovementConfig>>,
+    map: Option<Res<GameMap>>,
+    mut paths: Option<ResMut<PathService>>,
     mut active_jobs: ResMut<ActiveJobs>,
-    mut param_set: ParamSet<(
-        Query<(&mut AssignedJob, &mut Inventory, &mut Position), (With<Carrier>, Without<Miner>)>,
-        Query<(Entity, &mut Position), (With<Item>, With<Carriable>)>,
-    )>,
+    mut throttle: Option<ResMut<ThrottleState>>,
+    mut metrics: Option<ResMut<RunMetrics>>,
+    mut q_carriers: Query<
+        (
+            Entity,
+            &mut AssignedJob,
+            &mut Inventory,
+            &mut Position,
+            Option<&mut HaulPhase>,
+            Option<&MovementOverride>,
+        ),
+        (With<Carrier>, Without<Miner>, Without<Stalled>),
+    >,
+    q_items: Query<(Entity, &Position, &Item, Has<Forbidden>), (With<Carriable>, Without<Carrier>)>,
+    q_stockpiles: Query<(Entity, &Position, &Stockpile, Option<&ZoneBounds>), Without<Carrier>>,
+    q_carried: Query<&Item>,
+    q_names: Query<&Name>,
 ) {
-    // Internal structs for tracking planned updates
-    // This approach prevents borrowing conflicts by collecting all planned changes first
+    let config = config.as_deref().copied().unwrap_or_default();
 
-    /// Planned update for a carrier entity during hauling execution
-    #[derive(Clone, Copy)]
-    struct CarrierUpdate {
-        job_id: JobId,
-        target: (i32, i32),          // Where the carrier should move
-        from: (i32, i32),            // Original pickup location
-        dropping: bool,              // Whether carrier is dropping an item this tick
-        pickup_item: Option<Entity>, // Item entity to pick up (if any)
+    // Pickups see pre-tick item positions, so index before anything moves
+    let needs_pickup = q_carriers.iter().any(|(_, assigned, inventory, ..)| {
+        inventory.0.is_none()
+            && assigned
+                .0
+                .and_then(|id| active_jobs.jobs.get(&id))
+                .is_some_and(|job| matches!(job.kind, JobKind::Haul { .. }))
+    });
+    let mut items_at: HashMap<(i32, i32), (Entity, ItemType)> = HashMap::new();
+    let mut forbidden_at: HashSet<(i32, i32)> = HashSet::new();
+    if needs_pickup {
+        let order = stable_order(
+            q_items
+                .iter()
+                .map(|(e, pos, ..)| (e, Some(pos), q_names.get(e).ok())),
+        );
+        for item in order {
+            let (_, pos, kind, forbidden) = q_items.get(item).expect("listed above");
+            if forbidden {
+                forbidden_at.insert((pos.0, pos.1));
+                continue;
+            }
+            items_at
+                .entry((pos.0, pos.1))
+                .or_insert((item, kind.item_type));
+        }
     }
+    let stockpiles: Vec<_> = if needs_pickup {
+        q_stockpiles.iter().collect()
+    } else {
+        Vec::new()
+    };
 
-    /// Planned update for an item entity being hauled
-    #[derive(Clone, Copy)]
-    struct ItemUpdate {
-        entity: Entity,
-        target: (i32, i32), // Where the item should be moved
-    }
+    let order = stable_order(
+        q_carriers
+            .iter()
+            .map(|(e, _, _, at, ..)| (e, Some(at), q_names.get(e).ok())),
+    );
+    for carrier in order {
+        let (_, mut assigned_job, mut inventory, mut carrier_pos, phase, movement) =
+            q_carriers.get_mut(carrier).expect("listed above");
+        let mode = config.mode_for(movement);
+        let haul = assigned_job
+            .0
+            .and_then(|id| match active_jobs.jobs.get(&id) {
+                Some(&Job {
+                    kind: JobKind::Haul { from, to },
+                    to_stockpile,
+                    ..
+                }) => Some((id, from, to, to_stockpile)),
+                _ => None,
+            });
+        let Some((job_id, from, mut to, to_stockpile)) = haul else {
+            if phase.is_some() {
+                commands.entity(carrier).remove::<HaulPhase>();
+            }
+            continue;
+        };
 
-    // Pre-allocate collections to avoid repeated reallocations during planning
-    let carriers_count = { param_set.p0().iter().count() };
-    let mut carrier_updates: Vec<CarrierUpdate> = Vec::with_capacity(carriers_count);
-    let mut item_updates: Vec<ItemUpdate> = Vec::with_capacity(carriers_count);
-    let mut completed_jobs: Vec<JobId> = Vec::with_capacity(carriers_count);
-    // First pass: collect carrier state and plan updates
-    // Examines all carriers with haul jobs and determines what actions to take
-    {
-        let q_carriers = param_set.p0();
-        for (assigned_job, inventory, carrier_pos) in q_carriers.iter() {
-            if let Some(job_id) = assigned_job.0 {
-                if let Some(job) = active_jobs.jobs.get(&job_id) {
-                    if let JobKind::Haul { from, to } = job.kind {
-                        if let Some(carried_item) = inventory.0 {
-                            // Carrier has item, plan to move to destination and drop it
-                            carrier_updates.push(CarrierUpdate {
-                                job_id,
-                                target: to,
-                                from,
-                                dropping: true,
-                                pickup_item: None,
-                            });
-                            item_updates.push(ItemUpdate {
-                                entity: carried_item,
-                                target: to,
-                            });
-                            // Job completes on drop
-                            completed_jobs.push(job_id);
+        // A phase left over from an interrupted haul may disagree with
+        // what the carrier holds; restart from what it actually carries
+        let carrying = inventory.0.is_some();
+        let mut current = match phase.as_deref() {
+            Some(&p) if p.carrying() == carrying => p,
+            _ => HaulPhase::initial(carrying),
+        };
+
+        let next = loop {
+            let step = match current {
+                HaulPhase::Approach => {
+                    if travel(
+                        &mut carrier_pos,
+                        from,
+                        mode,
+                        map.as_deref(),
+                        paths.as_deref_mut(),
+                    ) {
+                        HaulStep::Continue(HaulPhase::Pickup)
+                    } else {
+                        HaulStep::Wait(HaulPhase::Approach)
+                    }
+                }
+                HaulPhase::Pickup => match items_at.remove(&from) {
+                    Some((item, item_type)) => {
+                        let destination = if to_stockpile {
+                            revalidate_haul(&stockpiles, from, to, item_type)
                         } else {
-                            // Carrier needs to pick up item first
-                            // If carrier is already at the pickup location, only pick up this tick.
-                            // Otherwise, allow immediate deliver (pickup-and-drop) within one tick to satisfy
-                            // simple pipeline tests that expect single-step hauling.
-                            if carrier_pos.0 == from.0 && carrier_pos.1 == from.1 {
-                                carrier_updates.push(CarrierUpdate {
-                                    job_id,
-                                    target: from,
-                                    from,
-                                    dropping: false,
-                                    pickup_item: None,
-                                });
-                            } else {
-                                // Plan immediate delivery path for testing compatibility
-                                carrier_updates.push(CarrierUpdate {
-                                    job_id,
-                                    target: to,
-                                    from,
-                                    dropping: true,
-                                    pickup_item: None,
-                                });
+                            HaulDestination::Valid
+                        };
+                        match destination {
+                            HaulDestination::Valid => {}
+                            HaulDestination::Retarget(next) => {
+                                to = next;
+                                if let Some(Job {
+                                    kind: JobKind::Haul { to, .. },
+                                    ..
+                                }) = active_jobs.jobs.get_mut(&job_id)
+                                {
+                                    *to = next;
+                                }
+                            }
+                            HaulDestination::Gone => {
+                                // Leave it for another haul, or for auto-haul
+                                // to find it a place once one exists
+                                items_at.insert(from, (item, item_type));
+                                if let Some(throttle) = throttle.as_deref_mut() {
+                                    if !throttle.haul_backlog.contains(&item) {
+                                        throttle.haul_backlog.push(item);
+                                    }
+                                }
+                                break None;
                             }
                         }
+                        inventory.0 = Some(item);
+                        commands
+                            .entity(item)
+                            .insert(CarriedBy(carrier))
+                            .remove::<Position>();
+                        HaulStep::Wait(HaulPhase::Deliver)
                     }
-                }
-            }
-        }
-    }
-
-    // Second pass: find items to pick up for carriers that need them
-    // Matches carriers with items at their pickup locations
-    {
-        let q_items = param_set.p1();
-        for carrier_update in &mut carrier_updates {
-            if !carrier_update.dropping {
-                // Carrier needs to pick up an item
-                let pickup_pos = carrier_update.target;
-                for (item_entity, item_pos) in q_items.iter() {
-                    if item_pos.0 == pickup_pos.0 && item_pos.1 == pickup_pos.1 {
-                        // Mark that we can pick up the item this tick at pickup position
-                        carrier_update.pickup_item = Some(item_entity);
-                        break;
+                    // Forbidden since the haul was posted; leave it be
+                    None if forbidden_at.contains(&from) => HaulStep::Finished,
+                    None => {
+                        report_fault(
+                            faults.as_deref_mut(),
+                            time.as_deref(),
+                            FaultKind::MissingItem,
+                            "hauling_execution_system",
+                            Some(carrier),
+                            format!("no item to pick up at {:?}", from),
+                        );
+                        HaulStep::Finished
                     }
-                }
-            } else if carrier_update.pickup_item.is_none() {
-                // Immediate deliver path: find item at 'from' and move it to target in the same tick.
-                // This supports single-tick hauling for simple test scenarios
-                let pickup_pos = carrier_update.from;
-                for (item_entity, item_pos) in q_items.iter() {
-                    if item_pos.0 == pickup_pos.0 && item_pos.1 == pickup_pos.1 {
-                        item_updates.push(ItemUpdate {
-                            entity: item_entity,
-                            target: carrier_update.target,
-                        });
-                        completed_jobs.push(carrier_update.job_id);
-                        break;
+                },
+                HaulPhase::Deliver => {
+                    if travel(
+                        &mut carrier_pos,
+                        to,
+                        mode,
+                        map.as_deref(),
+                        paths.as_deref_mut(),
+                    ) {
+                        HaulStep::Continue(HaulPhase::Drop)
+                    } else {
+                        HaulStep::Wait(HaulPhase::Deliver)
                     }
                 }
-            }
-        }
-    }
-
-    // Build a map for O(1) lookup by JobId during application phase
-    let update_map: HashMap<JobId, CarrierUpdate> = carrier_updates
-        .iter()
-        .copied()
-        .map(|u| (u.job_id, u))
-        .collect();
-
-    // Third pass: apply carrier updates
-    // Updates carrier positions, inventories, and job assignments
-    {
-        let mut q_carriers = param_set.p0();
-        for (mut assigned_job, mut inventory, mut carrier_pos) in q_carriers.iter_mut() {
-            if let Some(job_id) = assigned_job.0 {
-                if let Some(update) = update_map.get(&job_id) {
-                    // Update carrier position to target location
-                    carrier_pos.0 = update.target.0;
-                    carrier_pos.1 = update.target.1;
-
-                    if update.dropping {
-                        // Dropping item - clear inventory and complete job
-                        inventory.0 = None;
-                        assigned_job.0 = None;
-                    } else if let Some(item_entity) = update.pickup_item {
-                        // Picking up item - add to inventory
-                        inventory.0 = Some(item_entity);
+                HaulPhase::Drop => {
+                    if let Some(carried) = inventory.0.take() {
+                        if let Some(mut item) = commands.get_entity(carried) {
+                            item.insert(Position(to.0, to.1)).remove::<CarriedBy>();
+                            if let Some(metrics) = metrics.as_deref_mut() {
+                                metrics.items_hauled += 1;
+                                if q_carried
+                                    .get(carried)
+                                    .is_ok_and(|i| i.item_type == ItemType::Stone)
+                                {
+                                    metrics.stones_hauled += 1;
+                                }
+                            }
+                        } else {
+                            GcError::InvalidEntity(carried).report(
+                                faults.as_deref_mut(),
+                                time.as_deref(),
+                                "hauling_execution_system",
+                                Some(carrier),
+                            );
+                        }
                     }
+                    HaulStep::Finished
                 }
+            };
+            match step {
+                HaulStep::Continue(p) => current = p,
+                HaulStep::Wait(p) => break Some(p),
+                HaulStep::Finished => break None,
             }
-        }
-    }
+        };
 
-    // Fourth pass: apply item position updates
-    // Moves items to their destination positions when hauled
-    {
-        let mut q_items = param_set.p1();
-        for upd in item_updates {
-            if let Ok((_, mut item_pos)) = q_items.get_mut(upd.entity) {
-                item_pos.0 = upd.target.0;
-                item_pos.1 = upd.target.1;
+        match (next, phase) {
+            (Some(next), Some(mut phase)) => {
+                phase.set_if_neq(next);
+            }
+            (Some(next), None) => {
+                commands.entity(carrier).insert(next);
+            }
+            (None, phase) => {
+                assigned_job.0 = None;
+                active_jobs.jobs.remove(&job_id);
+                if phase.is_some() {
+                    commands.entity(carrier).remove::<HaulPhase>();
+                }
             }
         }
     }
-
-    // Mark completed jobs as done in ActiveJobs
-    // Removes completed haul jobs from the active job tracker
-    for job_id in completed_jobs.into_iter() {
-        active_jobs.jobs.remove(&job_id);
-    }
 }
 
 /// Automatically create haul jobs when items are spawned and stockpiles exist
 /// This system creates hauling jobs for newly spawned items (like from mining)
 /// Uses the `Added<Item>` filter to only process items created this tick
-/// Finds the nearest stockpile and creates a haul job from item to stockpile
+/// Items that land inside a stockpile accepting them are already stored and
+/// are skipped; the rest go to the highest-priority accepting stockpile,
+/// nearest first among equals. Items no stockpile accepts go to the nearest
+/// refuse zone, unless they already lie in one
+///
+/// New items join a backlog (kept in [`ThrottleState`]); at most
+/// [`JobThrottle::hauls_per_tick`] are looked at per run, round-robin
+/// across item types so a heap of stone cannot hold up the one bar, and
+/// by [`haul_priority`] within a type. Hauls are posted highest priority
+/// first and carry it in [`Job::priority`]. Backlogged items picked up or destroyed
+/// meanwhile are dropped from it, and items re-added while still queued
+/// (a restored checkpoint re-adds everything) are not queued twice.
+///
+/// A tile with as many hauls posted from it as it holds items gets no more,
+/// so items loaded alongside their saved hauls are not hauled twice.
+///
+/// [`Forbidden`] items are left out of the backlog, and rejoin it when
+/// their forbid is lifted.
+///
+/// A stockpile with a [`FillLimit`] is full once what it stores (per
+/// [`StockpileContents`]) plus the hauls bound for it reach the limit.
+/// Items whose chosen stockpile is full follow its [`OverflowPolicy`]; one
+/// with nowhere to go waits in the backlog until room opens up.
+///
+/// [`FillLimit`]: crate::stockpiles::FillLimit
+/// [`StockpileContents`]: crate::stockpiles::StockpileContents
+/// [`OverflowPolicy`]: crate::stockpiles::OverflowPolicy
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn auto_haul_system(
     mut job_board: ResMut<JobBoard>,
+    active_jobs: Option<Res<ActiveJobs>>,
     mut rng: ResMut<DeterministicRng>,
-    q_items: Query<&Position, (With<Item>, Added<Item>)>,
-    q_stockpiles: Query<&Position, With<Stockpile>>,
+    throttle: Option<Res<JobThrottle>>,
+    state: Option<ResMut<ThrottleState>>,
+    mut detached: Local<ThrottleState>,
+    contents: Option<Res<StockpileContents>>,
+    map: Option<Res<GameMap>>,
+    time: Option<Res<Time>>,
+    priority: Option<Res<HaulPriorityConfig>>,
+    q_new: Query<Entity, Added<Item>>,
+    mut unforbidden: RemovedComponents<Forbidden>,
+    q_items: Query<(&Position, &Item, Option<&Quality>), Without<Forbidden>>,
+    q_names: Query<&Name>,
+    q_stockpiles: Query<(
+        Entity,
+        &Position,
+        &Stockpile,
+        Option<&ZoneBounds>,
+        Option<&FillLimit>,
+        Option<&GiveTo>,
+    )>,
+    q_zones: Query<(Entity, &ZoneKind, &Position, &ZoneBounds)>,
 ) {
-    // Find nearest stockpile for each new item
-    for item_pos in q_items.iter() {
-        if let Some(stockpile_pos) = find_nearest_stockpile(&q_stockpiles, item_pos) {
-            add_job(
-                &mut job_board,
-                JobKind::Haul {
-                    from: (item_pos.0, item_pos.1),
-                    to: (stockpile_pos.0, stockpile_pos.1),
-                },
-                &mut rng.job_rng,
-            );
-        }
+    let ThrottleState {
+        haul_backlog: backlog,
+        haul_since: since,
+        haul_cursor: cursor,
+        ..
+    } = match state {
+        Some(state) => state.into_inner(),
+        None => &mut *detached,
+    };
+    let now = time.map_or(0, |t| t.ticks);
+    let queued: HashSet<Entity> = backlog.iter().copied().collect();
+    let mut new = stable_order(
+        q_new
+            .iter()
+            .chain(unforbidden.read())
+            .filter(|e| !queued.contains(e))
+            .map(|e| {
+                let pos = q_items.get(e).ok().map(|(pos, ..)| pos);
+                (e, pos, q_names.get(e).ok())
+            }),
+    );
+    new.dedup();
+    backlog.extend(new);
+    backlog.retain(|&e| q_items.contains(e));
+    let priority = priority.as_deref().copied().unwrap_or_default();
+    let mut scores: HashMap<Entity, u32> = HashMap::new();
+    for &entity in backlog.iter() {
+        let (pos, item, quality) = q_items.get(entity).expect("retained above");
+        let waited = now.saturating_sub(*since.entry(entity).or_insert(now));
+        let score = haul_priority(
+            &priority,
+            map.as_deref(),
+            item.item_type,
+            quality.copied(),
+            (pos.0, pos.1),
+            waited,
+        );
+        scores.insert(entity, score);
+    }
+    since.retain(|e, _| scores.contains_key(e));
+    let throttle = throttle.as_deref().copied().unwrap_or_default();
+    let cap = throttle.allowance(throttle.hauls_per_tick, &job_board);
+    let mut by_type: BTreeMap<ItemType, Vec<Entity>> = BTreeMap::new();
+    for &entity in backlog.iter() {
+        let (_, item, _) = q_items.get(entity).expect("retained above");
+        by_type.entry(item.item_type).or_default().push(entity);
+    }
+    // Highest score first within a type, oldest first among equals
+    let by_type = by_type
+        .into_iter()
+        .map(|(t, mut queue)| {
+            queue.sort_by_key(|e| std::cmp::Reverse(scores[e]));
+            (t, VecDeque::from(queue))
+        })
+        .collect();
+    let (mut batch, last) = round_robin(by_type, cursor.as_ref(), cap);
+    *cursor = last;
+    let taken: HashSet<Entity> = batch.iter().copied().collect();
+    backlog.retain(|e| !taken.contains(e));
+    batch.sort_by_key(|e| std::cmp::Reverse(scores[e]));
+    if batch.is_empty() {
+        return;
     }
-}
-
-/// Helper function to find the nearest stockpile to an item
-/// Uses Euclidean distance to determine the closest stockpile
-/// Returns None if no stockpiles exist in the world
-fn find_nearest_stockpile(
-    stockpiles: &Query<&Position, With<Stockpile>>,
-    item_pos: &Position,
-) -> Option<Position> {
-    let mut nearest: Option<Position> = None;
-    let mut min_distance = f32::INFINITY;
-
-    for stockpile_pos in stockpiles.iter() {
-        let dx = (stockpile_pos.0 - item_pos.0) as f32;
-        let dy = (stockpile_pos.1 - item_pos.1) as f32;
-        let distance = (dx * dx + dy * dy).sqrt();
 
-        if distance < min_distance {
-            min_distance = distance;
-            nearest = Some(*stockpile_pos);
+    let mut posted: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut bound_for: Vec<(i32, i32)> = Vec::new();
+    for job in job_board
+        .0
+        .iter()
+        .chain(active_jobs.iter().flat_map(|a| a.jobs.values()))
+    {
+        if let JobKind::Haul { from, to } = job.kind {
+            *posted.entry(from).or_default() += 1;
+            bound_for.push(to);
         }
     }
+    let mut items_on: HashMap<(i32, i32), usize> = HashMap::new();
+    for (pos, ..) in q_items.iter() {
+        *items_on.entry((pos.0, pos.1)).or_default() += 1;
+    }
+    // Items each fill-limited stockpile may still take
+    let mut room: HashMap<Entity, usize> = HashMap::new();
+    for (stockpile, pos, _, bounds, limit, _) in q_stockpiles.iter() {
+        let Some(limit) = limit else {
+            continue;
+        };
+        let stored = contents.as_ref().map_or(0, |c| c.get(stockpile).total);
+        let inbound = bound_for
+            .iter()
+            .filter(|to| stockpile_covers(pos, bounds, to.0, to.1))
+            .count();
+        let free = limit
+            .limit(stockpile_capacity(bounds))
+            .saturating_sub(stored + inbound);
+        room.insert(stockpile, free);
+    }
+    let has_room = |room: &HashMap<Entity, usize>, stockpile: Entity| {
+        room.get(&stockpile).map_or(true, |&free| free > 0)
+    };
+    let mut waiting = Vec::new();
 
-    nearest
+    for (entity, (item_pos, item, _)) in batch
+        .into_iter()
+        .map(|e| (e, q_items.get(e).expect("retained above")))
+    {
+        let score = scores[&entity];
+        let tile = (item_pos.0, item_pos.1);
+        let hauls = posted.entry(tile).or_default();
+        if *hauls >= items_on.get(&tile).copied().unwrap_or(0) {
+            continue;
+        }
+        if crate::stockpiles::item_is_stored(
+            q_stockpiles.iter().map(|(_, p, s, b, ..)| (p, s, b)),
+            item_pos.0,
+            item_pos.1,
+            item.item_type,
+        ) {
+            continue;
+        }
+        if let Some((best, best_pos)) = crate::stockpiles::select_haul_destination(
+            q_stockpiles.iter().map(|(e, p, s, ..)| (e, p, s)),
+            tile,
+            item.item_type,
+        ) {
+            let (_, _, _, bounds, limit, give_to) = q_stockpiles.get(best).expect("just selected");
+            // Where the item goes, and which stockpile it fills
+            let target = match limit.map(|l| l.overflow) {
+                _ if has_room(&room, best) => Some(((best_pos.0, best_pos.1), Some(best))),
+                Some(OverflowPolicy::Spill) => give_to
+                    .into_iter()
+                    .flat_map(|g| g.0.iter())
+                    .filter_map(|&e| q_stockpiles.get(e).ok())
+                    .find(|&(e, _, s, ..)| s.accepts_item(item.item_type) && has_room(&room, e))
+                    .map(|(e, p, ..)| ((p.0, p.1), Some(e))),
+                Some(OverflowPolicy::Drop) => map.as_deref().and_then(|map| {
+                    let beside = bounds.cloned().unwrap_or(ZoneBounds::new(
+                        best_pos.0, best_pos.1, best_pos.0, best_pos.1,
+                    ));
+                    let covered: Vec<_> =
+                        q_stockpiles.iter().map(|(_, p, _, b, ..)| (p, b)).collect();
+                    overflow_drop_tile(map, &beside, &covered, tile).map(|to| (to, None))
+                }),
+                _ => crate::stockpiles::select_haul_destination(
+                    q_stockpiles
+                        .iter()
+                        .filter(|&(e, ..)| has_room(&room, e))
+                        .map(|(e, p, s, ..)| (e, p, s)),
+                    tile,
+                    item.item_type,
+                )
+                .map(|(e, p)| ((p.0, p.1), Some(e))),
+            };
+            let Some((to, filled)) = target else {
+                waiting.push(entity);
+                continue;
+            };
+            add_job(
+                &mut job_board,
+                JobKind::Haul { from: tile, to },
+                rng.stream(RngStream::Job, "auto_haul_system"),
+            );
+            let job = job_board.0.last_mut().expect("just posted");
+            job.priority = score;
+            job.to_stockpile = filled.is_some();
+            *hauls += 1;
+            if let Some(free) = filled.and_then(|e| room.get_mut(&e)) {
+                *free -= 1;
+            }
+        } else if !crate::zones::in_zone_of(
+            q_zones.iter().map(|(_, k, _, b)| (k, b)),
+            ZoneKind::Refuse,
+            item_pos.0,
+            item_pos.1,
+        ) {
+            // Nothing stores it: dump it on the nearest refuse pile
+            if let Some((_, dump)) = crate::zones::nearest_zone(
+                q_zones.iter().map(|(e, k, p, _)| (e, k, p)),
+                ZoneKind::Refuse,
+                (item_pos.0, item_pos.1),
+            ) {
+                add_job(
+                    &mut job_board,
+                    JobKind::Haul {
+                        from: (item_pos.0, item_pos.1),
+                        to: (dump.0, dump.1),
+                    },
+                    rng.stream(RngStream::Job, "auto_haul_system"),
+                );
+                job_board.0.last_mut().expect("just posted").priority = score;
+                *hauls += 1;
+            }
+        }
+    }
+    backlog.extend(waiting);
+    let queued: HashSet<Entity> = backlog.iter().copied().collect();
+    since.retain(|e, _| queued.contains(e));
 }
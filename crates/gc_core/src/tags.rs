@@ -0,0 +1,328 @@
+//! Item tags and tag queries
+//!
+//! Every [`ItemType`] carries a set of tags ("stone", "building-material",
+//! "gear", ...) listed in the [`TagRegistry`]. Filters name tags instead of
+//! item types, so a new item type only needs tagging to be picked up by
+//! every stockpile and ingredient that wants its kind of thing.
+//!
+//! A [`TagQuery`] is written as alternatives separated by `|`, each a set of
+//! tags that must all match, where `!` negates a tag:
+//!
+//! ```text
+//! building-material          any building material
+//! gear !armor                gear that is not armor
+//! gem | building-material    either
+//! ```
+//!
+//! Queries are checked against the registry when they are set
+//! ([`TagRegistry::validate`]), so a misspelled tag is an error rather than
+//! a filter that silently matches nothing.
+//!
+//! Stockpiles filter by tags through [`AcceptsTags`]; recipes and other
+//! ingredient lists use [`Ingredient`].
+
+use crate::components::{ItemType, Stockpile};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Why a tag or query was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TagError {
+    #[error("invalid tag '{0}': use lowercase letters, digits and '-'")]
+    InvalidTag(String),
+    #[error("unknown tag '{0}'")]
+    UnknownTag(String),
+    #[error("empty tag query")]
+    EmptyQuery,
+}
+
+fn check_tag_syntax(tag: &str) -> Result<(), TagError> {
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(TagError::InvalidTag(tag.to_string()))
+    }
+}
+
+/// Tags of every item type
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "BTreeMap<ItemType, BTreeSet<String>>")]
+#[serde(into = "BTreeMap<ItemType, BTreeSet<String>>")]
+pub struct TagRegistry {
+    tags: BTreeMap<ItemType, BTreeSet<String>>,
+}
+
+impl Default for TagRegistry {
+    fn default() -> Self {
+        let defs: [(ItemType, &[&str]); 11] = [
+            (ItemType::Stone, &["stone", "building-material"]),
+            (ItemType::Weapon, &["gear", "weapon"]),
+            (ItemType::Armor, &["gear", "armor"]),
+            (ItemType::Gem, &["gem", "valuable"]),
+            (ItemType::Ore, &["ore"]),
+            (ItemType::Coal, &["fuel", "coal", "flammable"]),
+            (ItemType::Wood, &["fuel", "wood", "flammable"]),
+            (ItemType::Bar, &["metal", "bar"]),
+            (ItemType::Egg, &["food", "egg"]),
+            (ItemType::Wool, &["wool", "cloth", "flammable"]),
+            (ItemType::Block, &["block", "building-material"]),
+        ];
+        Self {
+            tags: defs
+                .into_iter()
+                .map(|(t, tags)| (t, tags.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<BTreeMap<ItemType, BTreeSet<String>>> for TagRegistry {
+    type Error = TagError;
+
+    fn try_from(tags: BTreeMap<ItemType, BTreeSet<String>>) -> Result<Self, TagError> {
+        for tag in tags.values().flatten() {
+            check_tag_syntax(tag)?;
+        }
+        Ok(Self { tags })
+    }
+}
+
+impl From<TagRegistry> for BTreeMap<ItemType, BTreeSet<String>> {
+    fn from(registry: TagRegistry) -> Self {
+        registry.tags
+    }
+}
+
+impl TagRegistry {
+    /// Tags of `item_type`, sorted
+    pub fn tags_of(&self, item_type: ItemType) -> impl Iterator<Item = &str> {
+        self.tags
+            .get(&item_type)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    pub fn has_tag(&self, item_type: ItemType, tag: &str) -> bool {
+        self.tags.get(&item_type).is_some_and(|t| t.contains(tag))
+    }
+
+    /// Every tag any item type carries
+    pub fn known_tags(&self) -> BTreeSet<&str> {
+        self.tags.values().flatten().map(String::as_str).collect()
+    }
+
+    /// Add `tag` to `item_type`
+    pub fn tag(&mut self, item_type: ItemType, tag: &str) -> Result<(), TagError> {
+        check_tag_syntax(tag)?;
+        self.tags
+            .entry(item_type)
+            .or_default()
+            .insert(tag.to_string());
+        Ok(())
+    }
+
+    /// Check that every tag `query` names is carried by some item type
+    pub fn validate(&self, query: &TagQuery) -> Result<(), TagError> {
+        let known = self.known_tags();
+        match query.tags().find(|t| !known.contains(t)) {
+            Some(unknown) => Err(TagError::UnknownTag(unknown.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Item types `query` matches, in item type order
+    pub fn matching(&self, query: &TagQuery) -> Vec<ItemType> {
+        self.tags
+            .keys()
+            .copied()
+            .filter(|&t| query.matches(t, self))
+            .collect()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// One tag in a query, possibly negated
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TagTerm {
+    tag: String,
+    negated: bool,
+}
+
+/// Tag filter: any of several alternatives, each a set of required (or
+/// excluded) tags
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TagQuery {
+    alternatives: Vec<Vec<TagTerm>>,
+}
+
+impl TagQuery {
+    /// Parse query text; tags are checked for syntax only (see
+    /// [`TagRegistry::validate`])
+    pub fn parse(text: &str) -> Result<Self, TagError> {
+        let mut alternatives = Vec::new();
+        for alt in text.split('|') {
+            let mut terms = Vec::new();
+            for word in alt.split(|c: char| c.is_whitespace() || c == '&') {
+                if word.is_empty() {
+                    continue;
+                }
+                let (negated, tag) = match word.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, word),
+                };
+                check_tag_syntax(tag)?;
+                terms.push(TagTerm {
+                    tag: tag.to_string(),
+                    negated,
+                });
+            }
+            if terms.is_empty() {
+                return Err(TagError::EmptyQuery);
+            }
+            alternatives.push(terms);
+        }
+        Ok(Self { alternatives })
+    }
+
+    /// Query matching items with `tag`
+    pub fn tag(tag: &str) -> Result<Self, TagError> {
+        check_tag_syntax(tag)?;
+        Self::parse(tag)
+    }
+
+    /// Every tag the query names
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.alternatives.iter().flatten().map(|t| t.tag.as_str())
+    }
+
+    pub fn matches(&self, item_type: ItemType, registry: &TagRegistry) -> bool {
+        self.alternatives.iter().any(|terms| {
+            terms
+                .iter()
+                .all(|t| registry.has_tag(item_type, &t.tag) != t.negated)
+        })
+    }
+}
+
+impl fmt::Display for TagQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, terms) in self.alternatives.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            for (j, t) in terms.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                }
+                if t.negated {
+                    f.write_str("!")?;
+                }
+                f.write_str(&t.tag)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for TagQuery {
+    type Error = TagError;
+
+    fn try_from(text: String) -> Result<Self, TagError> {
+        Self::parse(&text)
+    }
+}
+
+impl From<TagQuery> for String {
+    fn from(query: TagQuery) -> Self {
+        query.to_string()
+    }
+}
+
+/// An ingredient: one exact item type, or any item a tag query matches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ingredient {
+    Item(ItemType),
+    Tagged(TagQuery),
+}
+
+impl Ingredient {
+    /// Ingredient matching any item with `tag`, checked against `registry`
+    pub fn tagged(tag: &str, registry: &TagRegistry) -> Result<Self, TagError> {
+        let query = TagQuery::tag(tag)?;
+        registry.validate(&query)?;
+        Ok(Ingredient::Tagged(query))
+    }
+
+    pub fn matches(&self, item_type: ItemType, registry: &TagRegistry) -> bool {
+        match self {
+            Ingredient::Item(t) => *t == item_type,
+            Ingredient::Tagged(query) => query.matches(item_type, registry),
+        }
+    }
+}
+
+impl fmt::Display for Ingredient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ingredient::Item(t) => write!(f, "{:?}", t),
+            Ingredient::Tagged(query) => write!(f, "any item with tag {}", query),
+        }
+    }
+}
+
+/// Tag filter for a stockpile
+///
+/// [`stockpile_tag_filter_system`] keeps the stockpile's
+/// [`accepts`](Stockpile::accepts) list in step with the query and the
+/// registry.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct AcceptsTags(pub TagQuery);
+
+impl AcceptsTags {
+    /// Parse `text` and validate it against `registry`
+    pub fn new(text: &str, registry: &TagRegistry) -> Result<Self, TagError> {
+        let query = TagQuery::parse(text)?;
+        registry.validate(&query)?;
+        Ok(Self(query))
+    }
+}
+
+/// Resolve tag filters into stockpile item type lists
+///
+/// Runs whenever a filter is set or the registry changes; stockpiles
+/// without [`AcceptsTags`] keep whatever list they have.
+pub fn stockpile_tag_filter_system(
+    registry: Option<Res<TagRegistry>>,
+    mut stockpiles: Query<(Ref<AcceptsTags>, &mut Stockpile)>,
+) {
+    let registry_changed = registry.as_ref().is_some_and(|r| r.is_changed());
+    let fallback;
+    let registry = match registry.as_deref() {
+        Some(r) => r,
+        None => {
+            fallback = TagRegistry::default();
+            &fallback
+        }
+    };
+    for (filter, mut stockpile) in stockpiles.iter_mut() {
+        if registry_changed || filter.is_changed() {
+            stockpile.accepts = Some(registry.matching(&filter.0));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Entity telemetry: live entity counts by category, and leak alerts
+//!
+//! [`entity_telemetry_system`] counts entities into [`EntityCategory`]
+//! buckets every tick. Some buckets should stay small or shrink back on
+//! their own: consumed designations are cleaned up, items are always on the
+//! map or held by someone. A bucket that keeps growing without ever falling
+//! is how a leak looks, so each category may grow at most its
+//! [`TelemetryConfig`] limit over a run of ticks in which it never fell;
+//! past that a [`LeakAlert`] is raised and stays up until the count drops.
+//!
+//! Counts and alerts reach `ViewMetrics`, so they show up wherever metrics
+//! go (RPC events, the REPL `telemetry` command, the TUI entity panel).
+
+use crate::components::{
+    AssignedJob, CarriedBy, Dead, DesignationLifecycle, DesignationState, Item, ZoneBounds,
+};
+use crate::designations::MineDesignation;
+use crate::equipment::EquippedBy;
+use crate::systems::Time;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Bucket an entity is counted in; each entity lands in exactly one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityCategory {
+    /// Living entities that take jobs
+    Workers,
+    Corpses,
+    /// Items lying on the map, stockpiled or not
+    LooseItems,
+    /// Items carried or worn
+    HeldItems,
+    /// Items with no position and no holder; unreachable for good
+    OrphanItems,
+    /// Mine designations still waiting or being worked
+    Designations,
+    /// Mine designations whose work is done but which were never removed
+    ConsumedDesignations,
+    Zones,
+    Other,
+}
+
+impl EntityCategory {
+    pub const ALL: [EntityCategory; 9] = [
+        EntityCategory::Workers,
+        EntityCategory::Corpses,
+        EntityCategory::LooseItems,
+        EntityCategory::HeldItems,
+        EntityCategory::OrphanItems,
+        EntityCategory::Designations,
+        EntityCategory::ConsumedDesignations,
+        EntityCategory::Zones,
+        EntityCategory::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EntityCategory::Workers => "workers",
+            EntityCategory::Corpses => "corpses",
+            EntityCategory::LooseItems => "loose_items",
+            EntityCategory::HeldItems => "held_items",
+            EntityCategory::OrphanItems => "orphan_items",
+            EntityCategory::Designations => "designations",
+            EntityCategory::ConsumedDesignations => "consumed_designations",
+            EntityCategory::Zones => "zones",
+            EntityCategory::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for EntityCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// How far each category may grow before it is flagged
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryConfig {
+    /// Most a category may grow over a run of ticks in which it never fell;
+    /// categories not listed are counted but never flagged
+    pub growth_limits: BTreeMap<EntityCategory, u64>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            growth_limits: BTreeMap::from([
+                (EntityCategory::OrphanItems, 0),
+                (EntityCategory::ConsumedDesignations, 100),
+                (EntityCategory::Corpses, 200),
+                (EntityCategory::Other, 1000),
+            ]),
+        }
+    }
+}
+
+/// A category that grew past its limit without falling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeakAlert {
+    pub category: EntityCategory,
+    /// Count when the run of growth started
+    pub from: u64,
+    /// Latest count
+    pub to: u64,
+    /// Tick the run started at
+    pub since: u64,
+}
+
+impl fmt::Display for LeakAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} grew from {} to {} since tick {} without falling",
+            self.category, self.from, self.to, self.since
+        )
+    }
+}
+
+/// Where a category's current run of growth started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GrowthRun {
+    from: u64,
+    since: u64,
+}
+
+/// Latest per-category counts and the runs of growth behind them
+#[derive(Resource, Debug, Clone, Default)]
+pub struct EntityTelemetry {
+    counts: BTreeMap<EntityCategory, u64>,
+    runs: BTreeMap<EntityCategory, GrowthRun>,
+    ticks: u64,
+}
+
+impl EntityTelemetry {
+    /// Count of `category` at the last sample
+    pub fn count(&self, category: EntityCategory) -> u64 {
+        self.counts.get(&category).copied().unwrap_or(0)
+    }
+
+    /// Every category and its count, in [`EntityCategory::ALL`] order
+    pub fn counts(&self) -> impl Iterator<Item = (EntityCategory, u64)> + '_ {
+        EntityCategory::ALL.into_iter().map(|c| (c, self.count(c)))
+    }
+
+    /// Samples taken so far
+    pub fn samples(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Categories past their growth limit, in category order
+    pub fn leaks(&self, config: &TelemetryConfig) -> Vec<LeakAlert> {
+        config
+            .growth_limits
+            .iter()
+            .filter_map(|(&category, &limit)| {
+                let run = self.runs.get(&category)?;
+                let to = self.count(category);
+                (to.saturating_sub(run.from) > limit).then_some(LeakAlert {
+                    category,
+                    from: run.from,
+                    to,
+                    since: run.since,
+                })
+            })
+            .collect()
+    }
+
+    /// Record one sample of counts taken at `tick`
+    pub fn record(&mut self, tick: u64, counts: BTreeMap<EntityCategory, u64>) {
+        for category in EntityCategory::ALL {
+            let count = counts.get(&category).copied().unwrap_or(0);
+            let fell = count < self.count(category);
+            let run = self.runs.entry(category).or_insert(GrowthRun {
+                from: count,
+                since: tick,
+            });
+            if fell {
+                *run = GrowthRun {
+                    from: count,
+                    since: tick,
+                };
+            }
+        }
+        self.counts = counts;
+        self.ticks += 1;
+    }
+}
+
+/// Count live entities by category into [`EntityTelemetry`]
+#[allow(clippy::type_complexity)]
+pub fn entity_telemetry_system(
+    time: Option<Res<Time>>,
+    telemetry: Option<ResMut<EntityTelemetry>>,
+    q: Query<(
+        Has<Item>,
+        Has<Position>,
+        Has<CarriedBy>,
+        Has<EquippedBy>,
+        Option<&DesignationLifecycle>,
+        Has<MineDesignation>,
+        Has<ZoneBounds>,
+        Has<Dead>,
+        Has<AssignedJob>,
+    )>,
+) {
+    let Some(mut telemetry) = telemetry else {
+        return;
+    };
+    let mut counts = BTreeMap::new();
+    for (item, placed, carried, equipped, lifecycle, designation, zone, dead, worker) in &q {
+        let category = if item {
+            match (placed, carried || equipped) {
+                (true, _) => EntityCategory::LooseItems,
+                (false, true) => EntityCategory::HeldItems,
+                (false, false) => EntityCategory::OrphanItems,
+            }
+        } else if designation {
+            match lifecycle.map(|l| l.0) {
+                Some(DesignationState::Consumed) => EntityCategory::ConsumedDesignations,
+                _ => EntityCategory::Designations,
+            }
+        } else if dead {
+            EntityCategory::Corpses
+        } else if worker {
+            EntityCategory::Workers
+        } else if zone {
+            EntityCategory::Zones
+        } else {
+            EntityCategory::Other
+        };
+        *counts.entry(category).or_insert(0) += 1;
+    }
+    telemetry.record(time.map_or(0, |t| t.ticks), counts);
+}
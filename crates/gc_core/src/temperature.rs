@@ -0,0 +1,344 @@
+//! Room temperature, insulation, comfort and food spoilage
+//!
+//! [`temperature_system`] keeps a [`TemperatureMap`] in hundredths of a degree.
+//! Tiles open to the sky (see [`outdoor_tiles`]) follow the outdoor
+//! temperature: the site's [`AmbientTemperature`], less
+//! [`TemperatureConfig::night_drop`] after dark. Lava tiles stay at
+//! [`TemperatureConfig::lava`]. Everything else (enclosed rooms and the
+//! rock around them) relaxes toward its neighbours, so a room slowly drifts
+//! to the temperature of what surrounds it and evens out day and night.
+//!
+//! Heat moves between orthogonal neighbours. Between two open tiles it moves
+//! freely; walls and closed doors insulate, a closed door less than a wall.
+//! The relaxation runs at most [`TemperatureConfig::iterations`] sweeps per
+//! tick in integer arithmetic, reading the previous sweep only. It stops
+//! once a sweep changes nothing and stays stopped until the map, a door or
+//! the outdoor temperature changes. Runs are deterministic.
+//!
+//! Goblins standing somewhere colder or hotter than the comfort range lose
+//! [`Mood`] every [`TemperatureConfig::mood_interval`] ticks
+//! ([`temperature_comfort_system`]). Food lying on the ground gathers
+//! [`Spoilage`] faster the warmer its tile is, not at all when frozen, and
+//! rots away at [`TemperatureConfig::spoil_after`] ([`spoilage_system`]).
+
+use crate::burial::is_goblin;
+use crate::components::{Dead, Faction, Goblin, Item, Mood};
+use crate::fov::is_opaque;
+use crate::lighting::{outdoor_tiles, DayCycle};
+use crate::mechanisms::Door;
+use crate::overmap::AmbientTemperature;
+use crate::systems::Time;
+use crate::tags::TagRegistry;
+use crate::world::{GameMap, Position, TileKind};
+use bevy_ecs::prelude::*;
+use std::collections::HashSet;
+
+/// Share of the difference that crosses a link per sweep, in sixteenths
+const OPEN_LINK: i32 = 4;
+const DOOR_LINK: i32 = 2;
+const WALL_LINK: i32 = 1;
+
+/// Outdoor swing, lava heat, relaxation budget, comfort and spoilage
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemperatureConfig {
+    /// Relaxation sweeps per tick at most
+    pub iterations: u32,
+    /// Degrees the outdoors cools by at night
+    pub night_drop: i32,
+    /// Degrees of a lava tile
+    pub lava: i32,
+    /// Coldest comfortable temperature, in degrees
+    pub comfort_min: i32,
+    /// Hottest comfortable temperature, in degrees
+    pub comfort_max: i32,
+    /// Ticks between comfort checks
+    pub mood_interval: u64,
+    /// Mood lost per check out of comfort, plus as much again for every
+    /// further 10 degrees
+    pub mood_penalty: i16,
+    /// Spoilage at which food rots away
+    pub spoil_after: u32,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 4,
+            night_drop: 8,
+            lava: 1000,
+            comfort_min: 10,
+            comfort_max: 30,
+            mood_interval: 100,
+            mood_penalty: 10,
+            spoil_after: 4800,
+        }
+    }
+}
+
+impl TemperatureConfig {
+    /// Spoilage food gains per tick at `celsius`: none when frozen, one up
+    /// to 10 degrees and one more for every 10 above that
+    pub fn spoil_rate(&self, celsius: i32) -> u32 {
+        if celsius <= 0 {
+            0
+        } else {
+            1 + (celsius - 10).max(0) as u32 / 10
+        }
+    }
+
+    /// Mood lost per check at `celsius`; zero within the comfort range
+    pub fn discomfort(&self, celsius: i32) -> i16 {
+        let off = if celsius < self.comfort_min {
+            self.comfort_min - celsius
+        } else if celsius > self.comfort_max {
+            celsius - self.comfort_max
+        } else {
+            return 0;
+        };
+        let steps = (1 + off / 10).min(i16::MAX as i32) as i16;
+        self.mood_penalty.saturating_mul(steps)
+    }
+}
+
+/// Temperature of every tile, in hundredths of a degree
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemperatureMap {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major hundredths of a degree
+    pub hundredths: Vec<i32>,
+    /// Row-major: tiles that follow the outdoor temperature
+    pub outdoors: Vec<bool>,
+    /// Outdoor temperature the map was last relaxed against, in hundredths
+    pub outside: i32,
+    /// The last sweep changed nothing; no work until something does
+    pub settled: bool,
+}
+
+impl TemperatureMap {
+    /// A map of `map`'s size at `outside` hundredths everywhere
+    pub fn uniform(map: &GameMap, outside: i32) -> Self {
+        Self {
+            width: map.width,
+            height: map.height,
+            hundredths: vec![outside; map.tiles.len()],
+            outdoors: outdoor_tiles(map),
+            outside,
+            settled: false,
+        }
+    }
+
+    /// Tenths of a degree at the tile, `None` off the map
+    pub fn hundredths_at(&self, x: i32, y: i32) -> Option<i32> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        self.hundredths
+            .get((y as u32 * self.width + x as u32) as usize)
+            .copied()
+    }
+
+    /// Whole degrees at the tile, rounded toward zero
+    pub fn celsius(&self, x: i32, y: i32) -> Option<i32> {
+        self.hundredths_at(x, y).map(|t| t / 100)
+    }
+
+    /// Run up to `iterations` relaxation sweeps over `map`, with closed
+    /// doors at `doors` and lava at `lava` hundredths; returns the sweeps run
+    pub fn relax(
+        &mut self,
+        map: &GameMap,
+        doors: &HashSet<(i32, i32)>,
+        lava: i32,
+        iterations: u32,
+    ) -> u32 {
+        let (w, h) = (self.width as i32, self.height as i32);
+        for (i, kind) in map.tiles.iter().enumerate() {
+            if *kind == TileKind::Lava {
+                self.hundredths[i] = lava;
+            } else if self.outdoors[i] {
+                self.hundredths[i] = self.outside;
+            }
+        }
+        let link = |a: usize, b: usize, pa: (i32, i32), pb: (i32, i32)| {
+            if doors.contains(&pa) || doors.contains(&pb) {
+                DOOR_LINK
+            } else if is_opaque(map.tiles[a]) || is_opaque(map.tiles[b]) {
+                WALL_LINK
+            } else {
+                OPEN_LINK
+            }
+        };
+        let mut next = self.hundredths.clone();
+        for sweep in 0..iterations {
+            let mut changed = false;
+            for y in 0..h {
+                for x in 0..w {
+                    let i = (y * w + x) as usize;
+                    if self.outdoors[i] || map.tiles[i] == TileKind::Lava {
+                        continue;
+                    }
+                    let here = self.hundredths[i];
+                    let mut flow = 0;
+                    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        if let Some(j) = map.idx(x + dx, y + dy) {
+                            let k = link(i, j, (x, y), (x + dx, y + dy));
+                            flow += k * (self.hundredths[j] - here);
+                        }
+                    }
+                    let value = here + flow / 16;
+                    changed |= value != self.hundredths[i];
+                    next[i] = value;
+                }
+            }
+            // Pinned tiles agree in both buffers; the rest were all written
+            std::mem::swap(&mut self.hundredths, &mut next);
+            if !changed {
+                self.settled = true;
+                return sweep + 1;
+            }
+        }
+        iterations
+    }
+}
+
+/// Food that has started to go off
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spoilage(pub u32);
+
+/// Relax the [`TemperatureMap`] toward this tick's outdoor temperature
+///
+/// The map starts out at the outdoor temperature everywhere. Which tiles are
+/// outdoors is worked out again whenever the map changes.
+#[allow(clippy::too_many_arguments)]
+pub fn temperature_system(
+    map: Res<GameMap>,
+    time: Option<Res<Time>>,
+    cycle: Option<Res<DayCycle>>,
+    ambient: Option<Res<AmbientTemperature>>,
+    config: Option<Res<TemperatureConfig>>,
+    mut temps: ResMut<TemperatureMap>,
+    doors: Query<(&Position, Ref<Door>)>,
+    mut removed: RemovedComponents<Door>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let tick = time.map_or(0, |t| t.ticks);
+    let day = cycle.as_deref().copied().unwrap_or_default().is_day(tick);
+    let celsius = ambient.map_or(0, |a| a.0) - if day { 0 } else { config.night_drop };
+    let outside = celsius.saturating_mul(100);
+
+    if temps.hundredths.len() != map.tiles.len() || temps.width != map.width {
+        *temps = TemperatureMap::uniform(&map, outside);
+    } else if map.is_changed() {
+        temps.outdoors = outdoor_tiles(&map);
+        temps.settled = false;
+    }
+    if temps.outside != outside {
+        temps.outside = outside;
+        temps.settled = false;
+    }
+    if removed.read().count() > 0 || doors.iter().any(|(_, d)| d.is_changed()) {
+        temps.settled = false;
+    }
+    if temps.settled {
+        return;
+    }
+    let closed: HashSet<(i32, i32)> = doors
+        .iter()
+        .filter(|(_, d)| !d.open)
+        .map(|(p, _)| (p.0, p.1))
+        .collect();
+    temps.relax(
+        &map,
+        &closed,
+        config.lava.saturating_mul(100),
+        config.iterations,
+    );
+}
+
+/// Cost living goblins mood for standing somewhere too cold or too hot
+#[allow(clippy::type_complexity)]
+pub fn temperature_comfort_system(
+    mut commands: Commands,
+    time: Option<Res<Time>>,
+    config: Option<Res<TemperatureConfig>>,
+    temps: Res<TemperatureMap>,
+    mut living: Query<
+        (
+            Entity,
+            &Position,
+            Has<Goblin>,
+            Option<&Faction>,
+            Option<&mut Mood>,
+        ),
+        Without<Dead>,
+    >,
+) {
+    let tick = time.map_or(0, |t| t.ticks);
+    let config = config.as_deref().copied().unwrap_or_default();
+    if tick == 0 || config.mood_interval == 0 || tick % config.mood_interval != 0 {
+        return;
+    }
+    for (entity, pos, goblin, faction, mood) in living.iter_mut() {
+        if !is_goblin(goblin, faction) {
+            continue;
+        }
+        let Some(celsius) = temps.celsius(pos.0, pos.1) else {
+            continue;
+        };
+        let penalty = config.discomfort(celsius);
+        if penalty == 0 {
+            continue;
+        }
+        match mood {
+            Some(mut mood) => mood.shift(-penalty),
+            None => {
+                let mut mood = Mood::default();
+                mood.shift(-penalty);
+                commands.entity(entity).insert(mood);
+            }
+        }
+    }
+}
+
+/// Age food lying on the ground by its tile's temperature and remove what
+/// has rotted
+///
+/// Food is whatever the [`TagRegistry`] tags "food". Carried food keeps
+/// until it is put down again.
+pub fn spoilage_system(
+    mut commands: Commands,
+    config: Option<Res<TemperatureConfig>>,
+    registry: Option<Res<TagRegistry>>,
+    temps: Res<TemperatureMap>,
+    mut items: Query<(Entity, &Position, &Item, Option<&mut Spoilage>)>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let default_registry;
+    let registry = match registry.as_deref() {
+        Some(registry) => registry,
+        None => {
+            default_registry = TagRegistry::default();
+            &default_registry
+        }
+    };
+    for (entity, pos, item, spoilage) in items.iter_mut() {
+        if !registry.has_tag(item.item_type, "food") {
+            continue;
+        }
+        let rate = temps
+            .celsius(pos.0, pos.1)
+            .map_or(0, |c| config.spoil_rate(c));
+        if rate == 0 {
+            continue;
+        }
+        let total = spoilage.as_ref().map_or(0, |s| s.0).saturating_add(rate);
+        if total >= config.spoil_after {
+            commands.entity(entity).despawn();
+        } else if let Some(mut spoilage) = spoilage {
+            spoilage.0 = total;
+        } else {
+            commands.entity(entity).insert(Spoilage(total));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Runtime switches for whole subsystems, for debugging
+//!
+//! [`SystemToggles`] lists the [`Subsystem`]s that are switched off. The
+//! default schedule gates their systems with [`subsystem_enabled`], so a
+//! subsystem can be stopped and restarted mid-run (from the TUI menu or
+//! `gc_cli --disable`) to rule it out when chasing a bug, without building
+//! a different schedule. A world without the resource runs everything.
+//!
+//! A subsystem switched back on picks up where it stopped: its systems'
+//! change detection still covers everything that happened while it was off,
+//! so e.g. items dropped meanwhile are hauled.
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// A subsystem that can be switched off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    /// Hauling jobs posted for loose items
+    AutoHaul,
+    /// Attack resolution: attacks neither hit nor miss
+    Combat,
+    /// Hurt workers going to rest, and regeneration
+    Needs,
+    /// Daylight and temperature: the light map stops following the
+    /// day/night cycle, and tile temperatures, comfort and spoilage freeze
+    Weather,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 4] = [
+        Subsystem::AutoHaul,
+        Subsystem::Combat,
+        Subsystem::Needs,
+        Subsystem::Weather,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Subsystem::AutoHaul => "auto-haul",
+            Subsystem::Combat => "combat",
+            Subsystem::Needs => "needs",
+            Subsystem::Weather => "weather",
+        }
+    }
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Subsystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase().replace('_', "-");
+        Subsystem::ALL
+            .into_iter()
+            .find(|sub| sub.label() == name)
+            .ok_or_else(|| format!("unknown subsystem '{}'", s))
+    }
+}
+
+/// Subsystems switched off at runtime
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemToggles {
+    disabled: BTreeSet<Subsystem>,
+}
+
+impl SystemToggles {
+    /// Everything on except `disabled`
+    pub fn with_disabled(disabled: impl IntoIterator<Item = Subsystem>) -> Self {
+        Self {
+            disabled: disabled.into_iter().collect(),
+        }
+    }
+
+    pub fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        !self.disabled.contains(&subsystem)
+    }
+
+    pub fn set(&mut self, subsystem: Subsystem, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&subsystem);
+        } else {
+            self.disabled.insert(subsystem);
+        }
+    }
+
+    /// Flip `subsystem`; returns whether it is now enabled
+    pub fn toggle(&mut self, subsystem: Subsystem) -> bool {
+        let enabled = !self.is_enabled(subsystem);
+        self.set(subsystem, enabled);
+        enabled
+    }
+
+    /// Switched-off subsystems, in [`Subsystem::ALL`] order
+    pub fn disabled(&self) -> impl Iterator<Item = Subsystem> + '_ {
+        self.disabled.iter().copied()
+    }
+}
+
+/// Whether `subsystem` may run in `world`
+pub fn is_enabled(world: &World, subsystem: Subsystem) -> bool {
+    world
+        .get_resource::<SystemToggles>()
+        .map_or(true, |t| t.is_enabled(subsystem))
+}
+
+/// Run condition: `subsystem` is not switched off
+pub fn subsystem_enabled(
+    subsystem: Subsystem,
+) -> impl Fn(Option<Res<SystemToggles>>) -> bool + Clone {
+    move |toggles| toggles.map_or(true, |t| t.is_enabled(subsystem))
+}
@@ -0,0 +1,117 @@
+//! Traffic map: how often each tile is walked over
+//!
+//! [`traffic_system`] counts a step on a tile whenever an agent's position
+//! changes to it, so congestion shows up as hot tiles along shared routes.
+//! Counts halve every [`TrafficConfig::decay_every`] ticks, keeping the map
+//! weighted toward recent traffic; tiles that decay to nothing are dropped.
+//! Teleporting agents only mark the tile they arrive on.
+//!
+//! The map is a debugging aid: it is not saved, and nothing in the
+//! simulation reads it. Renderers get it through the world view.
+
+use crate::components::Item;
+use crate::systems::Time;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+use std::collections::BTreeMap;
+
+/// How quickly old traffic fades
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrafficConfig {
+    /// Ticks between halvings of every count; 0 never decays
+    pub decay_every: u64,
+}
+
+impl Default for TrafficConfig {
+    fn default() -> Self {
+        Self { decay_every: 100 }
+    }
+}
+
+/// Decaying per-tile step counts
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrafficMap {
+    counts: BTreeMap<(i32, i32), u32>,
+}
+
+impl TrafficMap {
+    /// Count one step onto (x, y)
+    pub fn record(&mut self, x: i32, y: i32) {
+        let count = self.counts.entry((x, y)).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Steps onto (x, y), as decayed so far
+    pub fn count(&self, x: i32, y: i32) -> u32 {
+        self.counts.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    /// Highest count on any tile
+    pub fn max(&self) -> u32 {
+        self.counts.values().copied().max().unwrap_or(0)
+    }
+
+    /// Tiles with any traffic and their counts, sorted by position
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), u32)> + '_ {
+        self.counts.iter().map(|(&pos, &count)| (pos, count))
+    }
+
+    /// The `n` busiest tiles, busiest first (ties by position)
+    pub fn hottest(&self, n: usize) -> Vec<((i32, i32), u32)> {
+        let mut tiles: Vec<_> = self.iter().collect();
+        tiles.sort_by_key(|&(pos, count)| (std::cmp::Reverse(count), pos));
+        tiles.truncate(n);
+        tiles
+    }
+
+    /// Halve every count, dropping tiles that reach zero
+    pub fn decay(&mut self) {
+        self.counts.retain(|_, count| {
+            *count /= 2;
+            *count > 0
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+/// Heat of a tile with `count` steps on a 0-9 scale, relative to the
+/// `busiest` tile: 0 for no traffic, otherwise at least 1
+pub fn heat_level(count: u32, busiest: u32) -> u32 {
+    if count == 0 {
+        return 0;
+    }
+    let level = (u64::from(count) * 9).div_ceil(u64::from(busiest.max(count)));
+    level.clamp(1, 9) as u32
+}
+
+/// Count this tick's moves onto the [`TrafficMap`], then decay it when due
+///
+/// Items are moved by their carriers and entities that just appeared have
+/// not walked anywhere, so neither counts.
+pub fn traffic_system(
+    time: Option<Res<Time>>,
+    config: Option<Res<TrafficConfig>>,
+    traffic: Option<ResMut<TrafficMap>>,
+    q: Query<Ref<Position>, Without<Item>>,
+) {
+    let Some(mut traffic) = traffic else {
+        return;
+    };
+    for pos in q.iter() {
+        if pos.is_changed() && !pos.is_added() {
+            traffic.record(pos.0, pos.1);
+        }
+    }
+    let decay_every = config.map_or(TrafficConfig::default().decay_every, |c| c.decay_every);
+    let tick = time.map_or(0, |t| t.ticks);
+    if decay_every > 0 && tick > 0 && tick % decay_every == 0 {
+        traffic.decay();
+    }
+}
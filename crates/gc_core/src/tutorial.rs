@@ -0,0 +1,321 @@
+//! Tutorials: scripted scenarios that walk a new player through the basics
+//!
+//! A [`Tutorial`] is a data file: editor strokes that set the scene, then
+//! steps, each a hint and a [`Goal`] read off the world state. Tutorials
+//! are JSON:
+//!
+//! ```json
+//! {
+//!   "name": "First dig",
+//!   "setup": [
+//!     { "brush": "wall", "a": [8, 3], "b": [11, 6], "material": "soil" },
+//!     { "brush": "miner", "a": [4, 5] }
+//!   ],
+//!   "steps": [
+//!     { "hint": "Designate the wall at (8, 4) for mining",
+//!       "goal": { "type": "designate", "a": [8, 4] } }
+//!   ]
+//! }
+//! ```
+//!
+//! [`start_tutorial`] paints the setup and makes the tutorial active;
+//! [`tutorial_system`] then announces each step's hint as it comes up and
+//! moves on once its goal holds, so hints reach the player through the
+//! same alert bar as everything else. Progress is not saved.
+//!
+//! Built-in tutorials ship as data files under `tutorials/` and are found
+//! by name with [`builtin_tutorial`].
+
+use crate::announcements::{Announcements, Severity};
+use crate::components::{ItemType, ZoneBounds};
+use crate::designations::MineDesignation;
+use crate::editor::{paint, place_stockpile, Brush};
+use crate::stockpiles::StockpileContents;
+use crate::systems::Time;
+use crate::world::{GameMap, Material, MaterialMap, Position, TileKind};
+use crate::zones::ZoneKind;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Built-in tutorials by name, as shipped JSON
+pub const BUILTIN_TUTORIALS: [(&str, &str); 1] =
+    [("first_dig", include_str!("../tutorials/first_dig.json"))];
+
+/// Why a tutorial could not be read or started
+#[derive(Debug, thiserror::Error)]
+pub enum TutorialError {
+    #[error("invalid tutorial JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{what} at ({x}, {y}) is outside the {width}x{height} map")]
+    OutOfBounds {
+        what: String,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    #[error("tutorial has no steps")]
+    NoSteps,
+}
+
+/// A scripted scenario: how to set the scene and what to ask of the player
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tutorial {
+    pub name: String,
+    /// Strokes applied in order by [`start_tutorial`]
+    #[serde(default)]
+    pub setup: Vec<SetupStroke>,
+    pub steps: Vec<TutorialStep>,
+}
+
+/// One editor stroke of a tutorial's setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetupStroke {
+    pub brush: Brush,
+    pub a: (i32, i32),
+    /// Far corner: tile brushes fill the rectangle, stockpiles need it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b: Option<(i32, i32)>,
+    /// Material laid under the painted tiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<Material>,
+}
+
+/// A hint and the goal that ends its step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TutorialStep {
+    pub hint: String,
+    pub goal: Goal,
+}
+
+/// What the player has to bring about; rectangles are inclusive and `b`
+/// defaults to `a`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Goal {
+    /// Every wall in the rectangle has a mining designation
+    Designate {
+        a: (i32, i32),
+        #[serde(default)]
+        b: Option<(i32, i32)>,
+    },
+    /// No wall is left in the rectangle
+    Mine {
+        a: (i32, i32),
+        #[serde(default)]
+        b: Option<(i32, i32)>,
+    },
+    /// A zone of `kind` overlaps the rectangle
+    Zone {
+        kind: ZoneKind,
+        a: (i32, i32),
+        #[serde(default)]
+        b: Option<(i32, i32)>,
+    },
+    /// Stockpiles hold at least `count` items of `item`
+    Store { item: ItemType, count: usize },
+    /// `ticks` ticks pass after the hint
+    Wait { ticks: u64 },
+}
+
+impl Goal {
+    /// The goal's rectangle, if it has one
+    fn area(&self) -> Option<ZoneBounds> {
+        match *self {
+            Goal::Designate { a, b } | Goal::Mine { a, b } | Goal::Zone { a, b, .. } => {
+                Some(rect(a, b))
+            }
+            Goal::Store { .. } | Goal::Wait { .. } => None,
+        }
+    }
+}
+
+/// The active tutorial and how far it has got
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TutorialProgress {
+    /// None when no tutorial is being played
+    pub tutorial: Option<Tutorial>,
+    /// Index of the current step; the step count once finished
+    pub step: usize,
+    /// Tick the current step's hint was announced
+    pub since: u64,
+    /// Whether the current step's hint (or the closing message) is out
+    pub announced: bool,
+}
+
+impl TutorialProgress {
+    pub fn finished(&self) -> bool {
+        self.tutorial
+            .as_ref()
+            .is_some_and(|t| self.step >= t.steps.len())
+    }
+}
+
+fn rect(a: (i32, i32), b: Option<(i32, i32)>) -> ZoneBounds {
+    let b = b.unwrap_or(a);
+    ZoneBounds::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1))
+}
+
+fn tiles(area: &ZoneBounds) -> impl Iterator<Item = (i32, i32)> {
+    let (min_x, max_x) = (area.min_x, area.max_x);
+    (area.min_y..=area.max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+}
+
+pub fn decode_tutorial_json(s: &str) -> Result<Tutorial, TutorialError> {
+    Ok(serde_json::from_str(s)?)
+}
+
+/// The built-in tutorial called `name`
+pub fn builtin_tutorial(name: &str) -> Option<Tutorial> {
+    BUILTIN_TUTORIALS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, json)| decode_tutorial_json(json).expect("built-in tutorials parse"))
+}
+
+/// Check that every stroke and goal of `tutorial` lies on the map
+pub fn validate_tutorial(world: &World, tutorial: &Tutorial) -> Result<(), TutorialError> {
+    if tutorial.steps.is_empty() {
+        return Err(TutorialError::NoSteps);
+    }
+    let (width, height) = world
+        .get_resource::<GameMap>()
+        .map_or((0, 0), |m| (m.width, m.height));
+    let check = |what: &str, (x, y): (i32, i32)| {
+        if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+            return Err(TutorialError::OutOfBounds {
+                what: what.to_string(),
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+        Ok(())
+    };
+    for stroke in &tutorial.setup {
+        let what = format!("{} stroke", stroke.brush.label());
+        check(&what, stroke.a)?;
+        check(&what, stroke.b.unwrap_or(stroke.a))?;
+    }
+    for (i, step) in tutorial.steps.iter().enumerate() {
+        if let Some(area) = step.goal.area() {
+            let what = format!("step {} goal", i + 1);
+            check(&what, (area.min_x, area.min_y))?;
+            check(&what, (area.max_x, area.max_y))?;
+        }
+    }
+    Ok(())
+}
+
+/// Paint `tutorial`'s setup and make it the active tutorial
+///
+/// Nothing is painted unless the whole tutorial fits the map. Must only be
+/// called between schedule runs.
+pub fn start_tutorial(world: &mut World, tutorial: Tutorial) -> Result<(), TutorialError> {
+    validate_tutorial(world, &tutorial)?;
+    for stroke in &tutorial.setup {
+        match (stroke.brush, stroke.b) {
+            (Brush::Stockpile, b) => {
+                place_stockpile(world, stroke.a, b.unwrap_or(stroke.a));
+            }
+            (Brush::Tile(_), b) => {
+                for (x, y) in tiles(&rect(stroke.a, b)) {
+                    paint(world, x, y, stroke.brush);
+                }
+            }
+            (brush, _) => {
+                paint(world, stroke.a.0, stroke.a.1, brush);
+            }
+        }
+        if let Some(material) = stroke.material {
+            let area = rect(stroke.a, stroke.b);
+            if let Some(mut materials) = world.get_resource_mut::<MaterialMap>() {
+                for (x, y) in tiles(&area) {
+                    materials
+                        .set(x, y, material)
+                        .expect("strokes are validated against the map");
+                }
+            }
+        }
+    }
+    world.insert_resource(TutorialProgress {
+        tutorial: Some(tutorial),
+        ..Default::default()
+    });
+    Ok(())
+}
+
+/// Announce the active tutorial's hints and advance it as goals are met
+///
+/// A step whose goal already holds when it comes up is passed over after
+/// its hint; the closing message is announced once, after the last step.
+#[allow(clippy::too_many_arguments)]
+pub fn tutorial_system(
+    progress: Option<ResMut<TutorialProgress>>,
+    time: Option<Res<Time>>,
+    map: Option<Res<GameMap>>,
+    contents: Option<Res<StockpileContents>>,
+    announcements: Option<ResMut<Announcements>>,
+    q_designations: Query<&Position, With<MineDesignation>>,
+    q_zones: Query<(&ZoneKind, &ZoneBounds)>,
+) {
+    let (Some(mut progress), Some(mut announcements)) = (progress, announcements) else {
+        return;
+    };
+    let progress = &mut *progress;
+    let Some(tutorial) = &progress.tutorial else {
+        return;
+    };
+    let tick = time.map_or(0, |t| t.ticks);
+    let total = tutorial.steps.len();
+    let mut designated: Option<HashSet<(i32, i32)>> = None;
+    while let Some(step) = tutorial.steps.get(progress.step) {
+        if !progress.announced {
+            announcements.push(
+                tick,
+                Severity::Info,
+                format!("Tutorial {}/{}: {}", progress.step + 1, total, step.hint),
+            );
+            progress.announced = true;
+            progress.since = tick;
+        }
+        let is_wall = |x, y| map.as_ref().and_then(|m| m.get_tile(x, y)) == Some(TileKind::Wall);
+        let met = match step.goal {
+            Goal::Designate { .. } => {
+                let designated = designated
+                    .get_or_insert_with(|| q_designations.iter().map(|p| (p.0, p.1)).collect());
+                let area = step.goal.area().expect("designate goals have an area");
+                tiles(&area).all(|(x, y)| !is_wall(x, y) || designated.contains(&(x, y)))
+            }
+            Goal::Mine { .. } => {
+                let area = step.goal.area().expect("mine goals have an area");
+                map.is_some() && tiles(&area).all(|(x, y)| !is_wall(x, y))
+            }
+            Goal::Zone { kind, .. } => {
+                let area = step.goal.area().expect("zone goals have an area");
+                q_zones.iter().any(|(k, bounds)| {
+                    *k == kind && tiles(&area).any(|(x, y)| bounds.contains(x, y))
+                })
+            }
+            Goal::Store { item, count } => {
+                contents.as_ref().is_some_and(|c| c.total(item) >= count)
+            }
+            Goal::Wait { ticks } => tick.saturating_sub(progress.since) >= ticks,
+        };
+        if !met {
+            return;
+        }
+        progress.step += 1;
+        progress.announced = false;
+    }
+    if !progress.announced {
+        announcements.push(
+            tick,
+            Severity::Info,
+            format!("Tutorial complete: {}", tutorial.name),
+        );
+        progress.announced = true;
+    }
+}
@@ -0,0 +1,395 @@
+//! Read-only world projections for renderers and external tools
+//!
+//! A [`WorldView`] is an owned, immutable snapshot of the simulation at one
+//! tick: tiles, entities with a selected set of components, jobs, and summary
+//! metrics. It borrows nothing from the live `World`, so it can be handed to a
+//! render thread (it is `Send + Sync`) or serialized for external clients.
+
+use crate::announcements::{Announcement, Announcements, Shortage, UnstaffedJobs};
+use crate::burial::{Ghost, Grave};
+use crate::burrowing::Burrower;
+use crate::caverns::Caverns;
+use crate::colony::{colony_summary, GoblinSummary};
+use crate::components::{
+    Animal, AssignedJob, Carrier, Dead, DesignationLifecycle, DesignationState, Faction,
+    FactionKind, Goblin, Health, Inventory, Item, ItemType, Miner, Stockpile, ZoneBounds,
+};
+use crate::construction::{Construction, Workshop};
+use crate::designations::MineDesignation;
+use crate::faults::SimFaults;
+use crate::fire::FireMap;
+use crate::forbid::Forbidden;
+use crate::fov::Visibility;
+use crate::history::describe_history;
+use crate::jobs::{ActiveJobs, IssuedBy, Job, JobBoard};
+use crate::livestock::OwnedBy;
+use crate::mechanisms::{Bridge, Door, Lever, PressurePlate, Trap};
+use crate::path::PathService;
+use crate::production::Station;
+use crate::quality::{material_item_value, Quality};
+use crate::render::RenderGlyph;
+use crate::stockpiles::{stockpile_contents, FillLimit, StockpileSummary};
+use crate::systems::Time;
+use crate::tags::{AcceptsTags, TagQuery};
+use crate::telemetry::{EntityCategory, EntityTelemetry, LeakAlert, TelemetryConfig};
+use crate::toggles::{Subsystem, SystemToggles};
+use crate::traffic::TrafficMap;
+use crate::world::{GameMap, Material, MaterialMap, Name, Position, TileKind};
+use crate::zones::ZoneKind;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Number of recent announcements carried by each view
+pub const VIEW_ANNOUNCEMENTS: usize = 16;
+
+/// Projection of a single entity's commonly inspected components
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityView {
+    /// Stable entity bits (`Entity::to_bits`) for correlating views across ticks
+    pub id: u64,
+    pub name: Option<String>,
+    pub pos: Option<(i32, i32)>,
+    /// Capability/role markers present on the entity (e.g. "miner", "carrier")
+    pub roles: Vec<String>,
+    pub item_type: Option<ItemType>,
+    /// Workmanship of crafted items
+    #[serde(default)]
+    pub quality: Option<Quality>,
+    /// Trade value for items (quality-adjusted)
+    #[serde(default)]
+    pub value: Option<u32>,
+    /// Entity bits of the carried item, if any
+    pub carrying: Option<u64>,
+    pub assigned_job: Option<String>,
+    pub faction: Option<FactionKind>,
+    /// Current and maximum hit points
+    pub health: Option<(i32, i32)>,
+    /// Stockpile bounds as (min_x, min_y, max_x, max_y)
+    pub zone: Option<(i32, i32, i32, i32)>,
+    /// What the zone is for
+    #[serde(default)]
+    pub zone_kind: Option<ZoneKind>,
+    /// Tag filter of a stockpile, if it has one
+    #[serde(default)]
+    pub accepts_tags: Option<TagQuery>,
+    /// Material of mined stone
+    #[serde(default)]
+    pub material: Option<Material>,
+    /// Progress label of a mining designation (e.g. "queued")
+    #[serde(default)]
+    pub designation: Option<String>,
+    /// Who placed a mining designation
+    #[serde(default)]
+    pub issued_by: Option<IssuedBy>,
+    /// Cached contents of a stockpile
+    #[serde(default)]
+    pub stored: Option<StockpileSummary>,
+    /// How full a stockpile may get
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_limit: Option<FillLimit>,
+    /// Explicit render glyph, if the entity has one
+    #[serde(default)]
+    pub glyph: Option<RenderGlyph>,
+    /// Recent events, oldest first (see [`entity_history`](crate::history::entity_history))
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<String>,
+}
+
+/// Job board and active job snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobsView {
+    /// Jobs waiting on the board, in board order
+    pub queued: Vec<Job>,
+    /// Jobs currently assigned to workers, sorted by id
+    pub active: Vec<Job>,
+}
+
+/// Aggregate counters for dashboards and quick checks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewMetrics {
+    pub entities: usize,
+    pub items: usize,
+    pub active_designations: usize,
+    pub queued_jobs: usize,
+    pub active_jobs: usize,
+    /// Simulation faults reported so far (see [`SimFaults`])
+    #[serde(default)]
+    pub faults: u64,
+    /// Hits and misses of the shared [`PathService`] cache
+    #[serde(default)]
+    pub path_cache: (usize, usize),
+    /// Live entities per [`EntityCategory`] at the last telemetry sample
+    #[serde(default)]
+    pub entity_counts: Vec<(EntityCategory, u64)>,
+    /// Categories growing past their [`TelemetryConfig`] limit
+    #[serde(default)]
+    pub leaks: Vec<LeakAlert>,
+}
+
+/// Immutable, serializable projection of the world at one tick
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldView {
+    pub tick: u64,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major tiles (`y * width + x`)
+    pub tiles: Vec<TileKind>,
+    /// [`GameMap::revision`] the tiles were copied at; renderers can keep
+    /// cached terrain while it is unchanged
+    #[serde(default)]
+    pub map_revision: u64,
+    /// Row-major tile materials (empty when the world has no material layer)
+    #[serde(default)]
+    pub materials: Vec<Material>,
+    /// Entities with a position, sorted by id
+    pub entities: Vec<EntityView>,
+    /// Union of tiles visible to any entity, sorted (empty without FOV)
+    #[serde(default)]
+    pub visible: Vec<(i32, i32)>,
+    pub jobs: JobsView,
+    pub metrics: ViewMetrics,
+    /// Most recent announcements, oldest first
+    #[serde(default)]
+    pub announcements: Vec<Announcement>,
+    /// Job kinds that have gone unstaffed past the warning threshold
+    #[serde(default)]
+    pub shortages: Vec<Shortage>,
+    /// Burning tiles, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub burning: Vec<(i32, i32)>,
+    /// Tiles of caverns not yet broken into, sorted; renderers draw them as
+    /// plain rock
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub concealed: Vec<(i32, i32)>,
+    /// Decayed step counts of tiles with traffic, sorted by position (see
+    /// [`TrafficMap`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub traffic: Vec<((i32, i32), u32)>,
+    /// Subsystems switched off (see [`SystemToggles`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<Subsystem>,
+    /// Living goblins, sorted by name (see [`colony_summary`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colony: Vec<GoblinSummary>,
+}
+
+impl WorldView {
+    /// Tile at (x, y), or None when out of bounds
+    pub fn tile(&self, x: i32, y: i32) -> Option<TileKind> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        self.tiles
+            .get((y as u32 * self.width + x as u32) as usize)
+            .copied()
+    }
+
+    /// Material at (x, y), or None when out of bounds or unknown
+    pub fn material(&self, x: i32, y: i32) -> Option<Material> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        self.materials
+            .get((y as u32 * self.width + x as u32) as usize)
+            .copied()
+    }
+
+    /// Look up an entity by its id bits
+    pub fn entity(&self, id: u64) -> Option<&EntityView> {
+        self.entities
+            .binary_search_by_key(&id, |e| e.id)
+            .ok()
+            .map(|i| &self.entities[i])
+    }
+
+    /// All entities standing on (x, y)
+    pub fn entities_at(&self, x: i32, y: i32) -> impl Iterator<Item = &EntityView> {
+        self.entities.iter().filter(move |e| e.pos == Some((x, y)))
+    }
+}
+
+/// Build a [`WorldView`] of the current world state
+///
+/// Only entities with a `Position` are projected. Missing resources are
+/// treated as empty so the view works on partially initialized worlds.
+pub fn snapshot_world(world: &mut World) -> WorldView {
+    let (width, height, tiles, map_revision) = world
+        .get_resource::<GameMap>()
+        .map(|m| (m.width, m.height, m.tiles.clone(), m.revision()))
+        .unwrap_or_default();
+    let materials = world
+        .get_resource::<MaterialMap>()
+        .map(|m| m.layers.clone())
+        .unwrap_or_default();
+    let tick = world.get_resource::<Time>().map_or(0, |t| t.ticks);
+
+    let mut entities = Vec::new();
+    let mut items = 0;
+    let mut active_designations = 0;
+    let mut q = world.query::<(Entity, &Position)>();
+    let positioned: Vec<(Entity, Position)> = q.iter(world).map(|(e, p)| (e, *p)).collect();
+    for (entity, pos) in positioned {
+        let e = world.entity(entity);
+        let mut roles = Vec::new();
+        for (present, role) in [
+            (e.contains::<Goblin>(), "goblin"),
+            (e.contains::<Miner>(), "miner"),
+            (e.contains::<Carrier>(), "carrier"),
+            (e.contains::<Stockpile>(), "stockpile"),
+            (e.contains::<MineDesignation>(), "mine_designation"),
+            (e.contains::<Construction>(), "construction"),
+            (e.contains::<Workshop>(), "workshop"),
+            (e.contains::<Station>(), "station"),
+            (e.contains::<Animal>(), "animal"),
+            (e.contains::<OwnedBy>(), "tame"),
+            (e.contains::<Grave>(), "grave"),
+            (e.contains::<Ghost>(), "ghost"),
+            (e.contains::<Lever>(), "lever"),
+            (e.contains::<PressurePlate>(), "pressure_plate"),
+            (e.contains::<Door>(), "door"),
+            (e.contains::<Bridge>(), "bridge"),
+            (e.contains::<Trap>(), "trap"),
+            (e.contains::<Dead>(), "dead"),
+            (e.contains::<Forbidden>(), "forbidden"),
+            (e.contains::<Burrower>(), "burrower"),
+        ] {
+            if present {
+                roles.push(role.to_string());
+            }
+        }
+        let item_type = e.get::<Item>().map(|i| i.item_type);
+        let quality = e.get::<Quality>().copied();
+        if item_type.is_some() {
+            items += 1;
+        }
+        if e.contains::<MineDesignation>()
+            && e.get::<DesignationLifecycle>()
+                .map_or(true, |l| l.0 == DesignationState::Active)
+        {
+            active_designations += 1;
+        }
+        entities.push(EntityView {
+            id: entity.to_bits(),
+            name: e.get::<Name>().map(|n| n.0.clone()),
+            pos: Some((pos.0, pos.1)),
+            roles,
+            item_type,
+            quality,
+            value: item_type.map(|t| material_item_value(t, e.get::<Material>().copied(), quality)),
+            carrying: e.get::<Inventory>().and_then(|i| i.0).map(Entity::to_bits),
+            assigned_job: e
+                .get::<AssignedJob>()
+                .and_then(|a| a.0)
+                .map(|id| id.0.to_string()),
+            faction: e.get::<Faction>().map(|f| f.kind),
+            health: e.get::<Health>().map(|h| (h.hp, h.max_hp)),
+            zone: e
+                .get::<ZoneBounds>()
+                .map(|b| (b.min_x, b.min_y, b.max_x, b.max_y)),
+            zone_kind: e.get::<ZoneKind>().copied(),
+            accepts_tags: e.get::<AcceptsTags>().map(|a| a.0.clone()),
+            material: e.get::<Material>().copied(),
+            designation: e
+                .get::<crate::designations::DesignationStatus>()
+                .map(|s| s.label().to_string()),
+            issued_by: e
+                .contains::<MineDesignation>()
+                .then(|| e.get::<IssuedBy>().copied().unwrap_or_default()),
+            stored: e
+                .contains::<Stockpile>()
+                .then(|| stockpile_contents(world, entity)),
+            fill_limit: e.get::<FillLimit>().copied(),
+            glyph: e.get::<RenderGlyph>().copied(),
+            history: describe_history(world, entity),
+        });
+    }
+    entities.sort_by_key(|e| e.id);
+
+    let mut visible: Vec<(i32, i32)> = world
+        .get_resource::<Visibility>()
+        .map(|v| v.per_entity.values().flatten().copied().collect())
+        .unwrap_or_default();
+    visible.sort_unstable();
+    visible.dedup();
+
+    let queued = world
+        .get_resource::<JobBoard>()
+        .map(|b| b.0.clone())
+        .unwrap_or_default();
+    let mut active: Vec<Job> = world
+        .get_resource::<ActiveJobs>()
+        .map(|a| a.jobs.values().cloned().collect())
+        .unwrap_or_default();
+    active.sort_by_key(|j| (j.seq, j.id.0));
+
+    let announcements = world
+        .get_resource::<Announcements>()
+        .map(|a| a.recent(VIEW_ANNOUNCEMENTS).cloned().collect())
+        .unwrap_or_default();
+
+    let shortages = world
+        .get_resource::<UnstaffedJobs>()
+        .map(|u| u.alerts().copied().collect())
+        .unwrap_or_default();
+
+    let burning = world
+        .get_resource::<FireMap>()
+        .map(|f| f.burning().map(|b| (b.x, b.y)).collect())
+        .unwrap_or_default();
+
+    let concealed = world
+        .get_resource::<Caverns>()
+        .map(Caverns::concealed)
+        .unwrap_or_default();
+
+    let metrics = ViewMetrics {
+        entities: entities.len(),
+        items,
+        active_designations,
+        queued_jobs: queued.len(),
+        active_jobs: active.len(),
+        faults: world
+            .get_resource::<SimFaults>()
+            .map_or(0, SimFaults::total),
+        path_cache: world
+            .get_resource::<PathService>()
+            .map_or((0, 0), PathService::stats),
+        entity_counts: world
+            .get_resource::<EntityTelemetry>()
+            .map(|t| t.counts().collect())
+            .unwrap_or_default(),
+        leaks: match (
+            world.get_resource::<EntityTelemetry>(),
+            world.get_resource::<TelemetryConfig>(),
+        ) {
+            (Some(telemetry), Some(config)) => telemetry.leaks(config),
+            _ => Vec::new(),
+        },
+    };
+
+    WorldView {
+        tick,
+        width,
+        height,
+        tiles,
+        map_revision,
+        materials,
+        entities,
+        visible,
+        jobs: JobsView { queued, active },
+        metrics,
+        announcements,
+        shortages,
+        burning,
+        concealed,
+        traffic: world
+            .get_resource::<TrafficMap>()
+            .map(|t| t.iter().collect())
+            .unwrap_or_default(),
+        disabled: world
+            .get_resource::<SystemToggles>()
+            .map(|t| t.disabled().collect())
+            .unwrap_or_default(),
+        colony: colony_summary(world),
+    }
+}
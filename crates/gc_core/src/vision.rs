@@ -0,0 +1,130 @@
+//! Vision modifiers: what night, fog, injuries and lanterns do to sight
+//!
+//! Every [`VisionRadius`] is a base radius plus a stack of modifiers.
+//! [`vision_modifier_system`] rebuilds each stack from scratch every tick,
+//! one entry per [`VisionSource`] that applies, so subsystems that affect
+//! sight compose instead of overwriting each other's changes to a single
+//! number:
+//!
+//! - [`VisionSource::Night`]: outdoors while the sun is down (see
+//!   [`DayCycle`]); indoors, night makes no difference
+//! - [`VisionSource::Fog`]: everyone, while [`Fog`] lies over the site
+//! - [`VisionSource::Injury`]: below [`VisionConfig::injured_below`]
+//!   percent of full health
+//! - [`VisionSource::Lantern`]: carrying a [`LightSource`] (or an item
+//!   that is one) gives back up to [`VisionConfig::lantern`] of what night
+//!   and fog took, never more
+//!
+//! A stack is only written when it changes, so field of view recomputes an
+//! entity's sight only when its radius may have moved.
+
+use crate::components::{Health, Inventory, VisionModifier, VisionRadius, VisionSource};
+use crate::lighting::{DayCycle, LightMap, LightSource};
+use crate::systems::Time;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+
+/// How much each source changes the vision radius, in tiles
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisionConfig {
+    /// Added outdoors at night
+    pub night: i32,
+    /// Added in fog
+    pub fog: i32,
+    /// Added when badly hurt
+    pub injury: i32,
+    /// Health percent below which an entity counts as badly hurt
+    pub injured_below: i32,
+    /// Most a carried light gives back of the night and fog penalties
+    pub lantern: i32,
+}
+
+impl Default for VisionConfig {
+    fn default() -> Self {
+        Self {
+            night: -3,
+            fog: -4,
+            injury: -2,
+            injured_below: 50,
+            lantern: 3,
+        }
+    }
+}
+
+/// Fog over the whole site while `true`
+///
+/// Nothing rolls fog yet; scenarios and tools set it.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fog(pub bool);
+
+/// Modifiers that apply to an entity, in [`VisionSource`] order
+pub fn vision_modifiers(
+    config: &VisionConfig,
+    night: bool,
+    fog: bool,
+    health: Option<&Health>,
+    lantern: bool,
+) -> Vec<VisionModifier> {
+    let mut modifiers = Vec::new();
+    let mut push = |source, delta| {
+        if delta != 0 {
+            modifiers.push(VisionModifier { source, delta });
+        }
+    };
+    let night = if night { config.night } else { 0 };
+    let fog = if fog { config.fog } else { 0 };
+    push(VisionSource::Night, night);
+    push(VisionSource::Fog, fog);
+    let hurt = health.is_some_and(|h| h.max_hp > 0 && h.hp * 100 < h.max_hp * config.injured_below);
+    push(VisionSource::Injury, if hurt { config.injury } else { 0 });
+    if lantern {
+        let dark = -(night + fog).min(0);
+        push(VisionSource::Lantern, config.lantern.clamp(0, dark));
+    }
+    modifiers
+}
+
+/// Rebuild every [`VisionRadius`] modifier stack
+///
+/// Without a [`LightMap`] everyone counts as outdoors; without a
+/// [`DayCycle`] the default one applies.
+#[allow(clippy::type_complexity)]
+pub fn vision_modifier_system(
+    time: Option<Res<Time>>,
+    cycle: Option<Res<DayCycle>>,
+    light: Option<Res<LightMap>>,
+    fog: Option<Res<Fog>>,
+    config: Option<Res<VisionConfig>>,
+    mut observers: Query<(
+        &mut VisionRadius,
+        &Position,
+        Option<&Health>,
+        Option<&LightSource>,
+        Option<&Inventory>,
+    )>,
+    lights: Query<(), With<LightSource>>,
+) {
+    let config = config.as_deref().copied().unwrap_or_default();
+    let tick = time.map_or(0, |t| t.ticks);
+    let day = cycle.as_deref().copied().unwrap_or_default().is_day(tick);
+    let fog = fog.is_some_and(|f| f.0);
+    for (mut vision, pos, health, own_light, inventory) in observers.iter_mut() {
+        let night = !day
+            && light
+                .as_deref()
+                .map_or(true, |l| l.is_outdoors(pos.0, pos.1));
+        let carried_light = inventory
+            .and_then(|inv| inv.0)
+            .is_some_and(|item| lights.contains(item));
+        let next = vision_modifiers(
+            &config,
+            night,
+            fog,
+            health,
+            own_light.is_some() || carried_light,
+        );
+        if vision.modifiers != next {
+            vision.modifiers = next;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Work areas: how far from home a worker may be sent
+//!
+//! A worker's [`WorkArea`] limits the board jobs it takes. `max_distance`
+//! caps the Manhattan length of the trip the job asks for: from the worker
+//! to the job's first site, and on to its second for hauls and burials.
+//! `bounds` keeps every site of the job inside a rectangle. Workers without
+//! their own area use [`WorkAreaConfig`], which is unlimited by default.
+//!
+//! Every job assignment system checks [`within_reach`] before handing a
+//! job out, so a stone across the map waits for a worker near it instead of
+//! pulling one away. Jobs no eligible worker can reach are warned about by
+//! [`unstaffed_jobs_system`](crate::announcements::unstaffed_jobs_system)
+//! like jobs with nobody to do them at all.
+
+use crate::components::ZoneBounds;
+use crate::jobs::JobKind;
+use crate::world::Position;
+use bevy_ecs::prelude::*;
+
+/// Limits on the board jobs a worker takes
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkArea {
+    /// Longest trip a job may ask for, in tiles; `None` is unlimited
+    pub max_distance: Option<u32>,
+    /// Rectangle every site of a job must lie in; `None` is the whole map
+    pub bounds: Option<ZoneBounds>,
+}
+
+impl WorkArea {
+    /// No limits at all
+    pub const UNLIMITED: WorkArea = WorkArea {
+        max_distance: None,
+        bounds: None,
+    };
+
+    /// Only jobs whose trip from the worker is at most `tiles` long
+    pub fn within(tiles: u32) -> Self {
+        Self {
+            max_distance: Some(tiles),
+            bounds: None,
+        }
+    }
+
+    /// Only jobs entirely inside `bounds`
+    pub fn inside(bounds: ZoneBounds) -> Self {
+        Self {
+            max_distance: None,
+            bounds: Some(bounds),
+        }
+    }
+
+    /// Whether a worker standing at `at` may take a job of `kind`
+    ///
+    /// The distance limit is skipped for workers without a position.
+    pub fn reaches(&self, at: Option<(i32, i32)>, kind: &JobKind) -> bool {
+        let sites = job_sites(kind);
+        if let Some(bounds) = &self.bounds {
+            if !sites.iter().all(|&(x, y)| bounds.contains(x, y)) {
+                return false;
+            }
+        }
+        match (self.max_distance, at) {
+            (Some(max), Some(at)) => trip_length(at, &sites) <= max,
+            _ => true,
+        }
+    }
+}
+
+/// Work area of workers without a [`WorkArea`] of their own
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkAreaConfig {
+    /// Longest trip a job may ask for, in tiles; `None` is unlimited
+    pub max_distance: Option<u32>,
+}
+
+/// Tiles a job of `kind` sends its worker to, in order
+pub fn job_sites(kind: &JobKind) -> Vec<(i32, i32)> {
+    match *kind {
+        JobKind::Haul { from, to } | JobKind::Bury { from, to } => vec![from, to],
+        JobKind::Mine { x, y }
+        | JobKind::Rest { x, y }
+        | JobKind::Equip { x, y, .. }
+        | JobKind::Build { x, y }
+        | JobKind::Craft { x, y }
+        | JobKind::Extinguish { x, y }
+        | JobKind::Shear { x, y }
+        | JobKind::PullLever { x, y } => vec![(x, y)],
+        JobKind::Unequip { .. } => Vec::new(),
+    }
+}
+
+/// Manhattan length of walking from `at` through `sites` in order
+fn trip_length(at: (i32, i32), sites: &[(i32, i32)]) -> u32 {
+    let mut from = at;
+    let mut total = 0u32;
+    for &site in sites {
+        total = total
+            .saturating_add(from.0.abs_diff(site.0))
+            .saturating_add(from.1.abs_diff(site.1));
+        from = site;
+    }
+    total
+}
+
+/// Whether a worker at `at` may take a job of `kind` under its own `area`,
+/// or `config`'s when it has none
+pub fn within_reach(
+    area: Option<&WorkArea>,
+    config: Option<&WorkAreaConfig>,
+    at: Option<&Position>,
+    kind: &JobKind,
+) -> bool {
+    let at = at.map(|p| (p.0, p.1));
+    match area {
+        Some(area) => area.reaches(at, kind),
+        None => WorkArea {
+            max_distance: config.and_then(|c| c.max_distance),
+            bounds: None,
+        }
+        .reaches(at, kind),
+    }
+}
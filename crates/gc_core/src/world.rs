@@ -1,5 +1,7 @@
+use crate::error::GcError;
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 /// World Representation and Core Spatial Components
 ///
@@ -31,6 +33,77 @@ pub struct MapGenConfig {
     pub width: u32,
     /// Height of the map in tiles
     pub height: u32,
+    /// Terrain generation backend
+    #[serde(default)]
+    pub algorithm: MapGenAlgorithm,
+    /// Parameters for [`MapGenAlgorithm::Caves`]
+    #[serde(default)]
+    pub caves: CaveParams,
+    /// Percent of bedrock that is igneous (see [`Material`])
+    #[serde(default = "default_igneous_percent")]
+    pub igneous_percent: u8,
+    /// Hidden caverns to seal into solid rock (see [`crate::caverns`])
+    #[serde(default)]
+    pub caverns: u32,
+}
+
+fn default_igneous_percent() -> u8 {
+    30
+}
+
+impl MapGenConfig {
+    /// Config for a map of the given size with default generation settings
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            algorithm: MapGenAlgorithm::default(),
+            caves: CaveParams::default(),
+            igneous_percent: default_igneous_percent(),
+            caverns: 0,
+        }
+    }
+}
+
+/// Terrain generation backend selected by [`MapGenConfig::algorithm`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapGenAlgorithm {
+    /// Fractal noise thresholded into water, floor, and wall
+    #[default]
+    Noise,
+    /// Cellular-automata caves: random fill, smoothing, one connected cavern
+    Caves,
+}
+
+impl std::str::FromStr for MapGenAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "noise" => Ok(MapGenAlgorithm::Noise),
+            "caves" | "cave" => Ok(MapGenAlgorithm::Caves),
+            other => Err(format!("unknown mapgen algorithm '{}'", other)),
+        }
+    }
+}
+
+/// Tuning for the cellular-automata cave generator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaveParams {
+    /// Chance (0-100) that an interior tile starts as wall
+    pub fill_percent: u32,
+    /// Number of smoothing passes
+    pub passes: u32,
+}
+
+impl Default for CaveParams {
+    fn default() -> Self {
+        Self {
+            fill_percent: 45,
+            passes: 5,
+        }
+    }
 }
 
 /// Component representing the 2D position of an entity in the world
@@ -42,17 +115,60 @@ pub struct Position(pub i32, pub i32);
 /// Component representing the velocity/movement direction of an entity
 /// Currently used minimally but reserved for movement systems
 /// Values represent delta movement per simulation step
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Clone, Default)]
 pub struct Velocity(pub i32, pub i32);
 
 /// Component providing a human-readable name for entities
 /// Used for debugging, logging, and future UI display
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Name(pub String);
 
+/// One tile write for [`GameMap::apply`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileChange {
+    pub pos: (i32, i32),
+    pub kind: TileKind,
+}
+
+/// A tile whose kind actually changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileChanged {
+    pub pos: (i32, i32),
+    pub old: TileKind,
+    pub new: TileKind,
+}
+
+/// A batch of tile changes, oldest first
+///
+/// `revision` is the map's [`GameMap::revision`] after the last of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapChanged {
+    pub revision: u64,
+    pub changes: Vec<TileChanged>,
+}
+
+impl MapChanged {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether any change turned a tile walkable or unwalkable
+    pub fn walkability_changed(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| (c.old == TileKind::Floor) != (c.new == TileKind::Floor))
+    }
+}
+
 /// Resource representing the game world as a 2D tile-based map
 /// This is the primary spatial representation of the game world,
 /// storing all terrain and structural information
+///
+/// Writes through [`Self::set_tile`] and [`Self::apply`] are logged, so
+/// observers (FOV, path caches, renderers) can keep a [`Self::revision`]
+/// cursor and ask for [`Self::changes_since`] instead of diffing the map.
+/// Writing `tiles` directly bypasses the log; that is for generators
+/// building a fresh map only.
 #[derive(Resource, Debug, Clone)]
 pub struct GameMap {
     /// Width of the map in tiles
@@ -62,19 +178,44 @@ pub struct GameMap {
     /// Flat vector storing all tiles in row-major order
     /// Index calculation: y * width + x
     pub tiles: Vec<TileKind>,
+    /// Count of logged changes since the map was created
+    revision: u64,
+    /// Most recent changes; the oldest are dropped beyond one per tile
+    log: VecDeque<TileChanged>,
 }
 
 impl GameMap {
     /// Create a new map filled with floor tiles
     /// This is the basic constructor for an empty, walkable map
     pub fn new(width: u32, height: u32) -> Self {
+        Self::from_tiles(
+            width,
+            height,
+            vec![TileKind::Floor; (width * height) as usize],
+        )
+    }
+
+    /// Wrap existing row-major tiles, with an empty change log
+    pub fn from_tiles(width: u32, height: u32, tiles: Vec<TileKind>) -> Self {
         Self {
             width,
             height,
-            tiles: vec![TileKind::Floor; (width * height) as usize],
+            tiles,
+            revision: 0,
+            log: VecDeque::new(),
         }
     }
 
+    /// Build a map by evaluating `f` at every tile in row-major order
+    /// Generators that draw from an RNG inside `f` consume it in that order
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(i32, i32) -> TileKind) -> Self {
+        let tiles = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self::from_tiles(width, height, tiles)
+    }
+
     /// Convert 2D coordinates to a 1D index into the tiles vector
     /// Returns None if coordinates are out of bounds
     /// Uses row-major ordering: index = y * width + x
@@ -102,14 +243,129 @@ impl GameMap {
     }
 
     /// Set the tile type at the specified coordinates
-    /// Returns true if the tile was successfully set, false if out of bounds
-    pub fn set_tile(&mut self, x: i32, y: i32, kind: TileKind) -> bool {
-        if let Some(i) = self.idx(x, y) {
-            self.tiles[i] = kind;
-            true
-        } else {
-            false
+    /// Fails with [`GcError::OutOfBounds`] if (x, y) is not on the map
+    pub fn set_tile(&mut self, x: i32, y: i32, kind: TileKind) -> Result<(), GcError> {
+        match self.write(x, y, kind) {
+            Some(_) => Ok(()),
+            None => Err(GcError::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            }),
+        }
+    }
+
+    /// Write several tiles at once
+    ///
+    /// Out-of-bounds writes are skipped; the returned batch lists the tiles
+    /// that actually changed, in write order.
+    pub fn apply(&mut self, changes: &[TileChange]) -> MapChanged {
+        let changes = changes
+            .iter()
+            .filter_map(|c| self.write(c.pos.0, c.pos.1, c.kind).flatten())
+            .collect();
+        MapChanged {
+            revision: self.revision,
+            changes,
+        }
+    }
+
+    /// Write one tile and log it; `None` when out of bounds, `Some(None)`
+    /// when the tile already had that kind
+    fn write(&mut self, x: i32, y: i32, kind: TileKind) -> Option<Option<TileChanged>> {
+        let i = self.idx(x, y)?;
+        let old = std::mem::replace(&mut self.tiles[i], kind);
+        if old == kind {
+            return Some(None);
+        }
+        let change = TileChanged {
+            pos: (x, y),
+            old,
+            new: kind,
+        };
+        self.revision += 1;
+        self.log.push_back(change);
+        while self.log.len() > self.tiles.len().max(64) {
+            self.log.pop_front();
+        }
+        Some(Some(change))
+    }
+
+    /// Number of logged tile changes so far
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Every change after `revision`, as one batch
+    ///
+    /// `None` when the log no longer reaches back that far or `revision` is
+    /// from the future (e.g. a cursor kept across a map swap); the caller
+    /// should then treat the whole map as changed.
+    pub fn changes_since(&self, revision: u64) -> Option<MapChanged> {
+        let oldest = self.revision - self.log.len() as u64;
+        if revision < oldest || revision > self.revision {
+            return None;
         }
+        Some(MapChanged {
+            revision: self.revision,
+            changes: self
+                .log
+                .iter()
+                .skip((revision - oldest) as usize)
+                .copied()
+                .collect(),
+        })
+    }
+
+    /// Coordinates of the tile at `idx` (the inverse of [`Self::idx`])
+    pub fn pos(&self, idx: usize) -> (i32, i32) {
+        let w = self.width.max(1) as usize;
+        ((idx % w) as i32, (idx / w) as i32)
+    }
+
+    /// Rows of tiles, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[TileKind]> + '_ {
+        self.tiles.chunks(self.width.max(1) as usize)
+    }
+
+    /// Every tile with its coordinates, in row-major order
+    pub fn iter_tiles(&self) -> impl Iterator<Item = ((i32, i32), TileKind)> + '_ {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| (self.pos(i), t))
+    }
+
+    /// Tiles in the inclusive rectangle `min..=max`, clipped to the map, in
+    /// row-major order
+    pub fn iter_rect(
+        &self,
+        min: (i32, i32),
+        max: (i32, i32),
+    ) -> impl Iterator<Item = ((i32, i32), TileKind)> + '_ {
+        let (x0, y0) = (min.0.max(0), min.1.max(0));
+        let x1 = max.0.min(self.width as i32 - 1);
+        let y1 = max.1.min(self.height as i32 - 1);
+        let w = self.width as usize;
+        (y0..=y1).flat_map(move |y| {
+            let row = if x0 <= x1 {
+                let start = y as usize * w;
+                &self.tiles[start + x0 as usize..=start + x1 as usize]
+            } else {
+                &[][..]
+            };
+            row.iter()
+                .enumerate()
+                .map(move |(dx, &t)| ((x0 + dx as i32, y), t))
+        })
+    }
+
+    /// Coordinates of every tile of `kind`, in row-major order
+    pub fn find_tiles(&self, kind: TileKind) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.iter_tiles()
+            .filter(move |&(_, t)| t == kind)
+            .map(|(p, _)| p)
     }
 
     /// Check if a tile can be walked through by entities
@@ -121,3 +377,103 @@ impl GameMap {
             .unwrap_or(false)
     }
 }
+
+/// Rock or earth a tile is made of
+///
+/// Harder layers need a higher [`MiningSkill`](crate::components::MiningSkill)
+/// to dig and yield different stone when mined: clay, limestone or granite,
+/// each worth a different [`value_percent`](Material::value_percent).
+#[derive(
+    Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Material {
+    Soil,
+    Sedimentary,
+    Igneous,
+}
+
+impl Material {
+    pub const ALL: [Material; 3] = [Material::Soil, Material::Sedimentary, Material::Igneous];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Material::Soil => "soil",
+            Material::Sedimentary => "sedimentary",
+            Material::Igneous => "igneous",
+        }
+    }
+
+    /// Minimum mining skill needed to dig this layer
+    pub fn required_skill(self) -> u8 {
+        match self {
+            Material::Soil | Material::Sedimentary => 0,
+            Material::Igneous => 2,
+        }
+    }
+
+    /// Name of the item produced by mining this layer
+    pub fn item_name(self) -> &'static str {
+        match self {
+            Material::Soil => "Clay",
+            Material::Sedimentary => "Limestone",
+            Material::Igneous => "Granite",
+        }
+    }
+
+    /// Trade value multiplier in percent for stone of this material
+    pub fn value_percent(self) -> u32 {
+        match self {
+            Material::Soil => 50,
+            Material::Sedimentary => 100,
+            Material::Igneous => 200,
+        }
+    }
+}
+
+/// Per-tile material layer, parallel to [`GameMap`]
+///
+/// Generated alongside the map; tiles keep their material after being mined
+/// so the floor shows what was dug out.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MaterialMap {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major materials (`y * width + x`)
+    pub layers: Vec<Material>,
+}
+
+impl MaterialMap {
+    /// Map of the given size made entirely of `material`
+    pub fn filled(width: u32, height: u32, material: Material) -> Self {
+        Self {
+            width,
+            height,
+            layers: vec![material; (width * height) as usize],
+        }
+    }
+
+    /// Material at (x, y), or None when out of bounds
+    pub fn get(&self, x: i32, y: i32) -> Option<Material> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        self.layers
+            .get((y as u32 * self.width + x as u32) as usize)
+            .copied()
+    }
+
+    /// Set the material at (x, y); [`GcError::OutOfBounds`] off the map
+    pub fn set(&mut self, x: i32, y: i32, material: Material) -> Result<(), GcError> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return Err(GcError::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        self.layers[(y as u32 * self.width + x as u32) as usize] = material;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+//! Mining yields: what a dug-out wall drops
+//!
+//! A [`YieldTable`] lists, per wall [`Material`], the items mining may
+//! produce and the percent chance of each. Chances are rolled on the
+//! `job_rng` stream in table order, so the same seed and the same digging
+//! order find the same gems. The default table drops stone every time and
+//! a gem one time in twenty.
+//!
+//! Tables load from JSON ([`YieldTable::from_json`]). Saves carry the table
+//! and its [`hash`](YieldTable::hash) and loading restores the table;
+//! [`load_world_checked`] rejects a table that no longer matches the hash
+//! (see [`SaveGame::yields_compatible`]).
+//!
+//! [`load_world_checked`]: crate::save::load_world_checked
+//! [`SaveGame::yields_compatible`]: crate::save::SaveGame::yields_compatible
+
+use crate::components::ItemType;
+use crate::world::Material;
+use bevy_ecs::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One possible drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YieldEntry {
+    pub item: ItemType,
+    /// Chance in percent; 100 or more always drops, 0 never does
+    pub chance: u8,
+}
+
+impl YieldEntry {
+    pub fn new(item: ItemType, chance: u8) -> Self {
+        Self { item, chance }
+    }
+}
+
+/// Drops per wall material
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YieldTable {
+    /// Drops for walls without a material, or whose material has no entry
+    pub default: Vec<YieldEntry>,
+    #[serde(default)]
+    pub materials: BTreeMap<Material, Vec<YieldEntry>>,
+}
+
+impl Default for YieldTable {
+    fn default() -> Self {
+        Self {
+            default: vec![
+                YieldEntry::new(ItemType::Stone, 100),
+                YieldEntry::new(ItemType::Gem, 5),
+            ],
+            materials: BTreeMap::new(),
+        }
+    }
+}
+
+impl YieldTable {
+    /// Drops for a wall of `material`
+    pub fn entries(&self, material: Option<Material>) -> &[YieldEntry] {
+        material
+            .and_then(|m| self.materials.get(&m))
+            .unwrap_or(&self.default)
+    }
+
+    /// Roll the drops for a wall of `material`, in table order
+    ///
+    /// Only entries with a chance strictly between 0 and 100 draw from
+    /// `rng`.
+    pub fn roll(&self, material: Option<Material>, rng: &mut StdRng) -> Vec<ItemType> {
+        self.entries(material)
+            .iter()
+            .filter(|e| match e.chance {
+                0 => false,
+                c if c >= 100 => true,
+                c => rng.gen_range(0..100) < c,
+            })
+            .map(|e| e.item)
+            .collect()
+    }
+
+    /// Stable hash of the table's contents
+    pub fn hash(&self) -> u64 {
+        let bytes = serde_json::to_vec(self).expect("yield table serializes");
+        crate::lockstep::fnv1a64(&bytes)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
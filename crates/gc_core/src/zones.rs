@@ -0,0 +1,189 @@
+//! Zone kinds and their behaviors
+//!
+//! Every zone is an entity with [`ZoneBounds`], a center [`Position`] and a
+//! [`ZoneKind`]. The kind decides what the zone does:
+//!
+//! - [`ZoneKind::Stockpile`]: storage; also carries a [`Stockpile`]
+//!   (see [`crate::stockpiles`])
+//! - [`ZoneKind::Refuse`]: where items no stockpile accepts are dumped
+//!   (see [`crate::systems::auto_haul_system`])
+//! - [`ZoneKind::Meeting`]: idle workers gather here ([`meeting_zone_system`])
+//! - [`ZoneKind::Hospital`]: crippled workers rest here
+//!   (see [`crate::health::rest_job_system`])
+//! - [`ZoneKind::Pasture`]: tame grazers are kept and grow wool here
+//!   (see [`crate::livestock`])
+//!
+//! [`Stockpile`]: crate::components::Stockpile
+
+use crate::components::{AssignedJob, Dead, Faction, FactionKind, Inventory, ZoneBounds};
+use crate::health::{Resting, Stalled};
+use crate::world::{GameMap, Position};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What a zone is for
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    #[default]
+    Stockpile,
+    Refuse,
+    Meeting,
+    Hospital,
+    Pasture,
+}
+
+impl ZoneKind {
+    /// Every kind, in display order
+    pub const ALL: [ZoneKind; 5] = [
+        ZoneKind::Stockpile,
+        ZoneKind::Refuse,
+        ZoneKind::Meeting,
+        ZoneKind::Hospital,
+        ZoneKind::Pasture,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ZoneKind::Stockpile => "stockpile",
+            ZoneKind::Refuse => "refuse",
+            ZoneKind::Meeting => "meeting",
+            ZoneKind::Hospital => "hospital",
+            ZoneKind::Pasture => "pasture",
+        }
+    }
+
+    /// Map glyph for the zone's tiles
+    pub fn glyph(self) -> char {
+        match self {
+            ZoneKind::Stockpile => '=',
+            ZoneKind::Refuse => '%',
+            ZoneKind::Meeting => '+',
+            ZoneKind::Hospital => 'H',
+            ZoneKind::Pasture => '"',
+        }
+    }
+
+    /// Look a kind up by its [`label`](Self::label)
+    pub fn from_label(label: &str) -> Option<ZoneKind> {
+        Self::ALL.into_iter().find(|k| k.label() == label)
+    }
+}
+
+/// Bundle for a zone without storage (refuse, meeting, hospital, pasture)
+///
+/// Stockpiles use [`crate::stockpiles::StockpileBundle`].
+#[derive(Bundle)]
+pub struct ZoneBundle {
+    pub kind: ZoneKind,
+    /// Center of the bounds
+    pub position: Position,
+    pub bounds: ZoneBounds,
+}
+
+impl ZoneBundle {
+    pub fn new(kind: ZoneKind, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Self {
+        let bounds = ZoneBounds::new(min_x, min_y, max_x, max_y);
+        let (x, y) = bounds.center();
+        Self {
+            kind,
+            position: Position(x, y),
+            bounds,
+        }
+    }
+}
+
+/// The zone of `kind` whose center is nearest to `from` (Manhattan), ties
+/// broken by entity
+pub fn nearest_zone<'a>(
+    zones: impl IntoIterator<Item = (Entity, &'a ZoneKind, &'a Position)>,
+    kind: ZoneKind,
+    from: (i32, i32),
+) -> Option<(Entity, Position)> {
+    zones
+        .into_iter()
+        .filter(|(_, k, _)| **k == kind)
+        .min_by_key(|&(entity, _, pos)| ((pos.0 - from.0).abs() + (pos.1 - from.1).abs(), entity))
+        .map(|(entity, _, pos)| (entity, *pos))
+}
+
+/// Whether (x, y) lies in any zone of `kind`
+pub fn in_zone_of<'a>(
+    zones: impl IntoIterator<Item = (&'a ZoneKind, &'a ZoneBounds)>,
+    kind: ZoneKind,
+    x: i32,
+    y: i32,
+) -> bool {
+    zones
+        .into_iter()
+        .any(|(k, bounds)| *k == kind && bounds.contains(x, y))
+}
+
+/// Walk idle workers one tile per tick toward the nearest meeting zone
+///
+/// Idle means no job, empty hands, not resting, and not hostile. Workers
+/// already inside a meeting zone stay put, and so do workers whose next
+/// step is blocked.
+#[allow(clippy::type_complexity)]
+pub fn meeting_zone_system(
+    map: Option<Res<GameMap>>,
+    zones: Query<(Entity, &ZoneKind, &Position, &ZoneBounds), Without<AssignedJob>>,
+    mut workers: Query<
+        (
+            &mut Position,
+            &AssignedJob,
+            Option<&Inventory>,
+            Option<&Faction>,
+        ),
+        (Without<Dead>, Without<Stalled>, Without<Resting>),
+    >,
+) {
+    if !zones.iter().any(|(_, k, ..)| *k == ZoneKind::Meeting) {
+        return;
+    }
+    for (mut pos, assigned, inventory, faction) in workers.iter_mut() {
+        let busy = assigned.0.is_some() || inventory.is_some_and(|i| i.0.is_some());
+        if busy || faction.is_some_and(|f| f.kind == FactionKind::Invaders) {
+            continue;
+        }
+        if in_zone_of(
+            zones.iter().map(|(_, k, _, b)| (k, b)),
+            ZoneKind::Meeting,
+            pos.0,
+            pos.1,
+        ) {
+            continue;
+        }
+        let Some((_, target)) = nearest_zone(
+            zones.iter().map(|(e, k, p, _)| (e, k, p)),
+            ZoneKind::Meeting,
+            (pos.0, pos.1),
+        ) else {
+            continue;
+        };
+        let next = (
+            pos.0 + (target.0 - pos.0).signum(),
+            pos.1 + (target.1 - pos.1).signum(),
+        );
+        if map
+            .as_deref()
+            .map_or(true, |m| m.is_walkable(next.0, next.1))
+        {
+            pos.0 = next.0;
+            pos.1 = next.1;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::time::Duration;
+
+fn world() -> World {
+    build_standard_world(20, 12, 5, WorldOptions::default())
+}
+
+#[test]
+fn goblin_death_is_urgent_and_requests_pause() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    let grak = world
+        .spawn((Name("Grak".into()), Position(2, 2), Goblin))
+        .id();
+    schedule.run(&mut world);
+    world.entity_mut(grak).insert(Dead);
+    schedule.run(&mut world);
+
+    let mut log = world.resource_mut::<Announcements>();
+    let last = log.iter().last().unwrap().clone();
+    assert_eq!(last.severity, Severity::Urgent);
+    assert_eq!(last.text, "Grak has died!");
+    assert_eq!(last.tick, 1);
+    assert!(log.take_pause_request());
+    assert!(!log.take_pause_request());
+}
+
+#[test]
+fn invader_arrival_is_announced_once_per_tick() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    for x in 0..3 {
+        world.spawn((Position(x, 1), Faction::new(FactionKind::Invaders)));
+    }
+    world.spawn((Position(4, 1), Faction::new(FactionKind::Neutral)));
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    let log = world.resource::<Announcements>();
+    let texts: Vec<_> = log.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(texts, vec!["Invaders have arrived!"]);
+}
+
+#[test]
+fn log_is_bounded_and_filterable() {
+    let mut log = Announcements::default();
+    log.capacity = 3;
+    log.pause_on_urgent = false;
+    log.push(0, Severity::Info, "a");
+    log.push(1, Severity::Warning, "b");
+    log.push(2, Severity::Urgent, "c");
+    log.push(3, Severity::Info, "d");
+    assert_eq!(log.len(), 3);
+    let texts: Vec<_> = log.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(texts, vec!["b", "c", "d"]);
+    let warn: Vec<_> = log.at_least(Severity::Warning).map(|a| a.tick).collect();
+    assert_eq!(warn, vec![1, 2]);
+    assert!(!log.take_pause_request());
+    assert_eq!("warn".parse::<Severity>(), Ok(Severity::Warning));
+}
+
+#[test]
+fn urgent_announcement_pauses_the_sim_thread() {
+    let mut world = world();
+    world.spawn((Position(1, 1), Faction::new(FactionKind::Invaders)));
+    let handle = spawn_sim_thread(world, build_default_schedule(), SimThreadConfig::default());
+    let mut paused = false;
+    let mut announced = false;
+    while let Some(event) = handle.recv_timeout(Duration::from_secs(5)) {
+        match event {
+            SimEvent::Paused(true) => paused = true,
+            SimEvent::Snapshot(view) => {
+                announced |= view
+                    .announcements
+                    .iter()
+                    .any(|a| a.text == "Invaders have arrived!");
+            }
+            _ => {}
+        }
+        if paused && announced {
+            break;
+        }
+    }
+    assert!(paused && announced);
+}
+
+#[test]
+fn jobs_nobody_can_take_are_announced_once() {
+    let mut world = world();
+    world.resource_mut::<StaffingConfig>().warn_after_ticks = 3;
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            gc_core::announcements::unstaffed_jobs_system,
+            gc_core::systems::advance_time,
+        )
+            .chain(),
+    );
+    for n in 0..37u128 {
+        world.resource_mut::<JobBoard>().0.push(Job {
+            id: JobId(uuid::Uuid::from_u128(n)),
+            kind: JobKind::Haul {
+                from: (1, 1),
+                to: (0, 0),
+            },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        });
+    }
+    // A dead carrier and a miner don't count as haulers
+    world.spawn((Carrier, Dead));
+    world.spawn(Miner);
+
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert!(world.resource::<Announcements>().is_empty());
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    let texts: Vec<_> = world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| (a.severity, a.text.clone()))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![(
+            Severity::Warning,
+            "No haulers available: 37 haul jobs pending".to_string()
+        )]
+    );
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.shortages.len(), 1);
+    assert_eq!(view.shortages[0].role, WorkerRole::Hauler);
+    assert_eq!(view.shortages[0].since, 0);
+
+    // A living carrier clears the shortage
+    world.spawn(Carrier);
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<UnstaffedJobs>().alerts().count(), 0);
+}
@@ -0,0 +1,55 @@
+use bevy_ecs::prelude::*;
+use gc_core::bench::{bench_schedule, build_bench_world, run_bench, BenchProfile};
+use gc_core::prelude::*;
+
+#[test]
+fn profiles_parse_by_name() {
+    assert_eq!("colony".parse(), Ok(BenchProfile::COLONY));
+    assert_eq!("SMOKE".parse(), Ok(BenchProfile::SMOKE));
+    assert!("huge".parse::<BenchProfile>().is_err());
+    assert_eq!(BenchProfile::COLONY.agents, 500);
+    assert_eq!(
+        (BenchProfile::COLONY.width, BenchProfile::COLONY.height),
+        (300, 300)
+    );
+}
+
+#[test]
+fn smoke_scenario_is_populated_and_deterministic() {
+    let summary = |seed| {
+        let mut world = build_bench_world(BenchProfile::SMOKE, seed);
+        let mut schedule = bench_schedule();
+        for _ in 0..30 {
+            schedule.run(&mut world);
+        }
+        let miners = world
+            .query_filtered::<(), With<Miner>>()
+            .iter(&world)
+            .count();
+        let carriers = world
+            .query_filtered::<(), With<Carrier>>()
+            .iter(&world)
+            .count();
+        let mut items: Vec<(i32, i32)> = world
+            .query_filtered::<&Position, With<Item>>()
+            .iter(&world)
+            .map(|p| (p.0, p.1))
+            .collect();
+        items.sort_unstable();
+        let tiles = world.resource::<GameMap>().tiles.clone();
+        (miners, carriers, items, tiles)
+    };
+    let (miners, carriers, items, tiles) = summary(5);
+    assert_eq!(miners + carriers, BenchProfile::SMOKE.agents as usize);
+    assert!(items.len() >= BenchProfile::SMOKE.items as usize);
+    assert_eq!(summary(5), (miners, carriers, items, tiles));
+}
+
+#[test]
+fn run_bench_reports_throughput() {
+    let report = run_bench(BenchProfile::SMOKE, 1, 5);
+    assert_eq!(report.profile, "smoke");
+    assert_eq!(report.ticks, 5);
+    assert!(report.ticks_per_sec > 0.0);
+    assert!(report.entities > BenchProfile::SMOKE.agents);
+}
@@ -0,0 +1,124 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+
+fn designation_positions(world: &mut World) -> Vec<(i32, i32)> {
+    let mut q = world.query_filtered::<&Position, With<MineDesignation>>();
+    let mut v: Vec<(i32, i32)> = q.iter(world).map(|p| (p.0, p.1)).collect();
+    v.sort_unstable();
+    v
+}
+
+#[test]
+fn capture_stores_local_coordinates_and_contained_zones() {
+    let mut world = World::new();
+    world.spawn(DesignationBundle {
+        pos: Position(3, 4),
+        ..Default::default()
+    });
+    world.spawn(DesignationBundle {
+        pos: Position(5, 4),
+        ..Default::default()
+    });
+    // Outside region
+    world.spawn(DesignationBundle {
+        pos: Position(20, 20),
+        ..Default::default()
+    });
+    world.spawn(StockpileBundle::new(3, 5, 4, 6));
+    // Straddles the region edge, not captured
+    world.spawn(StockpileBundle::new(5, 5, 9, 9));
+
+    let bp = capture_blueprint(&mut world, &ZoneBounds::new(2, 3, 6, 7));
+
+    assert_eq!((bp.width, bp.height), (5, 5));
+    assert_eq!(bp.mines, vec![(1, 1), (3, 1)]);
+    assert_eq!(bp.zones.len(), 1);
+    assert_eq!(bp.zones[0].min, (1, 2));
+    assert_eq!(bp.zones[0].max, (2, 3));
+}
+
+#[test]
+fn capture_skips_ignored_designations() {
+    let mut world = World::new();
+    world.spawn(DesignationBundle {
+        pos: Position(1, 1),
+        lifecycle: DesignationLifecycle(DesignationState::Ignored),
+        ..Default::default()
+    });
+    let bp = capture_blueprint(&mut world, &ZoneBounds::new(0, 0, 3, 3));
+    assert!(bp.mines.is_empty());
+}
+
+#[test]
+fn stamp_with_offset_and_rotation() {
+    let bp = Blueprint {
+        width: 3,
+        height: 2,
+        mines: vec![(0, 0), (2, 1)],
+        zones: vec![BlueprintZone {
+            min: (0, 0),
+            max: (1, 0),
+            accepts: None,
+        }],
+    };
+
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(30, 30));
+    let res = stamp_blueprint(&mut world, &bp, (10, 10), Rotation::None);
+    assert_eq!(res.designations.len(), 2);
+    assert_eq!(designation_positions(&mut world), vec![(10, 10), (12, 11)]);
+    // Stamping again over the same cells designates nothing new
+    let again = stamp_blueprint(&mut world, &bp, (10, 10), Rotation::None);
+    assert!(again.designations.is_empty());
+    assert_eq!(again.duplicates, 2);
+
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(30, 30));
+    let res = stamp_blueprint(&mut world, &bp, (10, 10), Rotation::Cw90);
+    // Cw90 in a 3x2 region: (x, y) -> (h - 1 - y, x)
+    assert_eq!(designation_positions(&mut world), vec![(10, 12), (11, 10)]);
+    let bounds = world.get::<ZoneBounds>(res.zones[0]).unwrap();
+    assert_eq!(
+        (bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y),
+        (11, 10, 11, 11)
+    );
+}
+
+#[test]
+fn stamp_skips_out_of_bounds_cells() {
+    let bp = Blueprint {
+        width: 2,
+        height: 1,
+        mines: vec![(0, 0), (1, 0)],
+        zones: vec![],
+    };
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(5, 5));
+    let res = stamp_blueprint(&mut world, &bp, (4, 0), Rotation::None);
+    assert_eq!(res.designations.len(), 1);
+    assert_eq!(res.skipped, 1);
+}
+
+#[test]
+fn capture_then_stamp_round_trips_through_json() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(40, 40));
+    for x in 2..5 {
+        world.spawn(DesignationBundle {
+            pos: Position(x, 2),
+            ..Default::default()
+        });
+    }
+    let bp = capture_blueprint(&mut world, &ZoneBounds::new(2, 2, 4, 4));
+
+    let json = encode_blueprint_json(&bp).unwrap();
+    let decoded = decode_blueprint_json(&json).unwrap();
+    assert_eq!(decoded, bp);
+
+    stamp_blueprint(&mut world, &decoded, (20, 20), Rotation::Cw180);
+    let stamped: Vec<(i32, i32)> = designation_positions(&mut world)
+        .into_iter()
+        .filter(|p| p.0 >= 20)
+        .collect();
+    assert_eq!(stamped, vec![(20, 22), (21, 22), (22, 22)]);
+}
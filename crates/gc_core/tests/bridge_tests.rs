@@ -0,0 +1,154 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::path::astar_path;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open ground split by a river down x = 10
+fn world() -> World {
+    let mut world = build_standard_world(20, 12, 5, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..12 {
+        for x in 0..20 {
+            let kind = if x == 10 {
+                TileKind::Water
+            } else {
+                TileKind::Floor
+            };
+            map.set_tile(x, y, kind).unwrap();
+        }
+    }
+    world
+}
+
+fn crosses(world: &World) -> bool {
+    astar_path(world.resource::<GameMap>(), (5, 5), (15, 5)).is_some()
+}
+
+fn build(world: &mut World, kind: ConstructionKind) -> Entity {
+    let site = world.spawn(ConstructionBundle::new(kind, 10, 5)).id();
+    let (item_type, required) = kind.requirement();
+    for x in 0..required as i32 {
+        world.spawn((Item { item_type }, Carriable, Position(3 + x, 4)));
+    }
+    world.spawn((Position(8, 8), Miner, AssignedJob::default()));
+    world.spawn((
+        Position(2, 2),
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+    ));
+    let mut schedule = build_default_schedule();
+    for _ in 0..60 {
+        schedule.run(world);
+    }
+    site
+}
+
+fn bridge(world: &mut World) -> Entity {
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(10, 5, TileKind::Floor)
+        .unwrap();
+    world
+        .spawn((Bridge::lowered(TileKind::Water), Position(10, 5)))
+        .id()
+}
+
+fn mechanisms_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(mechanism_system);
+    schedule
+}
+
+#[test]
+fn cast_floors_fill_water_for_good() {
+    let mut world = world();
+    assert!(!crosses(&world));
+    let site = build(&mut world, ConstructionKind::CastFloor);
+    assert!(world.get_entity(site).is_none());
+    let map = world.resource::<GameMap>();
+    assert_eq!(map.get_tile(10, 5), Some(TileKind::Floor));
+    assert_eq!(map.get_tile(10, 6), Some(TileKind::Water));
+    assert!(crosses(&world));
+}
+
+#[test]
+fn bridges_are_built_lowered_over_water() {
+    let mut world = world();
+    let site = build(&mut world, ConstructionKind::Bridge);
+    assert!(world.get::<Construction>(site).is_none());
+    assert_eq!(
+        world.get::<Bridge>(site),
+        Some(&Bridge::lowered(TileKind::Water))
+    );
+    assert_eq!(world.get::<Name>(site).unwrap().0, "Bridge");
+    assert!(crosses(&world));
+}
+
+#[test]
+fn levers_raise_and_lower_bridges() {
+    let mut world = world();
+    let span = bridge(&mut world);
+    let lever = world.spawn((Lever::default(), Position(4, 4))).id();
+    link_mechanism(&mut world, lever, span).unwrap();
+    assert!(crosses(&world));
+
+    activate(&mut world, lever).unwrap();
+    mechanisms_only().run(&mut world);
+    assert!(!world.get::<Bridge>(span).unwrap().lowered);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(10, 5),
+        Some(TileKind::Water)
+    );
+    assert!(!crosses(&world));
+
+    activate(&mut world, lever).unwrap();
+    mechanisms_only().run(&mut world);
+    assert!(world.get::<Bridge>(span).unwrap().lowered);
+    assert!(crosses(&world));
+}
+
+#[test]
+fn occupied_bridges_stay_down() {
+    let mut world = world();
+    let span = bridge(&mut world);
+    let lever = world.spawn((Lever::default(), Position(4, 4))).id();
+    link_mechanism(&mut world, lever, span).unwrap();
+    world.spawn((Goblin, Position(10, 5), Health::full(10)));
+    activate(&mut world, lever).unwrap();
+    mechanisms_only().run(&mut world);
+    assert!(world.get::<Bridge>(span).unwrap().lowered);
+    assert!(crosses(&world));
+}
+
+#[test]
+fn raised_bridges_survive_save_and_load() {
+    let mut world = world();
+    let span = bridge(&mut world);
+    let lever = world.spawn((Lever::default(), Position(4, 4))).id();
+    link_mechanism(&mut world, lever, span).unwrap();
+    activate(&mut world, lever).unwrap();
+    mechanisms_only().run(&mut world);
+
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    let (span, bridge) = loaded
+        .query::<(Entity, &Bridge)>()
+        .iter(&loaded)
+        .map(|(e, b)| (e, *b))
+        .next()
+        .unwrap();
+    assert!(!bridge.lowered);
+    let lever = loaded
+        .query_filtered::<Entity, With<Lever>>()
+        .single(&loaded);
+    assert_eq!(
+        loaded.get::<MechanismLinks>(lever),
+        Some(&MechanismLinks(vec![span]))
+    );
+    activate(&mut loaded, lever).unwrap();
+    mechanisms_only().run(&mut loaded);
+    assert!(crosses(&loaded));
+}
@@ -0,0 +1,169 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+fn colony(seed: u64) -> World {
+    build_standard_world(
+        20,
+        12,
+        seed,
+        WorldOptions {
+            populate_demo_scene: true,
+            ..Default::default()
+        },
+    )
+}
+
+/// A colony played for a while with a few commands along the way
+fn played(seed: u64) -> World {
+    let mut world = colony(seed);
+    let mut schedule = build_default_schedule();
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (2, 2),
+            b: (6, 4),
+        },
+    );
+    for _ in 0..5 {
+        schedule.run(&mut world);
+    }
+    apply_world_command_as(
+        &mut world,
+        &WorldCommand::CancelDesignation { x: 2, y: 2 },
+        IssuedBy::Player(1),
+    );
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    world
+}
+
+#[test]
+fn applied_commands_are_logged_and_saved() {
+    let mut world = played(4);
+    let log = world.resource::<CommandLog>().clone();
+    let ticks: Vec<(u64, IssuedBy)> = log.0.iter().map(|c| (c.tick, c.issuer)).collect();
+    assert_eq!(
+        ticks,
+        vec![(3, IssuedBy::LOCAL_PLAYER), (8, IssuedBy::Player(1))]
+    );
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    assert_eq!(*loaded.resource::<CommandLog>(), log);
+}
+
+#[test]
+fn bundles_round_trip_through_zip() {
+    let mut world = played(4);
+    for text in ["one", "two", "three"] {
+        announce(&mut world, Severity::Info, text);
+    }
+    let options = BugReportOptions {
+        events: 2,
+        config: Some("{\"presets\": {}}".to_string()),
+        ..Default::default()
+    };
+    let report = bug_report(&mut world, &options);
+    let zip = report.to_zip();
+    assert_eq!(&zip[..4], b"PK\x03\x04");
+    assert_eq!(
+        zip,
+        bug_report(&mut world, &options).to_zip(),
+        "stable bytes"
+    );
+
+    let read = BugReport::from_zip(&zip).unwrap();
+    assert_eq!(read.manifest, report.manifest);
+    assert_eq!(read.manifest.seed, 4);
+    assert_eq!(read.manifest.tick, 12);
+    assert_eq!(read.manifest.commands, 2);
+    let events: Vec<&str> = read.events.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(events, ["two", "three"]);
+    assert_eq!(read.config, options.config);
+    assert_eq!(read.metrics, *world.resource::<RunMetrics>());
+    read.save.verify().unwrap();
+    assert_eq!(read.save.content_hash(), read.manifest.save_hash);
+}
+
+#[test]
+fn replaying_the_log_from_the_seed_reaches_the_bundled_save() {
+    let mut world = played(9);
+    let report = bug_report(&mut world, &BugReportOptions::default());
+
+    let mut replayed = colony(report.manifest.seed);
+    let mut schedule = build_default_schedule();
+    replay_commands(
+        &mut replayed,
+        &mut schedule,
+        &report.save.commands,
+        report.manifest.tick,
+    );
+    assert_eq!(
+        save_world(&mut replayed).content_hash(),
+        report.manifest.save_hash
+    );
+}
+
+#[test]
+fn anonymizing_renames_entities_and_redacts_words() {
+    let mut world = build_standard_world(8, 8, 2, WorldOptions::default());
+    world.spawn((Name("Grub".into()), Position(1, 1)));
+    world.spawn((Name("Grubnik".into()), Position(2, 1)));
+    announce(
+        &mut world,
+        Severity::Info,
+        "Grub and Grubnik met Grubby at /home/alice/fort",
+    );
+    let report = bug_report(
+        &mut world,
+        &BugReportOptions {
+            anonymize: true,
+            redact: vec!["alice".into()],
+            config: Some("{\"path\": \"/home/alice/x\"}".into()),
+            ..Default::default()
+        },
+    );
+    assert!(report.manifest.anonymized);
+    let names: Vec<&str> = report
+        .save
+        .entities
+        .iter()
+        .filter_map(|e| e.name.as_deref())
+        .collect();
+    assert_eq!(names, ["Anon 1", "Anon 2"]);
+    // Only whole names are replaced
+    assert_eq!(
+        report.events.last().unwrap().text,
+        "Anon 1 and Anon 2 met Grubby at /home/[redacted]/fort"
+    );
+    assert_eq!(
+        report.config.as_deref(),
+        Some("{\"path\": \"/home/[redacted]/x\"}")
+    );
+    report.save.verify().unwrap();
+}
+
+#[test]
+fn damaged_bundles_are_refused() {
+    let mut world = colony(1);
+    let zip = bug_report(&mut world, &BugReportOptions::default()).to_zip();
+    assert!(matches!(
+        BugReport::from_zip(b"not a zip"),
+        Err(BundleError::Malformed(_))
+    ));
+    assert!(matches!(
+        BugReport::from_zip(&zip[..zip.len() / 2]),
+        Err(BundleError::Malformed(_))
+    ));
+    let mut flipped = zip.clone();
+    // Inside the manifest's data, just past its header and name
+    flipped[30 + "manifest.json".len() + 5] ^= 0x20;
+    assert!(matches!(
+        BugReport::from_zip(&flipped),
+        Err(BundleError::Checksum(name)) if name == "manifest.json"
+    ));
+}
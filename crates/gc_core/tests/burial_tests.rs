@@ -0,0 +1,243 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn goblin(world: &mut World, name: &str, x: i32, y: i32) -> Entity {
+    world
+        .spawn((Goblin, Name(name.into()), Position(x, y), Health::full(10)))
+        .id()
+}
+
+fn grave(world: &mut World, x: i32, y: i32) -> Entity {
+    world.spawn((Grave::default(), Position(x, y))).id()
+}
+
+fn carrier(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Position(x, y),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ))
+        .id()
+}
+
+fn kill(world: &mut World, entity: Entity) {
+    world.get_mut::<Health>(entity).unwrap().hp = 0;
+}
+
+fn texts(world: &World) -> Vec<String> {
+    world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| a.text.clone())
+        .collect()
+}
+
+#[test]
+fn graves_are_built_from_a_coffin() {
+    let mut world = world();
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Grave, 10, 4))
+        .id();
+    world.spawn((
+        Item {
+            item_type: ItemType::Wood,
+        },
+        Carriable,
+        Position(3, 4),
+    ));
+    world.spawn((Position(8, 8), Miner, AssignedJob::default()));
+    carrier(&mut world, 2, 2);
+    let mut schedule = build_default_schedule();
+    for _ in 0..60 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<Construction>(site).is_none());
+    assert_eq!(world.get::<Grave>(site), Some(&Grave::default()));
+    assert_eq!(world.get::<Name>(site).unwrap().0, "Grave");
+}
+
+#[test]
+fn carriers_bury_the_dead_in_the_nearest_free_grave() {
+    let mut world = world();
+    let far = grave(&mut world, 18, 10);
+    let near = grave(&mut world, 12, 6);
+    let body = goblin(&mut world, "Snagga", 4, 6);
+    let digger = carrier(&mut world, 2, 2);
+    kill(&mut world, body);
+
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert!(world.get::<Corpse>(body).is_some());
+    let job = world.get::<Corpse>(body).unwrap().burial_job;
+    assert!(job.is_some());
+    assert_eq!(world.get::<AssignedJob>(digger).unwrap().0, job);
+    assert!(matches!(
+        world.resource::<ActiveJobs>().jobs[&job.unwrap()].kind,
+        JobKind::Bury {
+            from: (4, 6),
+            to: (12, 6)
+        }
+    ));
+
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<Buried>(body).is_some());
+    assert!(world.get::<Corpse>(body).is_none());
+    assert_eq!(world.get::<Position>(body), Some(&Position(12, 6)));
+    assert_eq!(world.get::<Grave>(near).unwrap().occupant, Some(body));
+    assert_eq!(world.get::<Grave>(far).unwrap().occupant, None);
+    assert_eq!(world.get::<AssignedJob>(digger).unwrap().0, None);
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn unburied_corpses_rise_as_ghosts_and_haunt_the_camp() {
+    let run = || {
+        let mut world = world();
+        world.insert_resource(BurialConfig {
+            ghost_after: 10,
+            haunt_interval: 5,
+            mood_penalty: 40,
+        });
+        let body = goblin(&mut world, "Grub", 4, 4);
+        let mourner = goblin(&mut world, "Urk", 8, 8);
+        let invader = world
+            .spawn((Position(9, 9), Faction::new(FactionKind::Invaders)))
+            .id();
+        let mut smelter = Station::new(StationKind::Smelter);
+        smelter.progress = 3;
+        let near = world.spawn((smelter.clone(), Position(6, 4))).id();
+        let far = world.spawn((smelter, Position(16, 4))).id();
+        kill(&mut world, body);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                gc_core::health::death_system,
+                corpse_system,
+                ghost_system,
+                gc_core::systems::advance_time,
+            )
+                .chain(),
+        );
+        // No graves, so the body lies where it fell
+        for _ in 0..16 {
+            schedule.run(&mut world);
+        }
+        let ghost = world.get::<Ghost>(body).copied();
+        let moods = (
+            world.get::<Mood>(mourner).copied(),
+            world.get::<Mood>(invader).copied(),
+        );
+        let progress = (
+            world.get::<Station>(near).unwrap().progress,
+            world.get::<Station>(far).unwrap().progress,
+        );
+        (ghost, moods, progress, texts(&world))
+    };
+    let (ghost, moods, progress, announced) = run();
+    // Dead at tick 0, risen at 10, haunting at 15
+    assert_eq!(ghost, Some(Ghost { since: 10 }));
+    assert_eq!(moods, (Some(Mood(-40)), None));
+    assert_eq!(progress, (0, 3), "only the nearest work is spoiled");
+    assert_eq!(
+        announced,
+        [
+            "The ghost of Grub rises from its unburied body!".to_string(),
+            "The ghost of Grub haunts the camp and spoils the work at (6, 4)".to_string(),
+        ]
+    );
+    assert_eq!(run().3, announced);
+}
+
+#[test]
+fn burying_a_ghost_lays_it_to_rest() {
+    let mut world = world();
+    world.insert_resource(BurialConfig {
+        ghost_after: 1,
+        haunt_interval: 1000,
+        mood_penalty: 10,
+    });
+    let body = goblin(&mut world, "Grub", 4, 4);
+    kill(&mut world, body);
+    let mut schedule = build_default_schedule();
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<Ghost>(body).is_some());
+
+    grave(&mut world, 10, 4);
+    carrier(&mut world, 2, 4);
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<Ghost>(body).is_none());
+    assert!(world.get::<Buried>(body).is_some());
+    assert!(texts(&world).contains(&"The ghost of Grub has been laid to rest".to_string()));
+}
+
+#[test]
+fn a_body_dropped_on_the_way_is_buried_by_someone_else() {
+    let mut world = world();
+    grave(&mut world, 14, 6);
+    let body = goblin(&mut world, "Snagga", 4, 6);
+    let digger = carrier(&mut world, 3, 6);
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Step,
+    });
+    kill(&mut world, body);
+    let mut schedule = build_default_schedule();
+    for _ in 0..5 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.get::<CarriedBy>(body).map(|c| c.0),
+        Some(digger),
+        "picked up"
+    );
+    let carried_to = *world.get::<Position>(body).unwrap();
+    assert_eq!(Some(&carried_to), world.get::<Position>(digger));
+
+    // The gravedigger dies on the way: the body is put down where it is
+    world.entity_mut(digger).insert(Dead);
+    schedule.run(&mut world);
+    assert!(world.get::<CarriedBy>(body).is_none());
+    assert_eq!(world.get::<Position>(body), Some(&carried_to));
+
+    // Once the job is taken back, a new gravedigger finishes it
+    let second = carrier(&mut world, 1, 1);
+    for _ in 0..80 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<Buried>(body).is_some());
+    assert_eq!(world.get::<Position>(body), Some(&Position(14, 6)));
+    assert_eq!(world.get::<AssignedJob>(second).unwrap().0, None);
+}
+
+#[test]
+fn graves_survive_save_and_load() {
+    let mut world = world();
+    grave(&mut world, 5, 5);
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    let graves: Vec<(Position, Grave)> = loaded
+        .query::<(&Position, &Grave)>()
+        .iter(&loaded)
+        .map(|(p, g)| (*p, *g))
+        .collect();
+    assert_eq!(graves, vec![(Position(5, 5), Grave::default())]);
+}
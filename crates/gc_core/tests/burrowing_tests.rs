@@ -0,0 +1,204 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+/// An open field with a walled room at the right, floor inside
+///
+/// ```text
+/// ..........#####
+/// ..........#...#
+/// ..........#...#
+/// ..........#...#
+/// ..........#####
+/// ```
+fn fort() -> GameMap {
+    GameMap::from_fn(15, 5, |x, y| {
+        if x >= 10 && (x == 10 || x == 14 || y == 0 || y == 4) {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    })
+}
+
+fn world(map: GameMap) -> World {
+    let mut world = World::new();
+    world.insert_resource(map);
+    world.insert_resource(Time::new(100));
+    world.insert_resource(Announcements::default());
+    world.insert_resource(BurrowConfig {
+        dig_ticks: 3,
+        hearing: 5,
+    });
+    world
+}
+
+fn goblin(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((Position(x, y), AssignedJob::default(), Miner))
+        .id()
+}
+
+fn burrower(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Position(x, y),
+            Faction::new(FactionKind::Invaders),
+            Burrower::default(),
+        ))
+        .id()
+}
+
+fn schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((burrower_system, advance_time).chain());
+    schedule
+}
+
+fn pos(world: &World, e: Entity) -> (i32, i32) {
+    let p = world.get::<Position>(e).unwrap();
+    (p.0, p.1)
+}
+
+#[test]
+fn tunnels_dig_only_what_the_digger_can() {
+    let map = fort();
+    let (path, cost) = tunnel_path(&map, None, 0, 3, (0, 2), (12, 2)).unwrap();
+    assert_eq!(path.last(), Some(&(12, 2)));
+    assert_eq!(
+        path.iter()
+            .filter(|&&(x, y)| !map.is_walkable(x, y))
+            .count(),
+        1
+    );
+    assert_eq!(cost, 12 + 3, "one wall on the way costs its dig ticks");
+
+    let granite = MaterialMap::filled(15, 5, Material::Igneous);
+    assert!(!can_dig(&map, Some(&granite), 0, 10, 2));
+    assert!(can_dig(&map, Some(&granite), 2, 10, 2));
+    assert!(tunnel_path(&map, Some(&granite), 0, 3, (0, 2), (12, 2)).is_none());
+    assert!(
+        tunnel_path(&map, None, 0, 3, (0, 2), (10, 2)).is_none(),
+        "goal is rock"
+    );
+}
+
+#[test]
+fn burrowers_walk_open_paths_without_digging() {
+    let mut world = world(GameMap::new(12, 3));
+    let target = goblin(&mut world, 10, 1);
+    let invader = burrower(&mut world, 0, 1);
+    let mut schedule = schedule();
+    for _ in 0..20 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(pos(&world, invader), (9, 1), "stops next to the goblin");
+    assert_eq!(pos(&world, target), (10, 1));
+    assert_eq!(world.resource::<GameMap>().revision(), 0);
+    assert!(world.resource::<Announcements>().is_empty());
+}
+
+#[test]
+fn burrowers_dig_through_walls_at_work_pace_and_are_heard() {
+    let mut world = world(fort());
+    goblin(&mut world, 12, 2);
+    let invader = burrower(&mut world, 6, 2);
+    let mut schedule = schedule();
+
+    // Three ticks of walking to the wall, then three of digging
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(pos(&world, invader), (9, 2));
+    for _ in 0..2 {
+        schedule.run(&mut world);
+        let map = world.resource::<GameMap>();
+        assert_eq!(map.get_tile(10, 2), Some(TileKind::Wall));
+    }
+    let digging = world.get::<Burrower>(invader).unwrap().digging;
+    assert_eq!(digging, Some(((10, 2), 2)));
+    schedule.run(&mut world);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(10, 2),
+        Some(TileKind::Floor)
+    );
+
+    // It walks in through the breach and stops by the goblin
+    for _ in 0..2 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(pos(&world, invader), (11, 2));
+    let heard: Vec<_> = world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| (a.severity, a.text.clone()))
+        .collect();
+    assert_eq!(
+        heard,
+        vec![(
+            Severity::Warning,
+            "Digging heard in the rock near (10, 2)!".to_string()
+        )],
+        "announced once per tunnel"
+    );
+}
+
+#[test]
+fn digging_out_of_earshot_goes_unannounced() {
+    let mut map = fort();
+    // A far wall on the left to break through, out of the goblin's hearing
+    for y in 0..5 {
+        map.set_tile(2, y, TileKind::Wall).unwrap();
+    }
+    let mut world = world(map);
+    world.resource_mut::<BurrowConfig>().hearing = 3;
+    goblin(&mut world, 8, 2);
+    burrower(&mut world, 0, 2);
+    let mut schedule = schedule();
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(2, 2),
+        Some(TileKind::Floor)
+    );
+    assert!(world.resource::<Announcements>().is_empty());
+}
+
+#[test]
+fn too_hard_walls_keep_burrowers_out() {
+    let mut world = world(fort());
+    world.insert_resource(MaterialMap::filled(15, 5, Material::Igneous));
+    goblin(&mut world, 12, 2);
+    let weak = burrower(&mut world, 9, 1);
+    let strong = burrower(&mut world, 9, 2);
+    world.entity_mut(strong).insert(MiningSkill(2));
+    let mut schedule = schedule();
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    let map = world.resource::<GameMap>();
+    assert_eq!(map.get_tile(10, 1), Some(TileKind::Wall));
+    assert_eq!(map.get_tile(10, 2), Some(TileKind::Floor));
+    assert_eq!(pos(&world, weak), (9, 1));
+    assert_eq!(world.get::<Burrower>(weak).unwrap().digging, None);
+}
+
+#[test]
+fn the_default_schedule_runs_burrowers() {
+    let mut world = build_standard_world(15, 5, 3, WorldOptions::default());
+    world.insert_resource(fort());
+    world.insert_resource(MaterialMap::filled(15, 5, Material::Soil));
+    goblin(&mut world, 12, 2);
+    let invader = burrower(&mut world, 9, 2);
+    let mut schedule = build_default_schedule();
+    let reached = fast_forward(&mut world, &mut schedule, 20, |w| {
+        w.get::<Position>(invader)
+            .is_some_and(|p| (p.0 - 12).abs() + (p.1 - 2).abs() <= 1)
+    });
+    assert!(reached.is_some());
+    assert!(world
+        .resource::<Announcements>()
+        .iter()
+        .any(|a| a.text.starts_with("Digging heard")));
+}
@@ -0,0 +1,218 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+use gc_core::view::snapshot_world;
+
+fn solid(width: u32, height: u32) -> GameMap {
+    GameMap::from_tiles(
+        width,
+        height,
+        vec![TileKind::Wall; (width * height) as usize],
+    )
+}
+
+fn config(caverns: u32) -> MapGenConfig {
+    MapGenConfig {
+        caverns,
+        ..MapGenConfig::new(48, 32)
+    }
+}
+
+/// A solid world with one hand-made cavern at (10..=12, 8..=10) holding
+/// a pool at its center and a dog
+fn world() -> World {
+    let mut world = build_standard_world(30, 20, 5, WorldOptions::default());
+    world.insert_resource(solid(30, 20));
+    let tiles: Vec<(i32, i32)> = (10..=12)
+        .flat_map(|x| (8..=10).map(move |y| (x, y)))
+        .collect();
+    let kinds = tiles
+        .iter()
+        .map(|&p| {
+            if p == (11, 9) {
+                TileKind::Water
+            } else {
+                TileKind::Floor
+            }
+        })
+        .collect();
+    world.insert_resource(Caverns::new(vec![Cavern {
+        tiles,
+        kinds,
+        fauna: vec![((10, 8), Species::Dog)],
+        revealed: None,
+    }]));
+    world
+}
+
+fn reveal_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(cavern_reveal_system);
+    schedule
+}
+
+fn cave_animals(world: &mut World) -> Vec<(Position, Species)> {
+    world
+        .query_filtered::<(&Position, &Species), With<Animal>>()
+        .iter(world)
+        .map(|(p, s)| (*p, *s))
+        .collect()
+}
+
+#[test]
+fn caverns_are_deterministic_and_sealed_in_rock() {
+    let map = solid(48, 32);
+    let gen = MapGenerator::new();
+    let caverns = gen.generate_caverns(&config(3), &map, 11);
+    assert_eq!(caverns, gen.generate_caverns(&config(3), &map, 11));
+    assert_ne!(caverns, gen.generate_caverns(&config(3), &map, 12));
+    assert_eq!(caverns.caverns.len(), 3);
+    for cavern in &caverns.caverns {
+        assert!(cavern.is_concealed());
+        assert_eq!(cavern.tiles.len(), cavern.kinds.len());
+        assert_eq!(cavern.fauna.len(), 1);
+        let (den, _) = cavern.fauna[0];
+        let at = cavern.tiles.binary_search(&den).unwrap();
+        assert_eq!(cavern.kinds[at], TileKind::Floor, "animals start dry");
+        for &(x, y) in &cavern.tiles {
+            assert!(x > 1 && y > 1 && x < 46 && y < 30, "ring stays in the rock");
+            assert!(!map.is_walkable(x, y));
+        }
+    }
+    // Caverns never touch each other
+    for (i, a) in caverns.caverns.iter().enumerate() {
+        for b in &caverns.caverns[i + 1..] {
+            for &(ax, ay) in &a.tiles {
+                assert!(b
+                    .tiles
+                    .iter()
+                    .all(|&(bx, by)| (ax - bx).abs() > 2 || (ay - by).abs() > 2));
+            }
+        }
+    }
+    // Open ground has no room for caverns
+    let open = GameMap::new(48, 32);
+    assert!(gen.generate_caverns(&config(3), &open, 11).is_empty());
+}
+
+#[test]
+fn standard_worlds_seal_caverns_when_asked() {
+    let options = WorldOptions {
+        mapgen: MapGenAlgorithm::Caves,
+        caverns: 4,
+        ..Default::default()
+    };
+    let world = build_standard_world(64, 40, 3, options);
+    let caverns = world.resource::<Caverns>();
+    assert!(!caverns.is_empty());
+    let map = world.resource::<GameMap>();
+    for (x, y) in caverns.concealed() {
+        assert_eq!(map.get_tile(x, y), Some(TileKind::Wall));
+    }
+    let plain = build_standard_world(64, 40, 3, WorldOptions::default());
+    assert!(plain.resource::<Caverns>().is_empty());
+}
+
+#[test]
+fn digging_next_to_a_cavern_reveals_it() {
+    let mut world = world();
+    let mut schedule = reveal_only();
+    schedule.run(&mut world);
+    assert!(world.resource::<Caverns>().is_concealed(11, 9));
+    assert!(cave_animals(&mut world).is_empty());
+
+    // A diagonal touch is enough
+    let revision = world.resource::<GameMap>().revision();
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(13, 11, TileKind::Floor)
+        .unwrap();
+    schedule.run(&mut world);
+    let caverns = world.resource::<Caverns>();
+    assert!(!caverns.is_concealed(11, 9));
+    assert!(caverns.concealed().is_empty());
+    assert_eq!(caverns.caverns[0].revealed, Some(0));
+    let map = world.resource::<GameMap>();
+    assert!(map.revision() > revision + 1);
+    assert_eq!(map.get_tile(11, 9), Some(TileKind::Water));
+    assert_eq!(map.get_tile(10, 10), Some(TileKind::Floor));
+    assert_eq!(
+        cave_animals(&mut world),
+        vec![(Position(10, 8), Species::Dog)]
+    );
+    let texts: Vec<String> = world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| a.text.clone())
+        .collect();
+    assert_eq!(
+        texts,
+        vec!["Mining has broken into a hidden cavern at (10, 8)!".to_string()]
+    );
+
+    // Opening it again is a no-op
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(1, 1, TileKind::Floor)
+        .unwrap();
+    schedule.run(&mut world);
+    assert_eq!(cave_animals(&mut world).len(), 1);
+}
+
+#[test]
+fn miners_break_into_caverns_in_the_default_schedule() {
+    let mut world = world();
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for x in 1..9 {
+            map.set_tile(x, 9, TileKind::Floor).unwrap();
+        }
+    }
+    gc_core::designations::designate_mine_cells(&mut world, [(9, 9)]);
+    world.spawn((Position(1, 9), Miner, AssignedJob::default()));
+    let mut schedule = build_default_schedule();
+    for _ in 0..30 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(9, 9),
+        Some(TileKind::Floor)
+    );
+    assert!(world.resource::<Caverns>().concealed().is_empty());
+    assert_eq!(cave_animals(&mut world).len(), 1);
+}
+
+#[test]
+fn views_list_concealed_tiles() {
+    let mut world = world();
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.concealed.len(), 9);
+    assert!(view.concealed.contains(&(11, 9)));
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(9, 9, TileKind::Floor)
+        .unwrap();
+    reveal_only().run(&mut world);
+    assert!(snapshot_world(&mut world).concealed.is_empty());
+}
+
+#[test]
+fn caverns_survive_save_and_load() {
+    let mut world = world();
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    assert_eq!(loaded.resource::<Caverns>(), world.resource::<Caverns>());
+
+    // Still sealed after loading, and still opens
+    loaded
+        .resource_mut::<GameMap>()
+        .set_tile(9, 9, TileKind::Floor)
+        .unwrap();
+    reveal_only().run(&mut loaded);
+    assert_eq!(
+        loaded.resource::<GameMap>().get_tile(11, 9),
+        Some(TileKind::Water)
+    );
+}
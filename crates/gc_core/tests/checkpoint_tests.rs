@@ -0,0 +1,142 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::determinism::run_tick;
+use gc_core::lockstep::world_hash;
+use gc_core::prelude::*;
+
+/// A busy demo camp: goblins walking to a large dig, hauling the stone
+/// to a stockpile
+fn busy_camp() -> (World, Schedule) {
+    let mut world = build_standard_world(
+        40,
+        24,
+        11,
+        WorldOptions {
+            populate_demo_scene: true,
+            ..Default::default()
+        },
+    );
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Walk,
+    });
+    apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (0, 0),
+            b: (39, 23),
+        },
+    );
+    apply_world_command(
+        &mut world,
+        &WorldCommand::Zone {
+            kind: ZoneKind::Stockpile,
+            a: (18, 10),
+            b: (21, 13),
+            accepts: None,
+        },
+    );
+    let mut schedule = build_default_schedule();
+    for _ in 0..15 {
+        run_tick(&mut schedule, &mut world);
+    }
+    (world, schedule)
+}
+
+/// World hash and job board length after each of `ticks` ticks
+fn trace(world: &mut World, schedule: &mut Schedule, ticks: usize) -> Vec<(u64, usize)> {
+    (0..ticks)
+        .map(|_| {
+            run_tick(schedule, world);
+            (world_hash(world), world.resource::<JobBoard>().0.len())
+        })
+        .collect()
+}
+
+#[test]
+fn restored_worlds_replay_the_same_ticks() {
+    let (mut world, mut schedule) = busy_camp();
+    let before = world_hash(&mut world);
+    let snapshot = checkpoint(&mut world).unwrap();
+    assert_eq!(world_hash(&mut world), before);
+    assert!(snapshot.entity_count() > 0);
+
+    let first = trace(&mut world, &mut schedule, 40);
+    assert!(
+        first.windows(2).any(|w| w[0].0 != w[1].0),
+        "the camp keeps changing"
+    );
+
+    restore(&snapshot, &mut world);
+    assert_eq!(world_hash(&mut world), before);
+    assert_eq!(trace(&mut world, &mut schedule, 40), first);
+
+    // Again, from a world that has moved on further
+    trace(&mut world, &mut schedule, 25);
+    restore(&snapshot, &mut world);
+    assert_eq!(trace(&mut world, &mut schedule, 40), first);
+}
+
+#[test]
+fn restore_rewinds_the_rng_and_entity_ids() {
+    let (mut world, mut schedule) = busy_camp();
+    let snapshot = checkpoint(&mut world).unwrap();
+    let next_job_draw = |w: &mut World| {
+        use rand::Rng;
+        w.resource_mut::<DeterministicRng>().job_rng.gen::<u64>()
+    };
+    let draw = next_job_draw(&mut world);
+    let spawned = world.spawn(Position(1, 1)).id();
+
+    restore(&snapshot, &mut world);
+    assert_eq!(next_job_draw(&mut world), draw);
+    assert_eq!(world.spawn(Position(1, 1)).id(), spawned);
+
+    restore(&snapshot, &mut world);
+    trace(&mut world, &mut schedule, 5);
+    let moved_on = world.spawn(Position(1, 1)).id();
+    restore(&snapshot, &mut world);
+    trace(&mut world, &mut schedule, 5);
+    assert_eq!(world.spawn(Position(1, 1)).id(), moved_on);
+}
+
+#[test]
+fn restore_undoes_spawns_despawns_and_new_resources() {
+    let mut world = build_standard_world(10, 10, 1, WorldOptions::default());
+    let kept = world.spawn((Position(2, 3), Name("Snaga".into()))).id();
+    let gone = world.spawn(Position(4, 4)).id();
+    world.despawn(gone);
+    world.remove_resource::<TrafficMap>();
+    let snapshot = checkpoint(&mut world).unwrap();
+
+    world.despawn(kept);
+    let extra = world.spawn(Position(5, 5)).id();
+    world.insert_resource(TrafficMap::default());
+    world.resource_mut::<Time>().ticks = 99;
+
+    restore(&snapshot, &mut world);
+    assert_eq!(world.get::<Position>(kept), Some(&Position(2, 3)));
+    assert_eq!(world.get::<Name>(kept).map(|n| n.0.as_str()), Some("Snaga"));
+    assert!(world.get_entity(extra).is_none());
+    assert!(world.get_entity(gone).is_none());
+    assert!(!world.contains_resource::<TrafficMap>());
+    assert_eq!(world.resource::<Time>().ticks, 0);
+    assert_eq!(world.query::<&Position>().iter(&world).count(), 1);
+}
+
+#[test]
+fn unregistered_components_cannot_be_checkpointed() {
+    #[derive(Component, Clone)]
+    struct Scratch;
+
+    let mut world = build_standard_world(10, 10, 1, WorldOptions::default());
+    let entity = world.spawn((Position(1, 1), Scratch)).id();
+    let err = checkpoint(&mut world).unwrap_err();
+    let CheckpointError::Unregistered {
+        entity: culprit,
+        component,
+    } = err;
+    assert_eq!(culprit, entity);
+    assert!(component.ends_with("Scratch"), "{component}");
+    // The world is left alone
+    assert!(world.get::<Scratch>(entity).is_some());
+}
@@ -0,0 +1,110 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+fn names(rows: &[GoblinSummary]) -> Vec<&str> {
+    rows.iter().map(|r| r.name.as_str()).collect()
+}
+
+fn colony() -> World {
+    let mut world = build_standard_world(12, 8, 3, WorldOptions::default());
+    world.spawn((
+        Name("Snaga".into()),
+        Goblin,
+        Mood(-200),
+        Health::new(3, 10),
+        Position(4, 2),
+    ));
+    world.spawn((
+        Name("Bolg".into()),
+        Miner,
+        AssignedJob::default(),
+        Mood(300),
+        Health::new(10, 10),
+        Position(1, 5),
+    ));
+    world.spawn((
+        Name("Azog".into()),
+        Carrier,
+        AssignedJob::default(),
+        Position(7, 2),
+    ));
+    // Not colony members
+    world.spawn((
+        Name("Raider".into()),
+        Miner,
+        AssignedJob::default(),
+        Faction::new(FactionKind::Invaders),
+        Position(2, 2),
+    ));
+    world.spawn((Name("Gone".into()), Goblin, Dead, Position(3, 3)));
+    world.spawn((Name("Granite".into()), Item::stone(), Position(5, 5)));
+    world
+}
+
+#[test]
+fn the_summary_lists_living_goblins_by_name() {
+    let mut world = colony();
+    let rows = colony_summary(&mut world);
+    assert_eq!(names(&rows), ["Azog", "Bolg", "Snaga"]);
+    let snaga = &rows[2];
+    assert_eq!(snaga.mood, Some(-200));
+    assert_eq!(snaga.health, Some((3, 10)));
+    assert_eq!(snaga.health_percent(), Some(30));
+    assert_eq!(snaga.pos, Some((4, 2)));
+    assert_eq!((snaga.hunger, snaga.fatigue), (None, None));
+    assert_eq!(rows[0].mood, None);
+    assert_eq!(snapshot_world(&mut world).colony, rows);
+}
+
+#[test]
+fn rows_show_the_job_being_worked() {
+    let mut world = colony();
+    let bolg = world
+        .query::<(Entity, &Name)>()
+        .iter(&world)
+        .find(|(_, n)| n.0 == "Bolg")
+        .map(|(e, _)| e)
+        .unwrap();
+    let id = JobId(uuid::Uuid::nil());
+    world.resource_mut::<ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Mine { x: 10, y: 5 },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    world.get_mut::<AssignedJob>(bolg).unwrap().0 = Some(id);
+    let rows = colony_summary(&mut world);
+    let jobs: Vec<_> = rows.iter().map(|r| r.job.as_deref()).collect();
+    assert_eq!(jobs, [None, Some("mine"), None]);
+    assert_eq!(JobKind::PullLever { x: 0, y: 0 }.label(), "pull lever");
+}
+
+#[test]
+fn sorting_puts_missing_values_last_either_way() {
+    let mut world = colony();
+    let mut rows = colony_summary(&mut world);
+    sort_summary(&mut rows, SummaryColumn::Mood, false);
+    assert_eq!(names(&rows), ["Snaga", "Bolg", "Azog"]);
+    sort_summary(&mut rows, SummaryColumn::Mood, true);
+    assert_eq!(names(&rows), ["Bolg", "Snaga", "Azog"]);
+    sort_summary(&mut rows, SummaryColumn::Health, false);
+    assert_eq!(names(&rows), ["Snaga", "Bolg", "Azog"]);
+    // Row-major: y first
+    sort_summary(&mut rows, SummaryColumn::Location, false);
+    assert_eq!(names(&rows), ["Snaga", "Azog", "Bolg"]);
+    // Nobody has hunger yet, so name order holds
+    sort_summary(&mut rows, SummaryColumn::Hunger, true);
+    assert_eq!(names(&rows), ["Azog", "Bolg", "Snaga"]);
+    sort_summary(&mut rows, SummaryColumn::Name, true);
+    assert_eq!(names(&rows), ["Snaga", "Bolg", "Azog"]);
+
+    assert_eq!(SummaryColumn::Name.shift(-1), SummaryColumn::Location);
+    assert_eq!(SummaryColumn::Location.shift(1), SummaryColumn::Name);
+}
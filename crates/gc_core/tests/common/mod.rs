@@ -0,0 +1,13 @@
+//! Fixtures shared by the integration tests
+
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+/// A standard world whose generated map is replaced by open floor, so
+/// walking is unobstructed
+pub fn floor_world(width: u32, height: u32, seed: u64) -> World {
+    let mut world = build_standard_world(width, height, seed, WorldOptions::default());
+    world.insert_resource(GameMap::from_fn(width, height, |_, _| TileKind::Floor));
+    world
+}
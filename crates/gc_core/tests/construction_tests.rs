@@ -0,0 +1,189 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::{build_default_schedule, fast_forward, jobs_idle};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    let mut world = floor_world(16, 10, 5);
+    world.spawn((
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(1, 1),
+    ));
+    world.spawn((Miner, AssignedJob::default(), Position(1, 2)));
+    world
+}
+
+fn stone(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((Item::stone(), Stone, Carriable, Position(x, y)))
+        .id()
+}
+
+fn stones(world: &mut World) -> usize {
+    world
+        .query_filtered::<(), (With<Stone>, With<Position>)>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn walls_go_up_once_their_stone_is_delivered() {
+    let mut world = world();
+    for x in 2..5 {
+        stone(&mut world, x, 7);
+    }
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Wall, 10, 4))
+        .id();
+    let mut schedule = build_default_schedule();
+    let built = fast_forward(&mut world, &mut schedule, 200, |w| {
+        w.resource::<GameMap>().get_tile(10, 4) == Some(TileKind::Wall)
+    });
+    assert!(built.is_some());
+    assert!(world.get_entity(site).is_none());
+    // Only the two stones a wall needs were used
+    assert_eq!(stones(&mut world), 1);
+}
+
+#[test]
+fn building_waits_for_every_unit() {
+    let mut world = world();
+    stone(&mut world, 3, 7);
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Wall, 10, 4))
+        .id();
+    let mut schedule = build_default_schedule();
+    for _ in 0..60 {
+        schedule.run(&mut world);
+    }
+    let requirement = world.get::<MaterialRequirement>(site).unwrap();
+    assert_eq!((requirement.delivered, requirement.required), (1, 2));
+    assert!(requirement.incoming().is_empty());
+    assert_eq!(world.get::<Construction>(site).unwrap().build_job, None);
+    assert_eq!(world.get::<Construction>(site).unwrap().progress, 0);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(10, 4),
+        Some(TileKind::Floor)
+    );
+}
+
+#[test]
+fn cancelling_refunds_delivered_materials() {
+    let mut world = world();
+    stone(&mut world, 3, 7);
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Workshop, 10, 4))
+        .id();
+    let mut schedule = build_default_schedule();
+    let delivered = fast_forward(&mut world, &mut schedule, 60, |w| {
+        w.get::<MaterialRequirement>(site).unwrap().delivered == 1
+    });
+    assert!(delivered.is_some());
+    assert_eq!(stones(&mut world), 0);
+
+    assert!(cancel_construction(&mut world, site));
+    assert!(!cancel_construction(&mut world, site));
+    assert!(world.get_entity(site).is_none());
+    let refunded: Vec<Position> = world
+        .query_filtered::<&Position, With<Stone>>()
+        .iter(&world)
+        .copied()
+        .collect();
+    assert_eq!(refunded, vec![Position(10, 4)]);
+    assert!(world.resource::<JobBoard>().0.iter().all(|j| j.kind
+        != JobKind::Haul {
+            from: (3, 7),
+            to: (10, 4)
+        }));
+}
+
+#[test]
+fn finished_workshops_stay_and_sites_survive_saves() {
+    let mut world = world();
+    for x in 2..6 {
+        stone(&mut world, x, 7);
+    }
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Workshop, 10, 4))
+        .id();
+    let mut schedule = build_default_schedule();
+    fast_forward(&mut world, &mut schedule, 60, |w| {
+        w.get::<MaterialRequirement>(site).unwrap().delivered >= 2
+    })
+    .unwrap();
+    let delivered = world.get::<MaterialRequirement>(site).unwrap().delivered;
+
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let mut q = loaded.query::<(&Construction, &MaterialRequirement, &Position)>();
+    let (construction, requirement, pos) = q.single(&loaded);
+    assert_eq!(construction.kind, ConstructionKind::Workshop);
+    assert_eq!(requirement.delivered, delivered);
+    assert_eq!(*pos, Position(10, 4));
+
+    let built = fast_forward(&mut world, &mut schedule, 200, |w| {
+        w.get::<Workshop>(site).is_some()
+    });
+    assert!(built.is_some());
+    assert!(world.get::<Construction>(site).is_none());
+    assert_eq!(stones(&mut world), 0);
+    assert!(jobs_idle(&mut world));
+}
+
+#[test]
+fn builders_approach_walls_from_a_walkable_side() {
+    let mut world = world();
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Walk,
+    });
+    {
+        // The side facing the builder is solid rock
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 3..6 {
+            map.set_tile(9, y, TileKind::Wall).unwrap();
+        }
+    }
+    for x in 2..4 {
+        stone(&mut world, x, 7);
+    }
+    world.spawn(ConstructionBundle::new(ConstructionKind::Wall, 10, 4));
+    let mut schedule = build_default_schedule();
+    let built = fast_forward(&mut world, &mut schedule, 300, |w| {
+        w.resource::<GameMap>().get_tile(10, 4) == Some(TileKind::Wall)
+    });
+    assert!(built.is_some());
+    let mut workers = world.query_filtered::<&Position, With<AssignedJob>>();
+    let map = world.resource::<GameMap>();
+    for pos in workers.iter(&world) {
+        assert_eq!(map.get_tile(pos.0, pos.1), Some(TileKind::Floor));
+    }
+}
+
+#[test]
+fn finished_walls_move_creatures_off_their_tile() {
+    let mut world = world();
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Wall, 10, 4))
+        .id();
+    world
+        .get_mut::<MaterialRequirement>(site)
+        .unwrap()
+        .delivered = 2;
+    let goat = world.spawn((Position(10, 4), Health::full(5))).id();
+    let mut schedule = build_default_schedule();
+    let built = fast_forward(&mut world, &mut schedule, 100, |w| {
+        w.resource::<GameMap>().get_tile(10, 4) == Some(TileKind::Wall)
+    });
+    assert!(built.is_some());
+    let pos = *world.get::<Position>(goat).unwrap();
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(pos.0, pos.1),
+        Some(TileKind::Floor)
+    );
+    assert!((pos.0 - 10).abs() <= 1 && (pos.1 - 4).abs() <= 1);
+}
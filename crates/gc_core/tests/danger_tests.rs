@@ -0,0 +1,160 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::fov::refresh_visibility;
+use gc_core::prelude::*;
+use std::collections::BTreeSet;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(11, 7));
+    world.insert_resource(DangerMap::default());
+    world.insert_resource(PathQueue::default());
+    world
+}
+
+fn schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((danger_map_system, path_request_system).chain());
+    schedule
+}
+
+#[test]
+fn danger_falls_off_is_capped_and_undone_incrementally() {
+    let mut danger = DangerMap::default();
+    let config = DangerConfig::default();
+    danger.set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(5, 5)]));
+    assert_eq!(danger.cost(FactionKind::Goblins, 5, 5), config.peak);
+    assert!(danger.cost(FactionKind::Goblins, 6, 5) < config.peak);
+    assert_eq!(
+        danger.cost(FactionKind::Goblins, 5 + config.radius + 1, 5),
+        0
+    );
+    assert_eq!(danger.cost(FactionKind::Invaders, 5, 5), 0);
+
+    // Three hostiles on one tile's doorstep still only reach the cap
+    danger.set_known_hostiles(
+        FactionKind::Goblins,
+        BTreeSet::from([(5, 5), (5, 4), (4, 5)]),
+    );
+    assert_eq!(danger.cost(FactionKind::Goblins, 5, 5), config.cap);
+
+    // Moving one hostile leaves no residue where it was
+    danger.set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(20, 20)]));
+    assert_eq!(danger.cost(FactionKind::Goblins, 5, 5), 0);
+    danger.set_known_hostiles(FactionKind::Goblins, BTreeSet::new());
+    assert!(danger.is_clear(FactionKind::Goblins));
+    assert_eq!(danger.cost(FactionKind::Goblins, 20, 20), 0);
+}
+
+#[test]
+fn only_hostiles_a_faction_can_see_are_dangerous() {
+    let mut world = world();
+    world.spawn((Position(5, 0), Faction::new(FactionKind::Invaders)));
+    let mut schedule = schedule();
+    schedule.run(&mut world);
+    // No goblin is looking
+    assert!(world.resource::<DangerMap>().is_clear(FactionKind::Goblins));
+
+    world.spawn((
+        Position(0, 0),
+        Faction::new(FactionKind::Goblins),
+        VisionRadius::new(8),
+    ));
+    refresh_visibility(&mut world);
+    schedule.run(&mut world);
+    let known: Vec<_> = world
+        .resource::<DangerMap>()
+        .known_hostiles(FactionKind::Goblins)
+        .collect();
+    assert_eq!(known, vec![(5, 0)]);
+}
+
+#[test]
+fn civilians_detour_around_hostiles_and_squads_do_not() {
+    let mut world = world();
+    world
+        .resource_mut::<DangerMap>()
+        .set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(5, 3)]));
+    let civilian = world.spawn(Faction::new(FactionKind::Goblins)).id();
+    let soldier = world
+        .spawn((Faction::new(FactionKind::Goblins), Squad(1)))
+        .id();
+    let invader = world.spawn(Faction::new(FactionKind::Invaders)).id();
+    for who in [civilian, soldier, invader] {
+        world
+            .resource_mut::<PathQueue>()
+            .request(who, (0, 3), (10, 3));
+    }
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_request_system);
+    schedule.run(&mut world);
+
+    let path = |world: &World, e| world.get::<PathReady>(e).unwrap().result.clone().unwrap();
+    assert!(!world.resource::<DangerMap>().is_clear(FactionKind::Goblins));
+    let (detour, steps) = path(&world, civilian);
+    assert!(!detour.contains(&(5, 3)));
+    assert!(steps > 10);
+    assert_eq!(detour.len() as i32, steps + 1);
+    assert_eq!(path(&world, soldier).1, 10);
+    assert!(path(&world, soldier).0.contains(&(5, 3)));
+    assert_eq!(path(&world, invader).1, 10);
+}
+
+#[test]
+fn boxed_in_civilians_still_get_a_path() {
+    let mut world = world();
+    world.insert_resource(GameMap::new(11, 1));
+    world
+        .resource_mut::<DangerMap>()
+        .set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(5, 0), (6, 0)]));
+    let civilian = world.spawn_empty().id();
+    world
+        .resource_mut::<PathQueue>()
+        .request(civilian, (0, 0), (10, 0));
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_request_system);
+    schedule.run(&mut world);
+    let (path, steps) = world
+        .get::<PathReady>(civilian)
+        .unwrap()
+        .result
+        .clone()
+        .unwrap();
+    assert_eq!(steps, 10);
+    assert!(path.contains(&(5, 0)));
+}
+
+#[test]
+fn the_default_schedule_steers_civilians_around_hostiles_in_sight() {
+    let mut world = floor_world(20, 12, 5);
+    let civilian = world
+        .spawn((
+            Position(2, 5),
+            Faction::new(FactionKind::Goblins),
+            VisionRadius::new(8),
+        ))
+        .id();
+    world.spawn((Position(6, 5), Faction::new(FactionKind::Invaders)));
+    world
+        .resource_mut::<PathQueue>()
+        .request(civilian, (2, 5), (10, 5));
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+
+    let known: Vec<_> = world
+        .resource::<DangerMap>()
+        .known_hostiles(FactionKind::Goblins)
+        .collect();
+    assert_eq!(known, vec![(6, 5)]);
+    let (path, steps) = world
+        .get::<PathReady>(civilian)
+        .unwrap()
+        .result
+        .clone()
+        .unwrap();
+    assert!(!path.contains(&(6, 5)));
+    assert!(steps > 8);
+}
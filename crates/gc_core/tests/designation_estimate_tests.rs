@@ -0,0 +1,115 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::collections::BTreeMap;
+
+/// Open 10x6 map with a 4x2 block of soil walls at (2..=5, 1..=2)
+fn world() -> World {
+    let mut world = build_standard_world(10, 6, 3, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..6 {
+        for x in 0..10 {
+            let wall = (2..=5).contains(&x) && (1..=2).contains(&y);
+            map.set_tile(
+                x,
+                y,
+                if wall {
+                    TileKind::Wall
+                } else {
+                    TileKind::Floor
+                },
+            )
+            .unwrap();
+        }
+    }
+    world.insert_resource(MaterialMap::filled(10, 6, Material::Soil));
+    world
+}
+
+fn designations(world: &mut World) -> usize {
+    world
+        .query_filtered::<(), With<MineDesignation>>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn estimates_count_walls_and_expected_drops_without_designating() {
+    let mut world = world();
+    apply_world_command(&mut world, &WorldCommand::Designate { x: 2, y: 1 });
+    // One designated wall, seven fresh ones, floor around them and a
+    // column off the map
+    let estimate = estimate_designation(&mut world, &ZoneBounds::new(1, 1, 10, 2));
+    assert_eq!(estimate.tiles, 7);
+    assert_eq!(estimate.skipped, 13);
+    assert_eq!(estimate.work_ticks, 7);
+    assert_eq!(estimate.too_hard, 0);
+    assert_eq!(estimate.miners, 0);
+    assert_eq!(estimate.crew_ticks(), None);
+    assert_eq!(
+        estimate.yields,
+        BTreeMap::from([(ItemType::Stone, 700), (ItemType::Gem, 35)])
+    );
+    assert_eq!(estimate.expected(ItemType::Gem), 0.35);
+    assert_eq!(
+        estimate.to_string(),
+        "7 tiles, 7 work ticks, ~7 stone, ~0.3 gem, 13 skipped"
+    );
+    assert_eq!(designations(&mut world), 1);
+
+    // Once the area is designated there is nothing new left to dig
+    apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (10, 2),
+            b: (1, 1),
+        },
+    );
+    // Floor tiles are designated too, and later dropped as invalid
+    assert_eq!(designations(&mut world), 1 + 17);
+    let again = estimate_designation(&mut world, &ZoneBounds::new(1, 1, 10, 2));
+    assert_eq!(again.tiles, 0);
+    assert!(again.yields.is_empty());
+}
+
+#[test]
+fn estimates_follow_materials_yield_tables_and_miner_skill() {
+    let mut world = world();
+    {
+        let mut materials = world.resource_mut::<MaterialMap>();
+        for x in 4..=5 {
+            materials.set(x, 1, Material::Igneous).unwrap();
+            materials.set(x, 2, Material::Igneous).unwrap();
+        }
+    }
+    let mut table = YieldTable::default();
+    table.materials.insert(
+        Material::Igneous,
+        vec![
+            YieldEntry::new(ItemType::Stone, 100),
+            YieldEntry::new(ItemType::Gem, 50),
+        ],
+    );
+    world.insert_resource(table);
+    world.spawn((Position(0, 0), Miner));
+    world.spawn((Position(0, 1), Miner, MiningSkill(0)));
+    world.spawn((Position(0, 2), Miner, MiningSkill(2), Dead));
+
+    let bounds = ZoneBounds::new(2, 1, 5, 2);
+    let estimate = estimate_designation(&mut world, &bounds);
+    assert_eq!(estimate.tiles, 8);
+    assert_eq!(estimate.too_hard, 4);
+    assert_eq!(estimate.miners, 2);
+    assert_eq!(estimate.crew_ticks(), Some(2));
+    assert_eq!(estimate.expected(ItemType::Stone), 8.0);
+    assert_eq!(estimate.yields[&ItemType::Gem], 4 * 5 + 4 * 50);
+
+    world.spawn((Position(0, 3), Miner, MiningSkill(2)));
+    let estimate = estimate_designation(&mut world, &bounds);
+    assert_eq!(estimate.too_hard, 0);
+    assert_eq!(estimate.crew_ticks(), Some(3));
+    assert_eq!(
+        estimate.to_string(),
+        "8 tiles, 8 work ticks (~3 with 3 miners), ~8 stone, ~2.2 gem"
+    );
+}
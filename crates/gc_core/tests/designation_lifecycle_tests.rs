@@ -1,4 +1,8 @@
+mod common;
+
 use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
 use gc_core::prelude::*;
 use gc_core::{designations, jobs, systems};
 
@@ -22,7 +26,10 @@ fn designation_lifecycle_defaults_to_active() {
 #[test]
 fn single_designation_remains_active() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: false });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: false,
+        ..Default::default()
+    });
 
     let entity = world
         .spawn((
@@ -45,7 +52,10 @@ fn single_designation_remains_active() {
 #[test]
 fn duplicate_designations_marked_ignored() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: false });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: false,
+        ..Default::default()
+    });
 
     // Spawn multiple designations at the same position
     let entity1 = world
@@ -93,7 +103,10 @@ fn duplicate_designations_marked_ignored() {
 #[test]
 fn different_positions_remain_active() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: false });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: false,
+        ..Default::default()
+    });
 
     let entity1 = world
         .spawn((
@@ -139,7 +152,10 @@ fn different_positions_remain_active() {
 #[test]
 fn only_active_designations_create_jobs() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(jobs::JobBoard::default());
     world.insert_resource(systems::DeterministicRng::new(42));
 
@@ -180,7 +196,10 @@ fn only_active_designations_create_jobs() {
 #[test]
 fn full_pipeline_dedup_then_jobs() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(jobs::JobBoard::default());
     world.insert_resource(systems::DeterministicRng::new(42));
 
@@ -264,7 +283,10 @@ fn full_pipeline_dedup_then_jobs() {
 #[test]
 fn ignored_designations_stay_ignored() {
     let mut world = World::new();
-    world.insert_resource(designations::DesignationConfig { auto_jobs: false });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: false,
+        ..Default::default()
+    });
 
     // Create duplicate designations
     let entity1 = world
@@ -307,3 +329,704 @@ fn ignored_designations_stay_ignored() {
     assert_eq!(lifecycle1_after_second, DesignationState::Active);
     assert_eq!(lifecycle2_after_second, DesignationState::Ignored);
 }
+
+/// Test that the discovery rule holds back designations until exploration reaches them
+#[test]
+fn undiscovered_designations_wait_for_exploration() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(20, 5));
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        require_discovery: true,
+        ..Default::default()
+    });
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(systems::DeterministicRng::new(3));
+    world.insert_resource(gc_core::fov::Visibility::default());
+    world.insert_resource(gc_core::fov::Explored::default());
+
+    let scout = world.spawn((Position(2, 2), VisionRadius::new(3))).id();
+    let near = world
+        .spawn((
+            designations::MineDesignation,
+            Position(6, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+    let far = world
+        .spawn((
+            designations::MineDesignation,
+            Position(15, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            gc_core::fov::compute_visibility_system,
+            gc_core::fov::record_explored_system,
+            designations::designation_discovery_system,
+            designations::designation_to_jobs_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+
+    // (6, 2) borders the explored edge at (5, 2); (15, 2) is unseen
+    assert_eq!(
+        world.get::<DesignationLifecycle>(near).unwrap().0,
+        DesignationState::Consumed
+    );
+    assert_eq!(
+        world.get::<DesignationLifecycle>(far).unwrap().0,
+        DesignationState::Undiscovered
+    );
+    assert_eq!(world.resource::<JobBoard>().0.len(), 1);
+
+    // Exploration expands; the held designation is re-validated
+    world.get_mut::<Position>(scout).unwrap().0 = 12;
+    schedule.run(&mut world);
+    assert_eq!(
+        world.get::<DesignationLifecycle>(far).unwrap().0,
+        DesignationState::Consumed
+    );
+    assert_eq!(world.resource::<JobBoard>().0.len(), 2);
+}
+
+/// Test that the default schedule explores and discovers on its own
+#[test]
+fn default_schedule_discovers_designations_near_scouts() {
+    let mut world = floor_world(20, 5, 3);
+    world.insert_resource(designations::DesignationConfig {
+        require_discovery: true,
+        ..Default::default()
+    });
+    let mut map = world.resource_mut::<GameMap>();
+    map.set_tile(6, 2, TileKind::Wall).unwrap();
+    map.set_tile(18, 2, TileKind::Wall).unwrap();
+    world.spawn((Position(2, 2), VisionRadius::new(3)));
+    let near = world
+        .spawn((
+            designations::MineDesignation,
+            Position(6, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+    let far = world
+        .spawn((
+            designations::MineDesignation,
+            Position(18, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+
+    assert!(world.resource::<gc_core::fov::Explored>().contains(5, 2));
+    assert_ne!(
+        world.get::<DesignationLifecycle>(near).unwrap().0,
+        DesignationState::Undiscovered
+    );
+    assert_eq!(
+        world.get::<DesignationLifecycle>(far).unwrap().0,
+        DesignationState::Undiscovered
+    );
+}
+
+/// Test that the discovery rule is off by default
+#[test]
+fn discovery_rule_disabled_by_default() {
+    let mut world = World::new();
+    world.insert_resource(designations::DesignationConfig::default());
+    let entity = world
+        .spawn((
+            designations::MineDesignation,
+            Position(40, 40),
+            DesignationLifecycle(DesignationState::Undiscovered),
+        ))
+        .id();
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(designations::designation_discovery_system);
+    schedule.run(&mut world);
+
+    assert_eq!(
+        world.get::<DesignationLifecycle>(entity).unwrap().0,
+        DesignationState::Active
+    );
+}
+
+fn cleanup_world(cleanup: designations::ConsumedCleanup) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        consumed_cleanup: cleanup,
+        ..Default::default()
+    });
+    world.insert_resource(jobs::JobBoard::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    world.insert_resource(systems::DeterministicRng::new(7));
+    world.insert_resource(systems::Time::new(100));
+    let designation = world
+        .spawn((
+            designations::MineDesignation,
+            Position(3, 3),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+    (world, designation)
+}
+
+fn cleanup_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            designations::designation_cleanup_system,
+        )
+            .chain(),
+    );
+    schedule
+}
+
+/// Consumed designations record the job they created
+#[test]
+fn consumed_designation_links_its_job() {
+    let (mut world, designation) = cleanup_world(designations::ConsumedCleanup::Keep);
+    world.resource_mut::<systems::Time>().ticks = 12;
+    cleanup_schedule().run(&mut world);
+
+    let job = world.resource::<jobs::JobBoard>().0[0].id;
+    let link = world
+        .get::<designations::DesignationJob>(designation)
+        .unwrap();
+    assert_eq!(link.job, job);
+    assert_eq!(link.consumed_at, 12);
+}
+
+/// With OnJobComplete, designations survive while their job is queued or
+/// active and are despawned once it is finished
+#[test]
+fn consumed_designation_despawned_after_job_completes() {
+    let (mut world, designation) = cleanup_world(designations::ConsumedCleanup::OnJobComplete);
+    let mut schedule = cleanup_schedule();
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_some());
+
+    // Assigned: moves from the board to the active list
+    let job = world.resource_mut::<jobs::JobBoard>().0.remove(0);
+    world
+        .resource_mut::<jobs::ActiveJobs>()
+        .jobs
+        .insert(job.id, job.clone());
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_some());
+
+    world
+        .resource_mut::<jobs::ActiveJobs>()
+        .jobs
+        .remove(&job.id);
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_none());
+}
+
+/// AfterTicks despawns on a timer regardless of job state; Keep never does
+#[test]
+fn consumed_designation_grace_period_and_keep() {
+    let (mut world, designation) = cleanup_world(designations::ConsumedCleanup::AfterTicks(5));
+    let mut schedule = cleanup_schedule();
+    schedule.run(&mut world);
+    world.resource_mut::<systems::Time>().ticks = 4;
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_some());
+    world.resource_mut::<systems::Time>().ticks = 5;
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_none());
+
+    let (mut world, designation) = cleanup_world(designations::ConsumedCleanup::Keep);
+    let mut schedule = cleanup_schedule();
+    schedule.run(&mut world);
+    world.resource_mut::<jobs::JobBoard>().0.clear();
+    world.resource_mut::<systems::Time>().ticks = 10_000;
+    schedule.run(&mut world);
+    assert!(world.get_entity(designation).is_some());
+}
+
+/// The default game despawns designations once their mining job finishes
+#[test]
+fn default_schedule_cleans_up_mined_designations() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        10,
+        5,
+        1,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..5 {
+            for x in 0..10 {
+                map.set_tile(x, y, TileKind::Floor).unwrap();
+            }
+        }
+        map.set_tile(4, 2, TileKind::Wall).unwrap();
+    }
+    world.spawn((
+        Position(3, 2),
+        Velocity(0, 0),
+        Miner,
+        MiningSkill(2),
+        AssignedJob::default(),
+    ));
+    let designation = world
+        .spawn((
+            designations::MineDesignation,
+            Position(4, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+    let mut schedule = gc_core::bootstrap::build_default_schedule();
+    for _ in 0..20 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(4, 2),
+        Some(TileKind::Floor)
+    );
+    assert!(world.get_entity(designation).is_none());
+}
+
+fn status_world() -> (World, Entity, Entity) {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        10,
+        5,
+        1,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..5 {
+            for x in 0..10 {
+                map.set_tile(x, y, TileKind::Floor).unwrap();
+            }
+        }
+        map.set_tile(8, 2, TileKind::Wall).unwrap();
+    }
+    let designation = world
+        .spawn((
+            designations::MineDesignation,
+            Position(8, 2),
+            DesignationLifecycle::default(),
+        ))
+        .id();
+    let miner = world
+        .spawn((
+            Position(1, 2),
+            Miner,
+            MiningSkill(2),
+            AssignedJob::default(),
+        ))
+        .id();
+    (world, designation, miner)
+}
+
+/// Status follows the job from the board to a worker, and completion is
+/// reported before the designation is cleaned up
+#[test]
+fn designation_status_tracks_its_job() {
+    let (mut world, designation, miner) = status_world();
+    assert_eq!(
+        designations::designation_status(&world, designation),
+        Some(designations::DesignationStatus::Pending)
+    );
+
+    // Consumed but not yet assigned
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            designations::designation_status_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+    assert_eq!(
+        world.get::<designations::DesignationStatus>(designation),
+        Some(&designations::DesignationStatus::Queued)
+    );
+
+    // Held by a distant miner, then by one within reach
+    let job = world.resource_mut::<jobs::JobBoard>().0.remove(0);
+    world.get_mut::<AssignedJob>(miner).unwrap().0 = Some(job.id);
+    world
+        .resource_mut::<jobs::ActiveJobs>()
+        .jobs
+        .insert(job.id, job);
+    assert_eq!(
+        designations::designation_status(&world, designation),
+        Some(designations::DesignationStatus::Assigned(miner))
+    );
+    world.get_mut::<Position>(miner).unwrap().0 = 7;
+    assert_eq!(
+        designations::designation_status(&world, designation),
+        Some(designations::DesignationStatus::InProgress(miner))
+    );
+
+    let mut game = gc_core::bootstrap::build_default_schedule();
+    game.run(&mut world);
+    assert!(world.get_entity(designation).is_none());
+    let statuses: Vec<_> = world
+        .resource_mut::<designations::DesignationEvents>()
+        .drain()
+        .into_iter()
+        .filter(|c| c.designation == designation)
+        .map(|c| c.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            designations::DesignationStatus::Queued,
+            designations::DesignationStatus::Done,
+        ]
+    );
+}
+
+/// A job that vanishes while the tile is still a wall is a failure
+#[test]
+fn designation_status_reports_lost_jobs_as_failed() {
+    let (mut world, designation, _) = status_world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(designations::designation_to_jobs_system);
+    schedule.run(&mut world);
+    world.resource_mut::<jobs::JobBoard>().0.clear();
+    assert_eq!(
+        designations::designation_status(&world, designation),
+        Some(designations::DesignationStatus::Failed)
+    );
+}
+
+/// Cancelling withdraws the job and releases the worker holding it
+#[test]
+fn cancelling_a_designation_releases_its_worker() {
+    let (mut world, designation, miner) = status_world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            jobs::job_assignment_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+    assert!(world.get::<AssignedJob>(miner).unwrap().0.is_some());
+
+    assert!(gc_core::command::apply_world_command(
+        &mut world,
+        &gc_core::command::WorldCommand::CancelDesignation { x: 8, y: 2 },
+    ));
+    assert!(world.get_entity(designation).is_none());
+    assert!(world.get::<AssignedJob>(miner).unwrap().0.is_none());
+    assert!(world.resource::<jobs::ActiveJobs>().jobs.is_empty());
+    assert!(world.resource::<jobs::JobBoard>().0.is_empty());
+    let last = world
+        .resource::<designations::DesignationEvents>()
+        .iter()
+        .last()
+        .copied();
+    assert_eq!(
+        last.map(|c| c.status),
+        Some(designations::DesignationStatus::Cancelled)
+    );
+    assert!(!gc_core::command::apply_world_command(
+        &mut world,
+        &gc_core::command::WorldCommand::CancelDesignation { x: 8, y: 2 },
+    ));
+}
+
+/// Rectangles are clipped to the map before their cells are walked
+#[test]
+fn huge_designation_rects_only_walk_the_map() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(6, 4));
+    let batch = designations::designate_mine_rect(
+        &mut world,
+        &ZoneBounds::new(i32::MIN, i32::MIN, i32::MAX, i32::MAX),
+    );
+    assert_eq!(batch.spawned.len(), 6 * 4);
+    assert!(batch.invalid > usize::MAX / 2);
+
+    let batch = designations::designate_mine_rect(&mut world, &ZoneBounds::new(-2, 1, 2, 1));
+    assert_eq!(batch.duplicates, 3);
+    assert_eq!(batch.invalid, 2);
+}
+
+/// Batch designation skips off-map cells and anything already designated
+#[test]
+fn batch_designation_dedups_up_front() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(6, 4));
+    world.spawn(designations::DesignationBundle {
+        pos: Position(1, 1),
+        ..Default::default()
+    });
+
+    let batch =
+        designations::designate_mine_cells(&mut world, [(0, 0), (1, 1), (0, 0), (9, 9), (2, 3)]);
+    assert_eq!(batch.spawned.len(), 2);
+    assert_eq!(batch.duplicates, 2);
+    assert_eq!(batch.invalid, 1);
+    assert_eq!(
+        world.get::<Position>(batch.spawned[1]),
+        Some(&Position(2, 3))
+    );
+
+    // 3x2 rectangle hanging off the right edge; (1, 1) is taken
+    let rect = designations::designate_mine_rect(&mut world, &ZoneBounds::new(1, 0, 7, 1));
+    assert_eq!(rect.spawned.len(), 9);
+    assert_eq!(rect.duplicates, 1);
+    assert_eq!(rect.invalid, 4);
+
+    // Nothing left for dedup to ignore
+    let mut schedule = Schedule::default();
+    schedule.add_systems(designations::designation_dedup_system);
+    schedule.run(&mut world);
+    let ignored = world
+        .query::<&DesignationLifecycle>()
+        .iter(&world)
+        .filter(|l| l.0 == DesignationState::Ignored)
+        .count();
+    assert_eq!(ignored, 0);
+
+    assert!(gc_core::command::apply_world_command(
+        &mut world,
+        &gc_core::command::WorldCommand::DesignateRect {
+            a: (5, 3),
+            b: (4, 2)
+        },
+    ));
+    assert!(!gc_core::command::apply_world_command(
+        &mut world,
+        &gc_core::command::WorldCommand::DesignateRect {
+            a: (4, 2),
+            b: (5, 3)
+        },
+    ));
+}
+
+/// The issuer of a designation follows it onto its job and status events
+#[test]
+fn issuer_is_carried_to_jobs_and_events() {
+    let (mut world, designation, _) = status_world();
+    world.entity_mut(designation).insert(IssuedBy::Player(2));
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            jobs::job_assignment_system,
+            designations::designation_status_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+
+    let active = &world.resource::<jobs::ActiveJobs>().jobs;
+    assert_eq!(active.len(), 1);
+    assert!(active.values().all(|j| j.issued_by == IssuedBy::Player(2)));
+    let events = world.resource::<designations::DesignationEvents>();
+    assert!(events.iter().count() > 0);
+    assert!(events.iter().all(|c| c.issued_by == IssuedBy::Player(2)));
+
+    let view = gc_core::view::snapshot_world(&mut world);
+    let shown = view.entities.iter().find(|e| e.designation.is_some());
+    assert_eq!(shown.and_then(|e| e.issued_by), Some(IssuedBy::Player(2)));
+}
+
+/// Commands may only cancel their own issuer's designations, or the system's
+#[test]
+fn designation_issuers_survive_save_and_load() {
+    use gc_core::command::{apply_world_command_as, WorldCommand};
+    use gc_core::save::{load_world, save_world};
+    let (mut world, _, _) = status_world();
+    assert!(apply_world_command_as(
+        &mut world,
+        &WorldCommand::Designate { x: 3, y: 1 },
+        IssuedBy::Player(1),
+    ));
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+
+    let mut q = loaded
+        .query_filtered::<(&Position, Option<&IssuedBy>), With<designations::MineDesignation>>();
+    let mut issuers: Vec<_> = q.iter(&loaded).map(|(p, by)| (*p, by.copied())).collect();
+    issuers.sort_by_key(|(p, _)| (p.0, p.1));
+    assert_eq!(
+        issuers,
+        [
+            (Position(3, 1), Some(IssuedBy::Player(1))),
+            (Position(8, 2), None)
+        ]
+    );
+    let cancel = WorldCommand::CancelDesignation { x: 3, y: 1 };
+    assert!(!apply_world_command_as(
+        &mut loaded,
+        &cancel,
+        IssuedBy::Player(2)
+    ));
+    assert!(apply_world_command_as(
+        &mut loaded,
+        &cancel,
+        IssuedBy::Player(1)
+    ));
+}
+
+#[test]
+fn cancellation_is_scoped_by_issuer() {
+    use gc_core::command::{apply_world_command, apply_world_command_as, WorldCommand};
+    let (mut world, system_designation, _) = status_world();
+    assert!(apply_world_command_as(
+        &mut world,
+        &WorldCommand::Designate { x: 3, y: 1 },
+        IssuedBy::Player(1),
+    ));
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (5, 0),
+            b: (6, 0)
+        },
+    ));
+
+    // Player 2 cannot touch player 1's designation...
+    let cancel = WorldCommand::CancelDesignation { x: 3, y: 1 };
+    assert!(!apply_world_command_as(
+        &mut world,
+        &cancel,
+        IssuedBy::Player(2)
+    ));
+    assert!(apply_world_command_as(
+        &mut world,
+        &cancel,
+        IssuedBy::Player(1)
+    ));
+    // ...but anyone may withdraw the system's
+    assert!(apply_world_command_as(
+        &mut world,
+        &WorldCommand::CancelDesignation { x: 8, y: 2 },
+        IssuedBy::Script(4),
+    ));
+    assert!(world.get_entity(system_designation).is_none());
+
+    assert_eq!(
+        designations::cancel_designations_by(&mut world, IssuedBy::Player(1)),
+        0
+    );
+    assert_eq!(
+        designations::cancel_designations_by(&mut world, IssuedBy::LOCAL_PLAYER),
+        2
+    );
+    let left = world
+        .query_filtered::<(), With<designations::MineDesignation>>()
+        .iter(&world)
+        .count();
+    assert_eq!(left, 0);
+}
+
+/// Enclosed walls wait as Unreachable until mining opens a way in, and
+/// floor pockets no miner can walk to do not count as access
+#[test]
+fn unreachable_designations_wait_for_access() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        10,
+        5,
+        1,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..5 {
+            for x in 0..10 {
+                map.set_tile(
+                    x,
+                    y,
+                    if x <= 3 {
+                        TileKind::Floor
+                    } else {
+                        TileKind::Wall
+                    },
+                )
+                .unwrap();
+            }
+        }
+        // Sealed pocket
+        map.set_tile(8, 2, TileKind::Floor).unwrap();
+    }
+    world.spawn((
+        Position(1, 2),
+        Miner,
+        MiningSkill(2),
+        AssignedJob::default(),
+    ));
+    let designate = |world: &mut World, x, y| {
+        world
+            .spawn(designations::DesignationBundle {
+                pos: Position(x, y),
+                ..Default::default()
+            })
+            .id()
+    };
+    designate(&mut world, 4, 2);
+    let enclosed = designate(&mut world, 5, 2);
+    let pocket_wall = designate(&mut world, 8, 1);
+
+    let mut schedule = gc_core::bootstrap::build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(4, 2),
+        Some(TileKind::Floor)
+    );
+    assert_eq!(
+        world.get::<DesignationLifecycle>(enclosed).unwrap().0,
+        DesignationState::Unreachable
+    );
+    assert_eq!(
+        world.get::<designations::DesignationStatus>(pocket_wall),
+        Some(&designations::DesignationStatus::Unreachable)
+    );
+
+    // The map changed, so the enclosed wall is re-checked and mined
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(5, 2),
+        Some(TileKind::Floor)
+    );
+    assert_eq!(
+        world.get::<DesignationLifecycle>(pocket_wall).unwrap().0,
+        DesignationState::Unreachable
+    );
+    let reported: Vec<designations::DesignationStatus> = world
+        .resource::<designations::DesignationEvents>()
+        .iter()
+        .filter(|c| c.designation == enclosed)
+        .map(|c| c.status)
+        .collect();
+    assert_eq!(
+        reported.first(),
+        Some(&designations::DesignationStatus::Unreachable)
+    );
+    assert_eq!(
+        reported.last(),
+        Some(&designations::DesignationStatus::Done)
+    );
+
+    // Without the rule everything is released
+    world
+        .resource_mut::<designations::DesignationConfig>()
+        .require_access = false;
+    schedule.run(&mut world);
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(8, 1),
+        Some(TileKind::Floor)
+    );
+}
@@ -0,0 +1,170 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open 12x8 map with a row of walls at (3..=8, 3), a pool of water at
+/// (3, 2) and lava at (8, 4), and a miner at (5, 4)
+fn world() -> World {
+    let mut world = build_standard_world(12, 8, 3, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..8 {
+        for x in 0..12 {
+            let tile = match (x, y) {
+                (3..=8, 3) => TileKind::Wall,
+                (3, 2) => TileKind::Water,
+                (8, 4) => TileKind::Lava,
+                _ => TileKind::Floor,
+            };
+            map.set_tile(x, y, tile).unwrap();
+        }
+    }
+    world.insert_resource(MaterialMap::filled(12, 8, Material::Soil));
+    world.spawn((Position(5, 4), Miner, AssignedJob::default()));
+    world
+}
+
+fn state(world: &World, designation: Entity) -> DesignationState {
+    world.get::<DesignationLifecycle>(designation).unwrap().0
+}
+
+fn mined(world: &World, x: i32) -> bool {
+    world.resource::<GameMap>().get_tile(x, 3) == Some(TileKind::Floor)
+}
+
+#[test]
+fn walls_next_to_water_or_lava_are_hazards() {
+    let world = world();
+    let map = world.resource::<GameMap>();
+    assert_eq!(wall_hazard(map, 3, 3), Some(TileKind::Water));
+    assert_eq!(wall_hazard(map, 8, 3), Some(TileKind::Lava));
+    assert_eq!(wall_hazard(map, 5, 3), None);
+    // Diagonals and floors do not count
+    assert_eq!(wall_hazard(map, 4, 3), None);
+    assert_eq!(wall_hazard(map, 3, 1), None);
+
+    let mut both = map.clone();
+    both.set_tile(2, 3, TileKind::Lava).unwrap();
+    assert_eq!(wall_hazard(&both, 3, 3), Some(TileKind::Lava));
+}
+
+#[test]
+fn dangerous_designations_wait_for_confirmation() {
+    let mut world = world();
+    let batch = designate_mine_rect(&mut world, &ZoneBounds::new(3, 3, 8, 3));
+    assert_eq!(batch.spawned.len(), 6);
+    assert_eq!(batch.dangerous, 2);
+    let (water, lava) = (batch.spawned[0], batch.spawned[5]);
+    assert_eq!(state(&world, water), DesignationState::Dangerous);
+    assert_eq!(state(&world, batch.spawned[1]), DesignationState::Active);
+
+    let mut schedule = build_default_schedule();
+    for _ in 0..30 {
+        schedule.run(&mut world);
+    }
+    assert!((4..=7).all(|x| mined(&world, x)));
+    assert!(!mined(&world, 3) && !mined(&world, 8));
+    assert_eq!(
+        designation_status(&world, lava),
+        Some(DesignationStatus::Dangerous)
+    );
+    let warnings: Vec<String> = world
+        .resource::<Announcements>()
+        .iter()
+        .filter(|a| a.severity == Severity::Warning)
+        .map(|a| a.text.clone())
+        .collect();
+    assert_eq!(
+        warnings,
+        vec!["2 mining designations next to water or lava need confirming"]
+    );
+
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::ConfirmDesignations {
+            a: (8, 3),
+            b: (8, 2)
+        },
+    ));
+    for _ in 0..30 {
+        schedule.run(&mut world);
+    }
+    assert!(mined(&world, 8));
+    assert!(!mined(&world, 3));
+    assert_eq!(state(&world, water), DesignationState::Dangerous);
+}
+
+#[test]
+fn single_designations_are_flagged_and_released_when_the_hazard_goes() {
+    let mut world = world();
+    apply_world_command(&mut world, &WorldCommand::Designate { x: 3, y: 3 });
+    let designation = world
+        .query_filtered::<Entity, With<MineDesignation>>()
+        .single(&world);
+    assert_eq!(state(&world, designation), DesignationState::Active);
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(state(&world, designation), DesignationState::Dangerous);
+    assert!(world.resource::<JobBoard>().0.is_empty());
+    assert_eq!(world.resource::<Announcements>().iter().count(), 1);
+
+    // Still dangerous next tick, without warning again
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<Announcements>().iter().count(), 1);
+
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(3, 2, TileKind::Floor)
+        .unwrap();
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert!(mined(&world, 3));
+}
+
+#[test]
+fn turning_the_rule_off_releases_dangerous_designations() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    designate_mine_rect(&mut world, &ZoneBounds::new(8, 3, 8, 3));
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert!(!mined(&world, 8));
+    world.resource_mut::<DesignationConfig>().confirm_hazards = false;
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert!(mined(&world, 8));
+
+    // Without the rule nothing starts out dangerous
+    let mut world = self::world();
+    world.resource_mut::<DesignationConfig>().confirm_hazards = false;
+    let batch = designate_mine_rect(&mut world, &ZoneBounds::new(3, 3, 8, 3));
+    assert_eq!(batch.dangerous, 0);
+}
+
+#[test]
+fn confirmations_survive_saves_and_show_in_estimates() {
+    let mut world = world();
+    let estimate = estimate_designation(&mut world, &ZoneBounds::new(3, 3, 8, 3));
+    assert_eq!(estimate.dangerous, 2);
+    assert!(estimate.to_string().contains(", 2 by water/lava"));
+
+    designate_mine_rect(&mut world, &ZoneBounds::new(3, 3, 8, 3));
+    apply_world_command(
+        &mut world,
+        &WorldCommand::ConfirmDesignations {
+            a: (3, 3),
+            b: (3, 3),
+        },
+    );
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let confirmed: Vec<Position> = loaded
+        .query_filtered::<&Position, (With<MineDesignation>, With<HazardConfirmed>)>()
+        .iter(&loaded)
+        .copied()
+        .collect();
+    assert_eq!(confirmed, vec![Position(3, 3)]);
+}
@@ -1,4 +1,6 @@
 use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::determinism::{audit_source, run_tick, wall_clock, RngStream};
 use gc_core::prelude::*;
 use gc_core::{designations, jobs, systems};
 use rand::Rng;
@@ -23,7 +25,10 @@ fn deterministic_behavior_across_systems() {
 
         // Set up other resources
         world.insert_resource(JobBoard::default());
-        world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+        world.insert_resource(designations::DesignationConfig {
+            auto_jobs: true,
+            ..Default::default()
+        });
 
         // Create a designation that will generate a job
         world.spawn((
@@ -126,3 +131,241 @@ fn deterministic_rng_consistent_sequences() {
         "Job RNG streams should also be identical"
     );
 }
+
+#[test]
+fn audit_flags_clocks_and_unseeded_rngs_but_not_comments() {
+    let source = "\
+use std::time::Duration;
+// Instant::now is fine to mention in a comment
+fn tick() {
+    let t = std::time::Instant::now();
+    let r: u8 = rand::random();
+    let mut rng = rand::thread_rng();
+}
+";
+    let found: Vec<(usize, &str)> = audit_source(source)
+        .into_iter()
+        .map(|v| (v.line, v.pattern))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(4, "Instant::now"), (5, "rand::random"), (6, "thread_rng")]
+    );
+}
+
+#[test]
+fn engine_sources_only_reach_clocks_through_the_guard() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut offenders = Vec::new();
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.file_name().is_some_and(|n| n == "determinism.rs") {
+            continue;
+        }
+        let text = std::fs::read_to_string(&path).unwrap();
+        for v in audit_source(&text) {
+            offenders.push(format!("{}:{} {}", path.display(), v.line, v.pattern));
+        }
+    }
+    assert!(offenders.is_empty(), "{:#?}", offenders);
+}
+
+#[test]
+fn rng_streams_are_audited_per_system() {
+    let mut world = build_standard_world(12, 8, 3, WorldOptions::default());
+    let mut schedule = build_default_schedule();
+    let attacker = world
+        .spawn((CombatStats::new(20, 0, 0, 0, 5, 5), Health::full(20)))
+        .id();
+    let defender = world
+        .spawn((CombatStats::new(0, 0, 0, 0, 1, 1), Health::full(20)))
+        .id();
+    resolve_attack(&mut world, attacker, defender);
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(3, 3, TileKind::Wall)
+        .unwrap();
+    apply_world_command(&mut world, &WorldCommand::Designate { x: 3, y: 3 });
+    // The wall borders water on this map
+    apply_world_command(
+        &mut world,
+        &WorldCommand::ConfirmDesignations {
+            a: (3, 3),
+            b: (3, 3),
+        },
+    );
+    run_tick(&mut schedule, &mut world);
+
+    let rng = world.resource::<DeterministicRng>();
+    let audit = rng.audit();
+    // A hit draws twice: once to land, once for damage
+    assert!((1..=2).contains(&audit.uses("resolve_attack", RngStream::Combat)));
+    assert_eq!(
+        audit.streams_used_by("designation_to_jobs_system"),
+        vec![RngStream::Job]
+    );
+    assert!(audit
+        .log_lines()
+        .iter()
+        .any(|l| l.starts_with("resolve_attack: combat x")));
+}
+
+#[test]
+fn the_wall_clock_is_available_between_ticks() {
+    let start = wall_clock();
+    assert!(wall_clock() >= start);
+}
+
+#[cfg(feature = "determinism-guard")]
+#[test]
+#[should_panic(expected = "during a simulation tick")]
+fn the_guard_rejects_wall_clock_reads_inside_a_tick() {
+    use bevy_ecs::prelude::*;
+
+    fn peeks_at_the_clock() {
+        let _ = wall_clock();
+    }
+    let mut world = World::new();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(peeks_at_the_clock);
+    run_tick(&mut schedule, &mut world);
+}
+
+/// One entity of the shuffled colony
+fn spawn_part(world: &mut bevy_ecs::world::World, part: usize) {
+    let names = ["Grak", "Murz", "Snag", "Urok", "Bolg", "Yazn"];
+    match part {
+        0..=2 => {
+            world.spawn((
+                Name(names[part].into()),
+                Position(4 + part as i32, 3),
+                Velocity(0, 0),
+                Miner,
+                MiningSkill(2),
+                AssignedJob::default(),
+                VisionRadius::new(6),
+            ));
+        }
+        3..=5 => {
+            world.spawn((
+                Name(names[part].into()),
+                Position(part as i32, 6),
+                Velocity(0, 0),
+                Carrier,
+                Inventory::default(),
+                AssignedJob::default(),
+                VisionRadius::new(6),
+            ));
+        }
+        6 => {
+            world
+                .spawn(StockpileBundle::new(14, 8, 16, 10))
+                .insert(Name("Stockpile".into()));
+        }
+        12 => {
+            world.spawn(ConstructionBundle::new(ConstructionKind::Wall, 11, 4));
+        }
+        // Two stones stacked on one tile, told apart by name and quality, so
+        // which one a carrier picks up first shows in the save
+        13 | 14 => {
+            let (name, quality) = if part == 13 {
+                ("Fine stone", Quality::Fine)
+            } else {
+                ("Masterwork stone", Quality::Masterwork)
+            };
+            world.spawn((
+                Name(name.into()),
+                Item::stone(),
+                Stone,
+                Carriable,
+                quality,
+                Position(18, 10),
+            ));
+        }
+        _ => {
+            world.spawn((
+                Item::stone(),
+                Stone,
+                Carriable,
+                Position(part as i32 - 4, 10),
+            ));
+        }
+    }
+}
+
+/// A small colony spawned in `order`, churned through archetype moves
+fn shuffled_colony(order: &[usize]) -> bevy_ecs::world::World {
+    use bevy_ecs::prelude::*;
+
+    #[derive(Component)]
+    struct Churn;
+
+    let mut world = build_standard_world(20, 12, 11, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..12 {
+        for x in 0..20 {
+            let rock = (2..9).contains(&x) && y == 1;
+            let kind = if rock {
+                TileKind::Wall
+            } else {
+                TileKind::Floor
+            };
+            map.set_tile(x, y, kind).unwrap();
+        }
+    }
+    // Recycle a few entity slots so indices stop following spawn order
+    let spare: Vec<Entity> = (0..order.len()).map(|_| world.spawn_empty().id()).collect();
+    for &i in order {
+        world.despawn(spare[i]);
+    }
+    for &part in order {
+        spawn_part(&mut world, part);
+    }
+    let churned: Vec<Entity> = world
+        .query_filtered::<Entity, With<Position>>()
+        .iter(&world)
+        .step_by(2)
+        .collect();
+    for e in churned {
+        world.entity_mut(e).insert(Churn).remove::<Churn>();
+    }
+    apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (2, 1),
+            b: (8, 1),
+        },
+    );
+    world
+}
+
+#[test]
+fn spawn_order_does_not_change_the_outcome() {
+    let forward: Vec<usize> = (0..15).collect();
+    let reversed: Vec<usize> = forward.iter().rev().copied().collect();
+    let interleaved = [7, 12, 3, 14, 0, 11, 6, 2, 9, 4, 13, 1, 10, 5, 8];
+    let mut hashes = Vec::new();
+    for order in [&forward[..], &reversed, &interleaved] {
+        let mut world = shuffled_colony(order);
+        let mut schedule = build_default_schedule();
+        let mut trace = Vec::new();
+        for _ in 0..400 {
+            run_tick(&mut schedule, &mut world);
+            trace.push(save_world(&mut world).content_hash());
+        }
+        let map = world.resource::<GameMap>();
+        assert!((2..9).all(|x| map.get_tile(x, 1) == Some(TileKind::Floor)));
+        assert_eq!(map.get_tile(11, 4), Some(TileKind::Wall));
+        let stacked = world
+            .query::<(&Quality, &Position)>()
+            .iter(&world)
+            .filter(|(_, p)| (14..=16).contains(&p.0) && (8..=10).contains(&p.1))
+            .count();
+        assert_eq!(stacked, 2, "both stacked stones are hauled");
+        hashes.push(trace);
+    }
+    for (i, trace) in hashes.iter().enumerate().skip(1) {
+        let diverged = trace.iter().zip(&hashes[0]).position(|(a, b)| a != b);
+        assert_eq!(diverged, None, "order {i} diverged");
+    }
+}
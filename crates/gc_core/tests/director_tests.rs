@@ -0,0 +1,164 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+/// Raids every 100 ticks at no threat, every 50 from 10 threat; one
+/// raider at no threat, four from 10 threat
+fn config() -> DirectorConfig {
+    DirectorConfig {
+        grace_ticks: 10,
+        assess_interval: 5,
+        wealth_per_threat: 10,
+        threat_per_goblin: 2,
+        raid_size: Curve(vec![(0, 1), (10, 4)]),
+        raid_interval: Curve(vec![(0, 100), (10, 50)]),
+        burrower_percent: 50,
+    }
+}
+
+/// A floor map ringed by rock but for a gap at (0, 4)
+fn world(seed: u64) -> World {
+    let mut world = World::new();
+    world.insert_resource(GameMap::from_fn(10, 10, |x, y| {
+        let edge = x == 0 || y == 0 || x == 9 || y == 9;
+        if edge && (x, y) != (0, 4) {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    }));
+    world.insert_resource(Time::new(100));
+    world.insert_resource(DeterministicRng::new(seed));
+    world.insert_resource(RunMetrics::default());
+    world.insert_resource(EventDirector::default());
+    world.insert_resource(config());
+    world
+}
+
+fn run(world: &mut World, ticks: u32) {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((event_director_system, advance_time).chain());
+    for _ in 0..ticks {
+        schedule.run(world);
+    }
+}
+
+fn raiders(world: &mut World) -> Vec<((i32, i32), bool)> {
+    let mut q = world.query::<(&Faction, &Position, Has<Burrower>)>();
+    q.iter(world)
+        .filter(|(f, ..)| f.kind == FactionKind::Invaders)
+        .map(|(_, p, b)| ((p.0, p.1), b))
+        .collect()
+}
+
+#[test]
+fn curves_interpolate_and_clamp() {
+    let curve = Curve(vec![(10, 100), (20, 50), (40, 50)]);
+    assert_eq!(curve.sample(0), 100);
+    assert_eq!(curve.sample(15), 75);
+    assert_eq!(curve.sample(30), 50);
+    assert_eq!(curve.sample(1000), 50);
+    assert_eq!(Curve::default().sample(7), 0);
+}
+
+#[test]
+fn threat_grows_with_wealth_population_and_region() {
+    let config = config();
+    assert_eq!(threat_score(&config, 100, 0, 0), 0);
+    assert_eq!(threat_score(&config, 100, 95, 0), 9);
+    assert_eq!(threat_score(&config, 100, 40, 3), 10);
+    assert_eq!(threat_score(&config, 150, 40, 3), 15);
+    assert!(Biome::Mountains.params().raid_difficulty > Biome::Plains.params().raid_difficulty);
+}
+
+#[test]
+fn the_first_raid_comes_after_the_grace_period() {
+    let mut world = world(1);
+    run(&mut world, 10);
+    assert!(raiders(&mut world).is_empty());
+    assert_eq!(world.resource::<EventDirector>().next_raid, Some(10));
+
+    run(&mut world, 1);
+    // A poor, empty fort draws a single raider at the only way in
+    assert_eq!(raiders(&mut world), vec![((0, 4), false)]);
+    let director = world.resource::<EventDirector>();
+    assert_eq!(director.last_raid, 1);
+    assert_eq!(director.next_raid, Some(110));
+    assert_eq!(world.resource::<RunMetrics>().raids, 1);
+}
+
+#[test]
+fn rich_crowded_forts_draw_bigger_sooner_raids() {
+    let mut world = world(1);
+    for x in 1..4 {
+        world.spawn((Position(x, 1), AssignedJob::default()));
+    }
+    // Dead goblins and invaders are not population
+    world.spawn((Position(4, 1), AssignedJob::default(), Dead));
+    world.spawn((
+        Position(5, 1),
+        AssignedJob::default(),
+        Faction::new(FactionKind::Invaders),
+    ));
+    world.spawn((
+        Item {
+            item_type: ItemType::Gem,
+        },
+        Quality::Masterwork,
+        Position(2, 2),
+    ));
+    run(&mut world, 11);
+
+    let director = world.resource::<EventDirector>().clone();
+    assert_eq!(director.population, 3);
+    assert_eq!(director.wealth, 100);
+    assert_eq!(director.threat, 16);
+    assert_eq!(director.last_raid, 4);
+    assert_eq!(director.next_raid, Some(60));
+    let raid = raiders(&mut world);
+    let burrowing = raid.iter().filter(|(_, b)| *b).count();
+    // The raid joins the invader already there
+    assert_eq!((raid.len(), burrowing), (5, 2));
+}
+
+#[test]
+fn raids_enter_at_seeded_edge_tiles() {
+    let entries = |seed| {
+        let mut world = world(seed);
+        world.insert_resource(GameMap::new(10, 10));
+        run(&mut world, 11);
+        raiders(&mut world)[0].0
+    };
+    assert_eq!(entries(3), entries(3));
+    let (x, y) = entries(3);
+    assert!(x == 0 || y == 0 || x == 9 || y == 9);
+
+    // With no floor on the edge the raid is skipped, but the next is due
+    let mut world = world(3);
+    world.insert_resource(GameMap::from_fn(10, 10, |x, y| {
+        if x == 0 || y == 0 || x == 9 || y == 9 {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    }));
+    run(&mut world, 11);
+    assert!(raiders(&mut world).is_empty());
+    assert_eq!(world.resource::<EventDirector>().next_raid, Some(110));
+    assert_eq!(world.resource::<RunMetrics>().raids, 0);
+}
+
+#[test]
+fn standard_worlds_schedule_raids_and_save_the_schedule() {
+    let mut world = build_standard_world(16, 12, 5, WorldOptions::default());
+    let grace = world.resource::<DirectorConfig>().grace_ticks;
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<EventDirector>().next_raid, Some(grace));
+
+    world.resource_mut::<EventDirector>().next_raid = Some(1234);
+    let save = save_world(&mut world);
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    assert_eq!(loaded.resource::<EventDirector>().next_raid, Some(1234));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+
+fn world() -> World {
+    floor_world(20, 12, 9)
+}
+
+fn gear(world: &mut World, item_type: ItemType, quality: Quality, pos: (i32, i32)) -> Entity {
+    world
+        .spawn((
+            Item { item_type },
+            quality,
+            Gear::for_item(item_type).unwrap(),
+            Position(pos.0, pos.1),
+            Carriable,
+        ))
+        .id()
+}
+
+fn soldier(world: &mut World, squad: u32) -> Entity {
+    world
+        .spawn((
+            Position(0, 0),
+            Squad(squad),
+            Equipment::default(),
+            AssignedJob::default(),
+            CombatStats::new(2, 1, 0, 0, 3, 3),
+            Health::full(20),
+        ))
+        .id()
+}
+
+#[test]
+fn equipped_gear_adds_quality_scaled_bonuses() {
+    let mut world = world();
+    let sword = gear(&mut world, ItemType::Weapon, Quality::Fine, (0, 0));
+    let mail = gear(&mut world, ItemType::Armor, Quality::Poor, (0, 0));
+    let goblin = soldier(&mut world, 1);
+    world.entity_mut(goblin).insert(Equipment {
+        weapon: Some(sword),
+        armor: Some(mail),
+    });
+
+    let stats = effective_stats(&world, goblin).unwrap();
+    // Weapon 2 + fine 1; armor 2 - poor 1
+    assert_eq!(stats.attack, 3);
+    assert_eq!(stats.defense, 1);
+    assert!(effective_stats(&world, sword).is_none());
+}
+
+#[test]
+fn armor_reduces_damage_in_attack_resolution() {
+    let mut world = world();
+    let attacker = world
+        .spawn((CombatStats::new(20, 0, 0, 0, 5, 5), Health::full(20)))
+        .id();
+    let defender = soldier(&mut world, 1);
+    let plate = gear(&mut world, ItemType::Armor, Quality::Masterwork, (0, 0));
+    world.entity_mut(defender).insert(Equipment {
+        weapon: None,
+        armor: Some(plate),
+    });
+
+    let damage = (0..20)
+        .find_map(|_| match resolve_attack(&mut world, attacker, defender) {
+            Some(AttackOutcome::Hit { damage }) => Some(damage),
+            _ => None,
+        })
+        .expect("95% hit chance should land within 20 swings");
+    // 5 damage - (2 armor + 3 masterwork) floors at 1
+    assert_eq!(damage, 1);
+    assert!(world.get::<Health>(defender).unwrap().hp < 20);
+}
+
+#[test]
+fn squad_members_fetch_their_uniform_from_stockpiles() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn(StockpileBundle::new(4, 0, 6, 2));
+    let sword = gear(&mut world, ItemType::Weapon, Quality::Standard, (5, 1));
+    // Outside any stockpile: not handed out
+    gear(&mut world, ItemType::Armor, Quality::Standard, (1, 1));
+    let goblin = soldier(&mut world, 1);
+    world.resource_mut::<SquadUniforms>().0.insert(
+        1,
+        Uniform {
+            slots: vec![EquipSlot::Weapon, EquipSlot::Armor],
+            min_quality: None,
+        },
+    );
+
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    let equipment = *world.get::<Equipment>(goblin).unwrap();
+    assert_eq!(equipment.weapon, Some(sword));
+    assert_eq!(equipment.armor, None);
+    assert!(world.get::<Position>(sword).is_none());
+    assert_eq!(world.get::<EquippedBy>(sword).map(|e| e.0), Some(goblin));
+    assert!(world.get::<AssignedJob>(goblin).unwrap().0.is_none());
+}
+
+#[test]
+fn squad_members_walk_around_walls_to_their_uniform() {
+    let mut world = world();
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Walk,
+    });
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..11 {
+            map.set_tile(3, y, TileKind::Wall).unwrap();
+        }
+    }
+    world.spawn(StockpileBundle::new(4, 0, 6, 2));
+    let sword = gear(&mut world, ItemType::Weapon, Quality::Standard, (5, 1));
+    let goblin = soldier(&mut world, 1);
+    world.resource_mut::<SquadUniforms>().0.insert(
+        1,
+        Uniform {
+            slots: vec![EquipSlot::Weapon],
+            min_quality: None,
+        },
+    );
+
+    let mut schedule = build_default_schedule();
+    for _ in 0..100 {
+        schedule.run(&mut world);
+        let pos = *world.get::<Position>(goblin).unwrap();
+        let map = world.resource::<GameMap>();
+        assert_eq!(map.get_tile(pos.0, pos.1), Some(TileKind::Floor));
+    }
+    assert_eq!(world.get::<Equipment>(goblin).unwrap().weapon, Some(sword));
+}
+
+#[test]
+fn uniform_rejects_gear_below_minimum_quality() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn(StockpileBundle::new(2, 0, 3, 0));
+    gear(&mut world, ItemType::Weapon, Quality::Poor, (2, 0));
+    let good = gear(&mut world, ItemType::Weapon, Quality::Superior, (3, 0));
+    let goblin = soldier(&mut world, 2);
+    world.resource_mut::<SquadUniforms>().0.insert(
+        2,
+        Uniform {
+            slots: vec![EquipSlot::Weapon],
+            min_quality: Some(Quality::Fine),
+        },
+    );
+
+    for _ in 0..6 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<Equipment>(goblin).unwrap().weapon, Some(good));
+}
+
+#[test]
+fn gear_outside_the_uniform_is_dropped() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    let goblin = soldier(&mut world, 3);
+    let axe = world
+        .spawn((
+            Item {
+                item_type: ItemType::Weapon,
+            },
+            Gear::for_item(ItemType::Weapon).unwrap(),
+            EquippedBy(goblin),
+        ))
+        .id();
+    world.get_mut::<Equipment>(goblin).unwrap().weapon = Some(axe);
+    world
+        .resource_mut::<SquadUniforms>()
+        .0
+        .insert(3, Uniform::default());
+
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Equipment>(goblin).unwrap().weapon, None);
+    assert_eq!(world.get::<Position>(axe), Some(&Position(0, 0)));
+    assert!(world.get::<EquippedBy>(axe).is_none());
+}
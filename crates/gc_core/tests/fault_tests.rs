@@ -0,0 +1,239 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_core::{jobs, systems};
+
+fn job(n: u128, kind: JobKind) -> Job {
+    Job {
+        id: JobId(uuid::Uuid::from_u128(n)),
+        kind,
+        issued_by: IssuedBy::LOCAL_PLAYER,
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    }
+}
+
+/// World with just the resources the execution systems need
+fn base_world(faults: SimFaults) -> World {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(10, 10));
+    world.insert_resource(jobs::JobBoard::default());
+    world.insert_resource(jobs::ItemSpawnQueue::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    let mut time = systems::Time::new(100);
+    time.ticks = 7;
+    world.insert_resource(time);
+    world.insert_resource(faults);
+    world
+}
+
+fn activate(world: &mut World, job: Job) -> JobId {
+    let id = job.id;
+    world
+        .resource_mut::<jobs::ActiveJobs>()
+        .jobs
+        .insert(id, job);
+    id
+}
+
+fn run<M>(world: &mut World, systems: impl IntoSystemConfigs<M>) {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(systems);
+    schedule.run(world);
+}
+
+#[test]
+fn mining_a_tile_that_is_not_rock_is_reported() {
+    let mut world = base_world(SimFaults::default());
+    let id = activate(&mut world, job(1, JobKind::Mine { x: 5, y: 5 }));
+    let miner = world
+        .spawn((Position(5, 5), Miner, AssignedJob(Some(id))))
+        .id();
+
+    run(&mut world, jobs::mine_job_execution_system);
+
+    // The job is still dropped, as before
+    assert_eq!(world.get::<AssignedJob>(miner).unwrap().0, None);
+    assert!(world.resource::<jobs::ActiveJobs>().jobs.is_empty());
+    let faults = world.resource::<SimFaults>();
+    assert_eq!(faults.count(FaultKind::InvalidDesignationTarget), 1);
+    let fault = faults.iter().next().unwrap();
+    assert_eq!(fault.tick, 7);
+    assert_eq!(fault.entity, Some(miner));
+    assert_eq!(fault.system, "mine_job_execution_system");
+    assert!(fault.context.contains("(5, 5)"), "{}", fault.context);
+}
+
+#[test]
+fn assignments_to_missing_jobs_are_reported_and_released() {
+    let mut world = base_world(SimFaults::default());
+    world.insert_resource(JobGcConfig {
+        interval: 1,
+        ..Default::default()
+    });
+    let gone = JobId(uuid::Uuid::from_u128(9));
+    let miner = world
+        .spawn((Position(1, 1), Miner, AssignedJob(Some(gone))))
+        .id();
+    let hauler = world
+        .spawn((Position(2, 2), Carrier, AssignedJob(Some(gone))))
+        .id();
+
+    run(&mut world, jobs::mine_job_execution_system);
+    assert_eq!(world.get::<AssignedJob>(miner).unwrap().0, None);
+    run(&mut world, jobs::active_jobs_gc_system);
+    assert_eq!(world.get::<AssignedJob>(hauler).unwrap().0, None);
+
+    let mut faults = world.resource_mut::<SimFaults>();
+    assert_eq!(faults.count(FaultKind::OrphanAssignment), 2);
+    let reported: Vec<_> = faults.drain().into_iter().map(|f| f.entity).collect();
+    assert_eq!(reported, vec![Some(miner), Some(hauler)]);
+    // Draining keeps the counts
+    assert_eq!(faults.iter().count(), 0);
+    assert_eq!(faults.total(), 2);
+}
+
+#[test]
+fn hauls_without_their_item_are_reported() {
+    let mut world = base_world(SimFaults::default());
+    let id = activate(
+        &mut world,
+        job(
+            2,
+            JobKind::Haul {
+                from: (1, 1),
+                to: (4, 4),
+            },
+        ),
+    );
+    let carrier = world
+        .spawn((
+            Position(1, 1),
+            Carrier,
+            AssignedJob(Some(id)),
+            Inventory::default(),
+        ))
+        .id();
+
+    run(&mut world, systems::hauling_execution_system);
+
+    assert_eq!(world.get::<AssignedJob>(carrier).unwrap().0, None);
+    let faults = world.resource::<SimFaults>();
+    assert_eq!(faults.count(FaultKind::MissingItem), 1);
+    assert!(faults.iter().next().unwrap().context.contains("(1, 1)"));
+}
+
+#[test]
+fn refused_world_writes_are_reported_with_their_error() {
+    let mut world = base_world(SimFaults::default());
+    // A carried item that vanished before the drop
+    let haul = activate(
+        &mut world,
+        job(
+            4,
+            JobKind::Haul {
+                from: (1, 1),
+                to: (2, 2),
+            },
+        ),
+    );
+    let vanished = world.spawn(Item::stone()).id();
+    world.despawn(vanished);
+    let carrier = world
+        .spawn((
+            Position(2, 2),
+            Carrier,
+            AssignedJob(Some(haul)),
+            Inventory(Some(vanished)),
+        ))
+        .id();
+    // A wall site just off the right edge, one tick from done
+    let build = activate(&mut world, job(5, JobKind::Build { x: 10, y: 5 }));
+    let site = world
+        .spawn((
+            Position(10, 5),
+            Construction {
+                kind: ConstructionKind::Wall,
+                progress: ConstructionKind::Wall.work_ticks() - 1,
+                build_job: Some(build),
+            },
+        ))
+        .id();
+    world.spawn((Position(9, 5), Miner, AssignedJob(Some(build))));
+
+    run(
+        &mut world,
+        (systems::hauling_execution_system, build_execution_system).chain(),
+    );
+
+    let faults = world.resource::<SimFaults>();
+    let reported: Vec<_> = faults
+        .iter()
+        .map(|f| (f.kind, f.entity, f.context.clone()))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            (
+                FaultKind::MissingItem,
+                Some(carrier),
+                GcError::InvalidEntity(vanished).to_string()
+            ),
+            (
+                FaultKind::OutOfBounds,
+                Some(site),
+                "(10, 5) is outside the 10x10 map".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fault_totals_show_up_in_view_metrics() {
+    let mut world = base_world(SimFaults::default());
+    assert_eq!(snapshot_world(&mut world).metrics.faults, 0);
+    let id = activate(&mut world, job(3, JobKind::Mine { x: 50, y: 50 }));
+    world.spawn((Position(1, 1), Miner, AssignedJob(Some(id))));
+    run(&mut world, jobs::mine_job_execution_system);
+
+    let fault = world.resource::<SimFaults>().iter().next().unwrap().clone();
+    assert!(fault.to_string().contains("off the map"), "{fault}");
+    assert_eq!(snapshot_world(&mut world).metrics.faults, 1);
+}
+
+#[test]
+fn the_log_is_bounded_but_counts_are_not() {
+    let mut faults = SimFaults::default();
+    faults.capacity = 2;
+    for tick in 0..5 {
+        faults.push(SimFault {
+            tick,
+            kind: FaultKind::MissingItem,
+            system: "test",
+            entity: None,
+            context: String::new(),
+        });
+    }
+    let ticks: Vec<u64> = faults.iter().map(|f| f.tick).collect();
+    assert_eq!(ticks, vec![3, 4]);
+    assert_eq!(faults.count(FaultKind::MissingItem), 5);
+    assert_eq!(faults.count(FaultKind::OrphanAssignment), 0);
+}
+
+#[test]
+#[should_panic(expected = "simulation fault")]
+fn strict_mode_panics_on_the_first_fault() {
+    let mut world = base_world(SimFaults::strict());
+    let id = activate(&mut world, job(4, JobKind::Mine { x: 5, y: 5 }));
+    world.spawn((Position(5, 5), Miner, AssignedJob(Some(id))));
+    run(&mut world, jobs::mine_job_execution_system);
+}
+
+#[test]
+fn standard_worlds_collect_faults_but_do_not_panic() {
+    let world = build_standard_world(16, 12, 7, WorldOptions::default());
+    let faults = world.resource::<SimFaults>();
+    assert!(!faults.strict);
+    assert_eq!(faults.total(), 0);
+}
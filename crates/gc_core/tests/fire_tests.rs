@@ -0,0 +1,239 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::fire::{
+    extinguish_execution_system, extinguish_job_assignment_system, fire_job_system, fire_system,
+    ignite, FireConfig, FireMap,
+};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open floor over `material`
+fn world(material: Material) -> World {
+    let mut world = floor_world(16, 8, 5);
+    world.insert_resource(MaterialMap::filled(16, 8, material));
+    world
+}
+
+fn fire_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(fire_system);
+    schedule
+}
+
+fn burning(world: &World) -> Vec<(i32, i32)> {
+    world
+        .resource::<FireMap>()
+        .burning()
+        .map(|b| (b.x, b.y))
+        .collect()
+}
+
+#[test]
+fn fire_spreads_over_soil_and_burns_out() {
+    let run = || {
+        let mut world = world(Material::Soil);
+        // A strip of soil fenced in by walls
+        {
+            let mut map = world.resource_mut::<GameMap>();
+            for x in 0..16 {
+                map.set_tile(x, 0, TileKind::Wall).unwrap();
+                map.set_tile(x, 2, TileKind::Wall).unwrap();
+            }
+        }
+        assert!(ignite(&mut world, 0, 1));
+        assert!(!ignite(&mut world, 0, 1), "already burning");
+        let mut schedule = fire_only();
+        let mut history = Vec::new();
+        for _ in 0..80 {
+            schedule.run(&mut world);
+            history.push(burning(&world));
+        }
+        (world, history)
+    };
+    let (world, history) = run();
+    let config = FireConfig::default();
+    // Each fire lights its neighbour after `spread_after` ticks
+    assert_eq!(history[config.spread_after as usize - 2], vec![(0, 1)]);
+    assert_eq!(
+        history[config.spread_after as usize - 1],
+        vec![(0, 1), (1, 1)]
+    );
+    assert!(history.iter().any(|b| b.len() > 1));
+    // Everything burned once and nothing is left alight
+    let fire = world.resource::<FireMap>();
+    assert!(burning(&world).is_empty());
+    assert!((0..16).all(|x| fire.is_scorched(x, 1)));
+    assert!(!fire.is_scorched(0, 0), "walls never burn");
+    // Same start, same fire
+    assert_eq!(run().1, history);
+}
+
+#[test]
+fn lava_lights_flammable_items_but_not_bare_rock() {
+    let mut world = world(Material::Igneous);
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(8, 4, TileKind::Lava)
+        .unwrap();
+    world.spawn((
+        Item {
+            item_type: ItemType::Wood,
+        },
+        Carriable,
+        Position(9, 4),
+    ));
+    world.spawn((Item::stone(), Carriable, Position(7, 4)));
+    assert!(!ignite(&mut world, 3, 3), "rock has no fuel");
+
+    let mut schedule = fire_only();
+    schedule.run(&mut world);
+    assert_eq!(burning(&world), vec![(9, 4)]);
+    // The wood was consumed into the fire; the stone lies untouched
+    let items: Vec<ItemType> = world
+        .query::<&Item>()
+        .iter(&world)
+        .map(|i| i.item_type)
+        .collect();
+    assert_eq!(items, vec![ItemType::Stone]);
+    let fuel = world.resource::<FireMap>().burning().next().unwrap().fuel;
+    assert_eq!(fuel, FireConfig::default().item_fuel - 1);
+
+    let announced: Vec<String> = world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| a.text.clone())
+        .collect();
+    assert_eq!(announced, vec!["Fire has broken out at (9, 4)".to_string()]);
+
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert!(burning(&world).is_empty());
+}
+
+#[test]
+fn standing_in_fire_hurts() {
+    let mut world = world(Material::Soil);
+    let goblin = world.spawn((Position(4, 4), Health::full(20))).id();
+    let bystander = world.spawn((Position(12, 4), Health::full(20))).id();
+    ignite(&mut world, 4, 4);
+    let mut schedule = fire_only();
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    let damage = FireConfig::default().damage;
+    assert_eq!(world.get::<Health>(goblin).unwrap().hp, 20 - 2 * damage);
+    assert_eq!(world.get::<Health>(bystander).unwrap().hp, 20);
+}
+
+#[test]
+fn carriers_fetch_water_and_douse_fires() {
+    let mut world = world(Material::Igneous);
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(0, 7, TileKind::Water)
+        .unwrap();
+    let carrier = world
+        .spawn((
+            Position(2, 5),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ))
+        .id();
+    // A long-burning coal fire, far enough from the water to need the walk
+    world.insert_resource(FireConfig {
+        item_fuel: 40,
+        ..Default::default()
+    });
+    world.spawn((
+        Item {
+            item_type: ItemType::Coal,
+        },
+        Carriable,
+        Position(10, 2),
+    ));
+    assert!(ignite(&mut world, 10, 2));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            fire_system,
+            fire_job_system,
+            extinguish_job_assignment_system,
+            extinguish_execution_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+    assert!(matches!(
+        world
+            .resource::<ActiveJobs>()
+            .jobs
+            .values()
+            .next()
+            .map(|j| &j.kind),
+        Some(JobKind::Extinguish { x: 10, y: 2 })
+    ));
+    for _ in 0..20 {
+        schedule.run(&mut world);
+        if burning(&world).is_empty() {
+            break;
+        }
+    }
+    let fire = world.resource::<FireMap>();
+    assert!(burning(&world).is_empty());
+    assert!(fire.is_wet(10, 2));
+    assert!(!fire.is_scorched(10, 2), "put out before it burned out");
+    assert_eq!(world.get::<AssignedJob>(carrier).unwrap().0, None);
+    assert!(world.resource::<JobBoard>().0.is_empty());
+    // Wet tiles refuse to burn
+    assert!(!ignite(&mut world, 10, 2));
+}
+
+#[test]
+fn fires_are_smothered_without_water_in_the_full_schedule() {
+    let mut world = world(Material::Soil);
+    world.spawn((
+        Position(6, 6),
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+    ));
+    world.insert_resource(FireConfig {
+        spread_after: 50,
+        ..Default::default()
+    });
+    ignite(&mut world, 6, 4);
+    let mut schedule = build_default_schedule();
+    for _ in 0..FireConfig::default().smother_ticks + 4 {
+        schedule.run(&mut world);
+    }
+    let fire = world.resource::<FireMap>();
+    assert!(burning(&world).is_empty());
+    assert!(fire.is_wet(6, 4));
+}
+
+#[test]
+fn fire_state_survives_save_and_load() {
+    let mut world = world(Material::Soil);
+    ignite(&mut world, 3, 3);
+    let mut schedule = fire_only();
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    let before = burning(&world);
+    assert!(before.len() > 1);
+    let save = save_world(&mut world);
+    let json = serde_json::to_string(&save).unwrap();
+
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    assert_eq!(burning(&loaded), before);
+    assert_eq!(
+        loaded.resource::<FireMap>().burning().collect::<Vec<_>>(),
+        world.resource::<FireMap>().burning().collect::<Vec<_>>()
+    );
+}
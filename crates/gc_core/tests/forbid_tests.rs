@@ -0,0 +1,176 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::prelude::*;
+use std::collections::BTreeSet;
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn stone(world: &mut World, x: i32, y: i32) -> Entity {
+    world.spawn((Item::stone(), Carriable, Position(x, y))).id()
+}
+
+fn hauls_from(world: &World) -> Vec<(i32, i32)> {
+    let mut from: Vec<(i32, i32)> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|job| match job.kind {
+            JobKind::Haul { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect();
+    from.sort();
+    from
+}
+
+#[test]
+fn forbidden_items_are_not_hauled_until_unforbidden() {
+    let mut world = world();
+    world.spawn(StockpileBundle::new(15, 1, 17, 3));
+    let mut schedule = Schedule::default();
+    schedule.add_systems(auto_haul_system);
+    let loose = stone(&mut world, 2, 2);
+    let kept = stone(&mut world, 5, 5);
+    schedule.run(&mut world);
+    assert_eq!(hauls_from(&world), vec![(2, 2), (5, 5)]);
+
+    // Forbidding withdraws the haul already posted for the item
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::ForbidItems {
+            a: (6, 6),
+            b: (4, 4)
+        }
+    ));
+    assert_eq!(world.get::<Forbidden>(kept), Some(&Forbidden::default()));
+    assert!(world.get::<Forbidden>(loose).is_none());
+    assert_eq!(hauls_from(&world), vec![(2, 2)]);
+    // Nothing left to forbid there
+    assert!(!apply_world_command(
+        &mut world,
+        &WorldCommand::ForbidItems {
+            a: (5, 5),
+            b: (5, 5)
+        }
+    ));
+    schedule.run(&mut world);
+    assert_eq!(hauls_from(&world), vec![(2, 2)]);
+
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::UnforbidItems {
+            a: (0, 0),
+            b: (19, 11)
+        }
+    ));
+    assert!(world.get::<Forbidden>(kept).is_none());
+    schedule.run(&mut world);
+    assert_eq!(hauls_from(&world), vec![(2, 2), (5, 5)]);
+}
+
+#[test]
+fn carriers_leave_items_forbidden_after_the_haul_was_taken() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(hauling_execution_system);
+    let item = stone(&mut world, 3, 3);
+    let job = Job {
+        id: JobId(uuid::Uuid::from_u128(1)),
+        kind: JobKind::Haul {
+            from: (3, 3),
+            to: (8, 8),
+        },
+        issued_by: IssuedBy::System,
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    };
+    world
+        .resource_mut::<ActiveJobs>()
+        .jobs
+        .insert(job.id, job.clone());
+    let carrier = world
+        .spawn((
+            Carrier,
+            Inventory::default(),
+            AssignedJob(Some(job.id)),
+            Position(3, 3),
+        ))
+        .id();
+    world.entity_mut(item).insert(Forbidden::default());
+    schedule.run(&mut world);
+
+    assert_eq!(world.get::<AssignedJob>(carrier).unwrap().0, None);
+    assert_eq!(world.get::<Inventory>(carrier).unwrap().0, None);
+    assert_eq!(world.get::<Position>(item), Some(&Position(3, 3)));
+    assert_eq!(world.resource::<SimFaults>().total(), 0);
+}
+
+#[test]
+fn stations_skip_forbidden_inputs() {
+    let mut world = world();
+    world.spawn((
+        Workshop,
+        Station::new(StationKind::Smelter),
+        Position(10, 5),
+    ));
+    let coal = world
+        .spawn((
+            Item {
+                item_type: ItemType::Coal,
+            },
+            Carriable,
+            Position(2, 5),
+        ))
+        .id();
+    world.entity_mut(coal).insert(Forbidden::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems(station_supply_system);
+    schedule.run(&mut world);
+    assert!(hauls_from(&world).is_empty());
+
+    world.entity_mut(coal).remove::<Forbidden>();
+    schedule.run(&mut world);
+    assert_eq!(hauls_from(&world), vec![(2, 5)]);
+}
+
+#[test]
+fn danger_forbids_items_nearby_until_it_passes() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(danger_forbid_system);
+    let near = stone(&mut world, 6, 5);
+    let far = stone(&mut world, 12, 5);
+    let ordered = stone(&mut world, 5, 6);
+    world.entity_mut(ordered).insert(Forbidden::default());
+    world
+        .resource_mut::<DangerMap>()
+        .set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(5, 5)]));
+    schedule.run(&mut world);
+    assert_eq!(
+        world.get::<Forbidden>(near),
+        Some(&Forbidden { by_danger: true })
+    );
+    assert!(world.get::<Forbidden>(far).is_none());
+
+    // The player's own forbid outlives the danger
+    world
+        .resource_mut::<DangerMap>()
+        .set_known_hostiles(FactionKind::Goblins, BTreeSet::new());
+    schedule.run(&mut world);
+    assert!(world.get::<Forbidden>(near).is_none());
+    assert_eq!(world.get::<Forbidden>(ordered), Some(&Forbidden::default()));
+
+    // Switched off, danger forbids nothing
+    world.insert_resource(ForbidConfig { danger: None });
+    world
+        .resource_mut::<DangerMap>()
+        .set_known_hostiles(FactionKind::Goblins, BTreeSet::from([(5, 5)]));
+    schedule.run(&mut world);
+    assert!(world.get::<Forbidden>(near).is_none());
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::jobs;
+use gc_core::prelude::*;
+
+/// Open map with lava at (18, 10) and a stockpile in the far corner
+fn world() -> World {
+    let mut world = floor_world(20, 12, 5);
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(18, 10, TileKind::Lava)
+        .unwrap();
+    world.spawn(StockpileBundle::new(0, 0, 1, 1));
+    world
+}
+
+fn spawn(world: &mut World, item_type: ItemType, x: i32, y: i32) {
+    world.spawn((Item { item_type }, Position(x, y), Carriable));
+}
+
+/// (from, priority) of the queued hauls, in board order
+fn hauls(world: &World) -> Vec<((i32, i32), u32)> {
+    world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|j| match j.kind {
+            JobKind::Haul { from, .. } => Some((from, j.priority)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn scores_add_value_exposure_and_age() {
+    let config = HaulPriorityConfig::default();
+    let world = world();
+    let map = world.resource::<GameMap>();
+    let score = |item_type, quality, at, waited| {
+        haul_priority(&config, Some(map), item_type, quality, at, waited)
+    };
+    assert_eq!(score(ItemType::Stone, None, (5, 5), 0), 2);
+    assert_eq!(score(ItemType::Gem, None, (5, 5), 0), 25);
+    assert_eq!(
+        score(ItemType::Weapon, Some(Quality::Masterwork), (5, 5), 0),
+        item_value(ItemType::Weapon, Some(Quality::Masterwork))
+    );
+    // Lava's 40 points fall off over four rings
+    assert_eq!(score(ItemType::Stone, None, (18, 10), 0), 2 + 40);
+    assert_eq!(score(ItemType::Stone, None, (17, 9), 0), 2 + 30);
+    assert_eq!(score(ItemType::Stone, None, (15, 10), 0), 2 + 10);
+    assert_eq!(score(ItemType::Stone, None, (14, 10), 0), 2);
+    // A point per ten ticks waited, at most twenty
+    assert_eq!(score(ItemType::Stone, None, (5, 5), 39), 2 + 3);
+    assert_eq!(score(ItemType::Stone, None, (5, 5), 10_000), 2 + 20);
+    assert_eq!(
+        haul_priority(&config, None, ItemType::Stone, None, (18, 10), 0),
+        2
+    );
+
+    let mut water = map.clone();
+    water.set_tile(5, 6, TileKind::Water).unwrap();
+    assert_eq!(exposure(&config, &water, 5, 5), 15);
+    water.set_tile(4, 4, TileKind::Lava).unwrap();
+    assert_eq!(
+        exposure(&config, &water, 5, 5),
+        30,
+        "the worse hazard counts"
+    );
+}
+
+#[test]
+fn exposed_items_are_hauled_first_when_throttled() {
+    let mut world = world();
+    world.insert_resource(JobThrottle {
+        hauls_per_tick: 1,
+        ..Default::default()
+    });
+    spawn(&mut world, ItemType::Stone, 6, 6);
+    spawn(&mut world, ItemType::Stone, 17, 10);
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(hauls(&world), vec![((17, 10), 2 + 30)]);
+    schedule.run(&mut world);
+    assert_eq!(hauls(&world), vec![((17, 10), 32), ((6, 6), 2)]);
+}
+
+#[test]
+fn carriers_take_the_highest_priority_haul() {
+    let mut world = world();
+    spawn(&mut world, ItemType::Stone, 6, 6);
+    spawn(&mut world, ItemType::Stone, 8, 6);
+    spawn(&mut world, ItemType::Gem, 10, 6);
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(hauls(&world), vec![((10, 6), 25), ((6, 6), 2), ((8, 6), 2)]);
+
+    // Among equals the first on the board goes first
+    world.resource_mut::<JobBoard>().0.reverse();
+    let carriers: Vec<Entity> = (0..2)
+        .map(|_| {
+            world
+                .spawn((
+                    Position(2, 2),
+                    Carrier,
+                    AssignedJob::default(),
+                    Inventory::default(),
+                ))
+                .id()
+        })
+        .collect();
+    let mut assign = Schedule::default();
+    assign.add_systems(jobs::job_assignment_system);
+    assign.run(&mut world);
+    assign.run(&mut world);
+    let held: Vec<(i32, i32)> = carriers
+        .iter()
+        .map(|&c| {
+            let id = world.get::<AssignedJob>(c).unwrap().0.unwrap();
+            match world.resource::<ActiveJobs>().jobs[&id].kind {
+                JobKind::Haul { from, .. } => from,
+                _ => unreachable!(),
+            }
+        })
+        .collect();
+    assert_eq!(held, vec![(10, 6), (8, 6)]);
+}
+
+#[test]
+fn waiting_items_gain_priority_with_age() {
+    let mut world = world();
+    apply_world_command(
+        &mut world,
+        &WorldCommand::SetFillLimit {
+            x: 0,
+            y: 0,
+            limit: Some(FillLimit::new(0, OverflowPolicy::Reject)),
+        },
+    );
+    spawn(&mut world, ItemType::Stone, 6, 6);
+    let mut schedule = build_default_schedule();
+    for _ in 0..50 {
+        schedule.run(&mut world);
+    }
+    assert!(hauls(&world).is_empty());
+
+    apply_world_command(
+        &mut world,
+        &WorldCommand::SetFillLimit {
+            x: 0,
+            y: 0,
+            limit: None,
+        },
+    );
+    schedule.run(&mut world);
+    assert_eq!(hauls(&world), vec![((6, 6), 2 + 5)]);
+}
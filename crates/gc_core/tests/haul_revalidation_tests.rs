@@ -0,0 +1,141 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::{build_default_schedule, fast_forward};
+use gc_core::prelude::*;
+
+/// Open 20x12 floor with a priority stockpile at (2..=3, 2) and a stone
+/// at (5, 8)
+fn world() -> (World, Entity, Entity) {
+    let mut world = floor_world(20, 12, 5);
+    let pile = world
+        .spawn(StockpileBundle::new(2, 2, 3, 2).with_priority(5))
+        .id();
+    let stone = world.spawn((Item::stone(), Carriable, Position(5, 8))).id();
+    (world, pile, stone)
+}
+
+fn carrier(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+            Position(x, y),
+            MovementOverride(MovementMode::Step),
+        ))
+        .id()
+}
+
+fn hauls(world: &World) -> Vec<Job> {
+    world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .chain(world.resource::<ActiveJobs>().jobs.values())
+        .filter(|j| matches!(j.kind, JobKind::Haul { .. }))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn revalidation_keeps_retargets_or_gives_up() {
+    let near = Position(2, 2);
+    let far = Position(12, 2);
+    let bounds = ZoneBounds::new(2, 2, 3, 2);
+    let any = Stockpile {
+        accepts: None,
+        priority: 0,
+    };
+    let gems = Stockpile {
+        accepts: Some(vec![ItemType::Gem]),
+        priority: 0,
+    };
+    let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+
+    let both = [(a, &near, &any, Some(&bounds)), (b, &far, &any, None)];
+    assert_eq!(
+        revalidate_haul(&both, (5, 8), (3, 2), ItemType::Stone),
+        HaulDestination::Valid
+    );
+    let refiltered = [(a, &near, &gems, Some(&bounds)), (b, &far, &any, None)];
+    assert_eq!(
+        revalidate_haul(&refiltered, (5, 8), (3, 2), ItemType::Stone),
+        HaulDestination::Retarget((12, 2))
+    );
+    assert_eq!(
+        revalidate_haul(&refiltered[..1], (5, 8), (3, 2), ItemType::Stone),
+        HaulDestination::Gone
+    );
+}
+
+#[test]
+fn queued_hauls_follow_a_removed_stockpile_to_the_next_one() {
+    let (mut world, _, stone) = world();
+    world.spawn(StockpileBundle::new(12, 2, 13, 2));
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let posted = hauls(&world);
+    assert_eq!(posted.len(), 1);
+    assert!(posted[0].to_stockpile);
+    assert!(matches!(posted[0].kind, JobKind::Haul { to: (2, 2), .. }));
+
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::RemoveZone { x: 2, y: 2 }
+    ));
+    let hauler = carrier(&mut world, 5, 7);
+    schedule.run(&mut world);
+    assert!(world.get::<AssignedJob>(hauler).unwrap().0.is_some());
+    assert!(matches!(
+        hauls(&world)[..],
+        [Job {
+            kind: JobKind::Haul { to: (12, 2), .. },
+            ..
+        }]
+    ));
+
+    let done = fast_forward(&mut world, &mut schedule, 40, |w| {
+        w.get::<Position>(stone) == Some(&Position(12, 2))
+    });
+    assert!(done.is_some(), "the stone reached the remaining stockpile");
+}
+
+#[test]
+fn a_refiltered_stockpile_leaves_the_item_for_auto_haul_at_pickup() {
+    let (mut world, pile, stone) = world();
+    let hauler = carrier(&mut world, 5, 4);
+    let mut schedule = build_default_schedule();
+    let assigned = fast_forward(&mut world, &mut schedule, 5, |w| {
+        w.get::<AssignedJob>(hauler).unwrap().0.is_some()
+    });
+    assert!(assigned.is_some());
+
+    // The stockpile stops taking stone while the carrier is on its way
+    world.get_mut::<Stockpile>(pile).unwrap().accepts = Some(vec![ItemType::Gem]);
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::Zone {
+            kind: ZoneKind::Refuse,
+            a: (16, 9),
+            b: (17, 10),
+            accepts: None,
+        }
+    ));
+    let idle = fast_forward(&mut world, &mut schedule, 10, |w| {
+        w.get::<AssignedJob>(hauler).unwrap().0.is_none()
+    });
+    assert!(idle.is_some());
+    assert_eq!(world.get::<Position>(stone), Some(&Position(5, 8)));
+    assert!(world.get::<Inventory>(hauler).unwrap().0.is_none());
+    assert_eq!(world.resource::<SimFaults>().total(), 0);
+
+    // Handed back to auto-haul, which finds it the refuse pile instead
+    let dumped = fast_forward(&mut world, &mut schedule, 40, |w| {
+        w.get::<Position>(stone)
+            .is_some_and(|p| (16..=17).contains(&p.0) && (9..=10).contains(&p.1))
+    });
+    assert!(dumped.is_some());
+    assert!(hauls(&world).iter().all(|j| !j.to_stockpile));
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+#[test]
+fn injury_thresholds() {
+    assert_eq!(
+        InjuryState::from_health(&Health::new(50, 100)),
+        InjuryState::Healthy
+    );
+    assert_eq!(
+        InjuryState::from_health(&Health::new(49, 100)),
+        InjuryState::Wounded
+    );
+    assert_eq!(
+        InjuryState::from_health(&Health::new(24, 100)),
+        InjuryState::Crippled
+    );
+    assert_eq!(InjuryState::Crippled.action_interval(), 4);
+}
+
+#[test]
+fn wounded_entities_move_at_half_pace() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    let healthy = world
+        .spawn((Position(0, 1), Velocity(1, 0), Health::full(10)))
+        .id();
+    let wounded = world
+        .spawn((Position(0, 2), Velocity(1, 0), Health::new(4, 10)))
+        .id();
+    for _ in 0..8 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<Position>(healthy).unwrap().0, 8);
+    assert_eq!(world.get::<Position>(wounded).unwrap().0, 4);
+}
+
+#[test]
+fn crippled_worker_rests_in_nearest_bed_until_healed() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn((Position(15, 5), Bed));
+    world.spawn((Position(4, 5), Bed));
+    let miner = world
+        .spawn((
+            Name("Grak".into()),
+            Position(1, 5),
+            Miner,
+            AssignedJob::default(),
+            Health::new(2, 10),
+        ))
+        .id();
+
+    schedule.run(&mut world);
+    let job_id = world.get::<AssignedJob>(miner).unwrap().0.unwrap();
+    let kind = world.resource::<ActiveJobs>().jobs[&job_id].kind.clone();
+    assert_eq!(kind, JobKind::Rest { x: 4, y: 5 });
+
+    // Crippled pace: one tile every fourth tick
+    for _ in 0..16 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(*world.get::<Position>(miner).unwrap(), Position(4, 5));
+    assert!(world.get::<Resting>(miner).is_some());
+
+    for _ in 0..120 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<Health>(miner).unwrap().hp, 10);
+    assert!(world.get::<Resting>(miner).is_none());
+    assert_eq!(world.get::<AssignedJob>(miner).unwrap().0, None);
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn resting_workers_walk_around_walls_to_their_bed() {
+    let mut world = world();
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Walk,
+    });
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..11 {
+            map.set_tile(3, y, TileKind::Wall).unwrap();
+        }
+    }
+    world.spawn((Position(6, 5), Bed));
+    let miner = world
+        .spawn((
+            Position(1, 5),
+            Miner,
+            AssignedJob::default(),
+            Health::new(2, 10),
+        ))
+        .id();
+    let mut schedule = build_default_schedule();
+    for _ in 0..200 {
+        schedule.run(&mut world);
+        let pos = *world.get::<Position>(miner).unwrap();
+        let map = world.resource::<GameMap>();
+        assert_eq!(map.get_tile(pos.0, pos.1), Some(TileKind::Floor));
+    }
+    assert_eq!(*world.get::<Position>(miner).unwrap(), Position(6, 5));
+}
+
+#[test]
+fn interrupted_job_returns_to_board() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    let miner = world
+        .spawn((
+            Position(1, 1),
+            Miner,
+            AssignedJob::default(),
+            Health::full(10),
+        ))
+        .id();
+    // Occupy the miner with a job that cannot complete this tick
+    let id = JobId(uuid::Uuid::nil());
+    world.resource_mut::<ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Haul {
+                from: (0, 0),
+                to: (1, 1),
+            },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    world.get_mut::<AssignedJob>(miner).unwrap().0 = Some(id);
+    world.get_mut::<Health>(miner).unwrap().take_damage(8);
+
+    schedule.run(&mut world);
+    assert!(world.resource::<JobBoard>().0.iter().any(|j| j.id == id));
+    assert_ne!(world.get::<AssignedJob>(miner).unwrap().0, Some(id));
+}
+
+#[test]
+fn zero_hp_marks_dead_and_announces() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    let grak = world
+        .spawn((Name("Grak".into()), Position(1, 1), Goblin, Health::full(5)))
+        .id();
+    world.get_mut::<Health>(grak).unwrap().take_damage(5);
+    schedule.run(&mut world);
+    assert!(world.get::<Dead>(grak).is_some());
+    // Announced in the same tick the entity dies
+    let log = world.resource::<Announcements>();
+    assert!(log.iter().any(|a| a.text == "Grak has died!"));
+}
@@ -0,0 +1,195 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{
+    build_default_schedule, build_standard_world, fast_forward, WorldOptions,
+};
+use gc_core::prelude::*;
+
+fn floor_world() -> World {
+    let mut world = build_standard_world(12, 8, 3, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for (pos, _) in map.iter_rect((0, 0), (11, 7)).collect::<Vec<_>>() {
+        map.set_tile(pos.0, pos.1, TileKind::Floor).unwrap();
+    }
+    world
+}
+
+fn history_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(history_system);
+    schedule
+}
+
+fn events(world: &World, entity: Entity) -> Vec<HistoryEvent> {
+    entity_history(world, entity)
+        .into_iter()
+        .map(|e| e.event)
+        .collect()
+}
+
+#[test]
+fn mine_jobs_are_taken_and_finished() {
+    let mut world = floor_world();
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(5, 5, TileKind::Wall)
+        .unwrap();
+    let miner = world
+        .spawn((
+            Name("Grak".into()),
+            Miner,
+            MiningSkill(u8::MAX),
+            AssignedJob::default(),
+            Position(4, 5),
+        ))
+        .id();
+    world.spawn(DesignationBundle {
+        pos: Position(5, 5),
+        kind: MineDesignation,
+        lifecycle: DesignationLifecycle::default(),
+    });
+
+    let mut schedule = build_default_schedule();
+    let done = fast_forward(&mut world, &mut schedule, 20, |w| {
+        w.resource::<GameMap>().get_tile(5, 5) == Some(TileKind::Floor)
+    });
+    assert!(done.is_some());
+    schedule.run(&mut world);
+    let mine = JobKind::Mine { x: 5, y: 5 };
+    assert_eq!(
+        events(&world, miner),
+        vec![
+            HistoryEvent::JobTaken(mine.clone()),
+            HistoryEvent::JobEnded {
+                kind: mine,
+                finished: true,
+            },
+        ]
+    );
+    let lines = describe_history(&world, miner);
+    assert!(lines[0].ends_with(" took mine (5, 5)"), "{lines:?}");
+    assert!(lines[1].ends_with(" finished mine (5, 5)"), "{lines:?}");
+
+    // The view carries the same lines for the TUI
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.entity(miner.to_bits()).unwrap().history, lines);
+}
+
+#[test]
+fn jobs_handed_back_to_the_board_count_as_given_up() {
+    let mut world = floor_world();
+    let job = Job {
+        id: JobId(uuid::Uuid::from_u128(7)),
+        kind: JobKind::Build { x: 2, y: 2 },
+        issued_by: IssuedBy::default(),
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    };
+    world
+        .resource_mut::<ActiveJobs>()
+        .jobs
+        .insert(job.id, job.clone());
+    let worker = world.spawn(AssignedJob(Some(job.id))).id();
+    let mut schedule = history_schedule();
+    schedule.run(&mut world);
+
+    world.resource_mut::<ActiveJobs>().jobs.clear();
+    world.resource_mut::<JobBoard>().0.push(job.clone());
+    world.get_mut::<AssignedJob>(worker).unwrap().0 = None;
+    schedule.run(&mut world);
+    assert_eq!(
+        events(&world, worker),
+        vec![
+            HistoryEvent::JobTaken(job.kind.clone()),
+            HistoryEvent::JobEnded {
+                kind: job.kind,
+                finished: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn rests_deaths_and_despawns_are_tracked() {
+    let mut world = floor_world();
+    let mut schedule = history_schedule();
+    let goblin = world.spawn((Goblin, Position(1, 1))).id();
+    let other = world.spawn((Goblin, Position(2, 1))).id();
+    world.entity_mut(goblin).insert(Resting);
+    schedule.run(&mut world);
+    world.entity_mut(goblin).remove::<Resting>();
+    schedule.run(&mut world);
+    // Dying takes a worker out of bed without resting it
+    world.entity_mut(other).insert(Resting);
+    schedule.run(&mut world);
+    world.entity_mut(other).insert(Dead).remove::<Resting>();
+    schedule.run(&mut world);
+
+    assert_eq!(
+        events(&world, goblin),
+        vec![HistoryEvent::RestStarted, HistoryEvent::Rested]
+    );
+    assert_eq!(
+        events(&world, other),
+        vec![HistoryEvent::RestStarted, HistoryEvent::Died]
+    );
+
+    world.despawn(other);
+    schedule.run(&mut world);
+    let history = world.resource::<EntityHistory>();
+    assert_eq!(history.entities().collect::<Vec<_>>(), vec![goblin]);
+}
+
+#[test]
+fn attacks_land_in_both_timelines() {
+    let mut world = floor_world();
+    let attacker = world
+        .spawn((
+            Name("Grak".into()),
+            CombatStats::new(20, 0, 0, 0, 5, 5),
+            Health::full(20),
+        ))
+        .id();
+    let defender = world
+        .spawn((
+            Name("Snik".into()),
+            CombatStats::new(0, 0, 0, 0, 1, 1),
+            Health::full(20),
+        ))
+        .id();
+    world.resource_mut::<Time>().ticks = 40;
+    let outcome = resolve_attack(&mut world, attacker, defender).unwrap();
+
+    assert_eq!(
+        events(&world, attacker),
+        vec![HistoryEvent::Attacked {
+            target: defender,
+            outcome
+        }]
+    );
+    assert_eq!(
+        events(&world, defender),
+        vec![HistoryEvent::AttackedBy { attacker, outcome }]
+    );
+    let line = &describe_history(&world, defender)[0];
+    assert!(line.starts_with("t=40 attacked by Grak, "), "{line}");
+}
+
+#[test]
+fn timelines_keep_a_bounded_count_and_age() {
+    let mut history = EntityHistory::default();
+    history.capacity = 3;
+    history.max_age = 100;
+    let mut world = World::new();
+    let grak = world.spawn_empty().id();
+    for tick in [10, 20, 30, 150, 160] {
+        history.record(grak, tick, HistoryEvent::RestStarted);
+    }
+    let ticks = |h: &EntityHistory, now| h.recent(grak, now).map(|e| e.tick).collect::<Vec<_>>();
+    assert_eq!(ticks(&history, 160), vec![150, 160]);
+    assert_eq!(ticks(&history, 100), vec![30, 150, 160]);
+
+    history.prune(300);
+    assert!(history.entities().next().is_none());
+}
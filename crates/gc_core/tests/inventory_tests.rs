@@ -32,9 +32,7 @@ fn pick_up_item_success() {
         .id();
 
     // Pick up the item
-    let success = pick_up_item(&mut world, agent, item);
-
-    assert!(success, "Pick up should succeed");
+    pick_up_item(&mut world, agent, item).expect("Pick up should succeed");
 
     // Check that agent now carries the item
     let inventory = world.get::<Inventory>(agent).unwrap();
@@ -61,10 +59,14 @@ fn pick_up_item_fails_when_already_carrying() {
         .id();
 
     // Try to pick up the second item (should fail)
-    let success = pick_up_item(&mut world, agent, second_item);
+    let result = pick_up_item(&mut world, agent, second_item);
 
-    assert!(
-        !success,
+    assert_eq!(
+        result,
+        Err(GcError::InventoryFull {
+            agent,
+            carried: first_item
+        }),
         "Pick up should fail when already carrying something"
     );
 
@@ -92,9 +94,7 @@ fn put_down_item_success() {
 
     // Put down the item at a new position
     let target_pos = (10, 15);
-    let success = put_down_item(&mut world, agent, target_pos);
-
-    assert!(success, "Put down should succeed");
+    put_down_item(&mut world, agent, target_pos).expect("Put down should succeed");
 
     // Check that agent no longer carries anything
     let inventory = world.get::<Inventory>(agent).unwrap();
@@ -117,9 +117,13 @@ fn put_down_item_fails_when_not_carrying() {
 
     // Try to put down an item when not carrying anything
     let target_pos = (10, 15);
-    let success = put_down_item(&mut world, agent, target_pos);
+    let result = put_down_item(&mut world, agent, target_pos);
 
-    assert!(!success, "Put down should fail when not carrying anything");
+    assert_eq!(
+        result,
+        Err(GcError::NothingCarried(agent)),
+        "Put down should fail when not carrying anything"
+    );
 }
 
 #[test]
@@ -200,14 +204,12 @@ fn inventory_integrates_with_job_system() {
     );
 
     // Simulate picking up item (this would be part of job execution)
-    let pickup_success = pick_up_item(&mut world, agent, item);
-    assert!(pickup_success, "Agent should be able to pick up item");
+    pick_up_item(&mut world, agent, item).expect("Agent should be able to pick up item");
     assert!(is_carrying_item(&world, agent));
 
     // Simulate putting down item at a stockpile location
     let stockpile_pos = (10, 10);
-    let putdown_success = put_down_item(&mut world, agent, stockpile_pos);
-    assert!(putdown_success, "Agent should be able to put down item");
+    put_down_item(&mut world, agent, stockpile_pos).expect("Agent should be able to put down item");
     assert!(!is_carrying_item(&world, agent));
 
     // Verify item is now at stockpile location
@@ -215,3 +217,275 @@ fn inventory_integrates_with_job_system() {
     assert_eq!(item_position.0, stockpile_pos.0);
     assert_eq!(item_position.1, stockpile_pos.1);
 }
+
+#[test]
+fn carried_items_are_marked_with_their_carrier() {
+    let mut world = World::new();
+    let agent = world
+        .spawn((Goblin, Carrier, Inventory::default(), Position(5, 5)))
+        .id();
+    let item = world.spawn((Position(5, 5), Item::stone())).id();
+
+    pick_up_item(&mut world, agent, item).unwrap();
+    assert_eq!(world.get::<CarriedBy>(item), Some(&CarriedBy(agent)));
+    // Off the map while carried, so it cannot be picked up twice
+    assert!(world.get::<Position>(item).is_none());
+    let other = world.spawn(Inventory::default()).id();
+    assert_eq!(
+        pick_up_item(&mut world, other, item),
+        Err(GcError::NotCarriable(item))
+    );
+    put_down_item(&mut world, agent, (6, 6)).unwrap();
+    assert!(world.get::<CarriedBy>(item).is_none());
+    assert_eq!(world.get::<Position>(item), Some(&Position(6, 6)));
+}
+
+#[test]
+fn staged_haul_marks_item_until_delivery() {
+    let mut world = World::new();
+    world.insert_resource(ActiveJobs::default());
+    let carrier = world
+        .spawn((
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+            Position(2, 2),
+        ))
+        .id();
+    let item = world.spawn((Item::stone(), Carriable, Position(2, 2))).id();
+    let id = JobId(uuid::Uuid::from_u128(1));
+    world.resource_mut::<ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Haul {
+                from: (2, 2),
+                to: (7, 3),
+            },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    world.get_mut::<AssignedJob>(carrier).unwrap().0 = Some(id);
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::systems::hauling_execution_system);
+
+    // Pickup tick
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Inventory>(carrier).unwrap().0, Some(item));
+    assert_eq!(world.get::<CarriedBy>(item), Some(&CarriedBy(carrier)));
+    assert!(world.get::<Position>(item).is_none());
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.entities_at(2, 2).count(), 1, "only the carrier");
+    assert_eq!(view.metrics.items, 0);
+
+    // Delivery tick
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(item), Some(&Position(7, 3)));
+    assert!(world.get::<CarriedBy>(item).is_none());
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+fn haul_world(carrier_at: (i32, i32), item: bool) -> (World, Entity, JobId) {
+    let mut world = World::new();
+    world.insert_resource(ActiveJobs::default());
+    world.insert_resource(MovementConfig::default());
+    let carrier = world
+        .spawn((
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+            Position(carrier_at.0, carrier_at.1),
+        ))
+        .id();
+    if item {
+        world.spawn((Item::stone(), Carriable, Position(2, 2)));
+    }
+    let id = JobId(uuid::Uuid::from_u128(9));
+    world.resource_mut::<ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Haul {
+                from: (2, 2),
+                to: (7, 3),
+            },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    world.get_mut::<AssignedJob>(carrier).unwrap().0 = Some(id);
+    (world, carrier, id)
+}
+
+fn haul_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::systems::hauling_execution_system);
+    schedule
+}
+
+#[test]
+fn haul_phase_steps_through_the_haul() {
+    let (mut world, carrier, _) = haul_world((0, 0), true);
+    let mut schedule = haul_schedule();
+
+    // Approach and pickup share a tick
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(carrier), Some(&Position(2, 2)));
+    assert_eq!(world.get::<HaulPhase>(carrier), Some(&HaulPhase::Deliver));
+    assert!(world.get::<Inventory>(carrier).unwrap().0.is_some());
+
+    // Deliver and drop share the next
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(carrier), Some(&Position(7, 3)));
+    assert!(world.get::<HaulPhase>(carrier).is_none());
+    assert!(world.get::<AssignedJob>(carrier).unwrap().0.is_none());
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn stepping_carriers_walk_one_tile_per_tick() {
+    let (mut world, carrier, _) = haul_world((0, 0), true);
+    world.resource_mut::<MovementConfig>().mode = MovementMode::Step;
+    let mut schedule = haul_schedule();
+
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(carrier), Some(&Position(1, 1)));
+    assert_eq!(world.get::<HaulPhase>(carrier), Some(&HaulPhase::Approach));
+
+    // One more tick to reach the item (pickup on arrival), five to (7, 3)
+    let ticks = fast_forward(&mut world, &mut schedule, 20, jobs_idle);
+    assert_eq!(ticks, Some(6));
+    let mut q = world.query_filtered::<&Position, With<Item>>();
+    assert_eq!(q.single(&world), &Position(7, 3));
+
+    // A teleporting override in a stepping colony
+    let (mut world, carrier, _) = haul_world((0, 0), true);
+    world.resource_mut::<MovementConfig>().mode = MovementMode::Step;
+    world
+        .entity_mut(carrier)
+        .insert(MovementOverride(MovementMode::Teleport));
+    let mut schedule = haul_schedule();
+    assert_eq!(
+        fast_forward(&mut world, &mut schedule, 20, jobs_idle),
+        Some(2)
+    );
+}
+
+#[test]
+fn haul_without_an_item_is_abandoned() {
+    let (mut world, carrier, _) = haul_world((5, 5), false);
+    haul_schedule().run(&mut world);
+    assert!(world.get::<AssignedJob>(carrier).unwrap().0.is_none());
+    assert!(world.get::<HaulPhase>(carrier).is_none());
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn stale_haul_phase_restarts_from_inventory() {
+    // Left in Drop by an interrupted haul, but carrying nothing
+    let (mut world, carrier, _) = haul_world((2, 2), true);
+    world.entity_mut(carrier).insert(HaulPhase::Drop);
+    let mut schedule = haul_schedule();
+    schedule.run(&mut world);
+    assert_eq!(world.get::<HaulPhase>(carrier), Some(&HaulPhase::Deliver));
+    assert!(world.get::<Inventory>(carrier).unwrap().0.is_some());
+
+    // Phase is dropped once the carrier no longer holds a haul job
+    world.get_mut::<AssignedJob>(carrier).unwrap().0 = None;
+    schedule.run(&mut world);
+    assert!(world.get::<HaulPhase>(carrier).is_none());
+}
+
+#[test]
+fn carried_items_are_dropped_on_save_and_death() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(10, 10));
+    let agent = world
+        .spawn((
+            Carrier,
+            Inventory::default(),
+            Position(4, 4),
+            Health::full(5),
+            Name("Urok".into()),
+        ))
+        .id();
+    let item = world
+        .spawn((Position(4, 4), Item::stone(), Name("Stone".into())))
+        .id();
+    pick_up_item(&mut world, agent, item).unwrap();
+    world.get_mut::<Position>(agent).unwrap().0 = 6;
+
+    // Saved at the carrier's feet
+    let save = save_world(&mut world);
+    let stone = save
+        .entities
+        .iter()
+        .find(|e| e.item_type.is_some())
+        .unwrap();
+    assert_eq!(stone.pos, Some((6, 4)));
+
+    // Dying drops the load where the carrier fell
+    world.get_mut::<Health>(agent).unwrap().take_damage(5);
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::health::death_system);
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(item), Some(&Position(6, 4)));
+    assert!(world.get::<CarriedBy>(item).is_none());
+    assert!(!is_carrying_item(&world, agent));
+}
+
+#[test]
+fn refused_moves_say_why_and_change_nothing() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(8, 8));
+    let item = world.spawn((Position(1, 1), Item::stone())).id();
+    let handless = world.spawn((Goblin, Position(1, 1))).id();
+    assert_eq!(
+        pick_up_item(&mut world, handless, item),
+        Err(GcError::NoInventory(handless))
+    );
+    assert_eq!(
+        put_down_item(&mut world, handless, (2, 2)),
+        Err(GcError::NoInventory(handless))
+    );
+
+    let agent = world
+        .spawn((Goblin, Carrier, Inventory::default(), Position(1, 1)))
+        .id();
+    let gone = world.spawn((Position(2, 2), Item::stone())).id();
+    world.despawn(gone);
+    assert_eq!(
+        pick_up_item(&mut world, agent, gone),
+        Err(GcError::InvalidEntity(gone))
+    );
+    assert!(!is_carrying_item(&world, agent));
+
+    pick_up_item(&mut world, agent, item).unwrap();
+    let err = put_down_item(&mut world, agent, (8, 3)).unwrap_err();
+    assert_eq!(
+        err,
+        GcError::OutOfBounds {
+            x: 8,
+            y: 3,
+            width: 8,
+            height: 8
+        }
+    );
+    assert_eq!(err.to_string(), "(8, 3) is outside the 8x8 map");
+    assert_eq!(get_carried_item(&world, agent), Some(item));
+
+    // A carried item that vanished stays in the inventory for systems to report
+    world.despawn(item);
+    assert_eq!(
+        put_down_item(&mut world, agent, (2, 2)),
+        Err(GcError::InvalidEntity(item))
+    );
+    assert_eq!(get_carried_item(&world, agent), Some(item));
+}
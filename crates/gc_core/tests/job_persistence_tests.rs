@@ -0,0 +1,253 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{
+    build_default_schedule, build_standard_world, fast_forward, WorldOptions,
+};
+use gc_core::designations::designate_mine_rect;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+const SEED: u64 = 11;
+
+const STEPPING: MovementConfig = MovementConfig {
+    mode: MovementMode::Step,
+};
+
+/// Three miners facing a block of designated rock, and a carrier walking
+/// the spoil to a stockpile across the map
+fn quarry() -> World {
+    let mut world = build_standard_world(20, 12, SEED, WorldOptions::default());
+    world.insert_resource(STEPPING);
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..12 {
+        for x in 0..20 {
+            let rock = (8..14).contains(&x) && (2..8).contains(&y);
+            map.set_tile(
+                x,
+                y,
+                if rock {
+                    TileKind::Wall
+                } else {
+                    TileKind::Floor
+                },
+            )
+            .unwrap();
+        }
+    }
+    for (i, y) in [2, 5, 8].into_iter().enumerate() {
+        world.spawn((
+            Name(format!("miner {i}")),
+            Miner,
+            AssignedJob::default(),
+            Position(7, y),
+        ));
+    }
+    world.spawn((
+        Name("carrier".into()),
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(15, 5),
+    ));
+    world.spawn(StockpileBundle::new(0, 0, 2, 11));
+    designate_mine_rect(&mut world, &ZoneBounds::new(8, 2, 13, 7));
+    world
+}
+
+/// Run until some haul is under way but nothing is being carried, so the
+/// save holds active jobs without items in hand
+fn to_save_point(world: &mut World, schedule: &mut Schedule) {
+    fast_forward(world, schedule, 100, |w| {
+        let hauling = w
+            .resource::<ActiveJobs>()
+            .jobs
+            .values()
+            .any(|j| matches!(j.kind, JobKind::Haul { .. }));
+        hauling && w.query::<&Inventory>().iter(w).all(|inv| inv.0.is_none())
+    })
+    .expect("a save point comes up");
+}
+
+/// A fresh world of the same size with `save` loaded over it
+fn resume(save: SaveGame) -> World {
+    let mut world = build_standard_world(save.width, save.height, SEED, WorldOptions::default());
+    world.insert_resource(STEPPING);
+    load_world(save, &mut world);
+    world
+}
+
+/// Per tick: who holds which job, then the board from bottom to top
+fn trace(world: &mut World, schedule: &mut Schedule, ticks: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for _ in 0..ticks {
+        schedule.run(world);
+        let active = world.resource::<ActiveJobs>().jobs.clone();
+        let mut held: Vec<String> = world
+            .query::<(&Name, &AssignedJob)>()
+            .iter(world)
+            .map(|(n, a)| {
+                let job = a.0.and_then(|id| active.get(&id)).map(|j| &j.kind);
+                format!("{}: {job:?}", n.0)
+            })
+            .collect();
+        held.sort();
+        let board: Vec<String> = world
+            .resource::<JobBoard>()
+            .0
+            .iter()
+            .map(|j| format!("{:?}", j.kind))
+            .collect();
+        lines.push(format!("{} | {}", held.join(", "), board.join(", ")));
+    }
+    lines
+}
+
+fn board_ids(world: &World) -> Vec<(JobId, u64)> {
+    world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .map(|j| (j.id, j.seq))
+        .collect()
+}
+
+fn holders(world: &mut World) -> Vec<(String, Option<JobId>)> {
+    let mut held: Vec<(String, Option<JobId>)> = world
+        .query::<(&Name, &AssignedJob)>()
+        .iter(world)
+        .map(|(n, a)| (n.0.clone(), a.0))
+        .collect();
+    held.sort_by(|a, b| a.0.cmp(&b.0));
+    held
+}
+
+#[test]
+fn jobs_are_numbered_in_posting_order() {
+    let mut world = quarry();
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let board = world.resource::<JobBoard>();
+    assert!(!board.0.is_empty());
+    let seqs: Vec<u64> = board.0.iter().map(|j| j.seq).collect();
+    let mut sorted = seqs.clone();
+    sorted.sort_unstable();
+    assert_eq!(seqs, sorted);
+    assert!(seqs.iter().all(|&seq| seq < board.1));
+}
+
+#[test]
+fn board_and_active_jobs_load_back_exactly() {
+    let mut world = quarry();
+    let mut schedule = build_default_schedule();
+    to_save_point(&mut world, &mut schedule);
+    let active = world.resource::<ActiveJobs>().jobs.clone();
+
+    let save = save_world(&mut world);
+    let seqs: Vec<u64> = save.active_jobs.iter().map(|j| j.seq).collect();
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]));
+
+    let json = serde_json::to_string(&save).unwrap();
+    let mut loaded = resume(serde_json::from_str(&json).unwrap());
+    assert_eq!(board_ids(&loaded), board_ids(&world));
+    assert_eq!(loaded.resource::<ActiveJobs>().jobs, active);
+    assert_eq!(
+        loaded.resource::<JobBoard>().1,
+        world.resource::<JobBoard>().1
+    );
+    assert_eq!(holders(&mut loaded), holders(&mut world));
+
+    let again = save_world(&mut loaded);
+    assert_eq!(again.jobs, save.jobs);
+    assert_eq!(again.active_jobs, save.active_jobs);
+}
+
+#[test]
+fn a_resumed_run_assigns_jobs_like_an_uninterrupted_one() {
+    let mut world = quarry();
+    // Yield rolls draw on the job stream, whose position is not saved
+    world.remove_resource::<YieldTable>();
+    let mut schedule = build_default_schedule();
+    to_save_point(&mut world, &mut schedule);
+    let mut resumed = resume(save_world(&mut world));
+    resumed.remove_resource::<YieldTable>();
+
+    let straight = trace(&mut world, &mut schedule, 20);
+    let after_load = trace(&mut resumed, &mut build_default_schedule(), 20);
+    assert_eq!(after_load, straight);
+}
+
+#[test]
+fn reloading_a_save_replays_the_same_job_ids() {
+    let mut world = quarry();
+    let mut schedule = build_default_schedule();
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    let save = save_world(&mut world);
+    let run = |save: SaveGame| {
+        let mut world = resume(save);
+        let mut schedule = build_default_schedule();
+        let mut ticks = Vec::new();
+        for _ in 0..10 {
+            schedule.run(&mut world);
+            ticks.push((board_ids(&world), holders(&mut world)));
+        }
+        ticks
+    };
+    assert_eq!(run(save.clone()), run(save));
+}
+
+#[test]
+fn restored_jobs_get_ids_distinct_from_new_ones() {
+    let mut world = quarry();
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let save = save_world(&mut world);
+    let restored: Vec<JobId> = save.jobs.iter().map(|j| j.id).collect();
+    assert!(!restored.is_empty());
+
+    let mut loaded = resume(save);
+    designate_mine_rect(&mut loaded, &ZoneBounds::new(8, 9, 13, 9));
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut loaded);
+    let mut ids: Vec<JobId> = board_ids(&loaded).into_iter().map(|(id, _)| id).collect();
+    ids.extend(loaded.resource::<ActiveJobs>().jobs.keys().copied());
+    let count = ids.len();
+    ids.sort_by_key(|id| id.0);
+    ids.dedup();
+    assert_eq!(ids.len(), count, "no job id is handed out twice");
+}
+
+#[test]
+fn hauls_under_way_are_neither_lost_nor_posted_twice() {
+    let mut world = quarry();
+    let mut schedule = build_default_schedule();
+    let carrying = fast_forward(&mut world, &mut schedule, 100, |w| {
+        w.query::<&Inventory>().iter(w).any(|inv| inv.0.is_some())
+    });
+    assert!(carrying.is_some(), "a carrier picks something up");
+    let hauls = |world: &World| {
+        let mut hauls: Vec<(JobId, u64)> = world
+            .resource::<JobBoard>()
+            .0
+            .iter()
+            .chain(world.resource::<ActiveJobs>().jobs.values())
+            .filter(|j| matches!(j.kind, JobKind::Haul { .. }))
+            .map(|j| (j.id, j.seq))
+            .collect();
+        hauls.sort_by_key(|&(_, seq)| seq);
+        hauls
+    };
+    let before = hauls(&world);
+
+    let mut loaded = resume(save_world(&mut world));
+    assert_eq!(hauls(&loaded), before);
+    build_default_schedule().run(&mut loaded);
+    let after = hauls(&loaded);
+    let new = after.iter().filter(|h| !before.contains(h)).count();
+    schedule.run(&mut world);
+    let expected = hauls(&world).iter().filter(|h| !before.contains(h)).count();
+    assert_eq!(
+        new, expected,
+        "items already being hauled get no second haul"
+    );
+}
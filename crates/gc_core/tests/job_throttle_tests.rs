@@ -0,0 +1,185 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::designations::{designation_to_jobs_system, DesignationBundle};
+use gc_core::jobs::round_robin;
+use gc_core::prelude::*;
+use gc_core::systems::auto_haul_system;
+use std::collections::{BTreeMap, VecDeque};
+
+fn world(throttle: JobThrottle) -> World {
+    let mut world = build_standard_world(40, 20, 5, WorldOptions::default());
+    world.insert_resource(throttle);
+    world
+}
+
+fn designate(world: &mut World, issuer: IssuedBy, cells: impl IntoIterator<Item = (i32, i32)>) {
+    for (x, y) in cells {
+        world.spawn((
+            DesignationBundle {
+                pos: Position(x, y),
+                ..Default::default()
+            },
+            issuer,
+        ));
+    }
+}
+
+fn single<M>(system: impl IntoSystemConfigs<M>) -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(system);
+    schedule
+}
+
+fn mine_jobs_by(world: &World, issuer: IssuedBy) -> usize {
+    world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter(|j| matches!(j.kind, JobKind::Mine { .. }) && j.issued_by == issuer)
+        .count()
+}
+
+#[test]
+fn round_robin_takes_one_per_queue_per_round() {
+    let queues: BTreeMap<char, VecDeque<u32>> = [
+        ('a', VecDeque::from([1, 2, 3])),
+        ('b', VecDeque::from([10])),
+        ('c', VecDeque::from([20, 21])),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        round_robin(queues.clone(), None, 4),
+        (vec![1, 10, 20, 2], Some('a'))
+    );
+    // Resuming after 'a' serves 'b' first
+    assert_eq!(
+        round_robin(queues.clone(), Some(&'a'), 2),
+        (vec![10, 20], Some('c'))
+    );
+    assert_eq!(
+        round_robin(queues, Some(&'c'), 100),
+        (vec![1, 10, 20, 2, 21, 3], Some('a'))
+    );
+}
+
+#[test]
+fn designations_convert_a_capped_batch_per_tick() {
+    let mut world = world(JobThrottle {
+        designations_per_tick: 4,
+        ..Default::default()
+    });
+    designate(&mut world, IssuedBy::LOCAL_PLAYER, (1..=10).map(|x| (x, 1)));
+    let mut schedule = single(designation_to_jobs_system);
+    let mut posted = Vec::new();
+    for _ in 0..4 {
+        schedule.run(&mut world);
+        posted.push(world.resource::<JobBoard>().0.len());
+    }
+    assert_eq!(posted, vec![4, 8, 10, 10]);
+    // First come, first served within one issuer
+    let first: Vec<JobKind> = world.resource::<JobBoard>().0[..4]
+        .iter()
+        .map(|j| j.kind.clone())
+        .collect();
+    assert_eq!(
+        first,
+        (1..=4)
+            .map(|x| JobKind::Mine { x, y: 1 })
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn a_flood_from_one_issuer_does_not_starve_another() {
+    let mut world = world(JobThrottle {
+        designations_per_tick: 2,
+        ..Default::default()
+    });
+    let flooder = IssuedBy::Player(1);
+    let latecomer = IssuedBy::Player(2);
+    designate(&mut world, flooder, (1..=30).map(|x| (x, 1)));
+    designate(&mut world, latecomer, [(5, 5), (6, 5)]);
+    let mut schedule = single(designation_to_jobs_system);
+    schedule.run(&mut world);
+    assert_eq!(mine_jobs_by(&world, flooder), 1);
+    assert_eq!(mine_jobs_by(&world, latecomer), 1);
+    schedule.run(&mut world);
+    assert_eq!(mine_jobs_by(&world, latecomer), 2);
+    assert_eq!(mine_jobs_by(&world, flooder), 2);
+}
+
+#[test]
+fn a_full_board_pauses_generation() {
+    let mut world = world(JobThrottle {
+        board_limit: 6,
+        ..Default::default()
+    });
+    designate(&mut world, IssuedBy::LOCAL_PLAYER, (1..=10).map(|x| (x, 1)));
+    let mut schedule = single(designation_to_jobs_system);
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<JobBoard>().0.len(), 6);
+
+    // Workers draining the board let the rest through
+    world.resource_mut::<JobBoard>().0.drain(..4);
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<JobBoard>().0.len(), 6);
+    world.resource_mut::<JobBoard>().0.clear();
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<JobBoard>().0.len(), 0);
+}
+
+#[test]
+fn auto_haul_works_through_a_backlog_fairly() {
+    let mut world = world(JobThrottle {
+        hauls_per_tick: 2,
+        ..Default::default()
+    });
+    world.spawn(StockpileBundle::new(30, 10, 34, 14));
+    let stones: Vec<Entity> = (1..=5)
+        .map(|x| {
+            world
+                .spawn((
+                    Item {
+                        item_type: ItemType::Stone,
+                    },
+                    Carriable,
+                    Position(x, 2),
+                ))
+                .id()
+        })
+        .collect();
+    world.spawn((
+        Item {
+            item_type: ItemType::Bar,
+        },
+        Carriable,
+        Position(9, 2),
+    ));
+    let mut schedule = single(auto_haul_system);
+    let sources = |world: &World| -> Vec<(i32, i32)> {
+        world
+            .resource::<JobBoard>()
+            .0
+            .iter()
+            .filter_map(|j| match j.kind {
+                JobKind::Haul { from, .. } => Some(from),
+                _ => None,
+            })
+            .collect()
+    };
+    schedule.run(&mut world);
+    // The lone bar is not stuck behind the stone pile
+    assert_eq!(sources(&world), vec![(1, 2), (9, 2)]);
+
+    // A backlogged item that is gone by its turn is skipped
+    world.despawn(stones[1]);
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        sources(&world),
+        vec![(1, 2), (9, 2), (3, 2), (4, 2), (5, 2)]
+    );
+}
@@ -0,0 +1,176 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::fov::{compute_visibility_system, Visibility};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open ground with a sealed 5x5 room (walls at x/y 10..=14) and a wall
+/// strip down x = 3
+fn world() -> World {
+    let mut world = build_standard_world(24, 20, 5, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..20 {
+        for x in 0..24 {
+            let wall = ((10..=14).contains(&x) && (y == 10 || y == 14))
+                || ((10..=14).contains(&y) && (x == 10 || x == 14))
+                || (x == 3 && y < 8);
+            map.set_tile(
+                x,
+                y,
+                if wall {
+                    TileKind::Wall
+                } else {
+                    TileKind::Floor
+                },
+            )
+            .unwrap();
+        }
+    }
+    world
+}
+
+fn set_tick(world: &mut World, tick: u64) {
+    world.resource_mut::<Time>().ticks = tick;
+}
+
+/// Tick that is night under the default day cycle
+const NIGHT: u64 = 100;
+/// Tick that is day under the default day cycle
+const DAY: u64 = 1000;
+
+fn lighting_and_sight() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((lighting_system, compute_visibility_system).chain());
+    schedule
+}
+
+fn sees(world: &World, observer: Entity, x: i32, y: i32) -> bool {
+    world.resource::<Visibility>().per_entity[&observer].contains(&(x, y))
+}
+
+#[test]
+fn days_follow_the_cycle() {
+    let cycle = DayCycle::default();
+    assert!(!cycle.is_day(0));
+    assert!(cycle.is_day(cycle.dawn));
+    assert!(!cycle.is_day(cycle.dusk));
+    assert!(cycle.is_day(cycle.ticks_per_day + cycle.dawn));
+    let always = DayCycle {
+        ticks_per_day: 0,
+        ..cycle
+    };
+    assert!(always.is_day(0));
+}
+
+#[test]
+fn enclosed_rooms_get_no_daylight() {
+    let mut world = world();
+    set_tick(&mut world, DAY);
+    let mut schedule = lighting_and_sight();
+    schedule.run(&mut world);
+    let light = world.resource::<LightMap>();
+    assert!(light.daylight);
+    assert!(light.is_lit(0, 0));
+    assert!(light.is_lit(10, 10), "outer faces of the room's walls");
+    assert!(!light.is_outdoors(12, 12));
+    assert!(!light.is_lit(12, 12));
+
+    set_tick(&mut world, NIGHT);
+    schedule.run(&mut world);
+    let light = world.resource::<LightMap>();
+    assert!(!light.daylight);
+    assert!(!light.is_lit(0, 0));
+}
+
+#[test]
+fn darkness_limits_sight_to_lit_tiles() {
+    let mut world = world();
+    world.insert_resource(Visibility::default());
+    world.resource_mut::<LightingConfig>().darkness = true;
+    let observer = world.spawn((Position(5, 5), VisionRadius::new(6))).id();
+    let mut schedule = lighting_and_sight();
+
+    set_tick(&mut world, DAY);
+    schedule.run(&mut world);
+    assert!(sees(&world, observer, 9, 5));
+
+    set_tick(&mut world, NIGHT);
+    schedule.run(&mut world);
+    assert!(sees(&world, observer, 6, 5), "within the dark radius");
+    assert!(!sees(&world, observer, 9, 5));
+
+    // A torch lights the way, but not beyond the observer's own sight
+    world.spawn((Position(8, 5), LightSource::TORCH));
+    schedule.run(&mut world);
+    assert!(sees(&world, observer, 9, 5));
+    assert!(!sees(&world, observer, 12, 5));
+    assert!(world.resource::<LightMap>().is_lit(12, 5));
+}
+
+#[test]
+fn lamps_light_enclosed_rooms_by_day() {
+    let mut world = world();
+    world.insert_resource(Visibility::default());
+    world.resource_mut::<LightingConfig>().darkness = true;
+    set_tick(&mut world, DAY);
+    let observer = world.spawn((Position(11, 11), VisionRadius::new(6))).id();
+    let mut schedule = lighting_and_sight();
+    schedule.run(&mut world);
+    assert!(!sees(&world, observer, 13, 13));
+
+    let brazier = world.spawn((Position(13, 13), LightSource::BRAZIER)).id();
+    schedule.run(&mut world);
+    assert!(sees(&world, observer, 13, 13));
+
+    world.entity_mut(brazier).despawn();
+    schedule.run(&mut world);
+    assert!(!sees(&world, observer, 13, 13));
+}
+
+#[test]
+fn lighting_leaves_sight_alone_without_darkness() {
+    let mut world = world();
+    world.insert_resource(Visibility::default());
+    set_tick(&mut world, NIGHT);
+    let observer = world.spawn((Position(5, 5), VisionRadius::new(6))).id();
+    lighting_and_sight().run(&mut world);
+    assert!(sees(&world, observer, 9, 5));
+}
+
+#[test]
+fn torches_are_built_and_saved() {
+    let mut world = world();
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Torch, 6, 12))
+        .id();
+    world.spawn((
+        Item {
+            item_type: ItemType::Wood,
+        },
+        Carriable,
+        Position(5, 14),
+    ));
+    world.spawn((Position(8, 16), Miner, AssignedJob::default()));
+    world.spawn((
+        Position(2, 12),
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+    ));
+    let mut schedule = build_default_schedule();
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<LightSource>(site), Some(&LightSource::TORCH));
+    assert!(world.resource::<LightMap>().lamps.contains(&(6, 12)));
+
+    let save = save_world(&mut world);
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let lights: Vec<_> = loaded
+        .query::<(&Position, &LightSource)>()
+        .iter(&loaded)
+        .map(|(p, l)| ((p.0, p.1), *l))
+        .collect();
+    assert_eq!(lights, vec![((6, 12), LightSource::TORCH)]);
+}
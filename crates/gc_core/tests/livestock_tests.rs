@@ -0,0 +1,248 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn animal(world: &mut World, species: Species, x: i32, y: i32) -> Entity {
+    world.spawn((Animal, species, Position(x, y))).id()
+}
+
+fn goblin(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Position(x, y),
+            Name("Urk".into()),
+            Faction::new(FactionKind::Goblins),
+        ))
+        .id()
+}
+
+fn livestock_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((livestock_movement_system, livestock_production_system).chain());
+    schedule
+}
+
+fn count(world: &mut World, item_type: ItemType) -> usize {
+    world
+        .query::<&Item>()
+        .iter(world)
+        .filter(|i| i.item_type == item_type)
+        .count()
+}
+
+#[test]
+fn taming_checks_the_animal_and_owner() {
+    let mut world = world();
+    let owner = goblin(&mut world, 1, 1);
+    let dog = animal(&mut world, Species::Dog, 2, 2);
+    let rock = world.spawn(Position(3, 3)).id();
+    assert_eq!(
+        tame(&mut world, rock, owner),
+        Err(LivestockError::NotAnAnimal)
+    );
+    let gone = world.spawn(()).id();
+    world.despawn(gone);
+    assert_eq!(tame(&mut world, dog, gone), Err(LivestockError::NoOwner));
+    assert_eq!(tame(&mut world, dog, owner), Ok(()));
+    assert_eq!(world.get::<OwnedBy>(dog), Some(&OwnedBy(owner)));
+    assert_eq!(
+        tame(&mut world, dog, owner),
+        Err(LivestockError::AlreadyOwned)
+    );
+
+    let corpse = animal(&mut world, Species::Sheep, 4, 4);
+    world.entity_mut(corpse).insert(Dead);
+    assert_eq!(tame(&mut world, corpse, owner), Err(LivestockError::Dead));
+}
+
+#[test]
+fn tame_chickens_lay_eggs_on_a_timer() {
+    let mut world = world();
+    let owner = goblin(&mut world, 1, 1);
+    let hen = animal(&mut world, Species::Chicken, 5, 5);
+    let wild = animal(&mut world, Species::Chicken, 8, 5);
+    tame(&mut world, hen, owner).unwrap();
+
+    let interval = LivestockConfig::default().lay_interval;
+    let mut schedule = livestock_only();
+    for _ in 0..interval - 1 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(count(&mut world, ItemType::Egg), 0);
+    schedule.run(&mut world);
+    let eggs: Vec<Position> = world
+        .query::<(&Item, &Position)>()
+        .iter(&world)
+        .filter(|(i, _)| i.item_type == ItemType::Egg)
+        .map(|(_, p)| *p)
+        .collect();
+    assert_eq!(eggs, vec![Position(5, 5)]);
+    assert!(
+        world.get::<Livestock>(wild).is_none(),
+        "wild hens never lay"
+    );
+}
+
+#[test]
+fn sheep_walk_to_pasture_and_only_grow_wool_there() {
+    let mut world = world();
+    world.insert_resource(LivestockConfig {
+        wool_interval: 5,
+        ..Default::default()
+    });
+    world.spawn(ZoneBundle::new(ZoneKind::Pasture, 12, 4, 16, 8));
+    let owner = goblin(&mut world, 1, 1);
+    let sheep = animal(&mut world, Species::Sheep, 2, 6);
+    tame(&mut world, sheep, owner).unwrap();
+
+    let mut schedule = livestock_only();
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(sheep), Some(&Position(3, 6)));
+    assert_eq!(world.get::<Livestock>(sheep).unwrap().timer, 0);
+    for _ in 0..9 {
+        schedule.run(&mut world);
+    }
+    // Reached the pasture edge and stopped there
+    assert_eq!(world.get::<Position>(sheep), Some(&Position(12, 6)));
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<Position>(sheep), Some(&Position(12, 6)));
+    let livestock = world.get::<Livestock>(sheep).unwrap();
+    assert!(livestock.wool_ready);
+    assert_eq!(livestock.timer, 0, "a grown fleece stops the timer");
+}
+
+#[test]
+fn carriers_shear_sheep_with_grown_wool() {
+    let mut world = world();
+    world.spawn(ZoneBundle::new(ZoneKind::Pasture, 12, 4, 16, 8));
+    let owner = goblin(&mut world, 1, 1);
+    let sheep = animal(&mut world, Species::Sheep, 14, 6);
+    tame(&mut world, sheep, owner).unwrap();
+    world.get_mut::<Livestock>(sheep).unwrap().wool_ready = true;
+    let carrier = world
+        .spawn((
+            Position(3, 6),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ))
+        .id();
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            shearing_job_system,
+            shearing_job_assignment_system,
+            shearing_execution_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+    let job = world.get::<AssignedJob>(carrier).unwrap().0;
+    assert!(job.is_some());
+    assert_eq!(world.get::<Livestock>(sheep).unwrap().shear_job, job);
+    assert!(matches!(
+        world.resource::<ActiveJobs>().jobs[&job.unwrap()].kind,
+        JobKind::Shear { x: 14, y: 6 }
+    ));
+    for _ in 0..20 {
+        schedule.run(&mut world);
+    }
+    let livestock = world.get::<Livestock>(sheep).unwrap();
+    assert!(!livestock.wool_ready);
+    assert_eq!(livestock.shear_job, None);
+    assert_eq!(world.get::<AssignedJob>(carrier).unwrap().0, None);
+    assert_eq!(count(&mut world, ItemType::Wool), 1);
+    assert!(world.resource::<JobBoard>().0.is_empty());
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn pets_stay_on_their_owners_leash() {
+    let mut world = world();
+    let owner = goblin(&mut world, 2, 2);
+    let dog = animal(&mut world, Species::Dog, 3, 2);
+    tame(&mut world, dog, owner).unwrap();
+    let mut schedule = livestock_only();
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(dog), Some(&Position(3, 2)));
+
+    *world.get_mut::<Position>(owner).unwrap() = Position(12, 9);
+    for _ in 0..12 {
+        schedule.run(&mut world);
+    }
+    let at = *world.get::<Position>(dog).unwrap();
+    assert_eq!((12 - at.0).abs().max((9 - at.1).abs()), PET_LEASH);
+}
+
+#[test]
+fn livestock_run_in_the_default_schedule() {
+    let mut world = world();
+    let owner = goblin(&mut world, 1, 1);
+    let hen = animal(&mut world, Species::Chicken, 5, 5);
+    tame(&mut world, hen, owner).unwrap();
+    let mut schedule = build_default_schedule();
+    for _ in 0..LivestockConfig::default().lay_interval {
+        schedule.run(&mut world);
+    }
+    assert_eq!(count(&mut world, ItemType::Egg), 1);
+}
+
+#[test]
+fn ownership_and_timers_survive_save_and_load() {
+    let mut world = world();
+    let owner = goblin(&mut world, 1, 1);
+    let sheep = animal(&mut world, Species::Sheep, 5, 5);
+    animal(&mut world, Species::Dog, 9, 9);
+    tame(&mut world, sheep, owner).unwrap();
+    {
+        let mut livestock = world.get_mut::<Livestock>(sheep).unwrap();
+        livestock.timer = 7;
+        livestock.wool_ready = true;
+    }
+    // A pet whose owner has no name loads wild
+    let nameless = world.spawn(Position(2, 2)).id();
+    let stray = animal(&mut world, Species::Dog, 3, 3);
+    tame(&mut world, stray, nameless).unwrap();
+
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+
+    let mut animals: Vec<(Position, Species, Option<Livestock>, Option<String>)> = loaded
+        .query_filtered::<(&Position, &Species, Option<&Livestock>, Option<&OwnedBy>), With<Animal>>()
+        .iter(&loaded)
+        .map(|(p, s, l, o)| {
+            let owner = o.map(|o| loaded.get::<Name>(o.0).unwrap().0.clone());
+            (*p, *s, l.cloned(), owner)
+        })
+        .collect();
+    animals.sort_by_key(|(p, ..)| (p.0, p.1));
+    assert_eq!(
+        animals,
+        vec![
+            (Position(3, 3), Species::Dog, None, None),
+            (
+                Position(5, 5),
+                Species::Sheep,
+                Some(Livestock {
+                    timer: 7,
+                    wool_ready: true,
+                    shear_job: None,
+                }),
+                Some("Urk".into()),
+            ),
+            (Position(9, 9), Species::Dog, None, None),
+        ]
+    );
+}
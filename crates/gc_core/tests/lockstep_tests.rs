@@ -0,0 +1,172 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+const A: PeerId = PeerId(1);
+const B: PeerId = PeerId(2);
+
+fn peer(local: PeerId) -> (LockstepSession, World, Schedule) {
+    let world = build_standard_world(
+        24,
+        16,
+        11,
+        WorldOptions {
+            populate_demo_scene: true,
+            tick_ms: 100,
+            ..Default::default()
+        },
+    );
+    (
+        LockstepSession::new(local, &[A, B], 2),
+        world,
+        build_default_schedule(),
+    )
+}
+
+#[test]
+fn tick_waits_for_all_peers() {
+    let (mut a, mut world, mut schedule) = peer(A);
+    a.submit_local(vec![]).unwrap();
+    assert!(!a.ready());
+    assert_eq!(a.waiting_on(), vec![B]);
+    assert_eq!(a.advance(&mut world, &mut schedule).unwrap(), None);
+    assert_eq!(a.next_tick(), 0);
+
+    a.receive(LockstepMessage::Inputs {
+        peer: B,
+        tick: 0,
+        commands: vec![],
+    })
+    .unwrap();
+    assert!(a.ready());
+    a.advance(&mut world, &mut schedule).unwrap();
+    assert_eq!(a.next_tick(), 1);
+}
+
+#[test]
+fn peers_stay_in_sync_and_exchange_matching_hashes() {
+    let (mut a, mut wa, mut sa) = peer(A);
+    let (mut b, mut wb, mut sb) = peer(B);
+
+    for tick in 0..6 {
+        let cmds_a = if tick == 1 {
+            vec![WorldCommand::Designate { x: 4, y: 4 }]
+        } else {
+            vec![]
+        };
+        let cmds_b = if tick == 1 {
+            vec![WorldCommand::Designate { x: 6, y: 5 }]
+        } else {
+            vec![]
+        };
+        let ma = a.submit_local(cmds_a).unwrap();
+        let mb = b.submit_local(cmds_b).unwrap();
+        a.receive(mb).unwrap();
+        b.receive(ma).unwrap();
+
+        let ha = a.advance(&mut wa, &mut sa).unwrap();
+        let hb = b.advance(&mut wb, &mut sb).unwrap();
+        assert_eq!(ha.is_some(), (tick + 1) % 2 == 0);
+        if let (Some(ha), Some(hb)) = (ha, hb) {
+            a.receive(hb).unwrap();
+            b.receive(ha).unwrap();
+        }
+    }
+    assert_eq!(world_hash(&mut wa), world_hash(&mut wb));
+}
+
+#[test]
+fn diverged_worlds_are_reported_as_desync() {
+    let (mut a, mut wa, mut sa) = peer(A);
+    let (mut b, mut wb, mut sb) = peer(B);
+    // Tamper with one world outside the lockstep channel
+    wb.resource_mut::<GameMap>()
+        .set_tile(1, 1, TileKind::Lava)
+        .unwrap();
+
+    for _ in 0..2 {
+        let ma = a.submit_local(vec![]).unwrap();
+        let mb = b.submit_local(vec![]).unwrap();
+        a.receive(mb).unwrap();
+        b.receive(ma).unwrap();
+    }
+    a.advance(&mut wa, &mut sa).unwrap();
+    b.advance(&mut wb, &mut sb).unwrap();
+    let _ = a.advance(&mut wa, &mut sa).unwrap().unwrap();
+    let hb = b.advance(&mut wb, &mut sb).unwrap().unwrap();
+    assert!(matches!(
+        a.receive(hb),
+        Err(LockstepError::Desync { tick: 1, .. })
+    ));
+}
+
+#[test]
+fn rejects_unknown_duplicate_and_late_inputs() {
+    let (mut a, mut world, mut schedule) = peer(A);
+    let stranger = LockstepMessage::Inputs {
+        peer: PeerId(9),
+        tick: 0,
+        commands: vec![],
+    };
+    assert_eq!(
+        a.receive(stranger),
+        Err(LockstepError::UnknownPeer(PeerId(9)))
+    );
+
+    let from_b = LockstepMessage::Inputs {
+        peer: B,
+        tick: 0,
+        commands: vec![],
+    };
+    a.receive(from_b.clone()).unwrap();
+    assert_eq!(
+        a.receive(from_b.clone()),
+        Err(LockstepError::DuplicateInputs { peer: B, tick: 0 })
+    );
+
+    a.submit_local(vec![]).unwrap();
+    a.advance(&mut world, &mut schedule).unwrap();
+    assert_eq!(
+        a.receive(from_b),
+        Err(LockstepError::LateInputs { peer: B, tick: 0 })
+    );
+}
+
+#[test]
+fn messages_round_trip_through_json() {
+    let msg = LockstepMessage::Inputs {
+        peer: A,
+        tick: 3,
+        commands: vec![WorldCommand::Designate { x: 1, y: 2 }],
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    let back: LockstepMessage = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, msg);
+}
+
+#[test]
+fn commands_are_attributed_to_their_peer() {
+    let (mut a, mut world, mut schedule) = peer(A);
+    a.submit_local(vec![WorldCommand::Designate { x: 4, y: 4 }])
+        .unwrap();
+    a.receive(LockstepMessage::Inputs {
+        peer: B,
+        tick: 0,
+        commands: vec![WorldCommand::Designate { x: 6, y: 5 }],
+    })
+    .unwrap();
+    a.advance(&mut world, &mut schedule).unwrap();
+
+    // Status events outlive designations that were mined within the tick
+    let mut issuers: Vec<((i32, i32), IssuedBy)> = world
+        .resource::<gc_core::designations::DesignationEvents>()
+        .iter()
+        .map(|c| (c.pos, c.issued_by))
+        .collect();
+    issuers.sort();
+    issuers.dedup();
+    assert_eq!(
+        issuers,
+        vec![((4, 4), IssuedBy::Player(1)), ((6, 5), IssuedBy::Player(2))]
+    );
+}
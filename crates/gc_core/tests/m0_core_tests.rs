@@ -8,10 +8,39 @@ fn los_through_wall_blocks() {
     let gen = MapGenerator::new();
     let mut map = gen.generate(20, 10, 1);
     // place a wall between (1,1) and (18,8)
-    map.set_tile(10, 5, TileKind::Wall);
+    map.set_tile(10, 5, TileKind::Wall).unwrap();
     assert!(!los_visible(&map, 1, 1, 18, 8));
 }
 
+#[test]
+fn game_map_iterators_cover_tiles_in_row_major_order() {
+    let mut map = GameMap::from_fn(4, 3, |x, y| {
+        if x == y {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    });
+    map.set_tile(3, 2, TileKind::Water).unwrap();
+
+    let all: Vec<_> = map.iter_tiles().collect();
+    assert_eq!(all.len(), 12);
+    assert_eq!(all[0], ((0, 0), TileKind::Wall));
+    assert_eq!(all[5], ((1, 1), TileKind::Wall));
+    assert_eq!(map.pos(7), (3, 1));
+    assert_eq!(map.rows().count(), 3);
+    assert_eq!(map.rows().nth(2).unwrap()[3], TileKind::Water);
+
+    let walls: Vec<_> = map.find_tiles(TileKind::Wall).collect();
+    assert_eq!(walls, vec![(0, 0), (1, 1), (2, 2)]);
+
+    // Clipped to the map; inclusive on both corners
+    let rect: Vec<_> = map.iter_rect((2, -5), (9, 1)).map(|(p, _)| p).collect();
+    assert_eq!(rect, vec![(2, 0), (3, 0), (2, 1), (3, 1)]);
+    assert_eq!(map.iter_rect((5, 0), (9, 2)).count(), 0);
+    assert_eq!(map.iter_rect((1, 1), (0, 0)).count(), 0);
+}
+
 #[test]
 fn a_star_finds_path_on_floor() {
     let gen = MapGenerator::new();
@@ -142,3 +171,128 @@ fn load_legacy_save_without_determinism_fields() {
     assert_eq!(entities.len(), 1);
     assert_eq!(entities[0].0 .0, "TestGoblin");
 }
+
+#[test]
+fn material_layer_and_mined_stone_survive_save_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(4, 3));
+    let mut materials = MaterialMap::filled(4, 3, Material::Sedimentary);
+    materials.set(2, 1, Material::Igneous).unwrap();
+    world.insert_resource(materials.clone());
+    world.spawn((
+        Name("Granite".into()),
+        Position(2, 1),
+        Item::stone(),
+        Material::Igneous,
+    ));
+    world.spawn((Name("Digger".into()), Position(0, 0), Miner, MiningSkill(3)));
+
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut w2 = World::new();
+    // A stale layer from a previous world must not survive the load
+    w2.insert_resource(MaterialMap::filled(1, 1, Material::Soil));
+    load_world(serde_json::from_str(&json).unwrap(), &mut w2);
+
+    assert_eq!(*w2.resource::<MaterialMap>(), materials);
+    let mut q = w2.query::<(&Name, &Material)>();
+    let stones: Vec<_> = q.iter(&w2).map(|(n, m)| (n.0.clone(), *m)).collect();
+    assert_eq!(stones, [("Granite".to_string(), Material::Igneous)]);
+    let mut q = w2.query::<&MiningSkill>();
+    assert_eq!(q.single(&w2), &MiningSkill(3));
+
+    // Saves without a layer load without one
+    let mut bare = World::new();
+    bare.insert_resource(GameMap::new(2, 2));
+    let json = serde_json::to_string(&save_world(&mut bare)).unwrap();
+    assert!(!json.contains("materials"));
+    load_world(serde_json::from_str(&json).unwrap(), &mut w2);
+    assert!(w2.get_resource::<MaterialMap>().is_none());
+}
+
+#[test]
+fn explored_tiles_and_vision_survive_save_and_load() {
+    let mut world = World::new();
+    let mut map = GameMap::new(12, 12);
+    map.set_tile(5, 3, TileKind::Wall).unwrap();
+    world.insert_resource(map);
+    world.spawn((Name("Scout".into()), Position(3, 3), VisionRadius::new(4)));
+    // Tiles explored earlier, far from where the scout stands now
+    world.insert_resource(gc_core::fov::Explored {
+        tiles: [(10, 10), (11, 10)].into_iter().collect(),
+    });
+    let save = save_world(&mut world);
+    assert_eq!(save.explored, vec![(10, 10), (11, 10)]);
+
+    let json = serde_json::to_string(&save).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+
+    let (scout, radius) = loaded
+        .query::<(Entity, &VisionRadius)>()
+        .iter(&loaded)
+        .map(|(e, r)| (e, r.base))
+        .next()
+        .unwrap();
+    assert_eq!(radius, 4);
+    // Visibility is ready before any schedule runs, walls still block it
+    let vis = loaded.resource::<gc_core::fov::Visibility>();
+    let seen = &vis.per_entity[&scout];
+    assert!(seen.contains(&(3, 3)) && seen.contains(&(5, 3)));
+    assert!(!seen.contains(&(6, 3)));
+    // The saved layer is kept and the scout's current view is folded in
+    let explored = loaded.resource::<gc_core::fov::Explored>();
+    assert!(explored.contains(10, 10) && explored.contains(11, 10));
+    assert!(explored.contains(3, 3));
+
+    // Recomputing matches the schedule's own visibility systems
+    let expected = seen.clone();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::fov::compute_visibility_system);
+    schedule.run(&mut loaded);
+    let vis = loaded.resource::<gc_core::fov::Visibility>();
+    assert_eq!(vis.per_entity[&scout], expected);
+}
+
+#[test]
+fn saves_without_explored_layer_still_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(4, 4));
+    let mut save = save_world(&mut world);
+    save.explored.clear();
+    let json = serde_json::to_string(&save).unwrap();
+    assert!(!json.contains("explored"));
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    assert!(loaded.resource::<gc_core::fov::Explored>().tiles.is_empty());
+}
+
+#[test]
+fn factions_survive_save_and_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(8, 8));
+    world.spawn((
+        Name("Raider".into()),
+        Position(1, 1),
+        Miner,
+        AssignedJob::default(),
+        Faction::new(FactionKind::Invaders),
+    ));
+    world.spawn((Name("Grak".into()), Position(2, 2)));
+    let save = save_world(&mut world);
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let mut got: Vec<(String, Option<FactionKind>)> = loaded
+        .query::<(&Name, Option<&Faction>)>()
+        .iter(&loaded)
+        .map(|(n, f)| (n.0.clone(), f.map(|f| f.kind)))
+        .collect();
+    got.sort();
+    assert_eq!(
+        got,
+        vec![
+            ("Grak".to_string(), None),
+            ("Raider".to_string(), Some(FactionKind::Invaders)),
+        ]
+    );
+}
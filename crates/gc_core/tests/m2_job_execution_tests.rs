@@ -8,12 +8,15 @@ fn mining_to_item_to_haul_pipeline() {
 
     // Setup map
     let mut map = GameMap::new(20, 20);
-    map.set_tile(5, 5, TileKind::Wall); // Wall to mine
+    map.set_tile(5, 5, TileKind::Wall).unwrap(); // Wall to mine
     world.insert_resource(map);
 
     // Setup resources
     world.insert_resource(JobBoard::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::Time::new(100));
     world.insert_resource(systems::DeterministicRng::new(42));
     world.insert_resource(jobs::ItemSpawnQueue::default());
@@ -40,7 +43,10 @@ fn mining_to_item_to_haul_pipeline() {
     world.spawn((
         Name("TestStockpile".into()),
         Position(10, 10),
-        Stockpile { accepts: None },
+        Stockpile {
+            accepts: None,
+            priority: 0,
+        },
     ));
 
     // Add mining designation
@@ -158,13 +164,16 @@ fn multiple_items_create_multiple_haul_jobs() {
 
     // Setup map with multiple walls
     let mut map = GameMap::new(20, 20);
-    map.set_tile(5, 5, TileKind::Wall);
-    map.set_tile(6, 6, TileKind::Wall);
+    map.set_tile(5, 5, TileKind::Wall).unwrap();
+    map.set_tile(6, 6, TileKind::Wall).unwrap();
     world.insert_resource(map);
 
     // Setup resources
     world.insert_resource(JobBoard::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::Time::new(100));
     world.insert_resource(systems::DeterministicRng::new(42));
     world.insert_resource(jobs::ItemSpawnQueue::default());
@@ -199,7 +208,10 @@ fn multiple_items_create_multiple_haul_jobs() {
     world.spawn((
         Name("TestStockpile".into()),
         Position(10, 10),
-        Stockpile { accepts: None },
+        Stockpile {
+            accepts: None,
+            priority: 0,
+        },
     ));
 
     // Add multiple mining designations
@@ -263,3 +275,368 @@ fn multiple_items_create_multiple_haul_jobs() {
         "At least one item should be at the stockpile"
     );
 }
+
+fn haul_held_far_away(policy: ReassignPolicy, tick: u64) -> (World, Entity, Entity, JobId) {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(40, 40));
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    world.insert_resource(policy);
+    world.insert_resource(jobs::ReassignmentEvents::default());
+    let mut time = systems::Time::new(100);
+    time.ticks = tick;
+    world.insert_resource(time);
+
+    let id = JobId(uuid::Uuid::from_u128(7));
+    world.resource_mut::<jobs::ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Haul {
+                from: (10, 10),
+                to: (0, 0),
+            },
+            issued_by: IssuedBy::LOCAL_PLAYER,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    let holder = world
+        .spawn((
+            Position(30, 10),
+            Carrier,
+            Inventory::default(),
+            AssignedJob(Some(id)),
+        ))
+        .id();
+    let idle = world
+        .spawn((
+            Position(11, 10),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ))
+        .id();
+    (world, holder, idle, id)
+}
+
+fn run_assignment(world: &mut World) {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(jobs::job_assignment_system);
+    schedule.run(world);
+}
+
+#[test]
+fn never_policy_keeps_assignments() {
+    let (mut world, holder, idle, id) = haul_held_far_away(ReassignPolicy::Never, 0);
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, Some(id));
+    assert_eq!(world.get::<AssignedJob>(idle).unwrap().0, None);
+    assert_eq!(
+        world.resource::<jobs::ReassignmentEvents>().iter().count(),
+        0
+    );
+}
+
+#[test]
+fn much_closer_idle_worker_takes_the_job_and_reports_it() {
+    let (mut world, holder, idle, id) = haul_held_far_away(ReassignPolicy::TwiceAsClose, 3);
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, None);
+    assert_eq!(world.get::<AssignedJob>(idle).unwrap().0, Some(id));
+    let events = world.resource_mut::<jobs::ReassignmentEvents>().drain();
+    assert_eq!(
+        events,
+        vec![JobReassigned {
+            tick: 3,
+            job: id,
+            issued_by: IssuedBy::LOCAL_PLAYER,
+            faction: FactionKind::Goblins,
+            from: holder,
+            to: idle,
+        }]
+    );
+}
+
+#[test]
+fn periodic_policy_only_reevaluates_on_its_interval() {
+    let policy = ReassignPolicy::Every { ticks: 5 };
+    let (mut world, holder, _, id) = haul_held_far_away(policy, 4);
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, Some(id));
+
+    let (mut world, holder, idle, id) = haul_held_far_away(policy, 10);
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, None);
+    assert_eq!(world.get::<AssignedJob>(idle).unwrap().0, Some(id));
+}
+
+#[test]
+fn loaded_carriers_and_busy_boards_prevent_stealing() {
+    let (mut world, holder, idle, id) = haul_held_far_away(ReassignPolicy::TwiceAsClose, 0);
+    let item = world.spawn(Item::stone()).id();
+    world.get_mut::<Inventory>(holder).unwrap().0 = Some(item);
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, Some(id));
+    assert_eq!(world.get::<AssignedJob>(idle).unwrap().0, None);
+
+    // An idle carrier with board work takes that instead
+    let (mut world, holder, idle, id) = haul_held_far_away(ReassignPolicy::TwiceAsClose, 0);
+    let other = JobId(uuid::Uuid::from_u128(8));
+    world.resource_mut::<JobBoard>().0.push(Job {
+        id: other,
+        kind: JobKind::Haul {
+            from: (1, 1),
+            to: (0, 0),
+        },
+        issued_by: IssuedBy::System,
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    });
+    run_assignment(&mut world);
+    assert_eq!(world.get::<AssignedJob>(holder).unwrap().0, Some(id));
+    assert_eq!(world.get::<AssignedJob>(idle).unwrap().0, Some(other));
+}
+
+fn gc_world(tick: u64) -> World {
+    let mut world = World::new();
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    world.insert_resource(JobGcConfig::default());
+    let mut time = systems::Time::new(100);
+    time.ticks = tick;
+    world.insert_resource(time);
+    world
+}
+
+fn activate(world: &mut World, n: u128, kind: JobKind) -> JobId {
+    let id = JobId(uuid::Uuid::from_u128(n));
+    world.resource_mut::<jobs::ActiveJobs>().jobs.insert(
+        id,
+        Job {
+            id,
+            kind,
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    id
+}
+
+fn run_gc(world: &mut World) {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(jobs::active_jobs_gc_system);
+    schedule.run(world);
+}
+
+#[test]
+fn gc_returns_jobs_of_dead_and_despawned_workers_to_the_board() {
+    let mut world = gc_world(100);
+    let mine = activate(&mut world, 1, JobKind::Mine { x: 3, y: 3 });
+    let haul = activate(
+        &mut world,
+        2,
+        JobKind::Haul {
+            from: (1, 1),
+            to: (2, 2),
+        },
+    );
+    let rest = activate(&mut world, 3, JobKind::Rest { x: 0, y: 0 });
+    let kept = activate(&mut world, 4, JobKind::Mine { x: 5, y: 5 });
+    // The miner holding `mine` is gone; the carrier died mid-haul and
+    // dropped the stone where it fell
+    let dead = world
+        .spawn((Carrier, AssignedJob(Some(haul)), Dead, Position(4, 1)))
+        .id();
+    world.spawn((Item::stone(), Position(4, 1)));
+    world.spawn((Miner, AssignedJob(Some(kept))));
+    assert_eq!(orphaned_jobs(&mut world), {
+        let mut ids = vec![mine, haul, rest];
+        ids.sort_by_key(|id| id.0);
+        ids
+    });
+
+    run_gc(&mut world);
+
+    let board: Vec<JobId> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .map(|j| j.id)
+        .collect();
+    assert_eq!(board, vec![mine, haul]);
+    assert_eq!(
+        world.resource::<JobBoard>().0[1].kind,
+        JobKind::Haul {
+            from: (4, 1),
+            to: (2, 2),
+        }
+    );
+    let active = &world.resource::<jobs::ActiveJobs>().jobs;
+    assert_eq!(active.keys().copied().collect::<Vec<_>>(), vec![kept]);
+    assert_eq!(world.get::<AssignedJob>(dead).unwrap().0, None);
+    assert!(orphaned_jobs(&mut world).is_empty());
+}
+
+#[test]
+fn gc_drops_hauls_whose_item_is_gone() {
+    let mut world = gc_world(100);
+    let waiting = activate(
+        &mut world,
+        1,
+        JobKind::Haul {
+            from: (1, 1),
+            to: (2, 2),
+        },
+    );
+    let vanished = activate(
+        &mut world,
+        2,
+        JobKind::Haul {
+            from: (3, 3),
+            to: (2, 2),
+        },
+    );
+    // Died on the way, before picking anything up
+    world.spawn((Carrier, AssignedJob(Some(waiting)), Dead, Position(0, 1)));
+    world.spawn((Item::stone(), Position(1, 1)));
+    world.spawn((Carrier, AssignedJob(Some(vanished)), Dead, Position(5, 5)));
+
+    run_gc(&mut world);
+
+    let board = &world.resource::<JobBoard>().0;
+    assert_eq!(board.len(), 1);
+    assert_eq!(board[0].id, waiting);
+    assert_eq!(
+        board[0].kind,
+        JobKind::Haul {
+            from: (1, 1),
+            to: (2, 2),
+        }
+    );
+    assert!(world.resource::<jobs::ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn gc_only_runs_on_its_interval() {
+    let mut world = gc_world(7);
+    activate(&mut world, 1, JobKind::Mine { x: 3, y: 3 });
+    run_gc(&mut world);
+    assert!(world.resource::<JobBoard>().0.is_empty());
+    assert_eq!(orphaned_jobs(&mut world).len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "orphaned active job")]
+fn gc_assert_mode_panics_on_leaks() {
+    let mut world = gc_world(0);
+    world.insert_resource(JobGcConfig {
+        interval: 1,
+        assert_no_orphans: true,
+    });
+    activate(&mut world, 1, JobKind::Mine { x: 3, y: 3 });
+    run_gc(&mut world);
+}
+
+#[test]
+fn demo_colony_does_not_leak_active_jobs() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        32,
+        24,
+        11,
+        gc_core::bootstrap::WorldOptions {
+            populate_demo_scene: true,
+            ..Default::default()
+        },
+    );
+    world.insert_resource(JobGcConfig {
+        interval: 1,
+        assert_no_orphans: true,
+    });
+    for x in 2..8 {
+        let (x, y) = (x, 2);
+        world
+            .resource_mut::<GameMap>()
+            .set_tile(x, y, TileKind::Wall)
+            .unwrap();
+        world.spawn(DesignationBundle {
+            pos: Position(x, y),
+            kind: MineDesignation,
+            lifecycle: DesignationLifecycle::default(),
+        });
+    }
+    let mut schedule = gc_core::bootstrap::build_default_schedule();
+    for _ in 0..60 {
+        schedule.run(&mut world);
+    }
+    assert!(orphaned_jobs(&mut world).is_empty());
+}
+
+#[test]
+fn workers_only_take_jobs_of_their_own_faction() {
+    let mut world = World::new();
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
+    world.insert_resource(systems::DeterministicRng::new(3));
+    let invader = world
+        .spawn((
+            Position(1, 1),
+            Miner,
+            AssignedJob::default(),
+            Faction::new(FactionKind::Invaders),
+        ))
+        .id();
+    world.spawn((designations::DesignationBundle {
+        pos: Position(2, 2),
+        ..Default::default()
+    },));
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            jobs::job_assignment_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+
+    // The goblin designation stays on the board for goblins
+    assert_eq!(world.get::<AssignedJob>(invader).unwrap().0, None);
+    let board = &world.resource::<JobBoard>().0;
+    assert_eq!(board.len(), 1);
+    assert_eq!(board[0].faction, FactionKind::Goblins);
+
+    // A designation of their own is fair game
+    world.spawn((
+        designations::DesignationBundle {
+            pos: Position(3, 3),
+            ..Default::default()
+        },
+        Faction::new(FactionKind::Invaders),
+    ));
+    let goblin = world
+        .spawn((Position(1, 2), Miner, AssignedJob::default()))
+        .id();
+    // One assignment per run
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    let taken = |world: &World, e| {
+        let id = world.get::<AssignedJob>(e).unwrap().0.unwrap();
+        world.resource::<jobs::ActiveJobs>().jobs[&id].clone()
+    };
+    let job = taken(&world, invader);
+    assert_eq!(job.kind, JobKind::Mine { x: 3, y: 3 });
+    assert_eq!(job.faction, FactionKind::Invaders);
+    assert_eq!(taken(&world, goblin).kind, JobKind::Mine { x: 2, y: 2 });
+}
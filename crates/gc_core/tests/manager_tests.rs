@@ -0,0 +1,218 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn smelter(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((Workshop, Station::new(StationKind::Smelter), Position(x, y)))
+        .id()
+}
+
+fn bar(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Item {
+                item_type: ItemType::Bar,
+            },
+            Carriable,
+            Position(x, y),
+        ))
+        .id()
+}
+
+/// Count stockpiles, then evaluate standing orders
+fn manager_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((stockpile_contents_system, manager_system).chain());
+    schedule
+}
+
+fn queued(world: &World, station: Entity) -> usize {
+    world.get::<Station>(station).unwrap().orders.len()
+}
+
+#[test]
+fn standing_orders_are_validated() {
+    let mut world = world();
+    assert_eq!(
+        StandingOrder::new("smelt-iron", 5, 3),
+        Err(ManagerError::InvertedBand { min: 5, max: 3 })
+    );
+    assert_eq!(
+        add_standing_order(&mut world, "smelt-gold", 1, 2),
+        Err(ManagerError::UnknownRecipe("smelt-gold".into()))
+    );
+    add_standing_order(&mut world, "smelt-iron", 1, 2).unwrap();
+    add_standing_order(&mut world, "forge-armor", 1, 1).unwrap();
+    add_standing_order(&mut world, "smelt-iron", 20, 25).unwrap();
+    let manager = world.resource::<ManagerOrders>();
+    let bands: Vec<(&str, u32, u32)> = manager
+        .orders
+        .iter()
+        .map(|o| (o.recipe.as_str(), o.min, o.max))
+        .collect();
+    assert_eq!(
+        bands,
+        vec![("smelt-iron", 20, 25), ("forge-armor", 1, 1)],
+        "setting a recipe again replaces its band in place"
+    );
+    assert!(world.resource_mut::<ManagerOrders>().remove("forge-armor"));
+    assert!(!world.resource_mut::<ManagerOrders>().remove("forge-armor"));
+}
+
+#[test]
+fn orders_refill_below_min_and_rest_until_stock_drops_again() {
+    let mut world = world();
+    world.spawn(StockpileBundle::new(2, 2, 5, 5));
+    let station = smelter(&mut world, 12, 6);
+    let bars: Vec<Entity> = (0..3).map(|x| bar(&mut world, 2 + x, 2)).collect();
+    add_standing_order(&mut world, "smelt-iron", 2, 4).unwrap();
+    let mut schedule = manager_only();
+
+    // Three stockpiled is above the trigger level
+    schedule.run(&mut world);
+    assert_eq!(queued(&world, station), 0);
+
+    // Down to one: refill up to four
+    world.despawn(bars[0]);
+    world.despawn(bars[1]);
+    schedule.run(&mut world);
+    assert_eq!(queued(&world, station), 3);
+    let order = world.resource::<ManagerOrders>().get("smelt-iron").unwrap();
+    assert!(order.refilling);
+    assert_eq!(order.issued, 3);
+    // Queued crafts count, so nothing more is queued while they are worked
+    schedule.run(&mut world);
+    assert_eq!(queued(&world, station), 3);
+
+    // The crafts finish and their bars are stockpiled
+    world.get_mut::<Station>(station).unwrap().orders.clear();
+    for x in 0..3 {
+        bar(&mut world, 2 + x, 3);
+    }
+    schedule.run(&mut world);
+    assert!(!world.resource::<ManagerOrders>().orders[0].refilling);
+
+    // Taking one out of a full stockpile is inside the band: no work
+    world.despawn(bars[2]);
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(queued(&world, station), 0);
+    assert_eq!(world.resource::<ManagerOrders>().orders[0].issued, 3);
+}
+
+#[test]
+fn loose_products_count_as_on_their_way() {
+    let mut world = world();
+    world.spawn(StockpileBundle::new(2, 2, 5, 5));
+    let station = smelter(&mut world, 12, 6);
+    // Two bars lie outside any stockpile; an equipped one counts for nothing
+    bar(&mut world, 9, 9);
+    bar(&mut world, 10, 9);
+    let wearer = world.spawn(Position(11, 9)).id();
+    let worn = bar(&mut world, 11, 9);
+    world
+        .entity_mut(worn)
+        .remove::<Position>()
+        .insert(EquippedBy(wearer));
+    add_standing_order(&mut world, "smelt-iron", 1, 3).unwrap();
+    manager_only().run(&mut world);
+    assert_eq!(queued(&world, station), 1);
+}
+
+#[test]
+fn crafts_go_to_the_least_busy_station_in_entity_order() {
+    let mut world = world();
+    let first = smelter(&mut world, 12, 2);
+    let second = smelter(&mut world, 12, 8);
+    world.spawn((
+        Workshop,
+        Station::new(StationKind::Workshop),
+        Position(16, 5),
+    ));
+    world
+        .get_mut::<Station>(second)
+        .unwrap()
+        .orders
+        .push_back("smelt-iron".into());
+    add_standing_order(&mut world, "smelt-iron", 1, 4).unwrap();
+    manager_only().run(&mut world);
+    assert_eq!((queued(&world, first), queued(&world, second)), (2, 2));
+    assert_eq!(world.resource::<ManagerOrders>().orders[0].issued, 3);
+}
+
+#[test]
+fn standing_orders_keep_stockpiles_supplied_in_the_full_schedule() {
+    let run = || {
+        let mut world = world();
+        world.spawn(StockpileBundle::new(1, 8, 3, 10));
+        let station = smelter(&mut world, 10, 5);
+        for x in 3..6 {
+            world.spawn((
+                Item {
+                    item_type: ItemType::Ore,
+                },
+                Carriable,
+                Position(x, 3),
+            ));
+        }
+        for x in 6..12 {
+            world.spawn((
+                Item {
+                    item_type: ItemType::Coal,
+                },
+                Carriable,
+                Position(x, 1),
+            ));
+        }
+        world.spawn((Position(9, 5), Miner, AssignedJob::default()));
+        world.spawn((
+            Position(2, 2),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ));
+        add_standing_order(&mut world, "smelt-iron", 1, 2).unwrap();
+        let mut schedule = build_default_schedule();
+        for _ in 0..500 {
+            schedule.run(&mut world);
+        }
+        let crafts = world.get::<Station>(station).unwrap().crafts;
+        let order = world.resource::<ManagerOrders>().orders[0].clone();
+        let stockpiled = world.resource::<StockpileContents>().total(ItemType::Bar);
+        (crafts, order, stockpiled)
+    };
+    let (crafts, order, stockpiled) = run();
+    assert_eq!(crafts, 2, "stops at the refill level with ore to spare");
+    assert_eq!(stockpiled, 2);
+    assert_eq!(order.issued, 2);
+    assert!(!order.refilling);
+    assert_eq!(run(), (crafts, order, stockpiled));
+}
+
+#[test]
+fn standing_orders_survive_save_and_load() {
+    let mut world = world();
+    add_standing_order(&mut world, "smelt-iron", 2, 5).unwrap();
+    {
+        let mut manager = world.resource_mut::<ManagerOrders>();
+        manager.orders[0].refilling = true;
+        manager.orders[0].issued = 3;
+    }
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+    assert_eq!(
+        loaded.resource::<ManagerOrders>(),
+        world.resource::<ManagerOrders>()
+    );
+}
@@ -0,0 +1,83 @@
+use gc_core::prelude::*;
+use std::collections::{HashSet, VecDeque};
+
+fn caves(width: u32, height: u32, seed: u32) -> GameMap {
+    let config = MapGenConfig {
+        algorithm: MapGenAlgorithm::Caves,
+        ..MapGenConfig::new(width, height)
+    };
+    MapGenerator::new().generate_with(&config, seed)
+}
+
+fn floors(map: &GameMap) -> Vec<(i32, i32)> {
+    (0..map.height as i32)
+        .flat_map(|y| (0..map.width as i32).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.get_tile(x, y) == Some(TileKind::Floor))
+        .collect()
+}
+
+#[test]
+fn caves_are_deterministic_per_seed() {
+    assert_eq!(caves(48, 32, 7).tiles, caves(48, 32, 7).tiles);
+    assert_ne!(caves(48, 32, 7).tiles, caves(48, 32, 8).tiles);
+}
+
+#[test]
+fn caves_have_closed_border_and_one_connected_cavern() {
+    for seed in 0..5 {
+        let map = caves(40, 25, seed);
+        for x in 0..40 {
+            assert_eq!(map.get_tile(x, 0), Some(TileKind::Wall));
+            assert_eq!(map.get_tile(x, 24), Some(TileKind::Wall));
+        }
+        let open = floors(&map);
+        assert!(open.len() > 100, "seed {} left too little floor", seed);
+
+        // Flood fill from one floor tile reaches every other
+        let mut seen = HashSet::from([open[0]]);
+        let mut queue = VecDeque::from([open[0]]);
+        while let Some((x, y)) = queue.pop_front() {
+            for n in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if map.get_tile(n.0, n.1) == Some(TileKind::Floor) && seen.insert(n) {
+                    queue.push_back(n);
+                }
+            }
+        }
+        assert_eq!(seen.len(), open.len(), "seed {} has isolated pockets", seed);
+    }
+}
+
+#[test]
+fn algorithm_parses_and_defaults_to_noise() {
+    assert_eq!(MapGenConfig::new(4, 4).algorithm, MapGenAlgorithm::Noise);
+    assert_eq!("caves".parse(), Ok(MapGenAlgorithm::Caves));
+    assert!("lakes".parse::<MapGenAlgorithm>().is_err());
+    // Default backend is unchanged
+    assert_eq!(
+        MapGenerator::new().generate(30, 20, 3).tiles,
+        MapGenerator::new()
+            .generate_with(&MapGenConfig::new(30, 20), 3)
+            .tiles
+    );
+}
+
+#[test]
+fn material_layers_follow_igneous_share() {
+    let gen = MapGenerator::new();
+    let count = |igneous_percent: u8| {
+        let config = MapGenConfig {
+            igneous_percent,
+            ..MapGenConfig::new(48, 32)
+        };
+        let materials = gen.generate_materials(&config, 11);
+        assert_eq!(materials, gen.generate_materials(&config, 11));
+        assert_eq!(materials.layers.len(), 48 * 32);
+        Material::ALL.map(|m| materials.layers.iter().filter(|&&l| l == m).count())
+    };
+    let none = count(0);
+    assert_eq!(none[2], 0);
+    assert!(none[0] > 0 && none[1] > 0);
+    let heavy = count(80);
+    assert!(heavy[2] > count(30)[2]);
+    assert!(heavy[2] > heavy[1]);
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::{build_default_schedule, fast_forward};
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open floor over clay, with a hauler and a builder
+fn world() -> World {
+    let mut world = floor_world(16, 10, 5);
+    world.insert_resource(MaterialMap::filled(16, 10, Material::Soil));
+    world.spawn((
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(1, 1),
+    ));
+    world.spawn((Miner, AssignedJob::default(), Position(1, 2)));
+    world
+}
+
+fn stone(world: &mut World, material: Material, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Item::stone(),
+            Stone,
+            Carriable,
+            material,
+            Name(material.item_name().into()),
+            Position(x, y),
+        ))
+        .id()
+}
+
+fn blocks(world: &mut World) -> Vec<(Option<Material>, String)> {
+    let mut q = world.query::<(&Item, Option<&Material>, &Name)>();
+    let mut found: Vec<_> = q
+        .iter(world)
+        .filter(|(i, ..)| i.item_type == ItemType::Block)
+        .map(|(_, m, n)| (m.copied(), n.0.clone()))
+        .collect();
+    found.sort();
+    found
+}
+
+#[test]
+fn stone_is_worth_what_its_rock_is() {
+    assert_eq!(Material::Sedimentary.item_name(), "Limestone");
+    let stone = |m| material_item_value(ItemType::Stone, m, None);
+    assert_eq!(stone(None), item_value(ItemType::Stone, None));
+    assert_eq!(
+        [Material::Soil, Material::Sedimentary, Material::Igneous].map(|m| stone(Some(m))),
+        [1, 2, 4]
+    );
+    assert_eq!(
+        material_item_value(
+            ItemType::Block,
+            Some(Material::Igneous),
+            Some(Quality::Fine)
+        ),
+        15
+    );
+    // Gems are worth the same whatever rock they came out of
+    assert_eq!(
+        material_item_value(ItemType::Gem, Some(Material::Soil), None),
+        item_value(ItemType::Gem, None)
+    );
+    let registry = TagRegistry::default();
+    assert!(registry.has_tag(ItemType::Block, "building-material"));
+    assert!(!registry.has_tag(ItemType::Block, "stone"));
+}
+
+#[test]
+fn masons_cut_blocks_in_the_stone_they_were_given() {
+    let mut world = world();
+    let mason = world
+        .spawn((Workshop, Station::new(StationKind::Mason), Position(10, 5)))
+        .id();
+    stone(&mut world, Material::Igneous, 3, 7);
+    stone(&mut world, Material::Sedimentary, 4, 7);
+    queue_order(&mut world, mason, "cut-blocks").unwrap();
+    queue_order(&mut world, mason, "cut-blocks").unwrap();
+    assert!(matches!(
+        queue_order(&mut world, mason, "forge-weapon"),
+        Err(ProductionError::WrongStation { .. })
+    ));
+
+    let mut schedule = build_default_schedule();
+    let cut = fast_forward(&mut world, &mut schedule, 300, |w| {
+        w.get::<Station>(mason).unwrap().crafts == 2
+    });
+    assert!(cut.is_some());
+    let found = blocks(&mut world);
+    let materials: Vec<_> = found.iter().map(|(m, _)| *m).collect();
+    assert_eq!(
+        materials,
+        [Some(Material::Sedimentary), Some(Material::Igneous)]
+    );
+    assert!(found[1].1.ends_with("Granite Block"), "{:?}", found[1].1);
+    assert!(world.get::<Station>(mason).unwrap().materials.is_empty());
+}
+
+#[test]
+fn stocked_materials_survive_a_save() {
+    let mut world = world();
+    let mut station = Station::new(StationKind::Mason);
+    station.stock.insert(ItemType::Stone, 2);
+    station.materials.insert(Material::Igneous, 2);
+    world.spawn((Workshop, station, Position(10, 5)));
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Wall, 6, 6))
+        .id();
+    {
+        let mut requirement = world.get_mut::<MaterialRequirement>(site).unwrap();
+        requirement.delivered = 1;
+        requirement.materials.insert(Material::Sedimentary, 1);
+    }
+
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let mut q = loaded.query::<&Station>();
+    assert_eq!(
+        q.single(&loaded).materials.get(&Material::Igneous),
+        Some(&2)
+    );
+    let mut q = loaded.query::<&MaterialRequirement>();
+    assert_eq!(q.single(&loaded).material(), Some(Material::Sedimentary));
+}
+
+#[test]
+fn buildings_are_made_of_the_stone_delivered() {
+    let mut world = world();
+    stone(&mut world, Material::Igneous, 3, 7);
+    stone(&mut world, Material::Igneous, 4, 7);
+    stone(&mut world, Material::Sedimentary, 2, 9);
+    world.spawn(ConstructionBundle::new(ConstructionKind::Wall, 3, 4));
+    let mut schedule = build_default_schedule();
+    let built = fast_forward(&mut world, &mut schedule, 300, |w| {
+        w.resource::<GameMap>().get_tile(3, 4) == Some(TileKind::Wall)
+    });
+    assert!(built.is_some());
+    assert_eq!(
+        world.resource::<MaterialMap>().get(3, 4),
+        Some(Material::Igneous),
+        "the nearest stones were the granite ones"
+    );
+
+    stone(&mut world, Material::Igneous, 11, 7);
+    stone(&mut world, Material::Sedimentary, 12, 8);
+    let mason = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Mason, 12, 5))
+        .id();
+    let built = fast_forward(&mut world, &mut schedule, 300, |w| {
+        w.get::<Station>(mason).is_some()
+    });
+    assert!(built.is_some());
+    assert_eq!(
+        world.get::<Material>(mason),
+        Some(&Material::Sedimentary),
+        "two limestone outweigh one granite"
+    );
+    assert_eq!(world.get::<Name>(mason).unwrap().0, "Mason's workshop");
+}
+
+#[test]
+fn cancelled_sites_give_back_the_stone_they_were_given() {
+    let mut world = world();
+    stone(&mut world, Material::Igneous, 3, 7);
+    let site = world
+        .spawn(ConstructionBundle::new(ConstructionKind::Wall, 8, 4))
+        .id();
+    let mut schedule = build_default_schedule();
+    let delivered = fast_forward(&mut world, &mut schedule, 200, |w| {
+        w.get::<MaterialRequirement>(site).unwrap().delivered == 1
+    });
+    assert!(delivered.is_some());
+    assert!(cancel_construction(&mut world, site));
+    let mut q = world.query::<(&Material, &Name, &Position)>();
+    let refunded: Vec<_> = q
+        .iter(&world)
+        .map(|(m, n, p)| (*m, n.0.clone(), *p))
+        .collect();
+    assert_eq!(
+        refunded,
+        [(Material::Igneous, "Granite".to_string(), Position(8, 4))]
+    );
+}
+
+#[test]
+fn rooms_are_worth_their_walls_and_what_lies_in_them() {
+    let mut world = world();
+    let room = ZoneBounds::new(0, 0, 4, 4);
+    let mut map = world.resource_mut::<GameMap>();
+    for x in 0..5 {
+        map.set_tile(x, 0, TileKind::Wall).unwrap();
+    }
+    let clay = room_value(&mut world, &room);
+    // Five clay walls, two stone each, at one apiece
+    assert_eq!(clay, 10);
+
+    let mut materials = world.resource_mut::<MaterialMap>();
+    for x in 0..5 {
+        materials.set(x, 0, Material::Igneous).unwrap();
+    }
+    assert_eq!(room_value(&mut world, &room), 40);
+
+    stone(&mut world, Material::Igneous, 2, 2);
+    stone(&mut world, Material::Igneous, 9, 9);
+    world.spawn((
+        Item {
+            item_type: ItemType::Block,
+        },
+        Material::Sedimentary,
+        Quality::Masterwork,
+        Position(3, 3),
+    ));
+    assert_eq!(room_value(&mut world, &room), 40 + 4 + 20);
+}
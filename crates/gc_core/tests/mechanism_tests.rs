@@ -0,0 +1,264 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn lever(world: &mut World, x: i32, y: i32) -> Entity {
+    world.spawn((Lever::default(), Position(x, y))).id()
+}
+
+fn plate(world: &mut World, x: i32, y: i32) -> Entity {
+    world.spawn((PressurePlate::default(), Position(x, y))).id()
+}
+
+fn door(world: &mut World, x: i32, y: i32) -> Entity {
+    world.spawn((Door::default(), Position(x, y))).id()
+}
+
+fn invader(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .spawn((
+            Position(x, y),
+            Health::full(10),
+            Faction::new(FactionKind::Invaders),
+        ))
+        .id()
+}
+
+fn mechanisms_only() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(mechanism_system);
+    schedule
+}
+
+fn tile(world: &World, x: i32, y: i32) -> Option<TileKind> {
+    world.resource::<GameMap>().get_tile(x, y)
+}
+
+#[test]
+fn links_only_join_triggers_to_targets() {
+    let mut world = world();
+    let lever = lever(&mut world, 1, 1);
+    let gate = door(&mut world, 4, 4);
+    let rock = world.spawn(Position(2, 2)).id();
+    assert_eq!(
+        link_mechanism(&mut world, lever, rock),
+        Err(MechanismError::NotATarget)
+    );
+    assert_eq!(
+        link_mechanism(&mut world, rock, gate),
+        Err(MechanismError::NotATrigger)
+    );
+    assert_eq!(link_mechanism(&mut world, lever, gate), Ok(()));
+    assert_eq!(
+        link_mechanism(&mut world, lever, gate),
+        Err(MechanismError::AlreadyLinked)
+    );
+    assert_eq!(
+        world.get::<MechanismLinks>(lever),
+        Some(&MechanismLinks(vec![gate]))
+    );
+    assert_eq!(pull_lever(&mut world, gate), Err(MechanismError::NotALever));
+    assert_eq!(activate(&mut world, gate), Err(MechanismError::NotATrigger));
+}
+
+#[test]
+fn pulling_a_lever_closes_and_reopens_its_doors() {
+    let mut world = world();
+    let lever = lever(&mut world, 2, 2);
+    let gates = [door(&mut world, 10, 5), door(&mut world, 10, 6)];
+    for gate in gates {
+        link_mechanism(&mut world, lever, gate).unwrap();
+    }
+    let puller = world
+        .spawn((
+            Position(6, 2),
+            Carrier,
+            Inventory::default(),
+            AssignedJob::default(),
+        ))
+        .id();
+    pull_lever(&mut world, lever).unwrap();
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let job = world.get::<AssignedJob>(puller).unwrap().0;
+    assert!(job.is_some());
+    assert_eq!(world.get::<Lever>(lever).unwrap().pull_job, job);
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.get::<Lever>(lever),
+        Some(&Lever {
+            on: true,
+            pull_requested: false,
+            pull_job: None,
+        })
+    );
+    assert_eq!(world.get::<AssignedJob>(puller).unwrap().0, None);
+    for gate in gates {
+        assert_eq!(world.get::<Door>(gate), Some(&Door { open: false }));
+    }
+    assert_eq!(tile(&world, 10, 5), Some(TileKind::Wall));
+    assert_eq!(tile(&world, 10, 6), Some(TileKind::Wall));
+
+    pull_lever(&mut world, lever).unwrap();
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert!(!world.get::<Lever>(lever).unwrap().on);
+    assert_eq!(tile(&world, 10, 5), Some(TileKind::Floor));
+    assert!(world.resource::<ActiveJobs>().jobs.is_empty());
+}
+
+#[test]
+fn plates_fire_once_per_visit_and_ignore_goblins() {
+    let mut world = world();
+    let plate = plate(&mut world, 5, 5);
+    let gate = door(&mut world, 8, 5);
+    link_mechanism(&mut world, plate, gate).unwrap();
+    let mut schedule = mechanisms_only();
+
+    let goblin = world.spawn((Goblin, Position(5, 5), Health::full(10))).id();
+    schedule.run(&mut world);
+    assert!(world.get::<Door>(gate).unwrap().open, "goblins pass safely");
+    world.despawn(goblin);
+
+    let raider = invader(&mut world, 5, 5);
+    schedule.run(&mut world);
+    assert!(!world.get::<Door>(gate).unwrap().open);
+    assert!(world.get::<PressurePlate>(plate).unwrap().pressed);
+    // Standing on it does not fire it again
+    schedule.run(&mut world);
+    assert!(!world.get::<Door>(gate).unwrap().open);
+
+    *world.get_mut::<Position>(raider).unwrap() = Position(4, 5);
+    schedule.run(&mut world);
+    assert!(!world.get::<PressurePlate>(plate).unwrap().pressed);
+    *world.get_mut::<Position>(raider).unwrap() = Position(5, 5);
+    schedule.run(&mut world);
+    assert!(world.get::<Door>(gate).unwrap().open);
+    assert_eq!(tile(&world, 8, 5), Some(TileKind::Floor));
+}
+
+#[test]
+fn traps_strike_everything_on_them() {
+    let mut world = world();
+    world.insert_resource(MechanismConfig { trap_damage: 4 });
+    let plate = plate(&mut world, 3, 3);
+    let trap = world.spawn((Trap, Position(3, 3))).id();
+    link_mechanism(&mut world, plate, trap).unwrap();
+    let raider = invader(&mut world, 3, 3);
+    let bystander = invader(&mut world, 4, 3);
+    mechanisms_only().run(&mut world);
+    assert_eq!(world.get::<Health>(raider).unwrap().hp, 6);
+    assert_eq!(world.get::<Health>(bystander).unwrap().hp, 10);
+}
+
+#[test]
+fn blocked_doorways_stay_open() {
+    let mut world = world();
+    let lever = lever(&mut world, 1, 1);
+    let gate = door(&mut world, 6, 6);
+    link_mechanism(&mut world, lever, gate).unwrap();
+    world.spawn((
+        Item {
+            item_type: ItemType::Stone,
+        },
+        Position(6, 6),
+    ));
+    activate(&mut world, lever).unwrap();
+    mechanisms_only().run(&mut world);
+    assert!(world.get::<Door>(gate).unwrap().open);
+    assert_eq!(tile(&world, 6, 6), Some(TileKind::Floor));
+}
+
+#[test]
+fn same_tick_activations_do_not_depend_on_spawn_order() {
+    let run = |reversed: bool| {
+        let mut world = world();
+        let gate = door(&mut world, 10, 2);
+        let mut spots = vec![(2, 5), (8, 5)];
+        if reversed {
+            spots.reverse();
+        }
+        for (x, y) in spots {
+            let plate = plate(&mut world, x, y);
+            link_mechanism(&mut world, plate, gate).unwrap();
+            invader(&mut world, x, y);
+        }
+        let lever = lever(&mut world, 0, 0);
+        link_mechanism(&mut world, lever, gate).unwrap();
+        activate(&mut world, lever).unwrap();
+        let revision = world.resource::<GameMap>().revision();
+        mechanisms_only().run(&mut world);
+        let map = world.resource::<GameMap>();
+        (
+            *world.get::<Door>(gate).unwrap(),
+            map.get_tile(10, 2),
+            map.revision() - revision,
+        )
+    };
+    // Three toggles in one tick leave the door closed, in one map change
+    assert_eq!(run(false), (Door { open: false }, Some(TileKind::Wall), 1));
+    assert_eq!(run(true), run(false));
+}
+
+#[test]
+fn links_and_states_survive_save_and_load() {
+    let mut world = world();
+    let lever = lever(&mut world, 1, 1);
+    let plate = plate(&mut world, 3, 1);
+    let gate = door(&mut world, 5, 5);
+    let trap = world.spawn((Trap, Position(6, 5))).id();
+    link_mechanism(&mut world, lever, trap).unwrap();
+    link_mechanism(&mut world, lever, gate).unwrap();
+    link_mechanism(&mut world, plate, gate).unwrap();
+    world.get_mut::<Lever>(lever).unwrap().on = true;
+    pull_lever(&mut world, lever).unwrap();
+    world.get_mut::<Door>(gate).unwrap().open = false;
+
+    let json = serde_json::to_string(&save_world(&mut world)).unwrap();
+    let mut loaded = World::new();
+    load_world(serde_json::from_str(&json).unwrap(), &mut loaded);
+
+    let at = |world: &mut World, x: i32, y: i32| {
+        world
+            .query::<(Entity, &Position)>()
+            .iter(world)
+            .find(|(_, p)| **p == Position(x, y))
+            .map(|(e, _)| e)
+            .unwrap()
+    };
+    let (lever, plate, gate, trap) = (
+        at(&mut loaded, 1, 1),
+        at(&mut loaded, 3, 1),
+        at(&mut loaded, 5, 5),
+        at(&mut loaded, 6, 5),
+    );
+    assert_eq!(
+        loaded.get::<Lever>(lever),
+        Some(&Lever {
+            on: true,
+            pull_requested: true,
+            pull_job: None,
+        })
+    );
+    assert_eq!(
+        loaded.get::<MechanismLinks>(lever),
+        Some(&MechanismLinks(vec![trap, gate]))
+    );
+    assert_eq!(
+        loaded.get::<MechanismLinks>(plate),
+        Some(&MechanismLinks(vec![gate]))
+    );
+    assert_eq!(loaded.get::<Door>(gate), Some(&Door { open: false }));
+    assert!(loaded.get::<Trap>(trap).is_some());
+}
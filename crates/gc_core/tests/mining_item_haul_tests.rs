@@ -10,7 +10,10 @@ fn mining_item_haul_end_to_end() {
     // Setup resources
     world.insert_resource(GameMap::new(10, 10));
     world.insert_resource(JobBoard::default());
-    world.insert_resource(DesignationConfig { auto_jobs: true });
+    world.insert_resource(DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
@@ -18,7 +21,7 @@ fn mining_item_haul_end_to_end() {
     // Get mutable reference to map and place a wall
     {
         let mut map = world.resource_mut::<GameMap>();
-        map.set_tile(5, 5, TileKind::Wall);
+        map.set_tile(5, 5, TileKind::Wall).unwrap();
     }
 
     // Spawn a miner
@@ -47,7 +50,10 @@ fn mining_item_haul_end_to_end() {
         .spawn((
             Name("Stockpile".to_string()),
             Position(8, 8),
-            Stockpile { accepts: None },
+            Stockpile {
+                accepts: None,
+                priority: 0,
+            },
         ))
         .id();
 
@@ -82,7 +88,13 @@ fn mining_item_haul_end_to_end() {
 
     // Execute mining
     let mut mining_schedule = Schedule::default();
-    mining_schedule.add_systems(systems::mining_execution_system);
+    mining_schedule.add_systems(
+        (
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
     mining_schedule.run(&mut world);
 
     // Verify mining results - wall becomes floor, item spawned
@@ -126,10 +138,11 @@ fn mining_item_haul_end_to_end() {
     haul_job_assignment_schedule.add_systems(jobs::job_assignment_system);
     haul_job_assignment_schedule.run(&mut world);
 
-    // Execute hauling
+    // Execute hauling until the job completes (pickup, then delivery)
     let mut hauling_schedule = Schedule::default();
     hauling_schedule.add_systems(systems::hauling_execution_system);
-    hauling_schedule.run(&mut world);
+    let ticks = fast_forward(&mut world, &mut hauling_schedule, 10, jobs_idle);
+    assert!(ticks.is_some(), "Haul should complete");
 
     // Verify hauling results - item moved to stockpile
     let final_item_query_result = world
@@ -179,7 +192,10 @@ fn mining_without_wall_does_nothing() {
     // Setup resources
     world.insert_resource(GameMap::new(10, 10));
     world.insert_resource(JobBoard::default());
-    world.insert_resource(DesignationConfig { auto_jobs: true });
+    world.insert_resource(DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
@@ -227,7 +243,13 @@ fn mining_without_wall_does_nothing() {
 
     // Execute mining
     let mut mining_schedule = Schedule::default();
-    mining_schedule.add_systems(systems::mining_execution_system);
+    mining_schedule.add_systems(
+        (
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
     mining_schedule.run(&mut world);
 
     // Verify no changes - still floor, no items spawned
@@ -246,3 +268,136 @@ fn mining_without_wall_does_nothing() {
         "Should still have no items - no wall to mine"
     );
 }
+
+#[test]
+fn stone_mined_inside_a_stockpile_is_not_hauled() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(10, 10));
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
+    world.insert_resource(systems::DeterministicRng::new(42));
+    world.insert_resource(jobs::ItemSpawnQueue::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        map.set_tile(2, 2, TileKind::Wall).unwrap();
+        map.set_tile(7, 7, TileKind::Wall).unwrap();
+    }
+    world.spawn((Position(2, 2), Miner, AssignedJob::default()));
+    world.spawn((Position(7, 7), Miner, AssignedJob::default()));
+    // (2, 2) sits inside the stockpile; (7, 7) does not
+    world.spawn(StockpileBundle::new(1, 1, 3, 3));
+    for pos in [Position(2, 2), Position(7, 7)] {
+        world.spawn(DesignationBundle {
+            pos,
+            kind: MineDesignation,
+            lifecycle: DesignationLifecycle::default(),
+        });
+    }
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            jobs::mining_job_assignment_system,
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
+    let mut haul = Schedule::default();
+    haul.add_systems(systems::auto_haul_system);
+    for _ in 0..3 {
+        schedule.run(&mut world);
+        haul.run(&mut world);
+    }
+
+    assert_eq!(world.query::<&Item>().iter(&world).count(), 2);
+    let hauls: Vec<_> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|j| match j.kind {
+            JobKind::Haul { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(hauls, [(7, 7)]);
+}
+
+#[test]
+fn stockpiles_that_refuse_an_item_do_not_count_as_storage() {
+    let mut world = World::new();
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(systems::DeterministicRng::new(1));
+    let mut refusing = StockpileBundle::new(0, 0, 2, 2);
+    refusing.stockpile.accepts = Some(vec![ItemType::Weapon]);
+    world.spawn(refusing);
+    world.spawn(StockpileBundle::new(6, 6, 8, 8));
+    world.spawn((Item::stone(), Position(1, 1)));
+
+    let mut haul = Schedule::default();
+    haul.add_systems(systems::auto_haul_system);
+    haul.run(&mut world);
+    let board = &world.resource::<JobBoard>().0;
+    assert_eq!(board.len(), 1);
+    assert!(matches!(
+        board[0].kind,
+        JobKind::Haul {
+            from: (1, 1),
+            to: (7, 7)
+        }
+    ));
+}
+
+#[test]
+fn default_schedule_spawns_mined_stone_and_hauls_it() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        12,
+        6,
+        3,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..6 {
+            for x in 0..12 {
+                map.set_tile(x, y, TileKind::Floor).unwrap();
+            }
+        }
+        map.set_tile(2, 2, TileKind::Wall).unwrap();
+    }
+    world.spawn((Position(1, 2), Miner, AssignedJob::default()));
+    world.spawn((
+        Position(1, 3),
+        Carrier,
+        AssignedJob::default(),
+        Inventory::default(),
+    ));
+    world.spawn(StockpileBundle::new(9, 2, 10, 3));
+    world.spawn(DesignationBundle {
+        pos: Position(2, 2),
+        kind: MineDesignation,
+        lifecycle: DesignationLifecycle::default(),
+    });
+
+    let mut schedule = gc_core::bootstrap::build_default_schedule();
+    let stored = |world: &mut World| {
+        world
+            .query_filtered::<&Position, With<Stone>>()
+            .iter(world)
+            .any(|p| (9..=10).contains(&p.0) && (2..=3).contains(&p.1))
+    };
+    assert!(fast_forward(&mut world, &mut schedule, 30, stored).is_some());
+    assert!(world.resource::<jobs::ItemSpawnQueue>().requests.is_empty());
+    assert_eq!(
+        world
+            .query_filtered::<(), With<Stone>>()
+            .iter(&world)
+            .count(),
+        1
+    );
+}
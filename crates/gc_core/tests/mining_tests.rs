@@ -9,13 +9,16 @@ fn mine_job_converts_wall_to_floor() {
     world.insert_resource(jobs::JobBoard::default());
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
 
     // Set up a wall at position (5, 5)
     {
         let mut map = world.resource_mut::<GameMap>();
-        map.set_tile(5, 5, TileKind::Wall);
+        map.set_tile(5, 5, TileKind::Wall).unwrap();
     }
 
     // Create a miner at the wall position
@@ -80,13 +83,16 @@ fn mine_job_does_not_affect_non_wall_tiles() {
     world.insert_resource(jobs::JobBoard::default());
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
 
     // Set up a floor at position (5, 5) - not a wall
     {
         let mut map = world.resource_mut::<GameMap>();
-        map.set_tile(5, 5, TileKind::Floor);
+        map.set_tile(5, 5, TileKind::Floor).unwrap();
     }
 
     // Create a miner at the position
@@ -146,7 +152,10 @@ fn miner_gets_assigned_mine_jobs() {
     world.insert_resource(jobs::JobBoard::default());
     world.insert_resource(jobs::ItemSpawnQueue::default());
     world.insert_resource(jobs::ActiveJobs::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
 
     // Create a miner
@@ -190,3 +199,90 @@ fn miner_gets_assigned_mine_jobs() {
     let assigned = world.get::<AssignedJob>(miner_entity).unwrap();
     assert!(assigned.0.is_some());
 }
+
+fn layered_world(material: Material) -> World {
+    let mut world = World::new();
+    let mut map = GameMap::new(6, 6);
+    map.set_tile(3, 3, TileKind::Wall).unwrap();
+    world.insert_resource(map);
+    world.insert_resource(MaterialMap::filled(6, 6, material));
+    world.insert_resource(jobs::JobBoard::default());
+    world.insert_resource(jobs::ItemSpawnQueue::default());
+    world.insert_resource(jobs::ActiveJobs::default());
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
+    world.insert_resource(systems::DeterministicRng::new(7));
+    world.spawn((
+        designations::MineDesignation,
+        Position(3, 3),
+        DesignationLifecycle::default(),
+    ));
+    world
+}
+
+fn mining_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            designations::designation_to_jobs_system,
+            jobs::job_assignment_system,
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
+    schedule
+}
+
+#[test]
+fn mined_stone_carries_the_tile_material() {
+    let mut world = layered_world(Material::Sedimentary);
+    world.spawn((Position(3, 3), Miner, AssignedJob::default()));
+    let mut schedule = mining_schedule();
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+
+    let mut q = world.query::<(&Item, &Material, &Name)>();
+    let stones: Vec<_> = q.iter(&world).collect();
+    assert_eq!(stones.len(), 1);
+    assert_eq!(*stones[0].1, Material::Sedimentary);
+    assert_eq!(stones[0].2 .0, "Limestone");
+}
+
+#[test]
+fn igneous_rock_waits_for_a_skilled_miner() {
+    let mut world = layered_world(Material::Igneous);
+    world.spawn((Position(3, 3), Miner, AssignedJob::default()));
+    let mut schedule = mining_schedule();
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    // Novice leaves the job on the board
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(3, 3),
+        Some(TileKind::Wall)
+    );
+    assert_eq!(world.resource::<jobs::JobBoard>().0.len(), 1);
+
+    world.spawn((
+        Position(3, 3),
+        Miner,
+        MiningSkill(Material::Igneous.required_skill()),
+        AssignedJob::default(),
+    ));
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(3, 3),
+        Some(TileKind::Floor)
+    );
+    let mut q = world.query::<&Material>();
+    assert_eq!(
+        q.iter(&world).copied().collect::<Vec<_>>(),
+        [Material::Igneous]
+    );
+}
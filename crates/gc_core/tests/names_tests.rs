@@ -0,0 +1,62 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn same_seed_gives_same_names() {
+    let mut a = StdRng::seed_from_u64(9);
+    let mut b = StdRng::seed_from_u64(9);
+    for kind in [NameKind::Goblin, NameKind::Invader, NameKind::Animal] {
+        assert_eq!(generate_name(&mut a, kind), generate_name(&mut b, kind));
+    }
+}
+
+#[test]
+fn names_have_tradition_specific_parts() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let goblin = generate_name(&mut rng, NameKind::Goblin);
+    assert!(goblin.surname.is_some());
+    let animal = generate_name(&mut rng, NameKind::Animal);
+    assert!(animal.surname.is_none());
+    let named = GeneratedName {
+        given: "Grak".into(),
+        surname: Some("Mudfoot".into()),
+        profession: Some("miner".into()),
+    };
+    assert_eq!(named.full(), "Grak Mudfoot the miner");
+}
+
+#[test]
+fn names_are_varied() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let names: std::collections::HashSet<String> = (0..20)
+        .map(|_| generate_name(&mut rng, NameKind::Goblin).full())
+        .collect();
+    assert!(names.len() > 15, "too many collisions: {:?}", names);
+}
+
+fn named_world(seed: u64) -> Vec<String> {
+    let mut world = build_standard_world(16, 16, seed, WorldOptions::default());
+    world.spawn((Position(1, 1), Goblin, Miner));
+    world.spawn((Position(2, 1), Goblin, Carrier));
+    world.spawn((Position(3, 1), Faction::new(FactionKind::Invaders)));
+    // Not a goblin or invader: left unnamed
+    world.spawn(Position(4, 1));
+    build_default_schedule().run(&mut world);
+
+    let mut q = world.query::<(Entity, &Name)>();
+    let mut named: Vec<_> = q.iter(&world).map(|(e, n)| (e, n.0.clone())).collect();
+    named.sort();
+    named.into_iter().map(|(_, n)| n).collect()
+}
+
+#[test]
+fn unnamed_actors_are_named_deterministically_with_professions() {
+    let names = named_world(42);
+    assert_eq!(names.len(), 3);
+    assert!(names[0].ends_with(" the miner"));
+    assert!(names[1].ends_with(" the hauler"));
+    assert_eq!(names, named_world(42));
+}
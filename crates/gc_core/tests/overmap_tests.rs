@@ -0,0 +1,175 @@
+use gc_core::prelude::*;
+
+fn site(x: u32, y: u32, width: u32, height: u32) -> EmbarkSite {
+    EmbarkSite {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn overmap_is_deterministic_and_has_rivers() {
+    let a = Overmap::generate(48, 24, 11);
+    assert_eq!(a, Overmap::generate(48, 24, 11));
+    assert_ne!(a.regions, Overmap::generate(48, 24, 12).regions);
+    assert_eq!(a.render_ascii().lines().count(), 24);
+
+    let mountains = a
+        .regions
+        .iter()
+        .filter(|r| r.biome == Biome::Mountains)
+        .count();
+    if mountains > 0 {
+        assert!(a.regions.iter().any(|r| r.river));
+    }
+}
+
+#[test]
+fn embark_extracts_the_selected_rectangle() {
+    let overmap = Overmap::generate(32, 16, 5);
+    let map = overmap.embark(site(4, 3, 3, 2)).unwrap();
+    assert_eq!(
+        (map.width, map.height),
+        (3 * REGION_TILES, 2 * REGION_TILES)
+    );
+    assert_eq!(map.tiles, overmap.embark(site(4, 3, 3, 2)).unwrap().tiles);
+
+    // Overlapping embarks agree on shared regions
+    let wide = overmap.embark(site(4, 3, 2, 1)).unwrap();
+    let right = overmap.embark(site(5, 3, 1, 1)).unwrap();
+    let t = REGION_TILES as i32;
+    for y in 0..t {
+        for x in 0..t {
+            assert_eq!(wide.get_tile(x + t, y), right.get_tile(x, y));
+        }
+    }
+}
+
+#[test]
+fn ocean_regions_embark_as_water() {
+    let overmap = Overmap::generate(64, 32, 42);
+    let Some(i) = overmap.regions.iter().position(|r| r.biome == Biome::Ocean) else {
+        return;
+    };
+    let (x, y) = (i as u32 % 64, i as u32 / 64);
+    let map = overmap.embark(site(x, y, 1, 1)).unwrap();
+    assert!(map.tiles.iter().all(|&t| t == TileKind::Water));
+}
+
+#[test]
+fn invalid_sites_are_rejected() {
+    let overmap = Overmap::generate(8, 8, 1);
+    assert_eq!(
+        overmap.embark(site(0, 0, 0, 1)).unwrap_err(),
+        EmbarkError::Empty
+    );
+    assert_eq!(
+        overmap.embark(site(7, 0, 2, 1)).unwrap_err(),
+        EmbarkError::OutOfBounds(site(7, 0, 2, 1))
+    );
+    // Far edges past u32::MAX are out of bounds, not an overflow
+    for far in [site(u32::MAX, 0, 1, 1), site(0, u32::MAX, 1, u32::MAX)] {
+        assert_eq!(
+            overmap.embark(far).unwrap_err(),
+            EmbarkError::OutOfBounds(far)
+        );
+        assert_eq!(
+            overmap.embark_materials(far).unwrap_err(),
+            EmbarkError::OutOfBounds(far)
+        );
+    }
+}
+
+fn first_region(overmap: &Overmap, biome: Biome) -> Option<(u32, u32)> {
+    overmap
+        .regions
+        .iter()
+        .position(|r| r.biome == biome && !r.river)
+        .map(|i| (i as u32 % overmap.width, i as u32 / overmap.width))
+}
+
+fn count(map: &GameMap, kind: TileKind) -> usize {
+    map.tiles.iter().filter(|&&t| t == kind).count()
+}
+
+#[test]
+fn biome_params_shape_local_terrain() {
+    let overmap = Overmap::generate(64, 32, 42);
+    let embark =
+        |b| first_region(&overmap, b).map(|(x, y)| overmap.embark(site(x, y, 1, 1)).unwrap());
+    if let (Some(mountains), Some(plains)) = (embark(Biome::Mountains), embark(Biome::Plains)) {
+        assert!(count(&mountains, TileKind::Wall) > count(&plains, TileKind::Wall));
+    }
+    if let (Some(swamp), Some(desert)) = (embark(Biome::Swamp), embark(Biome::Desert)) {
+        assert!(count(&swamp, TileKind::Water) > count(&desert, TileKind::Water));
+    }
+    assert!(Biome::Forest.params().tree_density > Biome::Plains.params().tree_density);
+    assert!(Biome::Mountains.params().igneous_percent > Biome::Swamp.params().igneous_percent);
+}
+
+#[test]
+fn embark_world_spawns_flora_fauna_and_temperature() {
+    use bevy_ecs::prelude::*;
+    use gc_core::bootstrap::{build_embark_world, WorldOptions};
+
+    let overmap = Overmap::generate(64, 32, 42);
+    let (x, y) = first_region(&overmap, Biome::Forest).expect("seed 42 has forest");
+    let positions = |world: &mut World| {
+        let mut trees: Vec<_> = world
+            .query_filtered::<&Position, With<Tree>>()
+            .iter(world)
+            .map(|p| (p.0, p.1))
+            .collect();
+        trees.sort_unstable();
+        let animals = world
+            .query_filtered::<&Name, With<Animal>>()
+            .iter(world)
+            .count();
+        (trees, animals)
+    };
+
+    let mut a = build_embark_world(&overmap, site(x, y, 1, 1), 7, WorldOptions::default()).unwrap();
+    let mut b = build_embark_world(&overmap, site(x, y, 1, 1), 7, WorldOptions::default()).unwrap();
+    let (trees, _) = positions(&mut a);
+    assert!(!trees.is_empty());
+    assert_eq!(positions(&mut a), positions(&mut b));
+    assert_eq!(
+        a.resource::<AmbientTemperature>().0,
+        Biome::Forest.params().ambient_temperature
+    );
+    assert_eq!(
+        a.resource::<RegionDifficulty>().0,
+        Biome::Forest.params().raid_difficulty
+    );
+    let map = a.resource::<GameMap>();
+    assert!(trees
+        .iter()
+        .all(|&(x, y)| map.get_tile(x, y) == Some(TileKind::Floor)));
+}
+
+#[test]
+fn embark_materials_match_the_map_and_biome() {
+    let overmap = Overmap::generate(64, 32, 42);
+    let igneous = |(x, y)| {
+        let materials = overmap.embark_materials(site(x, y, 1, 1)).unwrap();
+        let map = overmap.embark(site(x, y, 1, 1)).unwrap();
+        assert_eq!((materials.width, materials.height), (map.width, map.height));
+        materials
+            .layers
+            .iter()
+            .filter(|&&m| m == Material::Igneous)
+            .count()
+    };
+    if let (Some(mountains), Some(swamp)) = (
+        first_region(&overmap, Biome::Mountains),
+        first_region(&overmap, Biome::Swamp),
+    ) {
+        assert!(igneous(mountains) > igneous(swamp));
+    }
+    assert_eq!(
+        overmap.embark_materials(site(63, 0, 2, 1)),
+        Err(EmbarkError::OutOfBounds(site(63, 0, 2, 1)))
+    );
+}
@@ -0,0 +1,120 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+
+/// A floor corridor along the top, a lake below it with a lava pool on its
+/// right, and a wall splitting the lake but for a gap at the bottom:
+///
+/// ```text
+/// ..........
+/// ~~~~#~~LL~
+/// ~~~~#~~LL~
+/// ~~~~~~~~~~
+/// ```
+fn map() -> GameMap {
+    GameMap::from_fn(10, 4, |x, y| match (x, y) {
+        (_, 0) => TileKind::Floor,
+        (4, 1 | 2) => TileKind::Wall,
+        (7 | 8, 1 | 2) => TileKind::Lava,
+        _ => TileKind::Water,
+    })
+}
+
+fn options(passability: Passability) -> PathOptions {
+    PathOptions {
+        passability,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn each_class_enters_its_own_tiles() {
+    use Passability::*;
+    use TileKind::*;
+    let rules = [
+        (Walks, [true, false, false, false]),
+        (Swims, [false, false, true, false]),
+        (Flies, [true, false, true, true]),
+    ];
+    for (class, expected) in rules {
+        let got = [Floor, Wall, Water, Lava].map(|t| class.can_enter(t));
+        assert_eq!(got, expected, "{class:?}");
+    }
+    assert_eq!(Passability::default(), Walks);
+    assert!(!Flies.passable(&map(), -1, 0), "nobody leaves the map");
+}
+
+#[test]
+fn searches_keep_to_the_movers_tiles() {
+    let map = map();
+    // Swimmers go round the wall through the gap, never up onto the floor
+    let (path, cost) = astar_path_with(&map, (0, 1), (9, 1), options(Passability::Swims)).unwrap();
+    assert!(path
+        .iter()
+        .all(|&(x, y)| map.get_tile(x, y) == Some(TileKind::Water)));
+    assert_eq!(cost, path.len() as i32 - 1);
+    assert!(astar_path_with(&map, (0, 1), (0, 0), options(Passability::Swims)).is_none());
+
+    // Flyers cross water onto lava, going round the wall
+    let (path, cost) = astar_path_with(&map, (0, 2), (8, 2), options(Passability::Flies)).unwrap();
+    assert_eq!(cost, 10);
+    assert!(!path.contains(&(4, 2)));
+    assert!(astar_path_with(&map, (0, 2), (4, 1), options(Passability::Flies)).is_none());
+
+    // Walkers stay on the corridor, and smoothing never takes anyone off
+    // their tiles
+    assert!(astar_path_with(&map, (0, 0), (0, 3), PathOptions::default()).is_none());
+    let smooth = PathOptions {
+        smooth: true,
+        ..options(Passability::Swims)
+    };
+    let (path, _) = astar_path_with(&map, (0, 1), (9, 3), smooth).unwrap();
+    assert!(path
+        .iter()
+        .all(|&(x, y)| Passability::Swims.passable(&map, x, y)));
+}
+
+#[test]
+fn queued_requests_search_for_the_requesters_class() {
+    let mut world = World::new();
+    world.insert_resource(map());
+    world.insert_resource(PathQueue::default());
+    let fish = world.spawn(Passability::Swims).id();
+    let goblin = world.spawn_empty().id();
+    {
+        let mut queue = world.resource_mut::<PathQueue>();
+        queue.request(fish, (0, 3), (9, 3));
+        queue.request(goblin, (0, 3), (9, 3));
+    }
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_request_system);
+    schedule.run(&mut world);
+
+    let result = |e| world.get::<PathReady>(e).unwrap().result.clone();
+    let (path, _) = result(fish).expect("fish swim along the bottom");
+    assert_eq!(path.len(), 10);
+    assert_eq!(result(goblin), None, "goblins cannot cross the lake");
+}
+
+#[test]
+fn movers_with_a_class_stay_on_their_tiles() {
+    let mut world = World::new();
+    world.insert_resource(map());
+    let fish = world
+        .spawn((Position(3, 1), Velocity(1, 0), Passability::Swims))
+        .id();
+    let bird = world
+        .spawn((Position(5, 1), Velocity(1, 0), Passability::Flies))
+        .id();
+    let ghost = world.spawn((Position(3, 2), Velocity(1, 0))).id();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(movement);
+    schedule.run(&mut world);
+
+    // The fish stops at the wall; the bird flies on over the lava
+    assert_eq!(world.get::<Position>(fish), Some(&Position(3, 1)));
+    assert_eq!(world.get::<Position>(bird), Some(&Position(6, 1)));
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Position>(bird), Some(&Position(7, 1)));
+    // Without a class movement is as free as ever
+    assert_eq!(world.get::<Position>(ghost), Some(&Position(5, 2)));
+}
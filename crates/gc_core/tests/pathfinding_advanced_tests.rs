@@ -11,7 +11,7 @@ fn create_map_with_walls() -> GameMap {
     let mut map = create_test_map(10, 10);
     // Create some walls to test obstacle avoidance
     for y in 2..8 {
-        map.set_tile(5, y, TileKind::Wall); // Vertical wall
+        map.set_tile(5, y, TileKind::Wall).unwrap(); // Vertical wall
     }
     map
 }
@@ -116,7 +116,7 @@ fn astar_path_no_path_available() {
     for x in 0..5 {
         for y in 0..5 {
             if x > 0 || y > 0 {
-                map.set_tile(x, y, TileKind::Wall);
+                map.set_tile(x, y, TileKind::Wall).unwrap();
             }
         }
     }
@@ -218,7 +218,7 @@ fn path_to_unreachable_area() {
     for dx in -1..=1 {
         for dy in -1..=1 {
             if dx != 0 || dy != 0 {
-                map.set_tile(8 + dx, 8 + dy, TileKind::Wall);
+                map.set_tile(8 + dx, 8 + dy, TileKind::Wall).unwrap();
             }
         }
     }
@@ -244,3 +244,237 @@ fn path_service_cache_eviction() {
     // Should have had cache hits for entries 2 and 3, but miss for entry 1 retrieval
     assert_eq!(misses, 4); // 3 initial + 1 re-fetch of evicted entry
 }
+
+#[test]
+fn time_sliced_search_matches_astar_cost() {
+    let map = create_map_with_walls();
+    let request = PathRequest {
+        start: (0, 5),
+        goal: (9, 5),
+    };
+    let expected = astar_path(&map, request.start, request.goal).unwrap();
+
+    let mut search = PathSearch::new(request);
+    let mut calls = 0;
+    let (path, cost) = loop {
+        calls += 1;
+        if let Some(result) = search.step(&map, 5) {
+            break result.unwrap();
+        }
+    };
+    assert!(calls > 1, "a five-node budget cannot finish this search");
+    assert_eq!(cost, expected.1);
+    assert_eq!(path.first(), Some(&request.start));
+    assert_eq!(path.last(), Some(&request.goal));
+    assert!(path.iter().all(|&(x, y)| map.is_walkable(x, y)));
+
+    // Unreachable goals finish with no path
+    let mut walled = create_test_map(5, 5);
+    walled.set_tile(4, 4, TileKind::Wall).unwrap();
+    let mut search = PathSearch::new(PathRequest {
+        start: (0, 0),
+        goal: (4, 4),
+    });
+    assert_eq!(search.step(&walled, 1000), Some(None));
+}
+
+#[test]
+fn path_queue_spreads_requests_over_ticks_within_budget() {
+    use bevy_ecs::prelude::*;
+
+    let mut world = World::new();
+    world.insert_resource(create_test_map(40, 40));
+    world.insert_resource(PathfindingConfig {
+        nodes_per_tick: 50,
+        ..Default::default()
+    });
+    world.insert_resource(PathQueue::default());
+    let agents: Vec<Entity> = (0..10).map(|_| world.spawn_empty().id()).collect();
+    for (i, &agent) in agents.iter().enumerate() {
+        world
+            .resource_mut::<PathQueue>()
+            .request(agent, (0, i as i32), (39, 39));
+    }
+    // Superseded before it is served
+    world
+        .resource_mut::<PathQueue>()
+        .request(agents[9], (0, 0), (1, 0));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_request_system);
+    let mut ticks = 0;
+    while !world.resource::<PathQueue>().is_empty() {
+        schedule.run(&mut world);
+        assert!(world.resource::<PathQueue>().last_tick_expanded <= 50);
+        ticks += 1;
+        assert!(ticks < 1000, "queue never drained");
+    }
+    assert!(ticks > 1);
+
+    for (i, &agent) in agents.iter().enumerate() {
+        let ready = world
+            .get::<PathReady>(agent)
+            .expect("every request answered");
+        let (path, cost) = ready.result.clone().unwrap();
+        if i == 9 {
+            assert_eq!(ready.request.goal, (1, 0));
+            assert_eq!(cost, 1);
+        } else {
+            assert_eq!(cost, 39 + 39 - i as i32);
+            assert_eq!(path.len() as i32, cost + 1);
+        }
+    }
+}
+
+fn turns(path: &[(i32, i32)]) -> usize {
+    path.windows(3)
+        .filter(|w| (w[1].0 - w[0].0, w[1].1 - w[0].1) != (w[2].0 - w[1].0, w[2].1 - w[1].1))
+        .count()
+}
+
+#[test]
+fn smoothed_path_is_walkable_contiguous_and_no_longer() {
+    let map = create_map_with_walls();
+    let (start, goal) = ((1, 1), (8, 8));
+    let (raw, _) = astar_path(&map, start, goal).unwrap();
+    let options = PathOptions {
+        smooth: true,
+        turn_penalty: 0,
+        ..Default::default()
+    };
+    let (path, cost) = astar_path_with(&map, start, goal, options).unwrap();
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&goal));
+    assert!(path.len() <= raw.len());
+    assert_eq!(cost, path.len() as i32 - 1);
+    assert!(path.iter().all(|&(x, y)| map.is_walkable(x, y)));
+    for w in path.windows(2) {
+        let (dx, dy) = ((w[1].0 - w[0].0).abs(), (w[1].1 - w[0].1).abs());
+        assert!(dx <= 1 && dy <= 1 && dx + dy > 0, "gap at {:?}", w);
+    }
+}
+
+#[test]
+fn turn_penalty_prefers_straight_runs_at_equal_length() {
+    let map = create_test_map(12, 12);
+    let (start, goal) = ((0, 0), (9, 9));
+    let (raw, raw_cost) = astar_path(&map, start, goal).unwrap();
+    let options = PathOptions {
+        smooth: false,
+        turn_penalty: 5,
+        ..Default::default()
+    };
+    let (path, cost) = astar_path_with(&map, start, goal, options).unwrap();
+    assert_eq!(cost, raw_cost);
+    assert!(turns(&path) <= 1, "path turned {} times", turns(&path));
+    assert!(turns(&path) <= turns(&raw));
+}
+
+#[test]
+fn shaped_paths_are_deterministic() {
+    let map = create_map_with_walls();
+    let options = PathOptions {
+        smooth: true,
+        turn_penalty: 3,
+        ..Default::default()
+    };
+    let first = astar_path_with(&map, (1, 8), (8, 1), options);
+    for _ in 0..5 {
+        assert_eq!(astar_path_with(&map, (1, 8), (8, 1), options), first);
+    }
+    assert_eq!(
+        astar_path_with(&map, (1, 1), (8, 8), PathOptions::default()),
+        astar_path(&map, (1, 1), (8, 8))
+    );
+}
+
+#[test]
+fn invalid_endpoints_fail_without_searching() {
+    let map = create_map_with_walls();
+    assert_eq!(astar_path(&map, (1, 1), (5, 4)), None);
+    assert_eq!(astar_path(&map, (-1, 1), (8, 8)), None);
+    assert_eq!(astar_path(&map, (1, 1), (20, 20)), None);
+    // Standing inside a wall, the start still counts as reached
+    assert_eq!(astar_path(&map, (5, 4), (5, 4)), Some((vec![(5, 4)], 0)));
+
+    let mut search = PathSearch::new(PathRequest {
+        start: (1, 1),
+        goal: (5, 4),
+    });
+    assert_eq!(search.step(&map, usize::MAX), Some(None));
+    assert_eq!(search.expanded, 0);
+}
+
+#[test]
+fn adjacent_goals_stop_next_to_a_wall() {
+    let map = create_map_with_walls();
+    let options = PathOptions {
+        adjacent: true,
+        ..Default::default()
+    };
+    let (path, cost) = astar_path_with(&map, (1, 4), (5, 4), options).unwrap();
+    assert_eq!(path.last(), Some(&(4, 4)));
+    assert_eq!(cost, 3);
+
+    // Already next to it: nowhere to go
+    let (path, cost) = astar_path_with(&map, (4, 4), (5, 4), options).unwrap();
+    assert_eq!((path, cost), (vec![(4, 4)], 0));
+
+    // A walkable goal is approached, not entered
+    let (path, _) = astar_path_with(&map, (0, 0), (3, 0), options).unwrap();
+    assert_eq!(path.last(), Some(&(2, 0)));
+}
+
+#[test]
+fn partial_paths_end_at_the_closest_reachable_tile() {
+    // Goal sealed in a walled pocket in the corner
+    let mut map = create_test_map(10, 10);
+    for i in 6..10 {
+        map.set_tile(6, i, TileKind::Wall).unwrap();
+        map.set_tile(i, 6, TileKind::Wall).unwrap();
+    }
+    assert_eq!(astar_path(&map, (0, 0), (8, 8)), None);
+    let options = PathOptions {
+        partial: true,
+        ..Default::default()
+    };
+    let (path, cost) = astar_path_with(&map, (0, 0), (8, 8), options).unwrap();
+    let end = *path.last().unwrap();
+    assert!(map.is_walkable(end.0, end.1));
+    assert_eq!((end.0 - 8).abs() + (end.1 - 8).abs(), 3, "ended at {end:?}");
+    assert_eq!(cost, path.len() as i32 - 1);
+    assert_eq!(
+        astar_path_with(&map, (0, 0), (8, 8), options),
+        Some((path, cost))
+    );
+
+    // Reachable goals are unaffected
+    assert_eq!(
+        astar_path_with(&map, (0, 0), (3, 3), options),
+        astar_path(&map, (0, 0), (3, 3))
+    );
+}
+
+#[test]
+fn queued_requests_can_carry_their_own_options() {
+    use bevy_ecs::prelude::*;
+
+    let mut world = World::new();
+    world.insert_resource(create_map_with_walls());
+    world.insert_resource(PathQueue::default());
+    let miner = world.spawn_empty().id();
+    let options = PathOptions {
+        adjacent: true,
+        ..Default::default()
+    };
+    world
+        .resource_mut::<PathQueue>()
+        .request_with(miner, (1, 4), (5, 4), options);
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_request_system);
+    schedule.run(&mut world);
+
+    let ready = world.get::<PathReady>(miner).expect("answered");
+    let (path, _) = ready.result.clone().unwrap();
+    assert_eq!(path.last(), Some(&(4, 4)));
+}
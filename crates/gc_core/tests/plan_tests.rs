@@ -0,0 +1,132 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    build_standard_world(20, 12, 3, WorldOptions::default())
+}
+
+fn plan() -> DesignationPlan {
+    DesignationPlan {
+        mines: vec![(4, 2), (5, 2)],
+        zones: vec![
+            PlanZone {
+                kind: ZoneKind::Stockpile,
+                min: (1, 1),
+                max: (3, 2),
+                accepts: Some(TagQuery::parse("gem | ore").unwrap()),
+            },
+            PlanZone {
+                kind: ZoneKind::Meeting,
+                min: (10, 8),
+                max: (12, 9),
+                accepts: None,
+            },
+        ],
+    }
+}
+
+fn counts(world: &mut World) -> (usize, usize) {
+    let mines = world
+        .query_filtered::<(), With<MineDesignation>>()
+        .iter(world)
+        .count();
+    let zones = world
+        .query_filtered::<(), With<ZoneKind>>()
+        .iter(world)
+        .count();
+    (mines, zones)
+}
+
+#[test]
+fn plans_round_trip_through_a_world() {
+    let mut world = world();
+    let report = import_plan(&mut world, &plan()).unwrap();
+    assert_eq!(
+        report,
+        PlanImport {
+            designations: 2,
+            zones: 2,
+            duplicates: 0,
+        }
+    );
+    assert_eq!(export_plan(&mut world), plan());
+
+    // Importing again only adds the zones
+    let again = import_plan(&mut world, &plan()).unwrap();
+    assert_eq!((again.designations, again.duplicates), (0, 2));
+}
+
+#[test]
+fn plans_round_trip_through_json_and_csv() {
+    let plan = plan();
+    let json = encode_plan(&plan, PlanFormat::Json).unwrap();
+    assert_eq!(decode_plan(&json, PlanFormat::Json).unwrap(), plan);
+
+    let csv = encode_plan_csv(&plan);
+    assert_eq!(
+        csv,
+        "kind,x0,y0,x1,y1,accepts\n\
+         mine,4,2,4,2,\n\
+         mine,5,2,5,2,\n\
+         stockpile,1,1,3,2,gem | ore\n\
+         meeting,10,8,12,9,\n"
+    );
+    assert_eq!(decode_plan_csv(&csv).unwrap(), plan);
+    assert_eq!(PlanFormat::from_path("dig/plan.CSV"), PlanFormat::Csv);
+    assert_eq!(PlanFormat::from_path("plan.json"), PlanFormat::Json);
+}
+
+#[test]
+fn csv_rows_may_cover_rectangles_and_quote_fields() {
+    let csv = "mine,6,3,4,3\n\n\"stockpile\",1,1,2,2,\"ore\"\n";
+    let plan = decode_plan_csv(csv).unwrap();
+    assert_eq!(plan.mines, vec![(4, 3), (5, 3), (6, 3)]);
+    assert_eq!(plan.zones[0].accepts, Some(TagQuery::parse("ore").unwrap()));
+
+    let err = decode_plan_csv("kind,x0,y0,x1,y1,accepts\nmine,1,1,1\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: expected 5 or 6 fields, found 4");
+    let err = decode_plan_csv("moat,1,1,1,1,\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: unknown kind 'moat'");
+    let err = decode_plan_csv("mine,1,a,1,1,\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: 'a' is not a coordinate");
+    assert!(decode_plan_csv("stockpile,1,1,2,2,\"ore\n").is_err());
+}
+
+#[test]
+fn invalid_plans_change_nothing() {
+    let mut world = world();
+    let before = counts(&mut world);
+
+    let mut outside = plan();
+    outside.zones[1].max = (20, 9);
+    let err = import_plan(&mut world, &outside).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "meeting zone (10, 8)-(20, 9) is outside the 20x12 map"
+    );
+
+    let mut unknown = plan();
+    unknown.zones[0].accepts = Some(TagQuery::parse("dragon").unwrap());
+    assert!(matches!(
+        import_plan(&mut world, &unknown),
+        Err(PlanError::InvalidZone { .. })
+    ));
+
+    let mut far = plan();
+    far.mines.push((-1, 0));
+    assert!(matches!(
+        import_plan(&mut world, &far),
+        Err(PlanError::MineOutOfBounds { x: -1, y: 0, .. })
+    ));
+    assert_eq!(counts(&mut world), before);
+}
+
+#[test]
+fn imported_designations_are_saved() {
+    let mut world = world();
+    import_plan(&mut world, &plan()).unwrap();
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    assert_eq!(export_plan(&mut loaded).mines, plan().mines);
+}
@@ -0,0 +1,165 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::jobs;
+use gc_core::prelude::*;
+
+fn presets(json: &str) -> Presets {
+    serde_json::from_str(json).unwrap()
+}
+
+/// Floor world with walls to dig in columns 2..=5 of rows 1..=2
+fn quarry() -> World {
+    let mut world = build_standard_world(10, 6, 1, WorldOptions::default());
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..6 {
+        for x in 0..10 {
+            let kind = if (2..=5).contains(&x) && (1..=2).contains(&y) {
+                TileKind::Wall
+            } else {
+                TileKind::Floor
+            };
+            map.set_tile(x, y, kind).unwrap();
+        }
+    }
+    world
+}
+
+fn mine_jobs(world: &World) -> Vec<((i32, i32), u32)> {
+    let mut jobs: Vec<_> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|job| match job.kind {
+            JobKind::Mine { x, y } => Some(((x, y), job.priority)),
+            _ => None,
+        })
+        .collect();
+    jobs.sort();
+    jobs
+}
+
+#[test]
+fn presets_decode_and_validate_against_the_registry() {
+    let all = presets(
+        r#"{
+            "metals": { "type": "zone", "kind": "stockpile", "accepts": "metal | ore" },
+            "dump": { "type": "zone", "kind": "refuse" },
+            "rush": { "type": "designation", "priority": 5 }
+        }"#,
+    );
+    let registry = TagRegistry::default();
+    all.validate(&registry).unwrap();
+    assert_eq!(
+        all.iter()
+            .map(|(name, preset)| format!("{name}: {preset}"))
+            .collect::<Vec<_>>(),
+        [
+            "dump: refuse",
+            "metals: stockpile accepting metal | ore",
+            "rush: mine at priority 5"
+        ]
+    );
+    assert_eq!(
+        all.apply("dump", (1, 1), (2, 3)).unwrap(),
+        vec![WorldCommand::Zone {
+            kind: ZoneKind::Refuse,
+            a: (1, 1),
+            b: (2, 3),
+            accepts: None,
+        }]
+    );
+    assert_eq!(
+        all.apply("nope", (0, 0), (0, 0)),
+        Err(PresetError::Unknown("nope".into()))
+    );
+
+    for (json, err) in [
+        (
+            r#"{ "bad": { "type": "zone", "kind": "stockpile", "accepts": "metl" } }"#,
+            "preset 'bad': unknown tag 'metl'",
+        ),
+        (
+            r#"{ "bad": { "type": "zone", "kind": "refuse", "accepts": "ore" } }"#,
+            "preset 'bad': only stockpiles take a tag filter",
+        ),
+        (
+            r#"{ "two words": { "type": "designation" } }"#,
+            "invalid preset name 'two words': use letters, digits, '-' and '_'",
+        ),
+    ] {
+        assert_eq!(
+            presets(json).validate(&registry).unwrap_err().to_string(),
+            err
+        );
+    }
+}
+
+#[test]
+fn designation_presets_post_prioritized_jobs_that_miners_take_first() {
+    let mut world = quarry();
+    world.resource_mut::<DesignationConfig>().auto_jobs = true;
+    let all = presets(r#"{ "rush": { "type": "designation", "priority": 5 } }"#);
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::DesignateRect {
+            a: (2, 1),
+            b: (3, 1)
+        }
+    ));
+    for command in all.apply("rush", (5, 2), (4, 2)).unwrap() {
+        assert!(apply_world_command(&mut world, &command));
+    }
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(designation_to_jobs_system);
+    schedule.run(&mut world);
+    assert_eq!(
+        mine_jobs(&world),
+        [((2, 1), 0), ((3, 1), 0), ((4, 2), 5), ((5, 2), 5)]
+    );
+
+    // Reprioritizing reaches jobs already on the board
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::PrioritizeDesignations {
+            a: (2, 1),
+            b: (2, 1),
+            priority: 9
+        }
+    ));
+    assert_eq!(mine_jobs(&world)[0], ((2, 1), 9));
+
+    let miner = world
+        .spawn((
+            Position(0, 0),
+            Miner,
+            MiningSkill(u8::MAX),
+            AssignedJob::default(),
+        ))
+        .id();
+    let mut assign = Schedule::default();
+    assign.add_systems(jobs::job_assignment_system);
+    assign.run(&mut world);
+    let id = world.get::<AssignedJob>(miner).unwrap().0.unwrap();
+    let taken = world.resource::<ActiveJobs>().jobs[&id].kind.clone();
+    assert_eq!(taken, JobKind::Mine { x: 2, y: 1 });
+}
+
+#[test]
+fn designation_priority_survives_save_and_load() {
+    let mut world = quarry();
+    for command in presets(r#"{ "rush": { "type": "designation", "priority": 3 } }"#)
+        .apply("rush", (2, 1), (2, 1))
+        .unwrap()
+    {
+        apply_world_command(&mut world, &command);
+    }
+    let save = save_world(&mut world);
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let mut q = loaded.query_filtered::<&DesignationPriority, With<MineDesignation>>();
+    assert_eq!(
+        q.iter(&loaded).copied().collect::<Vec<_>>(),
+        [DesignationPriority(3)]
+    );
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn station(world: &mut World, kind: StationKind, x: i32, y: i32) -> Entity {
+    world
+        .spawn((Workshop, Station::new(kind), Position(x, y)))
+        .id()
+}
+
+fn item(world: &mut World, item_type: ItemType, x: i32, y: i32) {
+    world.spawn((Item { item_type }, Carriable, Position(x, y)));
+}
+
+fn crew(world: &mut World) {
+    world.spawn((Position(9, 5), Miner, AssignedJob::default()));
+    world.spawn((
+        Position(2, 2),
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+    ));
+}
+
+fn count(world: &mut World, item_type: ItemType) -> usize {
+    world
+        .query::<&Item>()
+        .iter(world)
+        .filter(|i| i.item_type == item_type)
+        .count()
+}
+
+#[test]
+fn recipes_charge_at_least_their_station_minimum() {
+    let book = RecipeBook::default();
+    let smelt = book.get("smelt-iron").unwrap();
+    assert_eq!(smelt.station, StationKind::Smelter);
+    assert_eq!(smelt.fuel_cost(), 1);
+    // A fuel-free recipe still burns the smelter's minimum
+    let cold = Recipe {
+        fuel: 0,
+        ..smelt.clone()
+    };
+    assert_eq!(cold.fuel_cost(), 1);
+    assert_eq!(book.get("forge-weapon").unwrap().fuel_cost(), 0);
+
+    let mut smelter = Station::new(StationKind::Smelter);
+    assert_eq!(
+        smelter.queue(book.get("forge-weapon").unwrap()),
+        Err(ProductionError::WrongStation {
+            recipe: "forge-weapon".into(),
+            needs: "workshop",
+            station: "smelter",
+        })
+    );
+    assert!(smelter.queue(smelt).is_ok());
+    assert_eq!(smelter.orders, ["smelt-iron"]);
+
+    let json = r#"{"name":"melt","station":"smelter","inputs":[{"ingredient":{"tagged":"ore"},"count":2}],"output":"Bar","work_ticks":3}"#;
+    let parsed: Recipe = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed.fuel, 0);
+    assert_eq!(parsed.fuel_cost(), 1);
+}
+
+#[test]
+fn smelter_burns_fuel_per_craft() {
+    let mut world = world();
+    let smelter = station(&mut world, StationKind::Smelter, 10, 5);
+    for x in 3..5 {
+        item(&mut world, ItemType::Ore, x, 3);
+    }
+    item(&mut world, ItemType::Coal, 3, 8);
+    item(&mut world, ItemType::Wood, 4, 8);
+    item(&mut world, ItemType::Wood, 5, 8);
+    crew(&mut world);
+    queue_order(&mut world, smelter, "smelt-iron").unwrap();
+    queue_order(&mut world, smelter, "smelt-iron").unwrap();
+    assert_eq!(
+        queue_order(&mut world, smelter, "smelt-gold"),
+        Err(ProductionError::UnknownRecipe("smelt-gold".into()))
+    );
+
+    let mut schedule = build_default_schedule();
+    for _ in 0..300 {
+        schedule.run(&mut world);
+    }
+    let s = world.get::<Station>(smelter).unwrap();
+    assert!(s.orders.is_empty(), "orders left: {:?}", s.orders);
+    assert_eq!(s.crafts, 2);
+    assert_eq!(s.fuel_used, 2);
+    // Every ore went into a bar; the third fuel unit is the idle reserve
+    assert_eq!(count(&mut world, ItemType::Bar), 2);
+    assert_eq!(count(&mut world, ItemType::Ore), 0);
+    let s = world.get::<Station>(smelter).unwrap();
+    assert_eq!(s.fuel(&TagRegistry::default()), 1);
+}
+
+#[test]
+fn fuel_stations_post_fuel_hauls_up_to_the_reserve() {
+    let mut world = world();
+    world.insert_resource(FuelConfig { reserve: 3 });
+    let smelter = station(&mut world, StationKind::Smelter, 10, 5);
+    station(&mut world, StationKind::Workshop, 15, 5);
+    for x in 2..7 {
+        item(&mut world, ItemType::Coal, x, 9);
+    }
+    item(&mut world, ItemType::Stone, 1, 1);
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(station_supply_system);
+    schedule.run(&mut world);
+
+    // No orders, but the smelter stocks its reserve; the workshop burns nothing
+    let hauls: Vec<((i32, i32), (i32, i32))> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|j| match j.kind {
+            JobKind::Haul { from, to } => Some((from, to)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        hauls,
+        vec![((6, 9), (10, 5)), ((5, 9), (10, 5)), ((4, 9), (10, 5))]
+    );
+    assert_eq!(world.get::<Station>(smelter).unwrap().incoming().count(), 3);
+
+    // Running again posts nothing new while the hauls are outstanding
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<JobBoard>().0.len(), 3);
+}
+
+#[test]
+fn workshop_recipes_need_no_fuel() {
+    let mut world = world();
+    let workshop = station(&mut world, StationKind::Workshop, 10, 5);
+    item(&mut world, ItemType::Bar, 3, 3);
+    item(&mut world, ItemType::Bar, 4, 3);
+    crew(&mut world);
+    queue_order(&mut world, workshop, "forge-weapon").unwrap();
+
+    let mut schedule = build_default_schedule();
+    for _ in 0..200 {
+        schedule.run(&mut world);
+    }
+    let s = world.get::<Station>(workshop).unwrap();
+    assert_eq!((s.crafts, s.fuel_used), (1, 0));
+    assert!(s.stock.is_empty());
+    let weapons: Vec<bool> = world
+        .query::<(&Item, Has<Gear>, Has<Quality>)>()
+        .iter(&world)
+        .filter(|(i, ..)| i.item_type == ItemType::Weapon)
+        .map(|(_, gear, quality)| gear && quality)
+        .collect();
+    assert_eq!(weapons, vec![true]);
+}
+
+#[test]
+fn stations_survive_save_and_load() {
+    let mut world = world();
+    let smelter = station(&mut world, StationKind::Smelter, 10, 5);
+    queue_order(&mut world, smelter, "smelt-iron").unwrap();
+    {
+        let mut s = world.get_mut::<Station>(smelter).unwrap();
+        s.stock.insert(ItemType::Coal, 2);
+        s.crafts = 4;
+        s.fuel_used = 5;
+    }
+    // A workshop from a save written before stations existed
+    world.spawn((Workshop, Position(3, 3)));
+    let mut save = save_world(&mut world);
+    for e in save.entities.iter_mut().filter(|e| e.pos == Some((3, 3))) {
+        e.station = None;
+    }
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let mut stations: Vec<(Position, Station)> = loaded
+        .query::<(&Position, &Station)>()
+        .iter(&loaded)
+        .map(|(p, s)| (*p, s.clone()))
+        .collect();
+    stations.sort_by_key(|(p, _)| (p.0, p.1));
+    assert_eq!(stations.len(), 2);
+    assert_eq!(stations[0].1, Station::new(StationKind::Workshop));
+    let s = &stations[1].1;
+    assert_eq!(s.kind, StationKind::Smelter);
+    assert_eq!(s.orders, ["smelt-iron"]);
+    assert_eq!(s.stock.get(&ItemType::Coal), Some(&2));
+    assert_eq!((s.crafts, s.fuel_used), (4, 5));
+}
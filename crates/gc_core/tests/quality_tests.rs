@@ -0,0 +1,71 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn distribution(skill: u8) -> [usize; 5] {
+    let mut rng = StdRng::seed_from_u64(77);
+    let mut counts = [0; 5];
+    for _ in 0..1000 {
+        let q = roll_quality(CraftingSkill(skill), &mut rng);
+        counts[Quality::ALL.iter().position(|&x| x == q).unwrap()] += 1;
+    }
+    counts
+}
+
+#[test]
+fn skill_shifts_quality_upwards() {
+    let novice = distribution(0);
+    let master = distribution(MAX_SKILL);
+    // Novices never reach masterwork; masters never make poor goods
+    assert_eq!(novice[4], 0);
+    assert_eq!(master[0], 0);
+    assert!(master[4] > novice[4]);
+    assert!(novice[0] > 0);
+}
+
+#[test]
+fn crafting_is_deterministic_per_seed() {
+    let craft = |seed| {
+        let mut world = World::new();
+        world.insert_resource(DeterministicRng::new(seed));
+        let smith = world.spawn(CraftingSkill(10)).id();
+        (0..8)
+            .map(|i| {
+                let item = craft_item(&mut world, smith, ItemType::Stone, (i, 0));
+                *world.get::<Quality>(item).unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(craft(5), craft(5));
+}
+
+#[test]
+fn value_scales_with_quality_and_shows_in_views() {
+    assert_eq!(item_value(ItemType::Stone, None), 2);
+    assert_eq!(item_value(ItemType::Stone, Some(Quality::Masterwork)), 8);
+    assert_eq!(item_value(ItemType::Stone, Some(Quality::Poor)), 1);
+
+    let mut world = World::new();
+    world.spawn((Item::stone(), Quality::Fine, Position(1, 1)));
+    let view = snapshot_world(&mut world);
+    let item = &view.entities[0];
+    assert_eq!(item.quality, Some(Quality::Fine));
+    assert_eq!(item.value, Some(3));
+}
+
+#[test]
+fn quality_survives_save_and_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(4, 4));
+    world.spawn((Item::stone(), Quality::Superior, Position(1, 1), Carriable));
+    let json = encode_json(&save_world(&mut world)).unwrap();
+
+    let mut loaded = World::new();
+    load_world(decode_json(&json).unwrap(), &mut loaded);
+    let mut q = loaded.query::<&Quality>();
+    assert_eq!(
+        q.iter(&loaded).copied().collect::<Vec<_>>(),
+        vec![Quality::Superior]
+    );
+}
@@ -0,0 +1,104 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+fn world() -> World {
+    let mut world = World::new();
+    let mut map = GameMap::new(6, 4);
+    map.set_tile(0, 0, TileKind::Wall).unwrap();
+    map.set_tile(5, 3, TileKind::Water).unwrap();
+    world.insert_resource(map);
+    world
+}
+
+fn chars(draw: &DrawList, x: i32, y: i32) -> String {
+    draw.at(x, y).iter().map(|g| g.char).collect()
+}
+
+#[test]
+fn glyphs_on_a_tile_are_sorted_by_layer() {
+    let mut world = world();
+    world.spawn(StockpileBundle::new(1, 1, 3, 2));
+    world.spawn((Position(2, 1), Miner));
+    world.spawn((
+        Position(2, 1),
+        Item {
+            item_type: ItemType::Stone,
+        },
+    ));
+    world.spawn(DesignationBundle {
+        pos: Position(2, 1),
+        ..Default::default()
+    });
+
+    let draw = extract_draw_list(&snapshot_world(&mut world));
+    assert_eq!(chars(&draw, 2, 1), ".=xom");
+    let layers: Vec<_> = draw.at(2, 1).iter().map(|g| g.layer).collect();
+    assert!(layers.windows(2).all(|w| w[0] <= w[1]), "{layers:?}");
+    assert_eq!(chars(&draw, 0, 0), "#");
+    assert!(draw.at(6, 0).is_empty());
+
+    // Renderers can leave layers out
+    let top = draw.top(2, 1, |l| l <= RenderLayer::Designation).unwrap();
+    assert_eq!((top.char, top.color), ('x', GlyphColor::Designation));
+    assert_eq!(draw.to_ascii(), "#.....\n.=m=..\n.===..\n.....~\n");
+}
+
+#[test]
+fn explicit_glyphs_override_roles_and_are_saved() {
+    let mut world = world();
+    let glyph = RenderGlyph::new('!', RenderLayer::Unit, GlyphColor::Lava);
+    world.spawn((Position(1, 1), Miner, glyph));
+    world.spawn((Position(1, 1), Carrier));
+    // Entities without a role or glyph draw nothing
+    world.spawn(Position(4, 1));
+
+    let draw = extract_draw_list(&snapshot_world(&mut world));
+    // Same layer: the later entity draws on top
+    assert_eq!(chars(&draw, 1, 1), ".!c");
+    assert_eq!(chars(&draw, 4, 1), ".");
+
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let glyphs: Vec<RenderGlyph> = loaded
+        .query::<&RenderGlyph>()
+        .iter(&loaded)
+        .copied()
+        .collect();
+    assert_eq!(glyphs, vec![glyph]);
+}
+
+#[test]
+fn fire_and_concealment_are_part_of_the_draw_list() {
+    let mut view = snapshot_world(&mut world());
+    view.burning.push((3, 3));
+    view.concealed.push((1, 1));
+    view.entities.push(EntityView {
+        id: 1,
+        name: None,
+        pos: Some((1, 1)),
+        roles: vec!["miner".into()],
+        item_type: None,
+        quality: None,
+        value: None,
+        carrying: None,
+        assigned_job: None,
+        faction: None,
+        health: None,
+        zone: None,
+        zone_kind: None,
+        accepts_tags: None,
+        material: None,
+        designation: None,
+        issued_by: None,
+        stored: None,
+        fill_limit: None,
+        glyph: None,
+        history: Vec::new(),
+    });
+
+    let draw = extract_draw_list(&view);
+    assert_eq!(draw.at(3, 3).last(), Some(&FIRE_GLYPH));
+    // Whatever hides in a concealed cavern is drawn as rock
+    assert_eq!(draw.at(1, 1), &[tile_glyph(TileKind::Wall)]);
+}
@@ -0,0 +1,84 @@
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use gc_core::rpc::{
+    dispatch, parse_request, tick_notification, RpcAction, RpcResponse, INVALID_PARAMS,
+    METHOD_NOT_FOUND, PARSE_ERROR,
+};
+use serde_json::json;
+
+fn view() -> WorldView {
+    let mut world = build_standard_world(
+        16,
+        12,
+        3,
+        WorldOptions {
+            populate_demo_scene: true,
+            tick_ms: 100,
+            ..Default::default()
+        },
+    );
+    snapshot_world(&mut world)
+}
+
+fn action(line: &str) -> RpcAction {
+    dispatch(&view(), &parse_request(line).unwrap()).unwrap()
+}
+
+#[test]
+fn mutations_become_sim_commands() {
+    assert_eq!(
+        action(r#"{"jsonrpc":"2.0","id":1,"method":"step","params":{"ticks":5}}"#),
+        RpcAction::Command(SimCommand::Step(5), json!({ "queued": 5 }))
+    );
+    assert_eq!(
+        action(r#"{"id":2,"method":"step"}"#),
+        RpcAction::Command(SimCommand::Step(1), json!({ "queued": 1 }))
+    );
+    assert_eq!(
+        action(r#"{"id":3,"method":"designate","params":{"x":2,"y":3}}"#),
+        RpcAction::Command(
+            SimCommand::World(WorldCommand::Designate { x: 2, y: 3 }),
+            json!({ "queued": true })
+        )
+    );
+    assert_eq!(
+        action(r#"{"id":4,"method":"subscribe"}"#),
+        RpcAction::Subscribe(true)
+    );
+}
+
+#[test]
+fn queries_read_the_snapshot() {
+    let v = view();
+    let RpcAction::Reply(tile) = action(r#"{"id":1,"method":"get_tile","params":{"x":0,"y":0}}"#)
+    else {
+        panic!("expected reply");
+    };
+    assert_eq!(tile, json!(v.tile(0, 0)));
+
+    let RpcAction::Reply(all) = action(r#"{"id":2,"method":"get_entities"}"#) else {
+        panic!("expected reply");
+    };
+    assert_eq!(all.as_array().unwrap().len(), v.entities.len());
+}
+
+#[test]
+fn errors_use_json_rpc_codes() {
+    assert_eq!(parse_request("{not json").unwrap_err().code, PARSE_ERROR);
+    let v = view();
+    let unknown = parse_request(r#"{"id":1,"method":"explode"}"#).unwrap();
+    assert_eq!(dispatch(&v, &unknown).unwrap_err().code, METHOD_NOT_FOUND);
+    let oob = parse_request(r#"{"id":1,"method":"designate","params":{"x":99,"y":0}}"#).unwrap();
+    assert_eq!(dispatch(&v, &oob).unwrap_err().code, INVALID_PARAMS);
+    let half = parse_request(r#"{"id":1,"method":"get_entities","params":{"x":1}}"#).unwrap();
+    assert_eq!(dispatch(&v, &half).unwrap_err().code, INVALID_PARAMS);
+}
+
+#[test]
+fn responses_and_notifications_are_json_rpc_2() {
+    let ok = serde_json::to_value(RpcResponse::ok(json!(7), json!({"a": 1}))).unwrap();
+    assert_eq!(ok, json!({"jsonrpc": "2.0", "id": 7, "result": {"a": 1}}));
+    let note = tick_notification(&view());
+    assert_eq!(note["method"], "tick");
+    assert_eq!(note["params"]["tick"], 0);
+}
@@ -0,0 +1,138 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::lockstep::world_hash;
+use gc_core::prelude::*;
+use gc_core::save::{
+    backup_path, codec_by_name, codec_for_path, read_save_file, write_atomic, write_save_file,
+    write_save_file_with, SaveWriteOptions, CODECS,
+};
+
+/// A world exercising most save fields after some simulation
+fn sample_save() -> SaveGame {
+    let mut world = build_standard_world(
+        24,
+        16,
+        7,
+        WorldOptions {
+            populate_demo_scene: true,
+            ..Default::default()
+        },
+    );
+    world.spawn(StockpileBundle::new(1, 1, 2, 2).with_priority(4));
+    world
+        .resource_mut::<gc_core::fov::Explored>()
+        .tiles
+        .extend([(0, 0), (3, 1)]);
+    let mut schedule = build_default_schedule();
+    for _ in 0..15 {
+        schedule.run(&mut world);
+    }
+    save_world(&mut world)
+}
+
+fn loaded_hash(save: SaveGame) -> u64 {
+    let mut world = World::new();
+    load_world(save, &mut world);
+    world_hash(&mut world)
+}
+
+#[test]
+fn every_codec_round_trips_to_the_same_world() {
+    let save = sample_save();
+    let golden_json = encode_json(&save).unwrap();
+    let golden_hash = loaded_hash(save.clone());
+    for codec in CODECS {
+        let bytes = codec.encode(&save).unwrap();
+        let decoded = codec.decode(&bytes).unwrap();
+        assert_eq!(
+            encode_json(&decoded).unwrap(),
+            golden_json,
+            "{} changed the save",
+            codec.name()
+        );
+        assert_eq!(
+            loaded_hash(decoded),
+            golden_hash,
+            "{} loads a different world",
+            codec.name()
+        );
+    }
+}
+
+#[test]
+fn codecs_are_found_by_name_and_extension() {
+    for codec in CODECS {
+        assert_eq!(codec_by_name(codec.name()).unwrap().name(), codec.name());
+        let path = std::path::PathBuf::from(format!("world.{}", codec.extension().to_uppercase()));
+        assert_eq!(codec_for_path(&path).unwrap().name(), codec.name());
+    }
+    assert!(codec_by_name("yaml").is_none());
+    assert!(codec_for_path(std::path::Path::new("world.sav")).is_none());
+}
+
+#[test]
+fn save_files_pick_their_codec_from_extension_or_content() {
+    let dir = std::env::temp_dir().join(format!("gc-codec-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let save = sample_save();
+    let golden = encode_json(&save).unwrap();
+    for codec in CODECS {
+        let path = dir.join(format!("w.{}", codec.extension()));
+        write_save_file(&path, &save).unwrap();
+        assert!(codec.sniff(&std::fs::read(&path).unwrap()));
+        // Same bytes under an unhelpful name are recognized by content
+        let renamed = dir.join(format!("{}.sav", codec.name()));
+        std::fs::rename(&path, &renamed).unwrap();
+        let back = read_save_file(&renamed).unwrap();
+        assert_eq!(encode_json(&back).unwrap(), golden, "{}", codec.name());
+    }
+    let junk = dir.join("junk.sav");
+    std::fs::write(&junk, b"not a save").unwrap();
+    assert!(matches!(
+        read_save_file(&junk),
+        Err(gc_core::save::CodecError::UnknownFormat(_))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Names in `dir`, sorted
+fn listing(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn save_files_are_replaced_atomically_with_an_optional_backup() {
+    let dir = std::env::temp_dir().join(format!("gc-atomic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("camp.json");
+    let first = sample_save();
+    write_save_file(&path, &first).unwrap();
+    assert_eq!(listing(&dir), vec!["camp.json"]);
+
+    let mut second = first.clone();
+    second.ticks += 100;
+    write_save_file_with(&path, &second, SaveWriteOptions { backup: true }).unwrap();
+    assert_eq!(listing(&dir), vec!["camp.json", "camp.json.bak"]);
+    assert_eq!(backup_path(&path), dir.join("camp.json.bak"));
+    assert_eq!(read_save_file(&path).unwrap().ticks, second.ticks);
+    let backup = std::fs::read(backup_path(&path)).unwrap();
+    assert_eq!(backup, encode_json(&first).unwrap().into_bytes());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_writes_leave_the_target_and_no_temp_file() {
+    let dir = std::env::temp_dir().join(format!("gc-atomic-fail-{}", std::process::id()));
+    // A directory where the save should go makes the final rename fail
+    let target = dir.join("camp.json");
+    std::fs::create_dir_all(target.join("inside")).unwrap();
+    assert!(write_atomic(&target, b"{}", true).is_err());
+    assert_eq!(listing(&dir), vec!["camp.json"]);
+    assert_eq!(listing(&target), vec!["inside"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,89 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use gc_core::save::{load_world_checked, IntegrityError, CODECS};
+
+/// A demo world saved mid-simulation
+fn sample_save() -> SaveGame {
+    let mut world = build_standard_world(
+        24,
+        16,
+        7,
+        WorldOptions {
+            populate_demo_scene: true,
+            ..Default::default()
+        },
+    );
+    world.spawn(StockpileBundle::new(1, 1, 2, 2).with_priority(4));
+    let mut schedule = build_default_schedule();
+    for _ in 0..60 {
+        schedule.run(&mut world);
+    }
+    save_world(&mut world)
+}
+
+#[test]
+fn saves_record_a_hash_that_survives_every_codec() {
+    let save = sample_save();
+    let hash = save.world_hash.expect("saves are hashed");
+    assert_eq!(hash, save.content_hash());
+    for codec in CODECS {
+        let decoded = codec.decode(&codec.encode(&save).unwrap()).unwrap();
+        let mut world = World::new();
+        assert_eq!(
+            load_world_checked(decoded, &mut world),
+            Ok(()),
+            "{}",
+            codec.name()
+        );
+    }
+}
+
+#[test]
+fn edited_saves_are_caught_but_still_load() {
+    let mut save = sample_save();
+    let recorded = save.world_hash.unwrap();
+    save.tiles[0] = TileKind::Lava;
+    let mut world = World::new();
+    let err = load_world_checked(save.clone(), &mut world).unwrap_err();
+    assert_eq!(
+        err,
+        IntegrityError::Corrupted {
+            recorded,
+            actual: save.content_hash(),
+        }
+    );
+    assert!(err.to_string().contains("corrupted or was edited"));
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(0, 0),
+        Some(TileKind::Lava)
+    );
+}
+
+#[test]
+fn saves_that_do_not_load_back_are_caught() {
+    let mut save = sample_save();
+    // A material layer of the wrong size is dropped on load
+    save.materials = vec![Material::Soil; 3];
+    save.world_hash = Some(save.content_hash());
+    assert_eq!(save.verify(), Ok(()));
+    let mut world = World::new();
+    assert!(matches!(
+        load_world_checked(save, &mut world),
+        Err(IntegrityError::NotReconstructed { .. })
+    ));
+}
+
+#[test]
+fn saves_without_a_hash_are_not_checked() {
+    let mut save = sample_save();
+    save.world_hash = None;
+    save.ticks += 1;
+    let json = encode_json(&save).unwrap();
+    assert!(!json.contains("world_hash"));
+    let mut world = World::new();
+    assert_eq!(
+        load_world_checked(decode_json(&json).unwrap(), &mut world),
+        Ok(())
+    );
+}
@@ -0,0 +1,80 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+#[derive(Resource, Default)]
+struct Counter(u32);
+
+fn bump(mut counter: ResMut<Counter>) {
+    counter.0 += 1;
+}
+
+fn reset(mut counter: ResMut<Counter>) {
+    counter.0 = 0;
+}
+
+#[test]
+fn default_schedule_has_no_fatal_issues_on_a_standard_world() {
+    let mut world = build_standard_world(16, 16, 1, WorldOptions::default());
+    let mut schedule = build_default_schedule();
+    let fatal: Vec<_> = validate_schedule(&mut schedule, &mut world)
+        .into_iter()
+        .filter(ScheduleIssue::is_fatal)
+        .collect();
+    assert!(fatal.is_empty(), "{fatal:?}");
+    // Still runnable after validation
+    schedule.run(&mut world);
+}
+
+#[test]
+fn duplicate_systems_are_reported() {
+    let mut world = World::new();
+    world.insert_resource(Counter::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems((bump, bump.after(reset), reset));
+    let issues = validate_schedule(&mut schedule, &mut world);
+    assert!(issues.iter().any(|i| matches!(
+        i,
+        ScheduleIssue::DuplicateSystem { system, count: 2 } if system.ends_with("bump")
+    )));
+}
+
+#[test]
+fn missing_resources_are_reported_and_fatal() {
+    let mut world = World::new();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(bump);
+    let issues = validate_schedule(&mut schedule, &mut world);
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].is_fatal());
+    assert!(matches!(
+        &issues[0],
+        ScheduleIssue::MissingResource { resource, .. } if resource.ends_with("Counter")
+    ));
+}
+
+#[test]
+fn unordered_conflicting_systems_are_reported() {
+    let mut world = World::new();
+    world.insert_resource(Counter::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems((bump, reset));
+    let issues = validate_schedule(&mut schedule, &mut world);
+    assert_eq!(issues.len(), 1);
+    assert!(!issues[0].is_fatal());
+    assert!(issues[0].to_string().contains("Counter"));
+
+    let mut ordered = Schedule::default();
+    ordered.add_systems((bump, reset).chain());
+    assert!(validate_schedule(&mut ordered, &mut world).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "invalid schedule")]
+#[cfg(debug_assertions)]
+fn debug_validation_fails_fast() {
+    let mut world = World::new();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(bump);
+    debug_validate_schedule(&mut schedule, &mut world);
+}
@@ -0,0 +1,153 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{
+    build_default_schedule, build_standard_world, fast_forward, WorldOptions,
+};
+use gc_core::path::{path_cache_system, PathService};
+use gc_core::prelude::*;
+
+/// A stone behind a wall with one gap at the bottom, a carrier and a
+/// stockpile on the near side
+fn walled_haul(mode: MovementMode) -> (World, Entity) {
+    let mut world = build_standard_world(12, 7, 5, WorldOptions::default());
+    world.insert_resource(MovementConfig { mode });
+    let mut map = world.resource_mut::<GameMap>();
+    for y in 0..7 {
+        for x in 0..12 {
+            let wall = x == 5 && y < 6;
+            map.set_tile(
+                x,
+                y,
+                if wall {
+                    TileKind::Wall
+                } else {
+                    TileKind::Floor
+                },
+            )
+            .unwrap();
+        }
+    }
+    world.spawn((
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(2, 1),
+    ));
+    world.spawn(StockpileBundle::new(0, 0, 1, 1));
+    let stone = world
+        .spawn((
+            Item {
+                item_type: ItemType::Stone,
+            },
+            Position(8, 1),
+            Carriable,
+        ))
+        .id();
+    (world, stone)
+}
+
+/// Carrier positions, one per tick, until the stone is stockpiled
+fn haul_route(world: &mut World, stone: Entity) -> Vec<(i32, i32)> {
+    let mut schedule = build_default_schedule();
+    let mut route = Vec::new();
+    let done = fast_forward(world, &mut schedule, 60, |w| {
+        let pos = w
+            .query_filtered::<&Position, With<Carrier>>()
+            .single(w)
+            .to_owned();
+        route.push((pos.0, pos.1));
+        w.get::<Position>(stone)
+            .is_some_and(|p| p.0 <= 1 && p.1 <= 1)
+    });
+    assert!(done.is_some(), "the stone reaches the stockpile");
+    route
+}
+
+#[test]
+fn standard_worlds_share_one_path_service() {
+    let world = build_standard_world(16, 16, 1, WorldOptions::default());
+    assert_eq!(world.resource::<PathService>().stats(), (0, 0));
+}
+
+#[test]
+fn walking_carriers_route_around_walls_through_the_shared_cache() {
+    let (mut world, stone) = walled_haul(MovementMode::Walk);
+    let route = haul_route(&mut world, stone);
+    let map = world.resource::<GameMap>();
+    assert!(route
+        .iter()
+        .all(|&(x, y)| map.get_tile(x, y) == Some(TileKind::Floor)));
+    assert!(route.contains(&(5, 6)), "through the gap");
+    assert!(route
+        .windows(2)
+        .all(|w| (w[0].0 - w[1].0).abs() <= 1 && (w[0].1 - w[1].1).abs() <= 1));
+
+    // One search per leg; every later step comes from the cache
+    let (hits, misses) = world.resource::<PathService>().stats();
+    assert_eq!(misses, 2);
+    assert!(hits > misses);
+    assert_eq!(
+        snapshot_world(&mut world).metrics.path_cache,
+        (hits, misses)
+    );
+}
+
+#[test]
+fn stepping_and_unrouted_walking_stop_at_walls() {
+    for (mode, routed) in [(MovementMode::Step, true), (MovementMode::Walk, false)] {
+        let (mut world, _) = walled_haul(mode);
+        if !routed {
+            world.remove_resource::<PathService>();
+        }
+        let mut schedule = build_default_schedule();
+        for _ in 0..20 {
+            schedule.run(&mut world);
+        }
+        let pos = *world
+            .query_filtered::<&Position, With<Carrier>>()
+            .single(&world);
+        assert_eq!(pos, Position(4, 1), "{mode:?} stopped at the wall");
+    }
+}
+
+#[test]
+fn walkers_with_no_path_wait_instead_of_crossing_walls() {
+    let (mut world, _) = walled_haul(MovementMode::Walk);
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(5, 6, TileKind::Wall)
+        .unwrap();
+    let mut schedule = build_default_schedule();
+    for _ in 0..20 {
+        schedule.run(&mut world);
+        let pos = *world
+            .query_filtered::<&Position, With<Carrier>>()
+            .single(&world);
+        assert_eq!(pos, Position(2, 1));
+    }
+}
+
+#[test]
+fn replacing_the_map_clears_the_shared_cache() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(10, 3));
+    world.insert_resource(PathService::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems(path_cache_system);
+    schedule.run(&mut world);
+    world.resource_scope(|world, mut paths: Mut<PathService>| {
+        let map = world.resource::<GameMap>();
+        assert_eq!(paths.next_step(map, (0, 1), (9, 1)), Some((1, 1)));
+    });
+
+    // Same size and revision, but sealed across the middle
+    let mut sealed = GameMap::new(10, 3);
+    for y in 0..3 {
+        sealed.tiles[(y * 10 + 5) as usize] = TileKind::Wall;
+    }
+    world.insert_resource(sealed);
+    schedule.run(&mut world);
+    world.resource_scope(|world, mut paths: Mut<PathService>| {
+        let map = world.resource::<GameMap>();
+        assert_eq!(paths.next_step(map, (0, 1), (9, 1)), None);
+    });
+}
@@ -0,0 +1,184 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn demo_world() -> World {
+    build_standard_world(
+        24,
+        16,
+        7,
+        WorldOptions {
+            populate_demo_scene: true,
+            tick_ms: 100,
+            ..Default::default()
+        },
+    )
+}
+
+fn paused() -> SimThreadConfig {
+    SimThreadConfig {
+        start_paused: true,
+        ..Default::default()
+    }
+}
+
+/// Wait for the first snapshot at or after `tick`
+fn snapshot_at(handle: &SimHandle, tick: u64) -> Arc<WorldView> {
+    loop {
+        match handle.recv_timeout(Duration::from_secs(5)) {
+            Some(SimEvent::Snapshot(v)) if v.tick >= tick => return v,
+            Some(_) => {}
+            None => panic!("no snapshot for tick {}", tick),
+        }
+    }
+}
+
+#[test]
+fn publishes_initial_snapshot_and_steps_while_paused() {
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    assert_eq!(snapshot_at(&handle, 0).tick, 0);
+
+    handle.send(SimCommand::Step(3)).unwrap();
+    assert_eq!(snapshot_at(&handle, 3).tick, 3);
+
+    let (world, _) = handle.shutdown().unwrap();
+    assert_eq!(world.resource::<Time>().ticks, 3);
+}
+
+#[test]
+fn commands_apply_at_tick_boundaries_like_direct_application() {
+    // Threaded: designate then step, both before the same boundary
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    snapshot_at(&handle, 0);
+    handle
+        .send(SimCommand::World(WorldCommand::Designate { x: 5, y: 5 }))
+        .unwrap();
+    handle.send(SimCommand::Step(4)).unwrap();
+    let threaded = snapshot_at(&handle, 4);
+    drop(handle);
+
+    // Direct: same command order on the caller's thread
+    let mut world = demo_world();
+    let mut schedule = build_default_schedule();
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::Designate { x: 5, y: 5 }
+    ));
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(*threaded, snapshot_world(&mut world));
+}
+
+#[test]
+fn save_replies_with_current_world() {
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    handle.send(SimCommand::Step(2)).unwrap();
+    snapshot_at(&handle, 2);
+    handle.send(SimCommand::Save).unwrap();
+    let save = loop {
+        match handle.recv_timeout(Duration::from_secs(5)) {
+            Some(SimEvent::Saved(save)) => break save,
+            Some(_) => {}
+            None => panic!("no save reply"),
+        }
+    };
+    assert_eq!(save.ticks, 2);
+    assert_eq!((save.width, save.height), (24, 16));
+}
+
+#[test]
+fn estimate_replies_without_touching_the_world() {
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    snapshot_at(&handle, 0);
+    let bounds = ZoneBounds::new(0, 0, 23, 15);
+    handle.send(SimCommand::Estimate(bounds.clone())).unwrap();
+    let (replied, estimate) = loop {
+        match handle.recv_timeout(Duration::from_secs(5)) {
+            Some(SimEvent::Estimate { bounds, estimate }) => break (bounds, estimate),
+            Some(_) => {}
+            None => panic!("no estimate reply"),
+        }
+    };
+    assert_eq!(replied, bounds);
+    let (mut world, _) = handle.shutdown().unwrap();
+    assert_eq!(estimate, estimate_designation(&mut world, &bounds));
+    assert!(estimate.tiles > 0);
+    assert_eq!(snapshot_world(&mut world).metrics.active_designations, 0);
+}
+
+#[test]
+fn pause_and_resume_are_reported() {
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    handle.send(SimCommand::TogglePause).unwrap();
+    let mut seen = None;
+    while let Some(event) = handle.recv_timeout(Duration::from_secs(5)) {
+        if let SimEvent::Paused(p) = event {
+            seen = Some(p);
+            break;
+        }
+    }
+    assert_eq!(seen, Some(false));
+    // Running unpaused eventually advances time on its own
+    assert!(snapshot_at(&handle, 2).tick >= 2);
+}
+
+#[test]
+fn full_command_queue_is_reported_instead_of_blocking() {
+    let mut schedule = build_default_schedule();
+    // A deliberately slow tick keeps the thread busy between boundaries
+    schedule.add_systems(|| std::thread::sleep(Duration::from_millis(50)));
+    let handle = spawn_sim_thread(
+        demo_world(),
+        schedule,
+        SimThreadConfig {
+            tick_interval: Duration::ZERO,
+            command_capacity: 2,
+            ..Default::default()
+        },
+    );
+    let results: Vec<_> = (0..20).map(|_| handle.send(SimCommand::Snapshot)).collect();
+    assert!(results.contains(&Err(SimSendError::Full)));
+    assert!(handle.shutdown().is_some());
+}
+
+#[test]
+fn rejected_world_commands_report_false() {
+    let mut world = demo_world();
+    assert!(!apply_world_command(
+        &mut world,
+        &WorldCommand::Designate { x: 99, y: 0 }
+    ));
+    let json = serde_json::to_string(&WorldCommand::Designate { x: 1, y: 2 }).unwrap();
+    assert_eq!(json, r#"{"type":"designate","x":1,"y":2}"#);
+}
+
+#[test]
+fn subscribers_receive_their_own_filtered_events() {
+    let handle = spawn_sim_thread(demo_world(), build_default_schedule(), paused());
+    snapshot_at(&handle, 0);
+    let snapshots = handle.subscribe(EventFilter::only(&[SimEventKind::Snapshot]));
+    let saves = handle.subscribe(EventFilter::only(&[SimEventKind::Saved]));
+    let everything = handle.subscribe(EventFilter::all());
+    // Never read: a stalled subscriber must not hold the simulation back
+    let _stalled = handle.subscribe(EventFilter::all());
+    drop(everything);
+
+    for _ in 0..10 {
+        handle.send_blocking(SimCommand::Step(1)).unwrap();
+    }
+    // The handle's own queue is unaffected by subscribers
+    assert_eq!(snapshot_at(&handle, 10).tick, 10);
+    handle.send_blocking(SimCommand::Save).unwrap();
+
+    let save = saves.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(matches!(save, SimEvent::Saved(ref s) if s.ticks == 10));
+    let first = snapshots.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(first.kind(), SimEventKind::Snapshot);
+    assert!(snapshots
+        .try_iter()
+        .all(|e| e.kind() == SimEventKind::Snapshot));
+    assert!(saves.try_recv().is_err());
+}
@@ -0,0 +1,155 @@
+use bevy_ecs::prelude::*;
+use gc_core::bench::BenchProfile;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::time::Duration;
+
+fn world() -> World {
+    build_standard_world(10, 8, 3, WorldOptions::default())
+}
+
+fn kinds(violations: &[Violation]) -> Vec<InvariantKind> {
+    violations.iter().map(|v| v.kind).collect()
+}
+
+#[test]
+fn smoke_colony_soaks_clean() {
+    let mut seen = Vec::new();
+    let limits = SoakLimits {
+        duration: Duration::from_secs(600),
+        max_ticks: Some(2_000),
+        check_every: 500,
+    };
+    let report = run_soak(
+        BenchProfile::SMOKE,
+        42,
+        limits,
+        InvariantConfig::default(),
+        || Some(1_000),
+        |v| seen.push(v.clone()),
+    );
+    assert_eq!(seen, vec![]);
+    assert_eq!(
+        (report.ticks, report.checks, report.violations),
+        (2_000, 4, 0)
+    );
+    assert_eq!(report.memory_peak, Some(1_000));
+    assert!(report.metrics.entities > 0);
+}
+
+#[test]
+fn soaks_stop_at_max_ticks_mid_interval() {
+    let limits = SoakLimits {
+        duration: Duration::from_secs(600),
+        max_ticks: Some(120),
+        check_every: 50,
+    };
+    let report = run_soak(
+        BenchProfile::SMOKE,
+        42,
+        limits,
+        InvariantConfig::default(),
+        || None,
+        |_| {},
+    );
+    assert_eq!((report.ticks, report.checks), (120, 3));
+    assert_eq!(report.memory_peak, None);
+}
+
+#[test]
+fn point_checks_catch_broken_state() {
+    let mut world = world();
+    let config = InvariantConfig::default();
+    assert_eq!(check_invariants(&mut world, &config), vec![]);
+
+    world.spawn(Position(10, 2));
+    world.spawn(AssignedJob(Some(JobId(uuid::Uuid::from_u128(7)))));
+    world.spawn(Health { hp: 12, max_hp: 10 });
+    world.resource_mut::<JobBoard>().0.push(Job {
+        id: JobId(uuid::Uuid::from_u128(1)),
+        kind: JobKind::Mine { x: 1, y: 1 },
+        issued_by: IssuedBy::System,
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    });
+    let tight = InvariantConfig {
+        jobs_per_tile: 0,
+        ..config
+    };
+    let violations = check_invariants(&mut world, &tight);
+    assert_eq!(
+        kinds(&violations),
+        vec![
+            InvariantKind::OffMap,
+            InvariantKind::OrphanAssignment,
+            InvariantKind::HealthRange,
+            InvariantKind::JobBound,
+        ]
+    );
+    assert!(violations[0].to_string().starts_with("[tick 0] off_map: "));
+    assert_eq!(violations[3].detail, "1 jobs on a 10x8 map (bound 0)");
+}
+
+#[test]
+fn monitor_flags_steady_growth_once_and_new_faults() {
+    let mut world = world();
+    let mut monitor = SoakMonitor::new(InvariantConfig {
+        growth_window: 3,
+        ..Default::default()
+    });
+    let mut growth = Vec::new();
+    for _ in 0..6 {
+        world.spawn(Item::stone());
+        growth.extend(monitor.check(&mut world, None));
+    }
+    // Entities and items both climb; each is reported once
+    assert_eq!(kinds(&growth), vec![InvariantKind::Growth; 2]);
+    assert!(growth[0]
+        .detail
+        .starts_with("entities rose at each of the last 3 checks"));
+    assert!(growth[1].detail.starts_with("items rose"));
+
+    // A flat check ends the streak, and a new streak is reported again
+    assert_eq!(monitor.check(&mut world, None), vec![]);
+    let mut again = Vec::new();
+    for _ in 0..3 {
+        world.spawn(Item::stone());
+        again.extend(monitor.check(&mut world, None));
+    }
+    assert_eq!(again.len(), 2);
+
+    report_fault(
+        world.get_resource_mut::<SimFaults>().as_deref_mut(),
+        None,
+        FaultKind::MissingItem,
+        "test",
+        None,
+        "gone",
+    );
+    let faults = monitor.check(&mut world, None);
+    assert_eq!(faults[0].kind, InvariantKind::Faults);
+    assert_eq!(faults[0].detail, "1 new simulation faults");
+    assert_eq!(monitor.check(&mut world, None), vec![]);
+}
+
+#[test]
+fn memory_is_measured_from_the_warmed_up_level() {
+    let mut world = world();
+    let mut monitor = SoakMonitor::new(InvariantConfig {
+        growth_window: 2,
+        ..Default::default()
+    });
+    // Warm-up may climb freely
+    assert_eq!(monitor.check(&mut world, Some(50)), vec![]);
+    assert_eq!(monitor.check(&mut world, Some(100)), vec![]);
+    assert_eq!(monitor.check(&mut world, Some(200)), vec![]);
+    let leak = monitor.check(&mut world, Some(201));
+    assert_eq!(kinds(&leak), vec![InvariantKind::Memory]);
+    assert_eq!(leak[0].detail, "201 live heap bytes, up from 100");
+    // The next report needs another doubling
+    assert_eq!(monitor.check(&mut world, Some(400)), vec![]);
+    assert_eq!(monitor.check(&mut world, Some(403)).len(), 1);
+    assert_eq!(monitor.memory_peak(), 403);
+}
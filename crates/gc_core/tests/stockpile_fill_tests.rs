@@ -0,0 +1,198 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+
+/// Open map with a two-tile feeder stockpile at (2..=3, 2) that hauls
+/// prefer, a bulk stockpile far off at (12..=13, 2) and a small one at
+/// (6, 6) near where the stone lands
+fn world() -> World {
+    let mut world = floor_world(20, 12, 5);
+    world.spawn(StockpileBundle::new(2, 2, 3, 2).with_priority(5));
+    world.spawn(StockpileBundle::new(12, 2, 13, 2));
+    world.spawn(StockpileBundle::new(6, 6, 6, 6));
+    world
+}
+
+fn stone(world: &mut World, x: i32, y: i32) {
+    world.spawn((
+        Item {
+            item_type: ItemType::Stone,
+        },
+        Position(x, y),
+        Carriable,
+    ));
+}
+
+fn limit(world: &mut World, at: (i32, i32), percent: u8, overflow: OverflowPolicy) {
+    assert!(apply_world_command(
+        world,
+        &WorldCommand::SetFillLimit {
+            x: at.0,
+            y: at.1,
+            limit: Some(FillLimit::new(percent, overflow)),
+        },
+    ));
+}
+
+/// Destinations of the queued hauls, sorted
+fn haul_targets(world: &World) -> Vec<(i32, i32)> {
+    let mut to: Vec<(i32, i32)> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|j| match j.kind {
+            JobKind::Haul { to, .. } => Some(to),
+            _ => None,
+        })
+        .collect();
+    to.sort();
+    to
+}
+
+/// Three stones at (5, 8) after `setup`, and the hauls one tick posts
+fn haul_three(setup: impl FnOnce(&mut World)) -> (World, Schedule, Vec<(i32, i32)>) {
+    let mut world = world();
+    setup(&mut world);
+    for _ in 0..3 {
+        stone(&mut world, 5, 8);
+    }
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let targets = haul_targets(&world);
+    (world, schedule, targets)
+}
+
+#[test]
+fn full_stockpiles_reject_items_to_the_next_with_room() {
+    let (_, _, unlimited) = haul_three(|_| {});
+    assert_eq!(unlimited, vec![(2, 2); 3]);
+
+    let (_, _, targets) = haul_three(|w| limit(w, (2, 2), 50, OverflowPolicy::Reject));
+    assert_eq!(targets, vec![(2, 2), (6, 6), (6, 6)]);
+}
+
+#[test]
+fn spill_goes_to_linked_stockpiles_and_waits_when_they_fill() {
+    let link = |w: &mut World| {
+        limit(w, (2, 2), 50, OverflowPolicy::Spill);
+        assert!(apply_world_command(
+            w,
+            &WorldCommand::LinkStockpiles {
+                from: (3, 2),
+                to: (12, 2),
+            },
+        ));
+    };
+    let (_, _, targets) = haul_three(link);
+    assert_eq!(targets, vec![(2, 2), (12, 2), (12, 2)]);
+
+    let (mut world, mut schedule, targets) = haul_three(|w| {
+        link(w);
+        limit(w, (12, 2), 50, OverflowPolicy::Reject);
+    });
+    assert_eq!(targets, vec![(2, 2), (12, 2)]);
+    schedule.run(&mut world);
+    assert_eq!(haul_targets(&world).len(), 2, "the last stone waits");
+
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::SetFillLimit {
+            x: 12,
+            y: 2,
+            limit: None,
+        },
+    ));
+    schedule.run(&mut world);
+    assert_eq!(haul_targets(&world), vec![(2, 2), (12, 2), (12, 2)]);
+}
+
+#[test]
+fn drop_leaves_overflow_on_the_floor_beside_the_stockpile() {
+    let (_, _, targets) = haul_three(|w| limit(w, (2, 2), 50, OverflowPolicy::Drop));
+    // (4, 3) borders the feeder diagonally and is nearest the stone
+    assert_eq!(targets, vec![(2, 2), (4, 3), (4, 3)]);
+}
+
+#[test]
+fn stored_items_count_toward_the_limit() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    limit(&mut world, (2, 2), 50, OverflowPolicy::Reject);
+    stone(&mut world, 3, 2);
+    schedule.run(&mut world);
+    assert!(haul_targets(&world).is_empty());
+    for _ in 0..3 {
+        stone(&mut world, 5, 8);
+    }
+    schedule.run(&mut world);
+    assert_eq!(haul_targets(&world), vec![(6, 6); 3]);
+
+    let (_, _, targets) = haul_three(|w| limit(w, (2, 2), 0, OverflowPolicy::Reject));
+    assert_eq!(targets, vec![(6, 6); 3]);
+}
+
+#[test]
+fn fill_limits_and_links_survive_saves() {
+    let mut world = world();
+    limit(&mut world, (2, 2), 40, OverflowPolicy::Spill);
+    apply_world_command(
+        &mut world,
+        &WorldCommand::LinkStockpiles {
+            from: (2, 2),
+            to: (6, 6),
+        },
+    );
+    apply_world_command(
+        &mut world,
+        &WorldCommand::LinkStockpiles {
+            from: (2, 2),
+            to: (13, 2),
+        },
+    );
+
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let bounds_of = |w: &World, e: Entity| w.get::<ZoneBounds>(e).cloned().unwrap();
+    let (feeder, limit, give_to) = loaded
+        .query::<(Entity, &FillLimit, &GiveTo)>()
+        .single(&loaded);
+    assert_eq!(*limit, FillLimit::new(40, OverflowPolicy::Spill));
+    assert_eq!(bounds_of(&loaded, feeder), ZoneBounds::new(2, 2, 3, 2));
+    let targets: Vec<ZoneBounds> = give_to.0.iter().map(|&e| bounds_of(&loaded, e)).collect();
+    assert_eq!(
+        targets,
+        vec![ZoneBounds::new(6, 6, 6, 6), ZoneBounds::new(12, 2, 13, 2)]
+    );
+
+    // Unlinking and lifting the limit are commands too
+    assert!(apply_world_command(
+        &mut loaded,
+        &WorldCommand::UnlinkStockpiles { x: 3, y: 2 }
+    ));
+    assert!(loaded.get::<GiveTo>(feeder).is_none());
+    assert!(!apply_world_command(
+        &mut loaded,
+        &WorldCommand::SetFillLimit {
+            x: 9,
+            y: 9,
+            limit: None,
+        },
+    ));
+}
+
+#[test]
+fn overflow_policies_parse_from_their_labels() {
+    for policy in OverflowPolicy::ALL {
+        assert_eq!(policy.label().parse::<OverflowPolicy>(), Ok(policy));
+    }
+    assert_eq!(
+        "flood".parse::<OverflowPolicy>(),
+        Err("unknown overflow policy 'flood'".to_string())
+    );
+    assert_eq!(FillLimit::new(50, OverflowPolicy::Drop).limit(5), 2);
+    assert_eq!(stockpile_capacity(Some(&ZoneBounds::new(2, 2, 3, 4))), 6);
+}
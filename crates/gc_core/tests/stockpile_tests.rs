@@ -159,3 +159,238 @@ fn stockpile_integration_with_ecs() {
     let stockpile = world.get::<Stockpile>(entity).unwrap();
     assert!(stockpile.accepts.is_none());
 }
+
+#[test]
+fn haul_destination_prefers_priority_then_distance_then_entity() {
+    let mut world = World::new();
+    let near = world.spawn(StockpileBundle::new(2, 0, 2, 0)).id();
+    let far_high = world
+        .spawn(StockpileBundle::new(9, 0, 9, 0).with_priority(2))
+        .id();
+    let tied_a = world
+        .spawn(StockpileBundle::new(0, 5, 0, 5).with_priority(2))
+        .id();
+    let tied_b = world
+        .spawn(StockpileBundle::new(0, 5, 0, 5).with_priority(2))
+        .id();
+    let mut q = world.query::<(Entity, &Position, &Stockpile)>();
+
+    // Priority beats distance: (9,0) and (0,5) are both farther than (2,0)
+    let pick = |world: &World, q: &mut QueryState<(Entity, &Position, &Stockpile)>| {
+        select_haul_destination(q.iter(world), (0, 0), ItemType::Stone).map(|(e, _)| e)
+    };
+    assert_eq!(pick(&world, &mut q), Some(tied_a));
+
+    // Equal priority and distance fall back to the lower entity
+    world.despawn(tied_a);
+    assert_eq!(pick(&world, &mut q), Some(tied_b));
+    world.despawn(tied_b);
+    assert_eq!(pick(&world, &mut q), Some(far_high));
+    world.despawn(far_high);
+    assert_eq!(pick(&world, &mut q), Some(near));
+}
+
+#[test]
+fn auto_haul_targets_the_priority_stockpile() {
+    let mut world = World::new();
+    world.insert_resource(JobBoard::default());
+    world.insert_resource(DeterministicRng::new(1));
+    world.spawn(StockpileBundle::new(1, 0, 1, 0));
+    world.spawn(StockpileBundle::new(20, 20, 22, 22).with_priority(5));
+    world.spawn((Item::stone(), Position(0, 0), Carriable));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(auto_haul_system);
+    schedule.run(&mut world);
+
+    let board = world.resource::<JobBoard>();
+    assert_eq!(board.0.len(), 1);
+    assert_eq!(
+        board.0[0].kind,
+        JobKind::Haul {
+            from: (0, 0),
+            to: (21, 21)
+        }
+    );
+}
+
+#[test]
+fn stockpile_priority_survives_save_and_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(8, 8));
+    world.spawn(StockpileBundle::new(1, 1, 2, 2).with_priority(3));
+    world.spawn(StockpileBundle::new(4, 4, 5, 5));
+    let save = save_world(&mut world);
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let mut priorities: Vec<u8> = loaded
+        .query::<&Stockpile>()
+        .iter(&loaded)
+        .map(|s| s.priority)
+        .collect();
+    priorities.sort();
+    assert_eq!(priorities, vec![0, 3]);
+}
+
+#[test]
+fn stockpile_contents_follow_items_in_and_out() {
+    let mut world = World::new();
+    world.insert_resource(StockpileContents::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems(stockpile_contents_system);
+    let pile = world.spawn(StockpileBundle::new(0, 0, 2, 2)).id();
+    let stone = world.spawn((Item::stone(), Position(1, 1))).id();
+    world.spawn((
+        Item {
+            item_type: ItemType::Weapon,
+        },
+        Position(2, 2),
+    ));
+    let outside = world.spawn((Item::stone(), Position(5, 5))).id();
+    schedule.run(&mut world);
+
+    let summary = stockpile_contents(&world, pile);
+    assert_eq!(summary.total, 2);
+    assert_eq!(summary.count(ItemType::Stone), 1);
+    assert_eq!(summary.count(ItemType::Weapon), 1);
+
+    // Picked up, then another dropped in
+    world.entity_mut(stone).remove::<Position>();
+    world.get_mut::<Position>(outside).unwrap().0 = 0;
+    world.get_mut::<Position>(outside).unwrap().1 = 0;
+    schedule.run(&mut world);
+    assert_eq!(stockpile_contents(&world, pile).count(ItemType::Stone), 1);
+
+    world.despawn(outside);
+    schedule.run(&mut world);
+    let summary = stockpile_contents(&world, pile);
+    assert_eq!(summary.count(ItemType::Stone), 0);
+    assert_eq!(summary.total, 1);
+
+    // Growing the stockpile refiles items already lying in the new area
+    world.entity_mut(stone).insert(Position(4, 4));
+    schedule.run(&mut world);
+    assert_eq!(stockpile_contents(&world, pile).total, 1);
+    *world.get_mut::<ZoneBounds>(pile).unwrap() = ZoneBounds::new(0, 0, 4, 4);
+    schedule.run(&mut world);
+    assert_eq!(stockpile_contents(&world, pile).total, 2);
+}
+
+#[test]
+fn hauled_stone_shows_up_in_the_stockpile_summary() {
+    use gc_core::bootstrap::{
+        build_default_schedule, build_standard_world, fast_forward, WorldOptions,
+    };
+
+    let mut world = build_standard_world(12, 8, 3, WorldOptions::default());
+    let mut schedule = build_default_schedule();
+    let pile = world.spawn(StockpileBundle::new(8, 4, 9, 5)).id();
+    world.spawn((
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(1, 1),
+    ));
+    world.spawn((Item::stone(), Carriable, Stone, Position(2, 1)));
+    let done = fast_forward(&mut world, &mut schedule, 50, |w| {
+        stockpile_contents(w, pile).count(ItemType::Stone) == 1
+    });
+    assert!(done.is_some());
+    let view = snapshot_world(&mut world);
+    let stored = view
+        .entities
+        .iter()
+        .find(|e| e.id == pile.to_bits())
+        .and_then(|e| e.stored.clone())
+        .unwrap();
+    assert_eq!(stored.total, 1);
+}
+
+/// Accepted types, priority and bounds of a stockpile
+type PileRow = (Option<Vec<ItemType>>, u8, (i32, i32, i32, i32));
+
+fn stockpiles(world: &mut World) -> Vec<PileRow> {
+    let mut piles: Vec<_> = world
+        .query::<(&Stockpile, &ZoneBounds)>()
+        .iter(world)
+        .map(|(s, b)| {
+            (
+                s.accepts.clone(),
+                s.priority,
+                (b.min_x, b.min_y, b.max_x, b.max_y),
+            )
+        })
+        .collect();
+    piles.sort();
+    piles
+}
+
+#[test]
+fn stockpile_item_filters_survive_save_and_load() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(8, 8));
+    let mut bundle = StockpileBundle::new(1, 1, 2, 2);
+    bundle.stockpile.accepts = Some(vec![ItemType::Stone, ItemType::Ore]);
+    world.spawn(bundle);
+    let mut empty = StockpileBundle::new(4, 4, 5, 5);
+    empty.stockpile.accepts = Some(Vec::new());
+    world.spawn(empty);
+    world.spawn(StockpileBundle::new(6, 6, 7, 7));
+    let save = save_world(&mut world);
+    assert!(encode_json(&save)
+        .unwrap()
+        .contains("\"stockpile_accepts\""));
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    assert_eq!(stockpiles(&mut loaded), stockpiles(&mut world));
+}
+
+#[test]
+fn legacy_accepts_any_stockpiles_are_migrated_on_load() {
+    let legacy = r#"{
+        "width": 3,
+        "height": 3,
+        "tiles": ["Floor", "Floor", "Floor", "Floor", "Floor",
+                  "Floor", "Floor", "Floor", "Floor"],
+        "entities": [
+            {"name": "Stockpile", "pos": [1, 1], "vel": null, "item_type": null,
+             "carriable": false, "zone": [0, 0, 1, 1], "stockpile": {"accepts_any": true}},
+            {"name": "Stockpile", "pos": [2, 2], "vel": null, "item_type": null,
+             "carriable": false, "stockpile": {"accepts_any": false, "priority": 2}}
+        ]
+    }"#;
+    let mut save = decode_json(legacy).unwrap();
+    let mut world = World::new();
+    load_world(save.clone(), &mut world);
+    assert_eq!(
+        stockpiles(&mut world),
+        vec![(None, 0, (0, 0, 1, 1)), (Some(Vec::new()), 2, (2, 2, 2, 2))]
+    );
+
+    // Migrating writes the current shape and is idempotent
+    save.migrate();
+    let json = encode_json(&save).unwrap();
+    assert!(!json.contains("accepts_any"));
+    assert!(!json.contains("\"stockpile\""));
+    save.migrate();
+    assert_eq!(encode_json(&save).unwrap(), json);
+    let mut reloaded = World::new();
+    load_world(save, &mut reloaded);
+    assert_eq!(stockpiles(&mut reloaded), stockpiles(&mut world));
+}
+
+#[test]
+fn stockpile_components_read_both_shapes() {
+    let parse = |json: &str| serde_json::from_str::<Stockpile>(json).unwrap();
+    assert_eq!(parse(r#"{"accepts_any": true}"#).accepts, None);
+    assert_eq!(parse(r#"{"accepts_any": false}"#).accepts, Some(Vec::new()));
+    let current = Stockpile {
+        accepts: Some(vec![ItemType::Wood]),
+        priority: 4,
+    };
+    let json = serde_json::to_string(&current).unwrap();
+    assert_eq!(parse(&json), current);
+    assert_eq!(parse("{}").accepts, None);
+}
@@ -131,7 +131,10 @@ fn confine_to_map_system() {
 fn designation_to_jobs_system_basic() {
     let mut world = World::new();
     world.insert_resource(JobBoard::default());
-    world.insert_resource(designations::DesignationConfig { auto_jobs: true });
+    world.insert_resource(designations::DesignationConfig {
+        auto_jobs: true,
+        ..Default::default()
+    });
     world.insert_resource(systems::DeterministicRng::new(42));
 
     // Create a mine designation
@@ -169,6 +172,11 @@ fn job_assignment_system_basic() {
         job_board.0.push(Job {
             id: job_id,
             kind: JobKind::Mine { x: 10, y: 10 },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
         });
     }
 
@@ -189,7 +197,7 @@ fn job_assignment_system_basic() {
 }
 
 #[test]
-fn mining_execution_system_basic() {
+fn mine_job_execution_system_basic() {
     let mut world = World::new();
     world.insert_resource(GameMap::new(20, 20));
     world.insert_resource(jobs::ActiveJobs::default());
@@ -198,7 +206,7 @@ fn mining_execution_system_basic() {
     // Set up a wall tile to mine
     {
         let mut map = world.get_resource_mut::<GameMap>().unwrap();
-        map.set_tile(10, 10, TileKind::Wall);
+        map.set_tile(10, 10, TileKind::Wall).unwrap();
     }
 
     // Create a miner with an active job
@@ -219,12 +227,23 @@ fn mining_execution_system_basic() {
             Job {
                 id: job_id,
                 kind: JobKind::Mine { x: 10, y: 10 },
+                issued_by: IssuedBy::System,
+                faction: FactionKind::Goblins,
+                seq: 0,
+                priority: 0,
+                to_stockpile: false,
             },
         );
     }
 
     let mut schedule = Schedule::default();
-    schedule.add_systems(systems::mining_execution_system);
+    schedule.add_systems(
+        (
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
 
     // Run system
     schedule.run(&mut world);
@@ -276,6 +295,11 @@ fn hauling_execution_system_basic() {
                     from: (5, 5),
                     to: (10, 10),
                 },
+                issued_by: IssuedBy::System,
+                faction: FactionKind::Goblins,
+                seq: 0,
+                priority: 0,
+                to_stockpile: false,
             },
         );
     }
@@ -325,6 +349,11 @@ fn mining_job_assignment_system() {
         job_board.0.push(Job {
             id: job_id,
             kind: JobKind::Mine { x: 5, y: 5 },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
         });
     }
 
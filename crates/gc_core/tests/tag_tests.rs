@@ -0,0 +1,128 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+use gc_core::systems;
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+#[test]
+fn queries_combine_alternatives_and_negation() {
+    let registry = TagRegistry::default();
+    let query = TagQuery::parse("gear & !armor | gem").unwrap();
+    assert_eq!(
+        registry.matching(&query),
+        vec![ItemType::Weapon, ItemType::Gem]
+    );
+    assert_eq!(query.to_string(), "gear !armor | gem");
+    assert_eq!(TagQuery::parse(&query.to_string()).unwrap(), query);
+
+    assert_eq!(TagQuery::parse("stone |"), Err(TagError::EmptyQuery));
+    assert_eq!(
+        TagQuery::parse("Stone"),
+        Err(TagError::InvalidTag("Stone".into()))
+    );
+    // Well formed but unknown to the registry
+    let typo = TagQuery::parse("buidling-material").unwrap();
+    assert_eq!(
+        registry.validate(&typo),
+        Err(TagError::UnknownTag("buidling-material".into()))
+    );
+    assert!(AcceptsTags::new("buidling-material", &registry).is_err());
+}
+
+#[test]
+fn ingredients_match_exact_types_or_tags() {
+    let registry = TagRegistry::default();
+    let any_stone = Ingredient::tagged("stone", &registry).unwrap();
+    assert!(any_stone.matches(ItemType::Stone, &registry));
+    assert!(!any_stone.matches(ItemType::Gem, &registry));
+    assert_eq!(any_stone.to_string(), "any item with tag stone");
+    assert!(Ingredient::Item(ItemType::Gem).matches(ItemType::Gem, &registry));
+    assert!(Ingredient::tagged("edible", &registry).is_err());
+
+    let json = serde_json::to_string(&any_stone).unwrap();
+    assert_eq!(json, r#"{"tagged":"stone"}"#);
+    assert_eq!(
+        serde_json::from_str::<Ingredient>(&json).unwrap(),
+        any_stone
+    );
+}
+
+#[test]
+fn registry_loads_from_json_and_rejects_bad_tags() {
+    let registry = TagRegistry::from_json(r#"{ "Stone": ["stone", "heavy"] }"#).unwrap();
+    assert_eq!(
+        registry.tags_of(ItemType::Stone).collect::<Vec<_>>(),
+        ["heavy", "stone"]
+    );
+    assert_eq!(registry.tags_of(ItemType::Gem).count(), 0);
+    assert!(TagRegistry::from_json(r#"{ "Stone": ["Heavy Rock"] }"#).is_err());
+    let round = TagRegistry::from_json(&TagRegistry::default().to_json().unwrap()).unwrap();
+    assert_eq!(round, TagRegistry::default());
+}
+
+#[test]
+fn tagged_stockpiles_only_receive_matching_items() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems((stockpile_tag_filter_system, systems::auto_haul_system).chain());
+    let filter = AcceptsTags::new("building-material", &TagRegistry::default()).unwrap();
+    let pile = world.spawn((StockpileBundle::new(0, 0, 1, 1), filter)).id();
+    world.spawn((Item::stone(), Carriable, Position(5, 5)));
+    world.spawn((
+        Item {
+            item_type: ItemType::Gem,
+        },
+        Carriable,
+        Position(6, 6),
+    ));
+    schedule.run(&mut world);
+
+    assert_eq!(
+        world.get::<Stockpile>(pile).unwrap().accepts,
+        Some(vec![ItemType::Stone, ItemType::Block])
+    );
+    let hauled: Vec<(i32, i32)> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter_map(|j| match j.kind {
+            JobKind::Haul { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(hauled, vec![(5, 5)]);
+
+    // Retagging an item type reaches existing filters
+    world
+        .resource_mut::<TagRegistry>()
+        .tag(ItemType::Gem, "building-material")
+        .unwrap();
+    schedule.run(&mut world);
+    assert_eq!(
+        world.get::<Stockpile>(pile).unwrap().accepts,
+        Some(vec![ItemType::Stone, ItemType::Gem, ItemType::Block])
+    );
+}
+
+#[test]
+fn tag_filters_survive_save_and_load() {
+    let mut world = world();
+    let filter = AcceptsTags::new("gear !armor", &TagRegistry::default()).unwrap();
+    world.spawn((StockpileBundle::new(2, 2, 3, 3), filter.clone()));
+    let save = save_world(&mut world);
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let filters: Vec<AcceptsTags> = loaded
+        .query::<&AcceptsTags>()
+        .iter(&loaded)
+        .cloned()
+        .collect();
+    assert_eq!(filters, vec![filter]);
+}
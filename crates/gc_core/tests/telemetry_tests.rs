@@ -0,0 +1,104 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::collections::BTreeMap;
+
+fn world() -> World {
+    build_standard_world(12, 8, 3, WorldOptions::default())
+}
+
+#[test]
+fn entities_are_counted_in_one_category_each() {
+    let mut world = world();
+    let carrier = world
+        .spawn((Position(1, 1), Carrier, AssignedJob::default()))
+        .id();
+    world.spawn((Item::stone(), Position(2, 2)));
+    world.spawn((Item::stone(), CarriedBy(carrier)));
+    world.spawn(Item::stone());
+    world.spawn((
+        MineDesignation,
+        Position(3, 3),
+        DesignationLifecycle(DesignationState::Consumed),
+    ));
+    world.spawn((
+        MineDesignation,
+        Position(4, 3),
+        DesignationLifecycle::default(),
+    ));
+    world.spawn(StockpileBundle::new(0, 0, 1, 1));
+    world.spawn((Position(5, 5), Dead, AssignedJob::default()));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(entity_telemetry_system);
+    schedule.run(&mut world);
+    let telemetry = world.resource::<EntityTelemetry>();
+    let counts: BTreeMap<EntityCategory, u64> = telemetry.counts().collect();
+    for (category, n) in [
+        (EntityCategory::Workers, 1),
+        (EntityCategory::Corpses, 1),
+        (EntityCategory::LooseItems, 1),
+        (EntityCategory::HeldItems, 1),
+        (EntityCategory::OrphanItems, 1),
+        (EntityCategory::Designations, 1),
+        (EntityCategory::ConsumedDesignations, 1),
+        (EntityCategory::Zones, 1),
+    ] {
+        assert_eq!(counts[&category], n, "{category}");
+    }
+    assert_eq!(counts.values().sum::<u64>(), world.entities().len() as u64);
+}
+
+#[test]
+fn growth_without_a_fall_is_flagged_past_the_limit() {
+    let config = TelemetryConfig {
+        growth_limits: BTreeMap::from([(EntityCategory::ConsumedDesignations, 2)]),
+    };
+    let sample = |n| BTreeMap::from([(EntityCategory::ConsumedDesignations, n)]);
+    let mut telemetry = EntityTelemetry::default();
+    for (tick, n) in [(0, 5), (1, 5), (2, 6), (3, 7)] {
+        telemetry.record(tick, sample(n));
+    }
+    assert_eq!(telemetry.leaks(&config), vec![]);
+    telemetry.record(4, sample(8));
+    assert_eq!(
+        telemetry.leaks(&config),
+        vec![LeakAlert {
+            category: EntityCategory::ConsumedDesignations,
+            from: 5,
+            to: 8,
+            since: 0,
+        }]
+    );
+    assert_eq!(
+        telemetry.leaks(&config)[0].to_string(),
+        "consumed_designations grew from 5 to 8 since tick 0 without falling"
+    );
+
+    // Any fall starts a new run
+    telemetry.record(5, sample(7));
+    assert_eq!(telemetry.leaks(&config), vec![]);
+    telemetry.record(6, sample(10));
+    assert_eq!(telemetry.leaks(&config)[0].since, 5);
+    assert_eq!(telemetry.samples(), 7);
+}
+
+#[test]
+fn counts_and_leaks_reach_view_metrics() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let metrics = snapshot_world(&mut world).metrics;
+    assert_eq!(metrics.entity_counts.len(), EntityCategory::ALL.len());
+    assert!(metrics.leaks.is_empty());
+
+    // An item dropped out of the world without a holder
+    world.spawn(Item::stone());
+    schedule.run(&mut world);
+    let metrics = snapshot_world(&mut world).metrics;
+    assert!(metrics
+        .entity_counts
+        .contains(&(EntityCategory::OrphanItems, 1)));
+    assert_eq!(metrics.leaks.len(), 1);
+    assert_eq!(metrics.leaks[0].category, EntityCategory::OrphanItems);
+}
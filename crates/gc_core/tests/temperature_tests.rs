@@ -0,0 +1,228 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use std::collections::HashSet;
+
+/// Open ground with two sealed 5x5 rooms (walls at y 2..=6 and x 2..=6 or
+/// 10..=14); the second room's west wall at (10, 4) is `door`
+fn world(ambient: i32, door: Option<bool>) -> World {
+    let mut world = build_standard_world(18, 10, 3, WorldOptions::default());
+    world.insert_resource(AmbientTemperature(ambient));
+    {
+        let mut map = world.resource_mut::<GameMap>();
+        for y in 0..10 {
+            for x in 0..18 {
+                let ring = |x0: i32| {
+                    ((x0..=x0 + 4).contains(&x) && (y == 2 || y == 6))
+                        || ((2..=6).contains(&y) && (x == x0 || x == x0 + 4))
+                };
+                let wall = (ring(2) || ring(10)) && !((x, y) == (10, 4) && door == Some(true));
+                let kind = if wall {
+                    TileKind::Wall
+                } else {
+                    TileKind::Floor
+                };
+                map.set_tile(x, y, kind).unwrap();
+            }
+        }
+    }
+    if let Some(open) = door {
+        world.spawn((Door { open }, Position(10, 4)));
+    }
+    world
+}
+
+fn set_tick(world: &mut World, tick: u64) {
+    world.resource_mut::<Time>().ticks = tick;
+}
+
+/// Tick that is night under the default day cycle
+const NIGHT: u64 = 100;
+/// Tick that is day under the default day cycle
+const DAY: u64 = 1000;
+
+fn temperature_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            temperature_system,
+            temperature_comfort_system,
+            spoilage_system,
+        )
+            .chain(),
+    );
+    schedule
+}
+
+fn hundredths(world: &World, x: i32, y: i32) -> i32 {
+    world
+        .resource::<TemperatureMap>()
+        .hundredths_at(x, y)
+        .unwrap()
+}
+
+#[test]
+fn rooms_hold_their_warmth_through_the_night() {
+    let mut world = world(20, None);
+    let mut schedule = temperature_schedule();
+    set_tick(&mut world, DAY);
+    schedule.run(&mut world);
+    assert_eq!(hundredths(&world, 4, 4), 2000);
+    assert!(world.resource::<TemperatureMap>().settled);
+
+    // Night drops the outdoors 8 degrees at once; the room lags behind
+    set_tick(&mut world, NIGHT);
+    for _ in 0..10 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(hundredths(&world, 0, 0), 1200);
+    assert_eq!(
+        hundredths(&world, 2, 2),
+        1200,
+        "outer walls follow the outdoors"
+    );
+    let room = hundredths(&world, 4, 4);
+    assert!(1200 < room && room < 2000, "{room}");
+    // Corners lose heat through two walls, the centre through none
+    assert!(hundredths(&world, 3, 3) < room);
+}
+
+#[test]
+fn closed_doors_insulate_less_than_walls_and_open_ones_not_at_all() {
+    let mut closed = world(20, Some(false));
+    let mut schedule = temperature_schedule();
+    set_tick(&mut closed, DAY);
+    schedule.run(&mut closed);
+    set_tick(&mut closed, NIGHT);
+    for _ in 0..10 {
+        schedule.run(&mut closed);
+    }
+    let (walled, doored) = (hundredths(&closed, 3, 4), hundredths(&closed, 11, 4));
+    assert!(doored < walled, "{doored} vs {walled}");
+    assert!(doored > 1200);
+
+    let mut open = world(20, Some(true));
+    let mut schedule = temperature_schedule();
+    set_tick(&mut open, DAY);
+    schedule.run(&mut open);
+    set_tick(&mut open, NIGHT);
+    schedule.run(&mut open);
+    assert_eq!(hundredths(&open, 12, 4), 1200);
+    assert!(hundredths(&open, 4, 4) > 1200);
+
+    // Shutting the door seals the room again
+    open.query::<&mut Door>().single_mut(&mut open).open = false;
+    open.resource_mut::<GameMap>()
+        .set_tile(10, 4, TileKind::Wall)
+        .unwrap();
+    set_tick(&mut open, DAY);
+    schedule.run(&mut open);
+    let room = hundredths(&open, 12, 4);
+    assert!(1200 < room && room < 2000, "{room}");
+}
+
+#[test]
+fn relaxation_is_bounded_and_settles_the_same_every_time() {
+    let mut map = GameMap::new(12, 12);
+    for i in 2..=9 {
+        for (x, y) in [(i, 2), (i, 9), (2, i), (9, i)] {
+            map.set_tile(x, y, TileKind::Wall).unwrap();
+        }
+    }
+    map.set_tile(5, 5, TileKind::Lava).unwrap();
+    let doors = HashSet::new();
+    let mut temps = TemperatureMap::uniform(&map, 1000);
+    assert_eq!(temps.relax(&map, &doors, 50000, 3), 3);
+    assert!(!temps.settled);
+    assert_eq!(temps.hundredths_at(5, 5), Some(50000));
+    assert!(temps.hundredths_at(5, 6).unwrap() > 1000);
+    assert_eq!(temps.hundredths_at(0, 0), Some(1000));
+
+    let mut again = TemperatureMap::uniform(&map, 1000);
+    again.relax(&map, &doors, 50000, 3);
+    assert_eq!(again, temps);
+
+    let mut sweeps = 0;
+    while !temps.settled {
+        sweeps += temps.relax(&map, &doors, 50000, 16);
+        assert!(sweeps < 100_000, "never settles");
+    }
+    // Lava heats the sealed room well past the outdoors
+    assert!(temps.celsius(3, 3).unwrap() > 100);
+    assert_eq!(temps.celsius(0, 0), Some(10));
+}
+
+#[test]
+fn goblins_out_of_comfort_lose_mood() {
+    let config = TemperatureConfig::default();
+    assert_eq!(config.discomfort(20), 0);
+    assert_eq!(config.discomfort(config.comfort_min), 0);
+    assert_eq!(config.discomfort(config.comfort_min - 1), 10);
+    assert_eq!(config.discomfort(config.comfort_min - 15), 20);
+    assert_eq!(config.discomfort(config.comfort_max + 25), 30);
+
+    let mut world = world(-5, None);
+    world.insert_resource(DayCycle {
+        ticks_per_day: 0,
+        ..DayCycle::default()
+    });
+    let outside = world.spawn((Goblin, Position(0, 0))).id();
+    let invader = world
+        .spawn((
+            Faction::new(FactionKind::Invaders),
+            Position(1, 0),
+            Mood::default(),
+        ))
+        .id();
+    let mut schedule = temperature_schedule();
+    set_tick(&mut world, 99);
+    schedule.run(&mut world);
+    assert!(world.get::<Mood>(outside).is_none());
+
+    set_tick(&mut world, 100);
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Mood>(outside), Some(&Mood(-20)));
+    assert_eq!(world.get::<Mood>(invader), Some(&Mood(0)));
+}
+
+#[test]
+fn warm_food_spoils_sooner_and_frozen_food_keeps() {
+    let config = TemperatureConfig::default();
+    assert_eq!(config.spoil_rate(-3), 0);
+    assert_eq!(config.spoil_rate(5), 1);
+    assert_eq!(config.spoil_rate(25), 2);
+
+    let egg = || {
+        (
+            Item {
+                item_type: ItemType::Egg,
+            },
+            Position(0, 0),
+        )
+    };
+    let mut schedule = temperature_schedule();
+    let mut warm = world(25, None);
+    warm.insert_resource(TemperatureConfig {
+        spoil_after: 10,
+        ..config
+    });
+    set_tick(&mut warm, DAY);
+    let eggs = warm.spawn(egg()).id();
+    let stone = warm.spawn((Item::stone(), Position(0, 0))).id();
+    for _ in 0..4 {
+        schedule.run(&mut warm);
+    }
+    assert_eq!(warm.get::<Spoilage>(eggs), Some(&Spoilage(8)));
+    schedule.run(&mut warm);
+    assert!(warm.get_entity(eggs).is_none());
+    assert!(warm.get::<Spoilage>(stone).is_none());
+
+    let mut schedule = temperature_schedule();
+    let mut frozen = world(-3, None);
+    set_tick(&mut frozen, DAY);
+    let eggs = frozen.spawn(egg()).id();
+    for _ in 0..5 {
+        schedule.run(&mut frozen);
+    }
+    assert!(frozen.get::<Spoilage>(eggs).is_none());
+}
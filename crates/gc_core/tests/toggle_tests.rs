@@ -0,0 +1,137 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+fn set(world: &mut World, subsystem: Subsystem, enabled: bool) {
+    world
+        .resource_mut::<SystemToggles>()
+        .set(subsystem, enabled);
+}
+
+fn hauls(world: &World) -> usize {
+    world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .filter(|j| matches!(j.kind, JobKind::Haul { .. }))
+        .count()
+}
+
+#[test]
+fn subsystems_parse_from_their_labels() {
+    for subsystem in Subsystem::ALL {
+        assert_eq!(subsystem.label().parse::<Subsystem>(), Ok(subsystem));
+    }
+    assert_eq!("Auto_Haul".parse::<Subsystem>(), Ok(Subsystem::AutoHaul));
+    assert_eq!(
+        "rain".parse::<Subsystem>(),
+        Err("unknown subsystem 'rain'".to_string())
+    );
+}
+
+#[test]
+fn auto_haul_catches_up_on_items_dropped_while_off() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn(StockpileBundle::new(0, 0, 1, 1));
+    set(&mut world, Subsystem::AutoHaul, false);
+    world.spawn((
+        Item {
+            item_type: ItemType::Stone,
+        },
+        Position(8, 6),
+        Carriable,
+    ));
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(hauls(&world), 0);
+    assert_eq!(
+        snapshot_world(&mut world).disabled,
+        vec![Subsystem::AutoHaul]
+    );
+
+    set(&mut world, Subsystem::AutoHaul, true);
+    schedule.run(&mut world);
+    assert_eq!(hauls(&world), 1);
+    assert!(snapshot_world(&mut world).disabled.is_empty());
+}
+
+#[test]
+fn needs_off_stops_resting_and_regeneration() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    set(&mut world, Subsystem::Needs, false);
+    world.spawn((Position(4, 5), Bed));
+    let miner = world
+        .spawn((
+            Position(1, 5),
+            Miner,
+            AssignedJob::default(),
+            Health::new(2, 10),
+        ))
+        .id();
+    let resting = world
+        .spawn((Position(9, 9), Resting, Health::new(2, 10)))
+        .id();
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.get::<AssignedJob>(miner).unwrap().0, None);
+    assert_eq!(world.get::<Health>(resting).unwrap().hp, 2);
+
+    set(&mut world, Subsystem::Needs, true);
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    assert!(world.get::<AssignedJob>(miner).unwrap().0.is_some());
+    assert!(world.get::<Health>(resting).unwrap().hp > 2);
+}
+
+#[test]
+fn attacks_are_not_resolved_with_combat_off() {
+    let mut world = world();
+    let attacker = world
+        .spawn((CombatStats::new(20, 0, 0, 0, 5, 5), Health::full(20)))
+        .id();
+    let defender = world
+        .spawn((CombatStats::new(0, 0, 0, 0, 1, 1), Health::full(20)))
+        .id();
+    set(&mut world, Subsystem::Combat, false);
+    assert_eq!(resolve_attack(&mut world, attacker, defender), None);
+    assert_eq!(world.get::<Health>(defender).unwrap().hp, 20);
+    let audit = world.resource::<DeterministicRng>().audit().clone();
+    assert_eq!(
+        audit.uses("resolve_attack", gc_core::determinism::RngStream::Combat),
+        0
+    );
+
+    set(&mut world, Subsystem::Combat, true);
+    assert!(resolve_attack(&mut world, attacker, defender).is_some());
+}
+
+#[test]
+fn daylight_holds_while_weather_is_off() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    // Night under the default day cycle, then day
+    world.resource_mut::<Time>().ticks = 100;
+    schedule.run(&mut world);
+    assert!(!world.resource::<LightMap>().daylight);
+
+    set(&mut world, Subsystem::Weather, false);
+    world.resource_mut::<Time>().ticks = 1000;
+    schedule.run(&mut world);
+    assert!(!world.resource::<LightMap>().daylight);
+
+    set(&mut world, Subsystem::Weather, true);
+    schedule.run(&mut world);
+    assert!(world.resource::<LightMap>().daylight);
+}
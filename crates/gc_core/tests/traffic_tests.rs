@@ -0,0 +1,128 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+#[test]
+fn counts_decay_by_halves_and_vanish() {
+    let mut traffic = TrafficMap::default();
+    for _ in 0..5 {
+        traffic.record(2, 3);
+    }
+    traffic.record(4, 4);
+    traffic.record(1, 1);
+    traffic.record(1, 1);
+    assert_eq!(traffic.hottest(2), vec![((2, 3), 5), ((1, 1), 2)]);
+    assert_eq!(traffic.max(), 5);
+
+    traffic.decay();
+    assert_eq!(traffic.count(2, 3), 2);
+    assert_eq!(traffic.count(1, 1), 1);
+    assert_eq!(traffic.count(4, 4), 0);
+    assert_eq!(traffic.iter().count(), 2);
+}
+
+#[test]
+fn heat_is_relative_to_the_busiest_tile() {
+    assert_eq!(heat_level(0, 10), 0);
+    assert_eq!(heat_level(1, 100), 1);
+    assert_eq!(heat_level(50, 100), 5);
+    assert_eq!(heat_level(100, 100), 9);
+    assert_eq!(heat_level(3, 0), 9);
+}
+
+#[test]
+fn only_moves_of_agents_count() {
+    let mut world = World::new();
+    world.insert_resource(TrafficMap::default());
+    let walker = world.spawn(Position(0, 0)).id();
+    let item = world
+        .spawn((
+            Item {
+                item_type: ItemType::Stone,
+            },
+            Position(5, 5),
+        ))
+        .id();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(traffic_system);
+    schedule.run(&mut world);
+    assert!(
+        world.resource::<TrafficMap>().is_empty(),
+        "spawning is not walking"
+    );
+
+    for step in 1..=3 {
+        world.get_mut::<Position>(walker).unwrap().0 = step;
+        world.get_mut::<Position>(item).unwrap().0 = 5 + step;
+        schedule.run(&mut world);
+    }
+    // Standing still adds nothing
+    schedule.run(&mut world);
+    let traffic = world.resource::<TrafficMap>();
+    let walked: Vec<_> = traffic.iter().collect();
+    assert_eq!(walked, vec![((1, 0), 1), ((2, 0), 1), ((3, 0), 1)]);
+}
+
+#[test]
+fn busy_routes_heat_up_in_a_running_colony() {
+    let mut world = build_standard_world(12, 7, 5, WorldOptions::default());
+    world.insert_resource(MovementConfig {
+        mode: MovementMode::Step,
+    });
+    world.insert_resource(TrafficConfig { decay_every: 0 });
+    // An open row, so stepping carriers are not stopped by generated rock
+    for x in 0..12 {
+        world
+            .resource_mut::<GameMap>()
+            .set_tile(x, 3, TileKind::Floor)
+            .unwrap();
+    }
+    for x in 4..8 {
+        world.spawn((
+            Item {
+                item_type: ItemType::Stone,
+            },
+            Position(x, 3),
+            Carriable,
+        ));
+    }
+    world.spawn((
+        Carrier,
+        Inventory::default(),
+        AssignedJob::default(),
+        Position(1, 3),
+    ));
+    world.spawn(StockpileBundle::new(0, 3, 0, 3));
+    let mut schedule = build_default_schedule();
+    for _ in 0..40 {
+        schedule.run(&mut world);
+    }
+    let traffic = world.resource::<TrafficMap>();
+    // Every round trip passes the tiles next to the stockpile
+    let ((x, y), busiest) = traffic.hottest(1)[0];
+    assert!(y == 3 && (1..=2).contains(&x), "busiest at ({x}, {y})");
+    assert!(busiest >= 4 && traffic.count(1, 3) >= 4);
+    let view = snapshot_world(&mut world);
+    assert_eq!(
+        view.traffic,
+        world.resource::<TrafficMap>().iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn old_traffic_fades_on_schedule() {
+    let mut world = build_standard_world(8, 8, 1, WorldOptions::default());
+    world.insert_resource(TrafficConfig { decay_every: 4 });
+    world.resource_mut::<TrafficMap>().record(3, 3);
+    for _ in 0..4 {
+        world.resource_mut::<TrafficMap>().record(3, 3);
+    }
+    let mut schedule = build_default_schedule();
+    // Ticks 0-3 run without decay; tick 4 halves
+    for _ in 0..4 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(world.resource::<TrafficMap>().count(3, 3), 5);
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<TrafficMap>().count(3, 3), 2);
+}
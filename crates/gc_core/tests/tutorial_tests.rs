@@ -0,0 +1,158 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+fn world() -> World {
+    build_standard_world(20, 12, 7, WorldOptions::default())
+}
+
+fn messages(world: &World) -> Vec<String> {
+    world
+        .resource::<Announcements>()
+        .iter()
+        .filter(|a| a.text.starts_with("Tutorial"))
+        .map(|a| a.text.clone())
+        .collect()
+}
+
+fn step(world: &World) -> usize {
+    world.resource::<TutorialProgress>().step
+}
+
+/// Run until the tutorial reaches `target`, at most `limit` ticks
+fn run_until(world: &mut World, schedule: &mut Schedule, target: usize, limit: usize) {
+    for _ in 0..limit {
+        if step(world) >= target {
+            return;
+        }
+        schedule.run(world);
+    }
+    panic!("tutorial stuck at step {}", step(world) + 1);
+}
+
+#[test]
+fn first_dig_runs_from_setup_to_the_end() {
+    let mut world = world();
+    let tutorial = builtin_tutorial("first_dig").expect("shipped");
+    start_tutorial(&mut world, tutorial.clone()).unwrap();
+    let map = world.resource::<GameMap>();
+    assert_eq!(map.get_tile(8, 4), Some(TileKind::Wall));
+    assert_eq!(map.get_tile(4, 5), Some(TileKind::Floor));
+
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert_eq!(step(&world), 0);
+    assert_eq!(
+        messages(&world),
+        vec![format!("Tutorial 1/5: {}", tutorial.steps[0].hint)]
+    );
+
+    apply_world_command(&mut world, &WorldCommand::Designate { x: 8, y: 4 });
+    run_until(&mut world, &mut schedule, 2, 200);
+    designate_mine_rect(&mut world, &ZoneBounds::new(8, 3, 11, 6));
+    run_until(&mut world, &mut schedule, 3, 5);
+    apply_world_command(
+        &mut world,
+        &WorldCommand::Zone {
+            kind: ZoneKind::Stockpile,
+            a: (13, 8),
+            b: (15, 9),
+            accepts: None,
+        },
+    );
+    run_until(&mut world, &mut schedule, 5, 2000);
+    schedule.run(&mut world);
+
+    let messages = messages(&world);
+    assert_eq!(messages.len(), 6);
+    assert_eq!(
+        messages[4],
+        format!("Tutorial 5/5: {}", tutorial.steps[4].hint)
+    );
+    assert_eq!(messages[5], "Tutorial complete: First dig");
+    assert!(world.resource::<TutorialProgress>().finished());
+    schedule.run(&mut world);
+    assert_eq!(self::messages(&world).len(), 6, "the end is announced once");
+}
+
+#[test]
+fn met_goals_pass_after_their_hint_and_waits_count_ticks() {
+    let mut world = world();
+    let tutorial = decode_tutorial_json(
+        r#"{
+            "name": "Patience",
+            "setup": [{ "brush": "floor", "a": [1, 1], "b": [3, 3] }],
+            "steps": [
+                { "hint": "Clear ground", "goal": { "type": "mine", "a": [1, 1], "b": [3, 3] } },
+                { "hint": "Wait a bit", "goal": { "type": "wait", "ticks": 3 } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    start_tutorial(&mut world, tutorial).unwrap();
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    assert_eq!(
+        messages(&world),
+        vec!["Tutorial 1/2: Clear ground", "Tutorial 2/2: Wait a bit"]
+    );
+    assert_eq!(step(&world), 1);
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    assert_eq!(step(&world), 2);
+    assert_eq!(
+        messages(&world).last().unwrap(),
+        "Tutorial complete: Patience"
+    );
+    assert!(world
+        .resource::<Announcements>()
+        .iter()
+        .all(|a| a.severity == Severity::Info));
+}
+
+#[test]
+fn bad_tutorials_are_rejected_before_anything_is_painted() {
+    let mut world = world();
+    let before = world.resource::<GameMap>().clone();
+    let tutorial = Tutorial {
+        name: "Off the edge".into(),
+        setup: vec![SetupStroke {
+            brush: Brush::Tile(TileKind::Water),
+            a: (2, 2),
+            b: Some((25, 2)),
+            material: None,
+        }],
+        steps: vec![TutorialStep {
+            hint: "Swim".into(),
+            goal: Goal::Wait { ticks: 1 },
+        }],
+    };
+    let err = start_tutorial(&mut world, tutorial.clone()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "water stroke at (25, 2) is outside the 20x12 map"
+    );
+    assert_eq!(world.resource::<GameMap>().tiles, before.tiles);
+    assert_eq!(
+        *world.resource::<TutorialProgress>(),
+        TutorialProgress::default()
+    );
+
+    let empty = Tutorial {
+        steps: vec![],
+        ..tutorial
+    };
+    assert!(matches!(
+        start_tutorial(&mut world, empty),
+        Err(TutorialError::NoSteps)
+    ));
+    let err =
+        decode_tutorial_json(r#"{"name":"x","setup":[{"brush":"goblin","a":[0,0]}],"steps":[]}"#)
+            .unwrap_err();
+    assert!(err.to_string().contains("unknown brush 'goblin'"));
+    for (name, _) in BUILTIN_TUTORIALS {
+        assert!(builtin_tutorial(name).is_some());
+    }
+}
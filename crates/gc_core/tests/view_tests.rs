@@ -0,0 +1,112 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_default_schedule, build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+fn demo_world() -> World {
+    build_standard_world(
+        24,
+        16,
+        7,
+        WorldOptions {
+            populate_demo_scene: true,
+            tick_ms: 100,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn view_projects_tiles_entities_and_metrics() {
+    let mut world = demo_world();
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(3, 3, TileKind::Wall)
+        .unwrap();
+    world.spawn(DesignationBundle {
+        pos: Position(3, 3),
+        ..Default::default()
+    });
+
+    let view = snapshot_world(&mut world);
+    assert_eq!((view.width, view.height), (24, 16));
+    assert_eq!(view.tile(3, 3), Some(TileKind::Wall));
+    assert_eq!(view.tile(-1, 0), None);
+
+    let miner = view
+        .entities
+        .iter()
+        .find(|e| e.roles.contains(&"miner".to_string()))
+        .expect("demo miner");
+    assert_eq!(miner.name.as_deref(), Some("Grak"));
+    assert_eq!(view.entity(miner.id), Some(miner));
+    assert_eq!(view.entities_at(5, 5).count(), 2);
+
+    let stockpile = view
+        .entities
+        .iter()
+        .find(|e| e.zone.is_some())
+        .expect("demo stockpile");
+    assert_eq!(stockpile.zone, Some((9, 9, 11, 11)));
+    assert_eq!(view.metrics.active_designations, 1);
+    assert_eq!(view.metrics.entities, view.entities.len());
+}
+
+#[test]
+fn view_tracks_jobs_after_ticks_and_is_detached_from_world() {
+    // No workers, so the mining job stays queued on the board
+    let mut world = build_standard_world(24, 16, 7, WorldOptions::default());
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(6, 5, TileKind::Wall)
+        .unwrap();
+    world.spawn(DesignationBundle {
+        pos: Position(6, 5),
+        ..Default::default()
+    });
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.tick, 1);
+    assert_eq!(view.metrics.queued_jobs, 1);
+    assert_eq!(view.jobs.queued[0].kind, JobKind::Mine { x: 6, y: 5 });
+
+    // Further ticks do not affect an existing view
+    schedule.run(&mut world);
+    assert_eq!(view.tick, 1);
+    assert_eq!(snapshot_world(&mut world).tick, 2);
+}
+
+#[test]
+fn view_is_send_sync_and_round_trips_json() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<WorldView>();
+
+    let mut world = demo_world();
+    let view = snapshot_world(&mut world);
+    let json = serde_json::to_string(&view).unwrap();
+    let back: WorldView = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, view);
+}
+
+#[test]
+fn identical_seeds_produce_identical_views() {
+    let mut a = demo_world();
+    let mut b = demo_world();
+    let mut schedule_a = build_default_schedule();
+    let mut schedule_b = build_default_schedule();
+    for _ in 0..5 {
+        schedule_a.run(&mut a);
+        schedule_b.run(&mut b);
+    }
+    assert_eq!(snapshot_world(&mut a), snapshot_world(&mut b));
+}
+
+#[test]
+fn view_of_empty_world_is_empty() {
+    let mut world = World::new();
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.tiles.len(), 0);
+    assert!(view.entities.is_empty());
+    assert_eq!(view.metrics, ViewMetrics::default());
+}
@@ -21,7 +21,7 @@ fn visibility_resource_contains_entity_tiles() {
     let mut world = World::new();
     world.insert_resource(GameMap::new(16, 16));
     world.insert_resource(gc_core::fov::Visibility::default());
-    let e = world.spawn((Position(2, 2), VisionRadius(3))).id();
+    let e = world.spawn((Position(2, 2), VisionRadius::new(3))).id();
 
     let mut schedule = Schedule::default();
     schedule.add_systems((gc_core::fov::compute_visibility_system,));
@@ -31,3 +31,170 @@ fn visibility_resource_contains_entity_tiles() {
     let tiles = vis.per_entity.get(&e).expect("entity visibility missing");
     assert!(tiles.contains(&(2, 2)));
 }
+
+#[test]
+fn incremental_visibility_matches_a_fresh_computation() {
+    fn fresh(world: &mut World) -> std::collections::HashMap<Entity, Vec<(i32, i32)>> {
+        let mut q = world.query::<(Entity, &Position, Option<&VisionRadius>)>();
+        let mut copy = World::new();
+        copy.insert_resource(world.resource::<GameMap>().clone());
+        copy.insert_resource(gc_core::fov::Visibility::default());
+        let ids: Vec<_> = q
+            .iter(world)
+            .map(|(e, p, r)| {
+                let c = copy
+                    .spawn((*p, VisionRadius::new(r.map_or(8, |r| r.base))))
+                    .id();
+                (e, c)
+            })
+            .collect();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(gc_core::fov::compute_visibility_system);
+        schedule.run(&mut copy);
+        let vis = copy.resource::<gc_core::fov::Visibility>();
+        ids.into_iter()
+            .map(|(e, c)| (e, sorted(&vis.per_entity[&c])))
+            .collect()
+    }
+    fn sorted(tiles: &std::collections::HashSet<(i32, i32)>) -> Vec<(i32, i32)> {
+        let mut v: Vec<_> = tiles.iter().copied().collect();
+        v.sort_unstable();
+        v
+    }
+    fn current(world: &World) -> std::collections::HashMap<Entity, Vec<(i32, i32)>> {
+        let vis = world.resource::<gc_core::fov::Visibility>();
+        vis.per_entity
+            .iter()
+            .map(|(e, t)| (*e, sorted(t)))
+            .collect()
+    }
+
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(40, 20));
+    world.insert_resource(gc_core::fov::Visibility::default());
+    let near = world.spawn((Position(5, 5), VisionRadius::new(4))).id();
+    let far = world.spawn((Position(30, 10), VisionRadius::new(4))).id();
+    let gone = world.spawn(Position(20, 15)).id();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::fov::compute_visibility_system);
+    schedule.run(&mut world);
+    assert_eq!(current(&world), fresh(&mut world));
+
+    // A wall next to one observer, a move, and a despawn
+    world
+        .resource_mut::<GameMap>()
+        .set_tile(6, 5, TileKind::Wall)
+        .unwrap();
+    world.get_mut::<Position>(far).unwrap().0 = 28;
+    world.despawn(gone);
+    schedule.run(&mut world);
+    assert!(!current(&world)[&near].contains(&(8, 5)));
+    assert!(!current(&world).contains_key(&gone));
+    assert_eq!(current(&world), fresh(&mut world));
+}
+
+#[test]
+fn bulk_map_writes_report_what_changed() {
+    let mut map = GameMap::new(8, 8);
+    map.set_tile(1, 1, TileKind::Wall).unwrap();
+    let start = map.revision();
+    let batch = map.apply(&[
+        TileChange {
+            pos: (2, 2),
+            kind: TileKind::Wall,
+        },
+        // Already floor: not a change
+        TileChange {
+            pos: (3, 3),
+            kind: TileKind::Floor,
+        },
+        TileChange {
+            pos: (99, 0),
+            kind: TileKind::Wall,
+        },
+        TileChange {
+            pos: (1, 1),
+            kind: TileKind::Water,
+        },
+    ]);
+    let expected = vec![
+        TileChanged {
+            pos: (2, 2),
+            old: TileKind::Floor,
+            new: TileKind::Wall,
+        },
+        TileChanged {
+            pos: (1, 1),
+            old: TileKind::Wall,
+            new: TileKind::Water,
+        },
+    ];
+    assert_eq!(batch.changes, expected);
+    assert_eq!(batch.revision, map.revision());
+    assert_eq!(map.changes_since(start), Some(batch));
+    assert_eq!(map.changes_since(0).unwrap().changes.len(), 3);
+    assert_eq!(map.changes_since(map.revision() + 1), None);
+
+    // The log keeps at most one entry per tile (and at least 64)
+    for i in 0..100 {
+        let kind = if i % 2 == 0 {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        };
+        map.set_tile(0, 0, kind).unwrap();
+    }
+    assert_eq!(map.changes_since(start), None);
+    assert!(map.changes_since(map.revision() - 64).is_some());
+}
+
+#[test]
+fn path_cache_follows_map_changes() {
+    let mut map = GameMap::new(10, 3);
+    let mut svc = gc_core::path::PathService::new(8);
+    let (path, _) = svc.get(&map, (0, 1), (9, 1)).unwrap();
+    assert_eq!(path.len(), 10);
+    let _ = svc.get(&map, (0, 0), (0, 2));
+
+    // Blocking the straight route only drops the path through it
+    map.set_tile(5, 1, TileKind::Wall).unwrap();
+    let (path, _) = svc.get(&map, (0, 1), (9, 1)).unwrap();
+    assert!(!path.contains(&(5, 1)));
+    let _ = svc.get(&map, (0, 0), (0, 2));
+    assert_eq!(svc.stats(), (1, 3));
+
+    // Sealing the gap then reopening it clears everything
+    map.apply(&[
+        TileChange {
+            pos: (5, 0),
+            kind: TileKind::Wall,
+        },
+        TileChange {
+            pos: (5, 2),
+            kind: TileKind::Wall,
+        },
+    ]);
+    assert!(svc.get(&map, (0, 1), (9, 1)).is_none());
+    map.set_tile(5, 1, TileKind::Floor).unwrap();
+    let (path, _) = svc.get(&map, (0, 1), (9, 1)).unwrap();
+    assert_eq!(path.len(), 10);
+}
+
+#[test]
+fn visibility_recomputes_after_the_map_is_replaced() {
+    let mut world = World::new();
+    world.insert_resource(GameMap::new(16, 16));
+    world.insert_resource(gc_core::fov::Visibility::default());
+    let e = world.spawn((Position(2, 2), VisionRadius::new(4))).id();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(gc_core::fov::compute_visibility_system);
+    schedule.run(&mut world);
+    assert!(world.resource::<gc_core::fov::Visibility>().per_entity[&e].contains(&(5, 2)));
+
+    // Same size and revision, different tiles
+    let mut walled = GameMap::new(16, 16);
+    walled.tiles[(2 * 16 + 3) as usize] = TileKind::Wall;
+    world.insert_resource(walled);
+    schedule.run(&mut world);
+    assert!(!world.resource::<gc_core::fov::Visibility>().per_entity[&e].contains(&(5, 2)));
+}
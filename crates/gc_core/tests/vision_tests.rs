@@ -0,0 +1,156 @@
+use bevy_ecs::prelude::*;
+use gc_core::fov::{compute_visibility_system, Visibility};
+use gc_core::prelude::*;
+
+/// Tick that is night under the default day cycle
+const NIGHT: u64 = 100;
+/// Tick that is day under the default day cycle
+const DAY: u64 = 1000;
+
+/// Open ground with a sealed room (walls at x/y 10..=14)
+fn world(tick: u64) -> World {
+    let mut world = World::new();
+    let map = GameMap::from_fn(20, 20, |x, y| {
+        let room = (10..=14).contains(&x) && (10..=14).contains(&y);
+        if room && (x == 10 || x == 14 || y == 10 || y == 14) {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        }
+    });
+    world.insert_resource(LightMap::compute(&map, false, []));
+    world.insert_resource(map);
+    world.insert_resource(Time {
+        ticks: tick,
+        tick_ms: 100,
+    });
+    world.insert_resource(Visibility::default());
+    world
+}
+
+fn vision_and_sight() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((vision_modifier_system, compute_visibility_system).chain());
+    schedule
+}
+
+fn sources(world: &World, e: Entity) -> Vec<(VisionSource, i32)> {
+    world
+        .get::<VisionRadius>(e)
+        .unwrap()
+        .modifiers
+        .iter()
+        .map(|m| (m.source, m.delta))
+        .collect()
+}
+
+fn radius(world: &World, e: Entity) -> i32 {
+    world.get::<VisionRadius>(e).unwrap().radius()
+}
+
+#[test]
+fn modifiers_stack_and_lanterns_only_undo_the_dark() {
+    let config = VisionConfig::default();
+    assert!(vision_modifiers(&config, false, false, None, true).is_empty());
+
+    let hurt = Health::new(4, 10);
+    let stack = vision_modifiers(&config, true, true, Some(&hurt), true);
+    let stack: Vec<_> = stack.iter().map(|m| (m.source, m.delta)).collect();
+    assert_eq!(
+        stack,
+        [
+            (VisionSource::Night, -3),
+            (VisionSource::Fog, -4),
+            (VisionSource::Injury, -2),
+            (VisionSource::Lantern, 3),
+        ]
+    );
+    let mut vision = VisionRadius::new(8);
+    vision.modifiers = vision_modifiers(&config, true, false, None, true);
+    assert_eq!(
+        vision.radius(),
+        8,
+        "a lantern gives back no more than night took"
+    );
+    vision.modifiers = vision_modifiers(&config, true, true, Some(&hurt), false);
+    assert_eq!(vision.radius(), 1, "never below 1");
+    let mut blind = VisionRadius::new(0);
+    blind.modifiers = vision.modifiers.clone();
+    assert_eq!(blind.radius(), 0);
+}
+
+#[test]
+fn night_fog_and_injuries_apply_where_they_should() {
+    let mut world = world(NIGHT);
+    let outside = world.spawn((Position(3, 3), VisionRadius::new(8))).id();
+    let inside = world.spawn((Position(12, 12), VisionRadius::new(8))).id();
+    let hurt = world
+        .spawn((Position(5, 3), VisionRadius::new(8), Health::new(2, 10)))
+        .id();
+    let mut schedule = vision_and_sight();
+    schedule.run(&mut world);
+    assert_eq!(sources(&world, outside), [(VisionSource::Night, -3)]);
+    assert!(sources(&world, inside).is_empty(), "no night indoors");
+    assert_eq!(
+        sources(&world, hurt),
+        [(VisionSource::Night, -3), (VisionSource::Injury, -2)]
+    );
+
+    world.insert_resource(Fog(true));
+    world.resource_mut::<Time>().ticks = DAY;
+    schedule.run(&mut world);
+    assert_eq!(sources(&world, outside), [(VisionSource::Fog, -4)]);
+    assert_eq!(sources(&world, inside), [(VisionSource::Fog, -4)]);
+    assert_eq!(radius(&world, hurt), 2);
+}
+
+#[test]
+fn carried_lights_count_as_lanterns() {
+    let mut world = world(NIGHT);
+    let lamp = world.spawn(LightSource::TORCH).id();
+    let carrier = world
+        .spawn((Position(3, 3), VisionRadius::new(8), Inventory(Some(lamp))))
+        .id();
+    let torchbearer = world
+        .spawn((Position(6, 3), VisionRadius::new(8), LightSource::TORCH))
+        .id();
+    vision_and_sight().run(&mut world);
+    for e in [carrier, torchbearer] {
+        assert_eq!(
+            sources(&world, e),
+            [(VisionSource::Night, -3), (VisionSource::Lantern, 3)]
+        );
+        assert_eq!(radius(&world, e), 8);
+    }
+}
+
+#[test]
+fn sight_follows_the_modified_radius_and_unchanged_stacks_stay_unchanged() {
+    let mut world = world(DAY);
+    let scout = world.spawn((Position(3, 3), VisionRadius::new(6))).id();
+    let mut schedule = vision_and_sight();
+    schedule.run(&mut world);
+    let sees =
+        |world: &World, x, y| world.resource::<Visibility>().per_entity[&scout].contains(&(x, y));
+    assert!(sees(&world, 3, 9));
+
+    world.clear_trackers();
+    schedule.run(&mut world);
+    assert!(!world
+        .entity(scout)
+        .get_ref::<VisionRadius>()
+        .unwrap()
+        .is_changed());
+
+    world.insert_resource(Fog(true));
+    schedule.run(&mut world);
+    assert_eq!(radius(&world, scout), 2);
+    assert!(!sees(&world, 3, 9));
+    assert!(sees(&world, 3, 5));
+
+    // The base is what saves keep; modifiers come back from the world
+    let mut loaded = World::new();
+    load_world(save_world(&mut world), &mut loaded);
+    let mut q = loaded.query::<&VisionRadius>();
+    assert_eq!(q.single(&loaded).clone(), VisionRadius::new(6));
+}
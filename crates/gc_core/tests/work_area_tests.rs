@@ -0,0 +1,172 @@
+use bevy_ecs::prelude::*;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+
+fn world() -> World {
+    build_standard_world(40, 12, 5, WorldOptions::default())
+}
+
+fn job(n: u128, kind: JobKind) -> Job {
+    Job {
+        id: JobId(uuid::Uuid::from_u128(n)),
+        kind,
+        issued_by: IssuedBy::System,
+        faction: FactionKind::Goblins,
+        seq: 0,
+        priority: 0,
+        to_stockpile: false,
+    }
+}
+
+fn haul(n: u128, from: (i32, i32), to: (i32, i32)) -> Job {
+    job(n, JobKind::Haul { from, to })
+}
+
+fn assigned(world: &World, worker: Entity) -> Option<JobKind> {
+    let id = world.get::<AssignedJob>(worker)?.0?;
+    Some(world.resource::<ActiveJobs>().jobs.get(&id)?.kind.clone())
+}
+
+#[test]
+fn trips_count_every_leg_and_bounds_every_site() {
+    let far_haul = JobKind::Haul {
+        from: (3, 0),
+        to: (3, 6),
+    };
+    // 3 tiles to the item, 6 more to drop it off
+    assert!(WorkArea::within(9).reaches(Some((0, 0)), &far_haul));
+    assert!(!WorkArea::within(8).reaches(Some((0, 0)), &far_haul));
+    // Without a position only the bounds apply
+    assert!(WorkArea::within(0).reaches(None, &far_haul));
+
+    let area = WorkArea::inside(ZoneBounds::new(0, 0, 5, 5));
+    assert!(area.reaches(None, &JobKind::Mine { x: 5, y: 5 }));
+    assert!(!area.reaches(None, &far_haul), "drop-off lies outside");
+    assert!(WorkArea::UNLIMITED.reaches(Some((0, 0)), &far_haul));
+    assert_eq!(job_sites(&far_haul), vec![(3, 0), (3, 6)]);
+    assert!(job_sites(&JobKind::Unequip {
+        slot: EquipSlot::Weapon
+    })
+    .is_empty());
+}
+
+#[test]
+fn carriers_only_take_hauls_within_their_area() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(job_assignment_system);
+    world
+        .resource_mut::<JobBoard>()
+        .0
+        .extend([haul(1, (35, 5), (36, 5)), haul(2, (4, 5), (2, 5))]);
+    let near = world
+        .spawn((
+            Carrier,
+            AssignedJob::default(),
+            Position(1, 5),
+            WorkArea::within(10),
+        ))
+        .id();
+    schedule.run(&mut world);
+    // The far haul is first on the board but out of reach
+    assert_eq!(
+        assigned(&world, near),
+        Some(JobKind::Haul {
+            from: (4, 5),
+            to: (2, 5)
+        })
+    );
+
+    // The colony default applies to workers without an area of their own
+    world.insert_resource(WorkAreaConfig {
+        max_distance: Some(10),
+    });
+    let other = world
+        .spawn((Carrier, AssignedJob::default(), Position(1, 6)))
+        .id();
+    schedule.run(&mut world);
+    assert_eq!(assigned(&world, other), None);
+    assert_eq!(world.resource::<JobBoard>().0.len(), 1);
+
+    // ...but not to those with one
+    let roamer = world
+        .spawn((
+            Carrier,
+            AssignedJob::default(),
+            Position(1, 7),
+            WorkArea::UNLIMITED,
+        ))
+        .id();
+    schedule.run(&mut world);
+    assert!(assigned(&world, roamer).is_some());
+}
+
+#[test]
+fn other_job_kinds_respect_work_areas_too() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(build_job_assignment_system);
+    world
+        .resource_mut::<JobBoard>()
+        .0
+        .push(job(1, JobKind::Build { x: 30, y: 3 }));
+    let builder = world
+        .spawn((
+            Miner,
+            AssignedJob::default(),
+            Position(1, 1),
+            WorkArea::inside(ZoneBounds::new(0, 0, 10, 10)),
+        ))
+        .id();
+    schedule.run(&mut world);
+    assert_eq!(assigned(&world, builder), None);
+
+    world.get_mut::<WorkArea>(builder).unwrap().bounds = Some(ZoneBounds::new(0, 0, 39, 11));
+    schedule.run(&mut world);
+    assert_eq!(
+        assigned(&world, builder),
+        Some(JobKind::Build { x: 30, y: 3 })
+    );
+}
+
+#[test]
+fn jobs_out_of_every_workers_reach_are_announced() {
+    let mut world = world();
+    world.resource_mut::<StaffingConfig>().warn_after_ticks = 2;
+    world.insert_resource(WorkAreaConfig {
+        max_distance: Some(8),
+    });
+    let mut schedule = Schedule::default();
+    schedule.add_systems((unstaffed_jobs_system, advance_time).chain());
+    world.resource_mut::<JobBoard>().0.extend([
+        haul(1, (35, 5), (36, 5)),
+        haul(2, (30, 5), (36, 5)),
+        haul(3, (4, 5), (2, 5)),
+    ]);
+    world.spawn((Carrier, Position(1, 5)));
+
+    for _ in 0..3 {
+        schedule.run(&mut world);
+    }
+    let texts: Vec<_> = world
+        .resource::<Announcements>()
+        .iter()
+        .map(|a| (a.severity, a.text.clone()))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![(
+            Severity::Warning,
+            "No haulers in range: 2 haul jobs out of reach".to_string()
+        )]
+    );
+    let view = snapshot_world(&mut world);
+    assert_eq!(view.shortages.len(), 1);
+    assert!(view.shortages[0].out_of_reach);
+    assert_eq!(view.shortages[0].pending, 2);
+
+    // A carrier posted by the far end clears it
+    world.spawn((Carrier, Position(33, 5), WorkArea::within(20)));
+    schedule.run(&mut world);
+    assert_eq!(world.resource::<UnstaffedJobs>().alerts().count(), 0);
+}
@@ -0,0 +1,169 @@
+use bevy_ecs::prelude::*;
+use gc_core::jobs::{self, ItemSpawnQueue};
+use gc_core::prelude::*;
+use gc_core::save::{decode_json, encode_json, load_world_checked, save_world, IntegrityError};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+
+#[test]
+fn default_table_always_drops_stone_and_sometimes_a_gem() {
+    let table = YieldTable::default();
+    let finds = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..400)
+            .map(|_| table.roll(None, &mut rng))
+            .collect::<Vec<_>>()
+    };
+    let first = finds(7);
+    assert_eq!(first, finds(7));
+    assert!(first.iter().all(|d| d[0] == ItemType::Stone));
+    let gems = first.iter().filter(|d| d.contains(&ItemType::Gem)).count();
+    assert!((5..=40).contains(&gems), "{} gems in 400 digs", gems);
+}
+
+#[test]
+fn tables_load_from_json_and_hash_their_contents() {
+    let json = r#"{
+        "default": [{ "item": "Stone", "chance": 100 }],
+        "materials": { "igneous": [{ "item": "Gem", "chance": 100 }] }
+    }"#;
+    let table = YieldTable::from_json(json).unwrap();
+    assert_eq!(
+        table.entries(Some(Material::Igneous)),
+        &[YieldEntry::new(ItemType::Gem, 100)]
+    );
+    assert_eq!(
+        table.entries(Some(Material::Soil)),
+        &[YieldEntry::new(ItemType::Stone, 100)]
+    );
+    assert_eq!(
+        YieldTable::from_json(&table.to_json().unwrap()).unwrap(),
+        table
+    );
+    assert_eq!(table.hash(), table.clone().hash());
+    assert_ne!(table.hash(), YieldTable::default().hash());
+    assert!(YieldTable::from_json(r#"{"materials": {}}"#).is_err());
+}
+
+#[test]
+fn mining_spawns_every_drop_on_the_dug_tile() {
+    let mut world = World::new();
+    let mut map = GameMap::new(4, 4);
+    map.set_tile(2, 2, TileKind::Wall).unwrap();
+    world.insert_resource(map);
+    world.insert_resource(MaterialMap::filled(4, 4, Material::Igneous));
+    world.insert_resource(YieldTable {
+        default: vec![YieldEntry::new(ItemType::Stone, 100)],
+        materials: BTreeMap::from([(
+            Material::Igneous,
+            vec![
+                YieldEntry::new(ItemType::Stone, 100),
+                YieldEntry::new(ItemType::Gem, 100),
+            ],
+        )]),
+    });
+    world.insert_resource(DeterministicRng::new(1));
+    world.insert_resource(ItemSpawnQueue::default());
+    let mut active = ActiveJobs::default();
+    let id = JobId(uuid::Uuid::from_u128(1));
+    active.jobs.insert(
+        id,
+        Job {
+            id,
+            kind: JobKind::Mine { x: 2, y: 2 },
+            issued_by: IssuedBy::System,
+            faction: FactionKind::Goblins,
+            seq: 0,
+            priority: 0,
+            to_stockpile: false,
+        },
+    );
+    world.insert_resource(active);
+    world.spawn((Miner, Position(2, 1), AssignedJob(Some(id))));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            jobs::mine_job_execution_system,
+            jobs::process_item_spawn_queue_system,
+        )
+            .chain(),
+    );
+    schedule.run(&mut world);
+
+    let mut found: Vec<(ItemType, String, Position)> = world
+        .query::<(&Item, &Name, &Position)>()
+        .iter(&world)
+        .map(|(i, n, p)| (i.item_type, n.0.clone(), *p))
+        .collect();
+    found.sort_by_key(|(t, ..)| *t);
+    assert_eq!(
+        found,
+        vec![
+            (ItemType::Stone, "Granite".to_string(), Position(2, 2)),
+            (
+                ItemType::Gem,
+                "Rough igneous gem".to_string(),
+                Position(2, 2)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn saves_record_the_yield_table_hash() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        8,
+        8,
+        3,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    let save = save_world(&mut world);
+    let table = YieldTable::default();
+    assert_eq!(save.yield_table_hash, Some(table.hash()));
+    assert!(save.yields_compatible(&table));
+    let stingy = YieldTable {
+        default: vec![YieldEntry::new(ItemType::Stone, 100)],
+        ..Default::default()
+    };
+    assert!(!save.yields_compatible(&stingy));
+
+    // Saves from before yield tables fit any table
+    let mut old = decode_json(&encode_json(&save).unwrap()).unwrap();
+    old.yield_table_hash = None;
+    let old = decode_json(&encode_json(&old).unwrap()).unwrap();
+    assert!(old.yields_compatible(&stingy));
+}
+
+#[test]
+fn loading_restores_the_yield_table_and_rejects_a_swapped_one() {
+    let mut world = gc_core::bootstrap::build_standard_world(
+        8,
+        8,
+        3,
+        gc_core::bootstrap::WorldOptions::default(),
+    );
+    let stingy = YieldTable {
+        default: vec![YieldEntry::new(ItemType::Stone, 100)],
+        ..Default::default()
+    };
+    world.insert_resource(stingy.clone());
+    let save = save_world(&mut world);
+
+    let mut loaded = World::new();
+    load_world_checked(save.clone(), &mut loaded).unwrap();
+    assert_eq!(loaded.get_resource::<YieldTable>(), Some(&stingy));
+
+    // A table edited after saving no longer matches the recorded hash
+    let mut swapped = save;
+    swapped.yield_table = Some(YieldTable::default());
+    swapped.world_hash = None;
+    assert_eq!(
+        load_world_checked(swapped, &mut World::new()),
+        Err(IntegrityError::YieldsMismatch {
+            recorded: stingy.hash(),
+            actual: YieldTable::default().hash(),
+        })
+    );
+}
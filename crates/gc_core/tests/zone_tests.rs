@@ -0,0 +1,208 @@
+mod common;
+
+use bevy_ecs::prelude::*;
+use common::floor_world;
+use gc_core::bootstrap::build_default_schedule;
+use gc_core::prelude::*;
+use gc_core::save::{load_world, save_world};
+use gc_core::systems;
+
+fn world() -> World {
+    floor_world(20, 12, 5)
+}
+
+#[test]
+fn kinds_round_trip_through_their_labels() {
+    for kind in ZoneKind::ALL {
+        assert_eq!(ZoneKind::from_label(kind.label()), Some(kind));
+    }
+    assert_eq!(ZoneKind::from_label("barracks"), None);
+    let stockpile = StockpileBundle::new(0, 0, 1, 1);
+    assert_eq!(stockpile.kind, ZoneKind::Stockpile);
+}
+
+#[test]
+fn idle_workers_gather_in_the_meeting_zone() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn(ZoneBundle::new(ZoneKind::Meeting, 14, 4, 16, 6));
+    let idle = world
+        .spawn((Position(2, 5), Carrier, AssignedJob::default()))
+        .id();
+    let invader = world
+        .spawn((
+            Position(2, 8),
+            AssignedJob::default(),
+            Faction::new(FactionKind::Invaders),
+        ))
+        .id();
+
+    for _ in 0..30 {
+        schedule.run(&mut world);
+    }
+    // Stops at the zone's edge rather than walking to the center
+    assert_eq!(*world.get::<Position>(idle).unwrap(), Position(14, 5));
+    assert_eq!(*world.get::<Position>(invader).unwrap(), Position(2, 8));
+}
+
+#[test]
+fn crippled_workers_prefer_hospital_beds_then_hospital_floor() {
+    let mut world = world();
+    let mut schedule = build_default_schedule();
+    world.spawn(ZoneBundle::new(ZoneKind::Hospital, 12, 2, 13, 3));
+    world.spawn((Position(3, 5), Bed));
+    world.spawn((Position(13, 3), Bed));
+    let hurt = |world: &mut World, x| {
+        world
+            .spawn((
+                Position(x, 5),
+                Miner,
+                AssignedJob::default(),
+                Health::new(1, 10),
+            ))
+            .id()
+    };
+    let first = hurt(&mut world, 1);
+    let second = hurt(&mut world, 2);
+    let third = hurt(&mut world, 3);
+
+    schedule.run(&mut world);
+    let rest_spot = |world: &World, e| {
+        let id = world.get::<AssignedJob>(e).unwrap().0.unwrap();
+        world.resource::<ActiveJobs>().jobs[&id].kind.clone()
+    };
+    // The hospital bed goes first even though the other bed is nearer
+    assert_eq!(rest_spot(&world, first), JobKind::Rest { x: 13, y: 3 });
+    assert_eq!(rest_spot(&world, second), JobKind::Rest { x: 3, y: 5 });
+    // No beds left: the first free hospital tile
+    assert_eq!(rest_spot(&world, third), JobKind::Rest { x: 12, y: 2 });
+}
+
+#[test]
+fn unstorable_items_are_dumped_on_refuse() {
+    let mut world = world();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(systems::auto_haul_system);
+    let mut stockpile = StockpileBundle::new(0, 0, 1, 1);
+    stockpile.stockpile.accepts = Some(vec![ItemType::Weapon]);
+    world.spawn(stockpile);
+    world.spawn(ZoneBundle::new(ZoneKind::Refuse, 16, 8, 18, 10));
+    world.spawn((Item::stone(), Carriable, Position(5, 5)));
+    world.spawn((Item::stone(), Carriable, Position(17, 9)));
+    schedule.run(&mut world);
+
+    let hauls: Vec<JobKind> = world
+        .resource::<JobBoard>()
+        .0
+        .iter()
+        .map(|j| j.kind.clone())
+        .collect();
+    assert_eq!(
+        hauls,
+        vec![JobKind::Haul {
+            from: (5, 5),
+            to: (17, 9)
+        }]
+    );
+}
+
+#[test]
+fn zone_kinds_survive_save_and_load() {
+    let mut world = world();
+    world.spawn(ZoneBundle::new(ZoneKind::Hospital, 1, 1, 2, 2));
+    world.spawn(StockpileBundle::new(5, 5, 6, 6));
+    let save = save_world(&mut world);
+
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+    let mut zones: Vec<(ZoneKind, bool)> = loaded
+        .query::<(&ZoneKind, Has<Stockpile>)>()
+        .iter(&loaded)
+        .map(|(k, s)| (*k, s))
+        .collect();
+    zones.sort();
+    assert_eq!(
+        zones,
+        vec![(ZoneKind::Stockpile, true), (ZoneKind::Hospital, false)]
+    );
+}
+
+/// Kind, bounds and tag filter of a zone
+type ZoneRow = (ZoneKind, (i32, i32, i32, i32), Option<TagQuery>);
+
+fn zones(world: &mut World) -> Vec<ZoneRow> {
+    let mut q = world.query::<(&ZoneKind, &ZoneBounds, Option<&AcceptsTags>)>();
+    q.iter(world)
+        .map(|(k, b, a)| {
+            (
+                *k,
+                (b.min_x, b.min_y, b.max_x, b.max_y),
+                a.map(|a| a.0.clone()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn zone_commands_create_edit_and_remove_zones() {
+    let mut world = world();
+    let stone = TagQuery::parse("stone").unwrap();
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::Zone {
+            kind: ZoneKind::Stockpile,
+            a: (6, 5),
+            b: (3, 2),
+            accepts: Some(stone.clone()),
+        }
+    ));
+    assert_eq!(
+        zones(&mut world),
+        vec![(ZoneKind::Stockpile, (3, 2, 6, 5), Some(stone))]
+    );
+    let mut schedule = build_default_schedule();
+    schedule.run(&mut world);
+    let accepts = world.query::<&Stockpile>().single(&world).accepts.clone();
+    assert_eq!(accepts, Some(vec![ItemType::Stone]));
+
+    // Off the map, or filtering by a tag nothing carries
+    for rejected in [
+        WorldCommand::Zone {
+            kind: ZoneKind::Meeting,
+            a: (0, 0),
+            b: (20, 3),
+            accepts: None,
+        },
+        WorldCommand::Zone {
+            kind: ZoneKind::Stockpile,
+            a: (0, 0),
+            b: (1, 1),
+            accepts: Some(TagQuery::parse("cheese").unwrap()),
+        },
+        WorldCommand::RemoveZone { x: 10, y: 10 },
+    ] {
+        assert!(!apply_world_command(&mut world, &rejected), "{rejected:?}");
+    }
+
+    // Turning the stockpile into a refuse zone takes its storage away
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::EditZone {
+            x: 4,
+            y: 4,
+            kind: ZoneKind::Refuse,
+            accepts: None,
+        }
+    ));
+    assert_eq!(
+        zones(&mut world),
+        vec![(ZoneKind::Refuse, (3, 2, 6, 5), None)]
+    );
+    assert_eq!(world.query::<&Stockpile>().iter(&world).count(), 0);
+
+    assert!(apply_world_command(
+        &mut world,
+        &WorldCommand::RemoveZone { x: 6, y: 5 }
+    ));
+    assert!(zones(&mut world).is_empty());
+}
@@ -0,0 +1,72 @@
+//! TUI configuration file loading.
+//!
+//! The config is a small JSON document; every section is optional and falls
+//! back to defaults:
+//!
+//! ```json
+//! { "keymap": { "pause": ["p"] }, "palette": "deuteranopia" }
+//! ```
+//!
+//! `presets` holds named zone and designation settings (see
+//! [`gc_core::presets`]), checked against the default tag registry.
+//! `director` overrides raid tuning ([`DirectorConfig`]) field by field.
+
+use crate::keymap::{Action, Keymap};
+use crate::theme::Palette;
+use anyhow::{Context, Result};
+use gc_core::director::DirectorConfig;
+use gc_core::presets::Presets;
+use gc_core::tags::TagRegistry;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default config file looked up in the working directory by [`crate::run`].
+pub const DEFAULT_CONFIG_PATH: &str = "goblin-camp.json";
+
+/// On-disk config shape before key names are resolved.
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    keymap: BTreeMap<Action, Vec<String>>,
+    #[serde(default)]
+    palette: Palette,
+    #[serde(default)]
+    presets: Presets,
+    #[serde(default)]
+    director: DirectorConfig,
+}
+
+/// Resolved TUI configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuiConfig {
+    pub keymap: Keymap,
+    pub palette: Palette,
+    pub presets: Presets,
+    pub director: DirectorConfig,
+}
+
+impl TuiConfig {
+    /// Parse config JSON, applying overrides on top of the defaults.
+    pub fn from_json(s: &str) -> Result<Self> {
+        let raw: ConfigFile = serde_json::from_str(s).context("parsing TUI config")?;
+        raw.presets.validate(&TagRegistry::default())?;
+        Ok(Self {
+            keymap: Keymap::from_overrides(raw.keymap)?,
+            palette: raw.palette,
+            presets: raw.presets,
+            director: raw.director,
+        })
+    }
+
+    /// Load config from `path`; a missing file yields the defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        Self::from_json(&text)
+    }
+}
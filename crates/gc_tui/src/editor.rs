@@ -0,0 +1,212 @@
+//! Map editor mode.
+//!
+//! The editor works on a [`World`] whose schedule never runs: strokes apply
+//! [`gc_core::editor`] brushes at the cursor, and the save action writes the
+//! world as a JSON save that the game, screenshots, and tests can load. Number
+//! keys pick a brush; the stockpile brush takes two strokes, one per corner.
+
+use crate::keymap::{Action, Keymap};
+use crate::mode::{self, TuiMode};
+use crate::theme::Theme;
+use crate::{cleanup_terminal, draw_popup, inspect_line, render_cells, style_cells, RenderLayers};
+use anyhow::Result;
+use bevy_ecs::prelude::*;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::enable_raw_mode;
+use gc_core::bootstrap::{build_standard_world, WorldOptions};
+use gc_core::prelude::*;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    text::Text,
+    widgets::Paragraph,
+    Terminal,
+};
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+
+/// Editor UI state.
+#[derive(Debug, Clone)]
+pub struct EditorState {
+    pub cursor: (i32, i32),
+    pub brush: Brush,
+    /// First stockpile corner, waiting for the second stroke
+    pub anchor: Option<(i32, i32)>,
+    pub show_help: bool,
+    /// Where the save action writes the scenario
+    pub out: PathBuf,
+    /// Transient status message (e.g. saved path)
+    pub message: Option<String>,
+}
+
+impl EditorState {
+    pub fn new(out: impl Into<PathBuf>) -> Self {
+        Self {
+            cursor: (0, 0),
+            brush: Brush::ALL[0],
+            anchor: None,
+            show_help: false,
+            out: out.into(),
+            message: None,
+        }
+    }
+}
+
+/// Blank world for a new scenario: generated terrain, no population.
+pub fn editor_world(width: u32, height: u32, seed: u64) -> World {
+    build_standard_world(width, height, seed, WorldOptions::default())
+}
+
+/// Write the edited world as a save, in the format `path`'s extension names
+/// (JSON when it names none). The scenario it replaces is kept as a `.bak`.
+pub fn save_scenario(world: &mut World, path: impl AsRef<Path>) -> Result<()> {
+    write_save_file_with(path, &save_world(world), SaveWriteOptions { backup: true })?;
+    Ok(())
+}
+
+/// Handle one key press in the editor.
+///
+/// Returns `true` when the key requests leaving the editor. Kept free of
+/// terminal I/O so editing can be driven headlessly.
+pub fn editor_key(
+    world: &mut World,
+    state: &mut EditorState,
+    keymap: &Keymap,
+    code: KeyCode,
+) -> bool {
+    if let KeyCode::Char(d @ '1'..='9') = code {
+        state.brush = Brush::ALL[(d as u8 - b'1') as usize];
+        state.anchor = None;
+        return false;
+    }
+    let Some(action) = keymap.action_for(code) else {
+        return false;
+    };
+    let (w, h) = {
+        let map = world.resource::<GameMap>();
+        (map.width as i32, map.height as i32)
+    };
+    let mut move_cursor = |dx: i32, dy: i32| {
+        state.cursor.0 = (state.cursor.0 + dx).clamp(0, (w - 1).max(0));
+        state.cursor.1 = (state.cursor.1 + dy).clamp(0, (h - 1).max(0));
+    };
+    match action {
+        Action::Quit => return true,
+        Action::CursorUp => move_cursor(0, -1),
+        Action::CursorDown => move_cursor(0, 1),
+        Action::CursorLeft => move_cursor(-1, 0),
+        Action::CursorRight => move_cursor(1, 0),
+        Action::Designate => stroke(world, state),
+        Action::Cancel => {
+            if state.show_help {
+                state.show_help = false;
+            } else {
+                state.anchor = None;
+            }
+        }
+        Action::Save => {
+            state.message = Some(match save_scenario(world, &state.out) {
+                Ok(()) => format!("saved {}", state.out.display()),
+                Err(e) => format!("save failed: {}", e),
+            });
+        }
+        Action::Help => state.show_help = !state.show_help,
+        // Simulation and view controls have no meaning while editing
+        _ => {}
+    }
+    false
+}
+
+fn stroke(world: &mut World, state: &mut EditorState) {
+    let (x, y) = state.cursor;
+    if state.brush != Brush::Stockpile {
+        paint(world, x, y, state.brush);
+        return;
+    }
+    match state.anchor.take() {
+        None => state.anchor = Some((x, y)),
+        Some(corner) => {
+            place_stockpile(world, corner, (x, y));
+        }
+    }
+}
+
+/// Run the interactive editor on `world`, saving to `out` on request.
+pub fn run_editor(
+    mut world: World,
+    out: impl Into<PathBuf>,
+    config_path: impl AsRef<Path>,
+) -> Result<()> {
+    let config = crate::config::TuiConfig::load(config_path)?;
+    let keymap = config.keymap;
+    let theme = Theme::new(config.palette);
+    let mut state = EditorState::new(out);
+    let layers = RenderLayers {
+        visibility: false,
+        designations: true,
+        stockpiles: true,
+        units: true,
+        traffic: false,
+    };
+
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    loop {
+        // No simulation runs here, so a fresh snapshot per frame is cheap
+        let view = snapshot_world(&mut world);
+        let map = style_cells(render_cells(&view, layers, None), state.cursor, theme);
+        let hint = mode::hint_line(TuiMode::Edit, &keymap);
+        let mut status = format!(
+            "brush={}, cursor=({}, {})",
+            state.brush.label(),
+            state.cursor.0,
+            state.cursor.1
+        );
+        if let Some((x, y)) = state.anchor {
+            status.push_str(&format!(", corner=({}, {})", x, y));
+        }
+        status.push_str(" | ");
+        status.push_str(&inspect_line(&view, state.cursor));
+        if let Some(msg) = &state.message {
+            status.push_str(" | ");
+            status.push_str(msg);
+        }
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ])
+                .split(f.size());
+            let header = format!("Goblin Camp — Map editor ({})", state.out.display());
+            f.render_widget(Paragraph::new(Text::raw(header)), chunks[0]);
+            f.render_widget(Paragraph::new(map), chunks[1]);
+            f.render_widget(Paragraph::new(Text::raw(hint)), chunks[2]);
+            f.render_widget(Paragraph::new(Text::raw(status)), chunks[3]);
+            if state.show_help {
+                let mut lines = keymap.help_lines();
+                lines.extend(
+                    Brush::ALL
+                        .iter()
+                        .enumerate()
+                        .map(|(i, b)| format!("{:<12} brush: {}", i + 1, b.label())),
+                );
+                draw_popup(f, chunks[1], "Help", &lines);
+            }
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Press && editor_key(&mut world, &mut state, &keymap, key.code)
+        {
+            return cleanup_terminal();
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Remappable key bindings for the TUI input loop.
+//!
+//! Actions are decoupled from physical keys through a [`Keymap`] resource.
+//! Defaults mirror the original hardcoded bindings; a JSON config file can
+//! override any subset of actions, e.g.:
+//!
+//! ```json
+//! { "keymap": { "pause": ["p"], "cursor_up": ["k", "up"] } }
+//! ```
+
+use crate::config::TuiConfig;
+use anyhow::{anyhow, Result};
+use bevy_ecs::prelude::*;
+use crossterm::event::KeyCode;
+use gc_core::toggles::Subsystem;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A user-facing action the input handler can perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Pause,
+    Step,
+    ToggleVis,
+    ToggleTraffic,
+    ToggleEntities,
+    ToggleHistory,
+    Overview,
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    Designate,
+    DesignateArea,
+    ConfirmDig,
+    Forbid,
+    Unforbid,
+    Cancel,
+    Menu,
+    CyclePalette,
+    Screenshot,
+    Save,
+    Help,
+    RecordMacro,
+    PlayMacro,
+    Zone,
+    RemoveZone,
+    CyclePreset,
+    ToggleAutoHaul,
+    ToggleCombat,
+    ToggleNeeds,
+    ToggleWeather,
+}
+
+impl Action {
+    /// All actions in display order (used by the help screen).
+    pub const ALL: [Action; 32] = [
+        Action::Quit,
+        Action::Pause,
+        Action::Step,
+        Action::ToggleVis,
+        Action::ToggleTraffic,
+        Action::ToggleEntities,
+        Action::ToggleHistory,
+        Action::Overview,
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::Designate,
+        Action::DesignateArea,
+        Action::ConfirmDig,
+        Action::Forbid,
+        Action::Unforbid,
+        Action::Cancel,
+        Action::Menu,
+        Action::CyclePalette,
+        Action::Screenshot,
+        Action::Save,
+        Action::Help,
+        Action::RecordMacro,
+        Action::PlayMacro,
+        Action::Zone,
+        Action::RemoveZone,
+        Action::CyclePreset,
+        Action::ToggleAutoHaul,
+        Action::ToggleCombat,
+        Action::ToggleNeeds,
+        Action::ToggleWeather,
+    ];
+
+    /// Short human-readable description for help text.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Pause => "pause/resume",
+            Action::Step => "single step",
+            Action::ToggleVis => "toggle visibility overlay",
+            Action::ToggleTraffic => "toggle traffic heat map",
+            Action::ToggleEntities => "toggle entity counts and leak alerts",
+            Action::ToggleHistory => "toggle the recent history of whoever is under the cursor",
+            Action::Overview => {
+                "toggle the colony overview (left/right: sort column, designate: reverse)"
+            }
+            Action::CursorUp => "cursor up",
+            Action::CursorDown => "cursor down",
+            Action::CursorLeft => "cursor left",
+            Action::CursorRight => "cursor right",
+            Action::Designate => "enter designation mode / mark tile",
+            Action::DesignateArea => "mark a rectangle corner (designation mode)",
+            Action::ConfirmDig => "confirm digging next to water/lava (designation mode)",
+            Action::Forbid => "forbid the items under the cursor or in the area (designation mode)",
+            Action::Unforbid => {
+                "unforbid the items under the cursor or in the area (designation mode)"
+            }
+            Action::Cancel => "leave current mode",
+            Action::Menu => "toggle simulation menu",
+            Action::CyclePalette => "cycle color palette",
+            Action::Screenshot => "save map screenshot",
+            Action::Save => "save game",
+            Action::Help => "toggle help",
+            Action::RecordMacro => "start/stop recording a designation macro",
+            Action::PlayMacro => "replay the designation macro at the cursor",
+            Action::Zone => "enter zone mode / edit the zone under the cursor",
+            Action::RemoveZone => "remove the zone under the cursor (zone mode)",
+            Action::CyclePreset => "cycle the selection preset (designation/zone mode)",
+            Action::ToggleAutoHaul => "switch auto-haul off/on (menu)",
+            Action::ToggleCombat => "switch combat off/on (menu)",
+            Action::ToggleNeeds => "switch needs off/on (menu)",
+            Action::ToggleWeather => "switch weather off/on (menu)",
+        }
+    }
+
+    /// The subsystem a debug toggle action switches, if it is one.
+    pub fn subsystem(self) -> Option<Subsystem> {
+        match self {
+            Action::ToggleAutoHaul => Some(Subsystem::AutoHaul),
+            Action::ToggleCombat => Some(Subsystem::Combat),
+            Action::ToggleNeeds => Some(Subsystem::Needs),
+            Action::ToggleWeather => Some(Subsystem::Weather),
+            _ => None,
+        }
+    }
+}
+
+/// Mapping from actions to the keys that trigger them.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = BTreeMap::new();
+        bindings.insert(Action::Quit, vec![KeyCode::Char('q')]);
+        bindings.insert(Action::Pause, vec![KeyCode::Char(' ')]);
+        bindings.insert(Action::Step, vec![KeyCode::Char('.')]);
+        bindings.insert(Action::ToggleVis, vec![KeyCode::Char('v')]);
+        bindings.insert(Action::ToggleTraffic, vec![KeyCode::Char('t')]);
+        bindings.insert(Action::ToggleEntities, vec![KeyCode::Char('E')]);
+        bindings.insert(Action::ToggleHistory, vec![KeyCode::Char('i')]);
+        bindings.insert(Action::Overview, vec![KeyCode::Char('o')]);
+        bindings.insert(Action::CursorUp, vec![KeyCode::Up]);
+        bindings.insert(Action::CursorDown, vec![KeyCode::Down]);
+        bindings.insert(Action::CursorLeft, vec![KeyCode::Left]);
+        bindings.insert(Action::CursorRight, vec![KeyCode::Right]);
+        bindings.insert(Action::Designate, vec![KeyCode::Char('d'), KeyCode::Enter]);
+        bindings.insert(Action::DesignateArea, vec![KeyCode::Char('a')]);
+        bindings.insert(Action::ConfirmDig, vec![KeyCode::Char('y')]);
+        bindings.insert(Action::Forbid, vec![KeyCode::Char('f')]);
+        bindings.insert(Action::Unforbid, vec![KeyCode::Char('F')]);
+        bindings.insert(Action::Cancel, vec![KeyCode::Esc]);
+        bindings.insert(Action::Menu, vec![KeyCode::Char('m')]);
+        bindings.insert(Action::CyclePalette, vec![KeyCode::Char('c')]);
+        bindings.insert(Action::Screenshot, vec![KeyCode::Char('P')]);
+        bindings.insert(Action::Save, vec![KeyCode::Char('S')]);
+        bindings.insert(Action::Help, vec![KeyCode::Char('?')]);
+        bindings.insert(Action::RecordMacro, vec![KeyCode::Char('r')]);
+        bindings.insert(Action::PlayMacro, vec![KeyCode::Char('R')]);
+        bindings.insert(Action::Zone, vec![KeyCode::Char('z')]);
+        bindings.insert(Action::RemoveZone, vec![KeyCode::Char('x')]);
+        bindings.insert(Action::CyclePreset, vec![KeyCode::Char('p')]);
+        bindings.insert(Action::ToggleAutoHaul, vec![KeyCode::Char('H')]);
+        bindings.insert(Action::ToggleCombat, vec![KeyCode::Char('C')]);
+        bindings.insert(Action::ToggleNeeds, vec![KeyCode::Char('N')]);
+        bindings.insert(Action::ToggleWeather, vec![KeyCode::Char('W')]);
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Resolve a pressed key to its action, if bound.
+    ///
+    /// When a key is bound to several actions the first in [`Action::ALL`]
+    /// order wins, keeping resolution deterministic.
+    pub fn action_for(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&code))
+            .map(|(action, _)| *action)
+    }
+
+    /// Keys currently bound to `action` (empty if unbound).
+    pub fn keys_for(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Replace the keys bound to `action`.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.bindings.insert(action, keys);
+    }
+
+    /// Build a keymap from JSON config text, overriding defaults per action.
+    pub fn from_json(s: &str) -> Result<Self> {
+        Ok(TuiConfig::from_json(s)?.keymap)
+    }
+
+    /// Load a keymap from a config file; a missing file yields the defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(TuiConfig::load(path)?.keymap)
+    }
+
+    /// Apply per-action key name overrides on top of the defaults.
+    pub(crate) fn from_overrides(overrides: BTreeMap<Action, Vec<String>>) -> Result<Self> {
+        let mut keymap = Keymap::default();
+        for (action, names) in overrides {
+            let keys = names
+                .iter()
+                .map(|n| {
+                    parse_key(n).ok_or_else(|| anyhow!("unknown key '{}' for {:?}", n, action))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            keymap.bind(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// Compact label for the keys bound to `action` (e.g. `q` or `d/enter`).
+    pub fn label(&self, action: Action) -> String {
+        let keys: Vec<String> = self
+            .keys_for(action)
+            .iter()
+            .map(|k| key_label(*k))
+            .collect();
+        keys.join("/")
+    }
+
+    /// One line per action: `keys  description`, for the help screen.
+    pub fn help_lines(&self) -> Vec<String> {
+        Action::ALL
+            .iter()
+            .map(|&action| format!("{:<12} {}", self.label(action), action.description()))
+            .collect()
+    }
+}
+
+/// Parse a key name from config (`"q"`, `"space"`, `"esc"`, `"up"`, ...).
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let lower = name.to_ascii_lowercase();
+    let code = match lower.as_str() {
+        "space" => KeyCode::Char(' '),
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+    };
+    Some(code)
+}
+
+/// Display label for a key, the inverse of [`parse_key`].
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Esc => "esc".into(),
+        KeyCode::Enter => "enter".into(),
+        KeyCode::Tab => "tab".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Up => "up".into(),
+        KeyCode::Down => "down".into(),
+        KeyCode::Left => "left".into(),
+        KeyCode::Right => "right".into(),
+        other => format!("{:?}", other).to_ascii_lowercase(),
+    }
+}
@@ -9,20 +9,71 @@ use gc_core::fov;
 use gc_core::prelude::*;
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::Style,
-    text::Text,
-    widgets::Paragraph,
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
     Terminal,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{stdout, Stdout};
-use std::time::{Duration, Instant};
+use std::path::Path;
+use std::time::Duration;
+
+pub mod config;
+pub mod editor;
+pub mod keymap;
+pub mod macros;
+pub mod mode;
+pub mod overview;
+pub mod screenshot;
+pub mod theme;
+pub mod zones;
+
+use config::TuiConfig;
+use keymap::{Action, Keymap};
+use macros::DesignationMacro;
+use mode::TuiMode;
+use overview::Overview;
+use theme::{CellKind, Theme};
+use zones::{ZonePopup, ZoneTarget};
 
 pub struct AppState {
     pub paused: bool,
     pub steps_per_frame: u32,
     pub show_vis: bool,
+    /// Whether the traffic heat map is drawn over the map
+    pub show_traffic: bool,
+    /// Whether the keybinding help overlay is drawn over the map
+    pub show_help: bool,
+    /// Whether the entity telemetry panel is drawn over the map
+    pub show_entities: bool,
+    /// Whether the history of the entity under the cursor is drawn over
+    /// the map
+    pub show_history: bool,
+    /// Map cursor used for designations
+    pub cursor: (i32, i32),
+    /// First corner of an area designation, waiting for the second
+    pub anchor: Option<(i32, i32)>,
+    /// Current input mode (drives key handling and the hint bar)
+    pub mode: TuiMode,
+    /// Transient status message shown in the footer (e.g. screenshot path)
+    pub message: Option<String>,
+    /// Macro being recorded, if any
+    pub recording: Option<DesignationMacro>,
+    /// Last finished macro, replayed by [`Action::PlayMacro`]
+    pub recorded: Option<DesignationMacro>,
+    /// Kind/filter picker open in zone mode
+    pub zone_popup: Option<ZonePopup>,
+    /// Colony overview drawn over the map, while open
+    pub overview: Option<Overview>,
+    /// What the pending area designation would dig, while one is drawn
+    pub estimate: Option<DesignationEstimate>,
+    /// Presets from the config, picked with [`Action::CyclePreset`]
+    pub presets: Presets,
+    /// Name of the picked preset; applied to new zones in zone mode or new
+    /// designations in designation mode, whichever kind it is
+    pub preset: Option<String>,
 }
 
 impl Default for AppState {
@@ -31,6 +82,21 @@ impl Default for AppState {
             paused: false,
             steps_per_frame: 1,
             show_vis: false,
+            show_traffic: false,
+            show_help: false,
+            show_entities: false,
+            show_history: false,
+            cursor: (0, 0),
+            anchor: None,
+            mode: TuiMode::default(),
+            message: None,
+            recording: None,
+            recorded: None,
+            zone_popup: None,
+            overview: None,
+            estimate: None,
+            presets: Presets::default(),
+            preset: None,
         }
     }
 }
@@ -43,11 +109,13 @@ pub fn build_world(width: u32, height: u32, seed: u64) -> World {
         WorldOptions {
             populate_demo_scene: true,
             tick_ms: 100,
+            ..Default::default()
         },
     );
-    // Field of view and overlay cache are TUI responsibilities
+    // Field of view is a TUI responsibility
     world.insert_resource(fov::Visibility::default());
-    world.insert_resource(OverlayCache::default());
+    world.insert_resource(Keymap::default());
+    world.insert_resource(Theme::default());
     // Track a player agent for camera center; bootstrap guarantees a Miner exists
     let player = {
         let mut q = world.query_filtered::<Entity, With<Miner>>();
@@ -60,244 +128,1092 @@ pub fn build_world(width: u32, height: u32, seed: u64) -> World {
 }
 
 pub fn build_schedule() -> Schedule {
-    let mut schedule = core_build_default_schedule();
-    // Keep visibility up-to-date as entities move
-    schedule.add_systems(fov::compute_visibility_system);
-    schedule
-}
-
-/// Cache for the visibility overlay.
-///
-/// Holds the union of all entities' visible tiles so rendering can check
-/// visibility in O(1) per tile without recomputing per frame. The `dirty`
-/// flag indicates the cache must be recomputed (e.g., after sim updates or
-/// when the overlay toggle changes).
-#[derive(Resource, Default)]
-struct OverlayCache {
-    /// Union of all currently visible tiles across entities. Used by the
-    /// renderer for constant-time visibility checks per tile.
-    union_vis: HashSet<(i32, i32)>,
-    /// Marks that `union_vis` is stale and must be rebuilt before the next
-    /// render when the visibility overlay is enabled.
-    dirty: bool,
+    // Field of view is part of the core schedule
+    core_build_default_schedule()
 }
 
 /// Handle to the player agent entity for fast lookups during rendering.
 #[derive(Resource, Clone, Copy)]
 struct PlayerAgent(Entity);
 
-/// Get the `(x, y)` position for a specific entity if it exists.
-fn entity_position(world: &World, entity: Entity) -> Option<(i32, i32)> {
+/// Position of the tracked player agent, if the world has one.
+pub(crate) fn player_position(world: &World) -> Option<(i32, i32)> {
+    let agent = world.get_resource::<PlayerAgent>()?;
     world
-        .get_entity(entity)
+        .get_entity(agent.0)
         .and_then(|e| e.get::<Position>())
         .map(|pos| (pos.0, pos.1))
 }
 
-fn render_ascii_map(world: &World, show_vis: bool) -> String {
-    let map = world.resource::<GameMap>();
-    let cache = world.get_resource::<OverlayCache>();
+/// Id bits of the tracked player agent, for locating it in a [`WorldView`].
+fn player_id(world: &World) -> Option<u64> {
+    world.get_resource::<PlayerAgent>().map(|pa| pa.0.to_bits())
+}
 
-    // Query the actual agent position if present; fallback to center
-    let center = ((map.width as i32) / 2, (map.height as i32) / 2);
-    let agent_pos = world
-        .get_resource::<PlayerAgent>()
-        .and_then(|pa| entity_position(world, pa.0))
-        .unwrap_or(center);
+/// Optional layers drawn on top of the terrain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderLayers {
+    /// Highlight tiles visible to any entity
+    pub visibility: bool,
+    /// Mark tiles with mining designations
+    pub designations: bool,
+    /// Shade tiles inside stockpile zones
+    pub stockpiles: bool,
+    /// Draw workers and items (`m` miner, `c` carrier, `o` item)
+    pub units: bool,
+    /// Shade walked tiles by traffic, `1` (quiet) to `9` (busiest)
+    pub traffic: bool,
+}
 
-    // If overlay enabled, check cached union of visible tiles
-    let union_vis = if show_vis {
-        cache.map(|c| &c.union_vis)
+impl RenderLayers {
+    /// Only the visibility layer, matching the original map renderer.
+    pub fn vis_only(show_vis: bool) -> Self {
+        Self {
+            visibility: show_vis,
+            ..Default::default()
+        }
+    }
+}
+
+/// Classify every map cell into a glyph and a render category.
+///
+/// Shared by the plain ASCII renderer, the styled renderer, and screenshots
+/// so they always agree on what is drawn where. Glyphs come from the core
+/// draw list ([`extract_draw_list`]): each tile shows its topmost glyph
+/// among the layers enabled in `layers`, with the agent above everything
+/// and the traffic and visibility overlays above zones and terrain.
+fn render_cells(
+    view: &WorldView,
+    layers: RenderLayers,
+    agent_pos: Option<(i32, i32)>,
+) -> Vec<Vec<(char, CellKind)>> {
+    // The view's visible list is already the union across entities
+    let union_vis: HashSet<(i32, i32)> = if layers.visibility {
+        view.visible.iter().copied().collect()
     } else {
-        None
+        HashSet::new()
     };
 
-    let mut out = String::with_capacity((map.width * (map.height + 1)) as usize);
-    for y in 0..map.height as i32 {
-        for x in 0..map.width as i32 {
-            if (x, y) == agent_pos {
-                out.push('@');
-            } else {
-                // If visibility overlay enabled and this tile is visible by any entity, draw '*'
-                let visible = union_vis.map(|u| u.contains(&(x, y))).unwrap_or(false);
-                let ch = if visible {
-                    '*'
-                } else {
-                    match map.get_tile(x, y).unwrap_or(TileKind::Wall) {
-                        TileKind::Floor => '.',
-                        TileKind::Wall => '#',
-                        TileKind::Water => '~',
-                        TileKind::Lava => '^',
-                    }
-                };
-                out.push(ch);
+    let concealed: HashSet<(i32, i32)> = view.concealed.iter().copied().collect();
+    let traffic: HashMap<(i32, i32), u32> = if layers.traffic {
+        view.traffic.iter().copied().collect()
+    } else {
+        HashMap::new()
+    };
+    let busiest = traffic.values().copied().max().unwrap_or(0);
+    let draw = extract_draw_list(view);
+    let shown = |layer: RenderLayer| match layer {
+        RenderLayer::Unit | RenderLayer::Item => layers.units,
+        RenderLayer::Designation => layers.designations,
+        RenderLayer::Zone => layers.stockpiles,
+        RenderLayer::Terrain | RenderLayer::Fire => true,
+    };
+
+    let mut rows = Vec::with_capacity(view.height as usize);
+    for y in 0..view.height as i32 {
+        let mut row = Vec::with_capacity(view.width as usize);
+        for x in 0..view.width as i32 {
+            if Some((x, y)) == agent_pos {
+                row.push(('@', CellKind::Agent));
+                continue;
             }
+            let top = draw
+                .top(x, y, shown)
+                .unwrap_or_else(|| tile_glyph(TileKind::Wall));
+            // Overlays draw over zones and bare terrain: traffic as a heat
+            // digit, visibility as '*'
+            let overlaid = top.layer <= RenderLayer::Zone && !concealed.contains(&(x, y));
+            let heat = traffic
+                .get(&(x, y))
+                .and_then(|&n| char::from_digit(heat_level(n, busiest), 10));
+            let cell = if let (true, Some(digit)) = (overlaid, heat) {
+                (digit, CellKind::Traffic)
+            } else if overlaid && union_vis.contains(&(x, y)) {
+                ('*', CellKind::Visible)
+            } else {
+                (top.char, CellKind::from(top.color))
+            };
+            row.push(cell);
         }
+        rows.push(row);
+    }
+    rows
+}
+
+/// Agent marker position: the tracked agent, or the map center as a fallback.
+fn agent_or_center(view: &WorldView, agent: Option<(i32, i32)>) -> Option<(i32, i32)> {
+    agent.or(Some(((view.width as i32) / 2, (view.height as i32) / 2)))
+}
+
+/// Join classified cells into newline-terminated rows of glyphs.
+fn cells_to_string(cells: Vec<Vec<(char, CellKind)>>) -> String {
+    let mut out = String::with_capacity(cells.len() * (cells.first().map_or(0, Vec::len) + 1));
+    for row in cells {
+        out.extend(row.into_iter().map(|(ch, _)| ch));
         out.push('\n');
     }
     out
 }
 
+/// Render the map as styled text using the world's [`Theme`].
+///
+/// Convenience wrapper over [`render_view_styled`] for a live world.
+pub fn render_styled_map(
+    world: &mut World,
+    layers: RenderLayers,
+    cursor: (i32, i32),
+) -> Text<'static> {
+    let theme = world.get_resource::<Theme>().copied().unwrap_or_default();
+    let agent = player_position(world);
+    let view = snapshot_world(world);
+    render_view_styled(&view, layers, cursor, theme, agent)
+}
+
+/// Render a world snapshot as styled text.
+///
+/// The cursor cell is drawn as `X` with the theme's cursor style. Adjacent
+/// cells with the same category are merged into one span. Without an agent
+/// position the marker is drawn at the map center.
+pub fn render_view_styled(
+    view: &WorldView,
+    layers: RenderLayers,
+    cursor: (i32, i32),
+    theme: Theme,
+    agent: Option<(i32, i32)>,
+) -> Text<'static> {
+    style_cells(
+        render_cells(view, layers, agent_or_center(view, agent)),
+        cursor,
+        theme,
+    )
+}
+
+/// Shade the cells inside `selection` (inclusive corners, in any order).
+fn select_cells(
+    mut cells: Vec<Vec<(char, CellKind)>>,
+    selection: ((i32, i32), (i32, i32)),
+) -> Vec<Vec<(char, CellKind)>> {
+    let ((ax, ay), (bx, by)) = selection;
+    for (y, row) in cells.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let (x, y) = (x as i32, y as i32);
+            if (ax.min(bx)..=ax.max(bx)).contains(&x) && (ay.min(by)..=ay.max(by)).contains(&y) {
+                cell.1 = CellKind::Selection;
+            }
+        }
+    }
+    cells
+}
+
+/// Turn classified cells into styled lines, drawing the cursor as `X`.
+fn style_cells(
+    cells: Vec<Vec<(char, CellKind)>>,
+    cursor: (i32, i32),
+    theme: Theme,
+) -> Text<'static> {
+    let mut lines = Vec::with_capacity(cells.len());
+    for (y, row) in cells.into_iter().enumerate() {
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut run = String::new();
+        let mut run_kind: Option<CellKind> = None;
+        for (x, (ch, kind)) in row.into_iter().enumerate() {
+            let (ch, kind) = if (x as i32, y as i32) == cursor {
+                ('X', CellKind::Cursor)
+            } else {
+                (ch, kind)
+            };
+            if run_kind != Some(kind) {
+                if let Some(k) = run_kind {
+                    spans.push(Span::styled(std::mem::take(&mut run), theme.style(k)));
+                }
+                run_kind = Some(kind);
+            }
+            run.push(ch);
+        }
+        if let Some(k) = run_kind {
+            spans.push(Span::styled(run, theme.style(k)));
+        }
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
 /// Render the current world state to a deterministic ASCII map string.
 ///
 /// This is a thin public wrapper around the internal renderer, intended for
 /// snapshot testing and offline verification. It does not touch the terminal
 /// and is safe to call in unit/integration tests.
 pub fn render_ascii_snapshot(world: &mut World, show_vis: bool) -> String {
-    // Ensure overlay cache is prepared when requested
-    prepare_overlay_cache(world, show_vis);
-    render_ascii_map(world, show_vis)
+    let agent = player_position(world);
+    let view = snapshot_world(world);
+    cells_to_string(render_cells(
+        &view,
+        RenderLayers::vis_only(show_vis),
+        agent_or_center(&view, agent),
+    ))
+}
+
+/// UI-side state the draw call needs besides the snapshot itself.
+struct DrawContext<'a> {
+    keymap: &'a Keymap,
+    theme: Theme,
+    agent: Option<(i32, i32)>,
 }
 
 fn draw(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    world: &World,
+    view: &WorldView,
     app: &AppState,
+    ctx: &DrawContext,
 ) -> Result<()> {
-    let text = render_ascii_map(world, app.show_vis);
+    let layers = RenderLayers {
+        visibility: app.show_vis,
+        designations: true,
+        stockpiles: true,
+        units: false,
+        traffic: app.show_traffic,
+    };
+    let mut cells = render_cells(view, layers, agent_or_center(view, ctx.agent));
+    if let (TuiMode::Zone, Some(anchor)) = (app.mode, app.anchor) {
+        cells = select_cells(cells, (anchor, app.cursor));
+    }
+    let text = style_cells(cells, app.cursor, ctx.theme);
+    let keymap = ctx.keymap;
+    let hint = match (&app.zone_popup, &app.overview) {
+        (Some(_), _) => mode::zone_popup_hint(keymap),
+        (None, Some(_)) => mode::overview_hint(keymap),
+        (None, None) => mode::hint_line(app.mode, keymap),
+    };
     terminal.draw(|f| {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
+                Constraint::Length(1),
                 Constraint::Length(1),
                 Constraint::Min(0),
                 Constraint::Length(1),
+                Constraint::Length(1),
             ])
             .split(f.size());
 
-        let header = Paragraph::new(Text::raw(
-            "Goblin Camp — TUI (q:quit, space:pause, .:step, v:vis)",
-        ));
-        let body = Paragraph::new(Text::raw(text)).style(Style::default());
-        let footer = Paragraph::new(Text::raw(format!(
-            "paused={}, steps/frame={}, vis={}",
-            app.paused, app.steps_per_frame, app.show_vis
+        let header = Paragraph::new(Text::raw(format!(
+            "Goblin Camp — TUI ({}: help)",
+            keymap.label(Action::Help)
         )));
+        let alert = Paragraph::new(alert_line(view, ctx.theme));
+        let body = Paragraph::new(text).style(Style::default());
+        let hints = Paragraph::new(Text::raw(hint));
+        let mut status = format!(
+            "tick={}, paused={}, steps/frame={}, vis={}, cursor=({}, {}), palette={}",
+            view.tick,
+            app.paused,
+            app.steps_per_frame,
+            app.show_vis,
+            app.cursor.0,
+            app.cursor.1,
+            ctx.theme.palette.name()
+        );
+        if let Some((x, y)) = app.anchor {
+            status.push_str(&format!(", corner=({}, {})", x, y));
+        }
+        if let (Some(name), Some(_)) = (&app.preset, active_preset(app)) {
+            status.push_str(&format!(", preset={}", name));
+        }
+        if let Some(estimate) = &app.estimate {
+            status.push_str(&format!(", dig: {}", estimate));
+        }
+        if app.show_traffic {
+            let here = view
+                .traffic
+                .binary_search_by_key(&app.cursor, |&(pos, _)| pos)
+                .map_or(0, |i| view.traffic[i].1);
+            status.push_str(&format!(", traffic={}", here));
+        }
+        if !view.disabled.is_empty() {
+            let off: Vec<&str> = view.disabled.iter().map(|s| s.label()).collect();
+            status.push_str(&format!(", off={}", off.join("/")));
+        }
+        if let Some(recording) = &app.recording {
+            status.push_str(&format!(
+                ", recording macro ({} steps)",
+                recording.actions.len()
+            ));
+        }
+        status.push_str(" | ");
+        status.push_str(&inspect_line(view, app.cursor));
+        for shortage in &view.shortages {
+            status.push_str(&format!(
+                " | no {}{} ({} {} jobs)",
+                shortage.role.workers(),
+                if shortage.out_of_reach {
+                    " in range"
+                } else {
+                    ""
+                },
+                shortage.pending,
+                shortage.role.job()
+            ));
+        }
+        if let Some(msg) = &app.message {
+            status.push_str(" | ");
+            status.push_str(msg);
+        }
+        let footer = Paragraph::new(Text::raw(status));
 
         f.render_widget(header, chunks[0]);
-        f.render_widget(body, chunks[1]);
-        f.render_widget(footer, chunks[2]);
+        f.render_widget(alert, chunks[1]);
+        f.render_widget(body, chunks[2]);
+        f.render_widget(hints, chunks[3]);
+        f.render_widget(footer, chunks[4]);
+
+        // Popups are drawn last so they sit on top of the map
+        if app.mode == TuiMode::Menu {
+            draw_popup(
+                f,
+                chunks[2],
+                "Menu",
+                &mode::menu_lines(keymap, &view.disabled),
+            );
+        }
+        if let Some(popup) = &app.zone_popup {
+            draw_popup(f, chunks[2], popup.title(), &popup.lines());
+        }
+        if app.show_entities {
+            draw_popup(f, chunks[2], "Entities", &entity_panel_lines(view));
+        }
+        if app.show_history {
+            let (title, lines) = history_panel(view, app.cursor);
+            draw_popup(f, chunks[2], &title, &lines);
+        }
+        if let Some(overview) = &app.overview {
+            draw_popup(f, chunks[2], &overview.title(view), &overview.lines(view));
+        }
+        if app.show_help {
+            draw_popup(f, chunks[2], "Help", &keymap.help_lines());
+        }
     })?;
     Ok(())
 }
 
-fn run_frame(world: &mut World, schedule: &mut Schedule, app: &AppState) {
-    if !app.paused {
-        for _ in 0..app.steps_per_frame {
-            schedule.run(world);
-            mark_overlay_dirty(world);
+/// Alert bar: the most recent announcement, styled by severity.
+pub fn alert_line(view: &WorldView, theme: Theme) -> Line<'static> {
+    match view.announcements.last() {
+        Some(a) => Line::from(Span::styled(
+            format!(
+                "[{} t={}] {}",
+                a.severity.label().to_uppercase(),
+                a.tick,
+                a.text
+            ),
+            theme.severity_style(a.severity),
+        )),
+        None => Line::raw(""),
+    }
+}
+
+/// Inspect readout for the tile at `cursor`: terrain, material layer, the
+/// names of anything standing there, and the contents of a stockpile
+/// covering it.
+pub fn inspect_line(view: &WorldView, cursor: (i32, i32)) -> String {
+    let (x, y) = cursor;
+    let tile = view
+        .tile(x, y)
+        .map_or("?".to_string(), |t| format!("{:?}", t).to_lowercase());
+    let mut line = format!("tile={}", tile);
+    if let Some(material) = view.material(x, y) {
+        line.push_str(&format!(", material={}", material.label()));
+    }
+    let names: Vec<&str> = view
+        .entities_at(x, y)
+        .filter_map(|e| e.name.as_deref())
+        .collect();
+    if !names.is_empty() {
+        line.push_str(&format!(", here={}", names.join("/")));
+    }
+    if let Some((status, issued_by)) = view
+        .entities_at(x, y)
+        .find_map(|e| e.designation.as_deref().map(|s| (s, e.issued_by)))
+    {
+        line.push_str(&format!(", designation={}", status));
+        if let Some(issued_by) = issued_by {
+            line.push_str(&format!(" by {}", issued_by));
         }
     }
+    if let Some(stored) = view.entities.iter().find_map(|e| {
+        e.zone
+            .filter(|&(x0, y0, x1, y1)| (x0..=x1).contains(&x) && (y0..=y1).contains(&y))
+            .and(e.stored.as_ref())
+    }) {
+        let counts: Vec<String> = stored
+            .by_type
+            .iter()
+            .map(|(t, n)| format!("{} {}", n, format!("{:?}", t).to_lowercase()))
+            .collect();
+        let counts = if counts.is_empty() {
+            "empty".to_string()
+        } else {
+            counts.join(", ")
+        };
+        line.push_str(&format!(", stockpile={}", counts));
+    }
+    line
 }
 
-pub fn run(width: u32, height: u32, seed: u64) -> Result<()> {
-    // Terminal setup
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// Lines for the entity telemetry panel: live entities per category, then
+/// any categories growing past their leak limit.
+pub fn entity_panel_lines(view: &WorldView) -> Vec<String> {
+    let metrics = &view.metrics;
+    let mut lines: Vec<String> = metrics
+        .entity_counts
+        .iter()
+        .map(|(category, n)| format!("{:<22} {:>6}", category.label(), n))
+        .collect();
+    let total: u64 = metrics.entity_counts.iter().map(|&(_, n)| n).sum();
+    lines.push(format!("{:<22} {:>6}", "total", total));
+    if metrics.leaks.is_empty() {
+        lines.push("no leaks".to_string());
+    }
+    lines.extend(metrics.leaks.iter().map(|leak| format!("LEAK {leak}")));
+    lines
+}
 
-    // App state and world
-    let mut app = AppState::default();
-    let mut world = build_world(width, height, seed);
-    let mut schedule = build_schedule();
-    // Ensure initial visibility buffer is computed before first draw
-    schedule.run(&mut world);
-    mark_overlay_dirty(&mut world);
+/// Title and lines of the history panel: the recent events of the first
+/// entity at `cursor` that has any, or of the first named one there.
+pub fn history_panel(view: &WorldView, cursor: (i32, i32)) -> (String, Vec<String>) {
+    let (x, y) = cursor;
+    let subject = view
+        .entities_at(x, y)
+        .find(|e| !e.history.is_empty())
+        .or_else(|| view.entities_at(x, y).find(|e| e.name.is_some()));
+    match subject {
+        Some(e) => {
+            let name = e.name.clone().unwrap_or_else(|| format!("#{}", e.id));
+            let lines = if e.history.is_empty() {
+                vec!["nothing recent".to_string()]
+            } else {
+                e.history.clone()
+            };
+            (format!("History: {}", name), lines)
+        }
+        None => ("History".to_string(), vec!["nobody here".to_string()]),
+    }
+}
 
-    // Main loop
-    let tick = Duration::from_millis(16);
-    let mut last = Instant::now();
-    loop {
-        // Prepare overlay cache before drawing
-        prepare_overlay_cache(&mut world, app.show_vis);
-        // Draw
-        draw(&mut terminal, &world, &app)?;
+/// Draw a bordered popup listing `lines`, centered inside `area`.
+fn draw_popup(f: &mut ratatui::Frame, area: Rect, title: &str, lines: &[String]) {
+    let w = (lines.iter().map(|l| l.len()).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let h = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - w) / 2,
+        y: area.y + (area.height - h) / 2,
+        width: w,
+        height: h,
+    };
+    let body = Paragraph::new(Text::raw(lines.join("\n")))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, popup);
+    f.render_widget(body, popup);
+}
 
-        // Input
-        while event::poll(Duration::from_millis(0))? {
-            match event::read()? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        // Exit
-                        cleanup_terminal()?;
-                        return Ok(());
-                    }
-                    KeyCode::Char(' ') => app.paused = !app.paused,
-                    KeyCode::Char('.') => {
-                        // Single step: run the schedule once without changing paused state
-                        schedule.run(&mut world);
-                        mark_overlay_dirty(&mut world);
-                    }
-                    KeyCode::Char('v') => {
-                        // Toggle visibility overlay
-                        app.show_vis = !app.show_vis;
-                        mark_overlay_dirty(&mut world);
-                    }
-                    KeyCode::Char(d @ '1'..='9') => {
-                        let n = (d as u8 - b'0') as u32;
-                        app.steps_per_frame = n.max(1);
+/// What the simulation side must do after an input action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionEffect {
+    /// Handled entirely in UI state
+    None,
+    /// Exit the TUI
+    Quit,
+    /// Forward to the simulation
+    Sim(SimCommand),
+    /// Capture the current map to a file
+    Screenshot,
+    /// Forward several commands at once, in order
+    Batch(Vec<SimCommand>),
+    /// A macro recording finished; persist it
+    MacroRecorded(DesignationMacro),
+    /// Open the zone popup for the zones at (x, y); needs a snapshot
+    EditZone { x: i32, y: i32 },
+}
+
+/// Update UI state for `action` and report the simulation-side effect.
+///
+/// Does not touch the world, so the same input handling drives both the
+/// threaded TUI and the headless [`apply_action`] helper.
+pub fn interpret_action(
+    app: &mut AppState,
+    theme: &mut Theme,
+    map_size: (u32, u32),
+    action: Action,
+) -> ActionEffect {
+    if let Some(popup) = app.zone_popup.as_mut() {
+        match action {
+            Action::CursorUp => popup.move_row(-1),
+            Action::CursorDown => popup.move_row(1),
+            Action::Designate => {
+                if let Some(command) = popup.select() {
+                    app.zone_popup = None;
+                    return ActionEffect::Sim(SimCommand::World(command));
+                }
+            }
+            Action::Cancel => app.zone_popup = None,
+            Action::Quit => return ActionEffect::Quit,
+            _ => {}
+        }
+        return ActionEffect::None;
+    }
+    // The overview takes the cursor and designate keys; the rest still
+    // reach the map, so the simulation can be paused or stepped under it
+    let overview_key = matches!(
+        action,
+        Action::CursorLeft
+            | Action::CursorRight
+            | Action::CursorUp
+            | Action::CursorDown
+            | Action::Designate
+            | Action::Overview
+            | Action::Cancel
+    );
+    if let (Some(overview), true) = (app.overview.as_mut(), overview_key) {
+        match action {
+            Action::CursorLeft => overview.move_column(-1),
+            Action::CursorRight => overview.move_column(1),
+            Action::CursorUp => overview.scroll_by(-1),
+            Action::CursorDown => overview.scroll_by(1),
+            Action::Designate => overview.reverse(),
+            _ => app.overview = None,
+        }
+        return ActionEffect::None;
+    }
+    if let Some(recording) = app.recording.as_mut() {
+        if DesignationMacro::records(action) {
+            recording.actions.push(action);
+        }
+    }
+    let (w, h) = (map_size.0 as i32, map_size.1 as i32);
+    let mut move_cursor = |dx: i32, dy: i32| {
+        app.cursor.0 = (app.cursor.0 + dx).clamp(0, (w - 1).max(0));
+        app.cursor.1 = (app.cursor.1 + dy).clamp(0, (h - 1).max(0));
+    };
+    match action {
+        Action::Quit => return ActionEffect::Quit,
+        Action::Pause => {
+            app.paused = !app.paused;
+            return ActionEffect::Sim(if app.paused {
+                SimCommand::Pause
+            } else {
+                SimCommand::Resume
+            });
+        }
+        // Single step: run the schedule once without changing paused state
+        Action::Step => return ActionEffect::Sim(SimCommand::Step(1)),
+        Action::ToggleVis => app.show_vis = !app.show_vis,
+        Action::ToggleTraffic => app.show_traffic = !app.show_traffic,
+        Action::ToggleEntities => app.show_entities = !app.show_entities,
+        Action::ToggleHistory => app.show_history = !app.show_history,
+        Action::Overview => app.overview = Some(Overview::default()),
+        Action::CursorUp => move_cursor(0, -1),
+        Action::CursorDown => move_cursor(0, 1),
+        Action::CursorLeft => move_cursor(-1, 0),
+        Action::CursorRight => move_cursor(1, 0),
+        Action::Designate => match app.mode {
+            TuiMode::Designate => {
+                let (x, y) = app.cursor;
+                if let Some(preset) = active_preset(app) {
+                    return preset_effect(preset, app.cursor, app.cursor);
+                }
+                return ActionEffect::Sim(SimCommand::World(WorldCommand::Designate { x, y }));
+            }
+            TuiMode::Zone => match app.anchor.take() {
+                None => app.anchor = Some(app.cursor),
+                // A zone preset skips the kind/filter popup
+                Some(a) => match active_preset(app) {
+                    Some(preset) => return preset_effect(preset, a, app.cursor),
+                    None => {
+                        app.zone_popup = Some(ZonePopup::new(ZoneTarget::New { a, b: app.cursor }));
                     }
-                    _ => {}
                 },
-                Event::Resize(_, _) => {
-                    // No-op; next draw will adapt to the new size
+            },
+            _ => app.mode = TuiMode::Designate,
+        },
+        Action::DesignateArea if app.mode == TuiMode::Designate => match app.anchor.take() {
+            None => app.anchor = Some(app.cursor),
+            Some(a) => {
+                if let Some(preset) = active_preset(app) {
+                    return preset_effect(preset, a, app.cursor);
                 }
-                _ => {}
+                return ActionEffect::Sim(SimCommand::World(WorldCommand::DesignateRect {
+                    a,
+                    b: app.cursor,
+                }));
             }
+        },
+        Action::DesignateArea => {}
+        // Confirm hazardous designations under the cursor, or across the
+        // pending area
+        Action::ConfirmDig if app.mode == TuiMode::Designate => {
+            let a = app.anchor.take().unwrap_or(app.cursor);
+            return ActionEffect::Sim(SimCommand::World(WorldCommand::ConfirmDesignations {
+                a,
+                b: app.cursor,
+            }));
         }
-
-        // Tick
-        if last.elapsed() >= tick {
-            run_frame(&mut world, &mut schedule, &app);
-            last = Instant::now();
+        Action::ConfirmDig => {}
+        // Forbid or unforbid the items under the cursor, or across the
+        // pending area
+        Action::Forbid | Action::Unforbid if app.mode == TuiMode::Designate => {
+            let a = app.anchor.take().unwrap_or(app.cursor);
+            let b = app.cursor;
+            return ActionEffect::Sim(SimCommand::World(if action == Action::Forbid {
+                WorldCommand::ForbidItems { a, b }
+            } else {
+                WorldCommand::UnforbidItems { a, b }
+            }));
+        }
+        Action::Forbid | Action::Unforbid => {}
+        Action::Cancel => {
+            // Close the help overlay first, then drop a pending area
+            // corner, then fall back to cursor mode
+            if app.show_help {
+                app.show_help = false;
+            } else if app.anchor.is_some() {
+                app.anchor = None;
+            } else {
+                app.mode = TuiMode::Cursor;
+            }
+        }
+        Action::Menu => {
+            app.mode = if app.mode == TuiMode::Menu {
+                TuiMode::Cursor
+            } else {
+                TuiMode::Menu
+            };
+        }
+        // Zone presses are not recorded, so a macro could not replay them
+        Action::Zone if app.recording.is_some() => {
+            app.message = Some("stop recording before drawing zones".into());
+        }
+        Action::Zone if app.mode == TuiMode::Zone => {
+            let (x, y) = app.cursor;
+            return ActionEffect::EditZone { x, y };
+        }
+        Action::Zone => {
+            app.anchor = None;
+            app.mode = TuiMode::Zone;
+        }
+        Action::RemoveZone if app.mode == TuiMode::Zone => {
+            let (x, y) = app.cursor;
+            return ActionEffect::Sim(SimCommand::World(WorldCommand::RemoveZone { x, y }));
+        }
+        Action::RemoveZone => {}
+        Action::CyclePreset if matches!(app.mode, TuiMode::Designate | TuiMode::Zone) => {
+            cycle_preset(app);
+        }
+        Action::CyclePreset => {}
+        Action::ToggleAutoHaul
+        | Action::ToggleCombat
+        | Action::ToggleNeeds
+        | Action::ToggleWeather => {
+            if let (TuiMode::Menu, Some(subsystem)) = (app.mode, action.subsystem()) {
+                return ActionEffect::Sim(SimCommand::ToggleSubsystem(subsystem));
+            }
+        }
+        Action::CyclePalette => theme.palette = theme.palette.next(),
+        Action::Screenshot => return ActionEffect::Screenshot,
+        Action::Save => return ActionEffect::Sim(SimCommand::Save),
+        Action::Help => app.show_help = !app.show_help,
+        // Recording starts in designation mode so replay can assume it
+        Action::RecordMacro => match app.recording.take() {
+            None => {
+                app.recording = Some(DesignationMacro::default());
+                app.anchor = None;
+                app.mode = TuiMode::Designate;
+            }
+            Some(recorded) if recorded.is_empty() => {}
+            Some(recorded) => {
+                app.recorded = Some(recorded.clone());
+                return ActionEffect::MacroRecorded(recorded);
+            }
+        },
+        // Replaying while recording would nest the macro in itself
+        Action::PlayMacro if app.recording.is_some() => {}
+        Action::PlayMacro => {
+            let Some(recorded) = &app.recorded else {
+                app.message = Some("no macro recorded".into());
+                return ActionEffect::None;
+            };
+            let replay = recorded.replay(app.cursor, map_size);
+            app.cursor = replay.cursor;
+            return ActionEffect::Batch(
+                replay.commands.into_iter().map(SimCommand::World).collect(),
+            );
         }
     }
+    ActionEffect::None
 }
 
-fn prepare_overlay_cache(world: &mut World, show_vis: bool) {
-    // If the overlay is disabled, nothing to do. Leaving the cache as-is is
-    // intentional so toggling back on is instant unless marked dirty.
-    if !show_vis {
-        return;
-    }
-    // Check dirtiness without taking a mutable borrow yet
-    if let Some(cache) = world.get_resource::<OverlayCache>() {
-        if !cache.dirty {
-            return;
+/// The picked preset, if it is the kind the current mode creates.
+pub fn active_preset(app: &AppState) -> Option<&Preset> {
+    let preset = app.presets.get(app.preset.as_deref()?).ok()?;
+    let wanted = match app.mode {
+        TuiMode::Zone => true,
+        TuiMode::Designate => false,
+        _ => return None,
+    };
+    (preset.is_zone() == wanted).then_some(preset)
+}
+
+/// Pick the next preset of the kind the current mode creates, then none.
+fn cycle_preset(app: &mut AppState) {
+    let zone = app.mode == TuiMode::Zone;
+    let names: Vec<&str> = app
+        .presets
+        .iter()
+        .filter(|(_, preset)| preset.is_zone() == zone)
+        .map(|(name, _)| name)
+        .collect();
+    let at = app
+        .preset
+        .as_deref()
+        .and_then(|current| names.iter().position(|&n| n == current));
+    let next = match at {
+        None => names.first(),
+        Some(i) => names.get(i + 1),
+    };
+    app.preset = next.map(|name| name.to_string());
+    app.message = Some(match (next, active_preset(app)) {
+        (Some(name), Some(preset)) => format!("preset {name}: {preset}"),
+        _ if names.is_empty() => "no presets for this mode".into(),
+        _ => "no preset".into(),
+    });
+}
+
+fn preset_effect(preset: &Preset, a: (i32, i32), b: (i32, i32)) -> ActionEffect {
+    ActionEffect::Batch(
+        preset
+            .commands(a, b)
+            .into_iter()
+            .map(SimCommand::World)
+            .collect(),
+    )
+}
+
+/// Area a pending mining designation would cover: from the first corner
+/// to the cursor, in designation mode.
+pub fn preview_bounds(app: &AppState) -> Option<ZoneBounds> {
+    match (app.mode, app.anchor) {
+        (TuiMode::Designate, Some((ax, ay))) => {
+            let (cx, cy) = app.cursor;
+            Some(ZoneBounds::new(
+                ax.min(cx),
+                ay.min(cy),
+                ax.max(cx),
+                ay.max(cy),
+            ))
         }
+        _ => None,
     }
+}
 
-    // Build the union in a local buffer first
-    let mut union: HashSet<(i32, i32)> = HashSet::new();
-    if let Some(vis) = world.get_resource::<fov::Visibility>() {
-        for set in vis.per_entity.values() {
-            // Extend the union with all points from this entity's visibility set
-            union.extend(set.iter().copied());
+/// Open the zone popup for the newest zone covering (x, y) in `view`.
+///
+/// Leaves a status message instead when there is no zone there.
+pub fn open_zone_editor(app: &mut AppState, view: &WorldView, x: i32, y: i32) {
+    match zones::zone_at(view, x, y) {
+        Some(zone) => {
+            let kind = zone.zone_kind.unwrap_or_default();
+            app.zone_popup = Some(ZonePopup::edit(x, y, kind, zone.accepts_tags.as_ref()));
         }
+        None => app.message = Some("no zone here".into()),
     }
+}
 
-    // Now update the cache
-    if let Some(mut cache) = world.get_resource_mut::<OverlayCache>() {
-        cache.union_vis = union;
-        cache.dirty = false;
-    } else {
-        // Handle the missing-cache case gracefully by inserting a fresh cache.
-        world.insert_resource(OverlayCache {
-            union_vis: union,
-            dirty: false,
-        });
+/// Layers captured by in-game screenshots: what the map view shows.
+fn screenshot_layers(app: &AppState) -> RenderLayers {
+    RenderLayers {
+        visibility: app.show_vis,
+        designations: true,
+        stockpiles: true,
+        units: false,
+        traffic: app.show_traffic,
+    }
+}
+
+/// Status message after writing a screenshot of `view`.
+fn take_screenshot(
+    view: &WorldView,
+    app: &AppState,
+    theme: Theme,
+    agent: Option<(i32, i32)>,
+) -> String {
+    let path = format!("screenshot-{}.txt", view.tick);
+    match screenshot::save_view_screenshot(view, screenshot_layers(app), theme, agent, &path) {
+        Ok(()) => format!("saved {}", path),
+        Err(e) => format!("screenshot failed: {}", e),
     }
 }
 
-/// Mark the overlay cache as needing recomputation on the next draw.
+/// Status message after writing a save received from the simulation.
+fn write_save(save: &SaveGame) -> String {
+    let path = format!("save-{}.json", save.ticks);
+    match gc_core::save::write_save_file(&path, save) {
+        Ok(()) => format!("saved {}", path),
+        Err(e) => format!("save failed: {}", e),
+    }
+}
+
+/// Apply a mapped input action directly to a world on the caller's thread.
 ///
-/// Call this after simulation steps or input toggles that can change which
-/// tiles are visible, so `prepare_overlay_cache` knows to rebuild the union.
-fn mark_overlay_dirty(world: &mut World) {
-    if let Some(mut cache) = world.get_resource_mut::<OverlayCache>() {
-        cache.dirty = true;
+/// Returns `true` when the action requests exiting the TUI. Kept separate
+/// from terminal handling so input behavior can be tested headlessly.
+/// Simulation commands are applied immediately instead of at the next tick
+/// boundary of a simulation thread.
+pub fn apply_action(
+    world: &mut World,
+    schedule: &mut Schedule,
+    app: &mut AppState,
+    action: Action,
+) -> bool {
+    let map_size = {
+        let map = world.resource::<GameMap>();
+        (map.width, map.height)
+    };
+    let mut theme = world.get_resource::<Theme>().copied().unwrap_or_default();
+    let effect = interpret_action(app, &mut theme, map_size, action);
+    if let Some(mut t) = world.get_resource_mut::<Theme>() {
+        *t = theme;
+    }
+    match effect {
+        ActionEffect::None => {}
+        ActionEffect::Quit => return true,
+        ActionEffect::Sim(SimCommand::World(command)) => {
+            apply_world_command(world, &command);
+        }
+        ActionEffect::Batch(commands) => {
+            for command in commands {
+                if let SimCommand::World(command) = command {
+                    apply_world_command(world, &command);
+                }
+            }
+        }
+        // Headless input has no config directory to write to
+        ActionEffect::MacroRecorded(recorded) => {
+            app.message = Some(format!("macro recorded ({} steps)", recorded.actions.len()));
+        }
+        ActionEffect::Sim(SimCommand::Step(n)) => {
+            for _ in 0..n {
+                schedule.run(world);
+            }
+        }
+        ActionEffect::Sim(SimCommand::Save) => {
+            app.message = Some(write_save(&save_world(world)));
+        }
+        ActionEffect::Sim(SimCommand::ToggleSubsystem(subsystem)) => {
+            world
+                .get_resource_or_insert_with(SystemToggles::default)
+                .toggle(subsystem);
+        }
+        // Pause state lives in `app`; there is no thread to notify
+        ActionEffect::Sim(_) => {}
+        ActionEffect::Screenshot => {
+            let agent = player_position(world);
+            let view = snapshot_world(world);
+            app.message = Some(take_screenshot(&view, app, theme, agent));
+        }
+        ActionEffect::EditZone { x, y } => {
+            let view = snapshot_world(world);
+            open_zone_editor(app, &view, x, y);
+        }
+    }
+    app.estimate = preview_bounds(app).map(|bounds| estimate_designation(world, &bounds));
+    if let Some(overview) = app.overview.as_mut() {
+        overview.fit(colony_summary(world).len());
+    }
+    false
+}
+
+pub fn run(width: u32, height: u32, seed: u64) -> Result<()> {
+    run_with_config(
+        width,
+        height,
+        seed,
+        config::DEFAULT_CONFIG_PATH,
+        SystemToggles::default(),
+        None,
+    )
+}
+
+/// Run the TUI, loading key bindings and palette from the given config file (if present).
+///
+/// `toggles` sets which subsystems start switched off; the menu flips them.
+/// A `tutorial` is set up on the generated map before the first tick, and
+/// its hints arrive as announcements in the alert bar.
+///
+/// The simulation runs on its own thread (see [`gc_core::sim_thread`]); the
+/// UI thread only renders the latest snapshot and forwards commands, so slow
+/// ticks never delay input handling or drawing.
+pub fn run_with_config(
+    width: u32,
+    height: u32,
+    seed: u64,
+    config_path: impl AsRef<Path>,
+    toggles: SystemToggles,
+    tutorial: Option<Tutorial>,
+) -> Result<()> {
+    // Load config before touching the terminal so errors print cleanly
+    let config = TuiConfig::load(&config_path)?;
+    let keymap = config.keymap;
+    let mut theme = Theme::new(config.palette);
+    let macro_file = macros::macro_path(&config_path);
+
+    // A broken macro file loses the macro, not the session
+    let (recorded, macro_warning) = DesignationMacro::load_or_warn(&macro_file);
+    if let Some(warning) = &macro_warning {
+        eprintln!("warning: {warning}");
+    }
+
+    // App state and world
+    let mut app = AppState {
+        recorded,
+        presets: config.presets,
+        message: macro_warning,
+        ..Default::default()
+    };
+    let mut world = build_world(width, height, seed);
+    world.insert_resource(toggles);
+    world.insert_resource(config.director);
+    if let Some(tutorial) = tutorial {
+        start_tutorial(&mut world, tutorial)?;
+    }
+    let mut schedule = build_schedule();
+    debug_validate_schedule(&mut schedule, &mut world);
+    // Start the cursor on the tracked player agent
+    if let Some(pos) = player_position(&world) {
+        app.cursor = pos;
+    }
+    let player = player_id(&world);
+    // Ensure initial visibility buffer is computed before first draw
+    schedule.run(&mut world);
+
+    let sim = spawn_sim_thread(
+        world,
+        schedule,
+        SimThreadConfig {
+            tick_interval: Duration::from_millis(16),
+            ..Default::default()
+        },
+    );
+    let mut view = loop {
+        match sim.recv_timeout(Duration::from_secs(5)) {
+            Some(SimEvent::Snapshot(v)) => break v,
+            Some(_) => continue,
+            None => anyhow::bail!("simulation thread produced no snapshot"),
+        }
+    };
+
+    // Terminal setup
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Main loop
+    let frame = Duration::from_millis(16);
+    // Area the last estimate was requested for
+    let mut previewed: Option<ZoneBounds> = None;
+    loop {
+        for event in sim.drain() {
+            match event {
+                SimEvent::Snapshot(v) => view = v,
+                SimEvent::Saved(save) => app.message = Some(write_save(&save)),
+                // Replies to areas the cursor has since left are stale
+                SimEvent::Estimate { bounds, estimate } => {
+                    if preview_bounds(&app).as_ref() == Some(&bounds) {
+                        app.estimate = Some(estimate);
+                    }
+                }
+                SimEvent::Paused(p) => app.paused = p,
+                SimEvent::Stopped { .. } => {}
+            }
+        }
+        if let Some(overview) = app.overview.as_mut() {
+            overview.fit(view.colony.len());
+        }
+
+        // Draw
+        let agent = player.and_then(|id| view.entity(id)).and_then(|e| e.pos);
+        let ctx = DrawContext {
+            keymap: &keymap,
+            theme,
+            agent,
+        };
+        draw(&mut terminal, &view, &app, &ctx)?;
+
+        // Input; polling doubles as the frame pacing
+        if !event::poll(frame)? {
+            continue;
+        }
+        while event::poll(Duration::from_millis(0))? {
+            let Event::Key(key) = event::read()? else {
+                // Resize and other events: next draw adapts
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let effect = if let Some(action) = keymap.action_for(key.code) {
+                interpret_action(&mut app, &mut theme, (view.width, view.height), action)
+            } else if let KeyCode::Char(d @ '1'..='9') = key.code {
+                app.steps_per_frame = (d as u8 - b'0') as u32;
+                ActionEffect::Sim(SimCommand::SetSpeed(app.steps_per_frame))
+            } else {
+                ActionEffect::None
+            };
+            match effect {
+                ActionEffect::None => {}
+                ActionEffect::Quit => {
+                    cleanup_terminal()?;
+                    sim.shutdown();
+                    return Ok(());
+                }
+                ActionEffect::Sim(command) => {
+                    if let Err(e) = sim.send(command) {
+                        app.message = Some(e.to_string());
+                    }
+                }
+                ActionEffect::Screenshot => {
+                    app.message = Some(take_screenshot(&view, &app, theme, agent));
+                }
+                ActionEffect::EditZone { x, y } => open_zone_editor(&mut app, &view, x, y),
+                ActionEffect::Batch(commands) => {
+                    for command in commands {
+                        if let Err(e) = sim.send(command) {
+                            app.message = Some(e.to_string());
+                            break;
+                        }
+                    }
+                }
+                ActionEffect::MacroRecorded(recorded) => {
+                    app.message = Some(match recorded.save(&macro_file) {
+                        Ok(()) => format!(
+                            "macro recorded ({} steps), saved {}",
+                            recorded.actions.len(),
+                            macro_file.display()
+                        ),
+                        Err(e) => format!("macro not saved: {:#}", e),
+                    });
+                }
+            }
+        }
+        let bounds = preview_bounds(&app);
+        if bounds != previewed {
+            app.estimate = None;
+            if let Some(bounds) = &bounds {
+                if let Err(e) = sim.send(SimCommand::Estimate(bounds.clone())) {
+                    app.message = Some(e.to_string());
+                }
+            }
+            previewed = bounds;
+        }
     }
 }
 
@@ -0,0 +1,118 @@
+//! Designation macros: record a dig pattern once, replay it anywhere.
+//!
+//! While recording, every cursor movement and designation command is
+//! captured as its [`Action`]. Replaying feeds the same actions through
+//! [`interpret_action`] on a scratch [`AppState`] whose cursor starts at the
+//! new anchor, so a macro reproduces the pattern relative to wherever the
+//! cursor is, clipped at the map edges like live input.
+//!
+//! The last recorded macro is kept next to the TUI config file (see
+//! [`macro_path`]) so it survives restarts.
+
+use crate::keymap::Action;
+use crate::mode::TuiMode;
+use crate::theme::Theme;
+use crate::{interpret_action, ActionEffect, AppState};
+use anyhow::{Context, Result};
+use gc_core::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File name of the saved macro, in the config file's directory.
+pub const MACRO_FILE: &str = "goblin-camp-macro.json";
+
+/// A recorded sequence of cursor and designation actions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesignationMacro {
+    pub actions: Vec<Action>,
+}
+
+/// Result of replaying a macro.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// Designation commands, in recorded order
+    pub commands: Vec<WorldCommand>,
+    /// Where the cursor ended up
+    pub cursor: (i32, i32),
+}
+
+impl DesignationMacro {
+    /// Whether `action` is captured while recording.
+    pub fn records(action: Action) -> bool {
+        matches!(
+            action,
+            Action::CursorUp
+                | Action::CursorDown
+                | Action::CursorLeft
+                | Action::CursorRight
+                | Action::Designate
+                | Action::DesignateArea
+                | Action::Cancel
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Replay the macro with the cursor starting at `anchor`.
+    ///
+    /// Replay starts in designation mode, as recording does.
+    pub fn replay(&self, anchor: (i32, i32), map_size: (u32, u32)) -> Replay {
+        let mut scratch = AppState {
+            cursor: anchor,
+            mode: TuiMode::Designate,
+            ..Default::default()
+        };
+        let mut theme = Theme::default();
+        let mut commands = Vec::new();
+        for &action in &self.actions {
+            if let ActionEffect::Sim(SimCommand::World(command)) =
+                interpret_action(&mut scratch, &mut theme, map_size, action)
+            {
+                commands.push(command);
+            }
+        }
+        Replay {
+            commands,
+            cursor: scratch.cursor,
+        }
+    }
+
+    /// Load a macro from `path`; a missing file yields `None`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading macro {}", path.display()))?;
+        let parsed = serde_json::from_str(&text)
+            .with_context(|| format!("parsing macro {}", path.display()))?;
+        Ok(Some(parsed))
+    }
+
+    /// [`load`](Self::load), turning a broken file into no macro and a
+    /// warning to show, so startup carries on without it.
+    pub fn load_or_warn(path: impl AsRef<Path>) -> (Option<Self>, Option<String>) {
+        match Self::load(path) {
+            Ok(recorded) => (recorded, None),
+            Err(e) => (None, Some(format!("ignoring macro: {e:#}"))),
+        }
+    }
+
+    /// Write the macro to `path` as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self).context("encoding macro")?;
+        std::fs::write(path, json).with_context(|| format!("writing macro {}", path.display()))
+    }
+}
+
+/// Where the macro for the config file at `config_path` is stored.
+pub fn macro_path(config_path: impl AsRef<Path>) -> PathBuf {
+    config_path
+        .as_ref()
+        .parent()
+        .map_or_else(|| PathBuf::from(MACRO_FILE), |dir| dir.join(MACRO_FILE))
+}
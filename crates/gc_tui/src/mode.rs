@@ -0,0 +1,237 @@
+//! TUI input modes and the contextual hint bar.
+//!
+//! The TUI is a small state machine: the cursor mode is the resting state,
+//! designation mode marks tiles under the cursor, zone mode draws and edits
+//! zones, and the menu lists simulation controls. The hint bar text is derived from the active mode and
+//! the current [`Keymap`], so remapped keys are always shown correctly.
+
+use crate::keymap::{Action, Keymap};
+use gc_core::toggles::Subsystem;
+
+/// Current input mode of the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TuiMode {
+    /// Free cursor movement; the default mode
+    #[default]
+    Cursor,
+    /// Cursor movement plus marking tiles for mining
+    Designate,
+    /// Simulation control menu shown over the map
+    Menu,
+    /// Map editor: paint tiles and place entities (simulation stopped)
+    Edit,
+    /// Drawing, editing and removing zones
+    Zone,
+}
+
+impl TuiMode {
+    /// Short label shown in the status line.
+    pub fn label(self) -> &'static str {
+        match self {
+            TuiMode::Cursor => "cursor",
+            TuiMode::Designate => "designate",
+            TuiMode::Menu => "menu",
+            TuiMode::Edit => "edit",
+            TuiMode::Zone => "zone",
+        }
+    }
+
+    /// Actions worth advertising in this mode, in display order.
+    fn hinted_actions(self) -> &'static [Action] {
+        match self {
+            TuiMode::Cursor => &[
+                Action::Designate,
+                Action::Zone,
+                Action::ToggleHistory,
+                Action::Overview,
+                Action::Menu,
+                Action::Help,
+                Action::Quit,
+            ],
+            TuiMode::Designate => &[
+                Action::Designate,
+                Action::DesignateArea,
+                Action::ConfirmDig,
+                Action::Forbid,
+                Action::Unforbid,
+                Action::CyclePreset,
+                Action::RecordMacro,
+                Action::PlayMacro,
+                Action::Cancel,
+                Action::Help,
+            ],
+            TuiMode::Menu => &[
+                Action::Pause,
+                Action::Step,
+                Action::ToggleVis,
+                Action::ToggleTraffic,
+                Action::ToggleEntities,
+                Action::CyclePalette,
+                Action::Screenshot,
+                Action::Save,
+                Action::Menu,
+                Action::Cancel,
+            ],
+            TuiMode::Edit => &[
+                Action::Designate,
+                Action::Cancel,
+                Action::Save,
+                Action::Help,
+                Action::Quit,
+            ],
+            TuiMode::Zone => &[
+                Action::Designate,
+                Action::Zone,
+                Action::RemoveZone,
+                Action::CyclePreset,
+                Action::Cancel,
+                Action::Help,
+            ],
+        }
+    }
+}
+
+/// Short verb describing what `action` does in `mode`.
+fn hint_verb(mode: TuiMode, action: Action) -> &'static str {
+    match (mode, action) {
+        (TuiMode::Cursor, Action::Designate) => "designate",
+        (TuiMode::Designate, Action::Designate) => "mark tile",
+        (TuiMode::Designate, Action::DesignateArea) => "area corner",
+        (TuiMode::Designate, Action::ConfirmDig) => "confirm",
+        (TuiMode::Designate, Action::Forbid) => "forbid",
+        (TuiMode::Designate, Action::Unforbid) => "unforbid",
+        (TuiMode::Designate, Action::Cancel) => "done",
+        (TuiMode::Edit, Action::Designate) => "paint",
+        (TuiMode::Edit, Action::Cancel) => "clear corner",
+        (TuiMode::Zone, Action::Designate) => "corner",
+        (TuiMode::Zone, Action::Zone) => "edit",
+        (TuiMode::Zone, Action::RemoveZone) => "remove",
+        (TuiMode::Zone, Action::Cancel) => "done",
+        (_, Action::Zone) => "zones",
+        (TuiMode::Menu, Action::Menu) | (TuiMode::Menu, Action::Cancel) => "close",
+        (_, Action::Menu) => "menu",
+        (_, Action::Help) => "help",
+        (_, Action::Quit) => "quit",
+        (_, Action::Pause) => "pause",
+        (_, Action::Step) => "step",
+        (_, Action::ToggleVis) => "vis",
+        (_, Action::ToggleTraffic) => "traffic",
+        (_, Action::ToggleEntities) => "entities",
+        (_, Action::ToggleHistory) => "history",
+        (_, Action::Overview) => "overview",
+        (_, Action::CyclePalette) => "palette",
+        (_, Action::Screenshot) => "screenshot",
+        (_, Action::Save) => "save",
+        (_, Action::RecordMacro) => "record",
+        (_, Action::PlayMacro) => "replay",
+        (_, Action::CyclePreset) => "preset",
+        _ => action.description(),
+    }
+}
+
+/// Label for the four cursor actions, collapsing the default arrow keys.
+fn movement_label(keymap: &Keymap) -> String {
+    let defaults = Keymap::default();
+    let cursor = [
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::CursorLeft,
+        Action::CursorRight,
+    ];
+    if cursor
+        .iter()
+        .all(|&a| keymap.keys_for(a) == defaults.keys_for(a))
+    {
+        "arrows".to_string()
+    } else {
+        let labels: Vec<String> = cursor.iter().map(|&a| keymap.label(a)).collect();
+        labels.join(",")
+    }
+}
+
+/// One-line hint bar for the given mode, e.g. `[cursor] arrows: move  d: designate`.
+pub fn hint_line(mode: TuiMode, keymap: &Keymap) -> String {
+    let mut parts = Vec::new();
+    match mode {
+        TuiMode::Menu => parts.push("1-9: speed".to_string()),
+        TuiMode::Edit => {
+            parts.push(format!("{}: move", movement_label(keymap)));
+            parts.push("1-9: brush".to_string());
+        }
+        _ => parts.push(format!("{}: move", movement_label(keymap))),
+    }
+    for &action in mode.hinted_actions() {
+        let keys = keymap.label(action);
+        if !keys.is_empty() {
+            parts.push(format!("{}: {}", keys, hint_verb(mode, action)));
+        }
+    }
+    format!("[{}] {}", mode.label(), parts.join("  "))
+}
+
+/// Hint bar while the zone kind/filter popup is open.
+pub fn zone_popup_hint(keymap: &Keymap) -> String {
+    format!(
+        "[zone] {}/{}: row  {}: choose  {}: close",
+        keymap.label(Action::CursorUp),
+        keymap.label(Action::CursorDown),
+        keymap.label(Action::Designate),
+        keymap.label(Action::Cancel)
+    )
+}
+
+/// Hint bar while the colony overview is open.
+pub fn overview_hint(keymap: &Keymap) -> String {
+    format!(
+        "[overview] {}/{}: sort column  {}: reverse  {}/{}: scroll  {}: close",
+        keymap.label(Action::CursorLeft),
+        keymap.label(Action::CursorRight),
+        keymap.label(Action::Designate),
+        keymap.label(Action::CursorUp),
+        keymap.label(Action::CursorDown),
+        keymap.label(Action::Overview)
+    )
+}
+
+/// Lines for the simulation menu popup, with the debug subsystem switches
+/// and whether each is on (`disabled` lists those switched off).
+pub fn menu_lines(keymap: &Keymap, disabled: &[Subsystem]) -> Vec<String> {
+    let switches = [
+        Action::ToggleAutoHaul,
+        Action::ToggleCombat,
+        Action::ToggleNeeds,
+        Action::ToggleWeather,
+    ]
+    .into_iter()
+    .filter_map(move |a| {
+        let subsystem = a.subsystem()?;
+        let state = if disabled.contains(&subsystem) {
+            "off"
+        } else {
+            "on"
+        };
+        Some(format!(
+            "{:<12} {}: {}",
+            keymap.label(a),
+            subsystem.label(),
+            state
+        ))
+    });
+    [
+        Action::Pause,
+        Action::Step,
+        Action::ToggleVis,
+        Action::ToggleTraffic,
+        Action::ToggleEntities,
+        Action::CyclePalette,
+        Action::Screenshot,
+    ]
+    .iter()
+    .map(|&a| format!("{:<12} {}", keymap.label(a), a.description()))
+    .chain(std::iter::once(format!(
+        "{:<12} set steps per frame",
+        "1-9"
+    )))
+    .chain(switches)
+    .collect()
+}
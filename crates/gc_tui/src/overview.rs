@@ -0,0 +1,119 @@
+//! Colony overview: every goblin in one sortable table.
+//!
+//! The overview key opens an [`Overview`] over the map, listing the
+//! snapshot's colony summary (see [`colony_summary`]) with a column each
+//! for job, hunger, fatigue, mood, health and location. While it is open,
+//! left/right pick the sort column, the designate key reverses the order and
+//! up/down scroll; values the colony does not track yet show as `-`.
+
+use gc_core::prelude::*;
+
+/// Width of the name column; longer names are cut
+const NAME_WIDTH: usize = 16;
+
+/// Sort and scroll state of the open overview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Overview {
+    /// Column the rows are ordered by
+    pub column: SummaryColumn,
+    /// Largest first instead of smallest first
+    pub descending: bool,
+    /// Rows scrolled past at the top
+    pub scroll: usize,
+}
+
+impl Overview {
+    /// Sort by the column `step` places to the right, wrapping around.
+    pub fn move_column(&mut self, step: i32) {
+        self.column = self.column.shift(step);
+        self.scroll = 0;
+    }
+
+    /// Reverse the sort order.
+    pub fn reverse(&mut self) {
+        self.descending = !self.descending;
+        self.scroll = 0;
+    }
+
+    /// Scroll by `step` rows; see [`fit`](Self::fit) for the far end.
+    pub fn scroll_by(&mut self, step: i32) {
+        self.scroll = self.scroll.saturating_add_signed(step as isize);
+    }
+
+    /// Keep at least one of `rows` in view after scrolling or a shrinking
+    /// colony.
+    pub fn fit(&mut self, rows: usize) {
+        self.scroll = self.scroll.min(rows.saturating_sub(1));
+    }
+
+    /// Popup title naming the sort, e.g. `Colony (3) by mood, descending`.
+    pub fn title(&self, view: &WorldView) -> String {
+        format!(
+            "Colony ({}) by {}, {}",
+            view.colony.len(),
+            self.column.label(),
+            if self.descending {
+                "descending"
+            } else {
+                "ascending"
+            }
+        )
+    }
+
+    /// Header line then one line per goblin, sorted and scrolled.
+    pub fn lines(&self, view: &WorldView) -> Vec<String> {
+        let mut rows = view.colony.clone();
+        sort_summary(&mut rows, self.column, self.descending);
+        let headings: Vec<String> = SummaryColumn::ALL
+            .iter()
+            .map(|&c| {
+                if c == self.column {
+                    format!("{}{}", c.label(), if self.descending { "v" } else { "^" })
+                } else {
+                    c.label().to_string()
+                }
+            })
+            .collect();
+        let mut lines = vec![table_line(&headings)];
+        if rows.is_empty() {
+            lines.push("no goblins".to_string());
+        }
+        lines.extend(
+            rows.iter()
+                .skip(self.scroll)
+                .map(|row| table_line(&cells(row))),
+        );
+        lines
+    }
+}
+
+/// A row's cells, in [`SummaryColumn::ALL`] order.
+fn cells(row: &GoblinSummary) -> Vec<String> {
+    let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+    // Needs run 0..=1000; shown as percent
+    let need = |v: Option<u16>| or_dash(v.map(|n| format!("{}%", n / 10)));
+    vec![
+        row.name.chars().take(NAME_WIDTH).collect(),
+        or_dash(row.job.clone()),
+        need(row.hunger),
+        need(row.fatigue),
+        or_dash(row.mood.map(|m| m.to_string())),
+        or_dash(row.health.map(|(hp, max)| format!("{}/{}", hp, max))),
+        or_dash(row.pos.map(|(x, y)| format!("{},{}", x, y))),
+    ]
+}
+
+/// Pad cells into fixed-width columns.
+fn table_line(cells: &[String]) -> String {
+    format!(
+        "{:<name$} {:<10} {:>7} {:>8} {:>6} {:>7} {:>9}",
+        cells[0],
+        cells[1],
+        cells[2],
+        cells[3],
+        cells[4],
+        cells[5],
+        cells[6],
+        name = NAME_WIDTH
+    )
+}
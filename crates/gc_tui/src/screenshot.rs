@@ -0,0 +1,235 @@
+//! Map screenshots for sharing bug reports.
+//!
+//! Screenshots reuse the TUI cell classification, so a text screenshot is
+//! exactly what the map view shows (minus the cursor). PNG output rasterizes
+//! each cell as a solid square colored from the active [`Theme`]; the encoder
+//! is a minimal uncompressed PNG writer so no image dependency is needed.
+
+use crate::theme::{CellKind, Theme};
+use crate::{cells_to_string, player_position, render_cells, RenderLayers};
+use anyhow::{Context, Result};
+use bevy_ecs::prelude::*;
+use gc_core::fov;
+use gc_core::save::{load_world, SaveGame};
+use gc_core::view::{snapshot_world, WorldView};
+use ratatui::style::Color;
+use std::path::Path;
+
+/// Output encoding for a screenshot file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    /// Plain ASCII, one map row per line
+    Text,
+    /// RGB PNG with one colored square per cell
+    Png,
+}
+
+impl ScreenshotFormat {
+    /// Pick the format from a file extension (`.png` => PNG, otherwise text).
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => ScreenshotFormat::Png,
+            _ => ScreenshotFormat::Text,
+        }
+    }
+}
+
+/// Pixel size of one map cell in PNG screenshots.
+pub const PNG_CELL_PX: u32 = 4;
+
+/// Load a save into a fresh world with visibility computed, ready to capture.
+pub fn world_from_save(save: SaveGame) -> World {
+    let mut world = World::new();
+    load_world(save, &mut world);
+    world.insert_resource(fov::Visibility::default());
+    let mut schedule = Schedule::default();
+    schedule.add_systems(fov::compute_visibility_system);
+    schedule.run(&mut world);
+    world
+}
+
+/// Render the world as ASCII text with the requested overlay layers.
+pub fn render_text(world: &mut World, layers: RenderLayers) -> String {
+    let agent = player_position(world);
+    render_view_text(&snapshot_world(world), layers, agent)
+}
+
+/// Render the world as PNG bytes with the requested overlay layers.
+pub fn render_png(world: &mut World, layers: RenderLayers) -> Vec<u8> {
+    let theme = world.get_resource::<Theme>().copied().unwrap_or_default();
+    let agent = player_position(world);
+    render_view_png(&snapshot_world(world), layers, theme, agent)
+}
+
+/// Write a screenshot to `path`, choosing text or PNG by extension.
+pub fn save_screenshot(
+    world: &mut World,
+    layers: RenderLayers,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let theme = world.get_resource::<Theme>().copied().unwrap_or_default();
+    let agent = player_position(world);
+    save_view_screenshot(&snapshot_world(world), layers, theme, agent, path)
+}
+
+/// Render a snapshot as ASCII text; `agent` marks the tracked agent, if any.
+pub fn render_view_text(
+    view: &WorldView,
+    layers: RenderLayers,
+    agent: Option<(i32, i32)>,
+) -> String {
+    cells_to_string(render_cells(view, layers, agent))
+}
+
+/// Render a snapshot as PNG bytes colored from `theme`.
+pub fn render_view_png(
+    view: &WorldView,
+    layers: RenderLayers,
+    theme: Theme,
+    agent: Option<(i32, i32)>,
+) -> Vec<u8> {
+    let cells = render_cells(view, layers, agent);
+    let rows = cells.len() as u32;
+    let cols = cells.first().map_or(0, Vec::len) as u32;
+    let (w, h) = (cols * PNG_CELL_PX, rows * PNG_CELL_PX);
+
+    // Scanlines: filter byte 0 followed by RGB triples
+    let mut raw = Vec::with_capacity(((w * 3 + 1) * h) as usize);
+    for row in &cells {
+        let mut line = Vec::with_capacity((w * 3 + 1) as usize);
+        line.push(0);
+        for &(_, kind) in row {
+            let rgb = cell_rgb(&theme, kind);
+            for _ in 0..PNG_CELL_PX {
+                line.extend_from_slice(&rgb);
+            }
+        }
+        for _ in 0..PNG_CELL_PX {
+            raw.extend_from_slice(&line);
+        }
+    }
+    encode_png(w, h, &raw)
+}
+
+/// Write a snapshot screenshot to `path`, choosing text or PNG by extension.
+pub fn save_view_screenshot(
+    view: &WorldView,
+    layers: RenderLayers,
+    theme: Theme,
+    agent: Option<(i32, i32)>,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let bytes = match ScreenshotFormat::from_path(path) {
+        ScreenshotFormat::Text => render_view_text(view, layers, agent).into_bytes(),
+        ScreenshotFormat::Png => render_view_png(view, layers, theme, agent),
+    };
+    std::fs::write(path, bytes).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Solid color for a cell; palettes without hues fall back to gray levels.
+fn cell_rgb(theme: &Theme, kind: CellKind) -> [u8; 3] {
+    let style = theme.style(kind);
+    match style.bg.or(style.fg) {
+        Some(color) => color_rgb(color),
+        None => match kind {
+            CellKind::Floor => [40, 40, 40],
+            CellKind::Water => [90, 90, 90],
+            CellKind::Zone => [110, 110, 110],
+            CellKind::Stockpile => [130, 130, 130],
+            CellKind::Visible => [170, 170, 170],
+            CellKind::Wall => [210, 210, 210],
+            CellKind::Lava
+            | CellKind::Traffic
+            | CellKind::Designation
+            | CellKind::Agent
+            | CellKind::Unit
+            | CellKind::Selection
+            | CellKind::Cursor => [255, 255, 255],
+        },
+    }
+}
+
+/// Approximate RGB for terminal colors.
+fn color_rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+        Color::LightBlue => [59, 142, 234],
+        Color::Gray => [204, 204, 204],
+        Color::DarkGray => [102, 102, 102],
+        Color::White => [229, 229, 229],
+        _ => [128, 128, 128],
+    }
+}
+
+/// Encode 8-bit RGB scanlines (already prefixed with filter bytes) as PNG.
+fn encode_png(width: u32, height: u32, raw: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8, color type 2 (RGB), deflate, no filter method, no interlace
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(&mut out, b"IDAT", &zlib_stored(raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wrap data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut chunks = data.chunks(u16::MAX as usize).peekable();
+    if chunks.peek().is_none() {
+        // Empty input still needs one final stored block
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(u8::from(last));
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
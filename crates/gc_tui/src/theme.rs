@@ -0,0 +1,173 @@
+//! Render palettes for the styled TUI map.
+//!
+//! Every styled cell is classified into a [`CellKind`] and looked up through
+//! the [`Theme`] resource, so colors live in one table per [`Palette`] instead
+//! of being scattered through the renderer. Palettes can be chosen in the
+//! config file (`"palette": "deuteranopia"`) or cycled at runtime.
+
+use bevy_ecs::prelude::*;
+use gc_core::announcements::Severity;
+use gc_core::render::GlyphColor;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Category of a rendered map cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Floor,
+    Wall,
+    Water,
+    Lava,
+    /// The tracked player agent
+    Agent,
+    /// Other workers and items (editor view)
+    Unit,
+    /// Tile highlighted by the visibility overlay
+    Visible,
+    /// Tile shaded by the traffic heat map
+    Traffic,
+    /// Tile marked with a mining designation
+    Designation,
+    /// Tile inside a stockpile zone
+    Stockpile,
+    /// Tile inside a zone without storage (refuse, meeting, ...)
+    Zone,
+    /// Tile inside the rectangle being drawn in zone mode
+    Selection,
+    /// The map cursor
+    Cursor,
+}
+
+impl From<GlyphColor> for CellKind {
+    fn from(color: GlyphColor) -> Self {
+        match color {
+            GlyphColor::Floor => CellKind::Floor,
+            GlyphColor::Wall => CellKind::Wall,
+            GlyphColor::Water => CellKind::Water,
+            GlyphColor::Lava => CellKind::Lava,
+            GlyphColor::Unit => CellKind::Unit,
+            GlyphColor::Designation => CellKind::Designation,
+            GlyphColor::Stockpile => CellKind::Stockpile,
+            GlyphColor::Zone => CellKind::Zone,
+        }
+    }
+}
+
+/// Selectable color palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// Original full-color palette
+    #[default]
+    Default,
+    /// Okabe-Ito inspired colors that avoid red/green contrast
+    Deuteranopia,
+    /// No hues; categories differ only by text modifiers
+    HighContrast,
+}
+
+impl Palette {
+    /// All palettes in cycling order.
+    pub const ALL: [Palette; 3] = [
+        Palette::Default,
+        Palette::Deuteranopia,
+        Palette::HighContrast,
+    ];
+
+    /// Config/display name of the palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::HighContrast => "high_contrast",
+        }
+    }
+
+    /// The palette after this one, wrapping around.
+    pub fn next(self) -> Palette {
+        let i = Palette::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Palette::ALL[(i + 1) % Palette::ALL.len()]
+    }
+}
+
+/// Theme resource consulted by the styled renderer.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Theme {
+    pub palette: Palette,
+}
+
+impl Theme {
+    pub fn new(palette: Palette) -> Self {
+        Self { palette }
+    }
+
+    /// Style for a cell category under the active palette.
+    pub fn style(&self, kind: CellKind) -> Style {
+        let s = Style::default();
+        match self.palette {
+            Palette::Default => match kind {
+                CellKind::Floor => s.fg(Color::DarkGray),
+                CellKind::Wall => s.fg(Color::White),
+                CellKind::Water => s.fg(Color::Blue),
+                CellKind::Lava => s.fg(Color::Red),
+                CellKind::Agent => s.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                CellKind::Unit => s.fg(Color::LightYellow),
+                CellKind::Visible => s.fg(Color::Green),
+                CellKind::Traffic => s.fg(Color::LightRed),
+                CellKind::Designation => s.fg(Color::Magenta),
+                CellKind::Stockpile => s.fg(Color::Cyan),
+                CellKind::Zone => s.fg(Color::LightBlue),
+                CellKind::Selection => s.fg(Color::Black).bg(Color::Cyan),
+                CellKind::Cursor => s.fg(Color::Black).bg(Color::Yellow),
+            },
+            Palette::Deuteranopia => match kind {
+                CellKind::Floor => s.fg(Color::DarkGray),
+                CellKind::Wall => s.fg(Color::White),
+                CellKind::Water => s.fg(Color::Rgb(0, 114, 178)),
+                CellKind::Lava => s.fg(Color::Rgb(230, 159, 0)),
+                CellKind::Agent => s.fg(Color::Rgb(240, 228, 66)).add_modifier(Modifier::BOLD),
+                CellKind::Unit => s.fg(Color::Rgb(240, 228, 66)),
+                CellKind::Visible => s.fg(Color::Rgb(86, 180, 233)),
+                CellKind::Traffic => s.fg(Color::Rgb(230, 159, 0)),
+                CellKind::Designation => s.fg(Color::Rgb(213, 94, 0)),
+                CellKind::Stockpile => s.fg(Color::Rgb(0, 158, 115)),
+                CellKind::Zone => s.fg(Color::Rgb(204, 121, 167)),
+                CellKind::Selection => s.fg(Color::Black).bg(Color::Rgb(0, 158, 115)),
+                CellKind::Cursor => s.fg(Color::Black).bg(Color::Rgb(204, 121, 167)),
+            },
+            Palette::HighContrast => match kind {
+                CellKind::Floor => s,
+                CellKind::Wall => s.add_modifier(Modifier::REVERSED),
+                CellKind::Water => s.add_modifier(Modifier::DIM),
+                CellKind::Lava => s.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                CellKind::Agent => s.add_modifier(Modifier::BOLD),
+                CellKind::Unit => s.add_modifier(Modifier::ITALIC),
+                CellKind::Visible => s.add_modifier(Modifier::BOLD),
+                CellKind::Traffic => s.add_modifier(Modifier::BOLD | Modifier::ITALIC),
+                CellKind::Designation => s.add_modifier(Modifier::BOLD | Modifier::REVERSED),
+                CellKind::Stockpile => s.add_modifier(Modifier::UNDERLINED),
+                CellKind::Zone => s.add_modifier(Modifier::UNDERLINED | Modifier::DIM),
+                CellKind::Selection => s.add_modifier(Modifier::REVERSED),
+                CellKind::Cursor => s.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            },
+        }
+    }
+
+    /// Style for an announcement in the alert bar.
+    pub fn severity_style(&self, severity: Severity) -> Style {
+        let s = Style::default();
+        match (self.palette, severity) {
+            (_, Severity::Info) => s,
+            (Palette::Default, Severity::Warning) => s.fg(Color::Yellow),
+            (Palette::Default, Severity::Urgent) => s.fg(Color::Red).add_modifier(Modifier::BOLD),
+            (Palette::Deuteranopia, Severity::Warning) => s.fg(Color::Rgb(240, 228, 66)),
+            (Palette::Deuteranopia, Severity::Urgent) => {
+                s.fg(Color::Rgb(213, 94, 0)).add_modifier(Modifier::BOLD)
+            }
+            (Palette::HighContrast, Severity::Warning) => s.add_modifier(Modifier::BOLD),
+            (Palette::HighContrast, Severity::Urgent) => {
+                s.add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Zone drawing mode: the kind/filter popup and zone lookups.
+//!
+//! In zone mode the designate key marks two corners; the second opens a
+//! [`ZonePopup`] where the zone's kind and, for stockpiles, the accepted
+//! tags are picked before the zone is created. The edit key opens the same
+//! popup for the zones under the cursor, prefilled from the snapshot.
+
+use gc_core::prelude::*;
+use std::collections::BTreeSet;
+
+/// What a confirmed popup applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneTarget {
+    /// A new zone spanning two corners
+    New { a: (i32, i32), b: (i32, i32) },
+    /// The zones covering a tile
+    Existing { x: i32, y: i32 },
+}
+
+/// Kind and filter picker shown before a zone is created or edited.
+///
+/// Rows are the zone kinds, then (for stockpiles) one checkbox per tag,
+/// then a confirm row. Ticking no tags accepts every item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZonePopup {
+    pub target: ZoneTarget,
+    pub kind: ZoneKind,
+    /// Ticked tags; the stockpile accepts items carrying any of them
+    pub tags: BTreeSet<String>,
+    /// Highlighted row
+    pub row: usize,
+    /// Tags offered, in display order
+    options: Vec<String>,
+    /// Filter the edited zone already had, kept until a tag is toggled
+    original: Option<TagQuery>,
+}
+
+impl ZonePopup {
+    /// Popup for a new zone, defaulting to a stockpile that accepts everything.
+    pub fn new(target: ZoneTarget) -> Self {
+        Self {
+            target,
+            kind: ZoneKind::Stockpile,
+            tags: BTreeSet::new(),
+            row: 0,
+            options: TagRegistry::default()
+                .known_tags()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            original: None,
+        }
+    }
+
+    /// Popup for the zones at (x, y), prefilled with `kind` and `accepts`.
+    ///
+    /// A filter that is a plain list of alternatives (`stone | gem`) shows
+    /// up as ticked tags; anything richer is kept as-is unless a tag is
+    /// toggled.
+    pub fn edit(x: i32, y: i32, kind: ZoneKind, accepts: Option<&TagQuery>) -> Self {
+        let mut popup = Self::new(ZoneTarget::Existing { x, y });
+        popup.kind = kind;
+        popup.row = ZoneKind::ALL.iter().position(|&k| k == kind).unwrap_or(0);
+        if let Some(query) = accepts {
+            let tags: BTreeSet<String> = query.tags().map(str::to_string).collect();
+            let plain = tags.iter().cloned().collect::<Vec<_>>().join(" | ");
+            if query.tags().count() == tags.len() && TagQuery::parse(&plain).as_ref() == Ok(query) {
+                for tag in &tags {
+                    if !popup.options.contains(tag) {
+                        popup.options.push(tag.clone());
+                    }
+                }
+                popup.tags = tags;
+            } else {
+                popup.original = Some(query.clone());
+            }
+        }
+        popup
+    }
+
+    /// Tags offered as checkboxes (none unless the kind is a stockpile).
+    fn tag_rows(&self) -> &[String] {
+        if self.kind == ZoneKind::Stockpile {
+            &self.options
+        } else {
+            &[]
+        }
+    }
+
+    fn rows(&self) -> usize {
+        ZoneKind::ALL.len() + self.tag_rows().len() + 1
+    }
+
+    /// Move the highlight by `delta` rows, wrapping around.
+    pub fn move_row(&mut self, delta: i32) {
+        let n = self.rows() as i32;
+        self.row = (self.row as i32 + delta).rem_euclid(n) as usize;
+    }
+
+    /// Act on the highlighted row: pick a kind, toggle a tag, or confirm.
+    ///
+    /// Returns the command to send once the confirm row is chosen.
+    pub fn select(&mut self) -> Option<WorldCommand> {
+        let kinds = ZoneKind::ALL.len();
+        if self.row < kinds {
+            self.kind = ZoneKind::ALL[self.row];
+            return None;
+        }
+        if let Some(tag) = self.tag_rows().get(self.row - kinds).cloned() {
+            if !self.tags.remove(&tag) {
+                self.tags.insert(tag);
+            }
+            self.original = None;
+            return None;
+        }
+        Some(self.command())
+    }
+
+    /// Filter for the confirmed zone, if it is a filtered stockpile.
+    pub fn accepts(&self) -> Option<TagQuery> {
+        if self.kind != ZoneKind::Stockpile {
+            return None;
+        }
+        if self.original.is_some() {
+            return self.original.clone();
+        }
+        let text = self.tags.iter().cloned().collect::<Vec<_>>().join(" | ");
+        TagQuery::parse(&text).ok()
+    }
+
+    /// Command applying the popup's choices to its target.
+    pub fn command(&self) -> WorldCommand {
+        let (kind, accepts) = (self.kind, self.accepts());
+        match self.target {
+            ZoneTarget::New { a, b } => WorldCommand::Zone {
+                kind,
+                a,
+                b,
+                accepts,
+            },
+            ZoneTarget::Existing { x, y } => WorldCommand::EditZone {
+                x,
+                y,
+                kind,
+                accepts,
+            },
+        }
+    }
+
+    /// Popup title.
+    pub fn title(&self) -> &'static str {
+        match self.target {
+            ZoneTarget::New { .. } => "New zone",
+            ZoneTarget::Existing { .. } => "Edit zone",
+        }
+    }
+
+    /// Popup body, one line per row, with `>` on the highlighted one.
+    pub fn lines(&self) -> Vec<String> {
+        let mut rows: Vec<String> = ZoneKind::ALL
+            .iter()
+            .map(|&k| {
+                let mark = if k == self.kind { '*' } else { ' ' };
+                format!("({}) {}", mark, k.label())
+            })
+            .collect();
+        rows.extend(self.tag_rows().iter().map(|tag| {
+            let mark = if self.tags.contains(tag) { 'x' } else { ' ' };
+            format!("    [{}] {}", mark, tag)
+        }));
+        rows.push(match &self.original {
+            Some(query) => format!("confirm (keeps filter '{}')", query),
+            None => "confirm".to_string(),
+        });
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let cursor = if i == self.row { '>' } else { ' ' };
+                format!("{} {}", cursor, row)
+            })
+            .collect()
+    }
+}
+
+/// The newest zone covering (x, y) in `view`, if any.
+pub fn zone_at(view: &WorldView, x: i32, y: i32) -> Option<&EntityView> {
+    view.entities.iter().rev().find(|e| {
+        e.zone_kind.is_some()
+            && e.zone
+                .is_some_and(|(x0, y0, x1, y1)| (x0..=x1).contains(&x) && (y0..=y1).contains(&y))
+    })
+}
@@ -0,0 +1,140 @@
+use bevy_ecs::prelude::*;
+use crossterm::event::KeyCode;
+use gc_core::prelude::*;
+use gc_tui::editor::{editor_key, editor_world, save_scenario, EditorState};
+use gc_tui::keymap::Keymap;
+use gc_tui::screenshot::render_text;
+use gc_tui::RenderLayers;
+
+fn bounds(z: &ZoneBounds) -> (i32, i32, i32, i32) {
+    (z.min_x, z.min_y, z.max_x, z.max_y)
+}
+
+fn press(world: &mut World, state: &mut EditorState, keys: &[KeyCode]) {
+    let keymap = Keymap::default();
+    for &key in keys {
+        assert!(!editor_key(world, state, &keymap, key));
+    }
+}
+
+#[test]
+fn brushes_paint_tiles_and_place_entities() {
+    let mut world = editor_world(8, 6, 1);
+    let mut state = EditorState::new("unused.json");
+
+    // Lava at (0, 0), then a miner at (1, 0)
+    press(
+        &mut world,
+        &mut state,
+        &[
+            KeyCode::Char('4'),
+            KeyCode::Enter,
+            KeyCode::Char('5'),
+            KeyCode::Right,
+            KeyCode::Enter,
+        ],
+    );
+    assert_eq!(
+        world.resource::<GameMap>().get_tile(0, 0),
+        Some(TileKind::Lava)
+    );
+    let mut miners = world.query_filtered::<(&Position, Option<&MiningSkill>), With<Miner>>();
+    assert_eq!(
+        miners.single(&world),
+        (&Position(1, 0), Some(&MiningSkill::STARTING))
+    );
+
+    // Stockpile: first stroke sets a corner, second places the zone
+    press(
+        &mut world,
+        &mut state,
+        &[
+            KeyCode::Char('8'),
+            KeyCode::Enter,
+            KeyCode::Down,
+            KeyCode::Down,
+        ],
+    );
+    assert_eq!(state.anchor, Some((1, 0)));
+    press(&mut world, &mut state, &[KeyCode::Left, KeyCode::Enter]);
+    assert_eq!(state.anchor, None);
+    let mut zones = world.query_filtered::<&ZoneBounds, With<Stockpile>>();
+    assert_eq!(bounds(zones.single(&world)), (0, 0, 1, 2));
+
+    // Erase clears whatever covers the cursor
+    press(
+        &mut world,
+        &mut state,
+        &[KeyCode::Char('9'), KeyCode::Enter],
+    );
+    assert_eq!(zones.iter(&world).count(), 0);
+}
+
+#[test]
+fn saved_scenario_keeps_roles_and_stockpiles() {
+    let mut world = editor_world(8, 6, 2);
+    paint(&mut world, 2, 2, Brush::Carrier);
+    paint(&mut world, 3, 3, Brush::Stone);
+    place_stockpile(&mut world, (5, 4), (4, 3));
+    let path = std::env::temp_dir().join(format!("gc_editor_{}.json", std::process::id()));
+    save_scenario(&mut world, &path).unwrap();
+
+    let save = decode_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).ok();
+    let mut loaded = World::new();
+    load_world(save, &mut loaded);
+
+    let mut carriers = loaded.query_filtered::<&Position, (With<Carrier>, With<Inventory>)>();
+    assert_eq!(carriers.single(&loaded), &Position(2, 2));
+    let mut zones = loaded.query_filtered::<&ZoneBounds, With<Stockpile>>();
+    assert_eq!(bounds(zones.single(&loaded)), (4, 3, 5, 4));
+
+    let text = render_text(
+        &mut loaded,
+        RenderLayers {
+            units: true,
+            ..Default::default()
+        },
+    );
+    let rows: Vec<&str> = text.lines().collect();
+    assert_eq!(rows[2].chars().nth(2), Some('c'));
+    assert_eq!(rows[3].chars().nth(3), Some('o'));
+}
+
+#[test]
+fn inspect_line_reports_tile_material_and_occupants() {
+    let mut world = editor_world(8, 6, 1);
+    let mut state = EditorState::new("unused.json");
+    // Wall with a stone on it at (0, 0)
+    press(
+        &mut world,
+        &mut state,
+        &[
+            KeyCode::Char('2'),
+            KeyCode::Enter,
+            KeyCode::Char('7'),
+            KeyCode::Enter,
+        ],
+    );
+    let material = world.resource::<MaterialMap>().get(0, 0).unwrap();
+    let view = snapshot_world(&mut world);
+    assert_eq!(
+        gc_tui::inspect_line(&view, (0, 0)),
+        format!("tile=wall, material={}, here=Stone", material.label())
+    );
+}
+
+#[test]
+fn inspect_line_lists_stockpile_contents() {
+    let mut world = editor_world(8, 6, 1);
+    world.insert_resource(StockpileContents::default());
+    world.spawn(StockpileBundle::new(2, 2, 4, 4));
+    world.spawn((Item::stone(), Position(2, 2)));
+    world.spawn((Item::stone(), Position(4, 3)));
+    let mut schedule = Schedule::default();
+    schedule.add_systems(stockpile_contents_system);
+    schedule.run(&mut world);
+    let view = snapshot_world(&mut world);
+    assert!(gc_tui::inspect_line(&view, (3, 3)).ends_with(", stockpile=2 stone"));
+    assert!(!gc_tui::inspect_line(&view, (5, 5)).contains("stockpile="));
+}
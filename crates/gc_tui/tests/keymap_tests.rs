@@ -0,0 +1,91 @@
+use bevy_ecs::prelude::*;
+use crossterm::event::KeyCode;
+use gc_core::prelude::*;
+use gc_tui::keymap::{parse_key, Action, Keymap};
+use gc_tui::{apply_action, build_schedule, build_world, AppState};
+
+#[test]
+fn default_keymap_matches_original_bindings() {
+    let km = Keymap::default();
+    assert_eq!(km.action_for(KeyCode::Char('q')), Some(Action::Quit));
+    assert_eq!(km.action_for(KeyCode::Esc), Some(Action::Cancel));
+    assert_eq!(km.action_for(KeyCode::Char(' ')), Some(Action::Pause));
+    assert_eq!(km.action_for(KeyCode::Char('.')), Some(Action::Step));
+    assert_eq!(km.action_for(KeyCode::Char('v')), Some(Action::ToggleVis));
+    assert_eq!(km.action_for(KeyCode::Char('?')), Some(Action::Help));
+    assert_eq!(km.action_for(KeyCode::Char('z')), Some(Action::Zone));
+    assert_eq!(
+        km.action_for(KeyCode::Char('t')),
+        Some(Action::ToggleTraffic)
+    );
+    assert_eq!(km.action_for(KeyCode::Char('w')), None);
+}
+
+#[test]
+fn config_overrides_only_listed_actions() {
+    let km =
+        Keymap::from_json(r#"{ "keymap": { "pause": ["p"], "cursor_up": ["k", "up"] } }"#).unwrap();
+    assert_eq!(km.action_for(KeyCode::Char('p')), Some(Action::Pause));
+    assert_eq!(km.action_for(KeyCode::Char(' ')), None);
+    assert_eq!(
+        km.keys_for(Action::CursorUp),
+        &[KeyCode::Char('k'), KeyCode::Up]
+    );
+    // Untouched actions keep defaults
+    assert_eq!(km.action_for(KeyCode::Char('v')), Some(Action::ToggleVis));
+}
+
+#[test]
+fn config_rejects_unknown_key_names() {
+    assert!(Keymap::from_json(r#"{ "keymap": { "quit": ["ctrl-q"] } }"#).is_err());
+    assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+    assert_eq!(parse_key("Esc"), Some(KeyCode::Esc));
+}
+
+#[test]
+fn missing_config_file_yields_defaults() {
+    let km = Keymap::load("definitely/not/a/real/config.json").unwrap();
+    assert_eq!(km, Keymap::default());
+}
+
+#[test]
+fn help_lines_list_every_action() {
+    let lines = Keymap::default().help_lines();
+    assert_eq!(lines.len(), Action::ALL.len());
+    assert!(lines
+        .iter()
+        .any(|l| l.starts_with("space") && l.contains("pause")));
+}
+
+#[test]
+fn cursor_and_designate_actions_drive_world() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+
+    // Cursor clamps at map edges
+    assert!(!apply_action(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        Action::CursorUp
+    ));
+    assert_eq!(app.cursor, (0, 0));
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorDown);
+    assert_eq!(app.cursor, (1, 1));
+
+    // First press enters designation mode, second marks the tile
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    let mut q = world.query_filtered::<&Position, With<MineDesignation>>();
+    let positions: Vec<(i32, i32)> = q.iter(&world).map(|p| (p.0, p.1)).collect();
+    assert_eq!(positions, vec![(1, 1)]);
+
+    assert!(apply_action(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        Action::Quit
+    ));
+}
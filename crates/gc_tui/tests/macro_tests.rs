@@ -0,0 +1,114 @@
+use gc_core::prelude::*;
+use gc_tui::keymap::Action;
+use gc_tui::macros::{macro_path, DesignationMacro, MACRO_FILE};
+use gc_tui::mode::TuiMode;
+use gc_tui::theme::Theme;
+use gc_tui::{interpret_action, ActionEffect, AppState};
+
+fn press(app: &mut AppState, actions: &[Action]) -> Vec<ActionEffect> {
+    let mut theme = Theme::default();
+    actions
+        .iter()
+        .map(|&a| interpret_action(app, &mut theme, (20, 10), a))
+        .collect()
+}
+
+#[test]
+fn recorded_pattern_replays_at_a_new_anchor() {
+    let mut app = AppState {
+        cursor: (2, 2),
+        ..Default::default()
+    };
+    press(&mut app, &[Action::RecordMacro]);
+    assert_eq!(app.mode, TuiMode::Designate);
+    press(
+        &mut app,
+        &[
+            Action::Designate,
+            Action::CursorRight,
+            Action::Designate,
+            Action::DesignateArea,
+            Action::CursorDown,
+            Action::CursorRight,
+            Action::DesignateArea,
+            // Not part of the pattern
+            Action::ToggleVis,
+        ],
+    );
+    let done = press(&mut app, &[Action::RecordMacro]);
+    let recorded = app.recorded.clone().expect("macro kept");
+    assert_eq!(done, vec![ActionEffect::MacroRecorded(recorded.clone())]);
+    assert_eq!(recorded.actions.len(), 7);
+    assert!(app.recording.is_none());
+
+    app.cursor = (10, 5);
+    let replayed = press(&mut app, &[Action::PlayMacro]);
+    let expected = vec![
+        WorldCommand::Designate { x: 10, y: 5 },
+        WorldCommand::Designate { x: 11, y: 5 },
+        WorldCommand::DesignateRect {
+            a: (11, 5),
+            b: (12, 6),
+        },
+    ];
+    assert_eq!(
+        replayed,
+        vec![ActionEffect::Batch(
+            expected.into_iter().map(SimCommand::World).collect()
+        )]
+    );
+    // The cursor ends where the pattern did, ready to repeat it
+    assert_eq!(app.cursor, (12, 6));
+
+    // Replay clips at the map edge like live input
+    let clipped = recorded.replay((19, 9), (20, 10));
+    assert_eq!(
+        clipped.commands[..2],
+        [
+            WorldCommand::Designate { x: 19, y: 9 },
+            WorldCommand::Designate { x: 19, y: 9 }
+        ]
+    );
+}
+
+#[test]
+fn replay_without_a_macro_does_nothing() {
+    let mut app = AppState::default();
+    assert_eq!(
+        press(&mut app, &[Action::PlayMacro]),
+        vec![ActionEffect::None]
+    );
+    assert_eq!(app.message.as_deref(), Some("no macro recorded"));
+    // An empty recording is dropped rather than replacing anything
+    assert_eq!(
+        press(&mut app, &[Action::RecordMacro, Action::RecordMacro]),
+        vec![ActionEffect::None, ActionEffect::None]
+    );
+    assert!(app.recorded.is_none());
+}
+
+#[test]
+fn macros_are_saved_next_to_the_config() {
+    assert_eq!(
+        macro_path("conf/goblin-camp.json"),
+        std::path::Path::new("conf").join(MACRO_FILE)
+    );
+    let dir = std::env::temp_dir().join(format!("gc_macro_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = macro_path(dir.join("goblin-camp.json"));
+    assert_eq!(DesignationMacro::load(&path).unwrap(), None);
+
+    let recorded = DesignationMacro {
+        actions: vec![Action::Designate, Action::CursorLeft, Action::Designate],
+    };
+    recorded.save(&path).unwrap();
+    assert_eq!(DesignationMacro::load(&path).unwrap(), Some(recorded));
+
+    // A corrupt file is an error to load, but only a warning at startup
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(DesignationMacro::load(&path).is_err());
+    let (recorded, warning) = DesignationMacro::load_or_warn(&path);
+    assert_eq!(recorded, None);
+    assert!(warning.unwrap().contains("parsing macro"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
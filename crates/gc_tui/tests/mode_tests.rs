@@ -0,0 +1,356 @@
+use bevy_ecs::prelude::*;
+use crossterm::event::KeyCode;
+use gc_core::prelude::*;
+use gc_tui::keymap::{Action, Keymap};
+use gc_tui::mode::{hint_line, menu_lines, TuiMode};
+use gc_tui::{apply_action, build_schedule, build_world, AppState};
+
+fn designation_count(world: &mut World) -> usize {
+    let mut q = world.query_filtered::<Entity, With<MineDesignation>>();
+    q.iter(world).count()
+}
+
+#[test]
+fn designate_and_cancel_walk_the_state_machine() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    assert_eq!(app.mode, TuiMode::Cursor);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    assert_eq!(app.mode, TuiMode::Designate);
+    assert_eq!(designation_count(&mut world), 0);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    assert_eq!(designation_count(&mut world), 1);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Cancel);
+    assert_eq!(app.mode, TuiMode::Cursor);
+}
+
+#[test]
+fn area_designation_takes_two_corners() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+
+    // Ignored outside designation mode
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    assert_eq!(app.anchor, None);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    assert_eq!(app.anchor, Some((0, 0)));
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorDown);
+    // The pending area is previewed before it is confirmed
+    let preview = estimate_designation(&mut world, &ZoneBounds::new(0, 0, 2, 1));
+    assert_eq!(app.estimate.as_ref(), Some(&preview));
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    assert_eq!(app.anchor, None);
+    assert_eq!(app.estimate, None);
+    assert_eq!(designation_count(&mut world), 6);
+
+    // Cancel drops a pending corner before leaving the mode
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    assert!(app.estimate.is_some());
+    apply_action(&mut world, &mut schedule, &mut app, Action::Cancel);
+    assert_eq!(app.anchor, None);
+    assert_eq!(app.estimate, None);
+    assert_eq!(app.mode, TuiMode::Designate);
+}
+
+#[test]
+fn confirm_dig_covers_the_cursor_or_the_pending_area() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    let confirmed = |world: &mut World| {
+        let mut q = world.query_filtered::<&Position, With<HazardConfirmed>>();
+        let mut at: Vec<(i32, i32)> = q.iter(world).map(|p| (p.0, p.1)).collect();
+        at.sort();
+        at
+    };
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    apply_action(&mut world, &mut schedule, &mut app, Action::ConfirmDig);
+    assert_eq!(confirmed(&mut world), vec![(1, 0)]);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorLeft);
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::ConfirmDig);
+    assert_eq!(app.anchor, None);
+    assert_eq!(confirmed(&mut world), vec![(0, 0), (1, 0)]);
+}
+
+#[test]
+fn forbid_covers_the_cursor_or_the_pending_area() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    let stones: Vec<Entity> = [(0, 0), (1, 0), (2, 0)]
+        .into_iter()
+        .map(|(x, y)| world.spawn((Item::stone(), Position(x, y))).id())
+        .collect();
+    let forbidden = |world: &World| {
+        stones
+            .iter()
+            .map(|&e| world.get::<Forbidden>(e).is_some())
+            .collect::<Vec<_>>()
+    };
+
+    // Ignored outside designation mode
+    apply_action(&mut world, &mut schedule, &mut app, Action::Forbid);
+    assert_eq!(forbidden(&world), [false, false, false]);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Designate);
+    apply_action(&mut world, &mut schedule, &mut app, Action::Forbid);
+    assert_eq!(forbidden(&world), [true, false, false]);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::DesignateArea);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::CursorRight);
+    apply_action(&mut world, &mut schedule, &mut app, Action::Forbid);
+    assert_eq!(app.anchor, None);
+    assert_eq!(forbidden(&world), [true, true, true]);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Unforbid);
+    assert_eq!(forbidden(&world), [true, true, false]);
+}
+
+#[test]
+fn menu_toggles_and_cancel_closes_help_first() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Menu);
+    assert_eq!(app.mode, TuiMode::Menu);
+    apply_action(&mut world, &mut schedule, &mut app, Action::Help);
+    assert!(app.show_help);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Cancel);
+    assert!(!app.show_help);
+    assert_eq!(app.mode, TuiMode::Menu);
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Menu);
+    assert_eq!(app.mode, TuiMode::Cursor);
+}
+
+#[test]
+fn the_menu_switches_subsystems_off_and_on() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    let auto_haul_on = |world: &World| {
+        world
+            .resource::<SystemToggles>()
+            .is_enabled(Subsystem::AutoHaul)
+    };
+
+    // Only while the menu is open
+    apply_action(&mut world, &mut schedule, &mut app, Action::ToggleAutoHaul);
+    assert!(auto_haul_on(&world));
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Menu);
+    apply_action(&mut world, &mut schedule, &mut app, Action::ToggleAutoHaul);
+    assert!(!auto_haul_on(&world));
+    let disabled = snapshot_world(&mut world).disabled;
+    let lines = menu_lines(&Keymap::default(), &disabled);
+    assert!(
+        lines.contains(&format!("{:<12} auto-haul: off", "H")),
+        "{lines:?}"
+    );
+    assert!(
+        lines.contains(&format!("{:<12} combat: on", "C")),
+        "{lines:?}"
+    );
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::ToggleAutoHaul);
+    assert!(auto_haul_on(&world));
+}
+
+#[test]
+fn hint_line_changes_by_mode() {
+    let km = Keymap::default();
+    let cursor = hint_line(TuiMode::Cursor, &km);
+    assert!(cursor.starts_with("[cursor]"));
+    assert!(cursor.contains("arrows: move"));
+    assert!(cursor.contains("d/enter: designate"));
+
+    let designate = hint_line(TuiMode::Designate, &km);
+    assert!(designate.contains("d/enter: mark tile"));
+    assert!(designate.contains("esc: done"));
+    assert!(designate.contains("a: area corner"));
+
+    let menu = hint_line(TuiMode::Menu, &km);
+    assert!(menu.contains("space: pause"));
+    assert!(!menu.contains("move"));
+}
+
+#[test]
+fn hint_line_reflects_remapped_keys() {
+    let mut km = Keymap::default();
+    km.bind(Action::Designate, vec![KeyCode::Char('x')]);
+    km.bind(Action::CursorUp, vec![KeyCode::Char('k')]);
+    let hint = hint_line(TuiMode::Cursor, &km);
+    assert!(hint.contains("x: designate"));
+    assert!(hint.contains("k,down,left,right: move"));
+}
+
+#[test]
+fn interpreted_actions_become_simulation_commands() {
+    use gc_tui::theme::Theme;
+    use gc_tui::{interpret_action, ActionEffect};
+
+    let mut app = AppState::default();
+    let mut theme = Theme::default();
+    let size = (10, 10);
+
+    assert_eq!(
+        interpret_action(&mut app, &mut theme, size, Action::Pause),
+        ActionEffect::Sim(SimCommand::Pause)
+    );
+    assert_eq!(
+        interpret_action(&mut app, &mut theme, size, Action::Pause),
+        ActionEffect::Sim(SimCommand::Resume)
+    );
+
+    interpret_action(&mut app, &mut theme, size, Action::CursorRight);
+    interpret_action(&mut app, &mut theme, size, Action::Designate);
+    assert_eq!(
+        interpret_action(&mut app, &mut theme, size, Action::Designate),
+        ActionEffect::Sim(SimCommand::World(WorldCommand::Designate { x: 1, y: 0 }))
+    );
+    assert_eq!(
+        interpret_action(&mut app, &mut theme, size, Action::Save),
+        ActionEffect::Sim(SimCommand::Save)
+    );
+    assert_eq!(
+        interpret_action(&mut app, &mut theme, size, Action::CyclePalette),
+        ActionEffect::None
+    );
+    assert_ne!(theme, Theme::default());
+}
+
+#[test]
+fn tui_schedule_has_no_fatal_issues() {
+    let mut world = build_world(24, 16, 5);
+    let mut schedule = build_schedule();
+    let fatal: Vec<_> = validate_schedule(&mut schedule, &mut world)
+        .into_iter()
+        .filter(|i| i.is_fatal())
+        .collect();
+    assert!(fatal.is_empty(), "{fatal:?}");
+}
+
+#[test]
+fn entity_panel_lists_categories_and_leaks() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    apply_action(&mut world, &mut schedule, &mut app, Action::ToggleEntities);
+    assert!(app.show_entities);
+    schedule.run(&mut world);
+    world.spawn(Item::stone());
+    schedule.run(&mut world);
+
+    let lines = gc_tui::entity_panel_lines(&snapshot_world(&mut world));
+    assert_eq!(lines.len(), EntityCategory::ALL.len() + 2);
+    assert_eq!(lines[4], format!("{:<22} {:>6}", "orphan_items", 1));
+    assert!(lines[9].starts_with("total"));
+    let leak = lines.last().unwrap();
+    assert!(
+        leak.starts_with("LEAK orphan_items grew from 0 to 1 since tick "),
+        "{leak}"
+    );
+}
+
+#[test]
+fn history_panel_shows_whoever_is_under_the_cursor() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    apply_action(&mut world, &mut schedule, &mut app, Action::ToggleHistory);
+    assert!(app.show_history);
+    assert!(hint_line(TuiMode::Cursor, &Keymap::default()).contains("i: history"));
+
+    // Grak and Urok share the demo scene's start tile; Grak has a past
+    let grak = world
+        .query::<(Entity, &Name)>()
+        .iter(&world)
+        .find(|(_, n)| n.0 == "Grak")
+        .map(|(e, _)| e)
+        .unwrap();
+    world
+        .resource_mut::<EntityHistory>()
+        .record(grak, 0, HistoryEvent::RestStarted);
+    let view = snapshot_world(&mut world);
+    let (title, lines) = gc_tui::history_panel(&view, (5, 5));
+    assert_eq!(title, "History: Grak");
+    assert_eq!(lines, vec!["t=0 lay down to rest".to_string()]);
+
+    let (title, lines) = gc_tui::history_panel(&view, (0, 9));
+    assert_eq!((title.as_str(), lines.len()), ("History", 1));
+}
+
+#[test]
+fn overview_sorts_the_colony_by_the_picked_column() {
+    let mut world = build_world(20, 10, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    assert!(hint_line(TuiMode::Cursor, &Keymap::default()).contains("o: overview"));
+    world.spawn((Name("Azog".into()), Goblin, Mood(-50), Position(2, 8)));
+
+    apply_action(&mut world, &mut schedule, &mut app, Action::Overview);
+    let overview = app.overview.expect("o opens the overview");
+    let view = snapshot_world(&mut world);
+    let lines = overview.lines(&view);
+    assert_eq!(overview.title(&view), "Colony (3) by name, ascending");
+    assert!(lines[0].starts_with("name^"), "{}", lines[0]);
+    assert_eq!(
+        lines[1],
+        format!(
+            "{:<16} {:<10} {:>7} {:>8} {:>6} {:>7} {:>9}",
+            "Azog", "-", "-", "-", "-50", "-", "2,8"
+        )
+    );
+    assert!(lines[2].starts_with("Grak "));
+
+    // Right twice, past job, to hunger; nobody is hungry yet
+    for action in [Action::CursorRight, Action::CursorRight, Action::Designate] {
+        apply_action(&mut world, &mut schedule, &mut app, action);
+    }
+    let overview = app.overview.unwrap();
+    assert_eq!(
+        (overview.column, overview.descending),
+        (SummaryColumn::Hunger, true)
+    );
+    assert_eq!(app.cursor, (0, 0), "the map cursor stays put");
+
+    // Mood, descending: the demo workers have none, so Azog leads
+    for action in [Action::CursorRight, Action::CursorRight] {
+        apply_action(&mut world, &mut schedule, &mut app, action);
+    }
+    let lines = app.overview.unwrap().lines(&snapshot_world(&mut world));
+    assert!(lines[0].contains("moodv"), "{}", lines[0]);
+    assert!(lines[1].starts_with("Azog "));
+
+    // Scrolling stops at the last row
+    for _ in 0..5 {
+        apply_action(&mut world, &mut schedule, &mut app, Action::CursorDown);
+    }
+    assert_eq!(app.overview.unwrap().scroll, 2);
+
+    // Other keys still reach the map; Esc closes
+    apply_action(&mut world, &mut schedule, &mut app, Action::Pause);
+    assert!(app.paused);
+    apply_action(&mut world, &mut schedule, &mut app, Action::Cancel);
+    assert_eq!(app.overview, None);
+    assert_eq!(app.mode, TuiMode::Cursor);
+}
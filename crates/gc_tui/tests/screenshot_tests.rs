@@ -0,0 +1,143 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_tui::screenshot::{render_png, render_text, world_from_save, ScreenshotFormat};
+use gc_tui::{build_world, RenderLayers};
+
+fn small_world() -> World {
+    let mut world = World::new();
+    let mut map = GameMap::new(6, 4);
+    map.set_tile(0, 0, TileKind::Wall).unwrap();
+    world.insert_resource(map);
+    world
+}
+
+#[test]
+fn format_follows_extension() {
+    assert_eq!(
+        ScreenshotFormat::from_path("a/map.png"),
+        ScreenshotFormat::Png
+    );
+    assert_eq!(
+        ScreenshotFormat::from_path("MAP.PNG"),
+        ScreenshotFormat::Png
+    );
+    assert_eq!(
+        ScreenshotFormat::from_path("map.txt"),
+        ScreenshotFormat::Text
+    );
+    assert_eq!(ScreenshotFormat::from_path("map"), ScreenshotFormat::Text);
+}
+
+#[test]
+fn text_screenshot_draws_requested_layers_only() {
+    let mut world = small_world();
+    world.spawn(DesignationBundle {
+        pos: Position(1, 1),
+        ..Default::default()
+    });
+    world.spawn(StockpileBundle::new(3, 2, 4, 3));
+
+    let plain = render_text(&mut world, RenderLayers::default());
+    assert_eq!(plain, "#.....\n......\n......\n......\n");
+
+    let layered = render_text(
+        &mut world,
+        RenderLayers {
+            visibility: false,
+            designations: true,
+            stockpiles: true,
+            units: false,
+            traffic: false,
+        },
+    );
+    assert_eq!(layered, "#.....\n.x....\n...==.\n...==.\n");
+}
+
+#[test]
+fn entities_with_a_render_glyph_are_drawn_with_it() {
+    let mut world = small_world();
+    world.spawn((
+        Position(2, 2),
+        RenderGlyph::new('!', RenderLayer::Item, GlyphColor::Lava),
+    ));
+    world.spawn((Position(3, 2), Carrier));
+    let units = RenderLayers {
+        units: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        render_text(&mut world, units),
+        "#.....\n......\n..!c..\n......\n"
+    );
+    assert_eq!(
+        render_text(&mut world, RenderLayers::default()),
+        "#.....\n......\n......\n......\n"
+    );
+}
+
+#[test]
+fn traffic_overlay_shades_walked_tiles_by_heat() {
+    let mut world = small_world();
+    let mut traffic = TrafficMap::default();
+    for _ in 0..9 {
+        traffic.record(1, 1);
+    }
+    for _ in 0..3 {
+        traffic.record(2, 1);
+    }
+    traffic.record(0, 0);
+    world.insert_resource(traffic);
+    let heat = RenderLayers {
+        traffic: true,
+        ..Default::default()
+    };
+    // Heat is relative to the busiest tile
+    assert_eq!(
+        render_text(&mut world, heat),
+        "1.....\n.93...\n......\n......\n"
+    );
+    assert_eq!(
+        render_text(&mut world, RenderLayers::default()),
+        "#.....\n......\n......\n......\n"
+    );
+}
+
+#[test]
+fn png_screenshot_has_valid_header_and_dimensions() {
+    let mut world = small_world();
+    let png = render_png(&mut world, RenderLayers::default());
+    assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    assert_eq!(&png[12..16], b"IHDR");
+    let w = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let h = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    assert_eq!(
+        (w, h),
+        (
+            6 * gc_tui::screenshot::PNG_CELL_PX,
+            4 * gc_tui::screenshot::PNG_CELL_PX
+        )
+    );
+    // IEND chunk with its fixed CRC closes the file
+    assert_eq!(
+        &png[png.len() - 8..],
+        &[b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+    );
+}
+
+#[test]
+fn screenshot_of_saved_world_matches_source_terrain() {
+    let mut world = build_world(20, 10, 42);
+    let save = save_world(&mut world);
+    let mut loaded = world_from_save(save);
+    let text = render_text(&mut loaded, RenderLayers::default());
+    let map = world.resource::<GameMap>();
+    let first_row: String = (0..20)
+        .map(|x| match map.get_tile(x, 0).unwrap() {
+            TileKind::Floor => '.',
+            TileKind::Wall => '#',
+            TileKind::Water => '~',
+            TileKind::Lava => '^',
+        })
+        .collect();
+    assert_eq!(text.lines().next().unwrap(), first_row);
+}
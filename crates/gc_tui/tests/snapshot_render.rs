@@ -15,6 +15,7 @@ fn build_test_world(width: u32, height: u32, seed: u64) -> World {
         WorldOptions {
             populate_demo_scene: true,
             tick_ms: 100,
+            ..Default::default()
         },
     );
     // TUI-specific resources used by renderer
@@ -23,9 +24,9 @@ fn build_test_world(width: u32, height: u32, seed: u64) -> World {
     // Note: TUI renderer falls back to map center if no PlayerAgent resource
     // exists, so we don't insert the private PlayerAgent here.
 
-    // Compute initial FOV once for deterministic visibility state
+    // Compute initial FOV once for deterministic visibility state; the
+    // default schedule samples sight after movement and map confinement
     let mut schedule = build_default_schedule();
-    schedule.add_systems(fov::compute_visibility_system);
     schedule.run(&mut world);
 
     world
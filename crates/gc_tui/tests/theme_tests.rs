@@ -0,0 +1,121 @@
+use gc_tui::build_world;
+use gc_tui::config::TuiConfig;
+use gc_tui::theme::{CellKind, Palette, Theme};
+use gc_tui::{
+    apply_action, build_schedule, keymap::Action, render_styled_map, AppState, RenderLayers,
+};
+use ratatui::style::{Color, Modifier};
+
+const ALL_KINDS: [CellKind; 11] = [
+    CellKind::Floor,
+    CellKind::Wall,
+    CellKind::Water,
+    CellKind::Lava,
+    CellKind::Agent,
+    CellKind::Visible,
+    CellKind::Designation,
+    CellKind::Stockpile,
+    CellKind::Zone,
+    CellKind::Selection,
+    CellKind::Cursor,
+];
+
+#[test]
+fn palette_cycles_through_all_and_wraps() {
+    let mut p = Palette::Default;
+    for _ in 0..Palette::ALL.len() {
+        p = p.next();
+    }
+    assert_eq!(p, Palette::Default);
+    assert_eq!(Palette::Default.next(), Palette::Deuteranopia);
+}
+
+#[test]
+fn high_contrast_uses_no_colors() {
+    let theme = Theme::new(Palette::HighContrast);
+    for kind in ALL_KINDS {
+        let style = theme.style(kind);
+        assert_eq!(style.fg, None, "{:?} should not set a color", kind);
+        assert_eq!(style.bg, None, "{:?} should not set a color", kind);
+    }
+    assert!(theme
+        .style(CellKind::Cursor)
+        .add_modifier
+        .contains(Modifier::REVERSED));
+}
+
+#[test]
+fn deuteranopia_avoids_pure_red_and_green() {
+    let theme = Theme::new(Palette::Deuteranopia);
+    for kind in ALL_KINDS {
+        let style = theme.style(kind);
+        for color in [style.fg, style.bg].into_iter().flatten() {
+            assert!(
+                !matches!(
+                    color,
+                    Color::Red | Color::Green | Color::LightRed | Color::LightGreen
+                ),
+                "{:?} uses {:?}",
+                kind,
+                color
+            );
+        }
+    }
+}
+
+#[test]
+fn config_selects_palette() {
+    let cfg = TuiConfig::from_json(r#"{ "palette": "high_contrast" }"#).unwrap();
+    assert_eq!(cfg.palette, Palette::HighContrast);
+    assert_eq!(
+        TuiConfig::from_json("{}").unwrap().palette,
+        Palette::Default
+    );
+    assert!(TuiConfig::from_json(r#"{ "palette": "sepia" }"#).is_err());
+}
+
+#[test]
+fn config_overrides_raid_tuning_field_by_field() {
+    let cfg = TuiConfig::from_json(r#"{ "director": { "grace_ticks": 50 } }"#).unwrap();
+    let defaults = gc_core::director::DirectorConfig::default();
+    assert_eq!(cfg.director.grace_ticks, 50);
+    assert_eq!(cfg.director.raid_size, defaults.raid_size);
+    assert_eq!(TuiConfig::from_json("{}").unwrap().director, defaults);
+}
+
+#[test]
+fn styled_map_uses_theme_and_marks_cursor() {
+    let mut world = build_world(20, 10, 42);
+    let text = render_styled_map(&mut world, RenderLayers::default(), (0, 0));
+    assert_eq!(text.lines.len(), 10);
+    let first = &text.lines[0].spans[0];
+    assert_eq!(first.content.as_ref(), "X");
+    assert_eq!(first.style, Theme::default().style(CellKind::Cursor));
+
+    // Cycling the palette at runtime changes the rendered cursor style
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    apply_action(&mut world, &mut schedule, &mut app, Action::CyclePalette);
+    let text = render_styled_map(&mut world, RenderLayers::default(), (0, 0));
+    assert_eq!(
+        text.lines[0].spans[0].style,
+        Theme::new(Palette::Deuteranopia).style(CellKind::Cursor)
+    );
+}
+
+#[test]
+fn alert_bar_shows_latest_announcement_with_severity_style() {
+    use gc_core::prelude::*;
+    use gc_tui::alert_line;
+    use gc_tui::theme::Theme;
+
+    let mut world = gc_tui::build_world(20, 10, 42);
+    announce(&mut world, Severity::Info, "Stone mined");
+    announce(&mut world, Severity::Urgent, "Invaders have arrived!");
+    let view = snapshot_world(&mut world);
+    let theme = Theme::default();
+    let line = alert_line(&view, theme);
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    assert_eq!(text, "[URGENT t=0] Invaders have arrived!");
+    assert_eq!(line.spans[0].style, theme.severity_style(Severity::Urgent));
+}
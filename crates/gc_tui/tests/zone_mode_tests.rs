@@ -0,0 +1,315 @@
+use bevy_ecs::prelude::*;
+use gc_core::prelude::*;
+use gc_tui::config::TuiConfig;
+use gc_tui::keymap::{Action, Keymap};
+use gc_tui::mode::{hint_line, TuiMode};
+use gc_tui::zones::{ZonePopup, ZoneTarget};
+use gc_tui::{
+    active_preset, apply_action, build_schedule, build_world, render_styled_map, AppState,
+    RenderLayers,
+};
+
+fn press(world: &mut World, schedule: &mut Schedule, app: &mut AppState, actions: &[Action]) {
+    for &action in actions {
+        apply_action(world, schedule, app, action);
+    }
+}
+
+/// Kind, bounds and tag filter of a zone
+type ZoneRow = (ZoneKind, (i32, i32, i32, i32), Option<String>);
+
+fn zones(world: &mut World) -> Vec<ZoneRow> {
+    let mut q = world.query::<(&ZoneKind, &ZoneBounds, Option<&AcceptsTags>)>();
+    let mut zones: Vec<_> = q
+        .iter(world)
+        .map(|(k, b, a)| {
+            (
+                *k,
+                (b.min_x, b.min_y, b.max_x, b.max_y),
+                a.map(|a| a.0.to_string()),
+            )
+        })
+        .collect();
+    zones.sort();
+    zones
+}
+
+fn glyph_at(world: &mut World, x: usize, y: usize) -> char {
+    let layers = RenderLayers {
+        stockpiles: true,
+        ..Default::default()
+    };
+    let text = render_styled_map(world, layers, (-1, -1));
+    let row: String = text.lines[y]
+        .spans
+        .iter()
+        .map(|s| s.content.as_ref())
+        .collect();
+    row.chars().nth(x).unwrap()
+}
+
+#[test]
+fn a_dragged_rectangle_becomes_a_filtered_stockpile() {
+    let mut world = build_world(30, 20, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    let before = zones(&mut world);
+
+    press(&mut world, &mut schedule, &mut app, &[Action::Zone]);
+    assert_eq!(app.mode, TuiMode::Zone);
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[
+            Action::Designate,
+            Action::CursorRight,
+            Action::CursorRight,
+            Action::CursorDown,
+        ],
+    );
+    assert_eq!(app.anchor, Some((0, 0)));
+    press(&mut world, &mut schedule, &mut app, &[Action::Designate]);
+    assert_eq!(app.anchor, None);
+    let popup = app
+        .zone_popup
+        .as_ref()
+        .expect("second corner opens the popup");
+    assert_eq!(
+        popup.target,
+        ZoneTarget::New {
+            a: (0, 0),
+            b: (2, 1)
+        }
+    );
+    assert_eq!(
+        zones(&mut world),
+        before,
+        "nothing is created before confirming"
+    );
+
+    // Rows: five kinds, then tags in order ("armor" first), then confirm
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::CursorDown; 5],
+    );
+    press(&mut world, &mut schedule, &mut app, &[Action::Designate]);
+    press(&mut world, &mut schedule, &mut app, &[Action::CursorUp; 6]);
+    press(&mut world, &mut schedule, &mut app, &[Action::Designate]);
+    assert!(app.zone_popup.is_none());
+    assert_eq!(app.mode, TuiMode::Zone);
+    assert!(zones(&mut world).contains(&(
+        ZoneKind::Stockpile,
+        (0, 0, 2, 1),
+        Some("armor".to_string())
+    )));
+    assert_eq!(glyph_at(&mut world, 2, 1), '=');
+}
+
+#[test]
+fn zones_under_the_cursor_can_be_edited_and_removed() {
+    let mut world = build_world(30, 20, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    apply_world_command(
+        &mut world,
+        &WorldCommand::Zone {
+            kind: ZoneKind::Stockpile,
+            a: (1, 1),
+            b: (3, 2),
+            accepts: Some(TagQuery::parse("gem | ore").unwrap()),
+        },
+    );
+    let before = zones(&mut world);
+    press(&mut world, &mut schedule, &mut app, &[Action::Zone]);
+
+    // Nothing to edit at the origin
+    press(&mut world, &mut schedule, &mut app, &[Action::Zone]);
+    assert!(app.zone_popup.is_none());
+    assert_eq!(app.message.as_deref(), Some("no zone here"));
+
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::CursorRight, Action::CursorDown, Action::Zone],
+    );
+    let popup = app.zone_popup.as_ref().expect("edit opens the popup");
+    assert_eq!(popup.target, ZoneTarget::Existing { x: 1, y: 1 });
+    assert_eq!(popup.kind, ZoneKind::Stockpile);
+    assert_eq!(
+        popup.tags.iter().map(String::as_str).collect::<Vec<_>>(),
+        vec!["gem", "ore"]
+    );
+
+    // Switch to a meeting zone: kinds drop the tag rows, so confirm is row 5
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[
+            Action::CursorDown,
+            Action::CursorDown,
+            Action::Designate,
+            Action::CursorUp,
+            Action::CursorUp,
+            Action::CursorUp,
+            Action::Designate,
+        ],
+    );
+    let after = zones(&mut world);
+    assert!(after.contains(&(ZoneKind::Meeting, (1, 1, 3, 2), None)));
+    assert_eq!(after.len(), before.len());
+    assert_eq!(glyph_at(&mut world, 3, 2), '+');
+
+    press(&mut world, &mut schedule, &mut app, &[Action::RemoveZone]);
+    assert_eq!(zones(&mut world).len(), before.len() - 1);
+}
+
+#[test]
+fn cancel_closes_the_popup_before_leaving_the_mode() {
+    let mut world = build_world(30, 20, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    let before = zones(&mut world);
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::Zone, Action::Designate, Action::Designate],
+    );
+    assert!(app.zone_popup.is_some());
+    // The popup swallows everything but its own keys
+    press(&mut world, &mut schedule, &mut app, &[Action::Menu]);
+    assert_eq!(app.mode, TuiMode::Zone);
+    press(&mut world, &mut schedule, &mut app, &[Action::Cancel]);
+    assert!(app.zone_popup.is_none());
+    assert_eq!(app.mode, TuiMode::Zone);
+    press(&mut world, &mut schedule, &mut app, &[Action::Cancel]);
+    assert_eq!(app.mode, TuiMode::Cursor);
+    assert_eq!(zones(&mut world), before);
+
+    // Removing is only possible in zone mode
+    press(&mut world, &mut schedule, &mut app, &[Action::RemoveZone]);
+    assert_eq!(zones(&mut world), before);
+}
+
+#[test]
+fn zone_mode_is_closed_while_recording_a_macro() {
+    let mut world = build_world(30, 20, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState::default();
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::RecordMacro, Action::Zone],
+    );
+    assert_eq!(app.mode, TuiMode::Designate);
+    assert!(app.message.is_some());
+}
+
+#[test]
+fn richer_filters_are_kept_unless_tags_are_toggled() {
+    let query = TagQuery::parse("gear !armor").unwrap();
+    let mut popup = ZonePopup::edit(4, 4, ZoneKind::Stockpile, Some(&query));
+    assert!(popup.tags.is_empty());
+    assert_eq!(popup.accepts(), Some(query.clone()));
+    assert!(popup.lines().last().unwrap().contains("gear !armor"));
+    assert_eq!(
+        popup.command(),
+        WorldCommand::EditZone {
+            x: 4,
+            y: 4,
+            kind: ZoneKind::Stockpile,
+            accepts: Some(query),
+        }
+    );
+
+    popup.row = ZoneKind::ALL.len();
+    assert_eq!(popup.select(), None);
+    assert_eq!(popup.accepts(), Some(TagQuery::parse("armor").unwrap()));
+
+    // No ticked tags accepts everything
+    assert_eq!(popup.select(), None);
+    assert_eq!(popup.accepts(), None);
+}
+
+#[test]
+fn hint_line_advertises_zone_keys() {
+    let keymap = Keymap::default();
+    assert!(hint_line(TuiMode::Cursor, &keymap).contains("z: zones"));
+    let zone = hint_line(TuiMode::Zone, &keymap);
+    assert!(zone.starts_with("[zone]"));
+    assert!(zone.contains("d/enter: corner"));
+    assert!(zone.contains("z: edit"));
+    assert!(zone.contains("x: remove"));
+}
+
+#[test]
+fn presets_from_the_config_apply_without_the_popup() {
+    let config = TuiConfig::from_json(
+        r#"{ "presets": {
+            "metals": { "type": "zone", "kind": "stockpile", "accepts": "metal | ore" },
+            "rush": { "type": "designation", "priority": 4 }
+        } }"#,
+    )
+    .unwrap();
+    let err = TuiConfig::from_json(
+        r#"{ "presets": { "x": { "type": "zone", "kind": "stockpile", "accepts": "mithril" } } }"#,
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "preset 'x': unknown tag 'mithril'");
+
+    let mut world = build_world(30, 20, 42);
+    let mut schedule = build_schedule();
+    let mut app = AppState {
+        presets: config.presets,
+        ..Default::default()
+    };
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::Zone, Action::CyclePreset],
+    );
+    assert_eq!(app.preset.as_deref(), Some("metals"));
+    assert_eq!(
+        app.message.as_deref(),
+        Some("preset metals: stockpile accepting metal | ore")
+    );
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::Designate, Action::CursorRight, Action::Designate],
+    );
+    assert!(app.zone_popup.is_none());
+    assert!(zones(&mut world).contains(&(
+        ZoneKind::Stockpile,
+        (0, 0, 1, 0),
+        Some("metal | ore".to_string())
+    )));
+
+    // Only one zone preset: the next press turns presets off
+    press(&mut world, &mut schedule, &mut app, &[Action::CyclePreset]);
+    assert_eq!(
+        (app.preset.as_deref(), app.message.as_deref()),
+        (None, Some("no preset"))
+    );
+
+    // The designation preset only applies in designation mode
+    press(
+        &mut world,
+        &mut schedule,
+        &mut app,
+        &[Action::Cancel, Action::Designate, Action::CyclePreset],
+    );
+    assert_eq!(app.mode, TuiMode::Designate);
+    assert_eq!(app.preset.as_deref(), Some("rush"));
+    assert!(active_preset(&app).is_some());
+    app.mode = TuiMode::Zone;
+    assert!(active_preset(&app).is_none());
+}